@@ -1,20 +1,73 @@
 //! 数据库备份和恢复
 //!
-//! 提供 SQL 导出/导入和二进制快照备份功能。
+//! 提供 SQL 导出/导入、二进制快照备份和定时压缩备份功能。
 
-use super::{lock_conn, Database, DB_BACKUP_RETAIN};
-use crate::config::get_app_config_dir;
+use super::{database_file_state, lock_conn, Database, DatabaseFileState, DB_BACKUP_RETAIN};
 use crate::error::AppError;
 use chrono::Utc;
 use rusqlite::backup::Backup;
 use rusqlite::types::ValueRef;
 use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 use tempfile::NamedTempFile;
 
 const CC_SWITCH_SQL_EXPORT_HEADER: &str = "-- CC Switch SQLite 导出";
 
+/// 定时备份文件名前缀，与导入前的快照备份分开轮转
+const AUTO_BACKUP_PREFIX: &str = "auto_backup_";
+
+const AUTO_BACKUP_ENABLED_KEY: &str = "auto_backup_enabled";
+const AUTO_BACKUP_INTERVAL_KEY: &str = "auto_backup_interval_hours";
+const AUTO_BACKUP_RETAIN_KEY: &str = "auto_backup_retain";
+
+/// 定时备份设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoBackupConfig {
+    pub enabled: bool,
+    /// 备份间隔（小时）
+    pub interval_hours: u64,
+    /// 保留的定时备份数量，超出时删除最旧的
+    pub retain: usize,
+}
+
+impl Default for AutoBackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            retain: 7,
+        }
+    }
+}
+
+/// backups 目录中的一个备份文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// 修改时间（秒级时间戳）
+    pub modified_at: i64,
+    /// 是否为定时备份（否则为导入前的快照备份）
+    pub automatic: bool,
+}
+
+/// 备份内容摘要，恢复前展示给用户确认
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub providers: usize,
+    pub mcp_servers: usize,
+    pub prompts: usize,
+    /// 备份时的 Schema 版本（user_version）
+    pub schema_version: i32,
+}
+
 impl Database {
     /// 导出为 SQLite 兼容的 SQL 文本
     pub fn export_sql(&self, target_path: &Path) -> Result<(), AppError> {
@@ -50,8 +103,7 @@ impl Database {
             source: e,
         })?;
         let temp_path = temp_file.path().to_path_buf();
-        let temp_conn =
-            Connection::open(&temp_path).map_err(|e| AppError::Database(e.to_string()))?;
+        let temp_conn = self.open_companion(&temp_path)?;
 
         temp_conn
             .execute_batch(sql_content)
@@ -62,21 +114,108 @@ impl Database {
         Self::apply_schema_migrations_on_conn(&temp_conn)?;
         Self::validate_basic_state(&temp_conn)?;
 
-        // 使用 Backup 将临时库原子写回主库
+        self.replace_with(&temp_conn)?;
+
+        let backup_id = backup_path
+            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default();
+
+        Ok(backup_id)
+    }
+
+    /// 读取备份内容摘要，不修改备份文件
+    pub fn inspect_backup(&self, path: &Path) -> Result<BackupSummary, AppError> {
+        let (_temp, conn) = self.open_backup_copy(path)?;
+        let count = |table: &str| -> Result<usize, AppError> {
+            if !Self::table_exists(&conn, table)? {
+                return Ok(0);
+            }
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as usize)
+            .map_err(|e| AppError::Database(e.to_string()))
+        };
+        Ok(BackupSummary {
+            providers: count("providers")?,
+            mcp_servers: count("mcp_servers")?,
+            prompts: count("prompts")?,
+            schema_version: conn
+                .pragma_query_value(None, "user_version", |row| row.get(0))
+                .map_err(|e| AppError::Database(e.to_string()))?,
+        })
+    }
+
+    /// 用备份替换当前数据库，旧备份会先升级到当前 Schema；
+    /// 恢复前为当前数据库生成快照，返回该快照的备份 ID（若无备份则为空字符串）
+    pub fn restore_backup(&self, path: &Path) -> Result<String, AppError> {
+        let (_temp, temp_conn) = self.open_backup_copy(path)?;
+        Self::create_tables_on_conn(&temp_conn)?;
+        Self::apply_schema_migrations_on_conn(&temp_conn)?;
+        Self::validate_basic_state(&temp_conn)?;
+
+        let backup_path = self.backup_database_file()?;
+        self.replace_with(&temp_conn)?;
+
+        Ok(backup_path
+            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default())
+    }
+
+    /// 把备份复制（定时备份则解压）到临时文件后打开，避免迁移时改写备份本身
+    fn open_backup_copy(&self, path: &Path) -> Result<(NamedTempFile, Connection), AppError> {
+        let temp_file = NamedTempFile::new().map_err(|e| AppError::IoContext {
+            context: "创建临时数据库文件失败".to_string(),
+            source: e,
+        })?;
+        let mut source = fs::File::open(path).map_err(|e| AppError::io(path, e))?;
+        if is_auto_backup(path) {
+            let mut archive = zip::ZipArchive::new(source)
+                .map_err(|e| AppError::Message(format!("无法读取压缩备份: {e}")))?;
+            let mut entry = archive
+                .by_index(0)
+                .map_err(|e| AppError::Message(format!("压缩备份为空: {e}")))?;
+            std::io::copy(&mut entry, &mut temp_file.as_file())
+                .map_err(|e| AppError::io(temp_file.path(), e))?;
+        } else {
+            std::io::copy(&mut source, &mut temp_file.as_file())
+                .map_err(|e| AppError::io(temp_file.path(), e))?;
+        }
+
+        match (database_file_state(temp_file.path())?, &self.passphrase) {
+            // 启用加密前的备份仍为明文，先按当前口令加密副本再打开
+            (DatabaseFileState::Plaintext, Some(passphrase)) => {
+                Self::encrypt_file(temp_file.path(), passphrase)?;
+            }
+            (DatabaseFileState::Encrypted, None) => {
+                return Err(AppError::Message(
+                    "备份已加密，当前数据库未加密，无法读取该备份".to_string(),
+                ));
+            }
+            (DatabaseFileState::Missing, _) => {
+                return Err(AppError::Message(format!(
+                    "备份文件为空: {}",
+                    path.display()
+                )));
+            }
+            _ => {}
+        }
+        let conn = self.open_companion(temp_file.path())?;
+        Ok((temp_file, conn))
+    }
+
+    /// 使用 Backup 将临时库原子写回主库
+    fn replace_with(&self, source: &Connection) -> Result<(), AppError> {
         {
             let mut main_conn = lock_conn!(self.conn);
-            let backup = Backup::new(&temp_conn, &mut main_conn)
+            let backup = Backup::new(source, &mut main_conn)
                 .map_err(|e| AppError::Database(e.to_string()))?;
             backup
                 .step(-1)
                 .map_err(|e| AppError::Database(e.to_string()))?;
         }
-
-        let backup_id = backup_path
-            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
-            .unwrap_or_default();
-
-        Ok(backup_id)
+        self.bump_config_version();
+        Ok(())
     }
 
     /// 创建内存快照以避免长时间持有数据库锁
@@ -85,6 +224,25 @@ impl Database {
         let mut snapshot =
             Connection::open_in_memory().map_err(|e| AppError::Database(e.to_string()))?;
 
+        // SQLCipher 不支持从加密库 Backup 到明文内存库，改为附加主库文件后导出
+        if let Some(passphrase) = &self.passphrase {
+            let db_path = conn
+                .path()
+                .map(str::to_string)
+                .ok_or_else(|| AppError::Database("无法获取数据库文件路径".to_string()))?;
+            snapshot
+                .execute(
+                    "ATTACH DATABASE ?1 AS source KEY ?2",
+                    rusqlite::params![db_path, passphrase],
+                )
+                .and_then(|_| {
+                    snapshot.query_row("SELECT sqlcipher_export('main', 'source')", [], |_| Ok(()))
+                })
+                .and_then(|_| snapshot.execute("DETACH DATABASE source", []))
+                .map_err(|e| AppError::Database(format!("创建数据库快照失败: {e}")))?;
+            return Ok(snapshot);
+        }
+
         {
             let backup =
                 Backup::new(&conn, &mut snapshot).map_err(|e| AppError::Database(e.to_string()))?;
@@ -109,17 +267,135 @@ impl Database {
         ))
     }
 
+    /// 备份目录：`~/.cc-switch/backups`
+    pub fn backups_dir() -> PathBuf {
+        super::get_app_config_dir().join("backups")
+    }
+
+    /// 读取定时备份设置
+    pub fn get_auto_backup_config(&self) -> Result<AutoBackupConfig, AppError> {
+        let default = AutoBackupConfig::default();
+        Ok(AutoBackupConfig {
+            enabled: self
+                .get_setting(AUTO_BACKUP_ENABLED_KEY)?
+                .map(|v| v == "true")
+                .unwrap_or(default.enabled),
+            interval_hours: self
+                .get_setting(AUTO_BACKUP_INTERVAL_KEY)?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.interval_hours),
+            retain: self
+                .get_setting(AUTO_BACKUP_RETAIN_KEY)?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.retain),
+        })
+    }
+
+    /// 保存定时备份设置，后台任务下一次调度时生效
+    pub fn set_auto_backup_config(&self, config: AutoBackupConfig) -> Result<(), AppError> {
+        self.set_setting(AUTO_BACKUP_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(AUTO_BACKUP_INTERVAL_KEY, &config.interval_hours.to_string())?;
+        self.set_setting(AUTO_BACKUP_RETAIN_KEY, &config.retain.to_string())
+    }
+
+    /// 列出 backups 目录中的备份，最新的在前
+    pub fn list_backups() -> Result<Vec<BackupFile>, AppError> {
+        Self::list_backups_in(&Self::backups_dir())
+    }
+
+    pub(crate) fn list_backups_in(dir: &Path) -> Result<Vec<BackupFile>, AppError> {
+        let entries = match fs::read_dir(dir) {
+            Ok(iter) => iter,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::io(dir, e)),
+        };
+        let mut backups: Vec<BackupFile> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_name()?.to_string_lossy().to_string();
+                let automatic = is_auto_backup(&path);
+                if !automatic && path.extension().is_none_or(|ext| ext != "db") {
+                    return None;
+                }
+                let metadata = entry.metadata().ok()?;
+                let modified_at = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0);
+                Some(BackupFile {
+                    name,
+                    path,
+                    size_bytes: metadata.len(),
+                    modified_at,
+                    automatic,
+                })
+            })
+            .collect();
+        backups.sort_by(|a, b| b.modified_at.cmp(&a.modified_at).then(b.name.cmp(&a.name)));
+        Ok(backups)
+    }
+
+    /// 生成一份压缩的定时备份（zip 内为完整数据库文件），并只保留最新的 `retain` 份
+    pub fn create_auto_backup(&self, retain: usize) -> Result<PathBuf, AppError> {
+        self.create_auto_backup_in(&Self::backups_dir(), retain)
+    }
+
+    pub(crate) fn create_auto_backup_in(
+        &self,
+        dir: &Path,
+        retain: usize,
+    ) -> Result<PathBuf, AppError> {
+        fs::create_dir_all(dir).map_err(|e| AppError::io(dir, e))?;
+
+        // 先在同目录生成一致性快照，加密数据库的快照仍为加密状态
+        let snapshot = NamedTempFile::new_in(dir).map_err(|e| AppError::io(dir, e))?;
+        {
+            let conn = lock_conn!(self.conn);
+            let mut dest_conn = self.open_companion(snapshot.path())?;
+            let backup = Backup::new(&conn, &mut dest_conn)
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            backup
+                .step(-1)
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        let base_id = format!("{AUTO_BACKUP_PREFIX}{}", Utc::now().format("%Y%m%d_%H%M%S"));
+        let mut backup_path = dir.join(format!("{base_id}.db.zip"));
+        let mut counter = 1;
+        while backup_path.exists() {
+            backup_path = dir.join(format!("{base_id}_{counter}.db.zip"));
+            counter += 1;
+        }
+
+        let compress = || -> Result<(), Box<dyn std::error::Error>> {
+            let mut zip = zip::ZipWriter::new(fs::File::create(&backup_path)?);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+            zip.start_file("cc-switch.db", options)?;
+            std::io::copy(&mut fs::File::open(snapshot.path())?, &mut zip)?;
+            zip.finish()?;
+            Ok(())
+        };
+        if let Err(e) = compress() {
+            let _ = fs::remove_file(&backup_path);
+            return Err(AppError::Message(format!("压缩数据库备份失败: {e}")));
+        }
+
+        Self::prune_backups(dir, retain.max(1), is_auto_backup)?;
+        Ok(backup_path)
+    }
+
     /// 生成一致性快照备份，返回备份文件路径（不存在主库时返回 None）
     fn backup_database_file(&self) -> Result<Option<PathBuf>, AppError> {
-        let db_path = get_app_config_dir().join("cc-switch.db");
+        let db_path = Self::file_path();
         if !db_path.exists() {
             return Ok(None);
         }
 
-        let backup_dir = db_path
-            .parent()
-            .ok_or_else(|| AppError::Config("无效的数据库路径".to_string()))?
-            .join("backups");
+        let backup_dir = Self::backups_dir();
 
         fs::create_dir_all(&backup_dir).map_err(|e| AppError::io(&backup_dir, e))?;
 
@@ -135,8 +411,7 @@ impl Database {
 
         {
             let conn = lock_conn!(self.conn);
-            let mut dest_conn =
-                Connection::open(&backup_path).map_err(|e| AppError::Database(e.to_string()))?;
+            let mut dest_conn = self.open_companion(&backup_path)?;
             let backup = Backup::new(&conn, &mut dest_conn)
                 .map_err(|e| AppError::Database(e.to_string()))?;
             backup
@@ -144,33 +419,38 @@ impl Database {
                 .map_err(|e| AppError::Database(e.to_string()))?;
         }
 
-        Self::cleanup_db_backups(&backup_dir)?;
+        Self::prune_backups(&backup_dir, DB_BACKUP_RETAIN, |path| {
+            path.extension().map(|ext| ext == "db").unwrap_or(false)
+        })?;
         Ok(Some(backup_path))
     }
 
-    /// 清理旧的数据库备份，保留最新的 N 个
-    fn cleanup_db_backups(dir: &Path) -> Result<(), AppError> {
+    /// 清理旧的数据库备份，`matches` 选中的文件只保留最新的 `retain` 个
+    fn prune_backups(
+        dir: &Path,
+        retain: usize,
+        matches: impl Fn(&Path) -> bool,
+    ) -> Result<(), AppError> {
         let entries = match fs::read_dir(dir) {
             Ok(iter) => iter
                 .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry
-                        .path()
-                        .extension()
-                        .map(|ext| ext == "db")
-                        .unwrap_or(false)
-                })
+                .filter(|entry| matches(&entry.path()))
                 .collect::<Vec<_>>(),
             Err(_) => return Ok(()),
         };
 
-        if entries.len() <= DB_BACKUP_RETAIN {
+        if entries.len() <= retain {
             return Ok(());
         }
 
-        let remove_count = entries.len().saturating_sub(DB_BACKUP_RETAIN);
+        let remove_count = entries.len().saturating_sub(retain);
         let mut sorted = entries;
-        sorted.sort_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
+        sorted.sort_by_key(|entry| {
+            (
+                entry.metadata().and_then(|m| m.modified()).ok(),
+                entry.file_name(),
+            )
+        });
 
         for entry in sorted.into_iter().take(remove_count) {
             if let Err(err) = fs::remove_file(entry.path()) {
@@ -323,3 +603,10 @@ impl Database {
         }
     }
 }
+
+/// 是否为定时备份生成的压缩文件
+fn is_auto_backup(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with(AUTO_BACKUP_PREFIX) && name.ends_with(".zip"))
+}
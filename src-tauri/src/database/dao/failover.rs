@@ -7,6 +7,28 @@ use crate::error::AppError;
 use crate::provider::Provider;
 use serde::{Deserialize, Serialize};
 
+/// settings 表中保存故障转移切换时是否弹出桌面通知的键
+const FAILOVER_DESKTOP_NOTIFICATIONS_KEY: &str = "failover_desktop_notifications";
+
+/// failover_events 表最多保留的记录条数
+const FAILOVER_EVENT_RETENTION: i64 = 500;
+
+/// 一次自动故障转移切换的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverEvent {
+    /// 切换时间（Unix 秒）
+    pub created_at: i64,
+    pub app_type: String,
+    /// 切换前的当前供应商，未设置时为 None
+    pub from_provider_id: Option<String>,
+    pub from_provider_name: Option<String>,
+    pub to_provider_id: String,
+    pub to_provider_name: String,
+    /// 导致切换的上游错误或切回说明；前一个供应商被熔断器跳过时为 None
+    pub error: Option<String>,
+}
+
 /// 故障转移队列条目（简化版，用于前端展示）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,16 +36,88 @@ pub struct FailoverQueueItem {
     pub provider_id: String,
     pub provider_name: String,
     pub sort_index: Option<usize>,
+    /// 负载均衡权重，未设置时为 None
+    #[serde(default)]
+    pub weight: Option<u32>,
 }
 
 impl Database {
+    /// 记录一次故障转移切换，并只保留最近的若干条
+    pub fn record_failover_event(&self, event: &FailoverEvent) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO failover_events (created_at, app_type, from_provider_id, from_provider_name,
+                to_provider_id, to_provider_name, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                event.created_at,
+                event.app_type,
+                event.from_provider_id,
+                event.from_provider_name,
+                event.to_provider_id,
+                event.to_provider_name,
+                event.error
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM failover_events WHERE id <= (SELECT MAX(id) FROM failover_events) - ?1",
+            [FAILOVER_EVENT_RETENTION],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取最近的 `limit` 条故障转移记录，按时间倒序
+    pub fn get_failover_events(&self, limit: usize) -> Result<Vec<FailoverEvent>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT created_at, app_type, from_provider_id, from_provider_name,
+                        to_provider_id, to_provider_name, error
+                 FROM failover_events
+                 ORDER BY id DESC
+                 LIMIT ?1",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let events = stmt
+            .query_map([limit as i64], |row| {
+                Ok(FailoverEvent {
+                    created_at: row.get(0)?,
+                    app_type: row.get(1)?,
+                    from_provider_id: row.get(2)?,
+                    from_provider_name: row.get(3)?,
+                    to_provider_id: row.get(4)?,
+                    to_provider_name: row.get(5)?,
+                    error: row.get(6)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(events)
+    }
+
+    /// 故障转移切换供应商时是否弹出桌面通知，默认开启
+    pub fn get_failover_desktop_notifications(&self) -> Result<bool, AppError> {
+        Ok(self
+            .get_setting(FAILOVER_DESKTOP_NOTIFICATIONS_KEY)?
+            .is_none_or(|v| v != "false"))
+    }
+
+    pub fn set_failover_desktop_notifications(&self, enabled: bool) -> Result<(), AppError> {
+        self.set_setting(FAILOVER_DESKTOP_NOTIFICATIONS_KEY, &enabled.to_string())
+    }
+
     /// 获取故障转移队列（按 sort_index 排序）
     pub fn get_failover_queue(&self, app_type: &str) -> Result<Vec<FailoverQueueItem>, AppError> {
         let conn = lock_conn!(self.conn);
 
         let mut stmt = conn
             .prepare(
-                "SELECT id, name, sort_index
+                "SELECT id, name, sort_index, weight
                  FROM providers
                  WHERE app_type = ?1 AND in_failover_queue = 1
                  ORDER BY COALESCE(sort_index, 999999), id ASC",
@@ -36,6 +130,7 @@ impl Database {
                     provider_id: row.get(0)?,
                     provider_name: row.get(1)?,
                     sort_index: row.get(2)?,
+                    weight: row.get(3)?,
                 })
             })
             .map_err(|e| AppError::Database(e.to_string()))?
@@ -67,6 +162,7 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        self.bump_config_version();
         Ok(())
     }
 
@@ -94,6 +190,26 @@ impl Database {
 
         log::info!("已从故障转移队列移除供应商 {provider_id} ({app_type}), 并清除其健康状态");
 
+        self.bump_config_version();
+        Ok(())
+    }
+
+    /// 设置队列供应商的负载均衡权重，None 表示清除
+    pub fn set_provider_weight(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        weight: Option<u32>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE providers SET weight = ?1 WHERE id = ?2 AND app_type = ?3",
+            rusqlite::params![weight, provider_id, app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        self.bump_config_version();
         Ok(())
     }
 
@@ -107,6 +223,7 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        self.bump_config_version();
         Ok(())
     }
 
@@ -7,6 +7,7 @@ pub mod mcp;
 pub mod prompts;
 pub mod providers;
 pub mod proxy;
+pub mod request_log;
 pub mod settings;
 pub mod skills;
 pub mod stream_check;
@@ -14,4 +15,5 @@ pub mod universal_providers;
 
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
-pub use failover::FailoverQueueItem;
+pub use failover::{FailoverEvent, FailoverQueueItem};
+pub use request_log::DEFAULT_REQUEST_HISTORY_LIMIT;
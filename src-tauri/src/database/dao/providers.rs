@@ -9,6 +9,13 @@ use indexmap::IndexMap;
 use rusqlite::{params, OptionalExtension};
 use std::collections::HashMap;
 
+/// 端点延迟历史保留时长（秒）
+const ENDPOINT_LATENCY_RETENTION_SECS: i64 = 24 * 60 * 60;
+/// 计算延迟分位数时使用的最近成功采样数
+const ENDPOINT_PERCENTILE_SAMPLES: usize = 20;
+/// 计算稳定性评分时使用的最近采样数（含失败采样）
+const ENDPOINT_STABILITY_SAMPLES: usize = 20;
+
 impl Database {
     /// 获取指定应用类型的所有供应商
     pub fn get_all_providers(
@@ -17,7 +24,7 @@ impl Database {
     ) -> Result<IndexMap<String, Provider>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn.prepare(
-            "SELECT id, name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
+            "SELECT id, name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue, last_used_at, weight
              FROM providers WHERE app_type = ?1
              ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC"
         ).map_err(|e| AppError::Database(e.to_string()))?;
@@ -36,6 +43,8 @@ impl Database {
                 let icon_color: Option<String> = row.get(9)?;
                 let meta_str: String = row.get(10)?;
                 let in_failover_queue: bool = row.get(11)?;
+                let last_used_at: Option<i64> = row.get(12)?;
+                let weight: Option<u32> = row.get(13)?;
 
                 let settings_config =
                     serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
@@ -56,6 +65,8 @@ impl Database {
                         icon,
                         icon_color,
                         in_failover_queue,
+                        last_used_at,
+                        weight,
                     },
                 ))
             })
@@ -131,7 +142,7 @@ impl Database {
     ) -> Result<Option<Provider>, AppError> {
         let conn = lock_conn!(self.conn);
         let result = conn.query_row(
-            "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
+            "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue, last_used_at, weight
              FROM providers WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
             |row| {
@@ -146,6 +157,8 @@ impl Database {
                 let icon_color: Option<String> = row.get(8)?;
                 let meta_str: String = row.get(9)?;
                 let in_failover_queue: bool = row.get(10)?;
+                let last_used_at: Option<i64> = row.get(11)?;
+                let weight: Option<u32> = row.get(12)?;
 
                 let settings_config = serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
                 let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
@@ -163,6 +176,8 @@ impl Database {
                     icon,
                     icon_color,
                     in_failover_queue,
+                    last_used_at,
+                    weight,
                 })
             },
         );
@@ -241,8 +256,9 @@ impl Database {
             tx.execute(
                 "INSERT INTO providers (
                     id, app_type, name, settings_config, website_url, category,
-                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue,
+                    last_used_at, weight
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
                 params![
                     provider.id,
                     app_type,
@@ -258,6 +274,8 @@ impl Database {
                     serde_json::to_string(&meta_clone).unwrap(),
                     is_current,
                     in_failover_queue,
+                    provider.last_used_at,
+                    provider.weight,
                 ],
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -274,6 +292,7 @@ impl Database {
         }
 
         tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
         Ok(())
     }
 
@@ -285,6 +304,7 @@ impl Database {
             params![id, app_type],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
         Ok(())
     }
 
@@ -302,10 +322,10 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-        // 设置新的当前供应商
+        // 设置新的当前供应商，并记录最近使用时间
         tx.execute(
-            "UPDATE providers SET is_current = 1 WHERE id = ?1 AND app_type = ?2",
-            params![id, app_type],
+            "UPDATE providers SET is_current = 1, last_used_at = ?3 WHERE id = ?1 AND app_type = ?2",
+            params![id, app_type, chrono::Utc::now().timestamp()],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -330,6 +350,7 @@ impl Database {
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
         Ok(())
     }
 
@@ -346,6 +367,7 @@ impl Database {
             "INSERT INTO provider_endpoints (provider_id, app_type, url, added_at) VALUES (?1, ?2, ?3, ?4)",
             params![provider_id, app_type, url, added_at],
         ).map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
         Ok(())
     }
 
@@ -362,6 +384,12 @@ impl Database {
             params![provider_id, app_type, url],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM endpoint_latency_history WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
+            params![provider_id, app_type, url],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
         Ok(())
     }
 
@@ -375,10 +403,12 @@ impl Database {
         let mut stmt = conn
             .prepare(
                 "SELECT id, provider_id, app_type, url, latency_ms, last_tested_at,
-                        is_healthy, consecutive_failures, is_primary
+                        is_healthy, consecutive_failures, is_primary, is_pinned, weight,
+                        latency_p50_ms, latency_p95_ms, jitter_ms, recent_flaps, stability_score,
+                        region
                  FROM provider_endpoints
                  WHERE provider_id = ?1 AND app_type = ?2
-                 ORDER BY is_primary DESC, latency_ms ASC NULLS LAST",
+                 ORDER BY is_primary DESC, COALESCE(latency_p95_ms, latency_ms) ASC NULLS LAST",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -394,6 +424,14 @@ impl Database {
                     is_healthy: row.get::<_, i32>(6)? != 0,
                     consecutive_failures: row.get::<_, i32>(7)? as u32,
                     is_primary: row.get::<_, i32>(8)? != 0,
+                    is_pinned: row.get::<_, i32>(9)? != 0,
+                    weight: row.get(10)?,
+                    latency_p50_ms: row.get(11)?,
+                    latency_p95_ms: row.get(12)?,
+                    jitter_ms: row.get(13)?,
+                    recent_flaps: row.get::<_, i64>(14)?.max(0) as u32,
+                    stability_score: row.get(15)?,
+                    region: row.get(16)?,
                 })
             })
             .map_err(|e| AppError::Database(e.to_string()))?
@@ -403,7 +441,26 @@ impl Database {
         Ok(endpoints)
     }
 
-    /// 更新端点健康状态
+    /// 读取端点当前的（平滑后）延迟
+    fn endpoint_latency_on_conn(
+        conn: &rusqlite::Connection,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+    ) -> Result<Option<u64>, AppError> {
+        let latency: Option<Option<i64>> = conn
+            .query_row(
+                "SELECT latency_ms FROM provider_endpoints
+                 WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
+                params![provider_id, app_type, url],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(latency.flatten().map(|v| v.max(0) as u64))
+    }
+
+    /// 更新端点健康状态，新的延迟按 EWMA 并入已有值，测速失败时清空
     pub fn update_endpoint_health(
         &self,
         app_type: &str,
@@ -415,6 +472,13 @@ impl Database {
     ) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
         let now = chrono::Utc::now().timestamp();
+        let latency_ms = match latency_ms {
+            Some(sample) => Some(crate::proxy::types::ewma(
+                Self::endpoint_latency_on_conn(&conn, app_type, provider_id, url)?,
+                sample,
+            )),
+            None => None,
+        };
         conn.execute(
             "UPDATE provider_endpoints
              SET latency_ms = ?1, last_tested_at = ?2, is_healthy = ?3, consecutive_failures = ?4
@@ -433,6 +497,198 @@ impl Database {
         Ok(())
     }
 
+    /// 把一次成功的真实请求延迟按 EWMA 并入端点延迟，不改变健康状态
+    pub fn record_endpoint_request_latency(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        latency_ms: u64,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let previous = Self::endpoint_latency_on_conn(&conn, app_type, provider_id, url)?;
+        conn.execute(
+            "UPDATE provider_endpoints SET latency_ms = ?1
+             WHERE provider_id = ?2 AND app_type = ?3 AND url = ?4",
+            params![
+                crate::proxy::types::ewma(previous, latency_ms) as i64,
+                provider_id,
+                app_type,
+                url
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 记录一次端点延迟采样，并清理超出保留时长的旧采样
+    pub fn record_endpoint_latency(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        latency_ms: Option<u64>,
+        is_healthy: bool,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let now = chrono::Utc::now().timestamp();
+        conn.execute(
+            "INSERT INTO endpoint_latency_history (app_type, provider_id, url, tested_at, latency_ms, is_healthy)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                app_type,
+                provider_id,
+                url,
+                now,
+                latency_ms.map(|v| v as i64),
+                if is_healthy { 1 } else { 0 }
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM endpoint_latency_history WHERE tested_at < ?1",
+            params![now - ENDPOINT_LATENCY_RETENTION_SECS],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 按端点最近的成功采样重新计算 p50/p95 延迟并保存，返回 (p50, p95)
+    pub fn refresh_endpoint_percentiles(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+    ) -> Result<(Option<u64>, Option<u64>), AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT latency_ms FROM endpoint_latency_history
+                 WHERE app_type = ?1 AND provider_id = ?2 AND url = ?3
+                   AND is_healthy = 1 AND latency_ms IS NOT NULL
+                 ORDER BY tested_at DESC, id DESC
+                 LIMIT ?4",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut latencies = stmt
+            .query_map(
+                params![
+                    app_type,
+                    provider_id,
+                    url,
+                    ENDPOINT_PERCENTILE_SAMPLES as i64
+                ],
+                |row| row.get::<_, i64>(0),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .map(|v| v.map(|ms| ms.max(0) as u64))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        latencies.sort_unstable();
+
+        let p50 = crate::proxy::types::percentile(&latencies, 50);
+        let p95 = crate::proxy::types::percentile(&latencies, 95);
+        conn.execute(
+            "UPDATE provider_endpoints SET latency_p50_ms = ?1, latency_p95_ms = ?2
+             WHERE provider_id = ?3 AND app_type = ?4 AND url = ?5",
+            params![
+                p50.map(|v| v as i64),
+                p95.map(|v| v as i64),
+                provider_id,
+                app_type,
+                url
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok((p50, p95))
+    }
+
+    /// 按端点最近的采样重新计算抖动、状态切换次数与稳定性评分并保存
+    pub fn refresh_endpoint_stability(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+    ) -> Result<crate::proxy::types::EndpointStability, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT latency_ms, is_healthy FROM endpoint_latency_history
+                 WHERE app_type = ?1 AND provider_id = ?2 AND url = ?3
+                 ORDER BY tested_at DESC, id DESC
+                 LIMIT ?4",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut samples = stmt
+            .query_map(
+                params![
+                    app_type,
+                    provider_id,
+                    url,
+                    ENDPOINT_STABILITY_SAMPLES as i64
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<i64>>(0)?.map(|ms| ms.max(0) as u64),
+                        row.get::<_, i32>(1)? != 0,
+                    ))
+                },
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        samples.reverse();
+
+        let stability = crate::proxy::types::EndpointStability::from_samples(&samples);
+        conn.execute(
+            "UPDATE provider_endpoints SET jitter_ms = ?1, recent_flaps = ?2, stability_score = ?3
+             WHERE provider_id = ?4 AND app_type = ?5 AND url = ?6",
+            params![
+                stability.jitter_ms.map(|v| v as i64),
+                stability.flaps,
+                stability.score,
+                provider_id,
+                app_type,
+                url
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(stability)
+    }
+
+    /// 获取指定供应商自 `since`（Unix 秒）以来的端点延迟采样，按时间升序
+    pub fn get_endpoint_latency_history(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        since: i64,
+    ) -> Result<Vec<crate::proxy::types::EndpointLatencySample>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT url, tested_at, latency_ms, is_healthy
+                 FROM endpoint_latency_history
+                 WHERE app_type = ?1 AND provider_id = ?2 AND tested_at >= ?3
+                 ORDER BY tested_at ASC, id ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let samples = stmt
+            .query_map(params![app_type, provider_id, since], |row| {
+                Ok(crate::proxy::types::EndpointLatencySample {
+                    url: row.get(0)?,
+                    tested_at: row.get(1)?,
+                    latency_ms: row.get(2)?,
+                    is_healthy: row.get::<_, i32>(3)? != 0,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(samples)
+    }
+
     /// 设置主端点
     pub fn set_primary_endpoint(
         &self,
@@ -441,11 +697,11 @@ impl Database {
         url: &str,
     ) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
-        // 先清除该 provider 的所有主端点标记
+        // 先清除该 provider 的所有主端点标记，其他端点上的固定标记一并清除
         conn.execute(
-            "UPDATE provider_endpoints SET is_primary = 0
+            "UPDATE provider_endpoints SET is_primary = 0, is_pinned = (is_pinned AND url = ?3)
              WHERE provider_id = ?1 AND app_type = ?2",
-            params![provider_id, app_type],
+            params![provider_id, app_type, url],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
         // 设置新的主端点
@@ -458,7 +714,70 @@ impl Database {
         Ok(())
     }
 
-    /// 获取最佳端点 URL（主端点或延迟最低的健康端点）
+    /// 固定或取消固定主端点：固定时该端点同时成为主端点，后台测速不再改选
+    pub fn set_endpoint_pinned(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        pinned: bool,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = if pinned {
+            conn.execute(
+                "UPDATE provider_endpoints SET is_primary = (url = ?3), is_pinned = (url = ?3)
+                 WHERE provider_id = ?1 AND app_type = ?2",
+                params![provider_id, app_type, url],
+            )
+        } else {
+            conn.execute(
+                "UPDATE provider_endpoints SET is_pinned = 0
+                 WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
+                params![provider_id, app_type, url],
+            )
+        };
+        result.map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 设置端点在加权策略下的权重，None 表示清除
+    pub fn set_endpoint_weight(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        weight: Option<u32>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE provider_endpoints SET weight = ?1
+             WHERE provider_id = ?2 AND app_type = ?3 AND url = ?4",
+            params![weight, provider_id, app_type, url],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 设置端点的地区标签，None 表示清除
+    pub fn set_endpoint_region(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        region: Option<&str>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE provider_endpoints SET region = ?1
+             WHERE provider_id = ?2 AND app_type = ?3 AND url = ?4",
+            params![region, provider_id, app_type, url],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        self.bump_config_version();
+        Ok(())
+    }
+
+    /// 获取最佳端点 URL（主端点或 p95 延迟最低的健康端点）
     pub fn get_best_endpoint_url(
         &self,
         app_type: &str,
@@ -469,7 +788,7 @@ impl Database {
             .query_row(
                 "SELECT url FROM provider_endpoints
                  WHERE provider_id = ?1 AND app_type = ?2 AND is_healthy = 1
-                 ORDER BY is_primary DESC, latency_ms ASC NULLS LAST
+                 ORDER BY is_primary DESC, COALESCE(latency_p95_ms, latency_ms) ASC NULLS LAST
                  LIMIT 1",
                 params![provider_id, app_type],
                 |row| row.get(0),
@@ -3,11 +3,474 @@
 //! 处理代理配置、Provider健康状态和使用统计的数据库操作
 
 use crate::error::AppError;
+use crate::proxy::cors::CorsConfig;
+use crate::proxy::dns::{format_dns_overrides, parse_dns_overrides, DnsConfig};
+use crate::proxy::hedging::HedgingConfig;
+use crate::proxy::outlier::OutlierDetectionConfig;
+use crate::proxy::response_cache::ResponseCacheConfig;
+use crate::proxy::shadow::ShadowConfig;
+use crate::proxy::size_limits::SizeLimitConfig;
+use crate::proxy::switch_back::SwitchBackConfig;
+use crate::proxy::tls::ProxyTlsConfig;
 use crate::proxy::types::*;
+use crate::proxy::unix_socket::UnixSocketConfig;
+use crate::proxy::webhook::{
+    format_webhook_targets, parse_webhook_targets, WebhookConfig, WebhookEventKind,
+};
+use rusqlite::OptionalExtension;
 
 use super::super::{lock_conn, Database};
 
+/// settings 表中保存代理 HTTPS 开关的键
+const PROXY_TLS_ENABLED_KEY: &str = "proxy_tls_enabled";
+
+/// settings 表中保存代理证书路径的键
+const PROXY_TLS_CERT_PATH_KEY: &str = "proxy_tls_cert_path";
+
+/// settings 表中保存代理私钥路径的键
+const PROXY_TLS_KEY_PATH_KEY: &str = "proxy_tls_key_path";
+
+/// settings 表中保存代理访问令牌的键
+const PROXY_AUTH_TOKEN_KEY: &str = "proxy_auth_token";
+
+/// settings 表中保存代理 IP 白名单（逗号分隔的 CIDR）的键
+const PROXY_IP_ALLOWLIST_KEY: &str = "proxy_ip_allowlist";
+
+/// settings 表中保存代理 CORS 设置的键，列表均以逗号分隔
+const PROXY_CORS_ENABLED_KEY: &str = "proxy_cors_enabled";
+const PROXY_CORS_ORIGINS_KEY: &str = "proxy_cors_origins";
+const PROXY_CORS_METHODS_KEY: &str = "proxy_cors_methods";
+const PROXY_CORS_HEADERS_KEY: &str = "proxy_cors_headers";
+
+/// settings 表中保存代理 Unix socket 设置的键
+const PROXY_UNIX_SOCKET_ENABLED_KEY: &str = "proxy_unix_socket_enabled";
+const PROXY_UNIX_SOCKET_PATH_KEY: &str = "proxy_unix_socket_path";
+const PROXY_UNIX_SOCKET_ONLY_KEY: &str = "proxy_unix_socket_only";
+
+/// settings 表中保存端点离群检测设置的键
+const PROXY_OUTLIER_ENABLED_KEY: &str = "proxy_outlier_enabled";
+const PROXY_OUTLIER_MIN_REQUESTS_KEY: &str = "proxy_outlier_min_requests";
+const PROXY_OUTLIER_ERROR_MARGIN_KEY: &str = "proxy_outlier_error_rate_margin";
+const PROXY_OUTLIER_LATENCY_FACTOR_KEY: &str = "proxy_outlier_latency_factor";
+const PROXY_OUTLIER_EJECTION_SECONDS_KEY: &str = "proxy_outlier_base_ejection_seconds";
+const PROXY_OUTLIER_MAX_PERCENT_KEY: &str = "proxy_outlier_max_ejection_percent";
+
+/// settings 表中保存对冲请求设置的键
+const PROXY_HEDGING_ENABLED_KEY: &str = "proxy_hedging_enabled";
+const PROXY_HEDGING_PERCENTILE_KEY: &str = "proxy_hedging_percentile";
+const PROXY_HEDGING_MIN_DELAY_KEY: &str = "proxy_hedging_min_delay_ms";
+const PROXY_HEDGING_MIN_SAMPLES_KEY: &str = "proxy_hedging_min_samples";
+
+/// settings 表中保存响应缓存设置的键
+const PROXY_CACHE_ENABLED_KEY: &str = "proxy_cache_enabled";
+const PROXY_CACHE_TTL_KEY: &str = "proxy_cache_ttl_seconds";
+const PROXY_CACHE_MAX_ENTRIES_KEY: &str = "proxy_cache_max_entries";
+const PROXY_CACHE_MAX_SIZE_KEY: &str = "proxy_cache_max_size_kb";
+
+/// settings 表中保存全局上游代理地址的键
+const PROXY_UPSTREAM_URL_KEY: &str = "proxy_upstream_url";
+
+/// settings 表中保存端点地区偏好顺序的键，地区以逗号分隔
+const PROXY_REGION_ORDER_KEY: &str = "proxy_endpoint_region_order";
+
+/// settings 表中保存 DNS 设置的键，静态解析以 `host=ip` 逗号分隔
+const PROXY_DNS_CACHE_ENABLED_KEY: &str = "proxy_dns_cache_enabled";
+const PROXY_DNS_CACHE_TTL_KEY: &str = "proxy_dns_cache_ttl_seconds";
+const PROXY_DNS_OVERRIDES_KEY: &str = "proxy_dns_overrides";
+
+/// settings 表中保存请求与响应体大小上限（KB）的键
+const PROXY_MAX_REQUEST_KB_KEY: &str = "proxy_max_request_kb";
+const PROXY_MAX_RESPONSE_KB_KEY: &str = "proxy_max_response_kb";
+
+/// settings 表中保存 Webhook 设置的键，地址以 `[格式=]URL`、事件类型以逗号分隔
+const PROXY_WEBHOOK_TARGETS_KEY: &str = "proxy_webhook_targets";
+const PROXY_WEBHOOK_EVENTS_KEY: &str = "proxy_webhook_events";
+
 impl Database {
+    // ==================== Proxy TLS ====================
+
+    /// 获取代理 HTTPS 设置
+    pub fn get_proxy_tls_config(&self) -> Result<ProxyTlsConfig, AppError> {
+        let path = |key| -> Result<Option<String>, AppError> {
+            Ok(self.get_setting(key)?.filter(|v| !v.trim().is_empty()))
+        };
+        Ok(ProxyTlsConfig {
+            enabled: self
+                .get_setting(PROXY_TLS_ENABLED_KEY)?
+                .is_some_and(|v| v == "true"),
+            cert_path: path(PROXY_TLS_CERT_PATH_KEY)?,
+            key_path: path(PROXY_TLS_KEY_PATH_KEY)?,
+        })
+    }
+
+    /// 保存代理 HTTPS 设置，路径为空表示使用自签名证书
+    pub fn set_proxy_tls_config(&self, config: &ProxyTlsConfig) -> Result<(), AppError> {
+        self.set_setting(PROXY_TLS_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(
+            PROXY_TLS_CERT_PATH_KEY,
+            config.cert_path.as_deref().unwrap_or(""),
+        )?;
+        self.set_setting(
+            PROXY_TLS_KEY_PATH_KEY,
+            config.key_path.as_deref().unwrap_or(""),
+        )
+    }
+
+    // ==================== Proxy Auth Token ====================
+
+    /// 获取代理访问令牌，未设置时返回 None（不校验）
+    pub fn get_proxy_auth_token(&self) -> Result<Option<String>, AppError> {
+        Ok(self
+            .get_setting(PROXY_AUTH_TOKEN_KEY)?
+            .filter(|v| !v.trim().is_empty()))
+    }
+
+    /// 保存代理访问令牌，传入 None 关闭校验
+    pub fn set_proxy_auth_token(&self, token: Option<&str>) -> Result<(), AppError> {
+        self.set_setting(PROXY_AUTH_TOKEN_KEY, token.unwrap_or(""))
+    }
+
+    // ==================== Proxy IP Allowlist ====================
+
+    /// 获取允许连接代理的网段列表，空列表表示不限制
+    pub fn get_proxy_ip_allowlist(&self) -> Result<Vec<String>, AppError> {
+        self.get_list_setting(PROXY_IP_ALLOWLIST_KEY)
+    }
+
+    /// 保存允许连接代理的网段列表
+    pub fn set_proxy_ip_allowlist(&self, entries: &[String]) -> Result<(), AppError> {
+        self.set_setting(PROXY_IP_ALLOWLIST_KEY, &entries.join(","))
+    }
+
+    // ==================== Proxy CORS ====================
+
+    /// 获取代理 CORS 设置，未保存过时对任意来源放行
+    pub fn get_proxy_cors_config(&self) -> Result<CorsConfig, AppError> {
+        Ok(CorsConfig {
+            enabled: self
+                .get_setting(PROXY_CORS_ENABLED_KEY)?
+                .is_none_or(|v| v == "true"),
+            allowed_origins: self.get_list_setting(PROXY_CORS_ORIGINS_KEY)?,
+            allowed_methods: self.get_list_setting(PROXY_CORS_METHODS_KEY)?,
+            allowed_headers: self.get_list_setting(PROXY_CORS_HEADERS_KEY)?,
+        })
+    }
+
+    /// 保存代理 CORS 设置
+    pub fn set_proxy_cors_config(&self, config: &CorsConfig) -> Result<(), AppError> {
+        self.set_setting(PROXY_CORS_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(PROXY_CORS_ORIGINS_KEY, &config.allowed_origins.join(","))?;
+        self.set_setting(PROXY_CORS_METHODS_KEY, &config.allowed_methods.join(","))?;
+        self.set_setting(PROXY_CORS_HEADERS_KEY, &config.allowed_headers.join(","))
+    }
+
+    // ==================== Proxy Unix Socket ====================
+
+    /// 获取代理 Unix socket 设置
+    pub fn get_proxy_unix_socket_config(&self) -> Result<UnixSocketConfig, AppError> {
+        let flag = |key| -> Result<bool, AppError> {
+            Ok(self.get_setting(key)?.is_some_and(|v| v == "true"))
+        };
+        Ok(UnixSocketConfig {
+            enabled: flag(PROXY_UNIX_SOCKET_ENABLED_KEY)?,
+            path: self
+                .get_setting(PROXY_UNIX_SOCKET_PATH_KEY)?
+                .filter(|v| !v.trim().is_empty()),
+            socket_only: flag(PROXY_UNIX_SOCKET_ONLY_KEY)?,
+        })
+    }
+
+    /// 保存代理 Unix socket 设置，路径为空表示使用默认路径
+    pub fn set_proxy_unix_socket_config(&self, config: &UnixSocketConfig) -> Result<(), AppError> {
+        self.set_setting(PROXY_UNIX_SOCKET_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(
+            PROXY_UNIX_SOCKET_PATH_KEY,
+            config.path.as_deref().unwrap_or(""),
+        )?;
+        self.set_setting(PROXY_UNIX_SOCKET_ONLY_KEY, &config.socket_only.to_string())
+    }
+
+    // ==================== Outlier Detection ====================
+
+    /// 获取端点离群检测设置，未保存或无法解析的项使用默认值
+    pub fn get_proxy_outlier_config(&self) -> Result<OutlierDetectionConfig, AppError> {
+        fn parse<T: std::str::FromStr>(value: Option<String>, default: T) -> T {
+            value.and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        let default = OutlierDetectionConfig::default();
+        Ok(OutlierDetectionConfig {
+            enabled: parse(
+                self.get_setting(PROXY_OUTLIER_ENABLED_KEY)?,
+                default.enabled,
+            ),
+            min_requests: parse(
+                self.get_setting(PROXY_OUTLIER_MIN_REQUESTS_KEY)?,
+                default.min_requests,
+            ),
+            error_rate_margin: parse(
+                self.get_setting(PROXY_OUTLIER_ERROR_MARGIN_KEY)?,
+                default.error_rate_margin,
+            ),
+            latency_factor: parse(
+                self.get_setting(PROXY_OUTLIER_LATENCY_FACTOR_KEY)?,
+                default.latency_factor,
+            ),
+            base_ejection_seconds: parse(
+                self.get_setting(PROXY_OUTLIER_EJECTION_SECONDS_KEY)?,
+                default.base_ejection_seconds,
+            ),
+            max_ejection_percent: parse(
+                self.get_setting(PROXY_OUTLIER_MAX_PERCENT_KEY)?,
+                default.max_ejection_percent,
+            ),
+        })
+    }
+
+    /// 保存端点离群检测设置，运行中的代理在下一次选择端点时生效
+    pub fn set_proxy_outlier_config(
+        &self,
+        config: &OutlierDetectionConfig,
+    ) -> Result<(), AppError> {
+        self.set_setting(PROXY_OUTLIER_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(
+            PROXY_OUTLIER_MIN_REQUESTS_KEY,
+            &config.min_requests.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_OUTLIER_ERROR_MARGIN_KEY,
+            &config.error_rate_margin.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_OUTLIER_LATENCY_FACTOR_KEY,
+            &config.latency_factor.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_OUTLIER_EJECTION_SECONDS_KEY,
+            &config.base_ejection_seconds.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_OUTLIER_MAX_PERCENT_KEY,
+            &config.max_ejection_percent.to_string(),
+        )?;
+        self.bump_config_version();
+        Ok(())
+    }
+
+    // ==================== Hedging ====================
+
+    /// 获取对冲请求设置，未保存或无法解析的项使用默认值
+    pub fn get_proxy_hedging_config(&self) -> Result<HedgingConfig, AppError> {
+        fn parse<T: std::str::FromStr>(value: Option<String>, default: T) -> T {
+            value.and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        let default = HedgingConfig::default();
+        Ok(HedgingConfig {
+            enabled: parse(
+                self.get_setting(PROXY_HEDGING_ENABLED_KEY)?,
+                default.enabled,
+            ),
+            percentile: parse(
+                self.get_setting(PROXY_HEDGING_PERCENTILE_KEY)?,
+                default.percentile,
+            ),
+            min_delay_ms: parse(
+                self.get_setting(PROXY_HEDGING_MIN_DELAY_KEY)?,
+                default.min_delay_ms,
+            ),
+            min_samples: parse(
+                self.get_setting(PROXY_HEDGING_MIN_SAMPLES_KEY)?,
+                default.min_samples,
+            ),
+        })
+    }
+
+    /// 保存对冲请求设置，之后的新请求即按新设置处理
+    pub fn set_proxy_hedging_config(&self, config: &HedgingConfig) -> Result<(), AppError> {
+        self.set_setting(PROXY_HEDGING_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(PROXY_HEDGING_PERCENTILE_KEY, &config.percentile.to_string())?;
+        self.set_setting(
+            PROXY_HEDGING_MIN_DELAY_KEY,
+            &config.min_delay_ms.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_HEDGING_MIN_SAMPLES_KEY,
+            &config.min_samples.to_string(),
+        )
+    }
+
+    /// 获取非流式响应缓存设置，未保存时使用默认值（关闭）
+    pub fn get_proxy_response_cache_config(&self) -> Result<ResponseCacheConfig, AppError> {
+        fn parse<T: std::str::FromStr>(value: Option<String>, default: T) -> T {
+            value.and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        let default = ResponseCacheConfig::default();
+        Ok(ResponseCacheConfig {
+            enabled: parse(self.get_setting(PROXY_CACHE_ENABLED_KEY)?, default.enabled),
+            ttl_seconds: parse(self.get_setting(PROXY_CACHE_TTL_KEY)?, default.ttl_seconds),
+            max_entries: parse(
+                self.get_setting(PROXY_CACHE_MAX_ENTRIES_KEY)?,
+                default.max_entries,
+            ),
+            max_size_kb: parse(
+                self.get_setting(PROXY_CACHE_MAX_SIZE_KEY)?,
+                default.max_size_kb,
+            ),
+        })
+    }
+
+    /// 保存响应缓存设置，之后的新请求即按新设置处理
+    pub fn set_proxy_response_cache_config(
+        &self,
+        config: &ResponseCacheConfig,
+    ) -> Result<(), AppError> {
+        self.set_setting(PROXY_CACHE_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(PROXY_CACHE_TTL_KEY, &config.ttl_seconds.to_string())?;
+        self.set_setting(PROXY_CACHE_MAX_ENTRIES_KEY, &config.max_entries.to_string())?;
+        self.set_setting(PROXY_CACHE_MAX_SIZE_KEY, &config.max_size_kb.to_string())
+    }
+
+    // ==================== Endpoint Regions ====================
+
+    /// 获取端点地区偏好顺序，未设置时为空（不按地区排序）
+    pub fn get_endpoint_region_order(&self) -> Result<Vec<String>, AppError> {
+        Ok(self
+            .get_setting(PROXY_REGION_ORDER_KEY)?
+            .map(|v| crate::proxy::url_router::parse_region_order(&v))
+            .unwrap_or_default())
+    }
+
+    /// 保存端点地区偏好顺序，所有供应商的端点选择立即按新顺序进行
+    pub fn set_endpoint_region_order(&self, regions: &[String]) -> Result<(), AppError> {
+        self.set_setting(PROXY_REGION_ORDER_KEY, &regions.join(","))
+    }
+
+    // ==================== Upstream Proxy ====================
+
+    /// 获取全局上游代理地址，未设置时返回 None（直连）
+    pub fn get_proxy_upstream_url(&self) -> Result<Option<String>, AppError> {
+        Ok(self
+            .get_setting(PROXY_UPSTREAM_URL_KEY)?
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()))
+    }
+
+    /// 保存全局上游代理地址，传入 None 关闭；之后的新请求即经新代理发送
+    pub fn set_proxy_upstream_url(&self, url: Option<&str>) -> Result<(), AppError> {
+        self.set_setting(PROXY_UPSTREAM_URL_KEY, url.unwrap_or(""))
+    }
+
+    // ==================== DNS ====================
+
+    /// 获取 DNS 设置，未保存或无法解析的项使用默认值
+    pub fn get_proxy_dns_config(&self) -> Result<DnsConfig, AppError> {
+        fn parse<T: std::str::FromStr>(value: Option<String>, default: T) -> T {
+            value.and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        let default = DnsConfig::default();
+        Ok(DnsConfig {
+            cache_enabled: parse(
+                self.get_setting(PROXY_DNS_CACHE_ENABLED_KEY)?,
+                default.cache_enabled,
+            ),
+            cache_ttl_seconds: parse(
+                self.get_setting(PROXY_DNS_CACHE_TTL_KEY)?,
+                default.cache_ttl_seconds,
+            ),
+            overrides: self
+                .get_setting(PROXY_DNS_OVERRIDES_KEY)?
+                .and_then(|v| parse_dns_overrides(&v).ok())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// 保存 DNS 设置
+    pub fn set_proxy_dns_config(&self, config: &DnsConfig) -> Result<(), AppError> {
+        self.set_setting(
+            PROXY_DNS_CACHE_ENABLED_KEY,
+            &config.cache_enabled.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_DNS_CACHE_TTL_KEY,
+            &config.cache_ttl_seconds.to_string(),
+        )?;
+        self.set_setting(
+            PROXY_DNS_OVERRIDES_KEY,
+            &format_dns_overrides(&config.overrides),
+        )
+    }
+
+    // ==================== Size Limits ====================
+
+    /// 获取请求与响应体大小上限，未保存时使用默认值
+    pub fn get_proxy_size_limits(&self) -> Result<SizeLimitConfig, AppError> {
+        fn parse(value: Option<String>, default: u64) -> u64 {
+            value.and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        let default = SizeLimitConfig::default();
+        Ok(SizeLimitConfig {
+            max_request_kb: parse(
+                self.get_setting(PROXY_MAX_REQUEST_KB_KEY)?,
+                default.max_request_kb,
+            ),
+            max_response_kb: parse(
+                self.get_setting(PROXY_MAX_RESPONSE_KB_KEY)?,
+                default.max_response_kb,
+            ),
+        })
+    }
+
+    /// 保存请求与响应体大小上限，0 表示不限制；之后的新请求即按新上限检查
+    pub fn set_proxy_size_limits(&self, config: &SizeLimitConfig) -> Result<(), AppError> {
+        self.set_setting(PROXY_MAX_REQUEST_KB_KEY, &config.max_request_kb.to_string())?;
+        self.set_setting(
+            PROXY_MAX_RESPONSE_KB_KEY,
+            &config.max_response_kb.to_string(),
+        )
+    }
+
+    // ==================== Webhooks ====================
+
+    /// 获取 Webhook 设置，未保存时不通知任何地址，事件类型默认全部开启
+    pub fn get_proxy_webhook_config(&self) -> Result<WebhookConfig, AppError> {
+        let targets = self
+            .get_setting(PROXY_WEBHOOK_TARGETS_KEY)?
+            .and_then(|v| parse_webhook_targets(&v).ok())
+            .unwrap_or_default();
+        let events = match self.get_setting(PROXY_WEBHOOK_EVENTS_KEY)? {
+            Some(_) => self
+                .get_list_setting(PROXY_WEBHOOK_EVENTS_KEY)?
+                .iter()
+                .filter_map(|kind| kind.parse::<WebhookEventKind>().ok())
+                .collect(),
+            None => WebhookConfig::default().events,
+        };
+        Ok(WebhookConfig { targets, events })
+    }
+
+    /// 保存 Webhook 设置，之后发生的事件即按新设置通知
+    pub fn set_proxy_webhook_config(&self, config: &WebhookConfig) -> Result<(), AppError> {
+        self.set_setting(
+            PROXY_WEBHOOK_TARGETS_KEY,
+            &format_webhook_targets(&config.targets),
+        )?;
+        let events: Vec<&str> = config.events.iter().map(|kind| kind.as_str()).collect();
+        self.set_setting(PROXY_WEBHOOK_EVENTS_KEY, &events.join(","))
+    }
+
+    /// 读取以逗号分隔保存的列表设置
+    fn get_list_setting(&self, key: &str) -> Result<Vec<String>, AppError> {
+        Ok(self
+            .get_setting(key)?
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     // ==================== Global Proxy Config ====================
 
     /// 获取全局代理配置（统一字段）
@@ -174,6 +637,7 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        self.bump_config_version();
         Ok(())
     }
 
@@ -485,6 +949,7 @@ impl Database {
                         timeout_seconds: row.get::<_, i64>(2)? as u64,
                         error_rate_threshold: row.get(3)?,
                         min_requests: row.get::<_, i32>(4)? as u32,
+                        ..Default::default()
                     })
                 },
             )
@@ -531,9 +996,30 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        self.bump_config_version();
         Ok(())
     }
 
+    /// 供应商实际使用的熔断器配置：应用默认值叠加供应商自己的覆盖项
+    pub async fn get_provider_circuit_breaker_config(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<crate::proxy::circuit_breaker::CircuitBreakerConfig, AppError> {
+        let config = self
+            .get_proxy_config_for_app(app_type)
+            .await?
+            .circuit_breaker_config();
+        let tuning = self
+            .get_provider_by_id(provider_id, app_type)?
+            .and_then(|p| p.meta)
+            .and_then(|m| m.circuit_breaker);
+        Ok(match tuning {
+            Some(tuning) => tuning.apply(config),
+            None => config,
+        })
+    }
+
     // ==================== Live Backup ====================
 
     /// 保存 Live 配置备份
@@ -625,7 +1111,8 @@ impl Database {
         let conn = lock_conn!(self.conn);
 
         let result = conn.query_row(
-            "SELECT hybrid_mode_enabled, url_latency_test_interval, url_circuit_failure_threshold
+            "SELECT hybrid_mode_enabled, url_latency_test_interval, url_circuit_failure_threshold,
+                    speedtest_concurrency, speedtest_timeout_secs, speedtest_samples
              FROM proxy_config WHERE app_type = ?1",
             [app_type],
             |row| {
@@ -633,18 +1120,232 @@ impl Database {
                     enabled: row.get::<_, i32>(0).unwrap_or(0) != 0,
                     latency_test_interval: row.get::<_, i64>(1).unwrap_or(300) as u64,
                     url_circuit_failure_threshold: row.get::<_, i32>(2).unwrap_or(3) as u32,
+                    speedtest_concurrency: row.get::<_, i64>(3).unwrap_or(8) as u32,
+                    speedtest_timeout_secs: row.get::<_, i64>(4).unwrap_or(8) as u64,
+                    speedtest_samples: row.get::<_, i64>(5).unwrap_or(1) as u32,
                 })
             },
         );
 
-        match result {
-            Ok(config) => Ok(config),
-            Err(_) => Ok(crate::proxy::types::HybridModeConfig {
-                enabled: true,
-                latency_test_interval: 300,
-                url_circuit_failure_threshold: 3,
-            }),
-        }
+        Ok(result.unwrap_or_default())
+    }
+
+    /// 获取故障转移队列的负载均衡策略，未知值回退为 failover
+    pub fn get_load_balance_strategy(
+        &self,
+        app_type: &str,
+    ) -> Result<crate::proxy::types::LoadBalanceStrategy, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT load_balance_strategy FROM proxy_config WHERE app_type = ?1",
+                [app_type],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value.and_then(|v| v.parse().ok()).unwrap_or_default())
+    }
+
+    /// 设置故障转移队列的负载均衡策略
+    pub fn set_load_balance_strategy(
+        &self,
+        app_type: &str,
+        strategy: crate::proxy::types::LoadBalanceStrategy,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET load_balance_strategy = ?1 WHERE app_type = ?2",
+            rusqlite::params![strategy.as_str(), app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        self.bump_config_version();
+        Ok(())
+    }
+
+    /// 获取客户端侧限流配置，未配置时不限制
+    pub fn get_client_rate_limit(
+        &self,
+        app_type: &str,
+    ) -> Result<crate::proxy::types::ClientRateLimit, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value = conn
+            .query_row(
+                "SELECT rate_limit_rpm, rate_limit_concurrency FROM proxy_config WHERE app_type = ?1",
+                [app_type],
+                |row| {
+                    Ok(crate::proxy::types::ClientRateLimit {
+                        requests_per_minute: row.get::<_, i64>(0)?.max(0) as u32,
+                        max_concurrent: row.get::<_, i64>(1)?.max(0) as u32,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value.unwrap_or_default())
+    }
+
+    /// 设置客户端侧限流配置
+    pub fn set_client_rate_limit(
+        &self,
+        app_type: &str,
+        limit: crate::proxy::types::ClientRateLimit,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET rate_limit_rpm = ?1, rate_limit_concurrency = ?2
+             WHERE app_type = ?3",
+            rusqlite::params![limit.requests_per_minute, limit.max_concurrent, app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 获取流式响应中断重试开关
+    pub fn get_stream_failover(&self, app_type: &str) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value = conn
+            .query_row(
+                "SELECT stream_failover FROM proxy_config WHERE app_type = ?1",
+                [app_type],
+                |row| row.get::<_, i32>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value.unwrap_or(0) != 0)
+    }
+
+    /// 设置流式响应中断重试开关
+    pub fn set_stream_failover(&self, app_type: &str, enabled: bool) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET stream_failover = ?1 WHERE app_type = ?2",
+            rusqlite::params![if enabled { 1 } else { 0 }, app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 获取应用的独立监听端口，None 表示使用共享端口
+    pub fn get_dedicated_port(&self, app_type: &str) -> Result<Option<u16>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value = conn
+            .query_row(
+                "SELECT dedicated_port FROM proxy_config WHERE app_type = ?1",
+                [app_type],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value
+            .and_then(|port| u16::try_from(port).ok())
+            .filter(|&port| port > 0))
+    }
+
+    /// 获取应用的流量镜像设置
+    pub fn get_shadow_config(&self, app_type: &str) -> Result<ShadowConfig, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value = conn
+            .query_row(
+                "SELECT shadow_provider_id, shadow_percent FROM proxy_config WHERE app_type = ?1",
+                [app_type],
+                |row| {
+                    Ok(ShadowConfig {
+                        provider_id: row.get::<_, Option<String>>(0)?.filter(|id| !id.is_empty()),
+                        percent: row.get::<_, i64>(1)?.clamp(0, 100) as u32,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value.unwrap_or_default())
+    }
+
+    /// 保存应用的流量镜像设置，之后的新请求即按新设置镜像
+    pub fn set_shadow_config(&self, app_type: &str, config: &ShadowConfig) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET shadow_provider_id = ?1, shadow_percent = ?2
+             WHERE app_type = ?3",
+            rusqlite::params![config.provider_id, config.percent.min(100), app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 获取故障转移后切回首选供应商的设置，未知方式回退为 manual
+    pub fn get_switch_back_config(&self, app_type: &str) -> Result<SwitchBackConfig, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let value = conn
+            .query_row(
+                "SELECT switch_back_mode, switch_back_window_seconds FROM proxy_config
+                 WHERE app_type = ?1",
+                [app_type],
+                |row| {
+                    Ok(SwitchBackConfig {
+                        mode: row.get::<_, String>(0)?.parse().unwrap_or_default(),
+                        healthy_window_seconds: row.get::<_, i64>(1)?.max(0) as u64,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(value.unwrap_or_default())
+    }
+
+    /// 保存故障转移后切回首选供应商的设置
+    pub fn set_switch_back_config(
+        &self,
+        app_type: &str,
+        config: &SwitchBackConfig,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET switch_back_mode = ?1, switch_back_window_seconds = ?2
+             WHERE app_type = ?3",
+            rusqlite::params![
+                config.mode.as_str(),
+                i64::try_from(config.healthy_window_seconds).unwrap_or(i64::MAX),
+                app_type
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 设置应用的独立监听端口，传入 None 恢复使用共享端口
+    pub fn set_dedicated_port(&self, app_type: &str, port: Option<u16>) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        conn.execute(
+            "UPDATE proxy_config SET dedicated_port = ?1 WHERE app_type = ?2",
+            rusqlite::params![port.unwrap_or(0), app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
     }
 
     /// 设置混合模式启用状态
@@ -672,17 +1373,24 @@ impl Database {
             "UPDATE proxy_config SET
                 hybrid_mode_enabled = ?1,
                 url_latency_test_interval = ?2,
-                url_circuit_failure_threshold = ?3
-             WHERE app_type = ?4",
+                url_circuit_failure_threshold = ?3,
+                speedtest_concurrency = ?4,
+                speedtest_timeout_secs = ?5,
+                speedtest_samples = ?6
+             WHERE app_type = ?7",
             rusqlite::params![
                 if config.enabled { 1 } else { 0 },
                 config.latency_test_interval as i64,
                 config.url_circuit_failure_threshold as i32,
+                config.speedtest_concurrency as i64,
+                config.speedtest_timeout_secs as i64,
+                config.speedtest_samples as i64,
                 app_type
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        self.bump_config_version();
         Ok(())
     }
 }
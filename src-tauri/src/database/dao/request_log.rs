@@ -0,0 +1,249 @@
+//! 代理请求历史 DAO
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use crate::proxy::access_log::AccessLogConfig;
+use crate::proxy::request_log::{RequestCapture, RequestLogEntry, RequestLogFilter, StatusFilter};
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Row};
+
+/// settings 表中保存请求历史保留条数的键
+const REQUEST_HISTORY_LIMIT_KEY: &str = "request_history_limit";
+
+/// 默认保留的请求历史条数
+pub const DEFAULT_REQUEST_HISTORY_LIMIT: usize = 1000;
+
+/// settings 表中保存请求捕获条数的键
+const REQUEST_CAPTURE_LIMIT_KEY: &str = "request_capture_limit";
+
+/// settings 表中保存访问日志开关的键
+const ACCESS_LOG_ENABLED_KEY: &str = "access_log_enabled";
+
+/// settings 表中保存访问日志单文件大小上限（MB）的键
+const ACCESS_LOG_MAX_SIZE_KEY: &str = "access_log_max_size_mb";
+
+impl Database {
+    /// 获取请求历史保留条数，0 表示不记录
+    pub fn get_request_history_limit(&self) -> Result<usize, AppError> {
+        Ok(self
+            .get_setting(REQUEST_HISTORY_LIMIT_KEY)?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_REQUEST_HISTORY_LIMIT))
+    }
+
+    /// 设置请求历史保留条数，并立即清理超出的旧记录
+    pub fn set_request_history_limit(&self, limit: usize) -> Result<(), AppError> {
+        self.set_setting(REQUEST_HISTORY_LIMIT_KEY, &limit.to_string())?;
+        let conn = lock_conn!(self.conn);
+        Self::prune_request_log(&conn, limit)
+    }
+
+    /// 获取保存完整请求的条数，0 表示关闭捕获
+    pub fn get_request_capture_limit(&self) -> Result<usize, AppError> {
+        Ok(self
+            .get_setting(REQUEST_CAPTURE_LIMIT_KEY)?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0))
+    }
+
+    /// 设置请求捕获条数，并立即清理超出的旧捕获
+    pub fn set_request_capture_limit(&self, limit: usize) -> Result<(), AppError> {
+        self.set_setting(REQUEST_CAPTURE_LIMIT_KEY, &limit.to_string())?;
+        let conn = lock_conn!(self.conn);
+        Self::prune_request_captures(&conn, limit)
+    }
+
+    /// 获取访问日志设置
+    pub fn get_access_log_config(&self) -> Result<AccessLogConfig, AppError> {
+        let default = AccessLogConfig::default();
+        Ok(AccessLogConfig {
+            enabled: self
+                .get_setting(ACCESS_LOG_ENABLED_KEY)?
+                .is_some_and(|v| v == "true"),
+            max_size_mb: self
+                .get_setting(ACCESS_LOG_MAX_SIZE_KEY)?
+                .and_then(|v| v.parse().ok())
+                .filter(|&mb| mb > 0)
+                .unwrap_or(default.max_size_mb),
+        })
+    }
+
+    /// 保存访问日志设置
+    pub fn set_access_log_config(&self, config: AccessLogConfig) -> Result<(), AppError> {
+        self.set_setting(ACCESS_LOG_ENABLED_KEY, &config.enabled.to_string())?;
+        self.set_setting(ACCESS_LOG_MAX_SIZE_KEY, &config.max_size_mb.to_string())
+    }
+
+    /// 写入一条请求记录，超出保留条数的旧记录会被删除
+    pub fn insert_request_log(&self, entry: &RequestLogEntry) -> Result<(), AppError> {
+        let limit = self.get_request_history_limit()?;
+        if limit == 0 {
+            return Ok(());
+        }
+        let capture_limit = self.get_request_capture_limit()?;
+
+        let request_headers = serde_json::to_string(&entry.request_headers)
+            .map_err(|e| AppError::Database(format!("序列化请求头失败: {e}")))?;
+        let response_headers = serde_json::to_string(&entry.response_headers)
+            .map_err(|e| AppError::Database(format!("序列化响应头失败: {e}")))?;
+
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO request_log
+             (created_at, app_type, provider_id, provider_name, url, status_code,
+              latency_ms, queued_ms, error_message, request_headers, response_headers)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                entry.timestamp,
+                entry.app_type,
+                entry.provider_id,
+                entry.provider_name,
+                entry.url,
+                entry.status.map(i64::from),
+                entry.latency_ms as i64,
+                entry.queued_ms as i64,
+                entry.error,
+                request_headers,
+                response_headers,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        if let Some(capture) = &entry.capture {
+            let headers = serde_json::to_string(&capture.headers)
+                .map_err(|e| AppError::Database(format!("序列化捕获请求头失败: {e}")))?;
+            conn.execute(
+                "INSERT INTO request_capture (log_id, endpoint, headers, body)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    conn.last_insert_rowid(),
+                    capture.endpoint,
+                    headers,
+                    capture.body
+                ],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        Self::prune_request_log(&conn, limit)?;
+        Self::prune_request_captures(&conn, capture_limit)
+    }
+
+    fn prune_request_log(conn: &rusqlite::Connection, limit: usize) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM request_log WHERE id NOT IN
+             (SELECT id FROM request_log ORDER BY id DESC LIMIT ?1)",
+            params![limit as i64],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 只保留最近 `limit` 条捕获，并删除对应历史记录已被清理的捕获
+    fn prune_request_captures(conn: &rusqlite::Connection, limit: usize) -> Result<(), AppError> {
+        conn.execute(
+            "DELETE FROM request_capture
+             WHERE log_id NOT IN (SELECT id FROM request_log)
+                OR log_id NOT IN (SELECT log_id FROM request_capture ORDER BY log_id DESC LIMIT ?1)",
+            params![limit as i64],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 分页查询请求历史（最新的在前），返回当前页记录与匹配总数
+    pub fn query_request_logs(
+        &self,
+        filter: &RequestLogFilter,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<RequestLogEntry>, usize), AppError> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+
+        if let Some(app_type) = &filter.app_type {
+            conditions.push("app_type = ?");
+            values.push(SqlValue::Text(app_type.clone()));
+        }
+        if let Some(provider) = &filter.provider {
+            conditions.push("provider_name LIKE ?");
+            values.push(SqlValue::Text(format!("%{provider}%")));
+        }
+        match filter.status {
+            Some(StatusFilter::Code(code)) => {
+                conditions.push("status_code = ?");
+                values.push(SqlValue::Integer(code.into()));
+            }
+            Some(StatusFilter::Class(class)) => {
+                conditions.push("status_code BETWEEN ? AND ?");
+                values.push(SqlValue::Integer(i64::from(class) * 100));
+                values.push(SqlValue::Integer(i64::from(class) * 100 + 99));
+            }
+            Some(StatusFilter::Failed) => conditions.push("status_code IS NULL"),
+            None => {}
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let conn = lock_conn!(self.conn);
+        let total: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM request_log {where_clause}"),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT created_at, app_type, provider_id, provider_name, url, status_code,
+                        latency_ms, queued_ms, error_message, request_headers, response_headers,
+                        c.endpoint, c.headers, c.body
+                 FROM request_log LEFT JOIN request_capture c ON c.log_id = request_log.id
+                 {where_clause}
+                 ORDER BY id DESC LIMIT ? OFFSET ?"
+            ))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        values.push(SqlValue::Integer(limit as i64));
+        values.push(SqlValue::Integer(offset as i64));
+
+        let entries = stmt
+            .query_map(params_from_iter(values.iter()), row_to_entry)
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok((entries, total as usize))
+    }
+}
+
+fn row_to_entry(row: &Row) -> rusqlite::Result<RequestLogEntry> {
+    let headers = |index: usize| -> rusqlite::Result<Vec<(String, String)>> {
+        let json: String = row.get(index)?;
+        Ok(serde_json::from_str(&json).unwrap_or_default())
+    };
+    Ok(RequestLogEntry {
+        timestamp: row.get(0)?,
+        app_type: row.get(1)?,
+        provider_id: row.get(2)?,
+        provider_name: row.get(3)?,
+        url: row.get(4)?,
+        status: row.get::<_, Option<i64>>(5)?.map(|s| s as u16),
+        latency_ms: row.get::<_, i64>(6)? as u64,
+        queued_ms: row.get::<_, i64>(7)? as u64,
+        error: row.get(8)?,
+        request_headers: headers(9)?,
+        response_headers: headers(10)?,
+        capture: match row.get::<_, Option<String>>(11)? {
+            Some(endpoint) => Some(RequestCapture {
+                endpoint,
+                headers: headers(12)?,
+                body: row.get(13)?,
+            }),
+            None => None,
+        },
+    })
+}
@@ -0,0 +1,133 @@
+//! 数据库静态加密（SQLCipher）
+//!
+//! 以 `sqlcipher` feature 构建时使用 rusqlite 内置的 SQLCipher 加密整个数据库文件。
+//! 口令在 TUI 启动时输入，无交互场景可通过环境变量 `CC_SWITCH_DB_PASSPHRASE` 提供；
+//! 已有的明文数据库可用 [`Database::encrypt_file`] 就地转换。
+
+use super::Database;
+use crate::error::AppError;
+use rusqlite::{params, Connection, DatabaseName};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// 当前构建是否支持数据库加密
+pub const DATABASE_ENCRYPTION_AVAILABLE: bool = cfg!(feature = "sqlcipher");
+
+/// 提供数据库口令的环境变量
+pub const DB_PASSPHRASE_ENV: &str = "CC_SWITCH_DB_PASSPHRASE";
+
+/// 明文 SQLite 文件的文件头，加密后的文件头是随机字节
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 数据库文件的加密状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseFileState {
+    /// 文件不存在或为空，首次打开时创建
+    Missing,
+    Plaintext,
+    Encrypted,
+}
+
+/// 通过文件头判断数据库文件是否已加密
+pub fn database_file_state(path: &Path) -> Result<DatabaseFileState, AppError> {
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(DatabaseFileState::Missing)
+        }
+        Err(e) => return Err(AppError::io(path, e)),
+    };
+    let mut header = Vec::with_capacity(SQLITE_HEADER.len());
+    file.by_ref()
+        .take(SQLITE_HEADER.len() as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| AppError::io(path, e))?;
+
+    Ok(if header.is_empty() {
+        DatabaseFileState::Missing
+    } else if header == SQLITE_HEADER {
+        DatabaseFileState::Plaintext
+    } else {
+        DatabaseFileState::Encrypted
+    })
+}
+
+fn ensure_available() -> Result<(), AppError> {
+    if DATABASE_ENCRYPTION_AVAILABLE {
+        Ok(())
+    } else {
+        Err(AppError::Config(
+            "当前构建未启用数据库加密，请以 sqlcipher feature 重新构建".to_string(),
+        ))
+    }
+}
+
+/// 为连接设置口令，并读取一次 schema 校验口令是否正确
+pub(crate) fn apply_key(conn: &Connection, passphrase: &str) -> Result<(), AppError> {
+    ensure_available()?;
+    conn.pragma_update(None, "key", passphrase)
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AppError::Database("数据库口令错误，或文件不是加密数据库".to_string()))?;
+    Ok(())
+}
+
+impl Database {
+    /// 把明文数据库文件就地转换为加密数据库：先导出到临时文件并校验，成功后替换原文件
+    pub fn encrypt_file(path: &Path, passphrase: &str) -> Result<(), AppError> {
+        ensure_available()?;
+        if passphrase.is_empty() {
+            return Err(AppError::InvalidInput("数据库口令不能为空".to_string()));
+        }
+        if database_file_state(path)? != DatabaseFileState::Plaintext {
+            return Err(AppError::InvalidInput(format!(
+                "不是明文数据库: {}",
+                path.display()
+            )));
+        }
+
+        let temp_path = path.with_extension("db.encrypting");
+        if temp_path.exists() {
+            fs::remove_file(&temp_path).map_err(|e| AppError::io(&temp_path, e))?;
+        }
+        let export = || -> rusqlite::Result<()> {
+            let conn = Connection::open(path)?;
+            let user_version: i32 =
+                conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+            conn.execute(
+                "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+                params![temp_path.to_string_lossy(), passphrase],
+            )?;
+            conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+            // sqlcipher_export 不复制 user_version，需单独写入以免重复执行迁移
+            conn.pragma_update(
+                Some(DatabaseName::Attached("encrypted")),
+                "user_version",
+                user_version,
+            )?;
+            conn.execute("DETACH DATABASE encrypted", [])?;
+            Ok(())
+        };
+        if let Err(e) = export() {
+            let _ = fs::remove_file(&temp_path);
+            return Err(AppError::Database(format!("加密数据库失败: {e}")));
+        }
+
+        let verified = Connection::open(&temp_path)
+            .map_err(|e| AppError::Database(e.to_string()))
+            .and_then(|conn| apply_key(&conn, passphrase));
+        if let Err(e) = verified {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+        fs::rename(&temp_path, path).map_err(|e| AppError::io(path, e))?;
+        log::info!(
+            "数据库已加密: {}（backups 目录中此前的备份仍为明文，可自行删除）",
+            path.display()
+        );
+        Ok(())
+    }
+}
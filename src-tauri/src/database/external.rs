@@ -0,0 +1,74 @@
+//! 读取其它 cc-switch 实例的数据库
+//!
+//! 桌面 GUI 与 TUI 使用相同的表结构，复制到内存升级 Schema 后即可用现有 DAO 读取，
+//! 原文件保持只读。
+
+use super::{encryption, Database, DatabaseFileState, SCHEMA_VERSION};
+use crate::error::AppError;
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use std::sync::Mutex;
+
+impl Database {
+    /// 以只读方式打开另一份 cc-switch 数据库（如桌面版的 `~/.cc-switch/cc-switch.db`），
+    /// 传入目录时读取其中的 `cc-switch.db`；返回升级到当前 Schema 的内存副本
+    pub fn open_external(path: &Path) -> Result<Database, AppError> {
+        let path = resolve_external_path(path);
+        match encryption::database_file_state(&path)? {
+            DatabaseFileState::Missing => {
+                return Err(AppError::Message(format!(
+                    "数据库文件不存在: {}",
+                    path.display()
+                )))
+            }
+            DatabaseFileState::Encrypted => {
+                return Err(AppError::Message(format!(
+                    "{} 不是 SQLite 数据库或已加密",
+                    path.display()
+                )))
+            }
+            DatabaseFileState::Plaintext => {}
+        }
+
+        // 通过 Backup 复制可以带上 WAL 中尚未检查点的数据
+        let source = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut conn =
+            Connection::open_in_memory().map_err(|e| AppError::Database(e.to_string()))?;
+        {
+            let backup =
+                Backup::new(&source, &mut conn).map_err(|e| AppError::Database(e.to_string()))?;
+            backup
+                .step(-1)
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        Self::create_tables_on_conn(&conn)?;
+        // 对方版本更新时无法降级，按现有列尽量读取
+        let version = Self::get_user_version(&conn)?;
+        if version > SCHEMA_VERSION {
+            log::warn!(
+                "{} 的 Schema 版本（{version}）高于当前支持的 {SCHEMA_VERSION}，跳过迁移",
+                path.display()
+            );
+        } else {
+            Self::apply_schema_migrations_on_conn(&conn)?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config_version: AtomicU64::new(0),
+            passphrase: None,
+        })
+    }
+}
+
+fn resolve_external_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join("cc-switch.db")
+    } else {
+        path.to_path_buf()
+    }
+}
@@ -0,0 +1,192 @@
+//! 数据库维护：完整性检查、WAL 检查点与 VACUUM
+//!
+//! 请求日志与延迟历史表会让数据库持续增长，删除记录后文件不会自动收缩，需要 VACUUM 回收空间。
+//! 维护操作执行期间持有数据库连接锁，其他读写会等待其完成。
+
+use super::{lock_conn, Database};
+use crate::error::AppError;
+use serde::Serialize;
+
+/// 完整性检查最多报告的问题条数
+const INTEGRITY_MAX_ERRORS: u32 = 100;
+
+/// 数据库大小与各表行数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStats {
+    /// 数据库文件大小，内存数据库为 0
+    pub file_size_bytes: u64,
+    pub page_size: u64,
+    pub page_count: u64,
+    /// 空闲页数，VACUUM 可回收 `freelist_count * page_size` 字节
+    pub freelist_count: u64,
+    pub journal_mode: String,
+    /// 各表行数，从多到少排列
+    pub tables: Vec<(String, u64)>,
+}
+
+impl DatabaseStats {
+    /// VACUUM 预计可回收的字节数
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.freelist_count * self.page_size
+    }
+}
+
+/// 维护操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    IntegrityCheck,
+    WalCheckpoint,
+    Vacuum,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 3] = [Self::IntegrityCheck, Self::WalCheckpoint, Self::Vacuum];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::IntegrityCheck => "Integrity check",
+            Self::WalCheckpoint => "WAL checkpoint",
+            Self::Vacuum => "VACUUM",
+        }
+    }
+}
+
+/// 维护操作的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "task")]
+pub enum MaintenanceOutcome {
+    /// 发现的问题，为空表示数据库完好
+    IntegrityCheck { problems: Vec<String> },
+    /// 写回主库的 WAL 页数；非 WAL 模式时为 None
+    WalCheckpoint { checkpointed_pages: Option<u64> },
+    Vacuum {
+        size_before_bytes: u64,
+        size_after_bytes: u64,
+    },
+}
+
+impl Database {
+    /// 读取数据库大小、空闲页与各表行数
+    pub fn database_stats(&self) -> Result<DatabaseStats, AppError> {
+        let conn = lock_conn!(self.conn);
+        let pragma = |name: &str| -> Result<u64, AppError> {
+            conn.pragma_query_value(None, name, |row| row.get::<_, i64>(0))
+                .map(|v| v.max(0) as u64)
+                .map_err(|e| AppError::Database(e.to_string()))
+        };
+        let page_size = pragma("page_size")?;
+        let page_count = pragma("page_count")?;
+        let freelist_count = pragma("freelist_count")?;
+        let journal_mode: String = conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let file_size_bytes = conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .and_then(|path| std::fs::metadata(path).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        let names: Vec<String> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT name FROM sqlite_master
+                     WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|e| AppError::Database(e.to_string()))?
+        };
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let count: i64 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            tables.push((name, count.max(0) as u64));
+        }
+        tables.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(DatabaseStats {
+            file_size_bytes,
+            page_size,
+            page_count,
+            freelist_count,
+            journal_mode,
+            tables,
+        })
+    }
+
+    /// 执行一项维护操作
+    pub fn run_maintenance(&self, task: MaintenanceTask) -> Result<MaintenanceOutcome, AppError> {
+        match task {
+            MaintenanceTask::IntegrityCheck => Ok(MaintenanceOutcome::IntegrityCheck {
+                problems: self.integrity_check()?,
+            }),
+            MaintenanceTask::WalCheckpoint => Ok(MaintenanceOutcome::WalCheckpoint {
+                checkpointed_pages: self.wal_checkpoint()?,
+            }),
+            MaintenanceTask::Vacuum => {
+                let size_before_bytes = self.database_size_bytes()?;
+                {
+                    let conn = lock_conn!(self.conn);
+                    conn.execute_batch("VACUUM;")
+                        .map_err(|e| AppError::Database(format!("VACUUM 失败: {e}")))?;
+                }
+                Ok(MaintenanceOutcome::Vacuum {
+                    size_before_bytes,
+                    size_after_bytes: self.database_size_bytes()?,
+                })
+            }
+        }
+    }
+
+    /// 完整性检查，返回发现的问题（为空表示完好）
+    fn integrity_check(&self) -> Result<Vec<String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA integrity_check({INTEGRITY_MAX_ERRORS})"))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let messages: Vec<String> = rows
+            .collect::<Result<_, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    /// 把 WAL 写回主库并截断 WAL 文件，非 WAL 模式时返回 None
+    fn wal_checkpoint(&self) -> Result<Option<u64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let (busy, log_pages, checkpointed): (i64, i64, i64) = conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        if busy != 0 {
+            return Err(AppError::Database(
+                "WAL 检查点未完成：数据库正被其他连接使用".to_string(),
+            ));
+        }
+        Ok((log_pages >= 0).then_some(checkpointed.max(0) as u64))
+    }
+
+    /// 按页数计算的数据库大小，内存数据库同样适用
+    fn database_size_bytes(&self) -> Result<u64, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|v| v.max(0) as u64)
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
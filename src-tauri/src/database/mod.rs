@@ -14,6 +14,9 @@
 //! ├── mod.rs        - Database 结构体 + 初始化
 //! ├── schema.rs     - 表结构定义 + Schema 迁移
 //! ├── backup.rs     - SQL 导入导出 + 快照备份
+//! ├── encryption.rs - SQLCipher 静态加密
+//! ├── external.rs   - 读取桌面版等其它实例的数据库
+//! ├── maintenance.rs - VACUUM / 完整性检查 / WAL 检查点
 //! ├── migration.rs  - JSON → SQLite 数据迁移
 //! └── dao/          - 数据访问对象
 //!     ├── providers.rs
@@ -25,6 +28,9 @@
 
 mod backup;
 mod dao;
+mod encryption;
+mod external;
+mod maintenance;
 mod migration;
 mod schema;
 
@@ -33,12 +39,20 @@ mod tests;
 
 // DAO 类型导出供外部使用
 #[allow(unused_imports)]
-pub use dao::FailoverQueueItem;
+pub use dao::{FailoverEvent, FailoverQueueItem, DEFAULT_REQUEST_HISTORY_LIMIT};
+
+pub use backup::{AutoBackupConfig, BackupFile, BackupSummary};
+pub use encryption::{
+    database_file_state, DatabaseFileState, DATABASE_ENCRYPTION_AVAILABLE, DB_PASSPHRASE_ENV,
+};
+pub use maintenance::{DatabaseStats, MaintenanceOutcome, MaintenanceTask};
 
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
 use rusqlite::Connection;
 use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 
 // DAO 方法通过 impl Database 提供，无需额外导出
@@ -74,21 +88,48 @@ pub(crate) use lock_conn;
 /// rusqlite::Connection 本身不是 Sync 的，因此需要这层包装。
 pub struct Database {
     pub(crate) conn: Mutex<Connection>,
+    /// 路由配置版本号：供应商、故障转移队列或代理配置写入后递增，
+    /// 代理据此在不重启的情况下同步运行时状态
+    config_version: AtomicU64,
+    /// 加密数据库的口令，备份与导入时的临时库使用同一口令
+    passphrase: Option<String>,
 }
 
 impl Database {
+    /// 数据库文件路径：`~/.cc-switch/cc-switch.db`
+    pub fn file_path() -> PathBuf {
+        get_app_config_dir().join("cc-switch.db")
+    }
+
     /// 初始化数据库连接并创建表
     ///
     /// 数据库文件位于 `~/.cc-switch/cc-switch.db`
     pub fn init() -> Result<Self, AppError> {
-        let db_path = get_app_config_dir().join("cc-switch.db");
+        Self::init_with_passphrase(None)
+    }
 
+    /// 初始化数据库连接并创建表，提供口令时以 SQLCipher 加密数据库打开（文件不存在时新建加密库）
+    pub fn init_with_passphrase(passphrase: Option<&str>) -> Result<Self, AppError> {
+        Self::open_file(&Self::file_path(), passphrase)
+    }
+
+    fn open_file(db_path: &std::path::Path, passphrase: Option<&str>) -> Result<Self, AppError> {
         // 确保父目录存在
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
         }
 
-        let conn = Connection::open(&db_path).map_err(|e| AppError::Database(e.to_string()))?;
+        let conn = Connection::open(db_path).map_err(|e| AppError::Database(e.to_string()))?;
+        if let Some(passphrase) = passphrase {
+            encryption::apply_key(&conn, passphrase)?;
+        }
+
+        // WAL 模式下代理写入请求日志不会阻塞界面读取；不支持 WAL 的文件系统上保留默认模式
+        if let Err(e) =
+            conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+        {
+            log::warn!("启用 WAL 模式失败，继续使用默认日志模式: {e}");
+        }
 
         // 启用外键约束
         conn.execute("PRAGMA foreign_keys = ON;", [])
@@ -96,6 +137,8 @@ impl Database {
 
         let db = Self {
             conn: Mutex::new(conn),
+            config_version: AtomicU64::new(0),
+            passphrase: passphrase.map(str::to_string),
         };
         db.create_tables()?;
         db.apply_schema_migrations()?;
@@ -114,6 +157,8 @@ impl Database {
 
         let db = Self {
             conn: Mutex::new(conn),
+            config_version: AtomicU64::new(0),
+            passphrase: None,
         };
         db.create_tables()?;
         db.ensure_model_pricing_seeded()?;
@@ -121,6 +166,25 @@ impl Database {
         Ok(db)
     }
 
+    /// 打开磁盘上的辅助数据库（备份、导入临时库），加密模式下使用同一口令
+    pub(crate) fn open_companion(&self, path: &std::path::Path) -> Result<Connection, AppError> {
+        let conn = Connection::open(path).map_err(|e| AppError::Database(e.to_string()))?;
+        if let Some(passphrase) = &self.passphrase {
+            encryption::apply_key(&conn, passphrase)?;
+        }
+        Ok(conn)
+    }
+
+    /// 当前路由配置版本号
+    pub fn config_version(&self) -> u64 {
+        self.config_version.load(Ordering::Acquire)
+    }
+
+    /// 路由相关配置写入后调用，通知代理重新加载
+    pub(crate) fn bump_config_version(&self) {
+        self.config_version.fetch_add(1, Ordering::AcqRel);
+    }
+
     /// 检查 MCP 服务器表是否为空
     pub fn is_mcp_table_empty(&self) -> Result<bool, AppError> {
         let conn = lock_conn!(self.conn);
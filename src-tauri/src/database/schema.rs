@@ -32,6 +32,8 @@ impl Database {
                 meta TEXT NOT NULL DEFAULT '{}',
                 is_current BOOLEAN NOT NULL DEFAULT 0,
                 in_failover_queue BOOLEAN NOT NULL DEFAULT 0,
+                last_used_at INTEGER,
+                weight INTEGER,
                 PRIMARY KEY (id, app_type)
             )",
             [],
@@ -237,6 +239,108 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        // 17. Request Log 表 (代理请求历史摘要)
+        conn.execute("CREATE TABLE IF NOT EXISTS request_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, created_at INTEGER NOT NULL, app_type TEXT NOT NULL,
+            provider_id TEXT NOT NULL, provider_name TEXT NOT NULL, url TEXT NOT NULL, status_code INTEGER,
+            latency_ms INTEGER NOT NULL, queued_ms INTEGER NOT NULL DEFAULT 0, error_message TEXT,
+            request_headers TEXT NOT NULL DEFAULT '[]', response_headers TEXT NOT NULL DEFAULT '[]'
+        )", []).map_err(|e| AppError::Database(e.to_string()))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_request_log_app_provider ON request_log(app_type, provider_id)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 18. Provider Usage Daily 表 (按供应商、按天汇总的代理流量，不随请求日志清理)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_usage_daily (
+            app_type TEXT NOT NULL, provider_id TEXT NOT NULL, day TEXT NOT NULL,
+            request_count INTEGER NOT NULL DEFAULT 0, error_count INTEGER NOT NULL DEFAULT 0,
+            input_tokens INTEGER NOT NULL DEFAULT 0, output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0,
+            PRIMARY KEY (app_type, provider_id, day)
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Self::add_column_if_missing(
+            conn,
+            "provider_usage_daily",
+            "cost_usd",
+            "REAL NOT NULL DEFAULT 0",
+        )?;
+        Self::add_column_if_missing(
+            conn,
+            "provider_usage_daily",
+            "cache_read_tokens",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::add_column_if_missing(
+            conn,
+            "provider_usage_daily",
+            "cache_creation_tokens",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+
+        // 19. Model Usage Daily 表 (按模型、按天汇总 token，含缓存读写)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS model_usage_daily (
+            app_type TEXT NOT NULL, model TEXT NOT NULL, day TEXT NOT NULL,
+            request_count INTEGER NOT NULL DEFAULT 0,
+            input_tokens INTEGER NOT NULL DEFAULT 0, output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0,
+            PRIMARY KEY (app_type, model, day)
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 20. Endpoint Latency History 表 (后台测速的延迟采样，仅保留最近 24 小时)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS endpoint_latency_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            app_type TEXT NOT NULL, provider_id TEXT NOT NULL, url TEXT NOT NULL,
+            tested_at INTEGER NOT NULL, latency_ms INTEGER, is_healthy INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_endpoint_latency_history_provider
+             ON endpoint_latency_history(app_type, provider_id, tested_at)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 21. Request Capture 表 (捕获模式下保存的完整请求，按 request_log.id 关联)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS request_capture (
+            log_id INTEGER PRIMARY KEY, endpoint TEXT NOT NULL,
+            headers TEXT NOT NULL DEFAULT '[]', body TEXT NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 22. Failover Events 表 (自动故障转移切换供应商的历史记录)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS failover_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at INTEGER NOT NULL, app_type TEXT NOT NULL,
+            from_provider_id TEXT, from_provider_name TEXT,
+            to_provider_id TEXT NOT NULL, to_provider_name TEXT NOT NULL,
+            error TEXT
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
         // 尝试添加 live_takeover_active 列到 proxy_config 表
         let _ = conn.execute(
             "ALTER TABLE proxy_config ADD COLUMN live_takeover_active INTEGER NOT NULL DEFAULT 0",
@@ -283,6 +387,111 @@ impl Database {
             Self::migrate_proxy_config_to_per_app(conn)?;
         }
 
+        // 确保 load_balance_strategy 列存在（故障转移队列的负载均衡策略）
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "load_balance_strategy",
+                "TEXT NOT NULL DEFAULT 'failover'",
+            )?;
+            // 客户端侧限流（每分钟请求数、并发数），0 表示不限制
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "rate_limit_rpm",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "rate_limit_concurrency",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            // 流式响应在首个事件前中断时是否改用下一个供应商重试
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "stream_failover",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            // 应用独立监听端口，0 表示使用共享端口
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "dedicated_port",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            // 流量镜像：目标供应商与镜像比例，比例为 0 表示关闭
+            Self::add_column_if_missing(conn, "proxy_config", "shadow_provider_id", "TEXT")?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "shadow_percent",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            // 故障转移后切回首选供应商的方式与所需的连续健康时长（秒）
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "switch_back_mode",
+                "TEXT NOT NULL DEFAULT 'manual'",
+            )?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "switch_back_window_seconds",
+                "INTEGER NOT NULL DEFAULT 300",
+            )?;
+            // 端点延迟测试的并发数、每个 URL 的超时（秒）与采样次数
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "speedtest_concurrency",
+                "INTEGER NOT NULL DEFAULT 8",
+            )?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "speedtest_timeout_secs",
+                "INTEGER NOT NULL DEFAULT 8",
+            )?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "speedtest_samples",
+                "INTEGER NOT NULL DEFAULT 1",
+            )?;
+        }
+
+        // 确保 is_pinned 列存在（手动固定的主端点不随测速结果改选）
+        Self::add_column_if_missing(
+            conn,
+            "provider_endpoints",
+            "is_pinned",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+
+        // 确保 weight 列存在（供应商内多个端点的加权分流）
+        Self::add_column_if_missing(conn, "provider_endpoints", "weight", "INTEGER")?;
+
+        // 确保延迟分位数列存在（由近期测速采样计算）
+        Self::add_column_if_missing(conn, "provider_endpoints", "latency_p50_ms", "INTEGER")?;
+        Self::add_column_if_missing(conn, "provider_endpoints", "latency_p95_ms", "INTEGER")?;
+
+        // 确保稳定性统计列存在（抖动、状态切换次数与评分）
+        Self::add_column_if_missing(conn, "provider_endpoints", "jitter_ms", "INTEGER")?;
+        Self::add_column_if_missing(
+            conn,
+            "provider_endpoints",
+            "recent_flaps",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Self::add_column_if_missing(conn, "provider_endpoints", "stability_score", "INTEGER")?;
+
+        // 确保 region 列存在（端点所在地区标签，用于按地区顺序路由）
+        Self::add_column_if_missing(conn, "provider_endpoints", "region", "TEXT")?;
+
         // 确保 in_failover_queue 列存在（对于已存在的 v2 数据库）
         Self::add_column_if_missing(
             conn,
@@ -291,6 +500,12 @@ impl Database {
             "BOOLEAN NOT NULL DEFAULT 0",
         )?;
 
+        // 确保 last_used_at 列存在（记录供应商最近一次被设为当前的时间）
+        Self::add_column_if_missing(conn, "providers", "last_used_at", "INTEGER")?;
+
+        // 确保 weight 列存在（故障转移队列的负载均衡权重）
+        Self::add_column_if_missing(conn, "providers", "weight", "INTEGER")?;
+
         // 删除旧的 failover_queue 表（如果存在）
         let _ = conn.execute("DROP INDEX IF EXISTS idx_failover_queue_order", []);
         let _ = conn.execute("DROP TABLE IF EXISTS failover_queue", []);
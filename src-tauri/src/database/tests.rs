@@ -5,6 +5,21 @@
 use super::*;
 use crate::app_config::MultiAppConfig;
 use crate::provider::{Provider, ProviderManager};
+use crate::proxy::{
+    cors::CorsConfig,
+    dns::{parse_dns_overrides, DnsConfig},
+    hedging::HedgingConfig,
+    outlier::OutlierDetectionConfig,
+    response_cache::ResponseCacheConfig,
+    shadow::ShadowConfig,
+    size_limits::SizeLimitConfig,
+    switch_back::{SwitchBackConfig, SwitchBackMode},
+    tls::ProxyTlsConfig,
+    unix_socket::UnixSocketConfig,
+    webhook::{parse_webhook_targets, WebhookConfig, WebhookEventKind},
+    ClientRateLimit, HybridModeConfig, LoadBalanceStrategy, RequestCapture, RequestLogEntry,
+    RequestLogFilter,
+};
 use indexmap::IndexMap;
 use rusqlite::{params, Connection};
 use serde_json::json;
@@ -477,6 +492,8 @@ fn dry_run_validates_schema_compatibility() {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         },
     );
 
@@ -564,3 +581,929 @@ fn model_pricing_is_seeded_on_init() {
         gemini_count
     );
 }
+
+fn request_log_entry(app_type: &str, provider_name: &str, status: Option<u16>) -> RequestLogEntry {
+    RequestLogEntry {
+        timestamp: 1,
+        app_type: app_type.to_string(),
+        provider_id: provider_name.to_lowercase(),
+        provider_name: provider_name.to_string(),
+        url: "https://example.com/v1/messages".to_string(),
+        status,
+        latency_ms: 120,
+        queued_ms: 3,
+        error: status.is_none().then(|| "connect error".to_string()),
+        request_headers: vec![("x-api-key".to_string(), "[redacted]".to_string())],
+        response_headers: Vec::new(),
+        capture: None,
+    }
+}
+
+#[test]
+fn request_log_keeps_latest_entries_and_filters() {
+    let db = Database::memory().expect("create memory db");
+    db.set_request_history_limit(3).expect("set limit");
+
+    db.insert_request_log(&request_log_entry("claude", "Relay A", Some(200)))
+        .unwrap();
+    db.insert_request_log(&request_log_entry("claude", "Relay B", Some(429)))
+        .unwrap();
+    db.insert_request_log(&request_log_entry("codex", "Relay A", Some(502)))
+        .unwrap();
+    db.insert_request_log(&request_log_entry("claude", "relay a", None))
+        .unwrap();
+
+    let (all, total) = db
+        .query_request_logs(&RequestLogFilter::default(), 0, 10)
+        .unwrap();
+    assert_eq!(total, 3, "oldest entry should be pruned");
+    assert_eq!(all[0].status, None, "newest entry comes first");
+    assert_eq!(all[0].request_headers[0].1, "[redacted]");
+
+    let filter = RequestLogFilter::parse("app:claude provider:RELAY").unwrap();
+    let (_, total) = db.query_request_logs(&filter, 0, 10).unwrap();
+    assert_eq!(total, 2);
+
+    let (page, total) = db
+        .query_request_logs(&RequestLogFilter::parse("status:5xx").unwrap(), 0, 10)
+        .unwrap();
+    assert_eq!((total, page[0].status), (1, Some(502)));
+
+    let (page, _) = db
+        .query_request_logs(&RequestLogFilter::default(), 2, 10)
+        .unwrap();
+    assert_eq!(page.len(), 1);
+
+    db.set_request_history_limit(0).expect("disable history");
+    db.insert_request_log(&request_log_entry("claude", "Relay A", Some(200)))
+        .unwrap();
+    let (_, total) = db
+        .query_request_logs(&RequestLogFilter::default(), 0, 10)
+        .unwrap();
+    assert_eq!(total, 0);
+}
+
+#[test]
+fn request_captures_keep_latest_bodies() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_request_capture_limit().unwrap(), 0);
+    db.set_request_capture_limit(2).expect("enable capture");
+
+    for i in 0..3 {
+        let mut entry = request_log_entry("claude", "Relay A", Some(200));
+        entry.capture = Some(RequestCapture {
+            endpoint: "/v1/messages".to_string(),
+            headers: vec![("anthropic-version".to_string(), "2023-06-01".to_string())],
+            body: format!(r#"{{"n":{i}}}"#),
+        });
+        db.insert_request_log(&entry).unwrap();
+    }
+
+    let (entries, total) = db
+        .query_request_logs(&RequestLogFilter::default(), 0, 10)
+        .unwrap();
+    assert_eq!(total, 3, "history is kept even when the body is pruned");
+    let bodies: Vec<_> = entries
+        .iter()
+        .map(|e| e.capture.as_ref().map(|c| c.body.as_str()))
+        .collect();
+    assert_eq!(bodies, vec![Some(r#"{"n":2}"#), Some(r#"{"n":1}"#), None]);
+    assert_eq!(
+        entries[0].capture.as_ref().unwrap().headers[0].0,
+        "anthropic-version"
+    );
+
+    db.set_request_capture_limit(0).expect("disable capture");
+    let (entries, _) = db
+        .query_request_logs(&RequestLogFilter::default(), 0, 10)
+        .unwrap();
+    assert!(entries.iter().all(|e| e.capture.is_none()));
+}
+
+#[test]
+fn hybrid_mode_config_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    // 混合模式列由 v3 -> v4 迁移添加
+    db.apply_schema_migrations().expect("apply migrations");
+    let config = HybridModeConfig {
+        enabled: false,
+        latency_test_interval: 60,
+        url_circuit_failure_threshold: 5,
+        speedtest_concurrency: 2,
+        speedtest_timeout_secs: 20,
+        speedtest_samples: 3,
+    };
+    db.update_hybrid_mode_config("claude", &config)
+        .expect("update hybrid config");
+
+    assert_eq!(db.get_hybrid_mode_config("claude").unwrap(), config);
+    assert_ne!(
+        db.get_hybrid_mode_config("codex").unwrap(),
+        config,
+        "other apps keep their own config"
+    );
+}
+
+#[test]
+fn load_balance_strategy_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_load_balance_strategy("claude").unwrap(),
+        LoadBalanceStrategy::Failover
+    );
+
+    db.set_load_balance_strategy("claude", LoadBalanceStrategy::LeastLatency)
+        .expect("set strategy");
+    assert_eq!(
+        db.get_load_balance_strategy("claude").unwrap(),
+        LoadBalanceStrategy::LeastLatency
+    );
+    assert_eq!(
+        db.get_load_balance_strategy("codex").unwrap(),
+        LoadBalanceStrategy::Failover
+    );
+}
+
+#[test]
+fn client_rate_limit_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert!(db.get_client_rate_limit("claude").unwrap().is_unlimited());
+
+    let limit = ClientRateLimit {
+        requests_per_minute: 60,
+        max_concurrent: 4,
+    };
+    db.set_client_rate_limit("claude", limit)
+        .expect("set rate limit");
+    assert_eq!(db.get_client_rate_limit("claude").unwrap(), limit);
+    assert!(db.get_client_rate_limit("gemini").unwrap().is_unlimited());
+}
+
+#[test]
+fn stream_failover_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert!(!db.get_stream_failover("codex").unwrap());
+
+    db.set_stream_failover("codex", true)
+        .expect("enable stream failover");
+    assert!(db.get_stream_failover("codex").unwrap());
+    assert!(!db.get_stream_failover("claude").unwrap());
+}
+
+#[test]
+fn switch_back_config_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_switch_back_config("claude").unwrap(),
+        SwitchBackConfig::default()
+    );
+
+    let config = SwitchBackConfig {
+        mode: SwitchBackMode::Auto,
+        healthy_window_seconds: 120,
+    };
+    db.set_switch_back_config("claude", &config)
+        .expect("set switch-back config");
+    assert_eq!(db.get_switch_back_config("claude").unwrap(), config);
+    assert_eq!(
+        db.get_switch_back_config("codex").unwrap().mode,
+        SwitchBackMode::Manual
+    );
+}
+
+#[test]
+fn shadow_config_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_shadow_config("claude").unwrap(),
+        ShadowConfig::default()
+    );
+
+    let config = ShadowConfig {
+        provider_id: Some("new-relay".to_string()),
+        percent: 15,
+    };
+    db.set_shadow_config("claude", &config)
+        .expect("set shadow config");
+    assert_eq!(db.get_shadow_config("claude").unwrap(), config);
+    assert!(!db.get_shadow_config("codex").unwrap().is_active());
+
+    db.set_shadow_config("claude", &ShadowConfig::default())
+        .expect("clear shadow config");
+    assert_eq!(
+        db.get_shadow_config("claude").unwrap(),
+        ShadowConfig::default()
+    );
+}
+
+#[test]
+fn dedicated_port_is_stored_per_app() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_dedicated_port("gemini").unwrap(), None);
+
+    db.set_dedicated_port("gemini", Some(15731))
+        .expect("set dedicated port");
+    assert_eq!(db.get_dedicated_port("gemini").unwrap(), Some(15731));
+    assert_eq!(db.get_dedicated_port("claude").unwrap(), None);
+
+    db.set_dedicated_port("gemini", None)
+        .expect("clear dedicated port");
+    assert_eq!(db.get_dedicated_port("gemini").unwrap(), None);
+}
+
+#[test]
+fn proxy_tls_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_tls_config().unwrap(),
+        ProxyTlsConfig::default()
+    );
+
+    let config = ProxyTlsConfig {
+        enabled: true,
+        cert_path: Some("/etc/proxy/cert.pem".to_string()),
+        key_path: Some("/etc/proxy/key.pem".to_string()),
+    };
+    db.set_proxy_tls_config(&config).expect("save tls config");
+    assert_eq!(db.get_proxy_tls_config().unwrap(), config);
+
+    // 清空路径后回到自签名证书
+    db.set_proxy_tls_config(&ProxyTlsConfig {
+        enabled: true,
+        ..Default::default()
+    })
+    .expect("save tls config");
+    assert!(db.get_proxy_tls_config().unwrap().uses_self_signed());
+}
+
+#[test]
+fn proxy_auth_token_can_be_set_and_cleared() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_proxy_auth_token().unwrap(), None);
+
+    db.set_proxy_auth_token(Some("ccs-proxy-abc"))
+        .expect("save token");
+    assert_eq!(
+        db.get_proxy_auth_token().unwrap().as_deref(),
+        Some("ccs-proxy-abc")
+    );
+
+    db.set_proxy_auth_token(None).expect("clear token");
+    assert_eq!(db.get_proxy_auth_token().unwrap(), None);
+}
+
+#[test]
+fn proxy_cors_config_defaults_to_permissive_and_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_proxy_cors_config().unwrap(), CorsConfig::default());
+
+    let config = CorsConfig {
+        enabled: true,
+        allowed_origins: vec!["http://localhost:5173".to_string()],
+        allowed_methods: vec!["POST".to_string(), "OPTIONS".to_string()],
+        allowed_headers: vec!["content-type".to_string(), "x-api-key".to_string()],
+    };
+    db.set_proxy_cors_config(&config).expect("save cors config");
+    assert_eq!(db.get_proxy_cors_config().unwrap(), config);
+
+    let disabled = CorsConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    db.set_proxy_cors_config(&disabled)
+        .expect("save cors config");
+    assert_eq!(db.get_proxy_cors_config().unwrap(), disabled);
+}
+
+#[test]
+fn proxy_unix_socket_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    let config = db.get_proxy_unix_socket_config().unwrap();
+    assert_eq!(config, UnixSocketConfig::default());
+    assert!(config.listens_on_tcp());
+
+    let config = UnixSocketConfig {
+        enabled: true,
+        path: Some("/run/user/1000/cc-switch.sock".to_string()),
+        socket_only: true,
+    };
+    db.set_proxy_unix_socket_config(&config)
+        .expect("save unix socket config");
+    assert_eq!(db.get_proxy_unix_socket_config().unwrap(), config);
+    assert!(!config.listens_on_tcp());
+
+    db.set_proxy_unix_socket_config(&UnixSocketConfig {
+        path: None,
+        ..config
+    })
+    .expect("save unix socket config");
+    assert_eq!(db.get_proxy_unix_socket_config().unwrap().path, None);
+}
+
+#[test]
+fn proxy_outlier_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_outlier_config().unwrap(),
+        OutlierDetectionConfig::default()
+    );
+
+    let config = OutlierDetectionConfig {
+        enabled: false,
+        min_requests: 5,
+        error_rate_margin: 0.25,
+        latency_factor: 2.5,
+        base_ejection_seconds: 60,
+        max_ejection_percent: 34,
+    };
+    let version = db.config_version();
+    db.set_proxy_outlier_config(&config)
+        .expect("save outlier config");
+    assert_eq!(db.get_proxy_outlier_config().unwrap(), config);
+    assert_ne!(db.config_version(), version);
+}
+
+#[test]
+fn proxy_hedging_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_hedging_config().unwrap(),
+        HedgingConfig::default()
+    );
+
+    let config = HedgingConfig {
+        enabled: true,
+        percentile: 90,
+        min_delay_ms: 1500,
+        min_samples: 50,
+    };
+    db.set_proxy_hedging_config(&config)
+        .expect("save hedging config");
+    assert_eq!(db.get_proxy_hedging_config().unwrap(), config);
+}
+
+#[test]
+fn proxy_response_cache_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_response_cache_config().unwrap(),
+        ResponseCacheConfig::default()
+    );
+
+    let config = ResponseCacheConfig {
+        enabled: true,
+        ttl_seconds: 120,
+        max_entries: 64,
+        max_size_kb: 4096,
+    };
+    db.set_proxy_response_cache_config(&config)
+        .expect("save response cache config");
+    assert_eq!(db.get_proxy_response_cache_config().unwrap(), config);
+}
+
+#[test]
+fn proxy_upstream_url_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_proxy_upstream_url().unwrap(), None);
+
+    db.set_proxy_upstream_url(Some("socks5h://127.0.0.1:1080"))
+        .expect("save upstream proxy");
+    assert_eq!(
+        db.get_proxy_upstream_url().unwrap().as_deref(),
+        Some("socks5h://127.0.0.1:1080")
+    );
+
+    db.set_proxy_upstream_url(None)
+        .expect("clear upstream proxy");
+    assert_eq!(db.get_proxy_upstream_url().unwrap(), None);
+}
+
+#[test]
+fn proxy_dns_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(db.get_proxy_dns_config().unwrap(), DnsConfig::default());
+
+    let config = DnsConfig {
+        cache_enabled: true,
+        cache_ttl_seconds: 300,
+        overrides: parse_dns_overrides("api.relay.dev=203.0.113.7, api.relay.dev=203.0.113.8")
+            .unwrap(),
+    };
+    db.set_proxy_dns_config(&config).expect("save dns config");
+    assert_eq!(db.get_proxy_dns_config().unwrap(), config);
+}
+
+#[test]
+fn proxy_size_limits_round_trip() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_size_limits().unwrap(),
+        SizeLimitConfig::default()
+    );
+
+    let config = SizeLimitConfig {
+        max_request_kb: 0,
+        max_response_kb: 512,
+    };
+    db.set_proxy_size_limits(&config).expect("save size limits");
+    assert_eq!(db.get_proxy_size_limits().unwrap(), config);
+}
+
+#[test]
+fn proxy_webhook_config_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert_eq!(
+        db.get_proxy_webhook_config().unwrap(),
+        WebhookConfig::default()
+    );
+
+    let config = WebhookConfig {
+        targets: parse_webhook_targets(
+            "https://hooks.slack.com/services/T0/B0/x, json=https://oncall.dev/hook?k=v",
+        )
+        .unwrap(),
+        events: vec![WebhookEventKind::CircuitOpen],
+    };
+    db.set_proxy_webhook_config(&config).expect("save webhooks");
+    assert_eq!(db.get_proxy_webhook_config().unwrap(), config);
+
+    let muted = WebhookConfig {
+        events: Vec::new(),
+        ..config
+    };
+    db.set_proxy_webhook_config(&muted).expect("mute webhooks");
+    assert!(db.get_proxy_webhook_config().unwrap().events.is_empty());
+}
+
+#[test]
+fn failover_desktop_notifications_default_on() {
+    let db = Database::memory().expect("create memory db");
+    assert!(db.get_failover_desktop_notifications().unwrap());
+    db.set_failover_desktop_notifications(false)
+        .expect("disable notifications");
+    assert!(!db.get_failover_desktop_notifications().unwrap());
+}
+
+#[test]
+fn failover_events_are_listed_newest_first_and_pruned() {
+    let db = Database::memory().expect("create memory db");
+    let event = |n: i64| FailoverEvent {
+        created_at: 1_700_000_000 + n,
+        app_type: "claude".to_string(),
+        from_provider_id: (n > 0).then(|| format!("p{}", n - 1)),
+        from_provider_name: (n > 0).then(|| format!("P{}", n - 1)),
+        to_provider_id: format!("p{n}"),
+        to_provider_name: format!("P{n}"),
+        error: Some(format!("upstream error {n}")),
+    };
+    db.record_failover_event(&event(0)).expect("record event");
+    db.record_failover_event(&event(1)).expect("record event");
+
+    let events = db.get_failover_events(10).expect("load events");
+    assert_eq!(events, vec![event(1), event(0)]);
+    assert_eq!(db.get_failover_events(1).unwrap(), vec![event(1)]);
+
+    // 只保留最近 500 条
+    for n in 2..=505 {
+        db.record_failover_event(&event(n)).expect("record event");
+    }
+    let events = db.get_failover_events(1000).expect("load events");
+    assert_eq!(events.len(), 500);
+    assert_eq!(events.last().unwrap().to_provider_id, "p6");
+}
+
+#[test]
+fn proxy_ip_allowlist_round_trips() {
+    let db = Database::memory().expect("create memory db");
+    assert!(db.get_proxy_ip_allowlist().unwrap().is_empty());
+
+    let entries = vec!["192.168.1.0/24".to_string(), "172.17.0.0/16".to_string()];
+    db.set_proxy_ip_allowlist(&entries).expect("save allowlist");
+    assert_eq!(db.get_proxy_ip_allowlist().unwrap(), entries);
+
+    db.set_proxy_ip_allowlist(&[]).expect("clear allowlist");
+    assert!(db.get_proxy_ip_allowlist().unwrap().is_empty());
+}
+
+#[test]
+fn provider_weight_is_stored_and_kept_on_edit() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id(
+        "relay".to_string(),
+        "Relay".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.add_to_failover_queue("claude", "relay")
+        .expect("add to queue");
+    db.set_provider_weight("claude", "relay", Some(80))
+        .expect("set weight");
+
+    let queue = db.get_failover_queue("claude").expect("load queue");
+    assert_eq!(queue[0].weight, Some(80));
+
+    // 编辑供应商不应覆盖权重
+    db.save_provider("claude", &provider)
+        .expect("update provider");
+    let saved = db
+        .get_provider_by_id("relay", "claude")
+        .expect("get provider")
+        .expect("provider exists");
+    assert_eq!(saved.weight, Some(80));
+
+    db.set_provider_weight("claude", "relay", None)
+        .expect("clear weight");
+    assert_eq!(db.get_failover_providers("claude").unwrap()[0].weight, None);
+}
+
+#[test]
+fn set_current_provider_records_last_used_at() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id(
+        "p1".to_string(),
+        "P1".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    let before = db
+        .get_provider_by_id("p1", "claude")
+        .expect("get provider")
+        .expect("provider exists");
+    assert_eq!(before.last_used_at, None);
+
+    db.set_current_provider("claude", "p1")
+        .expect("set current provider");
+    let after = db
+        .get_provider_by_id("p1", "claude")
+        .expect("get provider")
+        .expect("provider exists");
+    assert!(after.last_used_at.is_some());
+
+    // 编辑供应商不应清除最近使用时间
+    db.save_provider("claude", &provider)
+        .expect("update provider");
+    let providers = db.get_all_providers("claude").expect("list providers");
+    assert_eq!(providers["p1"].last_used_at, after.last_used_at);
+}
+
+#[test]
+fn endpoint_latency_history_is_recorded_and_pruned() {
+    let db = Database::memory().expect("create memory db");
+    db.record_endpoint_latency("claude", "p1", "https://a.example", Some(120), true)
+        .expect("record sample");
+    db.record_endpoint_latency("claude", "p1", "https://b.example", None, false)
+        .expect("record failed sample");
+    db.record_endpoint_latency("claude", "p2", "https://a.example", Some(80), true)
+        .expect("record other provider");
+
+    let samples = db
+        .get_endpoint_latency_history("claude", "p1", 0)
+        .expect("load history");
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].url, "https://a.example");
+    assert_eq!(samples[0].latency_ms, Some(120));
+    assert!(!samples[1].is_healthy);
+    assert_eq!(samples[1].latency_ms, None);
+
+    // 超过 24 小时的采样在下次写入时被清理
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "UPDATE endpoint_latency_history SET tested_at = tested_at - 2 * 86400 WHERE provider_id = 'p2'",
+            [],
+        )
+        .unwrap();
+    }
+    db.record_endpoint_latency("claude", "p1", "https://a.example", Some(100), true)
+        .expect("record sample");
+    assert!(db
+        .get_endpoint_latency_history("claude", "p2", 0)
+        .unwrap()
+        .is_empty());
+
+    // 删除端点时一并删除其历史
+    let provider = Provider::with_id(
+        "p1".to_string(),
+        "P1".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.add_custom_endpoint("claude", "p1", "https://b.example")
+        .expect("add endpoint");
+    db.remove_custom_endpoint("claude", "p1", "https://b.example")
+        .expect("remove endpoint");
+    let urls: Vec<String> = db
+        .get_endpoint_latency_history("claude", "p1", 0)
+        .unwrap()
+        .into_iter()
+        .map(|s| s.url)
+        .collect();
+    assert_eq!(urls, vec!["https://a.example", "https://a.example"]);
+}
+
+#[test]
+fn endpoint_percentiles_follow_recent_healthy_samples() {
+    let db = Database::memory().expect("create memory db");
+    db.apply_schema_migrations().expect("apply migrations");
+    let provider = Provider::with_id(
+        "p1".to_string(),
+        "P1".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.add_custom_endpoint("claude", "p1", "https://a.example")
+        .expect("add endpoint");
+    assert_eq!(
+        db.refresh_endpoint_percentiles("claude", "p1", "https://a.example")
+            .unwrap(),
+        (None, None)
+    );
+
+    for ms in [100, 300, 200, 1000] {
+        db.record_endpoint_latency("claude", "p1", "https://a.example", Some(ms), true)
+            .expect("record sample");
+    }
+    // 失败采样不参与分位数计算
+    db.record_endpoint_latency("claude", "p1", "https://a.example", None, false)
+        .expect("record failed sample");
+    assert_eq!(
+        db.refresh_endpoint_percentiles("claude", "p1", "https://a.example")
+            .unwrap(),
+        (Some(200), Some(1000))
+    );
+    let endpoint = &db
+        .get_provider_endpoints_with_health("claude", "p1")
+        .unwrap()[0];
+    assert_eq!(endpoint.latency_p50_ms, Some(200));
+    assert_eq!(endpoint.latency_p95_ms, Some(1000));
+}
+
+#[test]
+fn endpoint_latency_is_smoothed_across_tests_and_requests() {
+    let db = Database::memory().expect("create memory db");
+    db.apply_schema_migrations().expect("apply migrations");
+    let provider = Provider::with_id(
+        "p1".to_string(),
+        "P1".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.add_custom_endpoint("claude", "p1", "https://a.example")
+        .expect("add endpoint");
+    let latency = |db: &Database| {
+        db.get_provider_endpoints_with_health("claude", "p1")
+            .unwrap()[0]
+            .latency_ms
+    };
+
+    db.update_endpoint_health("claude", "p1", "https://a.example", Some(100), true, 0)
+        .expect("first test");
+    assert_eq!(latency(&db), Some(100));
+    // 单次尖峰只按平滑系数拉高延迟
+    db.update_endpoint_health("claude", "p1", "https://a.example", Some(1100), true, 0)
+        .expect("spike");
+    assert_eq!(latency(&db), Some(400));
+    db.record_endpoint_request_latency("claude", "p1", "https://a.example", 100)
+        .expect("live request");
+    assert_eq!(latency(&db), Some(310));
+    // 测速失败时清空，恢复后重新开始平滑
+    db.update_endpoint_health("claude", "p1", "https://a.example", None, false, 1)
+        .expect("failed test");
+    assert_eq!(latency(&db), None);
+}
+
+#[test]
+fn pinned_endpoint_stays_primary_until_another_is_chosen() {
+    let db = Database::memory().expect("create memory db");
+    db.apply_schema_migrations().expect("apply migrations");
+    let provider = Provider::with_id(
+        "p1".to_string(),
+        "P1".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    for url in ["https://a.example", "https://b.example"] {
+        db.add_custom_endpoint("claude", "p1", url)
+            .expect("add endpoint");
+    }
+    let flags = |db: &Database| -> Vec<(String, bool, bool)> {
+        let mut endpoints = db
+            .get_provider_endpoints_with_health("claude", "p1")
+            .expect("load endpoints");
+        endpoints.sort_by(|a, b| a.url.cmp(&b.url));
+        endpoints
+            .into_iter()
+            .map(|e| (e.url, e.is_primary, e.is_pinned))
+            .collect()
+    };
+
+    db.set_endpoint_pinned("claude", "p1", "https://b.example", true)
+        .expect("pin endpoint");
+    assert_eq!(
+        flags(&db),
+        vec![
+            ("https://a.example".to_string(), false, false),
+            ("https://b.example".to_string(), true, true),
+        ]
+    );
+
+    // 重新设置同一个主端点不影响固定，改选其他端点则取消固定
+    db.set_primary_endpoint("claude", "p1", "https://b.example")
+        .expect("set primary");
+    assert!(flags(&db)[1].2);
+    db.set_primary_endpoint("claude", "p1", "https://a.example")
+        .expect("set primary");
+    assert_eq!(
+        flags(&db),
+        vec![
+            ("https://a.example".to_string(), true, false),
+            ("https://b.example".to_string(), false, false),
+        ]
+    );
+
+    db.set_endpoint_pinned("claude", "p1", "https://a.example", true)
+        .expect("pin endpoint");
+    db.set_endpoint_pinned("claude", "p1", "https://a.example", false)
+        .expect("unpin endpoint");
+    assert_eq!(
+        flags(&db)[0],
+        ("https://a.example".to_string(), true, false)
+    );
+}
+
+#[test]
+fn database_file_state_reads_sqlite_header() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("cc-switch.db");
+    assert_eq!(
+        database_file_state(&path).unwrap(),
+        DatabaseFileState::Missing
+    );
+
+    Connection::open(&path)
+        .and_then(|conn| conn.execute_batch("CREATE TABLE t (id INTEGER);"))
+        .expect("create plaintext db");
+    assert_eq!(
+        database_file_state(&path).unwrap(),
+        DatabaseFileState::Plaintext
+    );
+
+    std::fs::write(&path, [0x5a; 64]).expect("write random header");
+    assert_eq!(
+        database_file_state(&path).unwrap(),
+        DatabaseFileState::Encrypted
+    );
+}
+
+#[cfg(feature = "sqlcipher")]
+#[test]
+fn encrypt_file_migrates_plaintext_database() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("cc-switch.db");
+    {
+        let conn = Connection::open(&path).expect("open plaintext db");
+        conn.execute_batch(
+            "CREATE TABLE t (name TEXT); INSERT INTO t VALUES ('kept'); PRAGMA user_version = 7;",
+        )
+        .expect("seed plaintext db");
+    }
+
+    Database::encrypt_file(&path, "secret").expect("encrypt");
+    assert_eq!(
+        database_file_state(&path).unwrap(),
+        DatabaseFileState::Encrypted
+    );
+
+    let wrong = Connection::open(&path).expect("open encrypted db");
+    assert!(encryption::apply_key(&wrong, "wrong").is_err());
+
+    let conn = Connection::open(&path).expect("open encrypted db");
+    encryption::apply_key(&conn, "secret").expect("correct passphrase");
+    let name: String = conn
+        .query_row("SELECT name FROM t", [], |row| row.get(0))
+        .expect("query migrated row");
+    assert_eq!(name, "kept");
+    let version: i32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .expect("user_version");
+    assert_eq!(version, 7);
+
+    // 加密库的内存快照（SQL 导出等使用）仍可读取
+    let db = Database {
+        conn: Mutex::new(conn),
+        config_version: AtomicU64::new(0),
+        passphrase: Some("secret".to_string()),
+    };
+    let snapshot = db.snapshot_to_memory().expect("snapshot");
+    let count: i64 = snapshot
+        .query_row("SELECT count(*) FROM t", [], |row| row.get(0))
+        .expect("query snapshot");
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn auto_backup_config_round_trips() {
+    let db = Database::memory().expect("memory db");
+    assert_eq!(
+        db.get_auto_backup_config().unwrap(),
+        AutoBackupConfig::default()
+    );
+
+    let config = AutoBackupConfig {
+        enabled: true,
+        interval_hours: 6,
+        retain: 3,
+    };
+    db.set_auto_backup_config(config).unwrap();
+    assert_eq!(db.get_auto_backup_config().unwrap(), config);
+}
+
+#[test]
+fn auto_backups_are_compressed_and_rotated() {
+    let db = Database::memory().expect("memory db");
+    let dir = tempfile::tempdir().expect("tempdir");
+    // 导入前的快照备份不参与定时备份的轮转
+    std::fs::write(dir.path().join("db_backup_20240101_000000.db"), b"").unwrap();
+
+    let mut created = Vec::new();
+    for _ in 0..3 {
+        created.push(db.create_auto_backup_in(dir.path(), 2).expect("backup"));
+    }
+    assert!(!created[0].exists(), "oldest auto backup is pruned");
+    assert!(created[1].exists() && created[2].exists());
+
+    let mut archive =
+        zip::ZipArchive::new(std::fs::File::open(&created[2]).unwrap()).expect("zip archive");
+    let mut entry = archive.by_name("cc-switch.db").expect("db entry");
+    let mut header = [0u8; 16];
+    std::io::Read::read_exact(&mut entry, &mut header).unwrap();
+    assert_eq!(&header, b"SQLite format 3\0");
+
+    let backups = Database::list_backups_in(dir.path()).unwrap();
+    assert_eq!(backups.len(), 3);
+    assert_eq!(backups.iter().filter(|b| b.automatic).count(), 2);
+}
+
+#[test]
+fn maintenance_tasks_report_results() {
+    let db = Database::memory().expect("memory db");
+    {
+        let conn = db.conn.lock().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE bulk (payload TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+             INSERT INTO bulk SELECT hex(randomblob(200)) FROM n;
+             DELETE FROM bulk;",
+        )
+        .unwrap();
+    }
+
+    let stats = db.database_stats().unwrap();
+    assert!(stats.freelist_count > 0, "deleted rows leave free pages");
+    assert_eq!(stats.tables.iter().find(|(n, _)| n == "bulk").unwrap().1, 0);
+
+    assert_eq!(
+        db.run_maintenance(MaintenanceTask::IntegrityCheck).unwrap(),
+        MaintenanceOutcome::IntegrityCheck { problems: vec![] }
+    );
+    assert_eq!(
+        db.run_maintenance(MaintenanceTask::WalCheckpoint).unwrap(),
+        MaintenanceOutcome::WalCheckpoint {
+            checkpointed_pages: None
+        }
+    );
+    match db.run_maintenance(MaintenanceTask::Vacuum).unwrap() {
+        MaintenanceOutcome::Vacuum {
+            size_before_bytes,
+            size_after_bytes,
+        } => assert!(size_after_bytes < size_before_bytes),
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+    assert_eq!(db.database_stats().unwrap().freelist_count, 0);
+}
+
+#[test]
+fn file_database_uses_wal_and_checkpoints_it() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db = Database::open_file(&dir.path().join("cc-switch.db"), None).expect("open file db");
+    assert_eq!(db.database_stats().unwrap().journal_mode, "wal");
+    match db.run_maintenance(MaintenanceTask::WalCheckpoint).unwrap() {
+        MaintenanceOutcome::WalCheckpoint {
+            checkpointed_pages: Some(_),
+        } => {}
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+}
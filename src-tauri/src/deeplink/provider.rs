@@ -133,6 +133,8 @@ pub(crate) fn build_provider_from_request(
         icon: request.icon.clone(),
         icon_color: None,
         in_failover_queue: false,
+        last_used_at: None,
+        weight: None,
     };
 
     Ok(provider)
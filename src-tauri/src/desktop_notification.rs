@@ -0,0 +1,29 @@
+//! 系统桌面通知
+//!
+//! 后台故障转移切换了当前供应商时弹出通知，避免长时间不知情地消耗另一个账号的额度。
+//! 需要以 `desktop-notifications` feature 构建（依赖 notify-rust）；未启用时只记录日志。
+
+/// 当前构建是否支持桌面通知
+pub const DESKTOP_NOTIFICATIONS_AVAILABLE: bool = cfg!(feature = "desktop-notifications");
+
+/// 在后台线程弹出通知，不阻塞调用方；失败只记录日志
+pub fn show(summary: &str, body: &str) {
+    #[cfg(feature = "desktop-notifications")]
+    {
+        let summary = summary.to_string();
+        let body = body.to_string();
+        // Linux 下经 D-Bus 同步发送，放到独立线程避免阻塞运行时
+        std::thread::spawn(move || {
+            let result = notify_rust::Notification::new()
+                .appname("cc-switch")
+                .summary(&summary)
+                .body(&body)
+                .show();
+            if let Err(e) = result {
+                log::warn!("桌面通知发送失败: {e}");
+            }
+        });
+    }
+    #[cfg(not(feature = "desktop-notifications"))]
+    log::debug!("未启用桌面通知，跳过: {summary} - {body}");
+}
@@ -310,7 +310,16 @@ fn update_selected_type(selected_type: &str) -> Result<(), AppError> {
         serde_json::json!({})
     };
 
-    // 只更新 security.auth.selectedType 字段
+    apply_selected_type(&mut settings_content, selected_type);
+
+    // 写入文件
+    crate::config::write_json_file(&settings_path, &settings_content)?;
+
+    Ok(())
+}
+
+/// 只更新 settings 中的 security.auth.selectedType 字段，保留其他所有字段
+pub fn apply_selected_type(settings_content: &mut Value, selected_type: &str) {
     if let Some(obj) = settings_content.as_object_mut() {
         let security = obj
             .entry("security")
@@ -329,11 +338,6 @@ fn update_selected_type(selected_type: &str) -> Result<(), AppError> {
             }
         }
     }
-
-    // 写入文件
-    crate::config::write_json_file(&settings_path, &settings_content)?;
-
-    Ok(())
 }
 
 /// 为 Packycode Gemini 供应商写入 settings.json
@@ -11,6 +11,7 @@ mod commands;
 mod config;
 mod database;
 mod deeplink;
+mod desktop_notification;
 mod error;
 mod gemini_config;
 mod gemini_mcp;
@@ -21,6 +22,7 @@ mod prompt_files;
 mod provider;
 mod provider_defaults;
 mod proxy;
+mod redact;
 mod services;
 mod settings;
 mod store;
@@ -33,8 +35,13 @@ pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_l
 #[cfg(feature = "tauri")]
 pub use commands::*;
 pub use config::{get_claude_mcp_path, get_claude_settings_path, read_json_file};
-pub use database::Database;
+pub use database::{
+    database_file_state, AutoBackupConfig, BackupFile, BackupSummary, Database, DatabaseFileState,
+    DatabaseStats, FailoverEvent, MaintenanceOutcome, MaintenanceTask,
+    DATABASE_ENCRYPTION_AVAILABLE, DB_PASSPHRASE_ENV, DEFAULT_REQUEST_HISTORY_LIMIT,
+};
 pub use deeplink::{import_provider_from_deeplink, parse_deeplink_url, DeepLinkImportRequest};
+pub use desktop_notification::DESKTOP_NOTIFICATIONS_AVAILABLE;
 pub use error::AppError;
 pub use mcp::{
     import_from_claude, import_from_codex, import_from_gemini, remove_server_from_claude,
@@ -42,10 +49,43 @@ pub use mcp::{
     sync_enabled_to_codex, sync_enabled_to_gemini, sync_single_server_to_claude,
     sync_single_server_to_codex, sync_single_server_to_gemini,
 };
-pub use provider::{Provider, ProviderMeta};
+pub use prompt::Prompt;
+pub use provider::{BodyRule, HeaderRule, HeaderRuleAction, Provider, ProviderMeta};
+pub use proxy::access_log::AccessLogConfig;
+pub use proxy::circuit_breaker::{CircuitBreakerConfig, CircuitBreakerOverride};
+pub use proxy::cors::CorsConfig;
+pub use proxy::dns::{format_dns_overrides, parse_dns_overrides, DnsCacheStats, DnsConfig};
+pub use proxy::header_rules::{format_header_rules, parse_header_rules};
+pub use proxy::health::{HealthCheckConfig, HealthProbeResult};
+pub use proxy::hedging::HedgingConfig;
+pub use proxy::outlier::{EndpointEjection, OutlierDetectionConfig};
+pub use proxy::replay::ReplayResponse;
+pub use proxy::response_cache::{ResponseCacheConfig, ResponseCacheStats};
+pub use proxy::shadow::{ShadowConfig, ShadowStats};
+pub use proxy::size_limits::{SizeLimitConfig, SizeLimitStats};
+pub use proxy::switch_back::{SwitchBackCandidate, SwitchBackConfig, SwitchBackMode};
+pub use proxy::tls::ProxyTlsConfig;
+pub use proxy::unix_socket::UnixSocketConfig;
+pub use proxy::upstream_proxy::{
+    display_upstream_proxy, parse_upstream_proxy, UPSTREAM_PROXY_DIRECT,
+};
+pub use proxy::url_router::{parse_region_order, EndpointFallback, EndpointOutage, UrlStrategy};
+pub use proxy::webhook::{
+    format_webhook_targets, parse_webhook_targets, WebhookConfig, WebhookEventKind,
+};
+pub use proxy::{
+    ClientRateLimit, EndpointLatencySample, HybridModeConfig, LoadBalanceStrategy,
+    ProviderEndpoint, ProxyStatus, ProxyTakeoverStatus, RateLimitStats, RequestCapture, RequestLog,
+    RequestLogEntry, RequestLogFilter, StatusFilter,
+};
+pub use redact::{mask_secret, redact};
 pub use services::{
-    ConfigService, EndpointLatency, McpService, PromptService, ProviderService, ProxyService,
-    SkillService, SpeedtestService,
+    BackupScheduler, ConfigService, ConflictStrategy, DailyUsage, EndpointLatency,
+    EnvImportService, EnvProviderCandidate, ImportAction, ImportBundle, ImportCategory, ImportItem,
+    ImportStrategies, ImportSummary, LiveFileChange, McpCheckResult, McpCheckService, McpService,
+    ModelPrice, ModelUsage, PromptService, ProviderLimitStatus, ProviderService, ProviderUsage,
+    ProxyService, SkillService, SpeedtestMethod, SpeedtestOptions, SpeedtestProbe,
+    SpeedtestService, UiPreferences, EXPORT_FORMAT_VERSION,
 };
 pub use settings::{update_settings, AppSettings};
 pub use store::AppState;
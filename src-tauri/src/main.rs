@@ -1,30 +1,125 @@
 use std::sync::Arc;
 
-use anyhow::Result;
-use cc_switch_lib::{AppState, AppType, Database, McpService, PromptService, ProviderService};
+use anyhow::{bail, Result};
+use cc_switch_lib::{
+    database_file_state, AppState, AppType, BackupScheduler, Database, DatabaseFileState,
+    McpService, PromptService, ProviderService, DATABASE_ENCRYPTION_AVAILABLE, DB_PASSPHRASE_ENV,
+};
 
 mod tui;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    tui::logging::init();
+
+    let color_override = parse_color_flag(std::env::args().skip(1))?;
+    let encrypt_db = std::env::args().skip(1).any(|arg| arg == "--encrypt-db");
 
     log::info!("Starting CC Switch TUI v4.0.0");
 
-    let db = match Database::init() {
+    let db = match open_database(encrypt_db) {
         Ok(db) => Arc::new(db),
         Err(e) => {
             log::error!("Failed to initialize database: {e}");
-            return Err(e.into());
+            return Err(e);
         }
     };
 
+    let backup_scheduler = Arc::new(BackupScheduler::new(db.clone()));
+    backup_scheduler.start();
+
     let app_state = Arc::new(AppState::new(db));
 
     // 首次运行时自动导入配置
     import_on_first_run(&app_state);
 
-    tui::run(app_state).await
+    tui::run(app_state, color_override).await
+}
+
+/// 打开数据库：已加密时读取口令（环境变量或启动时输入），
+/// 指定 `--encrypt-db` 时把明文数据库迁移为加密数据库
+fn open_database(encrypt_db: bool) -> Result<Database> {
+    let env_passphrase = std::env::var(DB_PASSPHRASE_ENV)
+        .ok()
+        .filter(|p| !p.is_empty());
+    let db_path = Database::file_path();
+    let state = database_file_state(&db_path)?;
+
+    if (encrypt_db || state == DatabaseFileState::Encrypted) && !DATABASE_ENCRYPTION_AVAILABLE {
+        bail!("This build has no database encryption support; rebuild with --features sqlcipher");
+    }
+
+    let db = match state {
+        DatabaseFileState::Encrypted => {
+            let passphrase = match env_passphrase {
+                Some(p) => p,
+                None => tui::read_passphrase("Database passphrase: ")?,
+            };
+            Database::init_with_passphrase(Some(&passphrase))?
+        }
+        DatabaseFileState::Plaintext if encrypt_db => {
+            let passphrase = new_passphrase(env_passphrase)?;
+            Database::encrypt_file(&db_path, &passphrase)?;
+            eprintln!(
+                "Encrypted {}; existing backups in the backups directory remain unencrypted",
+                db_path.display()
+            );
+            Database::init_with_passphrase(Some(&passphrase))?
+        }
+        DatabaseFileState::Plaintext => {
+            if env_passphrase.is_some() {
+                bail!(
+                    "{DB_PASSPHRASE_ENV} is set but the database is not encrypted; \
+                     run with --encrypt-db to encrypt it"
+                );
+            }
+            Database::init()?
+        }
+        DatabaseFileState::Missing if encrypt_db || env_passphrase.is_some() => {
+            let passphrase = new_passphrase(env_passphrase)?;
+            Database::init_with_passphrase(Some(&passphrase))?
+        }
+        DatabaseFileState::Missing => Database::init()?,
+    };
+    Ok(db)
+}
+
+/// 设置新口令：优先使用环境变量，否则要求输入两次
+fn new_passphrase(env_passphrase: Option<String>) -> Result<String> {
+    if let Some(passphrase) = env_passphrase {
+        return Ok(passphrase);
+    }
+    let passphrase = tui::read_passphrase("New database passphrase: ")?;
+    if passphrase.is_empty() {
+        bail!("Database passphrase must not be empty");
+    }
+    if tui::read_passphrase("Confirm passphrase: ")? != passphrase {
+        bail!("Passphrases do not match");
+    }
+    Ok(passphrase)
+}
+
+/// 解析 `--color <truecolor|256|16|none|auto>` 参数，未指定或为 auto 时自动检测
+fn parse_color_flag(mut args: impl Iterator<Item = String>) -> Result<Option<tui::ColorSupport>> {
+    let mut value = None;
+    while let Some(arg) = args.next() {
+        if let Some(v) = arg.strip_prefix("--color=") {
+            value = Some(v.to_string());
+        } else if arg == "--color" {
+            match args.next() {
+                Some(v) => value = Some(v),
+                None => bail!("--color requires a value (truecolor, 256, 16, none, auto)"),
+            }
+        }
+    }
+
+    match value.as_deref() {
+        None | Some("auto") => Ok(None),
+        Some(v) => match tui::ColorSupport::from_name(v) {
+            Some(support) => Ok(Some(support)),
+            None => bail!("Invalid --color value '{v}' (expected truecolor, 256, 16, none, auto)"),
+        },
+    }
 }
 
 /// 首次运行时从 Live 配置导入数据
@@ -144,3 +144,21 @@ pub fn remove_server_from_claude(id: &str) -> Result<(), AppError> {
     // 写回
     crate::claude_mcp::set_mcp_servers_map(&current)
 }
+
+/// 批量更新 Claude live 配置：写入 `servers` 并移除 `removed`，只读写一次文件
+pub fn apply_servers_to_claude(
+    servers: &[(String, Value)],
+    removed: &[String],
+) -> Result<(), AppError> {
+    if !should_sync_claude_mcp() {
+        return Ok(());
+    }
+    let mut current = crate::claude_mcp::read_mcp_servers_map()?;
+    for (id, spec) in servers {
+        current.insert(id.clone(), spec.clone());
+    }
+    for id in removed {
+        current.remove(id);
+    }
+    crate::claude_mcp::set_mcp_servers_map(&current)
+}
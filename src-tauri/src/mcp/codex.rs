@@ -444,6 +444,55 @@ pub fn remove_server_from_codex(id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// 批量更新 Codex live 配置：写入 `servers` 并移除 `removed`，只读写一次 config.toml
+/// 现有 config.toml 语法无效时报错，不覆盖文件
+pub fn apply_servers_to_codex(
+    servers: &[(String, Value)],
+    removed: &[String],
+) -> Result<(), AppError> {
+    if !should_sync_codex_mcp() {
+        return Ok(());
+    }
+    use toml_edit::{Item, Table};
+
+    let base_text = crate::codex_config::read_and_validate_codex_config_text()?;
+    let mut doc = if base_text.trim().is_empty() {
+        toml_edit::DocumentMut::default()
+    } else {
+        base_text
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| AppError::McpValidation(format!("解析 config.toml 失败: {e}")))?
+    };
+
+    // 清理可能存在的错误格式 [mcp.servers]
+    if let Some(mcp_item) = doc.get_mut("mcp") {
+        if let Some(tbl) = mcp_item.as_table_like_mut() {
+            if tbl.contains_key("servers") {
+                log::warn!("检测到错误的 MCP 格式 [mcp.servers]，正在清理并迁移到 [mcp_servers]");
+                tbl.remove("servers");
+            }
+        }
+    }
+
+    if !doc.contains_key("mcp_servers") {
+        doc["mcp_servers"] = Item::Table(Table::new());
+    }
+    for (id, spec) in servers {
+        doc["mcp_servers"][id.as_str()] = Item::Table(json_server_to_toml_table(spec)?);
+    }
+    if let Some(mcp_servers) = doc.get_mut("mcp_servers").and_then(|s| s.as_table_mut()) {
+        for id in removed {
+            mcp_servers.remove(id);
+        }
+        if mcp_servers.is_empty() {
+            doc.as_table_mut().remove("mcp_servers");
+        }
+    }
+
+    let path = crate::codex_config::get_codex_config_path();
+    crate::config::write_text_file(&path, &doc.to_string())
+}
+
 // ============================================================================
 // TOML 转换辅助函数
 // ============================================================================
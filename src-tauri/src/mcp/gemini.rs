@@ -139,3 +139,21 @@ pub fn remove_server_from_gemini(id: &str) -> Result<(), AppError> {
     // 写回
     crate::gemini_mcp::set_mcp_servers_map(&current)
 }
+
+/// 批量更新 Gemini live 配置：写入 `servers` 并移除 `removed`，只读写一次文件
+pub fn apply_servers_to_gemini(
+    servers: &[(String, Value)],
+    removed: &[String],
+) -> Result<(), AppError> {
+    if !should_sync_gemini_mcp() {
+        return Ok(());
+    }
+    let mut current = crate::gemini_mcp::read_mcp_servers_map()?;
+    for (id, spec) in servers {
+        current.insert(id.clone(), spec.clone());
+    }
+    for id in removed {
+        current.remove(id);
+    }
+    crate::gemini_mcp::set_mcp_servers_map(&current)
+}
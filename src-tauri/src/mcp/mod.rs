@@ -14,15 +14,18 @@ mod codex;
 mod gemini;
 mod validation;
 
+pub(crate) use validation::validate_server_spec;
+
 // 重新导出公共 API
 pub use claude::{
-    import_from_claude, remove_server_from_claude, sync_enabled_to_claude,
+    apply_servers_to_claude, import_from_claude, remove_server_from_claude, sync_enabled_to_claude,
     sync_single_server_to_claude,
 };
 pub use codex::{
-    import_from_codex, remove_server_from_codex, sync_enabled_to_codex, sync_single_server_to_codex,
+    apply_servers_to_codex, import_from_codex, remove_server_from_codex, sync_enabled_to_codex,
+    sync_single_server_to_codex,
 };
 pub use gemini::{
-    import_from_gemini, remove_server_from_gemini, sync_enabled_to_gemini,
+    apply_servers_to_gemini, import_from_gemini, remove_server_from_gemini, sync_enabled_to_gemini,
     sync_single_server_to_gemini,
 };
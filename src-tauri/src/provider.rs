@@ -40,6 +40,13 @@ pub struct Provider {
     #[serde(default)]
     #[serde(rename = "inFailoverQueue")]
     pub in_failover_queue: bool,
+    /// 最近一次被设为当前供应商的时间（Unix 秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<i64>,
+    /// 负载均衡权重，仅对故障转移队列中的供应商生效
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
 }
 
 impl Provider {
@@ -63,6 +70,8 @@ impl Provider {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         }
     }
 }
@@ -165,6 +174,89 @@ pub struct ProviderMeta {
     /// 每月消费限额（USD）
     #[serde(rename = "limitMonthlyUsd", skip_serializing_if = "Option::is_none")]
     pub limit_monthly_usd: Option<String>,
+    /// 每日 token 限额（输入 + 输出）
+    #[serde(rename = "limitDailyTokens", skip_serializing_if = "Option::is_none")]
+    pub limit_daily_tokens: Option<u64>,
+    /// 每月 token 限额（输入 + 输出）
+    #[serde(rename = "limitMonthlyTokens", skip_serializing_if = "Option::is_none")]
+    pub limit_monthly_tokens: Option<u64>,
+    /// 超出限额后是否暂停向该供应商路由（否则仅提醒）
+    #[serde(rename = "pauseOnLimit", skip_serializing_if = "Option::is_none")]
+    pub pause_on_limit: Option<bool>,
+    /// 模型别名：客户端请求的模型 ID → 该供应商使用的模型 ID
+    #[serde(
+        rename = "modelAliases",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub model_aliases: HashMap<String, String>,
+    /// 代理转发时按顺序应用的请求头改写规则
+    #[serde(rename = "headerRules", default, skip_serializing_if = "Vec::is_empty")]
+    pub header_rules: Vec<HeaderRule>,
+    /// 代理转发前按顺序应用的请求体改写规则
+    #[serde(rename = "bodyRules", default, skip_serializing_if = "Vec::is_empty")]
+    pub body_rules: Vec<BodyRule>,
+    /// 代理同时发往该供应商的最大请求数，达到上限时转移到下一个候选供应商
+    #[serde(rename = "maxConcurrent", skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+    /// 该供应商的熔断参数，未设置的项沿用应用默认值
+    #[serde(rename = "circuitBreaker", skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<crate::proxy::circuit_breaker::CircuitBreakerOverride>,
+    /// 代理运行期间定期发送的主动健康检查，结果计入该供应商的熔断器
+    #[serde(rename = "healthCheck", skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<crate::proxy::health::HealthCheckConfig>,
+    /// 该供应商使用的上游代理，覆盖全局设置；`direct` 表示直连
+    #[serde(rename = "upstreamProxy", skip_serializing_if = "Option::is_none")]
+    pub upstream_proxy: Option<String>,
+    /// 测速与端点延迟测试使用的探测方式，未设置时预热后 GET 端点
+    #[serde(rename = "speedtest", skip_serializing_if = "Option::is_none")]
+    pub speedtest: Option<crate::services::speedtest::SpeedtestProbe>,
+    /// 多个端点之间的选择策略，未设置时主端点优先、其余按延迟排序
+    #[serde(rename = "urlStrategy", skip_serializing_if = "Option::is_none")]
+    pub url_strategy: Option<crate::proxy::url_router::UrlStrategy>,
+    /// 所有端点都不可用时的处理方式，未设置时降级到配置中的 base_url
+    #[serde(rename = "endpointFallback", skip_serializing_if = "Option::is_none")]
+    pub endpoint_fallback: Option<crate::proxy::url_router::EndpointFallback>,
+}
+
+/// 请求体改写规则，路径以 `.` 分隔（如 `metadata.user_id`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BodyRule {
+    /// 设置字段，缺失的中间对象会被创建
+    Set { path: String, value: Value },
+    /// 删除字段
+    Delete { path: String },
+    /// 将输出 token 上限压到不超过 max
+    ClampMaxTokens { max: u64 },
+    /// 强制设置 stream 字段
+    ForceStream { stream: bool },
+    /// 删除 stream 字段，交由上游默认值决定
+    StripStream,
+    /// 在系统提示词前插入一段文本
+    SystemPrefix { text: String },
+}
+
+/// 请求头改写动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderRuleAction {
+    /// 追加一个值，保留已有的同名请求头
+    Add,
+    /// 覆盖同名请求头
+    Set,
+    /// 删除同名请求头
+    Remove,
+}
+
+/// 单条请求头改写规则，名称不区分大小写
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderRule {
+    pub action: HeaderRuleAction,
+    pub name: String,
+    /// Remove 规则不使用该值
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
 }
 
 impl ProviderManager {
@@ -359,6 +451,8 @@ impl UniversalProvider {
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         })
     }
 
@@ -417,6 +511,8 @@ requires_openai_auth = true"#
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         })
     }
 
@@ -452,6 +548,8 @@ requires_openai_auth = true"#
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         })
     }
 }
@@ -0,0 +1,240 @@
+//! 结构化访问日志
+//!
+//! 开启后代理为每个客户端请求向 `~/.cc-switch/logs/access.jsonl` 追加一行 JSON，
+//! 文件超过设定大小时按 `access.jsonl.1` … `.N` 轮转。日志只包含路由与用量信息，
+//! 不记录请求头；错误信息中出现的 API Key 与 `key=` 查询参数会被替换为 `[redacted]`。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use super::usage::parser::TokenUsage;
+use crate::redact::redact_with;
+
+/// 访问日志文件名
+pub const ACCESS_LOG_FILE: &str = "access.jsonl";
+
+/// 默认单个文件大小上限（MB）
+pub const DEFAULT_ACCESS_LOG_MAX_SIZE_MB: u64 = 10;
+
+/// 保留的轮转文件个数（不含当前文件）
+const ACCESS_LOG_ROTATED_FILES: usize = 5;
+
+/// 访问日志设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    /// 单个文件大小上限（MB），超过后轮转
+    pub max_size_mb: u64,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size_mb: DEFAULT_ACCESS_LOG_MAX_SIZE_MB,
+        }
+    }
+}
+
+/// 一条访问日志，对应一次客户端请求（含故障转移后的最终结果）
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessLogEntry {
+    /// RFC 3339 时间戳
+    pub timestamp: String,
+    pub app: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 请求路径，不含查询参数
+    pub endpoint: String,
+    pub model: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_token_ms: Option<u64>,
+    pub streaming: bool,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_read_tokens: u32,
+    pub cache_creation_tokens: u32,
+    /// 切换到后续供应商的次数，0 表示首个供应商即完成
+    pub failover_hops: usize,
+    /// 由响应缓存直接返回，未转发到上游
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cache_hit: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AccessLogEntry {
+    pub fn with_usage(mut self, usage: &TokenUsage) -> Self {
+        self.input_tokens = usage.input_tokens;
+        self.output_tokens = usage.output_tokens;
+        self.cache_read_tokens = usage.cache_read_tokens;
+        self.cache_creation_tokens = usage.cache_creation_tokens;
+        self
+    }
+}
+
+/// 访问日志写入器，由 ProxyService 持有，设置变更即时生效
+pub struct AccessLog {
+    path: PathBuf,
+    enabled: AtomicBool,
+    max_bytes: AtomicU64,
+    /// 串行化写入与轮转
+    write_lock: Mutex<()>,
+}
+
+impl AccessLog {
+    pub fn new(path: PathBuf, config: AccessLogConfig) -> Self {
+        let log = Self {
+            path,
+            enabled: AtomicBool::new(false),
+            max_bytes: AtomicU64::new(0),
+            write_lock: Mutex::new(()),
+        };
+        log.configure(config);
+        log
+    }
+
+    /// 默认路径：`~/.cc-switch/logs/access.jsonl`
+    pub fn default_path() -> PathBuf {
+        crate::config::get_app_config_dir()
+            .join("logs")
+            .join(ACCESS_LOG_FILE)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn configure(&self, config: AccessLogConfig) {
+        self.enabled.store(config.enabled, Ordering::Relaxed);
+        self.max_bytes
+            .store(config.max_size_mb.max(1) * 1024 * 1024, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 追加一条记录，`secrets` 中的值（如供应商 API Key）会从错误信息中抹去
+    pub fn record(&self, mut entry: AccessLogEntry, secrets: &[&str]) {
+        if !self.is_enabled() {
+            return;
+        }
+        entry.endpoint = strip_query(&entry.endpoint).to_string();
+        entry.error = entry.error.map(|e| redact_with(&e, secrets));
+
+        let mut line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                log::warn!("序列化访问日志失败: {e}");
+                return;
+            }
+        };
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = self.append(line.as_bytes()) {
+            log::warn!("写入访问日志失败: {e}");
+        }
+    }
+
+    fn append(&self, line: &[u8]) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let max_bytes = self.max_bytes.load(Ordering::Relaxed);
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > max_bytes {
+            self.rotate()?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line)
+    }
+
+    /// access.jsonl → access.jsonl.1 → … → access.jsonl.N，最旧的文件被覆盖
+    fn rotate(&self) -> std::io::Result<()> {
+        for i in (1..ACCESS_LOG_ROTATED_FILES).rev() {
+            let from = rotated_path(&self.path, i);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, i + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+fn strip_query(endpoint: &str) -> &str {
+    endpoint.split('?').next().unwrap_or(endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(error: Option<&str>) -> AccessLogEntry {
+        AccessLogEntry {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            app: "gemini".to_string(),
+            provider_id: "p1".to_string(),
+            provider_name: "Relay".to_string(),
+            endpoint: "/v1beta/models/gemini:generateContent?key=AIzaSecret".to_string(),
+            model: "gemini".to_string(),
+            status: 502,
+            latency_ms: 120,
+            first_token_ms: None,
+            streaming: false,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            failover_hops: 1,
+            cache_hit: false,
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn entries_are_written_as_jsonl_and_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ACCESS_LOG_FILE);
+        let log = AccessLog::new(path.clone(), AccessLogConfig::default());
+
+        // 关闭时不写入
+        log.record(entry(None), &[]);
+        assert!(!path.exists());
+
+        log.configure(AccessLogConfig {
+            enabled: true,
+            max_size_mb: 1,
+        });
+        log.record(entry(Some("upstream rejected key sk-abc")), &["sk-abc"]);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("AIzaSecret"));
+        assert!(!content.contains("sk-abc"));
+        let value: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(value["endpoint"], "/v1beta/models/gemini:generateContent");
+        assert_eq!(value["failover_hops"], 1);
+
+        // 超过上限时轮转到 .1
+        fs::write(&path, vec![b'x'; 1024 * 1024]).unwrap();
+        log.record(entry(None), &[]);
+        assert!(rotated_path(&path, 1).exists());
+        assert!(fs::metadata(&path).unwrap().len() < 1024);
+    }
+}
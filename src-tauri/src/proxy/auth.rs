@@ -0,0 +1,107 @@
+//! 本地代理访问令牌
+//!
+//! 设置令牌后，除健康检查外的所有请求都必须携带该令牌，在转发前校验。
+//! 客户端可通过 `Authorization: Bearer`、`x-api-key` 或 `x-goog-api-key` 提供令牌，
+//! 这样接管 Live 配置时直接把令牌写入各 CLI 的 API Key 字段即可。
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::{server::ProxyState, ProxyError};
+
+/// 生成的令牌统一使用该前缀，便于在 Live 配置中识别出代理令牌
+pub const TOKEN_PREFIX: &str = "ccs-proxy-";
+
+/// 生成新的随机访问令牌
+pub fn generate_token() -> String {
+    format!(
+        "{TOKEN_PREFIX}{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// 是否为代理生成的访问令牌
+pub fn is_generated_token(value: &str) -> bool {
+    value.starts_with(TOKEN_PREFIX)
+}
+
+/// 从请求头中取出客户端提供的令牌
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.strip_prefix("Bearer ")
+                .or_else(|| v.strip_prefix("bearer "))
+        });
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .or_else(|| headers.get("x-goog-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+}
+
+/// 逐字节比较全部内容，耗时不随首个不同字节的位置变化
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 路由中间件：设置了令牌时拒绝未携带或携带错误令牌的请求
+pub async fn enforce(State(state): State<ProxyState>, request: Request, next: Next) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+    let expected = match state.db.get_proxy_auth_token() {
+        Ok(Some(token)) => token,
+        Ok(None) => return next.run(request).await,
+        Err(e) => return ProxyError::DatabaseError(e.to_string()).into_response(),
+    };
+
+    match presented_token(request.headers()) {
+        Some(token) if tokens_match(token, &expected) => next.run(request).await,
+        presented => {
+            let reason = if presented.is_some() {
+                "访问令牌无效"
+            } else {
+                "缺少访问令牌"
+            };
+            log::warn!("拒绝请求 {}: {reason}", request.uri().path());
+            ProxyError::AuthError(reason.to_string()).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_is_read_from_any_supported_header() {
+        let token = generate_token();
+        assert!(is_generated_token(&token));
+
+        for (name, value) in [
+            ("authorization", format!("Bearer {token}")),
+            ("x-api-key", token.clone()),
+            ("x-goog-api-key", token.clone()),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, value.parse().unwrap());
+            let presented = presented_token(&headers).unwrap();
+            assert!(tokens_match(presented, &token), "{name}");
+        }
+
+        assert!(presented_token(&HeaderMap::new()).is_none());
+        assert!(!tokens_match("ccs-proxy-0", &token));
+        assert_ne!(generate_token(), token);
+    }
+}
@@ -0,0 +1,241 @@
+//! 请求体改写规则
+//!
+//! 供应商在 meta.bodyRules 中声明字段设置/删除、max_tokens 限制、
+//! stream 强制或移除、系统提示词前缀等规则，转发器在模型映射之后、
+//! 格式转换之前按顺序应用，因此规则面向客户端原始的请求格式。
+
+use serde_json::{json, Map, Value};
+
+use crate::app_config::AppType;
+use crate::provider::BodyRule;
+
+/// 各 API 格式中表示输出 token 上限的字段
+const MAX_TOKEN_FIELDS: [&str; 3] = ["max_tokens", "max_completion_tokens", "max_output_tokens"];
+
+/// 按顺序应用规则，返回实际改动了请求体的规则数
+pub fn apply_body_rules(body: &mut Value, rules: &[BodyRule], app_type: &AppType) -> usize {
+    rules
+        .iter()
+        .filter(|rule| apply_rule(body, rule, app_type))
+        .count()
+}
+
+fn apply_rule(body: &mut Value, rule: &BodyRule, app_type: &AppType) -> bool {
+    match rule {
+        BodyRule::Set { path, value } => set_path(body, path, value.clone()),
+        BodyRule::Delete { path } => delete_path(body, path),
+        BodyRule::ClampMaxTokens { max } => clamp_max_tokens(body, *max),
+        BodyRule::ForceStream { stream } => set_path(body, "stream", Value::Bool(*stream)),
+        BodyRule::StripStream => delete_path(body, "stream"),
+        BodyRule::SystemPrefix { text } => prefix_system(body, text, app_type),
+    }
+}
+
+fn set_path(body: &mut Value, path: &str, value: Value) -> bool {
+    let mut parts: Vec<&str> = path.split('.').filter(|p| !p.is_empty()).collect();
+    let Some(last) = parts.pop() else {
+        return false;
+    };
+    let mut current = body;
+    for part in parts {
+        let Some(obj) = current.as_object_mut() else {
+            return false;
+        };
+        let entry = obj
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        current = entry;
+    }
+    match current.as_object_mut() {
+        Some(obj) if obj.get(last) != Some(&value) => {
+            obj.insert(last.to_string(), value);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn delete_path(body: &mut Value, path: &str) -> bool {
+    let mut parts: Vec<&str> = path.split('.').filter(|p| !p.is_empty()).collect();
+    let Some(last) = parts.pop() else {
+        return false;
+    };
+    let mut current = body;
+    for part in parts {
+        match current.get_mut(part) {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    current
+        .as_object_mut()
+        .is_some_and(|obj| obj.remove(last).is_some())
+}
+
+fn clamp_max_tokens(body: &mut Value, max: u64) -> bool {
+    let mut changed = false;
+    let mut clamp = |value: Option<&mut Value>| {
+        if let Some(value) = value.filter(|v| v.as_u64().is_some_and(|v| v > max)) {
+            *value = json!(max);
+            changed = true;
+        }
+    };
+    for field in MAX_TOKEN_FIELDS {
+        clamp(body.get_mut(field));
+    }
+    clamp(body.pointer_mut("/generationConfig/maxOutputTokens"));
+    changed
+}
+
+/// 按客户端请求格式插入系统提示词前缀：Claude 为 `system`，
+/// Codex 为 `instructions`（Chat Completions 格式时为首条 system 消息），
+/// Gemini 为 `systemInstruction`
+fn prefix_system(body: &mut Value, text: &str, app_type: &AppType) -> bool {
+    if text.is_empty() {
+        return false;
+    }
+    let Some(obj) = body.as_object_mut() else {
+        return false;
+    };
+
+    match app_type {
+        AppType::Claude => match obj.get_mut("system") {
+            Some(Value::Array(blocks)) => {
+                blocks.insert(0, json!({ "type": "text", "text": text }));
+            }
+            Some(Value::String(system)) => *system = format!("{text}\n\n{system}"),
+            _ => {
+                obj.insert("system".to_string(), Value::String(text.to_string()));
+            }
+        },
+        AppType::Codex => {
+            if let Some(Value::Array(messages)) = obj.get_mut("messages") {
+                let has_system = messages
+                    .first()
+                    .and_then(|m| m.get("role"))
+                    .and_then(Value::as_str)
+                    .is_some_and(|role| role == "system");
+                if !has_system {
+                    messages.insert(0, json!({ "role": "system", "content": text }));
+                    return true;
+                }
+                return match messages[0].get_mut("content") {
+                    Some(Value::String(content)) => {
+                        *content = format!("{text}\n\n{content}");
+                        true
+                    }
+                    Some(Value::Array(parts)) => {
+                        parts.insert(0, json!({ "type": "text", "text": text }));
+                        true
+                    }
+                    _ => false,
+                };
+            }
+            match obj.get_mut("instructions") {
+                Some(Value::String(instructions)) => {
+                    *instructions = format!("{text}\n\n{instructions}")
+                }
+                _ => {
+                    obj.insert("instructions".to_string(), Value::String(text.to_string()));
+                }
+            }
+        }
+        AppType::Gemini => {
+            let instruction = obj
+                .entry("systemInstruction")
+                .or_insert_with(|| json!({ "parts": [] }));
+            match instruction.get_mut("parts").and_then(Value::as_array_mut) {
+                Some(parts) => parts.insert(0, json!({ "text": text })),
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_and_stream_rules_apply_in_order() {
+        let mut body = json!({
+            "model": "m",
+            "stream": false,
+            "metadata": { "user_id": "u1" },
+            "max_tokens": 64000
+        });
+        let rules = vec![
+            BodyRule::Delete {
+                path: "metadata.user_id".to_string(),
+            },
+            BodyRule::Set {
+                path: "thinking.budget_tokens".to_string(),
+                value: json!(1024),
+            },
+            BodyRule::ClampMaxTokens { max: 8192 },
+            BodyRule::ForceStream { stream: true },
+            BodyRule::Delete {
+                path: "missing.field".to_string(),
+            },
+        ];
+
+        assert_eq!(apply_body_rules(&mut body, &rules, &AppType::Claude), 4);
+        assert_eq!(
+            body,
+            json!({
+                "model": "m",
+                "stream": true,
+                "metadata": {},
+                "max_tokens": 8192,
+                "thinking": { "budget_tokens": 1024 }
+            })
+        );
+
+        assert_eq!(
+            apply_body_rules(&mut body, &[BodyRule::StripStream], &AppType::Claude),
+            1
+        );
+        assert!(body.get("stream").is_none());
+        // 低于上限时不改动
+        assert_eq!(
+            apply_body_rules(
+                &mut body,
+                &[BodyRule::ClampMaxTokens { max: 10000 }],
+                &AppType::Claude
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn system_prefix_follows_request_format() {
+        let rule = [BodyRule::SystemPrefix {
+            text: "Be brief.".to_string(),
+        }];
+
+        let mut claude = json!({ "system": [{ "type": "text", "text": "base" }], "messages": [] });
+        apply_body_rules(&mut claude, &rule, &AppType::Claude);
+        assert_eq!(claude["system"][0]["text"], "Be brief.");
+        assert_eq!(claude["system"][1]["text"], "base");
+
+        let mut claude_empty = json!({ "messages": [{ "role": "user", "content": "hi" }] });
+        apply_body_rules(&mut claude_empty, &rule, &AppType::Claude);
+        assert_eq!(claude_empty["system"], "Be brief.");
+
+        let mut chat = json!({ "messages": [{ "role": "system", "content": "base" }] });
+        apply_body_rules(&mut chat, &rule, &AppType::Codex);
+        assert_eq!(chat["messages"][0]["content"], "Be brief.\n\nbase");
+
+        let mut responses = json!({ "instructions": "base", "input": [] });
+        apply_body_rules(&mut responses, &rule, &AppType::Codex);
+        assert_eq!(responses["instructions"], "Be brief.\n\nbase");
+
+        let mut gemini = json!({ "contents": [] });
+        apply_body_rules(&mut gemini, &rule, &AppType::Gemini);
+        assert_eq!(gemini["systemInstruction"]["parts"][0]["text"], "Be brief.");
+    }
+}
@@ -44,6 +44,20 @@ pub struct CircuitBreakerConfig {
     pub error_rate_threshold: f64,
     /// 最小请求数 - 计算错误率前的最小请求数
     pub min_requests: u32,
+    /// 半开状态下同时进行的探测请求上限
+    #[serde(default = "default_half_open_max_requests")]
+    pub half_open_max_requests: u32,
+    /// 探测反复失败时打开时长按倍数增长的上限（秒）
+    #[serde(default = "default_max_timeout_seconds")]
+    pub max_timeout_seconds: u64,
+}
+
+fn default_half_open_max_requests() -> u32 {
+    1
+}
+
+fn default_max_timeout_seconds() -> u64 {
+    1800
 }
 
 impl Default for CircuitBreakerConfig {
@@ -54,7 +68,56 @@ impl Default for CircuitBreakerConfig {
             timeout_seconds: 60,
             error_rate_threshold: 0.6,
             min_requests: 10,
+            half_open_max_requests: default_half_open_max_requests(),
+            max_timeout_seconds: default_max_timeout_seconds(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// 第 `reopens` 次探测失败后的打开时长：每次翻倍，不超过上限（上限小于基础时长时以基础时长为准）
+    pub fn open_timeout(&self, reopens: u32) -> u64 {
+        self.timeout_seconds
+            .saturating_mul(1u64 << reopens.min(20))
+            .min(self.max_timeout_seconds.max(self.timeout_seconds))
+    }
+}
+
+/// 供应商级别的熔断参数覆盖，未设置的项沿用应用默认值
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_threshold: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_rate_threshold: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_requests: Option<u32>,
+}
+
+impl CircuitBreakerOverride {
+    /// 没有覆盖任何参数
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 在应用默认配置上叠加覆盖项
+    pub fn apply(&self, mut config: CircuitBreakerConfig) -> CircuitBreakerConfig {
+        if let Some(v) = self.failure_threshold {
+            config.failure_threshold = v;
         }
+        if let Some(v) = self.timeout_seconds {
+            config.timeout_seconds = v;
+        }
+        if let Some(v) = self.error_rate_threshold {
+            config.error_rate_threshold = v;
+        }
+        if let Some(v) = self.min_requests {
+            config.min_requests = v;
+        }
+        config
     }
 }
 
@@ -74,8 +137,10 @@ pub struct CircuitBreaker {
     last_opened_at: Arc<RwLock<Option<Instant>>>,
     /// 配置（支持热更新）
     config: Arc<RwLock<CircuitBreakerConfig>>,
-    /// 半开状态已放行的请求数（用于限流）
+    /// 半开状态进行中的探测请求数（用于限流）
     half_open_requests: Arc<AtomicU32>,
+    /// 上次关闭以来探测失败、重新打开的次数（决定打开时长）
+    reopens: Arc<AtomicU32>,
 }
 
 /// 熔断器放行结果
@@ -100,6 +165,7 @@ impl CircuitBreaker {
             last_opened_at: Arc::new(RwLock::new(None)),
             config: Arc::new(RwLock::new(config)),
             half_open_requests: Arc::new(AtomicU32::new(0)),
+            reopens: Arc::new(AtomicU32::new(0)),
         }
     }
 
@@ -113,7 +179,8 @@ impl CircuitBreaker {
     ///
     /// 这个方法不会占用 HalfOpen 探测名额，仅用于路由选择阶段的“可用性判断”：
     /// - Closed / HalfOpen：可用（返回 true）
-    /// - Open：若超时到达则切到 HalfOpen 并返回 true，否则返回 false
+    /// - Open：若超时到达则切到 HalfOpen 并返回 true，否则返回 false；
+    ///   超时从 `timeout_seconds` 开始，探测每失败一次翻倍，直到 `max_timeout_seconds`
     ///
     /// 注意：真正发起请求前仍需调用 `allow_request()` 来获取 HalfOpen 探测名额，
     /// 并在请求结束后通过 `record_success()` / `record_failure()` 释放。
@@ -125,7 +192,7 @@ impl CircuitBreaker {
             CircuitState::Closed | CircuitState::HalfOpen => true,
             CircuitState::Open => {
                 if let Some(opened_at) = *self.last_opened_at.read().await {
-                    if opened_at.elapsed().as_secs() >= config.timeout_seconds {
+                    if opened_at.elapsed().as_secs() >= self.open_timeout(&config) {
                         drop(config); // 释放读锁再转换状态
                         log::info!(
                             "Circuit breaker transitioning from Open to HalfOpen (timeout reached)"
@@ -152,7 +219,7 @@ impl CircuitBreaker {
                 let config = self.config.read().await;
                 // 检查是否应该尝试半开
                 if let Some(opened_at) = *self.last_opened_at.read().await {
-                    if opened_at.elapsed().as_secs() >= config.timeout_seconds {
+                    if opened_at.elapsed().as_secs() >= self.open_timeout(&config) {
                         drop(config); // 释放读锁再转换状态
                         log::info!(
                             "Circuit breaker transitioning from Open to HalfOpen (timeout reached)"
@@ -166,7 +233,7 @@ impl CircuitBreaker {
                                 allowed: true,
                                 used_half_open_permit: false,
                             },
-                            CircuitState::HalfOpen => self.allow_half_open_probe(),
+                            CircuitState::HalfOpen => self.allow_half_open_probe().await,
                             CircuitState::Open => AllowResult {
                                 allowed: false,
                                 used_half_open_permit: false,
@@ -180,7 +247,7 @@ impl CircuitBreaker {
                     used_half_open_permit: false,
                 }
             }
-            CircuitState::HalfOpen => self.allow_half_open_probe(),
+            CircuitState::HalfOpen => self.allow_half_open_probe().await,
         }
     }
 
@@ -246,8 +313,12 @@ impl CircuitBreaker {
         // 检查是否应该打开熔断器
         match state {
             CircuitState::HalfOpen => {
-                // HalfOpen 状态下失败，立即转为 Open
-                log::warn!("Circuit breaker HalfOpen probe failed, transitioning to Open");
+                // HalfOpen 状态下失败，立即转为 Open，且下一次打开时长翻倍
+                let reopens = self.reopens.load(Ordering::SeqCst) + 1;
+                log::warn!(
+                    "Circuit breaker HalfOpen probe failed, transitioning to Open for {}s",
+                    config.open_timeout(reopens)
+                );
                 drop(config);
                 self.transition_to_open().await;
             }
@@ -291,6 +362,14 @@ impl CircuitBreaker {
         }
     }
 
+    /// 主动健康检查成功：熔断中的供应商提前进入半开状态，不必等满打开时长
+    pub async fn probe_succeeded(&self) {
+        if *self.state.read().await == CircuitState::Open {
+            log::info!("Circuit breaker transitioning from Open to HalfOpen (health check passed)");
+            self.transition_to_half_open().await;
+        }
+    }
+
     /// 获取当前状态
     #[allow(dead_code)]
     pub async fn get_state(&self) -> CircuitState {
@@ -298,14 +377,16 @@ impl CircuitBreaker {
     }
 
     /// 获取统计信息
-    #[allow(dead_code)]
     pub async fn get_stats(&self) -> CircuitBreakerStats {
+        let open_timeout_seconds = self.open_timeout(&*self.config.read().await);
         CircuitBreakerStats {
             state: *self.state.read().await,
             consecutive_failures: self.consecutive_failures.load(Ordering::SeqCst),
             consecutive_successes: self.consecutive_successes.load(Ordering::SeqCst),
             total_requests: self.total_requests.load(Ordering::SeqCst),
             failed_requests: self.failed_requests.load(Ordering::SeqCst),
+            reopens: self.reopens.load(Ordering::SeqCst),
+            open_timeout_seconds,
         }
     }
 
@@ -316,10 +397,21 @@ impl CircuitBreaker {
         self.transition_to_closed().await;
     }
 
-    fn allow_half_open_probe(&self) -> AllowResult {
-        // 半开状态限流：只允许有限请求通过进行探测
-        // 默认最多允许 1 个请求（可在配置中扩展）
-        let max_half_open_requests = 1u32;
+    /// 当前的打开时长（秒）
+    fn open_timeout(&self, config: &CircuitBreakerConfig) -> u64 {
+        config.open_timeout(self.reopens.load(Ordering::SeqCst))
+    }
+
+    /// 半开探测名额：同时进行的探测不超过 `half_open_max_requests`，
+    /// 且不超过关闭熔断器还需要的成功次数，多余的请求继续被拒绝
+    async fn allow_half_open_probe(&self) -> AllowResult {
+        let config = self.config.read().await;
+        let needed = config
+            .success_threshold
+            .saturating_sub(self.consecutive_successes.load(Ordering::SeqCst))
+            .max(1);
+        let max_half_open_requests = config.half_open_max_requests.max(1).min(needed);
+        drop(config);
         let current = self.half_open_requests.fetch_add(1, Ordering::SeqCst);
 
         if current < max_half_open_requests {
@@ -366,9 +458,14 @@ impl CircuitBreaker {
         }
     }
 
-    /// 转换到打开状态
+    /// 转换到打开状态（从半开状态转入时记一次重新打开）
     async fn transition_to_open(&self) {
-        *self.state.write().await = CircuitState::Open;
+        let mut state = self.state.write().await;
+        if *state == CircuitState::HalfOpen {
+            self.reopens.fetch_add(1, Ordering::SeqCst);
+        }
+        *state = CircuitState::Open;
+        drop(state);
         *self.last_opened_at.write().await = Some(Instant::now());
         self.consecutive_failures.store(0, Ordering::SeqCst);
         self.consecutive_successes.store(0, Ordering::SeqCst);
@@ -390,6 +487,7 @@ impl CircuitBreaker {
     /// 转换到关闭状态
     async fn transition_to_closed(&self) {
         *self.state.write().await = CircuitState::Closed;
+        self.reopens.store(0, Ordering::SeqCst);
         self.consecutive_failures.store(0, Ordering::SeqCst);
         self.consecutive_successes.store(0, Ordering::SeqCst);
         // 重置计数器
@@ -407,6 +505,12 @@ pub struct CircuitBreakerStats {
     pub consecutive_successes: u32,
     pub total_requests: u32,
     pub failed_requests: u32,
+    /// 上次关闭以来探测失败的次数
+    #[serde(default)]
+    pub reopens: u32,
+    /// 当前的打开时长（秒）
+    #[serde(default)]
+    pub open_timeout_seconds: u64,
 }
 
 #[cfg(test)]
@@ -503,4 +607,82 @@ mod tests {
         assert_eq!(breaker.get_state().await, CircuitState::Closed);
         assert!(breaker.allow_request().await.allowed);
     }
+
+    #[tokio::test]
+    async fn test_half_open_budget_stops_at_probes_needed_to_close() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 2,
+            timeout_seconds: 0,
+            half_open_max_requests: 3,
+            ..Default::default()
+        };
+        let breaker = CircuitBreaker::new(config);
+        breaker.record_failure(false).await;
+
+        // 并发上限为 3，但关闭只需要 2 次成功，第三个探测被拒绝
+        let first = breaker.allow_request().await;
+        let second = breaker.allow_request().await;
+        assert!(first.used_half_open_permit && second.used_half_open_permit);
+        assert!(!breaker.allow_request().await.allowed);
+
+        // 一次成功后仍只需 1 个探测，而它已在进行中
+        breaker.record_success(true).await;
+        assert_eq!(breaker.get_state().await, CircuitState::HalfOpen);
+        assert!(!breaker.allow_request().await.allowed);
+        breaker.record_success(true).await;
+        assert_eq!(breaker.get_state().await, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_open_timeout_backs_off_after_failed_probes() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            timeout_seconds: 30,
+            max_timeout_seconds: 100,
+            ..Default::default()
+        };
+        assert_eq!(config.open_timeout(0), 30);
+        assert_eq!(config.open_timeout(1), 60);
+        assert_eq!(config.open_timeout(2), 100);
+        assert_eq!(config.open_timeout(u32::MAX), 100);
+
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            timeout_seconds: 0,
+            ..config
+        });
+        breaker.record_failure(false).await;
+        for reopens in 1..=2 {
+            let probe = breaker.allow_request().await;
+            assert!(probe.used_half_open_permit);
+            breaker.record_failure(true).await;
+            assert_eq!(breaker.get_stats().await.reopens, reopens);
+        }
+
+        // 探测成功关闭后，下次打开重新从基础时长开始
+        let probe = breaker.allow_request().await;
+        breaker.record_success(probe.used_half_open_permit).await;
+        breaker.record_success(false).await;
+        let stats = breaker.get_stats().await;
+        assert_eq!(stats.state, CircuitState::Closed);
+        assert_eq!(stats.reopens, 0);
+    }
+
+    #[test]
+    fn test_override_only_replaces_set_fields() {
+        let base = CircuitBreakerConfig::default();
+        assert!(CircuitBreakerOverride::default().is_empty());
+
+        let tuned = CircuitBreakerOverride {
+            failure_threshold: Some(10),
+            error_rate_threshold: Some(0.9),
+            ..Default::default()
+        }
+        .apply(base.clone());
+        assert_eq!(tuned.failure_threshold, 10);
+        assert_eq!(tuned.error_rate_threshold, 0.9);
+        assert_eq!(tuned.timeout_seconds, base.timeout_seconds);
+        assert_eq!(tuned.min_requests, base.min_requests);
+        assert_eq!(tuned.success_threshold, base.success_threshold);
+    }
 }
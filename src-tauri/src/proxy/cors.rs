@@ -0,0 +1,139 @@
+//! 代理的 CORS 设置
+//!
+//! 浏览器中的客户端直接请求本地代理时需要通过预检。默认对任意来源放行（与之前的行为一致），
+//! 也可以限定允许的来源、方法与请求头，或完全关闭 CORS 响应头。列表为空表示不限制。
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use super::ProxyError;
+
+/// 代理 CORS 设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorsConfig {
+    /// 关闭时不返回任何 CORS 响应头，浏览器的跨域请求会被拦截
+    pub enabled: bool,
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_origins: Vec::new(),
+            allowed_methods: Vec::new(),
+            allowed_headers: Vec::new(),
+        }
+    }
+}
+
+impl CorsConfig {
+    /// 按设置构造 CORS 中间件，关闭时返回 None
+    pub fn layer(&self) -> Result<Option<CorsLayer>, ProxyError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let invalid = |kind: &str, value: &str| {
+            ProxyError::ConfigError(format!("无效的 CORS {kind}: {value}"))
+        };
+
+        let origins = if is_wildcard(&self.allowed_origins) {
+            AllowOrigin::from(Any)
+        } else {
+            AllowOrigin::list(
+                self.allowed_origins
+                    .iter()
+                    .map(|origin| {
+                        HeaderValue::from_str(origin.trim_end_matches('/'))
+                            .map_err(|_| invalid("来源", origin))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        };
+        let methods = if is_wildcard(&self.allowed_methods) {
+            AllowMethods::from(Any)
+        } else {
+            AllowMethods::list(
+                self.allowed_methods
+                    .iter()
+                    .map(|method| {
+                        Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                            .map_err(|_| invalid("方法", method))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        };
+        let headers = if is_wildcard(&self.allowed_headers) {
+            AllowHeaders::from(Any)
+        } else {
+            AllowHeaders::list(
+                self.allowed_headers
+                    .iter()
+                    .map(|header| {
+                        HeaderName::from_bytes(header.as_bytes())
+                            .map_err(|_| invalid("请求头", header))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        };
+
+        Ok(Some(
+            CorsLayer::new()
+                .allow_origin(origins)
+                .allow_methods(methods)
+                .allow_headers(headers),
+        ))
+    }
+}
+
+/// 空列表或包含 `*` 时视为不限制
+fn is_wildcard(values: &[String]) -> bool {
+    values.is_empty() || values.iter().any(|v| v == "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_allows_everything_and_disabled_has_no_layer() {
+        assert!(CorsConfig::default().layer().unwrap().is_some());
+        let disabled = CorsConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(disabled.layer().unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        let config = CorsConfig {
+            allowed_origins: vec!["http://localhost:3000".to_string()],
+            allowed_methods: vec!["post".to_string(), "OPTIONS".to_string()],
+            allowed_headers: vec!["x-api-key".to_string(), "content-type".to_string()],
+            ..Default::default()
+        };
+        assert!(config.layer().is_ok());
+
+        let bad_header = CorsConfig {
+            allowed_headers: vec!["bad header".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad_header.layer(),
+            Err(ProxyError::ConfigError(_))
+        ));
+        let bad_origin = CorsConfig {
+            allowed_origins: vec!["http://a\nb".to_string()],
+            ..Default::default()
+        };
+        assert!(bad_origin.layer().is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! 自定义 DNS 解析与缓存
+//!
+//! 部分中转域名会被解析到质量很差的 anycast 节点，可为其写死 IP（`host=ip`）。代理为每个
+//! 请求新建 HTTP 客户端，连接池不跨请求复用，因此每次转发、测速都会重新解析域名；开启缓存后
+//! 改用 hickory 按系统 DNS 配置查询，解析结果按记录 TTL 复用，有效期不超过设置的上限。
+//! hickory 无法使用或查询失败时退回系统解析器，此时按上限缓存。
+//!
+//! 解析器在进程内共享，代理转发、健康检查、请求重放与测速使用同一份覆盖与缓存。
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use hickory_resolver::TokioAsyncResolver;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize};
+
+/// 把域名固定解析到指定 IP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsOverride {
+    pub host: String,
+    pub ip: IpAddr,
+}
+
+/// DNS 设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsConfig {
+    pub cache_enabled: bool,
+    /// 缓存有效期上限（秒），记录 TTL 更短时以记录为准
+    pub cache_ttl_seconds: u64,
+    /// 静态解析，同一域名可出现多次
+    pub overrides: Vec<DnsOverride>,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            cache_enabled: false,
+            cache_ttl_seconds: 60,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// DNS 缓存命中统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 当前缓存的域名数
+    pub entries: usize,
+}
+
+/// 解析 `host=ip` 列表，条目以逗号或空白分隔
+pub fn parse_dns_overrides(text: &str) -> Result<Vec<DnsOverride>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (host, ip) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected host=ip, got '{entry}'"))?;
+            let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+            if host.is_empty() {
+                return Err(format!("Missing host in '{entry}'"));
+            }
+            let ip = ip
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid IP address in '{entry}'"))?;
+            Ok(DnsOverride { host, ip })
+        })
+        .collect()
+}
+
+/// 格式化为 [`parse_dns_overrides`] 可解析的文本
+pub fn format_dns_overrides(overrides: &[DnsOverride]) -> String {
+    overrides
+        .iter()
+        .map(|o| format!("{}={}", o.host, o.ip))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct CacheEntry {
+    ips: Vec<IpAddr>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, CacheEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    config: RwLock<DnsConfig>,
+    cache: Mutex<Cache>,
+    /// 首次需要记录 TTL 时按系统 DNS 配置创建，读取配置失败时为 `None`
+    ttl_resolver: OnceLock<Option<TokioAsyncResolver>>,
+}
+
+/// 先查静态解析，再查缓存，最后交给系统解析器
+#[derive(Clone, Default)]
+pub struct DnsResolver {
+    inner: Arc<Inner>,
+}
+
+static SHARED_RESOLVER: OnceLock<DnsResolver> = OnceLock::new();
+
+/// 进程内共享的解析器
+pub fn shared_resolver() -> &'static DnsResolver {
+    SHARED_RESOLVER.get_or_init(DnsResolver::default)
+}
+
+/// 使用共享解析器的 HTTP 客户端构建器，所有发往上游的客户端都应由此创建
+pub fn client_builder() -> ClientBuilder {
+    Client::builder().dns_resolver(Arc::new(shared_resolver().clone()))
+}
+
+impl DnsResolver {
+    /// 替换设置并清空缓存
+    pub fn configure(&self, config: DnsConfig) {
+        *self.inner.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+        *self.inner.cache.lock().unwrap_or_else(|e| e.into_inner()) = Cache::default();
+    }
+
+    pub fn stats(&self) -> DnsCacheStats {
+        let cache = self.inner.cache.lock().unwrap_or_else(|e| e.into_inner());
+        DnsCacheStats {
+            hits: cache.hits,
+            misses: cache.misses,
+            entries: cache.entries.len(),
+        }
+    }
+
+    async fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let (overridden, ttl) = {
+            let config = self.inner.config.read().unwrap_or_else(|e| e.into_inner());
+            let overridden: Vec<IpAddr> = config
+                .overrides
+                .iter()
+                .filter(|o| o.host == host)
+                .map(|o| o.ip)
+                .collect();
+            let ttl = (config.cache_enabled && config.cache_ttl_seconds > 0)
+                .then(|| Duration::from_secs(config.cache_ttl_seconds));
+            (overridden, ttl)
+        };
+        if !overridden.is_empty() {
+            return Ok(overridden);
+        }
+        let Some(ttl) = ttl else {
+            return system_lookup(host).await;
+        };
+        if let Some(ips) = self.cached(host, Instant::now()) {
+            return Ok(ips);
+        }
+        let (ips, valid_until) = self.lookup_with_ttl(host).await?;
+        self.store(host, ips.clone(), expiry(Instant::now(), ttl, valid_until));
+        Ok(ips)
+    }
+
+    /// 解析并返回记录的过期时间，拿不到记录 TTL 时为 `None`
+    async fn lookup_with_ttl(&self, host: &str) -> io::Result<(Vec<IpAddr>, Option<Instant>)> {
+        if let Some(resolver) = self.ttl_resolver() {
+            match resolver.lookup_ip(host).await {
+                Ok(lookup) => {
+                    let mut ips: Vec<IpAddr> = Vec::new();
+                    for ip in lookup.iter() {
+                        if !ips.contains(&ip) {
+                            ips.push(ip);
+                        }
+                    }
+                    if !ips.is_empty() {
+                        return Ok((ips, Some(lookup.valid_until())));
+                    }
+                }
+                Err(e) => log::debug!("[DNS] 查询 {host} 失败，改用系统解析器: {e}"),
+            }
+        }
+        Ok((system_lookup(host).await?, None))
+    }
+
+    fn ttl_resolver(&self) -> Option<&TokioAsyncResolver> {
+        self.inner
+            .ttl_resolver
+            .get_or_init(|| match TokioAsyncResolver::tokio_from_system_conf() {
+                Ok(resolver) => Some(resolver),
+                Err(e) => {
+                    log::warn!("[DNS] 读取系统 DNS 配置失败，缓存将不按记录 TTL 过期: {e}");
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    /// 查找未过期的缓存结果，并计入命中统计
+    fn cached(&self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        let mut cache = self.inner.cache.lock().unwrap_or_else(|e| e.into_inner());
+        match cache.entries.get(host) {
+            Some(entry) if entry.expires_at > now => {
+                let ips = entry.ips.clone();
+                cache.hits += 1;
+                Some(ips)
+            }
+            _ => {
+                cache.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn store(&self, host: &str, ips: Vec<IpAddr>, expires_at: Instant) {
+        let now = Instant::now();
+        let mut cache = self.inner.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entries.retain(|_, entry| entry.expires_at > now);
+        cache
+            .entries
+            .insert(host.to_string(), CacheEntry { ips, expires_at });
+    }
+}
+
+impl Resolve for DnsResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        let host = name.as_str().trim_end_matches('.').to_ascii_lowercase();
+        Box::pin(async move {
+            let ips = resolver.lookup(&host).await?;
+            // 端口由连接器按请求 URL 填充
+            let addrs: Addrs = Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+/// 缓存过期时间：记录 TTL 与设置上限中较早的一个
+fn expiry(now: Instant, cap: Duration, valid_until: Option<Instant>) -> Instant {
+    let capped = now + cap;
+    valid_until.map_or(capped, |valid_until| valid_until.min(capped))
+}
+
+async fn system_lookup(host: &str) -> io::Result<Vec<IpAddr>> {
+    let mut ips: Vec<IpAddr> = Vec::new();
+    for addr in tokio::net::lookup_host((host, 0)).await? {
+        if !ips.contains(&addr.ip()) {
+            ips.push(addr.ip());
+        }
+    }
+    if ips.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no addresses found for {host}"),
+        ));
+    }
+    Ok(ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_round_trip_through_text() {
+        let overrides = parse_dns_overrides(
+            "api.relay.dev=203.0.113.7,\nAPI.relay.dev.=2001:db8::1 x.io=1.2.3.4",
+        )
+        .unwrap();
+        assert_eq!(overrides.len(), 3);
+        assert_eq!(overrides[1].host, "api.relay.dev");
+        assert_eq!(
+            format_dns_overrides(&overrides),
+            "api.relay.dev=203.0.113.7, api.relay.dev=2001:db8::1, x.io=1.2.3.4"
+        );
+        assert!(parse_dns_overrides("api.relay.dev").is_err());
+        assert!(parse_dns_overrides("api.relay.dev=not-an-ip").is_err());
+        assert!(parse_dns_overrides("").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn overrides_win_over_the_system_resolver() {
+        let resolver = DnsResolver::default();
+        resolver.configure(DnsConfig {
+            overrides: parse_dns_overrides("relay.invalid=203.0.113.7").unwrap(),
+            ..Default::default()
+        });
+        let ips = resolver.lookup("relay.invalid").await.unwrap();
+        assert_eq!(ips, vec!["203.0.113.7".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn cached_results_expire() {
+        let resolver = DnsResolver::default();
+        let now = Instant::now();
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        assert!(resolver.cached("a.dev", now).is_none());
+        resolver.store("a.dev", vec![ip], now + Duration::from_secs(60));
+        assert_eq!(
+            resolver.cached("a.dev", now + Duration::from_secs(59)),
+            Some(vec![ip])
+        );
+        assert!(resolver
+            .cached("a.dev", now + Duration::from_secs(60))
+            .is_none());
+
+        let stats = resolver.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 1));
+
+        resolver.configure(DnsConfig::default());
+        assert_eq!(resolver.stats(), DnsCacheStats::default());
+    }
+
+    #[test]
+    fn record_ttl_is_capped_by_the_setting() {
+        let now = Instant::now();
+        let cap = Duration::from_secs(60);
+        assert_eq!(
+            expiry(now, cap, Some(now + Duration::from_secs(5))),
+            now + Duration::from_secs(5)
+        );
+        assert_eq!(
+            expiry(now, cap, Some(now + Duration::from_secs(3600))),
+            now + cap
+        );
+        assert_eq!(expiry(now, cap, None), now + cap);
+    }
+}
@@ -29,6 +29,28 @@ pub enum ProxyError {
     #[error("未配置供应商")]
     NoProvidersConfigured,
 
+    #[error("所有供应商均已超出预算，路由已暂停")]
+    AllProvidersOverBudget,
+
+    #[error("所有供应商均已达到并发上限")]
+    AllProvidersSaturated,
+
+    /// 供应商的所有端点都不可用，且设置为不降级
+    #[error("供应商 {provider} 的所有端点均不可用")]
+    AllEndpointsUnhealthy { provider: String },
+
+    /// 超出客户端侧限流配置，`retry_after` 为建议的重试等待秒数
+    #[error("超出本地限流（{reason}），请 {retry_after} 秒后重试")]
+    RateLimited { reason: String, retry_after: u64 },
+
+    /// 请求体超过设置的大小上限
+    #[error("请求体超过大小上限 {limit_kb} KB，请精简请求内容或调整代理的请求大小上限")]
+    RequestTooLarge { limit_kb: u64 },
+
+    /// 上游响应体超过设置的大小上限
+    #[error("上游响应超过大小上限 {limit_kb} KB，已中止，可调整代理的响应大小上限")]
+    ResponseTooLarge { limit_kb: u64 },
+
     #[allow(dead_code)]
     #[error("Provider不健康: {0}")]
     ProviderUnhealthy(String),
@@ -62,7 +84,6 @@ pub enum ProxyError {
     StreamIdleTimeout(u64),
 
     /// 认证错误
-    #[allow(dead_code)]
     #[error("认证失败: {0}")]
     AuthError(String),
 
@@ -73,6 +94,21 @@ pub enum ProxyError {
 
 impl IntoResponse for ProxyError {
     fn into_response(self) -> Response {
+        if let ProxyError::RateLimited { retry_after, .. } = &self {
+            let body = json!({
+                "error": {
+                    "message": self.to_string(),
+                    "type": "rate_limit_error",
+                }
+            });
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(axum::http::header::RETRY_AFTER, retry_after.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+
         let (status, body) = match &self {
             ProxyError::UpstreamError {
                 status: upstream_status,
@@ -123,6 +159,21 @@ impl IntoResponse for ProxyError {
                     ProxyError::NoProvidersConfigured => {
                         (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
                     }
+                    ProxyError::AllProvidersOverBudget => {
+                        (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+                    }
+                    ProxyError::AllProvidersSaturated => {
+                        (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+                    }
+                    ProxyError::AllEndpointsUnhealthy { .. } => {
+                        (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+                    }
+                    ProxyError::RequestTooLarge { .. } => {
+                        (StatusCode::PAYLOAD_TOO_LARGE, self.to_string())
+                    }
+                    ProxyError::ResponseTooLarge { .. } => {
+                        (StatusCode::BAD_GATEWAY, self.to_string())
+                    }
                     ProxyError::ProviderUnhealthy(_) => {
                         (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
                     }
@@ -145,7 +196,9 @@ impl IntoResponse for ProxyError {
                     ProxyError::Internal(_) => {
                         (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
                     }
-                    ProxyError::UpstreamError { .. } => unreachable!(),
+                    ProxyError::UpstreamError { .. } | ProxyError::RateLimited { .. } => {
+                        unreachable!()
+                    }
                 };
 
                 let error_body = json!({
@@ -33,6 +33,21 @@ pub fn map_proxy_error_to_status(error: &ProxyError) -> u16 {
         // 未配置供应商：503 Service Unavailable
         ProxyError::NoProvidersConfigured => 503,
 
+        // 所有供应商均超出预算：503 Service Unavailable
+        ProxyError::AllProvidersOverBudget => 503,
+
+        // 所有供应商均达到并发上限：503 Service Unavailable
+        ProxyError::AllProvidersSaturated => 503,
+
+        // 供应商所有端点均不可用：503 Service Unavailable
+        ProxyError::AllEndpointsUnhealthy { .. } => 503,
+
+        // 请求体过大：413 Payload Too Large
+        ProxyError::RequestTooLarge { .. } => 413,
+
+        // 上游响应过大：502 Bad Gateway
+        ProxyError::ResponseTooLarge { .. } => 502,
+
         // 重试耗尽：503 Service Unavailable
         ProxyError::MaxRetriesExceeded => 503,
 
@@ -50,9 +65,9 @@ pub fn map_proxy_error_to_status(error: &ProxyError) -> u16 {
     }
 }
 
-/// 将 ProxyError 转换为用户友好的错误消息
+/// 将 ProxyError 转换为用户友好的错误消息，其中的密钥已脱敏
 pub fn get_error_message(error: &ProxyError) -> String {
-    match error {
+    let message = match error {
         ProxyError::UpstreamError { status, body } => {
             if let Some(body) = body {
                 format!("上游错误 ({status}): {body}")
@@ -65,12 +80,18 @@ pub fn get_error_message(error: &ProxyError) -> String {
         ProxyError::NoAvailableProvider => "无可用 Provider".to_string(),
         ProxyError::AllProvidersCircuitOpen => "所有供应商已熔断，无可用渠道".to_string(),
         ProxyError::NoProvidersConfigured => "未配置供应商".to_string(),
+        ProxyError::AllProvidersOverBudget => "所有供应商均已超出预算，路由已暂停".to_string(),
+        ProxyError::AllProvidersSaturated => "所有供应商均已达到并发上限".to_string(),
+        ProxyError::AllEndpointsUnhealthy { provider } => {
+            format!("供应商 {provider} 的所有端点均不可用")
+        }
         ProxyError::MaxRetriesExceeded => "所有 Provider 都失败，重试耗尽".to_string(),
         ProxyError::ProviderUnhealthy(msg) => format!("Provider 不健康: {msg}"),
         ProxyError::DatabaseError(msg) => format!("数据库错误: {msg}"),
         ProxyError::TransformError(msg) => format!("请求/响应转换错误: {msg}"),
         _ => error.to_string(),
-    }
+    };
+    crate::redact::redact(&message)
 }
 
 #[cfg(test)]
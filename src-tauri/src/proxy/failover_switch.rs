@@ -2,10 +2,12 @@
 //!
 //! 处理故障转移成功后的供应商切换逻辑，包括：
 //! - 去重控制（避免多个请求同时触发）
-//! - 数据库更新
+//! - 数据库更新与切换历史记录
+//! - 桌面通知与 Webhook 通知
 
 use crate::database::Database;
 use crate::error::AppError;
+use crate::proxy::webhook::{WebhookEvent, WebhookEventKind, WebhookNotifier};
 use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -19,6 +21,8 @@ pub struct FailoverSwitchManager {
     /// 正在处理中的切换（key = "app_type:provider_id"）
     pending_switches: Arc<RwLock<HashSet<String>>>,
     db: Arc<Database>,
+    /// 切换完成后的事件通知
+    webhooks: Option<Arc<WebhookNotifier>>,
 }
 
 impl FailoverSwitchManager {
@@ -26,12 +30,20 @@ impl FailoverSwitchManager {
         Self {
             pending_switches: Arc::new(RwLock::new(HashSet::new())),
             db,
+            webhooks: None,
         }
     }
 
+    /// 切换完成后发送 Webhook 通知
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookNotifier>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
     /// 尝试执行故障转移切换
     ///
     /// 如果相同的切换已在进行中，则跳过；否则执行切换逻辑。
+    /// `reason` 为导致切换的上游错误或切回说明，记入故障转移历史。
     ///
     /// # Returns
     /// - `Ok(true)` - 切换成功执行
@@ -42,6 +54,7 @@ impl FailoverSwitchManager {
         app_type: &str,
         provider_id: &str,
         provider_name: &str,
+        reason: Option<&str>,
     ) -> Result<bool, AppError> {
         let switch_key = format!("{app_type}:{provider_id}");
 
@@ -56,7 +69,9 @@ impl FailoverSwitchManager {
         }
 
         // 执行切换（确保最后清理 pending 标记）
-        let result = self.do_switch(app_type, provider_id, provider_name).await;
+        let result = self
+            .do_switch(app_type, provider_id, provider_name, reason)
+            .await;
 
         // 清理 pending 标记
         {
@@ -72,6 +87,7 @@ impl FailoverSwitchManager {
         app_type: &str,
         provider_id: &str,
         provider_name: &str,
+        reason: Option<&str>,
     ) -> Result<bool, AppError> {
         // 检查该应用是否已被代理接管（enabled=true）
         // 只有被接管的应用才允许执行故障转移切换
@@ -88,8 +104,24 @@ impl FailoverSwitchManager {
             return Ok(false);
         }
 
+        // 请求期间配置可能已变更：供应商已删除或移出队列时不再切换到它
+        let in_queue = self
+            .db
+            .get_provider_by_id(provider_id, app_type)?
+            .is_some_and(|provider| provider.in_failover_queue);
+        if !in_queue {
+            log::info!("[Failover] {provider_name} ({provider_id}) 已不在故障转移队列中，跳过切换");
+            return Ok(false);
+        }
+
         log::info!("[Failover] 开始切换供应商: {app_type} -> {provider_name} ({provider_id})");
 
+        let previous_id = self.db.get_current_provider(app_type).ok().flatten();
+        let previous_provider = previous_id
+            .as_deref()
+            .and_then(|id| self.db.get_provider_by_id(id, app_type).ok().flatten())
+            .map(|provider| provider.name);
+
         // 1. 更新数据库 is_current
         self.db.set_current_provider(app_type, provider_id)?;
 
@@ -101,6 +133,40 @@ impl FailoverSwitchManager {
         // 3. Log the switch (TUI version - no tray/event emission)
         log::info!("[Failover] 供应商切换完成: {app_type} -> {provider_name} ({provider_id})");
 
+        let event = crate::database::FailoverEvent {
+            created_at: chrono::Utc::now().timestamp(),
+            app_type: app_type.to_string(),
+            from_provider_id: previous_id,
+            from_provider_name: previous_provider.clone(),
+            to_provider_id: provider_id.to_string(),
+            to_provider_name: provider_name.to_string(),
+            error: reason.map(str::to_string),
+        };
+        if let Err(e) = self.db.record_failover_event(&event) {
+            log::warn!("[Failover] 记录切换历史失败: {e}");
+        }
+
+        if self.db.get_failover_desktop_notifications().unwrap_or(true) {
+            let body = match &previous_provider {
+                Some(previous) => format!("{previous} → {provider_name}"),
+                None => format!("Now using {provider_name}"),
+            };
+            crate::desktop_notification::show(
+                &format!("cc-switch: {app_type} provider switched by failover"),
+                &body,
+            );
+        }
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.notify(WebhookEvent {
+                kind: WebhookEventKind::Failover,
+                app_type: app_type.to_string(),
+                provider_id: provider_id.to_string(),
+                provider_name: provider_name.to_string(),
+                previous_provider,
+            });
+        }
+
         Ok(true)
     }
 }
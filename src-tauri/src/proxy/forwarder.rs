@@ -4,19 +4,32 @@
 
 use super::{
     body_filter::filter_private_params_with_whitelist,
+    body_rules::apply_body_rules,
+    dns,
     error::*,
     failover_switch::FailoverSwitchManager,
+    header_rules::{apply_header_rules, apply_header_rules_to_pairs},
+    hedging::{self, HedgeOutcome, HedgingConfig},
+    load_balancer::OutstandingGuard,
     provider_router::ProviderRouter,
     providers::{get_adapter, ProviderAdapter},
-    types::ProxyStatus,
+    request_log::{
+        is_sensitive_header, sanitize_headers, RequestCapture, RequestLog, RequestLogEntry,
+    },
+    response_processor::is_sse_response,
+    shadow::ShadowOutcome,
+    stream_failover::prefetch_first_event,
+    types::{ProxyStatus, RateLimitStats},
+    upstream_proxy::{build_client, display_upstream_proxy, effective_upstream_proxy},
     ProxyError,
 };
+use crate::redact::{mask_secret, redact_with};
 use crate::{app_config::AppType, provider::Provider};
 use reqwest::{Client, Response};
 use serde_json::Value;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, RwLock};
 
 /// Headers 黑名单 - 不透传到上游的 Headers
 ///
@@ -33,6 +46,7 @@ const HEADER_BLACKLIST: &[&str] = &[
     // 认证类（会被覆盖）
     "authorization",
     "x-api-key",
+    "x-goog-api-key",
     // 连接类
     "host",
     "content-length",
@@ -86,15 +100,25 @@ const HEADER_BLACKLIST: &[&str] = &[
 pub struct ForwardResult {
     pub response: Response,
     pub provider: Provider,
+    /// 切换到后续供应商的次数
+    pub failover_hops: usize,
+    /// 进行中请求计数，应持有到响应体传输结束
+    pub in_flight: OutstandingGuard,
 }
 
 pub struct ForwardError {
     pub error: ProxyError,
     pub provider: Option<Provider>,
+    pub failover_hops: usize,
 }
 
 pub struct RequestForwarder {
+    /// 不经上游代理的客户端
     client: Client,
+    /// 客户端整体超时，为使用上游代理的供应商构建客户端时沿用
+    timeout: Duration,
+    /// 全局上游代理地址
+    upstream_proxy: Option<String>,
     /// 共享的 ProviderRouter（持有熔断器状态）
     router: Arc<ProviderRouter>,
     status: Arc<RwLock<ProxyStatus>>,
@@ -105,6 +129,18 @@ pub struct RequestForwarder {
     current_provider_id_at_start: String,
     /// URL 路由器（混合模式）
     url_router: Option<Arc<super::url_router::UrlRouter>>,
+    /// 最近请求记录
+    request_log: Arc<RequestLog>,
+    /// 流式首字节超时（秒），0 表示禁用
+    streaming_first_byte_timeout: u64,
+    /// 流式响应在首个事件前中断时是否改用下一个供应商
+    stream_failover: bool,
+    /// 选路时的路由配置版本，变化后按最新配置重新读取供应商
+    config_version: u64,
+    /// 首选端点响应过慢时向次优端点发送对冲请求
+    hedging: HedgingConfig,
+    /// 本次请求需要镜像到的供应商
+    shadow: Option<Provider>,
 }
 
 impl RequestForwarder {
@@ -116,38 +152,101 @@ impl RequestForwarder {
         current_providers: Arc<RwLock<std::collections::HashMap<String, (String, String)>>>,
         failover_manager: Arc<FailoverSwitchManager>,
         current_provider_id_at_start: String,
-        _streaming_first_byte_timeout: u64,
+        streaming_first_byte_timeout: u64,
         _streaming_idle_timeout: u64,
         url_router: Option<Arc<super::url_router::UrlRouter>>,
+        request_log: Arc<RequestLog>,
     ) -> Self {
         // 全局超时设置为 1800 秒（30 分钟），确保业务层超时配置能正常工作
         // 参考 Claude Code Hub 的 undici 全局超时设计
         const GLOBAL_TIMEOUT_SECS: u64 = 1800;
 
-        let mut client_builder = Client::builder();
-        if non_streaming_timeout > 0 {
+        let timeout = if non_streaming_timeout > 0 {
             // 使用配置的非流式超时
-            client_builder = client_builder.timeout(Duration::from_secs(non_streaming_timeout));
+            Duration::from_secs(non_streaming_timeout)
         } else {
             // 禁用超时时使用全局超时作为保底
-            client_builder = client_builder.timeout(Duration::from_secs(GLOBAL_TIMEOUT_SECS));
-        }
+            Duration::from_secs(GLOBAL_TIMEOUT_SECS)
+        };
 
-        let client = client_builder
+        let client = dns::client_builder()
+            .timeout(timeout)
             .build()
             .expect("Failed to create HTTP client");
 
         Self {
             client,
+            timeout,
+            upstream_proxy: None,
+            config_version: router.config_version(),
             router,
             status,
             current_providers,
             failover_manager,
             current_provider_id_at_start,
             url_router,
+            request_log,
+            streaming_first_byte_timeout,
+            stream_failover: false,
+            hedging: HedgingConfig::default(),
+            shadow: None,
         }
     }
 
+    /// 开启后先读到流式响应的首个事件才算转发成功，失败时转移到下一个供应商
+    pub fn with_stream_failover(mut self, enabled: bool) -> Self {
+        self.stream_failover = enabled;
+        self
+    }
+
+    /// 开启后按设置对慢响应发送对冲请求（仅混合模式下有多个可用端点的供应商）
+    pub fn with_hedging(mut self, config: HedgingConfig) -> Self {
+        self.hedging = config;
+        self
+    }
+
+    /// 把本次请求同时镜像到指定供应商，镜像响应只用于统计
+    pub fn with_shadow(mut self, provider: Option<Provider>) -> Self {
+        self.shadow = provider;
+        self
+    }
+
+    /// 经全局上游代理发送请求，供应商可单独覆盖
+    pub fn with_upstream_proxy(mut self, url: Option<String>) -> Self {
+        self.upstream_proxy = url;
+        self
+    }
+
+    /// 发往该供应商的客户端：未配置上游代理时复用默认客户端
+    fn client_for(&self, provider: &Provider) -> Result<Client, ProxyError> {
+        match effective_upstream_proxy(self.upstream_proxy.as_deref(), provider) {
+            None => Ok(self.client.clone()),
+            Some(proxy) => build_client(dns::client_builder().timeout(self.timeout), Some(proxy))
+                .map_err(|e| {
+                    ProxyError::ForwardFailed(format!(
+                        "上游代理 {} 不可用: {e}",
+                        display_upstream_proxy(proxy)
+                    ))
+                }),
+        }
+    }
+
+    /// 供应商当前的对冲阈值，未开启或延迟样本不足时为 None
+    async fn hedge_delay(&self, app_type: &str, provider_id: &str) -> Option<Duration> {
+        if !self.hedging.enabled {
+            return None;
+        }
+        let samples = self
+            .status
+            .read()
+            .await
+            .provider_stats
+            .iter()
+            .find(|s| s.app_type == app_type && s.provider_id == provider_id)
+            .map(|s| s.recent_latencies(usize::MAX))?;
+        self.hedging.delay(&samples)
+    }
+
     /// 转发请求（带故障转移）
     ///
     /// # Arguments
@@ -164,6 +263,127 @@ impl RequestForwarder {
         headers: axum::http::HeaderMap,
         providers: Vec<Provider>,
     ) -> Result<ForwardResult, ForwardError> {
+        // 镜像请求与主请求同时发出，拿到主请求结果后再比较状态码
+        let shadow = self
+            .shadow
+            .as_ref()
+            .filter(|shadow| providers.first().is_some_and(|p| p.id != shadow.id))
+            .map(|shadow| self.spawn_shadow(shadow.clone(), app_type, endpoint, &body, &headers));
+
+        let result = self
+            .forward_with_failover(app_type, endpoint, body, headers, providers)
+            .await;
+
+        if let Some(primary_status) = shadow {
+            let _ = primary_status.send(match &result {
+                Ok(forwarded) => Some(forwarded.response.status().as_u16()),
+                Err(ForwardError {
+                    error: ProxyError::UpstreamError { status, .. },
+                    ..
+                }) => Some(*status),
+                Err(_) => None,
+            });
+        }
+        result
+    }
+
+    /// 在后台向镜像供应商发送同一请求，返回用于传入主请求状态码的通道
+    fn spawn_shadow(
+        &self,
+        provider: Provider,
+        app_type: &AppType,
+        endpoint: &str,
+        body: &Value,
+        headers: &axum::http::HeaderMap,
+    ) -> oneshot::Sender<Option<u16>> {
+        let (primary_tx, primary_rx) = oneshot::channel();
+        let client = self.client_for(&provider);
+        let status = self.status.clone();
+        let app_type = app_type.clone();
+        let endpoint = endpoint.to_string();
+        let body = body.clone();
+        let headers = headers.clone();
+
+        tokio::spawn(async move {
+            let adapter = get_adapter(&app_type);
+            let app_type_str = app_type.as_str();
+            let start = Instant::now();
+            let request = client.and_then(|client| {
+                let base_url = adapter.extract_base_url(&provider)?;
+                let upstream = build_upstream_request(
+                    &client,
+                    &provider,
+                    &endpoint,
+                    &body,
+                    &headers,
+                    adapter.as_ref(),
+                    app_type_str,
+                    &base_url,
+                )?;
+                Ok((client, upstream.request))
+            });
+            let (shadow_status, latency_ms, error) = match request {
+                Ok((client, Ok(request))) => match client.execute(request).await {
+                    Ok(response) => {
+                        let code = response.status();
+                        let latency_ms = start.elapsed().as_millis() as u64;
+                        // 读完并丢弃响应体，流式响应中途断开同样算失败
+                        let error = match response.bytes().await {
+                            Ok(_) if code.is_success() => None,
+                            Ok(_) => Some(format!("HTTP {}", code.as_u16())),
+                            Err(e) => Some(e.to_string()),
+                        };
+                        (Some(code.as_u16()), latency_ms, error)
+                    }
+                    Err(e) => (
+                        None,
+                        start.elapsed().as_millis() as u64,
+                        Some(e.to_string()),
+                    ),
+                },
+                Ok((_, Err(e))) => (None, 0, Some(e.to_string())),
+                Err(e) => (None, 0, Some(e.to_string())),
+            };
+
+            let outcome = ShadowOutcome {
+                status: shadow_status,
+                primary_status: primary_rx.await.ok().flatten(),
+                latency_ms,
+                error,
+            };
+            log::info!(
+                "[{}] 镜像请求 -> {}: 状态 {:?}（主请求 {:?}），{}ms{}",
+                app_type_str,
+                provider.name,
+                outcome.status,
+                outcome.primary_status,
+                outcome.latency_ms,
+                outcome
+                    .error
+                    .as_deref()
+                    .map(|e| format!("，错误: {e}"))
+                    .unwrap_or_default()
+            );
+            status
+                .write()
+                .await
+                .shadow_stats_mut(app_type_str, &provider.id, &provider.name)
+                .record(&outcome);
+        });
+
+        primary_tx
+    }
+
+    /// 依次尝试候选供应商，直到某个供应商成功响应
+    async fn forward_with_failover(
+        &self,
+        app_type: &AppType,
+        endpoint: &str,
+        body: Value,
+        headers: axum::http::HeaderMap,
+        providers: Vec<Provider>,
+    ) -> Result<ForwardResult, ForwardError> {
+        let started_at = Instant::now();
         // 获取适配器
         let adapter = get_adapter(app_type);
         let app_type_str = app_type.as_str();
@@ -172,6 +392,7 @@ impl RequestForwarder {
             return Err(ForwardError {
                 error: ProxyError::NoAvailableProvider,
                 provider: None,
+                failover_hops: 0,
             });
         }
 
@@ -181,15 +402,57 @@ impl RequestForwarder {
             providers.len()
         );
 
-        let mut last_error = None;
+        let mut last_error: Option<ProxyError> = None;
         let mut last_provider = None;
         let mut attempted_providers = 0usize;
+        let mut saturated_providers = 0usize;
 
         // 单 Provider 场景下跳过熔断器检查（故障转移关闭时）
         let bypass_circuit_breaker = providers.len() == 1;
 
         // 依次尝试每个供应商
         for provider in providers.iter() {
+            // 请求期间配置发生变化（凭据更新、移出队列等）时使用最新配置，不再尝试已移除的供应商
+            let reloaded;
+            let provider = if self.router.config_version() == self.config_version {
+                provider
+            } else {
+                match self.router.reload_provider(app_type_str, provider).await {
+                    Some(latest) => {
+                        reloaded = latest;
+                        &reloaded
+                    }
+                    None => {
+                        log::info!(
+                            "[{}] Provider {} 已被删除或移出队列，跳过",
+                            app_type_str,
+                            provider.name
+                        );
+                        continue;
+                    }
+                }
+            };
+
+            // 达到并发上限的供应商不排队，直接转移到下一个候选
+            let max_concurrent = provider
+                .meta
+                .as_ref()
+                .and_then(|m| m.max_concurrent)
+                .filter(|&max| max > 0);
+            let Some(in_flight) =
+                self.router
+                    .try_begin_request(&provider.id, app_type_str, max_concurrent)
+            else {
+                log::info!(
+                    "[{}] Provider {} 已达到并发上限 {}，转移到下一个供应商",
+                    app_type_str,
+                    provider.name,
+                    max_concurrent.unwrap_or_default()
+                );
+                saturated_providers += 1;
+                continue;
+            };
+
             // 发起请求前先获取熔断器放行许可（HalfOpen 会占用探测名额）
             // 单 Provider 场景下跳过此检查，避免熔断器阻塞所有请求
             let (allowed, used_half_open_permit) = if bypass_circuit_breaker {
@@ -229,12 +492,15 @@ impl RequestForwarder {
                 status.current_provider_id = Some(provider.id.clone());
                 status.total_requests += 1;
                 status.last_request_at = Some(chrono::Utc::now().to_rfc3339());
+                status
+                    .provider_stats_mut(app_type_str, &provider.id, &provider.name)
+                    .requests += 1;
             }
 
             let start = Instant::now();
 
             // 转发请求（每个 Provider 只尝试一次，重试由客户端控制）
-            match self
+            let result = match self
                 .forward(
                     provider,
                     endpoint,
@@ -242,11 +508,27 @@ impl RequestForwarder {
                     &headers,
                     adapter.as_ref(),
                     app_type_str,
+                    started_at,
                 )
                 .await
             {
+                Ok(response) if self.stream_failover && is_sse_response(&response) => {
+                    let timeout = (self.streaming_first_byte_timeout > 0)
+                        .then(|| Duration::from_secs(self.streaming_first_byte_timeout));
+                    prefetch_first_event(response, timeout).await
+                }
+                result => result,
+            };
+            match result {
                 Ok(response) => {
                     let latency = start.elapsed().as_millis() as u64;
+                    self.status
+                        .write()
+                        .await
+                        .provider_stats_mut(app_type_str, &provider.id, &provider.name)
+                        .record_latency(latency);
+                    self.router
+                        .record_latency(&provider.id, app_type_str, latency);
 
                     // 成功：记录成功并更新熔断器
                     if let Err(e) = self
@@ -277,8 +559,13 @@ impl RequestForwarder {
                         let mut status = self.status.write().await;
                         status.success_requests += 1;
                         status.last_error = None;
-                        let should_switch =
-                            self.current_provider_id_at_start.as_str() != provider.id.as_str();
+                        // 负载均衡分流时首选供应商本就轮换，不切换当前供应商
+                        let balancing = self.router.is_load_balancing(app_type_str);
+                        let should_switch = !balancing
+                            && self.current_provider_id_at_start.as_str() != provider.id.as_str();
+                        if balancing && attempted_providers > 1 {
+                            status.failover_count += 1;
+                        }
                         if should_switch {
                             status.failover_count += 1;
                             log::info!(
@@ -293,9 +580,13 @@ impl RequestForwarder {
                             let pid = provider.id.clone();
                             let pname = provider.name.clone();
                             let at = app_type_str.to_string();
+                            // 前面的供应商都被熔断器跳过时没有错误
+                            let error = last_error.as_ref().map(|e| e.to_string());
 
                             tokio::spawn(async move {
-                                if let Err(e) = fm.try_switch(&at, &pid, &pname).await {
+                                if let Err(e) =
+                                    fm.try_switch(&at, &pid, &pname, error.as_deref()).await
+                                {
                                     log::error!("[Failover] 切换供应商失败: {e}");
                                 }
                             });
@@ -318,11 +609,21 @@ impl RequestForwarder {
                     return Ok(ForwardResult {
                         response,
                         provider: provider.clone(),
+                        failover_hops: attempted_providers.saturating_sub(1),
+                        in_flight,
                     });
                 }
                 Err(e) => {
                     let latency = start.elapsed().as_millis() as u64;
 
+                    {
+                        let mut status = self.status.write().await;
+                        let stats =
+                            status.provider_stats_mut(app_type_str, &provider.id, &provider.name);
+                        stats.failures += 1;
+                        stats.record_latency(latency);
+                    }
+
                     // 失败：记录失败并更新熔断器
                     if let Err(record_err) = self
                         .router
@@ -384,6 +685,7 @@ impl RequestForwarder {
                             return Err(ForwardError {
                                 error: e,
                                 provider: Some(provider.clone()),
+                                failover_hops: attempted_providers.saturating_sub(1),
                             });
                         }
                     }
@@ -392,19 +694,29 @@ impl RequestForwarder {
         }
 
         if attempted_providers == 0 {
-            // providers 列表非空，但全部被熔断器拒绝（典型：HalfOpen 探测名额被占用）
+            // providers 列表非空，但全部被并发上限或熔断器拒绝（典型：HalfOpen 探测名额被占用）
+            let error = if saturated_providers > 0 {
+                ProxyError::AllProvidersSaturated
+            } else {
+                ProxyError::NoAvailableProvider
+            };
             {
                 let mut status = self.status.write().await;
                 status.failed_requests += 1;
-                status.last_error = Some("所有供应商暂时不可用（熔断器限制）".to_string());
+                status.last_error = Some(if saturated_providers > 0 {
+                    error.to_string()
+                } else {
+                    "所有供应商暂时不可用（熔断器限制）".to_string()
+                });
                 if status.total_requests > 0 {
                     status.success_rate =
                         (status.success_requests as f32 / status.total_requests as f32) * 100.0;
                 }
             }
             return Err(ForwardError {
-                error: ProxyError::NoAvailableProvider,
+                error,
                 provider: None,
+                failover_hops: 0,
             });
         }
 
@@ -428,10 +740,12 @@ impl RequestForwarder {
         Err(ForwardError {
             error: last_error.unwrap_or(ProxyError::MaxRetriesExceeded),
             provider: last_provider,
+            failover_hops: attempted_providers.saturating_sub(1),
         })
     }
 
     /// 转发单个请求（使用适配器）
+    #[allow(clippy::too_many_arguments)]
     async fn forward(
         &self,
         provider: &Provider,
@@ -440,237 +754,167 @@ impl RequestForwarder {
         headers: &axum::http::HeaderMap,
         adapter: &dyn ProviderAdapter,
         app_type: &str,
+        started_at: Instant,
     ) -> Result<Response, ProxyError> {
         // 使用适配器提取 config base_url
         let config_base_url = adapter.extract_base_url(provider)?;
 
         // 如果启用混合模式，使用 UrlRouter 选择最佳 URL
-        let base_url = if let Some(ref url_router) = self.url_router {
-            if url_router.is_hybrid_mode_enabled(app_type) {
-                match url_router
-                    .select_url(&provider.id, app_type, &config_base_url)
-                    .await
-                {
-                    Ok(url) => url,
-                    Err(e) => {
-                        log::warn!(
-                            "[{}] UrlRouter 选择失败，使用 config base_url: {}",
-                            adapter.name(),
-                            e
-                        );
-                        config_base_url
-                    }
+        let url_router = self
+            .url_router
+            .as_ref()
+            .filter(|router| router.is_hybrid_mode_enabled(app_type));
+        // 次优端点仅用于对冲请求
+        let (mut base_url, hedge_base_url) = if let Some(url_router) = url_router {
+            match url_router
+                .select_urls(provider, app_type, &config_base_url)
+                .await
+            {
+                Ok(urls) => {
+                    let mut urls = urls.into_iter();
+                    let primary = urls.next().unwrap_or(config_base_url);
+                    (primary, urls.next())
+                }
+                // 供应商设置为所有端点不可用时不降级
+                Err(e @ ProxyError::AllEndpointsUnhealthy { .. }) => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "[{}] UrlRouter 选择失败，使用 config base_url: {}",
+                        adapter.name(),
+                        e
+                    );
+                    (config_base_url, None)
                 }
-            } else {
-                config_base_url
             }
         } else {
-            config_base_url
+            (config_base_url, None)
         };
 
         log::info!("[{}] base_url: {}", adapter.name(), base_url);
 
-        // 检查是否需要格式转换
-        let needs_transform = adapter.needs_transform(provider);
-
-        let effective_endpoint =
-            if needs_transform && adapter.name() == "Claude" && endpoint == "/v1/messages" {
-                "/v1/chat/completions"
-            } else {
-                endpoint
-            };
-
-        // 使用适配器构建 URL
-        let url = adapter.build_url(&base_url, effective_endpoint);
-
-        // 记录原始请求 JSON
-        log::info!(
-            "[{}] ====== 请求开始 ======\n>>> 原始请求 JSON:\n{}",
-            adapter.name(),
-            serde_json::to_string_pretty(body).unwrap_or_else(|_| body.to_string())
-        );
-
-        // 应用模型映射（独立于格式转换）
-        let (mapped_body, _original_model, mapped_model) =
-            super::model_mapper::apply_model_mapping(body.clone(), provider);
-
-        if let Some(ref mapped) = mapped_model {
-            log::info!(
-                "[{}] >>> 模型映射后的请求 JSON:\n{}",
+        let client = self.client_for(provider)?;
+        if let Some(proxy) = effective_upstream_proxy(self.upstream_proxy.as_deref(), provider) {
+            log::debug!(
+                "[{}] 经上游代理 {} 发送",
                 adapter.name(),
-                serde_json::to_string_pretty(&mapped_body).unwrap_or_default()
+                display_upstream_proxy(proxy)
             );
-            log::info!("[{}] 模型已映射到: {}", adapter.name(), mapped);
         }
 
-        // 转换请求体（如果需要）
-        let request_body = if needs_transform {
-            log::info!("[{}] 转换请求格式 (Anthropic → OpenAI)", adapter.name());
-            let transformed = adapter.transform_request(mapped_body, provider)?;
-            log::info!(
-                "[{}] >>> 转换后的请求 JSON:\n{}",
-                adapter.name(),
-                serde_json::to_string_pretty(&transformed).unwrap_or_default()
-            );
-            transformed
-        } else {
-            mapped_body
-        };
+        let UpstreamRequest {
+            request: built,
+            mut url,
+            endpoint: effective_endpoint,
+            headers: passed_headers,
+            body_len,
+        } = build_upstream_request(
+            &client, provider, endpoint, body, headers, adapter, app_type, &base_url,
+        )?;
 
-        // 过滤私有参数（以 `_` 开头的字段），防止内部信息泄露到上游
-        // 默认使用空白名单，过滤所有 _ 前缀字段
-        let filtered_body = filter_private_params_with_whitelist(request_body, &[]);
-
-        // ========== 请求体日志（截断显示） ==========
-        let body_str = serde_json::to_string_pretty(&filtered_body)
-            .unwrap_or_else(|_| filtered_body.to_string());
-        let body_preview = if body_str.len() > 2000 {
-            format!(
-                "{}...\n[截断，总长度: {} 字符]",
-                &body_str[..2000],
-                body_str.len()
-            )
-        } else {
-            body_str
+        // 发送请求
+        log::info!("[{}] 发送请求到: {}", adapter.name(), url);
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        let queued_ms = started_at.elapsed().as_millis() as u64;
+        let start = Instant::now();
+        self.status
+            .write()
+            .await
+            .provider_stats_mut(app_type, &provider.id, &provider.name)
+            .bytes_sent += body_len as u64;
+        let hedge_delay = match hedge_base_url {
+            Some(_) => self.hedge_delay(app_type, &provider.id).await,
+            None => None,
         };
-        log::info!(
-            "[{}] ====== 最终请求体 ======\n{}",
-            adapter.name(),
-            body_preview
-        );
-
-        log::info!(
-            "[{}] 转发请求: {} -> {}",
-            adapter.name(),
-            provider.name,
-            url
-        );
-
-        // 构建请求
-        let mut request = self.client.post(&url);
-
-        // ========== 详细 Headers 日志 ==========
-        log::info!("[{}] ====== 客户端原始 Headers ======", adapter.name());
-        for (key, value) in headers {
-            log::info!(
-                "[{}]   {}: {:?}",
-                adapter.name(),
-                key.as_str(),
-                value.to_str().unwrap_or("<binary>")
-            );
-        }
-
-        // 过滤黑名单 Headers，保护隐私并避免冲突
-        let mut filtered_headers: Vec<String> = Vec::new();
-        let mut passed_headers: Vec<(String, String)> = Vec::new();
-
-        for (key, value) in headers {
-            let key_str = key.as_str().to_lowercase();
-            if HEADER_BLACKLIST.contains(&key_str.as_str()) {
-                filtered_headers.push(key_str);
-                continue;
-            }
-            let value_str = value.to_str().unwrap_or("<binary>").to_string();
-            passed_headers.push((key.as_str().to_string(), value_str.clone()));
-            request = request.header(key, value);
-        }
-
-        if !filtered_headers.is_empty() {
-            log::info!(
-                "[{}] ====== 被过滤的 Headers ({}) ======",
-                adapter.name(),
-                filtered_headers.len()
-            );
-            for h in &filtered_headers {
-                log::info!("[{}]   - {}", adapter.name(), h);
-            }
-        }
-
-        // 处理 anthropic-beta Header（透传）
-        // 参考 Claude Code Hub 的实现，直接透传客户端的 beta 标记
-        if let Some(beta) = headers.get("anthropic-beta") {
-            if let Ok(beta_str) = beta.to_str() {
-                request = request.header("anthropic-beta", beta_str);
-                passed_headers.push(("anthropic-beta".to_string(), beta_str.to_string()));
-                log::info!("[{}] 透传 anthropic-beta: {}", adapter.name(), beta_str);
-            }
-        }
-
-        // 客户端 IP 透传（默认开启）
-        if let Some(xff) = headers.get("x-forwarded-for") {
-            if let Ok(xff_str) = xff.to_str() {
-                request = request.header("x-forwarded-for", xff_str);
-                passed_headers.push(("x-forwarded-for".to_string(), xff_str.to_string()));
-                log::debug!("[{}] 透传 x-forwarded-for: {}", adapter.name(), xff_str);
-            }
-        }
-        if let Some(real_ip) = headers.get("x-real-ip") {
-            if let Ok(real_ip_str) = real_ip.to_str() {
-                request = request.header("x-real-ip", real_ip_str);
-                passed_headers.push(("x-real-ip".to_string(), real_ip_str.to_string()));
-                log::debug!("[{}] 透传 x-real-ip: {}", adapter.name(), real_ip_str);
+        let result = match built {
+            Ok(built) => {
+                // 对冲请求与原请求完全相同，只替换为次优端点的 URL
+                let hedge = hedge_base_url
+                    .zip(hedge_delay)
+                    .and_then(|(hedge_base, delay)| {
+                        let hedge_url = adapter.build_url(&hedge_base, &effective_endpoint);
+                        let mut hedge_request = built.try_clone()?;
+                        *hedge_request.url_mut() = hedge_url.parse().ok()?;
+                        Some((hedge_base, hedge_url, hedge_request, delay))
+                    });
+                match hedge {
+                    None => client.execute(built).await,
+                    Some((hedge_base, hedge_url, hedge_request, delay)) => {
+                        let (result, outcome) = hedging::race(
+                            client.execute(built),
+                            delay,
+                            || client.execute(hedge_request),
+                            |r| r.as_ref().is_ok_and(|r| !r.status().is_server_error()),
+                        )
+                        .await;
+                        if outcome != HedgeOutcome::NotHedged {
+                            let hedge_won = outcome == HedgeOutcome::HedgeWon;
+                            log::info!(
+                                "[{}] {}ms 内未响应，已向 {} 发送对冲请求，采用{}的响应",
+                                adapter.name(),
+                                delay.as_millis(),
+                                hedge_url,
+                                if hedge_won {
+                                    "对冲端点"
+                                } else {
+                                    "首选端点"
+                                }
+                            );
+                            let mut status = self.status.write().await;
+                            let stats =
+                                status.provider_stats_mut(app_type, &provider.id, &provider.name);
+                            stats.hedged_requests += 1;
+                            if hedge_won {
+                                stats.hedge_wins += 1;
+                                base_url = hedge_base;
+                                url = hedge_url;
+                            }
+                        }
+                        result
+                    }
+                }
             }
-        }
-
-        // 禁用压缩，避免 gzip 流式响应解析错误
-        // 参考 CCH: undici 在连接提前关闭时会对不完整的 gzip 流抛出错误
-        request = request.header("accept-encoding", "identity");
-        passed_headers.push(("accept-encoding".to_string(), "identity".to_string()));
-
-        // 使用适配器添加认证头
-        if let Some(auth) = adapter.extract_auth(provider) {
-            log::debug!(
-                "[{}] 使用认证: {:?} (key: {})",
-                adapter.name(),
-                auth.strategy,
-                auth.masked_key()
-            );
-            request = adapter.add_auth_headers(request, &auth);
-            // 记录认证头（脱敏）
-            passed_headers.push((
-                "authorization".to_string(),
-                format!("Bearer {}...", &auth.api_key[..8.min(auth.api_key.len())]),
-            ));
-            passed_headers.push((
-                "x-api-key".to_string(),
-                format!("{}...", &auth.api_key[..8.min(auth.api_key.len())]),
-            ));
-        } else {
-            log::error!(
-                "[{}] 未找到 API Key！Provider: {}",
-                adapter.name(),
-                provider.name
+            Err(e) => Err(e),
+        };
+        self.request_log.push(RequestLogEntry {
+            timestamp: sent_at,
+            app_type: app_type.to_string(),
+            provider_id: provider.id.clone(),
+            provider_name: provider.name.clone(),
+            url: url.clone(),
+            status: result.as_ref().ok().map(|r| r.status().as_u16()),
+            latency_ms: start.elapsed().as_millis() as u64,
+            queued_ms,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            request_headers: sanitize_headers(
+                passed_headers.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            ),
+            response_headers: result
+                .as_ref()
+                .map(|r| {
+                    sanitize_headers(
+                        r.headers()
+                            .iter()
+                            .map(|(k, v)| (k.as_str(), v.to_str().unwrap_or("<binary>"))),
+                    )
+                })
+                .unwrap_or_default(),
+            capture: self
+                .request_log
+                .capture_enabled()
+                .then(|| RequestCapture::new(endpoint, client_headers(headers), body)),
+        });
+        // 网络错误与 5xx 计入端点离群检测，4xx 通常是请求本身的问题
+        if let Some(url_router) = url_router {
+            let success = result.as_ref().is_ok_and(|r| !r.status().is_server_error());
+            url_router.record_request(
+                &provider.id,
+                app_type,
+                &base_url,
+                success,
+                start.elapsed().as_millis() as u64,
             );
         }
-
-        // anthropic-version 透传：优先使用客户端的版本号
-        // 参考 Claude Code Hub：透传客户端值而非固定版本
-        if let Some(version) = headers.get("anthropic-version") {
-            if let Ok(version_str) = version.to_str() {
-                // 覆盖适配器设置的默认版本
-                request = request.header("anthropic-version", version_str);
-                passed_headers.push(("anthropic-version".to_string(), version_str.to_string()));
-                log::info!(
-                    "[{}] 透传 anthropic-version: {}",
-                    adapter.name(),
-                    version_str
-                );
-            }
-        }
-
-        // ========== 最终发送的 Headers 日志 ==========
-        log::info!(
-            "[{}] ====== 最终发送的 Headers ({}) ======",
-            adapter.name(),
-            passed_headers.len()
-        );
-        for (k, v) in &passed_headers {
-            log::info!("[{}]   {}: {}", adapter.name(), k, v);
-        }
-
-        // 发送请求
-        log::info!("[{}] 发送请求到: {}", adapter.name(), url);
-        let response = request.json(&filtered_body).send().await.map_err(|e| {
+        let response = result.map_err(|e| {
             log::error!("[{}] 请求失败: {}", adapter.name(), e);
             if e.is_timeout() {
                 ProxyError::Timeout(format!("请求超时: {e}"))
@@ -689,7 +933,31 @@ impl RequestForwarder {
             Ok(response)
         } else {
             let status_code = status.as_u16();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
             let body_text = response.text().await.ok();
+
+            if RateLimitStats::is_rate_limited(status_code, body_text.as_deref()) {
+                log::warn!(
+                    "[{}] Provider {} 被限流 ({}), Retry-After: {:?}",
+                    adapter.name(),
+                    provider.name,
+                    status_code,
+                    retry_after
+                );
+                let now = chrono::Utc::now().timestamp_millis();
+                let mut proxy_status = self.status.write().await;
+                let stats = proxy_status.provider_stats_mut(app_type, &provider.id, &provider.name);
+                stats.rate_limits.record(retry_after.clone(), now);
+                stats
+                    .endpoint_rate_limits
+                    .entry(base_url.trim_end_matches('/').to_string())
+                    .or_default()
+                    .record(retry_after, now);
+            }
             log::error!(
                 "[{}] 上游错误 ({}): {:?}",
                 adapter.name(),
@@ -710,6 +978,7 @@ impl RequestForwarder {
             ProxyError::Timeout(_) => ErrorCategory::Retryable,
             ProxyError::ForwardFailed(_) => ErrorCategory::Retryable,
             ProxyError::ProviderUnhealthy(_) => ErrorCategory::Retryable,
+            ProxyError::AllEndpointsUnhealthy { .. } => ErrorCategory::Retryable,
             // 上游 HTTP 错误：无论状态码如何，都尝试下一个供应商
             // 原因：不同供应商有不同的限制和认证，一个供应商的 4xx 错误
             // 不代表其他供应商也会失败
@@ -726,3 +995,303 @@ impl RequestForwarder {
         }
     }
 }
+
+/// 按供应商配置构建好的上游请求
+struct UpstreamRequest {
+    /// 构建失败时保留错误，由调用方计入请求记录
+    request: reqwest::Result<reqwest::Request>,
+    url: String,
+    /// 格式转换后实际请求的端点
+    endpoint: String,
+    /// 最终发送的请求头（认证信息已截断），用于日志与请求记录
+    headers: Vec<(String, String)>,
+    /// 请求体字节数
+    body_len: usize,
+}
+
+/// 日志中需要抹去的已知凭据：供应商 API Key 与客户端认证头的值（含 `Bearer ` 之后的令牌）
+fn known_secrets(headers: &axum::http::HeaderMap, api_key: Option<&str>) -> Vec<String> {
+    let mut secrets: Vec<String> = api_key.map(str::to_string).into_iter().collect();
+    for (name, value) in headers {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        if !is_sensitive_header(name.as_str()) {
+            continue;
+        }
+        let token = value
+            .get(..7)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("bearer "))
+            .map_or(value, |_| &value[7..]);
+        secrets.push(token.trim().to_string());
+    }
+    secrets.retain(|s| !s.is_empty());
+    // 先替换较长的值，避免其中包含的较短凭据被先替换后长值残留
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    secrets
+}
+
+/// 构建发往供应商的请求：模型映射、请求体改写、格式转换、请求头透传与认证、请求头改写规则
+#[allow(clippy::too_many_arguments)]
+fn build_upstream_request(
+    client: &Client,
+    provider: &Provider,
+    endpoint: &str,
+    body: &Value,
+    headers: &axum::http::HeaderMap,
+    adapter: &dyn ProviderAdapter,
+    app_type: &str,
+    base_url: &str,
+) -> Result<UpstreamRequest, ProxyError> {
+    // 检查是否需要格式转换
+    let needs_transform = adapter.needs_transform(provider);
+
+    let effective_endpoint =
+        if needs_transform && adapter.name() == "Claude" && endpoint == "/v1/messages" {
+            "/v1/chat/completions"
+        } else {
+            endpoint
+        };
+
+    // 使用适配器构建 URL
+    let url = adapter.build_url(base_url, effective_endpoint);
+
+    // 请求体与请求头只在 debug 级别记录，且先抹去供应商 Key 与客户端认证头中的凭据
+    let auth = adapter.extract_auth(provider);
+    let secrets = known_secrets(headers, auth.as_ref().map(|a| a.api_key.as_str()));
+    let secrets: Vec<&str> = secrets.iter().map(String::as_str).collect();
+    let json_for_log = |value: &Value| {
+        redact_with(
+            &serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+            &secrets,
+        )
+    };
+
+    // 记录原始请求 JSON
+    log::debug!(
+        "[{}] ====== 请求开始 ======\n>>> 原始请求 JSON:\n{}",
+        adapter.name(),
+        json_for_log(body)
+    );
+
+    // 应用模型映射（独立于格式转换）
+    let (mut mapped_body, _original_model, mapped_model) =
+        super::model_mapper::apply_model_mapping(body.clone(), provider);
+
+    if let Some(ref mapped) = mapped_model {
+        log::debug!(
+            "[{}] >>> 模型映射后的请求 JSON:\n{}",
+            adapter.name(),
+            json_for_log(&mapped_body)
+        );
+        log::info!("[{}] 模型已映射到: {}", adapter.name(), mapped);
+    }
+
+    // 应用供应商配置的请求体改写规则（面向客户端原始格式，先于格式转换）
+    if let (Some(meta), Ok(app)) = (&provider.meta, app_type.parse::<AppType>()) {
+        let applied = apply_body_rules(&mut mapped_body, &meta.body_rules, &app);
+        if applied > 0 {
+            log::info!("[{}] 已应用 {} 条请求体改写规则", adapter.name(), applied);
+        }
+    }
+
+    // 转换请求体（如果需要）
+    let request_body = if needs_transform {
+        log::info!("[{}] 转换请求格式 (Anthropic → OpenAI)", adapter.name());
+        let transformed = adapter.transform_request(mapped_body, provider)?;
+        log::debug!(
+            "[{}] >>> 转换后的请求 JSON:\n{}",
+            adapter.name(),
+            json_for_log(&transformed)
+        );
+        transformed
+    } else {
+        mapped_body
+    };
+
+    // 过滤私有参数（以 `_` 开头的字段），防止内部信息泄露到上游
+    // 默认使用空白名单，过滤所有 _ 前缀字段
+    let filtered_body = filter_private_params_with_whitelist(request_body, &[]);
+
+    // ========== 请求体日志（截断显示） ==========
+    if log::log_enabled!(log::Level::Debug) {
+        let body_str = json_for_log(&filtered_body);
+        let body_preview = if body_str.len() > 2000 {
+            format!(
+                "{}...\n[截断，总长度: {} 字符]",
+                &body_str[..2000],
+                body_str.len()
+            )
+        } else {
+            body_str
+        };
+        log::debug!(
+            "[{}] ====== 最终请求体 ======\n{}",
+            adapter.name(),
+            body_preview
+        );
+    }
+
+    log::info!(
+        "[{}] 转发请求: {} -> {}",
+        adapter.name(),
+        provider.name,
+        url
+    );
+
+    // 构建请求
+    let mut request = client.post(&url);
+
+    // ========== 详细 Headers 日志 ==========
+    log::debug!("[{}] ====== 客户端原始 Headers ======", adapter.name());
+    for (key, value) in headers {
+        log::debug!(
+            "[{}]   {}: {:?}",
+            adapter.name(),
+            key.as_str(),
+            redact_with(value.to_str().unwrap_or("<binary>"), &secrets)
+        );
+    }
+
+    // 过滤黑名单 Headers，保护隐私并避免冲突
+    let mut filtered_headers: Vec<String> = Vec::new();
+    let mut passed_headers: Vec<(String, String)> = Vec::new();
+
+    for (key, value) in headers {
+        let key_str = key.as_str().to_lowercase();
+        if HEADER_BLACKLIST.contains(&key_str.as_str()) {
+            filtered_headers.push(key_str);
+            continue;
+        }
+        let value_str = value.to_str().unwrap_or("<binary>").to_string();
+        passed_headers.push((key.as_str().to_string(), value_str.clone()));
+        request = request.header(key, value);
+    }
+
+    if !filtered_headers.is_empty() {
+        log::debug!(
+            "[{}] ====== 被过滤的 Headers ({}) ======",
+            adapter.name(),
+            filtered_headers.len()
+        );
+        for h in &filtered_headers {
+            log::debug!("[{}]   - {}", adapter.name(), h);
+        }
+    }
+
+    // 处理 anthropic-beta Header（透传）
+    // 参考 Claude Code Hub 的实现，直接透传客户端的 beta 标记
+    if let Some(beta) = headers.get("anthropic-beta") {
+        if let Ok(beta_str) = beta.to_str() {
+            request = request.header("anthropic-beta", beta_str);
+            passed_headers.push(("anthropic-beta".to_string(), beta_str.to_string()));
+            log::info!("[{}] 透传 anthropic-beta: {}", adapter.name(), beta_str);
+        }
+    }
+
+    // 客户端 IP 透传（默认开启）
+    if let Some(xff) = headers.get("x-forwarded-for") {
+        if let Ok(xff_str) = xff.to_str() {
+            request = request.header("x-forwarded-for", xff_str);
+            passed_headers.push(("x-forwarded-for".to_string(), xff_str.to_string()));
+            log::debug!("[{}] 透传 x-forwarded-for: {}", adapter.name(), xff_str);
+        }
+    }
+    if let Some(real_ip) = headers.get("x-real-ip") {
+        if let Ok(real_ip_str) = real_ip.to_str() {
+            request = request.header("x-real-ip", real_ip_str);
+            passed_headers.push(("x-real-ip".to_string(), real_ip_str.to_string()));
+            log::debug!("[{}] 透传 x-real-ip: {}", adapter.name(), real_ip_str);
+        }
+    }
+
+    // 禁用压缩，避免 gzip 流式响应解析错误
+    // 参考 CCH: undici 在连接提前关闭时会对不完整的 gzip 流抛出错误
+    request = request.header("accept-encoding", "identity");
+    passed_headers.push(("accept-encoding".to_string(), "identity".to_string()));
+
+    // 使用适配器添加认证头
+    if let Some(auth) = auth {
+        log::debug!(
+            "[{}] 使用认证: {:?} (key: {})",
+            adapter.name(),
+            auth.strategy,
+            auth.masked_key()
+        );
+        request = adapter.add_auth_headers(request, &auth);
+        // 记录认证头（脱敏）
+        let masked = mask_secret(&auth.api_key);
+        passed_headers.push(("authorization".to_string(), format!("Bearer {masked}")));
+        passed_headers.push(("x-api-key".to_string(), masked));
+    } else {
+        log::error!(
+            "[{}] 未找到 API Key！Provider: {}",
+            adapter.name(),
+            provider.name
+        );
+    }
+
+    // anthropic-version 透传：优先使用客户端的版本号
+    // 参考 Claude Code Hub：透传客户端值而非固定版本
+    if let Some(version) = headers.get("anthropic-version") {
+        if let Ok(version_str) = version.to_str() {
+            // 覆盖适配器设置的默认版本
+            request = request.header("anthropic-version", version_str);
+            passed_headers.push(("anthropic-version".to_string(), version_str.to_string()));
+            log::info!(
+                "[{}] 透传 anthropic-version: {}",
+                adapter.name(),
+                version_str
+            );
+        }
+    }
+
+    // 供应商配置的请求头改写规则，最后应用以便覆盖上面设置的值
+    let header_rules = provider
+        .meta
+        .as_ref()
+        .map(|m| m.header_rules.as_slice())
+        .unwrap_or_default();
+    apply_header_rules_to_pairs(&mut passed_headers, header_rules);
+
+    // ========== 最终发送的 Headers 日志 ==========
+    log::debug!(
+        "[{}] ====== 最终发送的 Headers ({}) ======",
+        adapter.name(),
+        passed_headers.len()
+    );
+    for (k, v) in &passed_headers {
+        log::debug!("[{}]   {}: {}", adapter.name(), k, redact_with(v, &secrets));
+    }
+
+    // 手动序列化请求体以统计发送字节数（等价于 RequestBuilder::json）
+    let payload = serde_json::to_vec(&filtered_body)
+        .map_err(|e| ProxyError::TransformError(format!("序列化请求体失败: {e}")))?;
+    let body_len = payload.len();
+    if !headers.contains_key(axum::http::header::CONTENT_TYPE) {
+        request = request.header("content-type", "application/json");
+    }
+    let request = request.body(payload).build().map(|mut built| {
+        apply_header_rules(built.headers_mut(), header_rules);
+        built
+    });
+
+    Ok(UpstreamRequest {
+        request,
+        url,
+        endpoint: effective_endpoint.to_string(),
+        headers: passed_headers,
+        body_len,
+    })
+}
+
+/// 捕获请求时保存的客户端请求头：与转发时一致，黑名单头除 anthropic-beta 外均不保留
+fn client_headers(headers: &axum::http::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(key, _)| {
+            key.as_str() == "anthropic-beta" || !HEADER_BLACKLIST.contains(&key.as_str())
+        })
+        .filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
@@ -5,8 +5,8 @@
 use crate::app_config::AppType;
 use crate::provider::Provider;
 use crate::proxy::{
-    extract_session_id, forwarder::RequestForwarder, server::ProxyState, types::AppProxyConfig,
-    ProxyError,
+    access_log::AccessLogEntry, extract_session_id, forwarder::RequestForwarder,
+    response_cache::CacheKey, server::ProxyState, types::AppProxyConfig, ProxyError,
 };
 use axum::http::HeaderMap;
 use std::time::Instant;
@@ -49,11 +49,16 @@ pub struct RequestContext {
     pub tag: &'static str,
     /// 应用类型字符串（如 "claude"、"codex"、"gemini"）
     pub app_type_str: &'static str,
-    /// 应用类型（预留，目前通过 app_type_str 使用）
-    #[allow(dead_code)]
+    /// 应用类型
     pub app_type: AppType,
     /// Session ID（从客户端请求提取或新生成）
     pub session_id: String,
+    /// 客户端请求的路径（不含查询参数）
+    pub endpoint: String,
+    /// 切换到后续供应商的次数（转发完成后填入）
+    pub failover_hops: usize,
+    /// 响应缓存键，None 表示本次请求不写入缓存
+    pub cache_key: Option<CacheKey>,
 }
 
 impl RequestContext {
@@ -110,15 +115,22 @@ impl RequestContext {
 
         // 使用共享的 ProviderRouter 选择 Provider（熔断器状态跨请求保持）
         // 注意：只在这里调用一次，结果传递给 forwarder，避免重复消耗 HalfOpen 名额
+        // 仅客户端提供的会话 ID 参与粘滞路由，自动生成的 ID 每次请求都不同
+        let sticky_session = session_result
+            .client_provided
+            .then_some(session_id.as_str());
         let providers = state
             .provider_router
-            .select_providers(app_type_str)
+            .select_providers(app_type_str, sticky_session)
             .await
             .map_err(|e| match e {
                 crate::error::AppError::AllProvidersCircuitOpen => {
                     ProxyError::AllProvidersCircuitOpen
                 }
                 crate::error::AppError::NoProvidersConfigured => ProxyError::NoProvidersConfigured,
+                crate::error::AppError::AllProvidersOverBudget => {
+                    ProxyError::AllProvidersOverBudget
+                }
                 _ => ProxyError::DatabaseError(e.to_string()),
             })?;
 
@@ -147,9 +159,24 @@ impl RequestContext {
             app_type_str,
             app_type,
             session_id,
+            endpoint: String::new(),
+            failover_hops: 0,
+            cache_key: None,
         })
     }
 
+    /// 记录客户端请求的路径，用于访问日志
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// 记录响应缓存键，成功的非流式响应会按此键写入缓存
+    pub fn with_cache_key(mut self, cache_key: Option<CacheKey>) -> Self {
+        self.cache_key = cache_key;
+        self
+    }
+
     /// 从 URI 提取模型名称（Gemini 专用）
     ///
     /// Gemini API 的模型名称在 URI 中，格式如：
@@ -160,13 +187,7 @@ impl RequestContext {
             .map(|pq| pq.as_str())
             .unwrap_or(uri.path());
 
-        self.request_model = endpoint
-            .split('/')
-            .find(|s| s.starts_with("models/"))
-            .and_then(|s| s.strip_prefix("models/"))
-            .map(|s| s.split(':').next().unwrap_or(s))
-            .unwrap_or("unknown")
-            .to_string();
+        self.request_model = model_from_endpoint(endpoint).unwrap_or_else(|| "unknown".to_string());
 
         log::info!("[{}] 从 URI 提取模型: {}", self.tag, self.request_model);
         self
@@ -207,7 +228,40 @@ impl RequestContext {
             first_byte_timeout,
             idle_timeout,
             Some(state.url_router.clone()),
+            state.request_log.clone(),
         )
+        .with_stream_failover(
+            state
+                .db
+                .get_stream_failover(self.app_type_str)
+                .unwrap_or(false),
+        )
+        .with_hedging(state.db.get_proxy_hedging_config().unwrap_or_default())
+        .with_shadow(self.shadow_provider(state))
+        .with_upstream_proxy(state.db.get_proxy_upstream_url().unwrap_or_default())
+    }
+
+    /// 按镜像比例抽样，本次请求需要镜像时返回镜像目标供应商
+    fn shadow_provider(&self, state: &ProxyState) -> Option<Provider> {
+        let config = state.db.get_shadow_config(self.app_type_str).ok()?;
+        if !config.should_mirror(uuid::Uuid::new_v4().as_u128() as u64) {
+            return None;
+        }
+        let provider_id = config.provider_id.as_deref()?;
+        match state.db.get_provider_by_id(provider_id, self.app_type_str) {
+            Ok(Some(provider)) => Some(provider),
+            Ok(None) => {
+                log::warn!(
+                    "[{}] 镜像目标供应商 {provider_id} 不存在，跳过镜像",
+                    self.tag
+                );
+                None
+            }
+            Err(e) => {
+                log::warn!("[{}] 读取镜像目标供应商失败: {e}", self.tag);
+                None
+            }
+        }
     }
 
     /// 获取 Provider 列表（用于故障转移）
@@ -217,6 +271,29 @@ impl RequestContext {
         self.providers.clone()
     }
 
+    /// 以当前上下文生成访问日志记录，用量由调用方补充
+    pub fn access_log_entry(&self, model: &str, status: u16, streaming: bool) -> AccessLogEntry {
+        AccessLogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            app: self.app_type_str.to_string(),
+            provider_id: self.provider.id.clone(),
+            provider_name: self.provider.name.clone(),
+            endpoint: self.endpoint.clone(),
+            model: model.to_string(),
+            status,
+            latency_ms: self.latency_ms(),
+            first_token_ms: None,
+            streaming,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            failover_hops: self.failover_hops,
+            cache_hit: false,
+            error: None,
+        }
+    }
+
     /// 计算请求延迟（毫秒）
     #[inline]
     pub fn latency_ms(&self) -> u64 {
@@ -245,3 +322,10 @@ impl RequestContext {
         }
     }
 }
+
+/// 从 Gemini 请求路径（如 `/v1beta/models/gemini-pro:generateContent`）提取模型名称
+pub fn model_from_endpoint(endpoint: &str) -> Option<String> {
+    let (_, rest) = endpoint.split_once("models/")?;
+    let model = rest.split(['/', '?', ':']).next()?;
+    (!model.is_empty()).then(|| model.to_string())
+}
@@ -8,14 +8,22 @@
 //! - Claude 的格式转换逻辑保留在此文件（用于 OpenRouter 旧接口回退）
 
 use super::{
+    access_log::AccessLogEntry,
     error_mapper::{get_error_message, map_proxy_error_to_status},
     handler_config::{
         CLAUDE_PARSER_CONFIG, CODEX_PARSER_CONFIG, GEMINI_PARSER_CONFIG, OPENAI_PARSER_CONFIG,
     },
-    handler_context::RequestContext,
+    handler_context::{model_from_endpoint, RequestContext},
     providers::{get_adapter, streaming::create_anthropic_sse_stream, transform},
-    response_processor::{create_logged_passthrough_stream, process_response, SseUsageCollector},
+    request_log::{sanitize_headers, RequestLogEntry},
+    response_cache::{cache_key, CacheKey, CachedResponse},
+    response_processor::{
+        count_received_bytes, create_logged_passthrough_stream, hold_until_body_end,
+        process_response, SseUsageCollector,
+    },
     server::ProxyState,
+    session::ClientFormat,
+    size_limits::{limit_response_stream, read_response_body},
     types::*,
     usage::parser::TokenUsage,
     ProxyError,
@@ -61,8 +69,17 @@ pub async fn handle_messages(
     headers: axum::http::HeaderMap,
     Json(body): Json<Value>,
 ) -> Result<axum::response::Response, ProxyError> {
-    let mut ctx =
-        RequestContext::new(&state, &body, &headers, AppType::Claude, "Claude", "claude").await?;
+    let cache_key = response_cache_key(&state, "claude", "/v1/messages", &headers, &body);
+    if let Some(response) = cache_key
+        .and_then(|key| cached_response(&state, "Claude", "claude", "/v1/messages", &body, key))
+    {
+        return Ok(response);
+    }
+
+    let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Claude, "Claude", "claude")
+        .await?
+        .with_endpoint("/v1/messages")
+        .with_cache_key(cache_key);
 
     let is_stream = body
         .get("stream")
@@ -86,12 +103,15 @@ pub async fn handle_messages(
             if let Some(provider) = err.provider.take() {
                 ctx.provider = provider;
             }
+            ctx.failover_hops = err.failover_hops;
             log_forward_error(&state, &ctx, is_stream, &err.error);
             return Err(err.error);
         }
     };
 
     ctx.provider = result.provider;
+    ctx.failover_hops = result.failover_hops;
+    let in_flight = result.in_flight;
     let response = result.response;
 
     // 检查是否需要格式转换（OpenRouter 等中转服务）
@@ -110,11 +130,15 @@ pub async fn handle_messages(
 
     // Claude 特有：格式转换处理
     if needs_transform {
-        return handle_claude_transform(response, &ctx, &state, &body, is_stream).await;
+        return handle_claude_transform(response, &ctx, &state, &body, is_stream)
+            .await
+            .map(|response| hold_until_body_end(response, in_flight));
     }
 
     // 通用响应处理（透传模式）
-    process_response(response, &ctx, &state, &CLAUDE_PARSER_CONFIG).await
+    process_response(response, &ctx, &state, &CLAUDE_PARSER_CONFIG)
+        .await
+        .map(|response| hold_until_body_end(response, in_flight))
 }
 
 /// Claude 格式转换处理（独有逻辑）
@@ -133,7 +157,11 @@ async fn handle_claude_transform(
         // 流式响应转换 (OpenAI SSE → Anthropic SSE)
         log::info!("[Claude] 开始流式响应转换 (OpenAI SSE → Anthropic SSE)");
 
-        let stream = response.bytes_stream();
+        let stream = limit_response_stream(
+            count_received_bytes(response.bytes_stream(), ctx, state),
+            state,
+            ctx.tag,
+        );
         let sse_stream = create_anthropic_sse_stream(stream);
 
         // 创建使用量收集器
@@ -143,9 +171,18 @@ async fn handle_claude_transform(
             let model = ctx.request_model.clone();
             let status_code = status.as_u16();
             let start_time = ctx.start_time;
+            let access_entry = ctx.access_log_entry(&ctx.request_model, status_code, true);
 
             SseUsageCollector::new(start_time, move |events, first_token_ms| {
-                if let Some(usage) = TokenUsage::from_claude_stream_events(&events) {
+                let usage = TokenUsage::from_claude_stream_events(&events);
+                let mut entry = access_entry
+                    .clone()
+                    .with_usage(&usage.clone().unwrap_or_default());
+                entry.latency_ms = start_time.elapsed().as_millis() as u64;
+                entry.first_token_ms = first_token_ms;
+                state.access_log.record(entry, &[]);
+
+                if let Some(usage) = usage {
                     let latency_ms = start_time.elapsed().as_millis() as u64;
                     let state = state.clone();
                     let provider_id = provider_id.clone();
@@ -177,8 +214,10 @@ async fn handle_claude_transform(
         let logged_stream = create_logged_passthrough_stream(
             sse_stream,
             "Claude/OpenRouter",
+            ClientFormat::Claude,
             Some(usage_collector),
             timeout_config,
+            state.active_streams.clone(),
         );
 
         let mut headers = axum::http::HeaderMap::new();
@@ -205,13 +244,11 @@ async fn handle_claude_transform(
 
     let response_headers = response.headers().clone();
 
-    let body_bytes = response.bytes().await.map_err(|e| {
-        log::error!("[Claude] 读取响应体失败: {e}");
-        ProxyError::ForwardFailed(format!("Failed to read response body: {e}"))
-    })?;
+    let body_bytes = read_response_body(response, state, ctx.tag).await?;
 
     let body_str = String::from_utf8_lossy(&body_bytes);
     log::info!("[Claude] OpenAI 响应长度: {} bytes", body_bytes.len());
+    state.record_received_bytes("claude", &ctx.provider.id, body_bytes.len() as u64);
     log::debug!("[Claude] OpenAI 原始响应: {body_str}");
 
     let openai_response: Value = serde_json::from_slice(&body_bytes).map_err(|e| {
@@ -237,7 +274,13 @@ async fn handle_claude_transform(
     );
 
     // 记录使用量
-    if let Some(usage) = TokenUsage::from_claude_response(&anthropic_response) {
+    let usage = TokenUsage::from_claude_response(&anthropic_response);
+    let entry = ctx
+        .access_log_entry(&ctx.request_model, status.as_u16(), false)
+        .with_usage(&usage.clone().unwrap_or_default());
+    state.access_log.record(entry, &[]);
+
+    if let Some(usage) = usage {
         let model = anthropic_response
             .get("model")
             .and_then(|m| m.as_str())
@@ -306,8 +349,17 @@ pub async fn handle_chat_completions(
 ) -> Result<axum::response::Response, ProxyError> {
     log::info!("[Codex] ====== /v1/chat/completions 请求开始 ======");
 
-    let mut ctx =
-        RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex").await?;
+    let cache_key = response_cache_key(&state, "codex", "/v1/chat/completions", &headers, &body);
+    if let Some(response) = cache_key.and_then(|key| {
+        cached_response(&state, "Codex", "codex", "/v1/chat/completions", &body, key)
+    }) {
+        return Ok(response);
+    }
+
+    let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex")
+        .await?
+        .with_endpoint("/v1/chat/completions")
+        .with_cache_key(cache_key);
 
     let is_stream = body
         .get("stream")
@@ -336,17 +388,22 @@ pub async fn handle_chat_completions(
             if let Some(provider) = err.provider.take() {
                 ctx.provider = provider;
             }
+            ctx.failover_hops = err.failover_hops;
             log_forward_error(&state, &ctx, is_stream, &err.error);
             return Err(err.error);
         }
     };
 
     ctx.provider = result.provider;
+    ctx.failover_hops = result.failover_hops;
+    let in_flight = result.in_flight;
     let response = result.response;
 
     log::info!("[Codex] 上游响应状态: {}", response.status());
 
-    process_response(response, &ctx, &state, &OPENAI_PARSER_CONFIG).await
+    process_response(response, &ctx, &state, &OPENAI_PARSER_CONFIG)
+        .await
+        .map(|response| hold_until_body_end(response, in_flight))
 }
 
 /// 处理 /v1/responses 请求（OpenAI Responses API - Codex CLI 透传）
@@ -355,8 +412,17 @@ pub async fn handle_responses(
     headers: axum::http::HeaderMap,
     Json(body): Json<Value>,
 ) -> Result<axum::response::Response, ProxyError> {
-    let mut ctx =
-        RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex").await?;
+    let cache_key = response_cache_key(&state, "codex", "/v1/responses", &headers, &body);
+    if let Some(response) = cache_key
+        .and_then(|key| cached_response(&state, "Codex", "codex", "/v1/responses", &body, key))
+    {
+        return Ok(response);
+    }
+
+    let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex")
+        .await?
+        .with_endpoint("/v1/responses")
+        .with_cache_key(cache_key);
 
     let is_stream = body
         .get("stream")
@@ -379,17 +445,22 @@ pub async fn handle_responses(
             if let Some(provider) = err.provider.take() {
                 ctx.provider = provider;
             }
+            ctx.failover_hops = err.failover_hops;
             log_forward_error(&state, &ctx, is_stream, &err.error);
             return Err(err.error);
         }
     };
 
     ctx.provider = result.provider;
+    ctx.failover_hops = result.failover_hops;
+    let in_flight = result.in_flight;
     let response = result.response;
 
     log::info!("[Codex] 上游响应状态: {}", response.status());
 
-    process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG).await
+    process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG)
+        .await
+        .map(|response| hold_until_body_end(response, in_flight))
 }
 
 // ============================================================================
@@ -403,17 +474,27 @@ pub async fn handle_gemini(
     headers: axum::http::HeaderMap,
     Json(body): Json<Value>,
 ) -> Result<axum::response::Response, ProxyError> {
-    // Gemini 的模型名称在 URI 中
-    let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Gemini, "Gemini", "gemini")
-        .await?
-        .with_model_from_uri(&uri);
-
     // 提取完整的路径和查询参数
     let endpoint = uri
         .path_and_query()
         .map(|pq| pq.as_str())
         .unwrap_or(uri.path());
 
+    let cache_key = response_cache_key(&state, "gemini", endpoint, &headers, &body);
+    if let Some(response) = cache_key.and_then(|key| {
+        // 记录时只用路径，查询参数中可能带有 API Key
+        cached_response(&state, "Gemini", "gemini", uri.path(), &body, key)
+    }) {
+        return Ok(response);
+    }
+
+    // Gemini 的模型名称在 URI 中
+    let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Gemini, "Gemini", "gemini")
+        .await?
+        .with_model_from_uri(&uri)
+        .with_endpoint(uri.path())
+        .with_cache_key(cache_key);
+
     log::info!("[Gemini] 请求端点: {endpoint}");
 
     let is_stream = body
@@ -437,17 +518,150 @@ pub async fn handle_gemini(
             if let Some(provider) = err.provider.take() {
                 ctx.provider = provider;
             }
+            ctx.failover_hops = err.failover_hops;
             log_forward_error(&state, &ctx, is_stream, &err.error);
             return Err(err.error);
         }
     };
 
     ctx.provider = result.provider;
+    ctx.failover_hops = result.failover_hops;
+    let in_flight = result.in_flight;
     let response = result.response;
 
     log::info!("[Gemini] 上游响应状态: {}", response.status());
 
-    process_response(response, &ctx, &state, &GEMINI_PARSER_CONFIG).await
+    process_response(response, &ctx, &state, &GEMINI_PARSER_CONFIG)
+        .await
+        .map(|response| hold_until_body_end(response, in_flight))
+}
+
+// ============================================================================
+// 响应缓存
+// ============================================================================
+
+/// 开启响应缓存且为非流式请求时返回缓存键
+fn response_cache_key(
+    state: &ProxyState,
+    app_type: &str,
+    endpoint: &str,
+    headers: &axum::http::HeaderMap,
+    body: &Value,
+) -> Option<CacheKey> {
+    let is_stream = body
+        .get("stream")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    // Gemini 通过路径区分流式请求
+    if is_stream || endpoint.contains("streamGenerateContent") {
+        return None;
+    }
+    let config = state.db.get_proxy_response_cache_config().ok()?;
+    config
+        .enabled
+        .then(|| cache_key(app_type, endpoint, headers, body))
+}
+
+/// 命中缓存时直接构造响应，不再转发到上游
+fn cached_response(
+    state: &ProxyState,
+    tag: &str,
+    app_type: &'static str,
+    endpoint: &str,
+    body: &Value,
+    key: CacheKey,
+) -> Option<axum::response::Response> {
+    let started = std::time::Instant::now();
+    let config = state.db.get_proxy_response_cache_config().ok()?;
+    let cached = state.response_cache.get(&config, key)?;
+    log::info!(
+        "[{tag}] 命中响应缓存（供应商 {}，{} bytes），跳过转发",
+        cached.provider_id,
+        cached.body.len()
+    );
+    let model = body
+        .get("model")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| model_from_endpoint(endpoint))
+        .unwrap_or_else(|| "unknown".to_string());
+    record_cache_hit(state, app_type, endpoint, &model, &cached, started);
+
+    let mut builder = axum::response::Response::builder()
+        .status(cached.status)
+        .header("x-cc-switch-cache", "hit");
+    for (key, value) in &cached.headers {
+        builder = builder.header(key, value);
+    }
+    builder.body(axum::body::Body::from(cached.body)).ok()
+}
+
+/// 缓存命中同样写入请求记录、访问日志与使用统计，用量记为 0，不产生费用
+fn record_cache_hit(
+    state: &ProxyState,
+    app_type: &'static str,
+    endpoint: &str,
+    model: &str,
+    cached: &CachedResponse,
+    started: std::time::Instant,
+) {
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let mut response_headers =
+        sanitize_headers(cached.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    response_headers.push(("x-cc-switch-cache".to_string(), "hit".to_string()));
+    state.request_log.push(RequestLogEntry {
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        app_type: app_type.to_string(),
+        provider_id: cached.provider_id.clone(),
+        provider_name: cached.provider_name.clone(),
+        url: endpoint.to_string(),
+        status: Some(cached.status),
+        latency_ms,
+        queued_ms: 0,
+        error: None,
+        request_headers: Vec::new(),
+        response_headers,
+        capture: None,
+    });
+
+    let entry = AccessLogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        app: app_type.to_string(),
+        provider_id: cached.provider_id.clone(),
+        provider_name: cached.provider_name.clone(),
+        endpoint: endpoint.to_string(),
+        model: model.to_string(),
+        status: cached.status,
+        latency_ms,
+        first_token_ms: None,
+        streaming: false,
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_read_tokens: 0,
+        cache_creation_tokens: 0,
+        failover_hops: 0,
+        cache_hit: true,
+        error: None,
+    };
+    let state = state.clone();
+    let provider_id = cached.provider_id.clone();
+    let model = model.to_string();
+    let status = cached.status;
+    tokio::spawn(async move {
+        state.access_log.record(entry, &[]);
+        log_usage(
+            &state,
+            &provider_id,
+            app_type,
+            &model,
+            TokenUsage::default(),
+            latency_ms,
+            None,
+            false,
+            status,
+        )
+        .await;
+    });
 }
 
 // ============================================================================
@@ -467,6 +681,14 @@ fn log_forward_error(
     let error_message = get_error_message(error);
     let request_id = uuid::Uuid::new_v4().to_string();
 
+    let mut entry = ctx.access_log_entry(&ctx.request_model, status_code, is_streaming);
+    entry.error = Some(error_message.clone());
+    let api_key = get_adapter(&ctx.app_type)
+        .extract_auth(&ctx.provider)
+        .map(|auth| auth.api_key)
+        .unwrap_or_default();
+    state.access_log.record(entry, &[api_key.as_str()]);
+
     if let Err(e) = logger.log_error_with_context(
         request_id,
         ctx.provider.id.clone(),
@@ -516,6 +738,12 @@ async fn log_usage(
         _ => Decimal::from(1),
     };
 
+    state
+        .status
+        .write()
+        .await
+        .record_token_usage(app_type, provider_id, &usage);
+
     let request_id = uuid::Uuid::new_v4().to_string();
 
     if let Err(e) = logger.log_with_calculation(
@@ -0,0 +1,151 @@
+//! 请求头改写规则
+//!
+//! 供应商在 meta.headerRules 中配置追加/覆盖/删除请求头的规则，
+//! 转发器在发送前（认证头和透传头都已设置之后）按顺序应用。
+//!
+//! 文本格式（TUI 编辑使用），多条规则以 `;` 分隔：
+//! - `Name: value` 覆盖
+//! - `+Name: value` 追加
+//! - `-Name` 删除
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::provider::{HeaderRule, HeaderRuleAction};
+
+/// 将规则应用到即将发送的请求头，无效的名称或值会被跳过
+pub fn apply_header_rules(headers: &mut HeaderMap, rules: &[HeaderRule]) {
+    for rule in rules {
+        let Ok(name) = HeaderName::from_bytes(rule.name.trim().as_bytes()) else {
+            log::warn!("[HeaderRules] 忽略无效的请求头名称: {}", rule.name);
+            continue;
+        };
+        if rule.action == HeaderRuleAction::Remove {
+            headers.remove(&name);
+            continue;
+        }
+        let Ok(value) = HeaderValue::from_str(&rule.value) else {
+            log::warn!("[HeaderRules] 忽略无效的请求头值: {}", rule.name);
+            continue;
+        };
+        if rule.action == HeaderRuleAction::Add {
+            headers.append(name, value);
+        } else {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// 同步更新用于日志记录的请求头列表
+pub fn apply_header_rules_to_pairs(pairs: &mut Vec<(String, String)>, rules: &[HeaderRule]) {
+    for rule in rules {
+        let name = rule.name.trim();
+        if rule.action != HeaderRuleAction::Add {
+            pairs.retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        }
+        if rule.action != HeaderRuleAction::Remove {
+            pairs.push((name.to_lowercase(), rule.value.clone()));
+        }
+    }
+}
+
+/// 解析 `;` 分隔的规则文本
+pub fn parse_header_rules(text: &str) -> Result<Vec<HeaderRule>, String> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(parse_rule)
+        .collect()
+}
+
+fn parse_rule(part: &str) -> Result<HeaderRule, String> {
+    if let Some(name) = part.strip_prefix('-') {
+        return Ok(HeaderRule {
+            action: HeaderRuleAction::Remove,
+            name: validate_name(name.trim())?,
+            value: String::new(),
+        });
+    }
+
+    let (action, rest) = match part.strip_prefix('+') {
+        Some(rest) => (HeaderRuleAction::Add, rest),
+        None => (HeaderRuleAction::Set, part),
+    };
+    let Some((name, value)) = rest.split_once(':') else {
+        return Err(format!("Expected \"Name: value\" in \"{part}\""));
+    };
+    let value = value.trim();
+    if HeaderValue::from_str(value).is_err() {
+        return Err(format!("Invalid header value in \"{part}\""));
+    }
+    Ok(HeaderRule {
+        action,
+        name: validate_name(name.trim())?,
+        value: value.to_string(),
+    })
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    match HeaderName::from_bytes(name.as_bytes()) {
+        Ok(_) => Ok(name.to_string()),
+        Err(_) => Err(format!("Invalid header name \"{name}\"")),
+    }
+}
+
+/// 将规则格式化为可编辑的文本，与 [`parse_header_rules`] 互逆
+pub fn format_header_rules(rules: &[HeaderRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| match rule.action {
+            HeaderRuleAction::Set => format!("{}: {}", rule.name, rule.value),
+            HeaderRuleAction::Add => format!("+{}: {}", rule.name, rule.value),
+            HeaderRuleAction::Remove => format!("-{}", rule.name),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_parse_format_and_apply_in_order() {
+        let rules = parse_header_rules("X-Api-Version: 2024-01; +X-Tag: a; -User-Agent; ").unwrap();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[1].action, HeaderRuleAction::Add);
+        assert_eq!(rules[2].action, HeaderRuleAction::Remove);
+        assert_eq!(
+            format_header_rules(&rules),
+            "X-Api-Version: 2024-01; +X-Tag: a; -User-Agent"
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-version", HeaderValue::from_static("old"));
+        headers.insert("x-tag", HeaderValue::from_static("client"));
+        headers.insert("user-agent", HeaderValue::from_static("claude-cli"));
+        apply_header_rules(&mut headers, &rules);
+        assert_eq!(headers.get("x-api-version").unwrap(), "2024-01");
+        assert_eq!(headers.get_all("x-tag").iter().count(), 2);
+        assert!(headers.get("user-agent").is_none());
+
+        let mut pairs = vec![
+            ("X-Api-Version".to_string(), "old".to_string()),
+            ("user-agent".to_string(), "claude-cli".to_string()),
+        ];
+        apply_header_rules_to_pairs(&mut pairs, &rules);
+        assert_eq!(
+            pairs,
+            vec![
+                ("x-api-version".to_string(), "2024-01".to_string()),
+                ("x-tag".to_string(), "a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(parse_header_rules("X-Missing-Colon").is_err());
+        assert!(parse_header_rules("Bad Name: v").is_err());
+        assert!(parse_header_rules("-").is_err());
+    }
+}
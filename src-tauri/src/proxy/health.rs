@@ -1,7 +1,374 @@
-//! 健康检查器
+//! 供应商主动健康检查
 //!
-//! 负责定期检查Provider健康状态（占位实现）
+//! 按供应商配置的探测请求（方法、路径、期望状态码、间隔）在后台定期检查上游，与真实流量无关。
+//! 探测结果写入供应商熔断器：连续失败会打开熔断器；熔断期间探测成功则提前进入半开状态，
+//! 让真实请求尽快恢复，而不必等满熔断时长。
 
-// 占位实现，稍后添加完整逻辑
-#[allow(dead_code)]
-pub struct HealthChecker;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::dns;
+use super::header_rules::apply_header_rules;
+use super::provider_router::ProviderRouter;
+use super::providers::get_adapter;
+use super::upstream_proxy::{build_client, effective_upstream_proxy};
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::provider::Provider;
+
+/// 调度检查的粒度
+const TICK: Duration = Duration::from_secs(5);
+
+/// 单次探测的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 探测间隔下限（秒）
+pub const MIN_INTERVAL_SECONDS: u64 = 10;
+
+/// 参与检查的应用类型
+const APP_TYPES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 供应商的主动健康检查设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckConfig {
+    pub enabled: bool,
+    /// HTTP 方法
+    pub method: String,
+    /// 相对供应商 Base URL 的路径，例如 `/v1/models`
+    pub path: String,
+    /// 视为健康的响应状态码
+    pub expected_status: u16,
+    /// 探测间隔（秒）
+    pub interval_seconds: u64,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            method: "GET".to_string(),
+            path: "/v1/models".to_string(),
+            expected_status: 200,
+            interval_seconds: 60,
+        }
+    }
+}
+
+/// 最近一次探测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthProbeResult {
+    /// 探测时间（毫秒时间戳）
+    pub checked_at: i64,
+    pub healthy: bool,
+    /// 上游响应状态码，请求失败时为 None
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// 后台健康检查任务，随代理启动与停止
+pub struct HealthChecker {
+    db: Arc<Database>,
+    provider_router: Arc<ProviderRouter>,
+    client: Client,
+    /// 各供应商最近一次探测结果，key 为 (app_type, provider_id)
+    results: Mutex<HashMap<(String, String), HealthProbeResult>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl HealthChecker {
+    pub fn new(db: Arc<Database>, provider_router: Arc<ProviderRouter>) -> Self {
+        Self {
+            db,
+            provider_router,
+            client: probe_client(),
+            results: Mutex::new(HashMap::new()),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台检查；每次调度时重新读取供应商配置，修改后无需重启
+    pub fn start(self: &Arc<Self>) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+        let checker = self.clone();
+        *task = Some(tokio::spawn(async move {
+            let mut last_run: HashMap<(String, String), Instant> = HashMap::new();
+            let mut ticker = tokio::time::interval(TICK);
+            loop {
+                ticker.tick().await;
+                checker.run_due(&mut last_run).await;
+            }
+        }));
+    }
+
+    /// 停止后台检查
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+    }
+
+    /// 供应商最近一次探测结果
+    pub fn result(&self, app_type: &str, provider_id: &str) -> Option<HealthProbeResult> {
+        self.results
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(app_type.to_string(), provider_id.to_string()))
+            .cloned()
+    }
+
+    /// 并发探测所有已到期的供应商，并把结果写入熔断器
+    async fn run_due(&self, last_run: &mut HashMap<(String, String), Instant>) {
+        let mut due = Vec::new();
+        for app_type in APP_TYPES {
+            let providers = match self.db.get_all_providers(app_type) {
+                Ok(providers) => providers,
+                Err(e) => {
+                    log::warn!("[HealthCheck] 读取 {app_type} 供应商失败: {e}");
+                    continue;
+                }
+            };
+            for provider in providers.into_values() {
+                let Some(config) = provider
+                    .meta
+                    .as_ref()
+                    .and_then(|m| m.health_check.clone())
+                    .filter(|c| c.enabled)
+                else {
+                    continue;
+                };
+                let key = (app_type.to_string(), provider.id.clone());
+                let interval =
+                    Duration::from_secs(config.interval_seconds.max(MIN_INTERVAL_SECONDS));
+                if last_run.get(&key).is_some_and(|at| at.elapsed() < interval) {
+                    continue;
+                }
+                last_run.insert(key, Instant::now());
+                due.push((app_type, provider, config));
+            }
+        }
+        last_run.retain(|_, at| at.elapsed() < Duration::from_secs(86400));
+
+        let upstream_proxy = self.db.get_proxy_upstream_url().unwrap_or_default();
+        let upstream_proxy = upstream_proxy.as_deref();
+        let probes = due.iter().map(|(app_type, provider, config)| async move {
+            let result =
+                probe_via_upstream(&self.client, upstream_proxy, app_type, provider, config).await;
+            (*app_type, provider, result)
+        });
+        for (app_type, provider, result) in join_all(probes).await {
+            if result.healthy {
+                log::debug!(
+                    "[HealthCheck] [{app_type}] {} 健康 ({}ms)",
+                    provider.name,
+                    result.latency_ms
+                );
+            } else {
+                log::warn!(
+                    "[HealthCheck] [{app_type}] {} 探测失败: {}",
+                    provider.name,
+                    result.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            if let Err(e) = self
+                .provider_router
+                .record_probe_result(&provider.id, app_type, result.healthy, result.error.clone())
+                .await
+            {
+                log::warn!("[HealthCheck] 记录 {} 的探测结果失败: {e}", provider.name);
+            }
+            self.results
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert((app_type.to_string(), provider.id.clone()), result);
+        }
+    }
+}
+
+/// 新建探测用的 HTTP 客户端
+pub(crate) fn probe_client() -> Client {
+    dns::client_builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// 探测与转发走同样的上游代理；供应商未单独设置代理时复用 `client`
+pub(crate) async fn probe_via_upstream(
+    client: &Client,
+    upstream_proxy: Option<&str>,
+    app_type: &str,
+    provider: &Provider,
+    config: &HealthCheckConfig,
+) -> HealthProbeResult {
+    let client = match effective_upstream_proxy(upstream_proxy, provider) {
+        None => Ok(client.clone()),
+        Some(proxy) => build_client(dns::client_builder().timeout(PROBE_TIMEOUT), Some(proxy)),
+    };
+    match client {
+        Ok(client) => probe(&client, app_type, provider, config).await,
+        Err(e) => HealthProbeResult {
+            checked_at: chrono::Utc::now().timestamp_millis(),
+            healthy: false,
+            status: None,
+            latency_ms: 0,
+            error: Some(format!("upstream proxy: {e}")),
+        },
+    }
+}
+
+/// 按配置向供应商发送一次探测请求（使用与转发相同的认证方式与请求头改写规则）
+async fn probe(
+    client: &Client,
+    app_type: &str,
+    provider: &Provider,
+    config: &HealthCheckConfig,
+) -> HealthProbeResult {
+    let checked_at = chrono::Utc::now().timestamp_millis();
+    let started = Instant::now();
+    let failed = |error: String| HealthProbeResult {
+        checked_at,
+        healthy: false,
+        status: None,
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: Some(error),
+    };
+
+    let Ok(app) = app_type.parse::<AppType>() else {
+        return failed(format!("unknown app type: {app_type}"));
+    };
+    let adapter = get_adapter(&app);
+    let base_url = match adapter.extract_base_url(provider) {
+        Ok(url) => url,
+        Err(e) => return failed(e.to_string()),
+    };
+    let Ok(method) = Method::from_bytes(config.method.trim().to_ascii_uppercase().as_bytes())
+    else {
+        return failed(format!("invalid method: {}", config.method));
+    };
+
+    let mut request = client.request(method, adapter.build_url(&base_url, &config.path));
+    if let Some(auth) = adapter.extract_auth(provider) {
+        request = adapter.add_auth_headers(request, &auth);
+    }
+    let mut request = match request.build() {
+        Ok(request) => request,
+        Err(e) => return failed(e.to_string()),
+    };
+    if let Some(meta) = &provider.meta {
+        apply_header_rules(request.headers_mut(), &meta.header_rules);
+    }
+
+    match client.execute(request).await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let healthy = status == config.expected_status;
+            HealthProbeResult {
+                checked_at,
+                healthy,
+                status: Some(status),
+                latency_ms: started.elapsed().as_millis() as u64,
+                error: (!healthy)
+                    .then(|| format!("HTTP {status}, expected {}", config.expected_status)),
+            }
+        }
+        Err(e) => failed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::circuit_breaker::{CircuitBreakerOverride, CircuitState};
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 启动只返回固定状态码的本地服务，并返回其地址
+    async fn upstream(status: u16) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn failed_probes_open_the_breaker_and_success_half_opens_it() {
+        let db = Arc::new(Database::memory().unwrap());
+
+        let down = upstream(503).await;
+        let mut provider = Provider::with_id(
+            "a".to_string(),
+            "Provider A".to_string(),
+            json!({"env": {"ANTHROPIC_BASE_URL": down, "ANTHROPIC_AUTH_TOKEN": "sk-test"}}),
+            None,
+        );
+        provider.meta = Some(crate::provider::ProviderMeta {
+            health_check: Some(HealthCheckConfig::default()),
+            circuit_breaker: Some(CircuitBreakerOverride {
+                failure_threshold: Some(2),
+                timeout_seconds: Some(3600),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        db.save_provider("claude", &provider).unwrap();
+
+        let router = Arc::new(ProviderRouter::new(db.clone()));
+        let checker = HealthChecker::new(db.clone(), router.clone());
+        let mut last_run = HashMap::new();
+        checker.run_due(&mut last_run).await;
+        // 未到间隔的供应商不会重复探测
+        checker.run_due(&mut last_run).await;
+        let result = checker.result("claude", "a").unwrap();
+        assert!(!result.healthy);
+        assert_eq!(result.status, Some(503));
+        let stats = router
+            .get_circuit_breaker_stats("a", "claude")
+            .await
+            .unwrap();
+        assert_eq!(stats.consecutive_failures, 1);
+
+        last_run.clear();
+        checker.run_due(&mut last_run).await;
+        let stats = router
+            .get_circuit_breaker_stats("a", "claude")
+            .await
+            .unwrap();
+        assert_eq!(stats.state, CircuitState::Open);
+
+        // 上游恢复后，探测成功让熔断器提前进入半开状态
+        let up = upstream(200).await;
+        provider.settings_config =
+            json!({"env": {"ANTHROPIC_BASE_URL": up, "ANTHROPIC_AUTH_TOKEN": "sk-test"}});
+        db.save_provider("claude", &provider).unwrap();
+        last_run.clear();
+        checker.run_due(&mut last_run).await;
+        assert!(checker.result("claude", "a").unwrap().healthy);
+        let stats = router
+            .get_circuit_breaker_stats("a", "claude")
+            .await
+            .unwrap();
+        assert_eq!(stats.state, CircuitState::HalfOpen);
+        assert_eq!(stats.consecutive_successes, 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! 对冲请求
+//!
+//! 长尾延迟多半来自单个端点偶发的慢响应。开启后，若首选端点在阈值（默认为该供应商
+//! 近期延迟的 p95）内仍未响应，就把同一请求发往该供应商的次优端点，采用先返回的响应，
+//! 另一个请求随即取消。对冲只在混合模式下、供应商有两个以上可用端点时生效。
+
+use std::future::Future;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::percentile;
+
+/// 对冲请求设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HedgingConfig {
+    pub enabled: bool,
+    /// 以供应商近期延迟的第几百分位作为对冲阈值（1-99）
+    pub percentile: u32,
+    /// 阈值下限（毫秒），避免延迟普遍很低时频繁对冲
+    pub min_delay_ms: u64,
+    /// 延迟样本少于该数量时不对冲
+    pub min_samples: u32,
+}
+
+impl Default for HedgingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            percentile: 95,
+            min_delay_ms: 1000,
+            min_samples: 20,
+        }
+    }
+}
+
+impl HedgingConfig {
+    /// 按近期延迟样本计算对冲阈值，未开启或样本不足时为 None
+    pub fn delay(&self, samples: &[u64]) -> Option<Duration> {
+        if !self.enabled || samples.len() < self.min_samples.max(1) as usize {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let threshold = percentile(&sorted, self.percentile.clamp(1, 99) as usize)?;
+        Some(Duration::from_millis(threshold.max(self.min_delay_ms)))
+    }
+}
+
+/// 一次请求的对冲结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HedgeOutcome {
+    /// 首选端点在阈值内完成，没有发出对冲请求
+    NotHedged,
+    /// 已发出对冲请求，最终采用首选端点的结果
+    PrimaryWon,
+    /// 已发出对冲请求，最终采用对冲端点的结果
+    HedgeWon,
+}
+
+/// 先发出 `primary`，超过 `delay` 仍未完成时再发出 `hedge()`，采用先完成且被 `accept`
+/// 认可的结果并丢弃（取消）另一个；两边都不被认可时返回首选端点的结果
+pub async fn race<O, P, H, F>(
+    primary: P,
+    delay: Duration,
+    hedge: H,
+    accept: impl Fn(&O) -> bool,
+) -> (O, HedgeOutcome)
+where
+    P: Future<Output = O>,
+    H: FnOnce() -> F,
+    F: Future<Output = O>,
+{
+    tokio::pin!(primary);
+    tokio::select! {
+        output = &mut primary => return (output, HedgeOutcome::NotHedged),
+        _ = tokio::time::sleep(delay) => {}
+    }
+
+    let hedge = hedge();
+    tokio::pin!(hedge);
+    tokio::select! {
+        output = &mut primary => {
+            if accept(&output) {
+                return (output, HedgeOutcome::PrimaryWon);
+            }
+            let hedged = hedge.await;
+            if accept(&hedged) {
+                (hedged, HedgeOutcome::HedgeWon)
+            } else {
+                (output, HedgeOutcome::PrimaryWon)
+            }
+        }
+        hedged = &mut hedge => {
+            if accept(&hedged) {
+                return (hedged, HedgeOutcome::HedgeWon);
+            }
+            (primary.await, HedgeOutcome::PrimaryWon)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    async fn answer(
+        value: Result<&'static str, &'static str>,
+        after_ms: u64,
+    ) -> Result<&'static str, &'static str> {
+        tokio::time::sleep(Duration::from_millis(after_ms)).await;
+        value
+    }
+
+    #[test]
+    fn delay_uses_percentile_with_floor_and_sample_minimum() {
+        let config = HedgingConfig {
+            enabled: true,
+            min_delay_ms: 100,
+            ..Default::default()
+        };
+        let samples: Vec<u64> = (1..=100).map(|i| i * 10).collect();
+        assert_eq!(config.delay(&samples), Some(Duration::from_millis(950)));
+        assert_eq!(config.delay(&samples[..10]), None);
+        assert_eq!(
+            config.delay(&[5; 20]),
+            Some(Duration::from_millis(100)),
+            "fast providers are hedged no earlier than the floor"
+        );
+        let disabled = HedgingConfig::default();
+        assert_eq!(disabled.delay(&samples), None);
+    }
+
+    #[tokio::test]
+    async fn fast_primary_never_fires_the_hedge() {
+        let fired = AtomicBool::new(false);
+        let (output, outcome) = race(
+            answer(Ok("primary"), 0),
+            Duration::from_millis(200),
+            || {
+                fired.store(true, Ordering::SeqCst);
+                answer(Ok("hedge"), 0)
+            },
+            Result::is_ok,
+        )
+        .await;
+        assert_eq!(output, Ok("primary"));
+        assert_eq!(outcome, HedgeOutcome::NotHedged);
+        assert!(!fired.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn slow_primary_loses_to_the_hedge_and_is_cancelled() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let primary = {
+            let finished = finished.clone();
+            async move {
+                let output = answer(Ok("primary"), 500).await;
+                finished.store(true, Ordering::SeqCst);
+                output
+            }
+        };
+        let (output, outcome) = race(
+            primary,
+            Duration::from_millis(20),
+            || answer(Ok("hedge"), 10),
+            Result::is_ok,
+        )
+        .await;
+        assert_eq!(output, Ok("hedge"));
+        assert_eq!(outcome, HedgeOutcome::HedgeWon);
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn failed_answer_waits_for_the_other_side() {
+        let (output, outcome) = race(
+            answer(Ok("primary"), 80),
+            Duration::from_millis(20),
+            || answer(Err("hedge failed"), 0),
+            Result::is_ok,
+        )
+        .await;
+        assert_eq!(output, Ok("primary"));
+        assert_eq!(outcome, HedgeOutcome::PrimaryWon);
+
+        let (output, outcome) = race(
+            answer(Err("primary failed"), 40),
+            Duration::from_millis(20),
+            || answer(Err("hedge failed"), 0),
+            Result::is_ok,
+        )
+        .await;
+        assert_eq!(output, Err("primary failed"));
+        assert_eq!(outcome, HedgeOutcome::PrimaryWon);
+    }
+}
@@ -0,0 +1,85 @@
+//! 代理连接的 IP 白名单
+//!
+//! 监听 0.0.0.0 等非本机地址时，可以只允许指定网段（CIDR）的客户端连接，
+//! 例如 devcontainer 或局域网内的另一台机器。白名单在接受 TCP 连接时检查，
+//! 不在名单内的连接会被直接关闭。名单为空表示不限制；本机回环地址始终允许。
+
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+use super::ProxyError;
+
+/// 允许连接的网段列表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpAllowlist {
+    nets: Vec<IpNet>,
+}
+
+impl IpAllowlist {
+    /// 解析 CIDR 列表，单个 IP 视为 /32（IPv6 为 /128）
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self, ProxyError> {
+        let nets = entries
+            .iter()
+            .map(|entry| entry.as_ref().trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map(|net| net.trunc())
+                    .map_err(|_| ProxyError::ConfigError(format!("无效的网段: {entry}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { nets })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nets.is_empty()
+    }
+
+    /// 规范化后的网段文本，用于保存与展示
+    pub fn entries(&self) -> Vec<String> {
+        self.nets.iter().map(ToString::to_string).collect()
+    }
+
+    /// 是否允许来自该地址的连接
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.nets.is_empty() || ip.is_loopback() || self.nets.iter().any(|net| net.contains(&ip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cidr_and_single_addresses_are_matched() {
+        let list = IpAllowlist::parse(&["192.168.1.0/24", " 10.0.0.7 ", "fd00::/8"]).unwrap();
+        assert!(list.allows("192.168.1.42".parse().unwrap()));
+        assert!(list.allows("10.0.0.7".parse().unwrap()));
+        assert!(list.allows("fd12::1".parse().unwrap()));
+        // IPv4 映射的 IPv6 地址按 IPv4 匹配
+        assert!(list.allows("::ffff:192.168.1.9".parse().unwrap()));
+        // 本机始终允许
+        assert!(list.allows("127.0.0.1".parse().unwrap()));
+        assert!(list.allows("::1".parse().unwrap()));
+
+        assert!(!list.allows("192.168.2.1".parse().unwrap()));
+        assert!(!list.allows("10.0.0.8".parse().unwrap()));
+    }
+
+    #[test]
+    fn empty_list_allows_everyone_and_bad_entries_are_rejected() {
+        let list = IpAllowlist::parse::<&str>(&[]).unwrap();
+        assert!(list.allows("203.0.113.5".parse().unwrap()));
+
+        assert!(IpAllowlist::parse(&["192.168.1.0/33"]).is_err());
+        assert!(IpAllowlist::parse(&["lan"]).is_err());
+        assert_eq!(
+            IpAllowlist::parse(&["192.168.1.7/24"]).unwrap().entries(),
+            vec!["192.168.1.0/24".to_string()]
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! 负载均衡模块
+//!
+//! 在故障转移队列中按策略挑选首选供应商，其余供应商保持队列顺序作为后备。
+//! 分流时同一会话的请求会粘滞到同一供应商，直到其不可用或会话闲置过期。
+
+use crate::provider::Provider;
+use crate::proxy::types::LoadBalanceStrategy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 延迟指数移动平均的平滑系数
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+/// 会话粘滞的闲置过期时间
+const STICKY_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+/// 粘滞会话表超过该数量时清理过期条目
+const STICKY_SESSION_PRUNE_THRESHOLD: usize = 1024;
+
+/// 负载均衡运行时状态，key 格式与熔断器一致: "app_type:provider_id"
+#[derive(Default)]
+pub struct LoadBalancer {
+    /// 各应用最近一次选路使用的策略
+    strategies: Mutex<HashMap<String, LoadBalanceStrategy>>,
+    /// 轮询计数器，按应用区分
+    round_robin: Mutex<HashMap<String, usize>>,
+    /// 平滑加权轮询的当前权重
+    current_weights: Mutex<HashMap<String, i64>>,
+    /// 近期平均延迟（毫秒）
+    latencies: Mutex<HashMap<String, f64>>,
+    /// 进行中的请求数
+    outstanding: Arc<Mutex<HashMap<String, usize>>>,
+    /// 会话粘滞绑定，key 为 "app_type:session_id"，值为供应商 ID 与最近使用时间
+    sticky_sessions: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+/// 进行中请求的计数守卫，释放时自动减一
+pub struct OutstandingGuard {
+    key: String,
+    outstanding: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Drop for OutstandingGuard {
+    fn drop(&mut self) {
+        let mut outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = outstanding.get_mut(&self.key) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+impl LoadBalancer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按策略重排可用供应商：首选供应商移到最前，其余保持原有顺序
+    ///
+    /// 分流策略下，带会话 ID 的请求优先沿用该会话上次的供应商（仍在可用列表中时）
+    pub fn order(
+        &self,
+        strategy: LoadBalanceStrategy,
+        app_type: &str,
+        session_id: Option<&str>,
+        mut providers: Vec<Provider>,
+    ) -> Vec<Provider> {
+        self.strategies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(app_type.to_string(), strategy);
+        if providers.len() < 2 {
+            return providers;
+        }
+
+        let session_key = session_id
+            .filter(|_| strategy != LoadBalanceStrategy::Failover)
+            .map(|session| key(app_type, session));
+        if let Some(index) = session_key
+            .as_deref()
+            .and_then(|k| self.sticky_index(k, &providers))
+        {
+            let preferred = providers.remove(index);
+            providers.insert(0, preferred);
+            return providers;
+        }
+
+        let index = match strategy {
+            LoadBalanceStrategy::Failover => 0,
+            LoadBalanceStrategy::RoundRobin => self.next_round_robin(app_type, providers.len()),
+            LoadBalanceStrategy::Weighted => self.next_weighted(app_type, &providers),
+            LoadBalanceStrategy::WeightedRandom => {
+                let roll = uuid::Uuid::new_v4().as_u128() as u64;
+                pick_weighted(&weights(&providers), roll)
+            }
+            LoadBalanceStrategy::LeastLatency => {
+                let latencies = self.latencies.lock().unwrap_or_else(|e| e.into_inner());
+                // 尚无样本的供应商视为 0ms，优先获得流量以建立基线
+                min_index(&providers, |p| {
+                    latencies.get(&key(app_type, &p.id)).copied().unwrap_or(0.0)
+                })
+            }
+            LoadBalanceStrategy::LeastOutstanding => {
+                let outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+                min_index(&providers, |p| {
+                    outstanding.get(&key(app_type, &p.id)).copied().unwrap_or(0) as f64
+                })
+            }
+        };
+
+        let preferred = providers.remove(index);
+        if let Some(session_key) = session_key {
+            self.bind_session(session_key, &preferred.id);
+        }
+        providers.insert(0, preferred);
+        providers
+    }
+
+    /// 查找会话绑定的供应商在可用列表中的位置，命中时刷新使用时间
+    fn sticky_index(&self, session_key: &str, providers: &[Provider]) -> Option<usize> {
+        let mut sessions = self
+            .sticky_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let (provider_id, last_used) = sessions.get_mut(session_key)?;
+        if last_used.elapsed() > STICKY_SESSION_TTL {
+            sessions.remove(session_key);
+            return None;
+        }
+        let index = providers.iter().position(|p| &p.id == provider_id)?;
+        *last_used = Instant::now();
+        Some(index)
+    }
+
+    fn bind_session(&self, session_key: String, provider_id: &str) {
+        let mut sessions = self
+            .sticky_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if sessions.len() >= STICKY_SESSION_PRUNE_THRESHOLD {
+            sessions.retain(|_, (_, last_used)| last_used.elapsed() <= STICKY_SESSION_TTL);
+        }
+        sessions.insert(session_key, (provider_id.to_string(), Instant::now()));
+    }
+
+    /// 队列配置变化后调用：清理已移出队列的供应商的延迟与会话绑定，
+    /// 并让平滑加权轮询按新权重重新开始
+    pub fn retain_providers(&self, app_type: &str, provider_ids: &[String]) {
+        let prefix = key(app_type, "");
+        let removed = |k: &str| {
+            k.strip_prefix(&prefix)
+                .is_some_and(|id| !provider_ids.iter().any(|p| p == id))
+        };
+        self.current_weights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, _| !k.starts_with(&prefix));
+        self.latencies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, _| !removed(k));
+        self.sticky_sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, (id, _)| !k.starts_with(&prefix) || provider_ids.contains(id));
+    }
+
+    /// 该应用最近一次选路是否在多个供应商间分流（而非单纯故障转移）
+    pub fn is_balancing(&self, app_type: &str) -> bool {
+        self.strategies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(app_type)
+            .is_some_and(|s| *s != LoadBalanceStrategy::Failover)
+    }
+
+    /// 记录一次请求延迟，更新该供应商的平均延迟
+    pub fn record_latency(&self, app_type: &str, provider_id: &str, latency_ms: u64) {
+        let mut latencies = self.latencies.lock().unwrap_or_else(|e| e.into_inner());
+        let average = latencies
+            .entry(key(app_type, provider_id))
+            .or_insert(latency_ms as f64);
+        *average = LATENCY_EWMA_ALPHA * latency_ms as f64 + (1.0 - LATENCY_EWMA_ALPHA) * *average;
+    }
+
+    /// 进行中请求数未达到 `max_concurrent` 时开始一次请求，否则返回 None；
+    /// 返回的守卫在请求结束（被丢弃）时减少计数
+    pub fn try_begin_request(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        max_concurrent: Option<u32>,
+    ) -> Option<OutstandingGuard> {
+        let key = key(app_type, provider_id);
+        let mut outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        let count = outstanding.entry(key.clone()).or_insert(0);
+        if max_concurrent.is_some_and(|max| *count >= max as usize) {
+            return None;
+        }
+        *count += 1;
+        Some(OutstandingGuard {
+            key,
+            outstanding: self.outstanding.clone(),
+        })
+    }
+
+    /// 该供应商当前进行中的请求数
+    pub fn in_flight(&self, app_type: &str, provider_id: &str) -> usize {
+        self.outstanding
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key(app_type, provider_id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn next_round_robin(&self, app_type: &str, len: usize) -> usize {
+        let mut counters = self.round_robin.lock().unwrap_or_else(|e| e.into_inner());
+        let counter = counters.entry(app_type.to_string()).or_insert(0);
+        let index = *counter % len;
+        *counter = counter.wrapping_add(1);
+        index
+    }
+
+    /// 平滑加权轮询（nginx 算法）
+    fn next_weighted(&self, app_type: &str, providers: &[Provider]) -> usize {
+        let weights: Vec<i64> = weights(providers).into_iter().map(i64::from).collect();
+        let total: i64 = weights.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+
+        let mut current = self
+            .current_weights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut best = 0;
+        let mut best_weight = i64::MIN;
+        for (i, (provider, weight)) in providers.iter().zip(&weights).enumerate() {
+            let value = current.entry(key(app_type, &provider.id)).or_insert(0);
+            *value += weight;
+            if *value > best_weight {
+                best = i;
+                best_weight = *value;
+            }
+        }
+        if let Some(value) = current.get_mut(&key(app_type, &providers[best].id)) {
+            *value -= total;
+        }
+        best
+    }
+}
+
+/// 各供应商的权重：任一供应商配置了权重时使用配置值（未配置的记为 1），
+/// 否则按队列位置递减，n 个供应商中第一个权重为 n
+fn weights(providers: &[Provider]) -> Vec<u32> {
+    if providers.iter().any(|p| p.weight.is_some()) {
+        providers.iter().map(|p| p.weight.unwrap_or(1)).collect()
+    } else {
+        (1..=providers.len() as u32).rev().collect()
+    }
+}
+
+/// 用随机数 `roll` 按权重挑选下标，权重全为 0 时选第一个
+pub(crate) fn pick_weighted(weights: &[u32], roll: u64) -> usize {
+    let total: u64 = weights.iter().map(|w| u64::from(*w)).sum();
+    if total == 0 {
+        return 0;
+    }
+    let mut remaining = roll % total;
+    for (i, weight) in weights.iter().enumerate() {
+        let weight = u64::from(*weight);
+        if remaining < weight {
+            return i;
+        }
+        remaining -= weight;
+    }
+    0
+}
+
+fn key(app_type: &str, provider_id: &str) -> String {
+    format!("{app_type}:{provider_id}")
+}
+
+/// 取度量值最小的下标，相同时保留队列中靠前的
+fn min_index(providers: &[Provider], metric: impl Fn(&Provider) -> f64) -> usize {
+    let mut best = 0;
+    let mut best_value = f64::INFINITY;
+    for (i, provider) in providers.iter().enumerate() {
+        let value = metric(provider);
+        if value < best_value {
+            best = i;
+            best_value = value;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn providers(ids: &[&str]) -> Vec<Provider> {
+        ids.iter()
+            .map(|id| Provider::with_id(id.to_string(), id.to_string(), json!({}), None))
+            .collect()
+    }
+
+    fn first(balancer: &LoadBalancer, strategy: LoadBalanceStrategy, ids: &[&str]) -> String {
+        balancer.order(strategy, "claude", None, providers(ids))[0]
+            .id
+            .clone()
+    }
+
+    #[test]
+    fn round_robin_rotates_preferred_provider() {
+        let balancer = LoadBalancer::new();
+        let picks: Vec<String> = (0..4)
+            .map(|_| first(&balancer, LoadBalanceStrategy::RoundRobin, &["a", "b", "c"]))
+            .collect();
+        assert_eq!(picks, vec!["a", "b", "c", "a"]);
+
+        // 其余供应商保持队列顺序作为后备
+        let order: Vec<String> = balancer
+            .order(
+                LoadBalanceStrategy::RoundRobin,
+                "claude",
+                None,
+                providers(&["a", "b", "c"]),
+            )
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(order, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn weighted_favors_providers_earlier_in_queue() {
+        let balancer = LoadBalancer::new();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for _ in 0..6 {
+            *counts
+                .entry(first(
+                    &balancer,
+                    LoadBalanceStrategy::Weighted,
+                    &["a", "b", "c"],
+                ))
+                .or_default() += 1;
+        }
+        assert_eq!(counts["a"], 3);
+        assert_eq!(counts["b"], 2);
+        assert_eq!(counts["c"], 1);
+    }
+
+    #[test]
+    fn configured_weights_split_traffic() {
+        let mut chain = providers(&["relay", "official"]);
+        chain[0].weight = Some(80);
+        chain[1].weight = Some(20);
+        assert_eq!(weights(&chain), vec![80, 20]);
+
+        assert_eq!(pick_weighted(&[80, 20], 0), 0);
+        assert_eq!(pick_weighted(&[80, 20], 79), 0);
+        assert_eq!(pick_weighted(&[80, 20], 80), 1);
+        assert_eq!(pick_weighted(&[80, 20], 199), 1);
+        assert_eq!(pick_weighted(&[0, 0], 5), 0);
+
+        let balancer = LoadBalancer::new();
+        let relay = (0..10)
+            .filter(|_| {
+                balancer.order(LoadBalanceStrategy::Weighted, "claude", None, chain.clone())[0].id
+                    == "relay"
+            })
+            .count();
+        assert_eq!(relay, 8);
+    }
+
+    #[test]
+    fn sessions_stick_to_provider_while_available() {
+        let balancer = LoadBalancer::new();
+        let pick = |session: &str, ids: &[&str]| {
+            balancer.order(
+                LoadBalanceStrategy::RoundRobin,
+                "claude",
+                Some(session),
+                providers(ids),
+            )[0]
+            .id
+            .clone()
+        };
+
+        assert_eq!(pick("s1", &["a", "b"]), "a");
+        assert_eq!(pick("s2", &["a", "b"]), "b");
+        assert_eq!(pick("s1", &["a", "b"]), "a");
+        assert_eq!(pick("s2", &["a", "b"]), "b");
+
+        // 绑定的供应商不可用（如熔断）时改选并重新绑定
+        assert_eq!(pick("s1", &["b", "c"]), "b");
+        assert_eq!(pick("s1", &["a", "b", "c"]), "b");
+
+        // 故障转移模式不做粘滞
+        let order = balancer.order(
+            LoadBalanceStrategy::Failover,
+            "claude",
+            Some("s2"),
+            providers(&["a", "b"]),
+        );
+        assert_eq!(order[0].id, "a");
+    }
+
+    #[test]
+    fn concurrency_cap_rejects_until_a_request_finishes() {
+        let balancer = LoadBalancer::new();
+        let first = balancer.try_begin_request("claude", "a", Some(2)).unwrap();
+        let _second = balancer.try_begin_request("claude", "a", Some(2)).unwrap();
+        assert!(balancer.try_begin_request("claude", "a", Some(2)).is_none());
+        assert_eq!(balancer.in_flight("claude", "a"), 2);
+        // 其他应用下的同 ID 供应商独立计数
+        assert!(balancer.try_begin_request("codex", "a", Some(2)).is_some());
+
+        drop(first);
+        assert_eq!(balancer.in_flight("claude", "a"), 1);
+        assert!(balancer.try_begin_request("claude", "a", Some(2)).is_some());
+    }
+
+    #[test]
+    fn least_latency_and_outstanding_pick_lightest_provider() {
+        let balancer = LoadBalancer::new();
+        balancer.record_latency("claude", "a", 900);
+        balancer.record_latency("claude", "b", 200);
+        assert_eq!(
+            first(&balancer, LoadBalanceStrategy::LeastLatency, &["a", "b"]),
+            "b"
+        );
+
+        let guard = balancer.try_begin_request("claude", "a", None).unwrap();
+        assert_eq!(
+            first(
+                &balancer,
+                LoadBalanceStrategy::LeastOutstanding,
+                &["a", "b"]
+            ),
+            "b"
+        );
+        drop(guard);
+        assert_eq!(
+            first(
+                &balancer,
+                LoadBalanceStrategy::LeastOutstanding,
+                &["a", "b"]
+            ),
+            "a"
+        );
+    }
+}
@@ -2,8 +2,13 @@
 //!
 //! 提供本地HTTP代理服务，支持多Provider故障转移和请求透传
 
+pub mod access_log;
+pub mod auth;
 pub mod body_filter;
+pub mod body_rules;
 pub mod circuit_breaker;
+pub mod cors;
+pub mod dns;
 pub mod error;
 pub mod error_mapper;
 pub(crate) mod failover_switch;
@@ -11,17 +16,34 @@ mod forwarder;
 pub mod handler_config;
 pub mod handler_context;
 mod handlers;
-mod health;
+pub mod header_rules;
+pub mod health;
+pub mod hedging;
+pub mod ip_allowlist;
+pub mod load_balancer;
 pub mod model_mapper;
+pub mod outlier;
 pub mod provider_router;
 pub mod providers;
+pub mod rate_limiter;
+pub mod replay;
+pub mod request_log;
+pub mod response_cache;
 pub mod response_handler;
 pub mod response_processor;
 pub(crate) mod server;
 pub mod session;
+pub mod shadow;
+pub mod size_limits;
+pub mod stream_failover;
+pub mod switch_back;
+pub mod tls;
 pub(crate) mod types;
+pub mod unix_socket;
+pub mod upstream_proxy;
 pub mod url_router;
 pub mod usage;
+pub mod webhook;
 
 // 公开导出给外部使用（commands, services等模块需要）
 #[allow(unused_imports)]
@@ -33,13 +55,21 @@ pub use error::ProxyError;
 #[allow(unused_imports)]
 pub use provider_router::ProviderRouter;
 #[allow(unused_imports)]
+pub use request_log::{
+    RequestCapture, RequestLog, RequestLogEntry, RequestLogFilter, StatusFilter,
+};
+#[allow(unused_imports)]
 pub use response_handler::{NonStreamHandler, ResponseType, StreamHandler};
 #[allow(unused_imports)]
 pub use session::{
     extract_session_id, ClientFormat, ProxySession, SessionIdResult, SessionIdSource,
 };
 #[allow(unused_imports)]
-pub use types::{ProxyConfig, ProxyServerInfo, ProxyStatus};
+pub use types::{
+    ClientRateLimit, EndpointLatencySample, HybridModeConfig, LoadBalanceStrategy,
+    ProviderEndpoint, ProxyConfig, ProxyServerInfo, ProxyStatus, ProxyTakeoverStatus,
+    RateLimitStats,
+};
 #[allow(unused_imports)]
 pub use url_router::UrlRouter;
 
@@ -1,6 +1,7 @@
 //! 模型映射模块
 //!
-//! 在请求转发前，根据 Provider 配置替换请求中的模型名称
+//! 在请求转发前，根据 Provider 配置替换请求中的模型名称；
+//! 供应商模型别名替换后，响应中的模型名会还原为客户端请求的名称
 
 use crate::provider::Provider;
 use serde_json::Value;
@@ -104,13 +105,51 @@ pub fn has_thinking_enabled(body: &Value) -> bool {
         == Some("enabled")
 }
 
+/// 查找供应商为该模型配置的别名（精确匹配客户端模型 ID）
+pub fn model_alias(provider: &Provider, model: &str) -> Option<String> {
+    provider
+        .meta
+        .as_ref()?
+        .model_aliases
+        .get(model)
+        .filter(|alias| !alias.is_empty() && alias.as_str() != model)
+        .cloned()
+}
+
+/// 将响应 JSON 中的上游模型名还原为客户端请求的模型名
+///
+/// 覆盖顶层 `model`、Claude 流式 `message_start` 的 `message.model`
+/// 以及 Codex 流式事件的 `response.model`
+pub fn restore_response_model(value: &mut Value, upstream: &str, original: &str) -> bool {
+    let mut changed = false;
+    for pointer in ["/model", "/message/model", "/response/model"] {
+        if let Some(model) = value.pointer_mut(pointer) {
+            if model.as_str() == Some(upstream) {
+                *model = Value::String(original.to_string());
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
 /// 对请求体应用模型映射
 ///
+/// 供应商模型别名优先于 env 中的模型映射。
 /// 返回 (映射后的请求体, 原始模型名, 映射后模型名)
 pub fn apply_model_mapping(
     mut body: Value,
     provider: &Provider,
 ) -> (Value, Option<String>, Option<String>) {
+    let original_model = body.get("model").and_then(|m| m.as_str()).map(String::from);
+    if let Some(original) = original_model {
+        if let Some(alias) = model_alias(provider, &original) {
+            log::info!("[ModelMapper] 模型别名: {original} → {alias}");
+            body["model"] = serde_json::json!(alias);
+            return (body, Some(original), Some(alias));
+        }
+    }
+
     let mapping = ModelMapping::from_provider(provider);
 
     // 如果没有配置映射，直接返回
@@ -163,6 +202,8 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         }
     }
 
@@ -180,6 +221,8 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         }
     }
 
@@ -201,6 +244,8 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         }
     }
 
@@ -308,4 +353,43 @@ mod tests {
         assert_eq!(result["model"], "sonnet-mapped");
         assert_eq!(mapped, Some("sonnet-mapped".to_string()));
     }
+
+    #[test]
+    fn test_model_alias_takes_precedence_and_is_restored() {
+        let mut provider = create_provider_with_mapping();
+        provider.meta = Some(crate::provider::ProviderMeta {
+            model_aliases: [("claude-sonnet-4-5".to_string(), "relay/sonnet".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        });
+
+        let body = json!({"model": "claude-sonnet-4-5"});
+        let (result, original, mapped) = apply_model_mapping(body, &provider);
+        assert_eq!(result["model"], "relay/sonnet");
+        assert_eq!(original, Some("claude-sonnet-4-5".to_string()));
+        assert_eq!(mapped, Some("relay/sonnet".to_string()));
+
+        // 未配置别名的模型仍走 env 映射
+        let (result, _, _) = apply_model_mapping(json!({"model": "claude-opus-4-5"}), &provider);
+        assert_eq!(result["model"], "opus-mapped");
+
+        let mut event = json!({
+            "type": "message_start",
+            "message": {"model": "relay/sonnet"}
+        });
+        assert!(restore_response_model(
+            &mut event,
+            "relay/sonnet",
+            "claude-sonnet-4-5"
+        ));
+        assert_eq!(event["message"]["model"], "claude-sonnet-4-5");
+
+        let mut other = json!({"model": "something-else"});
+        assert!(!restore_response_model(
+            &mut other,
+            "relay/sonnet",
+            "claude-sonnet-4-5"
+        ));
+    }
 }
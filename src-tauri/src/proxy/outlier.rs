@@ -0,0 +1,351 @@
+//! 端点离群检测
+//!
+//! 熔断器只处理连续失败这类硬故障。离群检测按供应商内各端点最近的真实请求结果做被动统计：
+//! 错误率明显高于同一供应商的其他端点，或平均延迟远高于其他端点的中位数时，暂时把该端点
+//! 移出路由（参考 Envoy 的 outlier detection）。同一端点反复被剔除时剔除时长按次数增长，
+//! 同一供应商同时被剔除的端点数量受比例上限约束。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// 每个端点参与统计的最近请求数
+const WINDOW: usize = 20;
+
+/// 离群检测设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlierDetectionConfig {
+    pub enabled: bool,
+    /// 端点参与比较前至少需要的请求数
+    pub min_requests: u32,
+    /// 错误率比其他端点平均值高出多少（0-1）视为离群
+    pub error_rate_margin: f64,
+    /// 平均延迟达到其他端点中位数的多少倍视为离群
+    pub latency_factor: f64,
+    /// 基础剔除时长（秒），第 n 次剔除持续 n 倍
+    pub base_ejection_seconds: u64,
+    /// 同一供应商同时被剔除的端点比例上限（百分比）
+    pub max_ejection_percent: u32,
+}
+
+impl Default for OutlierDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_requests: 10,
+            error_rate_margin: 0.3,
+            latency_factor: 3.0,
+            base_ejection_seconds: 30,
+            max_ejection_percent: 50,
+        }
+    }
+}
+
+/// 端点最近一次被剔除的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointEjection {
+    /// 剔除原因，例如 "error rate 45% vs peers 5%"
+    pub reason: String,
+    /// 剔除时间（毫秒时间戳）
+    pub ejected_at: i64,
+    /// 恢复路由的时间（毫秒时间戳）
+    pub until: i64,
+    /// 端点恢复稳定前累计的剔除次数
+    pub count: u32,
+}
+
+impl EndpointEjection {
+    /// 在 `now`（毫秒时间戳）时是否仍处于剔除期
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.until
+    }
+}
+
+#[derive(Default)]
+struct EndpointStats {
+    /// 最近的请求结果：(是否成功, 延迟毫秒)
+    outcomes: VecDeque<(bool, u64)>,
+    ejection: Option<EndpointEjection>,
+    /// 端点恢复稳定前累计的剔除次数
+    ejections: u32,
+}
+
+impl EndpointStats {
+    fn error_rate(&self) -> f64 {
+        let failures = self.outcomes.iter().filter(|(ok, _)| !ok).count();
+        failures as f64 / self.outcomes.len().max(1) as f64
+    }
+
+    /// 成功请求的平均延迟
+    fn mean_latency(&self) -> Option<f64> {
+        let latencies: Vec<u64> = self
+            .outcomes
+            .iter()
+            .filter(|(ok, _)| *ok)
+            .map(|(_, ms)| *ms)
+            .collect();
+        (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<u64>() as f64 / latencies.len() as f64)
+    }
+
+    fn is_ejected(&self, now: i64) -> bool {
+        self.ejection.as_ref().is_some_and(|e| e.is_active(now))
+    }
+}
+
+/// 按 (应用, 供应商) 分组统计各端点的请求结果
+#[derive(Default)]
+pub struct OutlierDetector {
+    endpoints: Mutex<HashMap<(String, String), HashMap<String, EndpointStats>>>,
+}
+
+impl OutlierDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次发往端点的请求结果；该端点因此被剔除时返回剔除记录
+    pub fn record(
+        &self,
+        config: &OutlierDetectionConfig,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        success: bool,
+        latency_ms: u64,
+    ) -> Option<EndpointEjection> {
+        self.record_at(
+            config,
+            app_type,
+            provider_id,
+            url,
+            success,
+            latency_ms,
+            chrono::Utc::now().timestamp_millis(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record_at(
+        &self,
+        config: &OutlierDetectionConfig,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        success: bool,
+        latency_ms: u64,
+        now: i64,
+    ) -> Option<EndpointEjection> {
+        let mut all = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let group = all
+            .entry((app_type.to_string(), provider_id.to_string()))
+            .or_default();
+        let key = normalize(url);
+        let stats = group.entry(key.clone()).or_default();
+        if stats.outcomes.len() >= WINDOW {
+            stats.outcomes.pop_front();
+        }
+        stats.outcomes.push_back((success, latency_ms));
+        if !config.enabled || stats.is_ejected(now) {
+            return None;
+        }
+
+        let reason = outlier_reason(config, group, &key, now);
+        let stats = group.get_mut(&key)?;
+        let Some(reason) = reason else {
+            // 恢复后稳定运行满一个窗口，剔除时长重新从基础值开始
+            if stats.outcomes.len() >= WINDOW {
+                stats.ejections = 0;
+            }
+            return None;
+        };
+
+        let total = group.len() as u32;
+        let ejected = group.values().filter(|s| s.is_ejected(now)).count() as u32;
+        if (ejected + 1) * 100 > total * config.max_ejection_percent {
+            log::debug!(
+                "[{app_type}] 端点 {key} 离群（{reason}），但已达到剔除比例上限，保留在路由中"
+            );
+            return None;
+        }
+
+        let stats = group.get_mut(&key)?;
+        stats.ejections += 1;
+        stats.outcomes.clear();
+        let duration_ms =
+            (config.base_ejection_seconds * 1000).saturating_mul(stats.ejections as u64);
+        let ejection = EndpointEjection {
+            reason,
+            ejected_at: now,
+            until: now.saturating_add(duration_ms as i64),
+            count: stats.ejections,
+        };
+        log::warn!(
+            "[{app_type}] 供应商 {provider_id} 的端点 {key} 离群（{}），暂停路由 {}s（第 {} 次）",
+            ejection.reason,
+            duration_ms / 1000,
+            ejection.count
+        );
+        stats.ejection = Some(ejection.clone());
+        Some(ejection)
+    }
+
+    /// 端点当前是否被剔除
+    pub fn is_ejected(&self, app_type: &str, provider_id: &str, url: &str) -> bool {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(app_type.to_string(), provider_id.to_string()))
+            .and_then(|group| group.get(&normalize(url)))
+            .is_some_and(|stats| stats.is_ejected(now))
+    }
+
+    /// 供应商各端点最近一次的剔除记录，key 为去掉末尾 `/` 的 URL
+    pub fn ejections(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> HashMap<String, EndpointEjection> {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(app_type.to_string(), provider_id.to_string()))
+            .map(|group| {
+                group
+                    .iter()
+                    .filter_map(|(url, stats)| Some((url.clone(), stats.ejection.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 与同一供应商中未被剔除、样本充足的其他端点比较，返回离群原因
+fn outlier_reason(
+    config: &OutlierDetectionConfig,
+    group: &HashMap<String, EndpointStats>,
+    key: &str,
+    now: i64,
+) -> Option<String> {
+    let enough = |s: &EndpointStats| s.outcomes.len() >= config.min_requests.max(1) as usize;
+    let stats = group.get(key).filter(|s| enough(s))?;
+    let peers: Vec<&EndpointStats> = group
+        .iter()
+        .filter(|(url, s)| url.as_str() != key && enough(s) && !s.is_ejected(now))
+        .map(|(_, s)| s)
+        .collect();
+    if peers.is_empty() {
+        return None;
+    }
+
+    let error_rate = stats.error_rate();
+    let peer_error_rate = peers.iter().map(|s| s.error_rate()).sum::<f64>() / peers.len() as f64;
+    if error_rate - peer_error_rate >= config.error_rate_margin {
+        return Some(format!(
+            "error rate {:.0}% vs peers {:.0}%",
+            error_rate * 100.0,
+            peer_error_rate * 100.0
+        ));
+    }
+
+    let mut peer_latencies: Vec<f64> = peers.iter().filter_map(|s| s.mean_latency()).collect();
+    if let (Some(latency), false) = (stats.mean_latency(), peer_latencies.is_empty()) {
+        peer_latencies.sort_by(f64::total_cmp);
+        let median = peer_latencies[peer_latencies.len() / 2];
+        if median > 0.0 && latency >= median * config.latency_factor {
+            return Some(format!("latency {latency:.0}ms vs peers {median:.0}ms"));
+        }
+    }
+    None
+}
+
+fn normalize(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: &str = "https://fast.example.com";
+    const FLAKY: &str = "https://flaky.example.com/";
+
+    fn feed(
+        detector: &OutlierDetector,
+        url: &str,
+        n: usize,
+        success: bool,
+        latency: u64,
+        now: i64,
+    ) {
+        let config = OutlierDetectionConfig::default();
+        for _ in 0..n {
+            detector.record_at(&config, "claude", "p", url, success, latency, now);
+        }
+    }
+
+    #[test]
+    fn endpoint_with_high_error_rate_is_ejected_and_returns_later() {
+        let detector = OutlierDetector::new();
+        let config = OutlierDetectionConfig::default();
+        feed(&detector, FAST, 10, true, 200, 0);
+        feed(&detector, FLAKY, 9, false, 200, 0);
+        let ejection = detector
+            .record_at(&config, "claude", "p", FLAKY, false, 200, 0)
+            .expect("flaky endpoint should be ejected");
+        assert_eq!(ejection.count, 1);
+        assert_eq!(ejection.until, 30_000);
+        assert!(ejection.reason.starts_with("error rate 100%"));
+
+        let ejections = detector.ejections("claude", "p");
+        assert!(ejections["https://flaky.example.com"].is_active(29_999));
+        assert!(!ejections["https://flaky.example.com"].is_active(30_000));
+
+        // 恢复后再次离群，剔除时长翻倍
+        feed(&detector, FLAKY, 9, false, 200, 30_000);
+        let again = detector
+            .record_at(&config, "claude", "p", FLAKY, false, 200, 30_000)
+            .unwrap();
+        assert_eq!(again.count, 2);
+        assert_eq!(again.until, 90_000);
+    }
+
+    #[test]
+    fn slow_endpoint_is_ejected_but_never_more_than_the_cap() {
+        let detector = OutlierDetector::new();
+        let config = OutlierDetectionConfig::default();
+        feed(&detector, FAST, 10, true, 100, 0);
+        feed(&detector, FLAKY, 9, true, 400, 0);
+        let ejection = detector
+            .record_at(&config, "claude", "p", FLAKY, true, 400, 0)
+            .unwrap();
+        assert_eq!(ejection.reason, "latency 400ms vs peers 100ms");
+
+        // 三个端点时最多剔除一个：第三个端点随后离群也保留在路由中
+        const OTHER: &str = "https://other.example.com";
+        feed(&detector, OTHER, 10, false, 100, 1);
+        assert_eq!(detector.ejections("claude", "p").len(), 1);
+        assert!(!detector.is_ejected("claude", "p", OTHER));
+    }
+
+    #[test]
+    fn disabled_or_single_endpoint_is_never_ejected() {
+        let detector = OutlierDetector::new();
+        feed(&detector, FLAKY, 20, false, 200, 0);
+        assert!(detector.ejections("claude", "p").is_empty());
+
+        let disabled = OutlierDetectionConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        feed(&detector, FAST, 10, true, 200, 0);
+        for _ in 0..10 {
+            detector.record_at(&disabled, "claude", "p", FLAKY, false, 200, 0);
+        }
+        assert!(detector.ejections("claude", "p").is_empty());
+    }
+}
@@ -5,37 +5,148 @@
 use crate::database::Database;
 use crate::error::AppError;
 use crate::provider::Provider;
-use crate::proxy::circuit_breaker::{AllowResult, CircuitBreaker, CircuitBreakerConfig};
+use crate::proxy::circuit_breaker::{
+    AllowResult, CircuitBreaker, CircuitBreakerConfig, CircuitState,
+};
+use crate::proxy::load_balancer::{LoadBalancer, OutstandingGuard};
+use crate::proxy::webhook::{WebhookEventKind, WebhookNotifier};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
+/// 参与路由的应用类型
+const APP_TYPES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 同步时用于比较的供应商配置快照
+#[derive(Clone, PartialEq)]
+struct ProviderSnapshot {
+    settings_config: String,
+    in_failover_queue: bool,
+}
+
 /// 供应商路由器
 pub struct ProviderRouter {
     /// 数据库连接
     db: Arc<Database>,
     /// 熔断器管理器 - key 格式: "app_type:provider_id"
     circuit_breakers: Arc<RwLock<HashMap<String, Arc<CircuitBreaker>>>>,
+    /// 故障转移队列的负载均衡状态
+    balancer: Arc<LoadBalancer>,
+    /// 运行时状态已同步到的路由配置版本
+    synced_version: AtomicU64,
+    /// 上次同步时的供应商快照，key 格式同熔断器
+    snapshots: Mutex<HashMap<String, ProviderSnapshot>>,
+    /// 熔断器打开时的事件通知
+    webhooks: Option<Arc<WebhookNotifier>>,
 }
 
 impl ProviderRouter {
     /// 创建新的供应商路由器
     pub fn new(db: Arc<Database>) -> Self {
         Self {
+            synced_version: AtomicU64::new(db.config_version()),
+            snapshots: Mutex::new(snapshot_providers(&db)),
             db,
             circuit_breakers: Arc::new(RwLock::new(HashMap::new())),
+            balancer: Arc::new(LoadBalancer::new()),
+            webhooks: None,
         }
     }
 
+    /// 熔断器打开时发送 Webhook 通知
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookNotifier>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// 当前路由配置版本，转发器据此判断请求期间配置是否发生变化
+    pub fn config_version(&self) -> u64 {
+        self.db.config_version()
+    }
+
+    /// 路由配置版本变化后同步运行时状态，无需重启代理，进行中的请求不受影响
+    ///
+    /// - 已删除或配置（凭据、端点等）发生变化的供应商：重置熔断器，旧配置下的失败不再计入
+    /// - 清理负载均衡中已移出队列的供应商状态，平滑加权轮询按新权重重新开始
+    /// - 熔断参数（应用默认值或供应商覆盖项）变化：应用到已有熔断器
+    async fn sync_config(&self) {
+        let version = self.db.config_version();
+        if self.synced_version.swap(version, Ordering::AcqRel) == version {
+            return;
+        }
+        let current = snapshot_providers(&self.db);
+        let previous = std::mem::replace(
+            &mut *self.snapshots.lock().unwrap_or_else(|e| e.into_inner()),
+            current.clone(),
+        );
+
+        let mut breakers = self.circuit_breakers.write().await;
+        breakers.retain(|key, _| {
+            let unchanged = current.get(key).is_some_and(|snapshot| {
+                previous
+                    .get(key)
+                    .is_none_or(|old| old.settings_config == snapshot.settings_config)
+            });
+            if !unchanged {
+                log::info!("[{key}] 供应商配置已变更，重置熔断器");
+            }
+            unchanged
+        });
+        for app_type in APP_TYPES {
+            let prefix = format!("{app_type}:");
+            for (key, breaker) in breakers.iter().filter(|(k, _)| k.starts_with(&prefix)) {
+                let config = self.breaker_config(app_type, &key[prefix.len()..]).await;
+                breaker.update_config(config).await;
+            }
+
+            let queue: Vec<String> = current
+                .iter()
+                .filter(|(k, s)| k.starts_with(&prefix) && s.in_failover_queue)
+                .map(|(k, _)| k[prefix.len()..].to_string())
+                .collect();
+            self.balancer.retain_providers(app_type, &queue);
+        }
+        log::info!("路由配置已更新（版本 {version}），运行时状态已同步");
+    }
+
+    /// 请求期间配置发生变化时重新读取供应商（例如凭据已更新）
+    ///
+    /// 供应商已删除，或故障转移开启时已移出队列，返回 None
+    pub async fn reload_provider(&self, app_type: &str, provider: &Provider) -> Option<Provider> {
+        let latest = match self.db.get_provider_by_id(&provider.id, app_type) {
+            Ok(latest) => latest?,
+            Err(e) => {
+                log::warn!("[{app_type}] 重新读取供应商 {} 失败: {e}", provider.id);
+                return Some(provider.clone());
+            }
+        };
+        let failover_enabled = self
+            .db
+            .get_proxy_config_for_app(app_type)
+            .await
+            .is_ok_and(|config| config.auto_failover_enabled);
+        (!failover_enabled || latest.in_failover_queue).then_some(latest)
+    }
+
     /// 选择可用的供应商（支持故障转移）
     ///
     /// 返回按优先级排序的可用供应商列表：
     /// - 故障转移关闭时：仅返回当前供应商
-    /// - 故障转移开启时：完全按照故障转移队列顺序返回，忽略当前供应商设置
-    pub async fn select_providers(&self, app_type: &str) -> Result<Vec<Provider>, AppError> {
+    /// - 故障转移开启时：按故障转移队列顺序返回，忽略当前供应商设置；
+    ///   配置了负载均衡策略时，由策略挑选的首选供应商排在最前；
+    ///   同一会话（`session_id`）在其供应商可用期间保持不变
+    pub async fn select_providers(
+        &self,
+        app_type: &str,
+        session_id: Option<&str>,
+    ) -> Result<Vec<Provider>, AppError> {
+        self.sync_config().await;
+
         let mut result = Vec::new();
         let mut total_providers = 0usize;
         let mut circuit_open_count = 0usize;
+        let mut over_budget_count = 0usize;
 
         // 检查该应用的自动故障转移开关是否开启（从 proxy_config 表读取）
         let auto_failover_enabled = match self.db.get_proxy_config_for_app(app_type).await {
@@ -62,6 +173,16 @@ impl ProviderRouter {
             );
 
             for provider in failover_providers {
+                if self.db.is_provider_paused_by_limit(&provider, app_type) {
+                    over_budget_count += 1;
+                    log::warn!(
+                        "[{}] Queue provider {} exceeded its budget, skipping",
+                        app_type,
+                        provider.name
+                    );
+                    continue;
+                }
+
                 // 检查熔断器状态
                 let circuit_key = format!("{}:{}", app_type, provider.id);
                 let breaker = self.get_or_create_circuit_breaker(&circuit_key).await;
@@ -93,6 +214,16 @@ impl ProviderRouter {
                     );
                 }
             }
+
+            let strategy = self
+                .db
+                .get_load_balance_strategy(app_type)
+                .unwrap_or_else(|e| {
+                    log::warn!("[{app_type}] Failed to read load balance strategy: {e}");
+                    Default::default()
+                });
+            result = self.balancer.order(strategy, app_type, session_id, result);
+            log::debug!("[{app_type}] Load balance strategy: {}", strategy.as_str());
         } else {
             // 故障转移关闭：仅使用当前供应商，跳过熔断器检查
             // 原因：单 Provider 场景下，熔断器打开会导致所有请求失败，用户体验差
@@ -100,14 +231,23 @@ impl ProviderRouter {
 
             if let Some(current_id) = self.db.get_current_provider(app_type)? {
                 if let Some(current) = self.db.get_provider_by_id(&current_id, app_type)? {
-                    log::info!(
-                        "[{}] Current provider: {} ({})",
-                        app_type,
-                        current.name,
-                        current.id
-                    );
                     total_providers = 1;
-                    result.push(current);
+                    if self.db.is_provider_paused_by_limit(&current, app_type) {
+                        over_budget_count += 1;
+                        log::warn!(
+                            "[{}] Current provider {} exceeded its budget, routing paused",
+                            app_type,
+                            current.name
+                        );
+                    } else {
+                        log::info!(
+                            "[{}] Current provider: {} ({})",
+                            app_type,
+                            current.name,
+                            current.id
+                        );
+                        result.push(current);
+                    }
                 } else {
                     log::debug!(
                         "[{app_type}] Current provider id {current_id} not found in database"
@@ -119,8 +259,13 @@ impl ProviderRouter {
         }
 
         if result.is_empty() {
-            // 区分两种情况：全部熔断 vs 未配置供应商
-            if total_providers > 0 && circuit_open_count == total_providers {
+            // 区分三种情况：全部超出预算 vs 全部熔断（或部分超预算） vs 未配置供应商
+            if total_providers > 0 && over_budget_count == total_providers {
+                log::warn!("[{app_type}] 所有 {total_providers} 个供应商均已超出预算，路由已暂停");
+                return Err(AppError::AllProvidersOverBudget);
+            } else if total_providers > 0
+                && circuit_open_count + over_budget_count == total_providers
+            {
                 log::warn!("[{app_type}] 所有 {total_providers} 个供应商均已熔断，无可用渠道");
                 return Err(AppError::AllProvidersCircuitOpen);
             } else {
@@ -138,6 +283,33 @@ impl ProviderRouter {
         Ok(result)
     }
 
+    /// 当前是否按负载均衡策略分流（分流时不应把首选供应商视为故障转移）
+    pub fn is_load_balancing(&self, app_type: &str) -> bool {
+        self.balancer.is_balancing(app_type)
+    }
+
+    /// 标记一次发往供应商的请求开始，守卫释放时结束；已达到并发上限时返回 None
+    pub fn try_begin_request(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        max_concurrent: Option<u32>,
+    ) -> Option<OutstandingGuard> {
+        self.balancer
+            .try_begin_request(app_type, provider_id, max_concurrent)
+    }
+
+    /// 供应商当前进行中的请求数
+    pub fn in_flight(&self, provider_id: &str, app_type: &str) -> usize {
+        self.balancer.in_flight(app_type, provider_id)
+    }
+
+    /// 记录供应商的请求延迟，供最低延迟策略使用
+    pub fn record_latency(&self, provider_id: &str, app_type: &str, latency_ms: u64) {
+        self.balancer
+            .record_latency(app_type, provider_id, latency_ms);
+    }
+
     /// 请求执行前获取熔断器“放行许可”
     ///
     /// - Closed：直接放行
@@ -161,9 +333,13 @@ impl ProviderRouter {
         success: bool,
         error_msg: Option<String>,
     ) -> Result<(), AppError> {
-        // 1. 按应用独立获取熔断器配置（用于更新健康状态和判断是否禁用）
-        let failure_threshold = match self.db.get_proxy_config_for_app(app_type).await {
-            Ok(app_config) => app_config.circuit_failure_threshold,
+        // 1. 获取该供应商的熔断器配置（用于更新健康状态和判断是否禁用）
+        let failure_threshold = match self
+            .db
+            .get_provider_circuit_breaker_config(app_type, provider_id)
+            .await
+        {
+            Ok(config) => config.failure_threshold,
             Err(e) => {
                 log::warn!(
                     "Failed to load circuit config for {app_type}, using default threshold: {e}"
@@ -180,12 +356,18 @@ impl ProviderRouter {
             breaker.record_success(used_half_open_permit).await;
             log::debug!("Provider {provider_id} request succeeded");
         } else {
+            let was_open = breaker.get_state().await == CircuitState::Open;
             breaker.record_failure(used_half_open_permit).await;
             log::warn!(
                 "Provider {} request failed: {}",
                 provider_id,
                 error_msg.as_deref().unwrap_or("Unknown error")
             );
+            if !was_open && breaker.get_state().await == CircuitState::Open {
+                if let Some(webhooks) = &self.webhooks {
+                    webhooks.notify_provider(WebhookEventKind::CircuitOpen, app_type, provider_id);
+                }
+            }
         }
 
         // 3. 更新数据库健康状态（使用配置的阈值）
@@ -202,6 +384,25 @@ impl ProviderRouter {
         Ok(())
     }
 
+    /// 记录主动健康检查结果：与真实请求一样计入熔断器，探测成功时熔断中的供应商提前半开
+    pub async fn record_probe_result(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        healthy: bool,
+        error_msg: Option<String>,
+    ) -> Result<(), AppError> {
+        if healthy {
+            let circuit_key = format!("{app_type}:{provider_id}");
+            self.get_or_create_circuit_breaker(&circuit_key)
+                .await
+                .probe_succeeded()
+                .await;
+        }
+        self.record_result(provider_id, app_type, false, healthy, error_msg)
+            .await
+    }
+
     /// 重置熔断器（手动恢复）
     pub async fn reset_circuit_breaker(&self, circuit_key: &str) {
         let breakers = self.circuit_breakers.read().await;
@@ -220,20 +421,43 @@ impl ProviderRouter {
     /// 更新所有熔断器的配置（热更新）
     ///
     /// 当用户在 UI 中修改熔断器配置后调用此方法，
-    /// 所有现有的熔断器会立即使用新配置
+    /// 所有现有的熔断器会立即使用新配置；设置了覆盖项的供应商仍保留自己的参数
     pub async fn update_all_configs(&self, config: CircuitBreakerConfig) {
         let breakers = self.circuit_breakers.read().await;
         let count = breakers.len();
 
-        for breaker in breakers.values() {
-            breaker.update_config(config.clone()).await;
+        for (key, breaker) in breakers.iter() {
+            let tuning = key.split_once(':').and_then(|(app_type, provider_id)| {
+                self.db
+                    .get_provider_by_id(provider_id, app_type)
+                    .ok()
+                    .flatten()
+                    .and_then(|p| p.meta)
+                    .and_then(|m| m.circuit_breaker)
+            });
+            let config = match tuning {
+                Some(tuning) => tuning.apply(config.clone()),
+                None => config.clone(),
+            };
+            breaker.update_config(config).await;
         }
 
         log::info!("已更新 {count} 个熔断器的配置");
     }
 
+    /// 统计当前处于打开（熔断）状态的熔断器数量
+    pub async fn count_open_breakers(&self) -> usize {
+        let breakers = self.circuit_breakers.read().await;
+        let mut count = 0;
+        for breaker in breakers.values() {
+            if breaker.get_state().await == CircuitState::Open {
+                count += 1;
+            }
+        }
+        count
+    }
+
     /// 获取熔断器状态
-    #[allow(dead_code)]
     pub async fn get_circuit_breaker_stats(
         &self,
         provider_id: &str,
@@ -267,42 +491,65 @@ impl ProviderRouter {
             return breaker.clone();
         }
 
-        // 从 key 中提取 app_type (格式: "app_type:provider_id")
-        let app_type = key.split(':').next().unwrap_or("claude");
+        // 从 key 中提取 app_type 与 provider_id (格式: "app_type:provider_id")
+        let (app_type, provider_id) = key.split_once(':').unwrap_or(("claude", key));
+        let config = self.breaker_config(app_type, provider_id).await;
+
+        log::debug!("Creating new circuit breaker for {key} with config: {config:?}");
+
+        let breaker = Arc::new(CircuitBreaker::new(config));
+        breakers.insert(key.to_string(), breaker.clone());
+
+        breaker
+    }
 
-        // 按应用独立读取熔断器配置
-        let config = match self.db.get_proxy_config_for_app(app_type).await {
-            Ok(app_config) => {
+    /// 读取供应商的熔断器配置（应用默认值叠加供应商覆盖项）
+    async fn breaker_config(&self, app_type: &str, provider_id: &str) -> CircuitBreakerConfig {
+        match self
+            .db
+            .get_provider_circuit_breaker_config(app_type, provider_id)
+            .await
+        {
+            Ok(config) => {
                 log::debug!(
-                    "Loading circuit breaker config for {key} (app={app_type}): \
+                    "Loading circuit breaker config (app={app_type}, provider={provider_id}): \
                     failure_threshold={}, success_threshold={}, timeout={}s",
-                    app_config.circuit_failure_threshold,
-                    app_config.circuit_success_threshold,
-                    app_config.circuit_timeout_seconds
+                    config.failure_threshold,
+                    config.success_threshold,
+                    config.timeout_seconds
                 );
-                crate::proxy::circuit_breaker::CircuitBreakerConfig {
-                    failure_threshold: app_config.circuit_failure_threshold,
-                    success_threshold: app_config.circuit_success_threshold,
-                    timeout_seconds: app_config.circuit_timeout_seconds as u64,
-                    error_rate_threshold: app_config.circuit_error_rate_threshold,
-                    min_requests: app_config.circuit_min_requests,
-                }
+                config
             }
             Err(e) => {
                 log::warn!(
-                    "Failed to load circuit breaker config for {key} (app={app_type}): {e}, using default"
+                    "Failed to load circuit breaker config (app={app_type}): {e}, using default"
                 );
-                crate::proxy::circuit_breaker::CircuitBreakerConfig::default()
+                CircuitBreakerConfig::default()
             }
-        };
-
-        log::debug!("Creating new circuit breaker for {key} with config: {config:?}");
-
-        let breaker = Arc::new(CircuitBreaker::new(config));
-        breakers.insert(key.to_string(), breaker.clone());
+        }
+    }
+}
 
-        breaker
+/// 读取所有应用的供应商快照
+fn snapshot_providers(db: &Database) -> HashMap<String, ProviderSnapshot> {
+    let mut snapshots = HashMap::new();
+    for app_type in APP_TYPES {
+        match db.get_all_providers(app_type) {
+            Ok(providers) => {
+                for (id, provider) in providers {
+                    snapshots.insert(
+                        format!("{app_type}:{id}"),
+                        ProviderSnapshot {
+                            settings_config: provider.settings_config.to_string(),
+                            in_failover_queue: provider.in_failover_queue,
+                        },
+                    );
+                }
+            }
+            Err(e) => log::warn!("[{app_type}] 读取供应商快照失败: {e}"),
+        }
     }
+    snapshots
 }
 
 #[cfg(test)]
@@ -335,7 +582,7 @@ mod tests {
         db.add_to_failover_queue("claude", "b").unwrap();
 
         let router = ProviderRouter::new(db.clone());
-        let providers = router.select_providers("claude").await.unwrap();
+        let providers = router.select_providers("claude", None).await.unwrap();
 
         assert_eq!(providers.len(), 1);
         assert_eq!(providers[0].id, "a");
@@ -366,7 +613,7 @@ mod tests {
         db.update_proxy_config_for_app(config).await.unwrap();
 
         let router = ProviderRouter::new(db.clone());
-        let providers = router.select_providers("claude").await.unwrap();
+        let providers = router.select_providers("claude", None).await.unwrap();
 
         assert_eq!(providers.len(), 2);
         // 按 sort_index 排序：b(1) 在前，a(2) 在后
@@ -409,9 +656,131 @@ mod tests {
             .await
             .unwrap();
 
-        let providers = router.select_providers("claude").await.unwrap();
+        let providers = router.select_providers("claude", None).await.unwrap();
         assert_eq!(providers.len(), 2);
 
         assert!(router.allow_provider_request("b", "claude").await.allowed);
     }
+
+    #[tokio::test]
+    async fn config_changes_are_picked_up_without_restart() {
+        let db = Arc::new(Database::memory().unwrap());
+        db.update_circuit_breaker_config(&CircuitBreakerConfig {
+            failure_threshold: 1,
+            timeout_seconds: 3600,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        let mut provider_b = Provider::with_id(
+            "b".to_string(),
+            "Provider B".to_string(),
+            json!({"env": {"ANTHROPIC_AUTH_TOKEN": "old"}}),
+            None,
+        );
+        db.save_provider("claude", &provider_a).unwrap();
+        db.save_provider("claude", &provider_b).unwrap();
+        db.add_to_failover_queue("claude", "a").unwrap();
+        db.add_to_failover_queue("claude", "b").unwrap();
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        router
+            .record_result("b", "claude", false, false, Some("401".to_string()))
+            .await
+            .unwrap();
+        let providers = router.select_providers("claude", None).await.unwrap();
+        assert_eq!(providers.len(), 1);
+
+        // 更新凭据后熔断器重置，下一次选路即可使用
+        let version = router.config_version();
+        provider_b.settings_config = json!({"env": {"ANTHROPIC_AUTH_TOKEN": "new"}});
+        db.save_provider("claude", &provider_b).unwrap();
+        assert_ne!(router.config_version(), version);
+        let providers = router.select_providers("claude", None).await.unwrap();
+        assert_eq!(providers.len(), 2);
+        let reloaded = router.reload_provider("claude", &providers[1]).await;
+        assert_eq!(
+            reloaded.unwrap().settings_config,
+            provider_b.settings_config
+        );
+
+        // 移出队列的供应商不再被转发器重试
+        db.remove_from_failover_queue("claude", "b").unwrap();
+        assert!(router
+            .reload_provider("claude", &provider_b)
+            .await
+            .is_none());
+        let providers = router.select_providers("claude", None).await.unwrap();
+        assert_eq!(providers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn provider_breaker_tuning_overrides_app_defaults() {
+        use crate::proxy::circuit_breaker::CircuitBreakerOverride;
+
+        let db = Arc::new(Database::memory().unwrap());
+        db.update_circuit_breaker_config(&CircuitBreakerConfig {
+            failure_threshold: 1,
+            timeout_seconds: 3600,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        let mut provider_b =
+            Provider::with_id("b".to_string(), "Provider B".to_string(), json!({}), None);
+        provider_b.meta = Some(crate::provider::ProviderMeta {
+            circuit_breaker: Some(CircuitBreakerOverride {
+                failure_threshold: Some(3),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        db.save_provider("claude", &provider_a).unwrap();
+        db.save_provider("claude", &provider_b).unwrap();
+        db.add_to_failover_queue("claude", "a").unwrap();
+        db.add_to_failover_queue("claude", "b").unwrap();
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        for id in ["a", "b"] {
+            router
+                .record_result(id, "claude", false, false, Some("500".to_string()))
+                .await
+                .unwrap();
+        }
+        // a 沿用应用默认阈值 1 已熔断，b 的阈值为 3 仍可用
+        let providers = router.select_providers("claude", None).await.unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, "b");
+
+        // 调整覆盖项后，已有熔断器立即使用新阈值
+        if let Some(tuning) = provider_b
+            .meta
+            .as_mut()
+            .and_then(|m| m.circuit_breaker.as_mut())
+        {
+            tuning.failure_threshold = Some(2);
+        }
+        db.save_provider("claude", &provider_b).unwrap();
+        router.select_providers("claude", None).await.unwrap();
+        router
+            .record_result("b", "claude", false, false, Some("500".to_string()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            router.select_providers("claude", None).await,
+            Err(AppError::AllProvidersCircuitOpen)
+        ));
+    }
 }
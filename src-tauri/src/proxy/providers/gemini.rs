@@ -251,6 +251,8 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         }
     }
 
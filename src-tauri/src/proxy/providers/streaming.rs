@@ -64,7 +64,7 @@ struct Usage {
 
 /// 创建 Anthropic SSE 流
 pub fn create_anthropic_sse_stream(
-    stream: impl Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+    stream: impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
 ) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
     async_stream::stream! {
         let mut buffer = String::new();
@@ -0,0 +1,191 @@
+//! 客户端侧限流
+//!
+//! 按应用限制每分钟请求数（令牌桶，容量为一分钟的配额）与同时处理中的请求数，
+//! 超出时直接在本地返回 429，避免失控的 Agent 循环消耗按量计费的上游额度。
+//! 并发名额在响应体（包括流式响应）传输结束后才释放。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::{
+    response_processor::hold_until_body_end, server::ProxyState, types::ClientRateLimit, ProxyError,
+};
+
+/// 单个应用的限流状态
+struct AppLimitState {
+    tokens: f64,
+    refilled_at: Instant,
+    in_flight: usize,
+}
+
+/// 按应用区分的令牌桶与并发计数
+#[derive(Default)]
+pub struct ClientRateLimiter {
+    apps: Arc<Mutex<HashMap<String, AppLimitState>>>,
+}
+
+/// 并发名额，释放时自动归还
+pub struct RateLimitPermit {
+    app_type: String,
+    apps: Arc<Mutex<HashMap<String, AppLimitState>>>,
+}
+
+impl Drop for RateLimitPermit {
+    fn drop(&mut self) {
+        let mut apps = self.apps.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(state) = apps.get_mut(&self.app_type) {
+            state.in_flight = state.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+impl ClientRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 尝试占用一个请求名额，超限时返回 `ProxyError::RateLimited`
+    pub fn try_acquire(
+        &self,
+        app_type: &str,
+        limit: ClientRateLimit,
+        now: Instant,
+    ) -> Result<RateLimitPermit, ProxyError> {
+        let mut apps = self.apps.lock().unwrap_or_else(|e| e.into_inner());
+        let state = apps
+            .entry(app_type.to_string())
+            .or_insert_with(|| AppLimitState {
+                tokens: f64::from(limit.requests_per_minute),
+                refilled_at: now,
+                in_flight: 0,
+            });
+
+        if limit.max_concurrent > 0 && state.in_flight >= limit.max_concurrent as usize {
+            return Err(ProxyError::RateLimited {
+                reason: format!("{} concurrent requests", limit.max_concurrent),
+                retry_after: 1,
+            });
+        }
+
+        if limit.requests_per_minute > 0 {
+            let capacity = f64::from(limit.requests_per_minute);
+            let per_second = capacity / 60.0;
+            let elapsed = now.saturating_duration_since(state.refilled_at);
+            state.tokens = (state.tokens + elapsed.as_secs_f64() * per_second).min(capacity);
+            state.refilled_at = now;
+            if state.tokens < 1.0 {
+                return Err(ProxyError::RateLimited {
+                    reason: format!("{} requests per minute", limit.requests_per_minute),
+                    retry_after: ((1.0 - state.tokens) / per_second).ceil() as u64,
+                });
+            }
+            state.tokens -= 1.0;
+        }
+
+        state.in_flight += 1;
+        Ok(RateLimitPermit {
+            app_type: app_type.to_string(),
+            apps: self.apps.clone(),
+        })
+    }
+}
+
+/// 按请求路径判断所属应用，非代理端点返回 None
+fn app_type_for_path(path: &str) -> Option<&'static str> {
+    if path.ends_with("/messages") {
+        Some("claude")
+    } else if path.ends_with("/chat/completions") || path.ends_with("/responses") {
+        Some("codex")
+    } else if path.starts_with("/v1beta/") || path.starts_with("/gemini/") {
+        Some("gemini")
+    } else {
+        None
+    }
+}
+
+/// 路由中间件：超限时直接返回 429，否则持有并发名额直到响应体传输结束
+pub async fn enforce(State(state): State<ProxyState>, request: Request, next: Next) -> Response {
+    let Some(app_type) = app_type_for_path(request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let limit = state.db.get_client_rate_limit(app_type).unwrap_or_default();
+    if limit.is_unlimited() {
+        return next.run(request).await;
+    }
+
+    let permit = match state
+        .rate_limiter
+        .try_acquire(app_type, limit, Instant::now())
+    {
+        Ok(permit) => permit,
+        Err(e) => {
+            log::warn!("[{app_type}] {e}");
+            return e.into_response();
+        }
+    };
+
+    hold_until_body_end(next.run(request).await, permit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let limiter = ClientRateLimiter::new();
+        let limit = ClientRateLimit {
+            requests_per_minute: 2,
+            max_concurrent: 0,
+        };
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire("claude", limit, start).is_ok());
+        assert!(limiter.try_acquire("claude", limit, start).is_ok());
+        match limiter.try_acquire("claude", limit, start) {
+            Err(ProxyError::RateLimited { retry_after, .. }) => assert_eq!(retry_after, 30),
+            _ => panic!("third request should be rate limited"),
+        }
+        // 其他应用使用独立的令牌桶
+        assert!(limiter.try_acquire("codex", limit, start).is_ok());
+        // 30 秒补充一个令牌
+        assert!(limiter
+            .try_acquire("claude", limit, start + Duration::from_secs(30))
+            .is_ok());
+    }
+
+    #[test]
+    fn concurrency_permits_are_released_on_drop() {
+        let limiter = ClientRateLimiter::new();
+        let limit = ClientRateLimit {
+            requests_per_minute: 0,
+            max_concurrent: 1,
+        };
+        let now = Instant::now();
+
+        let permit = limiter.try_acquire("gemini", limit, now).unwrap();
+        assert!(limiter.try_acquire("gemini", limit, now).is_err());
+        drop(permit);
+        assert!(limiter.try_acquire("gemini", limit, now).is_ok());
+    }
+
+    #[test]
+    fn paths_map_to_app_types() {
+        assert_eq!(app_type_for_path("/claude/v1/messages"), Some("claude"));
+        assert_eq!(app_type_for_path("/v1/chat/completions"), Some("codex"));
+        assert_eq!(app_type_for_path("/responses"), Some("codex"));
+        assert_eq!(
+            app_type_for_path("/v1beta/models/gemini-pro:generateContent"),
+            Some("gemini")
+        );
+        assert_eq!(app_type_for_path("/health"), None);
+    }
+}
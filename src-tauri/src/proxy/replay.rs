@@ -0,0 +1,123 @@
+//! 请求重放
+//!
+//! 将捕获的客户端请求通过指定供应商重新发送，按转发器的顺序应用模型映射、
+//! 请求体改写规则、格式转换与请求头规则，但不经过故障转移、熔断和统计。
+
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use super::{
+    body_filter::filter_private_params_with_whitelist,
+    body_rules::apply_body_rules,
+    dns,
+    header_rules::apply_header_rules,
+    model_mapper::apply_model_mapping,
+    providers::get_adapter,
+    request_log::{sanitize_headers, RequestCapture},
+    upstream_proxy::{build_client, effective_upstream_proxy},
+    ProxyError,
+};
+use crate::{app_config::AppType, provider::Provider};
+
+/// 重放请求的超时时间
+const REPLAY_TIMEOUT_SECS: u64 = 300;
+
+/// 重放结果
+#[derive(Debug, Clone)]
+pub struct ReplayResponse {
+    pub provider_name: String,
+    pub url: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    /// 上游响应头（已脱敏）
+    pub headers: Vec<(String, String)>,
+    /// 响应体原文，流式请求为完整的 SSE 文本
+    pub body: String,
+}
+
+/// 通过 `provider` 重新发送捕获的请求，`upstream_proxy` 为全局上游代理
+pub async fn replay_request(
+    provider: &Provider,
+    app_type: &AppType,
+    capture: &RequestCapture,
+    upstream_proxy: Option<&str>,
+) -> Result<ReplayResponse, ProxyError> {
+    let adapter = get_adapter(app_type);
+    let body: Value = serde_json::from_str(&capture.body)
+        .map_err(|e| ProxyError::TransformError(format!("解析捕获的请求体失败: {e}")))?;
+
+    let base_url = adapter.extract_base_url(provider)?;
+    let needs_transform = adapter.needs_transform(provider);
+    let endpoint =
+        if needs_transform && *app_type == AppType::Claude && capture.endpoint == "/v1/messages" {
+            "/v1/chat/completions"
+        } else {
+            capture.endpoint.as_str()
+        };
+    let url = adapter.build_url(&base_url, endpoint);
+
+    let (mut body, _, _) = apply_model_mapping(body, provider);
+    if let Some(meta) = &provider.meta {
+        apply_body_rules(&mut body, &meta.body_rules, app_type);
+    }
+    if needs_transform {
+        body = adapter.transform_request(body, provider)?;
+    }
+    let payload = serde_json::to_vec(&filter_private_params_with_whitelist(body, &[]))
+        .map_err(|e| ProxyError::TransformError(format!("序列化请求体失败: {e}")))?;
+
+    let client = build_client(
+        dns::client_builder().timeout(Duration::from_secs(REPLAY_TIMEOUT_SECS)),
+        effective_upstream_proxy(upstream_proxy, provider),
+    )
+    .map_err(ProxyError::ForwardFailed)?;
+    let mut request = client.post(&url);
+    for (name, value) in &capture.headers {
+        request = request.header(name, value);
+    }
+    request = request.header("accept-encoding", "identity");
+    if let Some(auth) = adapter.extract_auth(provider) {
+        request = adapter.add_auth_headers(request, &auth);
+    }
+    if !capture
+        .headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+    {
+        request = request.header("content-type", "application/json");
+    }
+    let mut built = request
+        .body(payload)
+        .build()
+        .map_err(|e| ProxyError::ForwardFailed(e.to_string()))?;
+    if let Some(meta) = &provider.meta {
+        apply_header_rules(built.headers_mut(), &meta.header_rules);
+    }
+
+    let start = Instant::now();
+    let response = client
+        .execute(built)
+        .await
+        .map_err(|e| ProxyError::ForwardFailed(e.to_string()))?;
+    let status = response.status().as_u16();
+    let headers = sanitize_headers(
+        response
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.to_str().unwrap_or("<binary>"))),
+    );
+    let body = response
+        .text()
+        .await
+        .map_err(|e| ProxyError::ForwardFailed(format!("读取响应失败: {e}")))?;
+
+    Ok(ReplayResponse {
+        provider_name: provider.name.clone(),
+        url,
+        status,
+        latency_ms: start.elapsed().as_millis() as u64,
+        headers,
+        body,
+    })
+}
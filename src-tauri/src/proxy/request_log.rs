@@ -0,0 +1,374 @@
+//! 最近请求记录
+//!
+//! 以有界环形缓冲保存最近转发的请求，并通过广播通道推送给订阅者（如 TUI）；
+//! 配置了存储时同时写入数据库 `request_log` 表作为持久化历史
+
+use super::body_filter::filter_private_params_with_whitelist;
+use crate::database::Database;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// 默认保留的最近请求条数
+pub const DEFAULT_REQUEST_LOG_CAPACITY: usize = 200;
+
+/// 单次上游请求记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestLogEntry {
+    /// 请求时间（Unix 毫秒时间戳）
+    pub timestamp: i64,
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub url: String,
+    /// 上游响应状态码，请求未到达上游时为 None
+    pub status: Option<u16>,
+    /// 发出请求到收到响应头的耗时
+    pub latency_ms: u64,
+    /// 发出请求前的耗时（请求预处理及此前故障转移尝试）
+    pub queued_ms: u64,
+    pub error: Option<String>,
+    /// 发往上游的请求头（已脱敏）
+    pub request_headers: Vec<(String, String)>,
+    /// 上游响应头（已脱敏）
+    pub response_headers: Vec<(String, String)>,
+    /// 捕获模式下保存的客户端请求，仅写入数据库，不保留在内存缓冲中
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture: Option<RequestCapture>,
+}
+
+/// 捕获的客户端请求，可在请求历史中重放
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCapture {
+    /// 客户端请求的端点，已移除 `key` 查询参数
+    pub endpoint: String,
+    /// 透传给上游的客户端请求头，不含认证类头
+    pub headers: Vec<(String, String)>,
+    /// 客户端原始请求体（JSON），已移除 `_` 开头的私有字段
+    pub body: String,
+}
+
+impl RequestCapture {
+    pub fn new(endpoint: &str, headers: Vec<(String, String)>, body: &serde_json::Value) -> Self {
+        Self {
+            endpoint: strip_key_param(endpoint),
+            headers: headers
+                .into_iter()
+                .filter(|(name, _)| !SENSITIVE_HEADERS.contains(&name.to_lowercase().as_str()))
+                .collect(),
+            body: filter_private_params_with_whitelist(body.clone(), &[]).to_string(),
+        }
+    }
+}
+
+/// 移除 URL 中的 `key` 查询参数（Gemini 的 API Key）
+fn strip_key_param(endpoint: &str) -> String {
+    let Some((path, query)) = endpoint.split_once('?') else {
+        return endpoint.to_string();
+    };
+    let query: Vec<&str> = query
+        .split('&')
+        .filter(|pair| pair.split('=').next() != Some("key"))
+        .collect();
+    if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{}", query.join("&"))
+    }
+}
+
+impl RequestLogEntry {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+/// 需要脱敏的请求/响应头
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "api-key",
+    "cookie",
+    "set-cookie",
+];
+
+/// 是否为携带凭据的请求/响应头
+pub fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADERS.contains(&name.to_lowercase().as_str())
+}
+
+/// 将敏感头的值替换为 `[redacted]`
+pub fn sanitize_headers<'a>(
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive_header(name) {
+                "[redacted]"
+            } else {
+                value
+            };
+            (name.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+/// 状态码过滤条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    /// 精确状态码，如 429
+    Code(u16),
+    /// 状态码类别，如 5 表示 5xx
+    Class(u16),
+    /// 未收到上游响应（网络错误、超时等）
+    Failed,
+}
+
+/// 请求历史的过滤条件
+///
+/// 查询语法：`app:claude provider:relay status:429`，`status` 也可写作 `5xx` 或 `err`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestLogFilter {
+    pub app_type: Option<String>,
+    /// 供应商名称（不区分大小写的子串匹配）
+    pub provider: Option<String>,
+    pub status: Option<StatusFilter>,
+}
+
+impl RequestLogFilter {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for token in query.split_whitespace() {
+            let (key, value) = token
+                .split_once(':')
+                .filter(|(_, v)| !v.is_empty())
+                .ok_or_else(|| format!("Invalid filter '{token}', expected key:value"))?;
+            match key {
+                "app" => filter.app_type = Some(value.to_lowercase()),
+                "provider" => filter.provider = Some(value.to_string()),
+                "status" => filter.status = Some(parse_status_filter(value)?),
+                _ => return Err(format!("Unknown filter key '{key}'")),
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn parse_status_filter(value: &str) -> Result<StatusFilter, String> {
+    let lower = value.to_lowercase();
+    if lower == "err" {
+        return Ok(StatusFilter::Failed);
+    }
+    if let Some(class) = lower.strip_suffix("xx") {
+        if let Ok(class @ 1..=5) = class.parse::<u16>() {
+            return Ok(StatusFilter::Class(class));
+        }
+    }
+    match lower.parse::<u16>() {
+        Ok(code @ 100..=599) => Ok(StatusFilter::Code(code)),
+        _ => Err(format!("Invalid status filter '{value}'")),
+    }
+}
+
+impl fmt::Display for RequestLogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(app) = &self.app_type {
+            parts.push(format!("app:{app}"));
+        }
+        if let Some(provider) = &self.provider {
+            parts.push(format!("provider:{provider}"));
+        }
+        match self.status {
+            Some(StatusFilter::Code(code)) => parts.push(format!("status:{code}")),
+            Some(StatusFilter::Class(class)) => parts.push(format!("status:{class}xx")),
+            Some(StatusFilter::Failed) => parts.push("status:err".to_string()),
+            None => {}
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// 最近请求的环形缓冲
+pub struct RequestLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<RequestLogEntry>>,
+    sender: broadcast::Sender<RequestLogEntry>,
+    /// 持久化存储，为 None 时只保留内存记录
+    store: Option<Arc<Database>>,
+}
+
+impl RequestLog {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            sender,
+            store: None,
+        }
+    }
+
+    /// 同时将记录写入数据库 `request_log` 表
+    pub fn with_store(capacity: usize, db: Arc<Database>) -> Self {
+        Self {
+            store: Some(db),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// 是否开启了请求捕获（仅在配置了存储时可用）
+    pub fn capture_enabled(&self) -> bool {
+        self.store
+            .as_ref()
+            .is_some_and(|db| db.get_request_capture_limit().unwrap_or(0) > 0)
+    }
+
+    /// 记录一条请求，超出容量时丢弃最旧的记录
+    pub fn push(&self, mut entry: RequestLogEntry) {
+        if let Some(db) = &self.store {
+            if let Err(e) = db.insert_request_log(&entry) {
+                log::warn!("写入请求历史失败: {e}");
+            }
+        }
+        // 请求体可能很大，只保存在数据库中
+        entry.capture = None;
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.sender.send(entry);
+    }
+
+    /// 按时间顺序返回当前缓冲中的全部记录
+    pub fn recent(&self) -> Vec<RequestLogEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().cloned().collect()
+    }
+
+    /// 订阅新记录
+    pub fn subscribe(&self) -> broadcast::Receiver<RequestLogEntry> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for RequestLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_REQUEST_LOG_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: u16) -> RequestLogEntry {
+        RequestLogEntry {
+            timestamp: 0,
+            app_type: "claude".to_string(),
+            provider_id: "p".to_string(),
+            provider_name: "P".to_string(),
+            url: "https://example.com/v1/messages".to_string(),
+            status: Some(status),
+            latency_ms: 10,
+            queued_ms: 0,
+            error: None,
+            request_headers: Vec::new(),
+            response_headers: Vec::new(),
+            capture: None,
+        }
+    }
+
+    #[test]
+    fn push_drops_oldest_beyond_capacity() {
+        let log = RequestLog::new(2);
+        log.push(entry(200));
+        log.push(entry(429));
+        log.push(entry(500));
+
+        let statuses: Vec<_> = log.recent().iter().map(|e| e.status).collect();
+        assert_eq!(statuses, vec![Some(429), Some(500)]);
+    }
+
+    #[test]
+    fn subscribers_receive_new_entries() {
+        let log = RequestLog::new(4);
+        let mut rx = log.subscribe();
+        log.push(entry(200));
+
+        let received = rx.try_recv().expect("entry should be broadcast");
+        assert!(received.is_success());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn sanitize_headers_redacts_credentials() {
+        let headers = sanitize_headers([
+            ("Authorization", "Bearer sk-secret"),
+            ("x-goog-api-key", "AIza"),
+            ("content-type", "application/json"),
+        ]);
+        assert_eq!(headers[0].1, "[redacted]");
+        assert_eq!(headers[1].1, "[redacted]");
+        assert_eq!(headers[2].1, "application/json");
+    }
+
+    #[test]
+    fn capture_strips_credentials() {
+        let capture = RequestCapture::new(
+            "/v1beta/models/gemini:streamGenerateContent?alt=sse&key=AIzaSecret",
+            vec![
+                ("x-goog-api-key".to_string(), "AIzaSecret".to_string()),
+                ("anthropic-beta".to_string(), "tools".to_string()),
+            ],
+            &serde_json::json!({ "model": "m", "_internal": true }),
+        );
+        assert_eq!(
+            capture.endpoint,
+            "/v1beta/models/gemini:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(
+            capture.headers,
+            vec![("anthropic-beta".to_string(), "tools".to_string())]
+        );
+        assert_eq!(capture.body, r#"{"model":"m"}"#);
+        assert_eq!(strip_key_param("/v1/models?key=k"), "/v1/models");
+    }
+
+    #[test]
+    fn filter_parse_round_trips() {
+        let filter = RequestLogFilter::parse("app:Claude provider:relay status:5xx").unwrap();
+        assert_eq!(filter.app_type.as_deref(), Some("claude"));
+        assert_eq!(filter.provider.as_deref(), Some("relay"));
+        assert_eq!(filter.status, Some(StatusFilter::Class(5)));
+        assert_eq!(filter.to_string(), "app:claude provider:relay status:5xx");
+
+        assert_eq!(
+            RequestLogFilter::parse("status:429").unwrap().status,
+            Some(StatusFilter::Code(429))
+        );
+        assert_eq!(
+            RequestLogFilter::parse("status:err").unwrap().status,
+            Some(StatusFilter::Failed)
+        );
+        assert!(RequestLogFilter::parse("").unwrap().is_empty());
+        assert!(RequestLogFilter::parse("status:999").is_err());
+        assert!(RequestLogFilter::parse("model:x").is_err());
+        assert!(RequestLogFilter::parse("app").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! 非流式响应缓存
+//!
+//! Agent 重试时经常原样重发同一请求。开启后，成功的非流式响应按（应用, 路径, 影响响应的请求头,
+//! 请求体）的 SHA-256 摘要缓存，在有效期内收到完全相同的请求时直接返回缓存内容，不再转发到上游。
+//! 缓存只保存在内存中，条目数与总字节数超限时先淘汰最早写入的条目。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// 请求缓存键（SHA-256 摘要）
+pub type CacheKey = [u8; 32];
+
+/// 参与缓存键计算的请求头：API 版本与 beta 特性会改变响应，会话头影响粘滞路由，
+/// 认证头使不同调用方不共享缓存
+const KEY_HEADERS: &[&str] = &[
+    "anthropic-version",
+    "anthropic-beta",
+    "openai-beta",
+    "openai-organization",
+    "openai-project",
+    "session_id",
+    "x-session-id",
+    "authorization",
+    "x-api-key",
+    "x-goog-api-key",
+];
+
+/// 响应缓存设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    /// 缓存有效期（秒）
+    pub ttl_seconds: u64,
+    /// 最多缓存的响应数
+    pub max_entries: usize,
+    /// 所有缓存响应体的总大小上限（KB）
+    pub max_size_kb: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_seconds: 300,
+            max_entries: 256,
+            max_size_kb: 32 * 1024,
+        }
+    }
+}
+
+/// 缓存命中统计，展示在代理面板
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 当前缓存的响应数
+    pub entries: usize,
+    /// 当前缓存的响应体总字节数
+    pub bytes: u64,
+}
+
+/// 缓存的上游响应
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+    /// 产生该响应的供应商
+    pub provider_id: String,
+    pub provider_name: String,
+}
+
+struct Entry {
+    response: CachedResponse,
+    stored_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<CacheKey, Entry>,
+    /// 写入顺序，用于淘汰最早的条目
+    order: VecDeque<CacheKey>,
+    bytes: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl Inner {
+    fn remove(&mut self, key: CacheKey) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.response.body.len() as u64;
+            self.order.retain(|k| *k != key);
+        }
+    }
+}
+
+/// 进程内响应缓存，随代理服务存在，跨代理重启不保留
+#[derive(Default)]
+pub struct ResponseCache {
+    inner: Mutex<Inner>,
+}
+
+/// 计算请求的缓存键
+pub fn cache_key(app_type: &str, endpoint: &str, headers: &HeaderMap, body: &Value) -> CacheKey {
+    let mut hasher = Sha256::new();
+    // 每段都带长度前缀，不同字段拼接后不会相同
+    let mut update = |part: &[u8]| {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    };
+    update(app_type.as_bytes());
+    update(endpoint.as_bytes());
+    for name in KEY_HEADERS {
+        for value in headers.get_all(*name) {
+            update(name.as_bytes());
+            update(value.as_bytes());
+        }
+    }
+    update(body.to_string().as_bytes());
+    hasher.finalize().into()
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 查找未过期的缓存响应，并计入命中统计
+    pub fn get(&self, config: &ResponseCacheConfig, key: CacheKey) -> Option<CachedResponse> {
+        self.get_at(config, key, Instant::now())
+    }
+
+    fn get_at(
+        &self,
+        config: &ResponseCacheConfig,
+        key: CacheKey,
+        now: Instant,
+    ) -> Option<CachedResponse> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let ttl = Duration::from_secs(config.ttl_seconds);
+        match inner.entries.get(&key) {
+            Some(entry) if now.duration_since(entry.stored_at) < ttl => {
+                let response = entry.response.clone();
+                inner.hits += 1;
+                Some(response)
+            }
+            Some(_) => {
+                inner.remove(key);
+                inner.misses += 1;
+                None
+            }
+            None => {
+                inner.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// 写入响应；单个响应超过总大小上限时不缓存
+    pub fn insert(&self, config: &ResponseCacheConfig, key: CacheKey, response: CachedResponse) {
+        self.insert_at(config, key, response, Instant::now());
+    }
+
+    fn insert_at(
+        &self,
+        config: &ResponseCacheConfig,
+        key: CacheKey,
+        response: CachedResponse,
+        now: Instant,
+    ) {
+        let max_bytes = config.max_size_kb.saturating_mul(1024);
+        let size = response.body.len() as u64;
+        if config.max_entries == 0 || size > max_bytes {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.remove(key);
+        while inner.entries.len() >= config.max_entries || inner.bytes + size > max_bytes {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some(entry) = inner.entries.remove(&oldest) {
+                inner.bytes -= entry.response.body.len() as u64;
+            }
+        }
+        inner.bytes += size;
+        inner.order.push_back(key);
+        inner.entries.insert(
+            key,
+            Entry {
+                response,
+                stored_at: now,
+            },
+        );
+    }
+
+    /// 清空缓存与统计
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = Inner::default();
+    }
+
+    pub fn stats(&self) -> ResponseCacheStats {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        ResponseCacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            entries: inner.entries.len(),
+            bytes: inner.bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(body: &'static str) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: Bytes::from_static(body.as_bytes()),
+            provider_id: "p".to_string(),
+            provider_name: "P".to_string(),
+        }
+    }
+
+    fn key(n: u8) -> CacheKey {
+        [n; 32]
+    }
+
+    #[test]
+    fn identical_requests_hit_until_the_entry_expires() {
+        let cache = ResponseCache::new();
+        let config = ResponseCacheConfig {
+            enabled: true,
+            ttl_seconds: 60,
+            ..Default::default()
+        };
+        let body = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+        let headers = HeaderMap::new();
+        let key = cache_key("claude", "/v1/messages", &headers, &body);
+        assert_ne!(key, cache_key("codex", "/v1/messages", &headers, &body));
+        assert_ne!(
+            key,
+            cache_key("claude", "/v1/messages", &headers, &json!({"model": "m"}))
+        );
+
+        let now = Instant::now();
+        assert!(cache.get_at(&config, key, now).is_none());
+        cache.insert_at(&config, key, response("{\"ok\":true}"), now);
+        let hit = cache
+            .get_at(&config, key, now + Duration::from_secs(59))
+            .unwrap();
+        assert_eq!(hit.body, Bytes::from_static(b"{\"ok\":true}"));
+        assert!(cache
+            .get_at(&config, key, now + Duration::from_secs(60))
+            .is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 0));
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_over_the_limits() {
+        let cache = ResponseCache::new();
+        let config = ResponseCacheConfig {
+            enabled: true,
+            max_entries: 2,
+            max_size_kb: 1,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        cache.insert_at(&config, key(1), response("a"), now);
+        cache.insert_at(&config, key(2), response("b"), now);
+        cache.insert_at(&config, key(3), response("c"), now);
+        assert!(cache.get_at(&config, key(1), now).is_none());
+        assert!(cache.get_at(&config, key(3), now).is_some());
+
+        // 超过总大小上限的响应不缓存，也不会挤掉已有条目
+        let large = CachedResponse {
+            body: Bytes::from(vec![b'x'; 2048]),
+            ..response("")
+        };
+        cache.insert_at(&config, key(4), large, now);
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.bytes), (2, 2));
+    }
+
+    #[test]
+    fn response_affecting_headers_change_the_key() {
+        let body = json!({"model": "m"});
+        let mut headers = HeaderMap::new();
+        headers.insert("user-agent", "a".parse().unwrap());
+        let base = cache_key("claude", "/v1/messages", &headers, &body);
+        headers.insert("user-agent", "b".parse().unwrap());
+        assert_eq!(cache_key("claude", "/v1/messages", &headers, &body), base);
+
+        headers.insert("anthropic-beta", "prompt-caching".parse().unwrap());
+        let beta = cache_key("claude", "/v1/messages", &headers, &body);
+        assert_ne!(beta, base);
+        headers.insert("anthropic-version", "2023-06-01".parse().unwrap());
+        assert_ne!(cache_key("claude", "/v1/messages", &headers, &body), beta);
+    }
+}
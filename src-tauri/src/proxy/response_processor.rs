@@ -5,7 +5,12 @@
 use super::{
     handler_config::UsageParserConfig,
     handler_context::{RequestContext, StreamingTimeoutConfig},
+    model_mapper::{model_alias, restore_response_model},
+    response_cache::CachedResponse,
     server::ProxyState,
+    session::ClientFormat,
+    size_limits::{limit_response_stream, read_response_body},
+    stream_failover::{error_event, is_terminal_event},
     usage::parser::TokenUsage,
     ProxyError,
 };
@@ -17,7 +22,7 @@ use serde_json::Value;
 use std::{
     str::FromStr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
@@ -57,9 +62,16 @@ pub async fn handle_streaming(
     }
 
     // 创建字节流
-    let stream = response
-        .bytes_stream()
-        .map(|chunk| chunk.map_err(|e| std::io::Error::other(e.to_string())));
+    let stream = limit_response_stream(
+        count_received_bytes(response.bytes_stream(), ctx, state),
+        state,
+        ctx.tag,
+    );
+    // 请求使用了供应商模型别名时，把事件中的模型名还原为客户端请求的名称
+    let stream = match model_alias(&ctx.provider, &ctx.request_model) {
+        Some(upstream) => restore_sse_model(stream, upstream, ctx.request_model.clone()).boxed(),
+        None => stream.boxed(),
+    };
 
     // 创建使用量收集器
     let usage_collector = create_usage_collector(ctx, state, status.as_u16(), parser_config);
@@ -68,8 +80,14 @@ pub async fn handle_streaming(
     let timeout_config = ctx.streaming_timeout_config();
 
     // 创建带日志和超时的透传流
-    let logged_stream =
-        create_logged_passthrough_stream(stream, ctx.tag, Some(usage_collector), timeout_config);
+    let logged_stream = create_logged_passthrough_stream(
+        stream,
+        ctx.tag,
+        ClientFormat::from_path(&ctx.endpoint),
+        Some(usage_collector),
+        timeout_config,
+        state.active_streams.clone(),
+    );
 
     let body = axum::body::Body::from_stream(logged_stream);
     builder.body(body).unwrap()
@@ -86,10 +104,21 @@ pub async fn handle_non_streaming(
     let status = response.status();
 
     // 读取响应体
-    let body_bytes = response.bytes().await.map_err(|e| {
-        log::error!("[{}] 读取响应失败: {e}", ctx.tag);
-        ProxyError::ForwardFailed(format!("Failed to read response body: {e}"))
-    })?;
+    let mut body_bytes = read_response_body(response, state, ctx.tag).await?;
+    state.record_received_bytes(ctx.app_type_str, &ctx.provider.id, body_bytes.len() as u64);
+
+    // 请求使用了供应商模型别名时，把响应中的模型名还原为客户端请求的名称
+    let mut body_rewritten = false;
+    if let Some(upstream) = model_alias(&ctx.provider, &ctx.request_model) {
+        if let Ok(mut json_value) = serde_json::from_slice::<Value>(&body_bytes) {
+            if restore_response_model(&mut json_value, &upstream, &ctx.request_model) {
+                if let Ok(bytes) = serde_json::to_vec(&json_value) {
+                    body_bytes = Bytes::from(bytes);
+                    body_rewritten = true;
+                }
+            }
+        }
+    }
 
     // 解析并记录使用量
     if let Ok(json_value) = serde_json::from_slice::<Value>(&body_bytes) {
@@ -150,14 +179,129 @@ pub async fn handle_non_streaming(
 
     // 构建响应
     let mut builder = axum::response::Response::builder().status(status);
+    let mut headers = Vec::new();
     for (key, value) in response_headers.iter() {
+        // 响应体被改写后长度变化，由 axum 重新计算
+        if body_rewritten && key == axum::http::header::CONTENT_LENGTH {
+            continue;
+        }
         builder = builder.header(key, value);
+        if let Ok(value) = value.to_str() {
+            headers.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    if let Some(key) = ctx.cache_key.filter(|_| status.is_success()) {
+        let config = state
+            .db
+            .get_proxy_response_cache_config()
+            .unwrap_or_default();
+        state.response_cache.insert(
+            &config,
+            key,
+            CachedResponse {
+                status: status.as_u16(),
+                headers,
+                body: body_bytes.clone(),
+                provider_id: ctx.provider.id.clone(),
+                provider_name: ctx.provider.name.clone(),
+            },
+        );
     }
 
     let body = axum::body::Body::from(body_bytes);
     Ok(builder.body(body).unwrap())
 }
 
+/// 统计上游响应流的字节数，计入当前 Provider
+pub fn count_received_bytes<E>(
+    stream: impl Stream<Item = Result<Bytes, E>> + Send + 'static,
+    ctx: &RequestContext,
+    state: &ProxyState,
+) -> impl Stream<Item = Result<Bytes, E>> + Send + 'static {
+    let state = state.clone();
+    let app_type = ctx.app_type_str;
+    let provider_id = ctx.provider.id.clone();
+    stream.inspect(move |chunk| {
+        if let Ok(bytes) = chunk {
+            state.record_received_bytes(app_type, &provider_id, bytes.len() as u64);
+        }
+    })
+}
+
+/// 让 `guard` 随响应体存活，直到响应体（包括流式响应）传输结束或被丢弃才释放
+pub fn hold_until_body_end<T: Send + 'static>(response: Response, guard: T) -> Response {
+    response.map(|body| {
+        axum::body::Body::from_stream(body.into_data_stream().map(move |chunk| {
+            let _ = &guard;
+            chunk
+        }))
+    })
+}
+
+/// 按 SSE 事件改写 `data:` 中的模型名，事件边界（空行）之前的数据先缓冲
+fn restore_sse_model(
+    stream: impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+    upstream: String,
+    original: String,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+    async_stream::stream! {
+        let mut buffer: Vec<u8> = Vec::new();
+        tokio::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            let bytes = match chunk {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            buffer.extend_from_slice(&bytes);
+
+            let mut output = Vec::new();
+            while let Some(pos) = buffer.windows(2).position(|w| w == b"\n\n") {
+                let event: Vec<u8> = buffer.drain(..pos + 2).collect();
+                output.extend(rewrite_sse_event(&event, &upstream, &original));
+            }
+            if !output.is_empty() {
+                yield Ok(Bytes::from(output));
+            }
+        }
+        if !buffer.is_empty() {
+            yield Ok(Bytes::from(rewrite_sse_event(&buffer, &upstream, &original)));
+        }
+    }
+}
+
+fn rewrite_sse_event(event: &[u8], upstream: &str, original: &str) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(event) else {
+        return event.to_vec();
+    };
+    let mut changed = false;
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let Some(data) = line.strip_prefix("data: ") else {
+                return line.to_string();
+            };
+            let Ok(mut value) = serde_json::from_str::<Value>(data) else {
+                return line.to_string();
+            };
+            if restore_response_model(&mut value, upstream, original) {
+                changed = true;
+                format!("data: {value}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if changed {
+        lines.join("\n").into_bytes()
+    } else {
+        event.to_vec()
+    }
+}
+
 /// 通用响应处理入口
 ///
 /// 根据响应类型自动选择流式或非流式处理
@@ -265,9 +409,19 @@ fn create_usage_collector(
     let stream_parser = parser_config.stream_parser;
     let model_extractor = parser_config.model_extractor;
     let session_id = ctx.session_id.clone();
+    let access_entry = ctx.access_log_entry(&request_model, status_code, true);
 
     SseUsageCollector::new(start_time, move |events, first_token_ms| {
-        if let Some(usage) = stream_parser(&events) {
+        let usage = stream_parser(&events);
+        let mut entry = access_entry
+            .clone()
+            .with_usage(&usage.clone().unwrap_or_default());
+        entry.model = model_extractor(&events, &request_model);
+        entry.latency_ms = start_time.elapsed().as_millis() as u64;
+        entry.first_token_ms = first_token_ms;
+        state.access_log.record(entry, &[]);
+
+        if let Some(usage) = usage {
             let model = model_extractor(&events, &request_model);
             let latency_ms = start_time.elapsed().as_millis() as u64;
 
@@ -332,8 +486,12 @@ fn spawn_log_usage(
     let model = model.to_string();
     let latency_ms = ctx.latency_ms();
     let session_id = ctx.session_id.clone();
+    let entry = ctx
+        .access_log_entry(&model, status_code, is_streaming)
+        .with_usage(&usage);
 
     tokio::spawn(async move {
+        state.access_log.record(entry, &[]);
         log_usage_internal(
             &state,
             &provider_id,
@@ -384,6 +542,12 @@ async fn log_usage_internal(
         _ => Decimal::from(1),
     };
 
+    state
+        .status
+        .write()
+        .await
+        .record_token_usage(app_type, provider_id, &usage);
+
     let request_id = uuid::Uuid::new_v4().to_string();
 
     log::debug!(
@@ -413,17 +577,43 @@ async fn log_usage_internal(
     }
 }
 
+/// 流式响应计数守卫，流结束或被丢弃时自动减一
+struct ActiveStreamGuard(Arc<AtomicUsize>);
+
+impl ActiveStreamGuard {
+    fn new(counter: Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for ActiveStreamGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 /// 创建带日志记录和超时控制的透传流
+///
+/// 上游出错、超时或在结束事件前关闭时，按 `format` 追加一条错误事件后正常结束，
+/// 而不是直接截断连接
 pub fn create_logged_passthrough_stream(
     stream: impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
     tag: &'static str,
+    format: ClientFormat,
     usage_collector: Option<SseUsageCollector>,
     timeout_config: StreamingTimeoutConfig,
+    active_streams: Arc<AtomicUsize>,
 ) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
     async_stream::stream! {
+        let _active = ActiveStreamGuard::new(active_streams);
         let mut buffer = String::new();
         let mut collector = usage_collector;
         let mut is_first_chunk = true;
+        // 是否已收到结束事件（或上游自行发送的错误事件）
+        let mut completed = false;
+        // 未能正常结束时发给客户端的错误说明
+        let mut interrupted: Option<String> = None;
 
         // 超时配置
         let first_byte_timeout = if timeout_config.first_byte_timeout > 0 {
@@ -456,7 +646,7 @@ pub fn create_logged_passthrough_stream(
                             // 超时
                             let timeout_type = if is_first_chunk { "首字节" } else { "静默期" };
                             log::error!("[{tag}] 流式响应{}超时 ({}秒)", timeout_type, duration.as_secs());
-                            yield Err(std::io::Error::other(format!("流式响应{timeout_type}超时")));
+                            interrupted = Some(format!("流式响应{timeout_type}超时"));
                             break;
                         }
                     }
@@ -479,6 +669,7 @@ pub fn create_logged_passthrough_stream(
                             // 提取 data 部分并尝试解析为 JSON
                             for line in event_text.lines() {
                                 if let Some(data) = line.strip_prefix("data: ") {
+                                    completed |= is_terminal_event(data);
                                     if data.trim() != "[DONE]" {
                                         if let Ok(json_value) = serde_json::from_str::<Value>(data) {
                                             if let Some(c) = &collector {
@@ -504,16 +695,27 @@ pub fn create_logged_passthrough_stream(
                 }
                 Some(Err(e)) => {
                     log::error!("[{tag}] 流错误: {e}");
-                    yield Err(std::io::Error::other(e.to_string()));
+                    interrupted = Some(format!("上游流式响应中断: {e}"));
                     break;
                 }
                 None => {
-                    // 流正常结束
+                    if !completed {
+                        log::warn!("[{tag}] 上游在结束事件前关闭了流式响应");
+                        interrupted = Some("上游在流式响应完成前关闭了连接".to_string());
+                    }
                     break;
                 }
             }
         }
 
+        if let Some(message) = interrupted {
+            // 先结束缓冲中未完整的事件，避免客户端把错误事件拼接到半个事件上
+            if !buffer.trim().is_empty() {
+                yield Ok(Bytes::from_static(b"\n\n"));
+            }
+            yield Ok(error_event(format, &message));
+        }
+
         log::info!("[{}] ====== 流结束 ======", tag);
 
         if let Some(c) = collector.take() {
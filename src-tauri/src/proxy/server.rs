@@ -3,20 +3,39 @@
 //! 基于Axum的HTTP服务器，处理代理请求
 
 use super::{
-    failover_switch::FailoverSwitchManager, handlers, provider_router::ProviderRouter, types::*,
-    url_router::UrlRouter, ProxyError,
+    access_log::AccessLog, auth, circuit_breaker::CircuitState,
+    failover_switch::FailoverSwitchManager, handlers, health::HealthChecker,
+    health::HealthProbeResult, ip_allowlist::IpAllowlist, provider_router::ProviderRouter,
+    rate_limiter, rate_limiter::ClientRateLimiter, request_log::RequestLog,
+    response_cache::ResponseCache, size_limits, size_limits::SizeLimitCounters,
+    switch_back::SwitchBackMonitor, tls, types::*, unix_socket, url_router::UrlRouter,
+    webhook::WebhookNotifier, ProxyError,
 };
 use crate::database::Database;
+use crate::error::AppError;
 use crate::services::url_latency::UrlLatencyService;
 use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
     routing::{get, post},
     Router,
 };
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto::Builder,
+    service::TowerToHyperService,
+};
+use std::collections::HashMap;
+use std::fmt;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::sync::{oneshot, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, watch, RwLock};
 use tokio::task::JoinHandle;
-use tower_http::cors::{Any, CorsLayer};
+use tokio_rustls::TlsAcceptor;
+use tower_http::cors::CorsLayer;
 
 /// 代理服务器状态（共享）
 #[derive(Clone)]
@@ -35,6 +54,39 @@ pub struct ProxyState {
     pub url_router: Arc<UrlRouter>,
     /// URL 延迟测试服务
     pub latency_service: Arc<UrlLatencyService>,
+    /// 供应商主动健康检查
+    pub health_checker: Arc<HealthChecker>,
+    /// 故障转移后探测并切回首选供应商
+    pub switch_back: Arc<SwitchBackMonitor>,
+    /// 非流式响应缓存
+    pub response_cache: Arc<ResponseCache>,
+    /// 正在进行的流式响应数
+    pub active_streams: Arc<AtomicUsize>,
+    /// 最近请求记录（由 ProxyService 持有，跨重启保留）
+    pub request_log: Arc<RequestLog>,
+    /// 结构化访问日志（由 ProxyService 持有，设置变更即时生效）
+    pub access_log: Arc<AccessLog>,
+    /// 各 Provider 接收的响应字节数 ((app_type, provider_id) -> bytes)
+    pub received_bytes: Arc<Mutex<HashMap<(String, String), u64>>>,
+    /// 客户端侧限流状态
+    pub rate_limiter: Arc<ClientRateLimiter>,
+    /// 允许连接的客户端网段（接受连接时同步读取）
+    pub ip_allowlist: Arc<std::sync::RwLock<IpAllowlist>>,
+    /// 请求与响应超过大小上限的次数
+    pub size_limits: Arc<SizeLimitCounters>,
+}
+
+impl ProxyState {
+    /// 累加指定 Provider 接收的响应字节数（流式响应逐块调用，故使用同步锁）
+    pub fn record_received_bytes(&self, app_type: &str, provider_id: &str, bytes: u64) {
+        let mut received = self
+            .received_bytes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *received
+            .entry((app_type.to_string(), provider_id.to_string()))
+            .or_default() += bytes;
+    }
 }
 
 /// 代理HTTP服务器
@@ -47,15 +99,32 @@ pub struct ProxyServer {
 }
 
 impl ProxyServer {
-    pub fn new(config: ProxyConfig, db: Arc<Database>) -> Self {
+    pub fn new(
+        config: ProxyConfig,
+        db: Arc<Database>,
+        request_log: Arc<RequestLog>,
+        access_log: Arc<AccessLog>,
+    ) -> Self {
+        // 故障转移、熔断与端点故障事件的 Webhook 通知
+        let webhooks = Arc::new(WebhookNotifier::new(db.clone()));
         // 创建共享的 ProviderRouter（熔断器状态将跨所有请求保持）
-        let provider_router = Arc::new(ProviderRouter::new(db.clone()));
+        let provider_router =
+            Arc::new(ProviderRouter::new(db.clone()).with_webhooks(webhooks.clone()));
         // 创建故障转移切换管理器
-        let failover_manager = Arc::new(FailoverSwitchManager::new(db.clone()));
+        let failover_manager =
+            Arc::new(FailoverSwitchManager::new(db.clone()).with_webhooks(webhooks.clone()));
         // 创建 URL 路由器
-        let url_router = Arc::new(UrlRouter::new(db.clone()));
+        let url_router = Arc::new(UrlRouter::new(db.clone()).with_webhooks(webhooks));
         // 创建 URL 延迟测试服务
         let latency_service = Arc::new(UrlLatencyService::new(db.clone(), url_router.clone()));
+        // 创建供应商主动健康检查
+        let health_checker = Arc::new(HealthChecker::new(db.clone(), provider_router.clone()));
+        // 创建首选供应商切回任务
+        let switch_back = Arc::new(SwitchBackMonitor::new(
+            db.clone(),
+            provider_router.clone(),
+            failover_manager.clone(),
+        ));
 
         let state = ProxyState {
             db,
@@ -67,6 +136,16 @@ impl ProxyServer {
             failover_manager,
             url_router,
             latency_service,
+            health_checker,
+            switch_back,
+            response_cache: Arc::new(ResponseCache::new()),
+            active_streams: Arc::new(AtomicUsize::new(0)),
+            request_log,
+            access_log,
+            received_bytes: Arc::new(Mutex::new(HashMap::new())),
+            rate_limiter: Arc::new(ClientRateLimiter::new()),
+            ip_allowlist: Arc::new(std::sync::RwLock::new(IpAllowlist::default())),
+            size_limits: Arc::new(SizeLimitCounters::default()),
         };
 
         Self {
@@ -91,15 +170,91 @@ impl ProxyServer {
         // 创建关闭通道
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
 
-        // 构建路由
-        let app = self.build_router();
+        // 构建路由，CORS 设置有误则不启动
+        let cors = self
+            .state
+            .db
+            .get_proxy_cors_config()
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+            .layer()?;
+        let app = self.build_router(cors.clone(), None);
+
+        // 开启 HTTPS 时先加载证书，证书有误则不启动
+        let tls_config = self
+            .state
+            .db
+            .get_proxy_tls_config()
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        let acceptor = if tls_config.enabled {
+            Some(tls::build_acceptor(&tls_config)?)
+        } else {
+            None
+        };
 
-        // 绑定监听器
-        let listener = tokio::net::TcpListener::bind(&addr)
-            .await
-            .map_err(|e| ProxyError::BindFailed(e.to_string()))?;
+        let allowlist = self
+            .state
+            .db
+            .get_proxy_ip_allowlist()
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        let allowlist = IpAllowlist::parse(&allowlist)?;
+        if !allowlist.is_empty() {
+            log::info!("仅允许以下网段连接代理: {}", allowlist.entries().join(", "));
+        }
+        self.set_ip_allowlist(allowlist);
+
+        let socket_config = self
+            .state
+            .db
+            .get_proxy_unix_socket_config()
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        let listens_on_tcp = socket_config.listens_on_tcp();
+
+        let mut listeners = Vec::new();
+        let mut app_ports = Vec::new();
+        let scheme = if acceptor.is_some() { "https" } else { "http" };
+        // 只监听 socket 时不绑定任何 TCP 端口（包括应用独立端口）
+        if listens_on_tcp {
+            // 绑定监听器
+            let listener = TcpListener::bind(&addr)
+                .await
+                .map_err(|e| ProxyError::BindFailed(e.to_string()))?;
+            log::info!("代理服务器启动于 {scheme}://{addr}");
+            listeners.push((Listener::Tcp(listener), app.clone()));
+
+            // 设置了独立端口的应用额外监听一个只包含自身端点的端口
+            for app_type in ["claude", "codex", "gemini"] {
+                let port = self
+                    .state
+                    .db
+                    .get_dedicated_port(app_type)
+                    .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+                let Some(port) = port else {
+                    continue;
+                };
+                let app_addr = SocketAddr::new(addr.ip(), port);
+                let app_listener = TcpListener::bind(app_addr).await.map_err(|e| {
+                    ProxyError::BindFailed(format!("{app_type} 独立端口 {port}: {e}"))
+                })?;
+                log::info!("{app_type} 独立端口监听于 {scheme}://{app_addr}");
+                listeners.push((
+                    Listener::Tcp(app_listener),
+                    self.build_router(cors.clone(), Some(app_type)),
+                ));
+                app_ports.push((app_type.to_string(), port));
+            }
+        }
 
-        log::info!("代理服务器启动于 {addr}");
+        // Unix socket 上提供完整的路由，不使用 TLS
+        let socket_path = if socket_config.enabled {
+            unix_socket::ensure_supported()?;
+            let path = socket_config.resolved_path();
+            #[cfg(unix)]
+            listeners.push((Listener::Unix(unix_socket::bind(&path)?), app));
+            log::info!("代理服务器监听 Unix socket {}", path.display());
+            Some(path)
+        } else {
+            None
+        };
 
         // 保存关闭句柄
         *self.shutdown_tx.write().await = Some(shutdown_tx);
@@ -109,6 +264,10 @@ impl ProxyServer {
         status.running = true;
         status.address = self.config.listen_address.clone();
         status.port = self.config.listen_port;
+        status.tls = acceptor.is_some();
+        status.app_ports = app_ports;
+        status.unix_socket = socket_path.as_ref().map(|path| path.display().to_string());
+        status.socket_only = !listens_on_tcp;
         drop(status);
 
         // 记录启动时间
@@ -117,12 +276,29 @@ impl ProxyServer {
         // 启动服务器
         let state = self.state.clone();
         let handle = tokio::spawn(async move {
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async {
-                    shutdown_rx.await.ok();
+            let (stop_tx, stop_rx) = watch::channel(());
+            let servers: Vec<_> = listeners
+                .into_iter()
+                .map(|(listener, app)| {
+                    tokio::spawn(serve(
+                        listener,
+                        app,
+                        acceptor.clone(),
+                        state.clone(),
+                        stop_rx.clone(),
+                    ))
                 })
-                .await
-                .ok();
+                .collect();
+            shutdown_rx.await.ok();
+            let _ = stop_tx.send(());
+            for server in servers {
+                let _ = server.await;
+            }
+            if let Some(path) = socket_path {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("删除 socket 文件 {} 失败: {e}", path.display());
+                }
+            }
 
             // 服务器停止后更新状态
             state.status.write().await.running = false;
@@ -148,6 +324,11 @@ impl ProxyServer {
             log::info!("URL 延迟测试服务已启动，间隔 {} 秒", interval);
         }
 
+        // 启动供应商主动健康检查（未配置检查的供应商不会被探测）
+        self.state.health_checker.start();
+        // 启动首选供应商切回探测（切回方式为 never 的应用不会被探测）
+        self.state.switch_back.start();
+
         Ok(ProxyServerInfo {
             address: self.config.listen_address.clone(),
             port: self.config.listen_port,
@@ -158,6 +339,8 @@ impl ProxyServer {
     pub async fn stop(&self) -> Result<(), ProxyError> {
         // 1. 停止 URL 延迟测试服务
         self.state.latency_service.stop().await;
+        self.state.health_checker.stop();
+        self.state.switch_back.stop();
 
         // 2. 发送关闭信号
         if let Some(tx) = self.shutdown_tx.write().await.take() {
@@ -180,6 +363,39 @@ impl ProxyServer {
 
     pub async fn get_status(&self) -> ProxyStatus {
         let mut status = self.state.status.read().await.clone();
+        status.active_streams = self.state.active_streams.load(Ordering::Relaxed);
+        status.response_cache = self.state.response_cache.stats();
+        status.size_limits = self.state.size_limits.stats();
+        status.switch_back = self.state.switch_back.candidates();
+        status.endpoint_outages = self.state.url_router.endpoint_outages();
+        {
+            let received = self
+                .state
+                .received_bytes
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            for stats in &mut status.provider_stats {
+                let key = (stats.app_type.clone(), stats.provider_id.clone());
+                stats.bytes_received = received.get(&key).copied().unwrap_or(0);
+            }
+        }
+        for stats in &mut status.provider_stats {
+            let circuit = self
+                .state
+                .provider_router
+                .get_circuit_breaker_stats(&stats.provider_id, &stats.app_type)
+                .await
+                .map_or(CircuitState::Closed, |s| s.state);
+            stats.health_score = stats.compute_health_score(circuit);
+            stats.endpoint_ejections = self
+                .state
+                .url_router
+                .endpoint_ejections(&stats.provider_id, &stats.app_type);
+            stats.in_flight = self
+                .state
+                .provider_router
+                .in_flight(&stats.provider_id, &stats.app_type);
+        }
 
         // 计算运行时间
         if let Some(start) = *self.state.start_time.read().await {
@@ -200,43 +416,72 @@ impl ProxyServer {
         status
     }
 
-    fn build_router(&self) -> Router {
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any);
+    /// 构建路由；`only_app` 为 Some 时只注册该应用的端点（用于应用独立端口）
+    fn build_router(&self, cors: Option<CorsLayer>, only_app: Option<&str>) -> Router {
+        let serves = |app: &str| only_app.is_none_or(|only| only == app);
 
-        Router::new()
+        let mut router = Router::new()
             // 健康检查
             .route("/health", get(handlers::health_check))
-            .route("/status", get(handlers::get_status))
+            .route("/status", get(handlers::get_status));
+        if serves("claude") {
             // Claude API (支持带前缀和不带前缀两种格式)
-            .route("/v1/messages", post(handlers::handle_messages))
-            .route("/claude/v1/messages", post(handlers::handle_messages))
-            // OpenAI Chat Completions API (Codex CLI，支持带前缀和不带前缀)
-            .route("/chat/completions", post(handlers::handle_chat_completions))
-            .route(
-                "/v1/chat/completions",
-                post(handlers::handle_chat_completions),
-            )
-            .route(
-                "/v1/v1/chat/completions",
-                post(handlers::handle_chat_completions),
-            )
-            .route(
-                "/codex/v1/chat/completions",
-                post(handlers::handle_chat_completions),
-            )
-            // OpenAI Responses API (Codex CLI，支持带前缀和不带前缀)
-            .route("/responses", post(handlers::handle_responses))
-            .route("/v1/responses", post(handlers::handle_responses))
-            .route("/v1/v1/responses", post(handlers::handle_responses))
-            .route("/codex/v1/responses", post(handlers::handle_responses))
+            router = router
+                .route("/v1/messages", post(handlers::handle_messages))
+                .route("/claude/v1/messages", post(handlers::handle_messages));
+        }
+        if serves("codex") {
+            router = router
+                // OpenAI Chat Completions API (Codex CLI，支持带前缀和不带前缀)
+                .route("/chat/completions", post(handlers::handle_chat_completions))
+                .route(
+                    "/v1/chat/completions",
+                    post(handlers::handle_chat_completions),
+                )
+                .route(
+                    "/v1/v1/chat/completions",
+                    post(handlers::handle_chat_completions),
+                )
+                .route(
+                    "/codex/v1/chat/completions",
+                    post(handlers::handle_chat_completions),
+                )
+                // OpenAI Responses API (Codex CLI，支持带前缀和不带前缀)
+                .route("/responses", post(handlers::handle_responses))
+                .route("/v1/responses", post(handlers::handle_responses))
+                .route("/v1/v1/responses", post(handlers::handle_responses))
+                .route("/codex/v1/responses", post(handlers::handle_responses));
+        }
+        if serves("gemini") {
             // Gemini API (支持带前缀和不带前缀)
-            .route("/v1beta/*path", post(handlers::handle_gemini))
-            .route("/gemini/v1beta/*path", post(handlers::handle_gemini))
-            .layer(cors)
-            .with_state(self.state.clone())
+            router = router
+                .route("/v1beta/*path", post(handlers::handle_gemini))
+                .route("/gemini/v1beta/*path", post(handlers::handle_gemini));
+        }
+
+        let router = router
+            // 请求体大小上限由 size_limits 按设置控制，关闭 axum 默认的 2MB 限制
+            .route_layer(DefaultBodyLimit::disable())
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                size_limits::enforce,
+            ))
+            // 客户端侧限流，仅作用于已匹配的代理端点
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                rate_limiter::enforce,
+            ))
+            // 访问令牌校验，先于限流执行
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                auth::enforce,
+            ))
+            .with_state(self.state.clone());
+
+        match cors {
+            Some(cors) => router.layer(cors),
+            None => router,
+        }
     }
 
     /// 在不重启服务的情况下更新运行时配置
@@ -244,6 +489,15 @@ impl ProxyServer {
         *self.state.config.write().await = config.clone();
     }
 
+    /// 更新允许连接的客户端网段，对之后建立的连接生效
+    pub fn set_ip_allowlist(&self, allowlist: IpAllowlist) {
+        *self
+            .state
+            .ip_allowlist
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = allowlist;
+    }
+
     /// 热更新熔断器配置
     ///
     /// 将新配置应用到所有已创建的熔断器实例
@@ -254,6 +508,35 @@ impl ProxyServer {
         self.state.provider_router.update_all_configs(config).await;
     }
 
+    /// 当前处于打开状态的供应商熔断器数量
+    pub async fn open_circuit_breaker_count(&self) -> usize {
+        self.state.provider_router.count_open_breakers().await
+    }
+
+    /// 立即测试指定应用所有端点的延迟
+    pub async fn test_latency_now(&self, app_type: &str) -> Result<(), AppError> {
+        self.state.latency_service.test_now(app_type).await
+    }
+
+    /// 指定 Provider 最近一次主动健康检查结果
+    pub fn health_check_result(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Option<HealthProbeResult> {
+        self.state.health_checker.result(app_type, provider_id)
+    }
+
+    /// 立即切回指定应用已恢复的首选供应商，返回其名称
+    pub async fn switch_back_now(&self, app_type: &str) -> Result<String, AppError> {
+        self.state.switch_back.switch_now(app_type).await
+    }
+
+    /// 清空响应缓存及其命中统计
+    pub fn clear_response_cache(&self) {
+        self.state.response_cache.clear();
+    }
+
     /// 重置指定 Provider 的熔断器
     pub async fn reset_provider_circuit_breaker(&self, provider_id: &str, app_type: &str) {
         self.state
@@ -262,3 +545,122 @@ impl ProxyServer {
             .await;
     }
 }
+
+/// 代理监听器：TCP 端口或 Unix socket
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+}
+
+/// 已接受的连接
+enum Connection {
+    Tcp(TcpStream, SocketAddr),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl Listener {
+    async fn accept(&self) -> std::io::Result<Connection> {
+        match self {
+            Listener::Tcp(listener) => listener
+                .accept()
+                .await
+                .map(|(stream, peer)| Connection::Tcp(stream, peer)),
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .accept()
+                .await
+                .map(|(stream, _)| Connection::Unix(stream)),
+        }
+    }
+}
+
+/// 接受连接并提供服务，收到关闭信号后停止接受新连接
+///
+/// 不在 IP 白名单内的 TCP 连接在握手前直接关闭；配置了证书时先完成 TLS 握手。
+/// Unix socket 的访问由文件权限控制，不检查白名单也不使用 TLS。
+/// 停止时通知已建立的连接处理完当前请求后关闭，并等待所有连接结束
+async fn serve(
+    listener: Listener,
+    app: Router,
+    acceptor: Option<TlsAcceptor>,
+    state: ProxyState,
+    mut stop_rx: watch::Receiver<()>,
+) {
+    // 每个连接持有一个接收端，全部释放即表示所有连接已结束
+    let (close_tx, close_rx) = watch::channel(());
+    loop {
+        let connection = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log::warn!("接受连接失败: {e}");
+                    continue;
+                }
+            },
+            _ = stop_rx.changed() => break,
+        };
+
+        if let Connection::Tcp(_, peer) = &connection {
+            let allowed = state
+                .ip_allowlist
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .allows(peer.ip());
+            if !allowed {
+                log::warn!("拒绝来自 {peer} 的连接：不在 IP 白名单内");
+                continue;
+            }
+        }
+
+        let app = app.clone();
+        let acceptor = acceptor.clone();
+        let stop_rx = stop_rx.clone();
+        let close_rx = close_rx.clone();
+        tokio::spawn(async move {
+            match connection {
+                Connection::Tcp(stream, peer) => match acceptor {
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(stream) => serve_connection(stream, app, peer, stop_rx).await,
+                        Err(e) => log::debug!("TLS 握手失败 ({peer}): {e}"),
+                    },
+                    None => serve_connection(stream, app, peer, stop_rx).await,
+                },
+                #[cfg(unix)]
+                Connection::Unix(stream) => {
+                    serve_connection(stream, app, "unix socket", stop_rx).await
+                }
+            }
+            drop(close_rx);
+        });
+    }
+
+    drop(close_rx);
+    close_tx.closed().await;
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    app: Router,
+    peer: impl fmt::Display,
+    mut stop_rx: watch::Receiver<()>,
+) where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let builder = Builder::new(TokioExecutor::new());
+    let connection =
+        builder.serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(app));
+    tokio::pin!(connection);
+
+    let result = tokio::select! {
+        result = connection.as_mut() => result,
+        _ = stop_rx.changed() => {
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    };
+    if let Err(e) = result {
+        log::debug!("连接异常结束 ({peer}): {e}");
+    }
+}
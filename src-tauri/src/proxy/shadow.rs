@@ -0,0 +1,148 @@
+//! 流量镜像
+//!
+//! 把一定比例的请求复制一份发往另一个供应商（通常是准备加入故障转移队列的新中转）。镜像请求
+//! 与主请求并行发送，响应读完后直接丢弃，只记录状态码、延迟以及与主请求的状态码是否一致，
+//! 用真实流量验证新中转的正确性与延迟。镜像请求不计入熔断器、用量与主请求的统计。
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::percentile;
+
+/// 参与计算延迟分位数的最近样本数
+const LATENCY_SAMPLE_WINDOW: usize = 200;
+
+/// 单个应用的流量镜像设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowConfig {
+    /// 镜像目标供应商，None 表示关闭
+    pub provider_id: Option<String>,
+    /// 镜像的请求比例（0-100）
+    pub percent: u32,
+}
+
+impl ShadowConfig {
+    pub fn is_active(&self) -> bool {
+        self.provider_id.is_some() && self.percent > 0
+    }
+
+    /// 按随机数 `roll` 决定本次请求是否镜像
+    pub fn should_mirror(&self, roll: u64) -> bool {
+        self.is_active() && roll % 100 < u64::from(self.percent.min(100))
+    }
+}
+
+/// 一次镜像请求的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowOutcome {
+    /// 镜像响应的状态码，请求失败时为 None
+    pub status: Option<u16>,
+    /// 主请求最终的状态码，未拿到上游响应时为 None
+    pub primary_status: Option<u16>,
+    /// 收到响应头的耗时
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// 镜像目标供应商的统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowStats {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub requests: u64,
+    pub failures: u64,
+    /// 与主请求状态码不一致的次数（任一方没有响应时不比较）
+    pub status_mismatches: u64,
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    latency_samples: VecDeque<u64>,
+}
+
+impl ShadowStats {
+    pub fn new(app_type: &str, provider_id: &str, provider_name: &str) -> Self {
+        Self {
+            app_type: app_type.to_string(),
+            provider_id: provider_id.to_string(),
+            provider_name: provider_name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, outcome: &ShadowOutcome) {
+        self.requests += 1;
+        if let Some(error) = &outcome.error {
+            self.failures += 1;
+            self.last_error = Some(error.clone());
+        }
+        if let (Some(status), Some(primary)) = (outcome.status, outcome.primary_status) {
+            if status != primary {
+                self.status_mismatches += 1;
+            }
+        }
+        if outcome.status.is_some() {
+            if self.latency_samples.len() >= LATENCY_SAMPLE_WINDOW {
+                self.latency_samples.pop_front();
+            }
+            self.latency_samples.push_back(outcome.latency_ms);
+            let mut sorted: Vec<u64> = self.latency_samples.iter().copied().collect();
+            sorted.sort_unstable();
+            self.p50_latency_ms = percentile(&sorted, 50);
+            self.p95_latency_ms = percentile(&sorted, 95);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrors_the_configured_share_of_requests() {
+        let config = ShadowConfig {
+            provider_id: Some("new-relay".to_string()),
+            percent: 10,
+        };
+        let mirrored = (0..1000u64)
+            .filter(|&roll| config.should_mirror(roll))
+            .count();
+        assert_eq!(mirrored, 100);
+        assert!(!ShadowConfig::default().should_mirror(0));
+        let all = ShadowConfig {
+            percent: 250,
+            ..config
+        };
+        assert!((0..100u64).all(|roll| all.should_mirror(roll)));
+    }
+
+    #[test]
+    fn stats_count_failures_mismatches_and_latency() {
+        let mut stats = ShadowStats::default();
+        let ok = |latency_ms, primary_status| ShadowOutcome {
+            status: Some(200),
+            primary_status,
+            latency_ms,
+            error: None,
+        };
+        stats.record(&ok(100, Some(200)));
+        stats.record(&ok(300, Some(500)));
+        stats.record(&ok(200, None));
+        stats.record(&ShadowOutcome {
+            status: None,
+            primary_status: Some(200),
+            latency_ms: 10_000,
+            error: Some("connection refused".to_string()),
+        });
+
+        assert_eq!(stats.requests, 4);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.status_mismatches, 1);
+        assert_eq!(stats.p50_latency_ms, Some(200));
+        assert_eq!(stats.p95_latency_ms, Some(300));
+        assert_eq!(stats.last_error.as_deref(), Some("connection refused"));
+    }
+}
@@ -0,0 +1,246 @@
+//! 请求与响应体大小上限
+//!
+//! 失控的 Agent 可能把大量文件内容塞进一次请求，上游也可能返回异常庞大的响应，拖垮代理和
+//! 客户端。请求体超过上限时在转发前以 413 拒绝；非流式响应超过上限时返回 502；流式响应的
+//! 响应头已经发出，超限时追加一条错误事件后结束。上限为 0 表示不限制，超限次数计入代理状态。
+
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bytes::{Bytes, BytesMut};
+use futures::stream::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::{server::ProxyState, ProxyError};
+
+/// 请求与响应体大小上限（KB），0 表示不限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeLimitConfig {
+    pub max_request_kb: u64,
+    pub max_response_kb: u64,
+}
+
+impl Default for SizeLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_request_kb: 32 * 1024,
+            max_response_kb: 64 * 1024,
+        }
+    }
+}
+
+impl SizeLimitConfig {
+    pub fn max_request_bytes(&self) -> Option<usize> {
+        limit_bytes(self.max_request_kb)
+    }
+
+    pub fn max_response_bytes(&self) -> Option<usize> {
+        limit_bytes(self.max_response_kb)
+    }
+}
+
+fn limit_bytes(kb: u64) -> Option<usize> {
+    (kb > 0).then(|| usize::try_from(kb.saturating_mul(1024)).unwrap_or(usize::MAX))
+}
+
+/// 超限次数，展示在代理面板
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeLimitStats {
+    /// 因请求体过大被拒绝的请求数
+    pub rejected_requests: u64,
+    /// 因响应体过大被中止的响应数
+    pub rejected_responses: u64,
+}
+
+/// 本次运行中的超限计数
+#[derive(Debug, Default)]
+pub struct SizeLimitCounters {
+    requests: AtomicU64,
+    responses: AtomicU64,
+}
+
+impl SizeLimitCounters {
+    pub fn stats(&self) -> SizeLimitStats {
+        SizeLimitStats {
+            rejected_requests: self.requests.load(Ordering::Relaxed),
+            rejected_responses: self.responses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_response(&self) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn content_length(headers: &axum::http::HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// 读完整个数据流，累计超过 `limit` 字节时提前返回 None
+async fn read_limited<E>(
+    stream: impl Stream<Item = Result<Bytes, E>>,
+    limit: usize,
+) -> Result<Option<Bytes>, E> {
+    tokio::pin!(stream);
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if buffer.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(Some(buffer.freeze()))
+}
+
+/// 路由中间件：请求体超过上限时返回 413，不再转发
+pub async fn enforce(State(state): State<ProxyState>, request: Request, next: Next) -> Response {
+    let config = match state.db.get_proxy_size_limits() {
+        Ok(config) => config,
+        Err(e) => return ProxyError::DatabaseError(e.to_string()).into_response(),
+    };
+    let Some(limit) = config.max_request_bytes() else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path().to_string();
+    let reject = || {
+        log::warn!("拒绝请求 {path}: 请求体超过 {} KB", config.max_request_kb);
+        state.size_limits.record_request();
+        ProxyError::RequestTooLarge {
+            limit_kb: config.max_request_kb,
+        }
+        .into_response()
+    };
+    if content_length(request.headers()).is_some_and(|len| len > limit as u64) {
+        return reject();
+    }
+
+    let (parts, body) = request.into_parts();
+    match read_limited(body.into_data_stream(), limit).await {
+        Ok(Some(bytes)) => {
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        Ok(None) => reject(),
+        Err(e) => {
+            ProxyError::InvalidRequest(format!("Failed to read request body: {e}")).into_response()
+        }
+    }
+}
+
+/// 读取非流式响应体，超过上限时返回 [`ProxyError::ResponseTooLarge`]
+pub async fn read_response_body(
+    response: reqwest::Response,
+    state: &ProxyState,
+    tag: &str,
+) -> Result<Bytes, ProxyError> {
+    let config = state.db.get_proxy_size_limits().unwrap_or_default();
+    let read_failed = |e: reqwest::Error| {
+        log::error!("[{tag}] 读取响应失败: {e}");
+        ProxyError::ForwardFailed(format!("Failed to read response body: {e}"))
+    };
+    let Some(limit) = config.max_response_bytes() else {
+        return response.bytes().await.map_err(read_failed);
+    };
+
+    let body = if content_length(response.headers()).is_some_and(|len| len > limit as u64) {
+        None
+    } else {
+        read_limited(response.bytes_stream(), limit)
+            .await
+            .map_err(read_failed)?
+    };
+    body.ok_or_else(|| {
+        log::error!("[{tag}] 上游响应超过 {} KB，已中止", config.max_response_kb);
+        state.size_limits.record_response();
+        ProxyError::ResponseTooLarge {
+            limit_kb: config.max_response_kb,
+        }
+    })
+}
+
+/// 流式响应累计超过上限时以错误结束，由下游追加错误事件
+pub fn limit_response_stream<E: std::fmt::Display>(
+    stream: impl Stream<Item = Result<Bytes, E>> + Send + 'static,
+    state: &ProxyState,
+    tag: &'static str,
+) -> impl Stream<Item = Result<Bytes, io::Error>> + Send + 'static {
+    let config = state.db.get_proxy_size_limits().unwrap_or_default();
+    let limit = config.max_response_bytes();
+    let state = state.clone();
+    // 状态为 None 表示已超限，不再读取上游
+    stream.scan(Some(0usize), move |received, chunk| {
+        let Some(total) = received.as_mut() else {
+            return futures::future::ready(None);
+        };
+        let item = match chunk {
+            Ok(bytes) => {
+                *total += bytes.len();
+                if limit.is_some_and(|limit| *total > limit) {
+                    *received = None;
+                    log::error!("[{tag}] 流式响应超过 {} KB，已中止", config.max_response_kb);
+                    state.size_limits.record_response();
+                    Err(io::Error::other(
+                        ProxyError::ResponseTooLarge {
+                            limit_kb: config.max_response_kb,
+                        }
+                        .to_string(),
+                    ))
+                } else {
+                    Ok(bytes)
+                }
+            }
+            Err(e) => Err(io::Error::other(e.to_string())),
+        };
+        futures::future::ready(Some(item))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+
+    fn chunks(sizes: &[usize]) -> impl Stream<Item = Result<Bytes, io::Error>> {
+        stream::iter(
+            sizes
+                .iter()
+                .map(|&n| Ok(Bytes::from(vec![b'x'; n])))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn read_limited_stops_once_the_limit_is_exceeded() {
+        let body = read_limited(chunks(&[4, 4]), 8).await.unwrap();
+        assert_eq!(body.map(|b| b.len()), Some(8));
+        assert!(read_limited(chunks(&[4, 4, 1]), 8).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn zero_means_unlimited() {
+        let config = SizeLimitConfig {
+            max_request_kb: 0,
+            max_response_kb: 2,
+        };
+        assert_eq!(config.max_request_bytes(), None);
+        assert_eq!(config.max_response_bytes(), Some(2048));
+    }
+}
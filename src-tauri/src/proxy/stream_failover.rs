@@ -0,0 +1,212 @@
+//! 流式响应中断处理
+//!
+//! 上游在流式响应完成前断开时，代理不再静默截断：
+//! - 开启中断重试后，转发器先读到首个完整 SSE 事件再确认供应商可用，
+//!   在此之前断开、出错或超时都视为可重试错误，从头改用下一个供应商；
+//! - 事件一旦发给客户端就无法安全重试，此时按客户端协议追加一条错误事件后结束流。
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use serde_json::{json, Value};
+
+use super::{session::ClientFormat, ProxyError};
+
+/// 读取上游流式响应直到出现首个完整事件，返回内容不变的响应
+pub async fn prefetch_first_event(
+    response: reqwest::Response,
+    timeout: Option<Duration>,
+) -> Result<reqwest::Response, ProxyError> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mut upstream = response.bytes_stream();
+    let mut head: Vec<Bytes> = Vec::new();
+
+    let read = async {
+        let mut seen: Vec<u8> = Vec::new();
+        while let Some(chunk) = upstream.next().await {
+            let chunk = chunk
+                .map_err(|e| ProxyError::ForwardFailed(format!("流式响应在首个事件前中断: {e}")))?;
+            seen.extend_from_slice(&chunk);
+            head.push(chunk);
+            if contains_event_boundary(&seen) {
+                return Ok(());
+            }
+        }
+        Err(ProxyError::ForwardFailed(
+            "上游在发送首个事件前关闭了流式响应".to_string(),
+        ))
+    };
+    match timeout {
+        Some(duration) => tokio::time::timeout(duration, read).await.map_err(|_| {
+            ProxyError::Timeout(format!("流式响应首个事件超时 ({}秒)", duration.as_secs()))
+        })??,
+        None => read.await?,
+    }
+
+    let body = stream::iter(head.into_iter().map(Ok)).chain(upstream);
+    let mut builder = axum::http::Response::builder()
+        .status(status)
+        .version(version);
+    if let Some(map) = builder.headers_mut() {
+        *map = headers;
+    }
+    let rebuilt = builder
+        .body(reqwest::Body::wrap_stream(body))
+        .map_err(|e| ProxyError::Internal(e.to_string()))?;
+    Ok(reqwest::Response::from(rebuilt))
+}
+
+fn contains_event_boundary(bytes: &[u8]) -> bool {
+    bytes.windows(2).any(|w| w == b"\n\n") || bytes.windows(4).any(|w| w == b"\r\n\r\n")
+}
+
+/// `data:` 负载是否表示流已正常结束（或上游已自行发送了错误事件）
+pub fn is_terminal_event(data: &str) -> bool {
+    let data = data.trim();
+    if data == "[DONE]" {
+        return true;
+    }
+    let Ok(value) = serde_json::from_str::<Value>(data) else {
+        return false;
+    };
+    if value.get("error").is_some() {
+        return true;
+    }
+    if let Some(kind) = value.get("type").and_then(|t| t.as_str()) {
+        if matches!(
+            kind,
+            "message_stop"
+                | "error"
+                | "response.completed"
+                | "response.failed"
+                | "response.incomplete"
+        ) {
+            return true;
+        }
+    }
+    let finished = |list: &str, field: &str| {
+        value
+            .get(list)
+            .and_then(|v| v.as_array())
+            .is_some_and(|items| {
+                items
+                    .iter()
+                    .any(|item| item.get(field).is_some_and(|reason| !reason.is_null()))
+            })
+    };
+    finished("choices", "finish_reason") || finished("candidates", "finishReason")
+}
+
+/// 按客户端协议构造一条 SSE 错误事件
+pub fn error_event(format: ClientFormat, message: &str) -> Bytes {
+    let event = match format {
+        ClientFormat::Claude => format!(
+            "event: error\ndata: {}\n\n",
+            json!({
+                "type": "error",
+                "error": { "type": "api_error", "message": message },
+            })
+        ),
+        ClientFormat::Codex => format!(
+            "event: error\ndata: {}\n\n",
+            json!({
+                "type": "error",
+                "code": "stream_interrupted",
+                "message": message,
+            })
+        ),
+        ClientFormat::Gemini | ClientFormat::GeminiCli => format!(
+            "data: {}\n\n",
+            json!({
+                "error": { "code": 502, "message": message, "status": "UNAVAILABLE" },
+            })
+        ),
+        ClientFormat::OpenAI | ClientFormat::Unknown => format!(
+            "data: {}\n\n",
+            json!({
+                "error": { "message": message, "type": "proxy_error" },
+            })
+        ),
+    };
+    Bytes::from(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sse_response(chunks: Vec<Result<&'static str, std::io::Error>>) -> reqwest::Response {
+        let body = stream::iter(chunks.into_iter().map(|c| c.map(Bytes::from)));
+        axum::http::Response::builder()
+            .header("content-type", "text/event-stream")
+            .body(reqwest::Body::wrap_stream(body))
+            .map(reqwest::Response::from)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn prefetch_rejects_streams_closed_before_first_event() {
+        let response = sse_response(vec![Ok("event: message_start\n")]);
+        assert!(matches!(
+            prefetch_first_event(response, None).await,
+            Err(ProxyError::ForwardFailed(_))
+        ));
+
+        let response = sse_response(vec![Err(std::io::Error::other("reset"))]);
+        assert!(prefetch_first_event(response, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn prefetch_keeps_the_full_body() {
+        let response = sse_response(vec![
+            Ok("data: {\"a\":"),
+            Ok("1}\n\n"),
+            Ok("data: [DONE]\n\n"),
+        ]);
+        let response = prefetch_first_event(response, None).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        assert_eq!(
+            response.text().await.unwrap(),
+            "data: {\"a\":1}\n\ndata: [DONE]\n\n"
+        );
+    }
+
+    #[test]
+    fn terminal_events_are_detected_per_protocol() {
+        assert!(is_terminal_event("[DONE]"));
+        assert!(is_terminal_event(r#"{"type":"message_stop"}"#));
+        assert!(is_terminal_event(r#"{"type":"response.completed"}"#));
+        assert!(is_terminal_event(
+            r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#
+        ));
+        assert!(is_terminal_event(
+            r#"{"candidates":[{"finishReason":"STOP"}]}"#
+        ));
+        assert!(is_terminal_event(r#"{"error":{"message":"overloaded"}}"#));
+
+        assert!(!is_terminal_event(r#"{"type":"content_block_delta"}"#));
+        assert!(!is_terminal_event(
+            r#"{"choices":[{"delta":{"content":"hi"},"finish_reason":null}]}"#
+        ));
+    }
+
+    #[test]
+    fn error_events_match_client_protocol() {
+        let claude = error_event(ClientFormat::Claude, "boom");
+        let text = std::str::from_utf8(&claude).unwrap();
+        assert!(text.starts_with("event: error\ndata: "));
+        let data: Value =
+            serde_json::from_str(text.lines().nth(1).unwrap().strip_prefix("data: ").unwrap())
+                .unwrap();
+        assert_eq!(data["error"]["message"], "boom");
+
+        let openai = error_event(ClientFormat::OpenAI, "boom");
+        let data = std::str::from_utf8(&openai).unwrap();
+        assert!(is_terminal_event(
+            data.trim().strip_prefix("data: ").unwrap()
+        ));
+    }
+}
@@ -0,0 +1,453 @@
+//! 故障转移后切回首选供应商
+//!
+//! 首选供应商即故障转移队列中的第一个。故障转移把当前供应商切走后，后台定期探测首选供应商
+//! （按其健康检查设置，未设置时使用默认探测），连续健康满设定时长即可切回：auto 模式自动切回，
+//! manual 模式只在代理面板提示、由用户确认，never 模式不探测。负载均衡时首选供应商本就轮换，
+//! 不做处理。
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::failover_switch::FailoverSwitchManager;
+use super::health::{probe_client, probe_via_upstream};
+use super::provider_router::ProviderRouter;
+use super::types::LoadBalanceStrategy;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::provider::Provider;
+
+/// 探测首选供应商的间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 参与切回的应用类型
+const APP_TYPES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 切回首选供应商的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchBackMode {
+    /// 首选供应商连续健康满设定时长后自动切回
+    Auto,
+    /// 只提示可以切回，由用户确认
+    #[default]
+    Manual,
+    /// 不探测、不提示
+    Never,
+}
+
+impl SwitchBackMode {
+    pub const ALL: [SwitchBackMode; 3] = [Self::Auto, Self::Manual, Self::Never];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Manual => "manual",
+            Self::Never => "never",
+        }
+    }
+
+    /// 界面展示用名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Auto => "automatic",
+            Self::Manual => "ask first",
+            Self::Never => "never",
+        }
+    }
+
+    /// 循环切换到下一种方式
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl FromStr for SwitchBackMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| format!("Invalid switch-back mode: {s}"))
+    }
+}
+
+/// 单个应用的切回设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchBackConfig {
+    pub mode: SwitchBackMode,
+    /// 首选供应商需要连续健康的时长（秒）
+    pub healthy_window_seconds: u64,
+}
+
+impl Default for SwitchBackConfig {
+    fn default() -> Self {
+        Self {
+            mode: SwitchBackMode::Manual,
+            healthy_window_seconds: 300,
+        }
+    }
+}
+
+/// 正在恢复的首选供应商，展示在代理面板
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchBackCandidate {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 开始连续健康的时间（毫秒时间戳）
+    pub healthy_since: i64,
+    /// 已连续健康满设定时长，可以切回
+    pub ready: bool,
+}
+
+/// 后台探测首选供应商的任务，随代理启动与停止
+pub struct SwitchBackMonitor {
+    db: Arc<Database>,
+    provider_router: Arc<ProviderRouter>,
+    failover_manager: Arc<FailoverSwitchManager>,
+    client: Client,
+    /// 各应用正在恢复的首选供应商，key 为 app_type
+    candidates: Mutex<HashMap<String, SwitchBackCandidate>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SwitchBackMonitor {
+    pub fn new(
+        db: Arc<Database>,
+        provider_router: Arc<ProviderRouter>,
+        failover_manager: Arc<FailoverSwitchManager>,
+    ) -> Self {
+        Self {
+            db,
+            provider_router,
+            failover_manager,
+            client: probe_client(),
+            candidates: Mutex::new(HashMap::new()),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台探测；每轮重新读取设置，修改后无需重启
+    pub fn start(self: &Arc<Self>) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+        let monitor = self.clone();
+        *task = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(PROBE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                for app_type in APP_TYPES {
+                    if let Err(e) = monitor.check(app_type).await {
+                        log::warn!("[SwitchBack] [{app_type}] 检查首选供应商失败: {e}");
+                        monitor.clear(app_type);
+                    }
+                }
+            }
+        }));
+    }
+
+    /// 停止后台探测
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+        self.candidates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// 各应用正在恢复的首选供应商
+    pub fn candidates(&self) -> Vec<SwitchBackCandidate> {
+        let candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+        APP_TYPES
+            .iter()
+            .filter_map(|app_type| candidates.get(*app_type).cloned())
+            .collect()
+    }
+
+    /// 立即切回已恢复的首选供应商，返回其名称
+    pub async fn switch_now(&self, app_type: &str) -> Result<String, AppError> {
+        let candidate = self
+            .candidates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(app_type)
+            .filter(|c| c.ready)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::Message("The preferred provider is not ready to switch back".to_string())
+            })?;
+        self.switch_back(&candidate, "Switched back manually")
+            .await?;
+        Ok(candidate.provider_name)
+    }
+
+    fn clear(&self, app_type: &str) {
+        self.candidates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(app_type);
+    }
+
+    /// 探测一次首选供应商并更新其连续健康状态，auto 模式下满足条件时切回
+    async fn check(&self, app_type: &str) -> Result<(), AppError> {
+        let config = self.db.get_switch_back_config(app_type)?;
+        let Some(preferred) = self.preferred_provider(app_type, config.mode).await? else {
+            self.clear(app_type);
+            return Ok(());
+        };
+
+        let own_check = preferred
+            .meta
+            .as_ref()
+            .and_then(|m| m.health_check.clone())
+            .filter(|c| c.enabled);
+        let upstream_proxy = self.db.get_proxy_upstream_url().unwrap_or_default();
+        let result = probe_via_upstream(
+            &self.client,
+            upstream_proxy.as_deref(),
+            app_type,
+            &preferred,
+            &own_check.clone().unwrap_or_default(),
+        )
+        .await;
+        // 配置了健康检查的供应商由 HealthChecker 写入熔断器，这里不重复计数
+        if own_check.is_none() {
+            self.provider_router
+                .record_probe_result(
+                    &preferred.id,
+                    app_type,
+                    result.healthy,
+                    result.error.clone(),
+                )
+                .await?;
+        }
+        if !result.healthy {
+            log::debug!(
+                "[SwitchBack] [{app_type}] 首选供应商 {} 仍不可用: {}",
+                preferred.name,
+                result.error.as_deref().unwrap_or("unknown error")
+            );
+            self.clear(app_type);
+            return Ok(());
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let window_ms =
+            i64::try_from(config.healthy_window_seconds.saturating_mul(1000)).unwrap_or(i64::MAX);
+        let candidate = {
+            let mut candidates = self.candidates.lock().unwrap_or_else(|e| e.into_inner());
+            let candidate =
+                candidates
+                    .entry(app_type.to_string())
+                    .or_insert_with(|| SwitchBackCandidate {
+                        app_type: app_type.to_string(),
+                        provider_id: preferred.id.clone(),
+                        provider_name: preferred.name.clone(),
+                        healthy_since: now,
+                        ready: false,
+                    });
+            if candidate.provider_id != preferred.id {
+                // 队列顺序变了，重新计时
+                candidate.provider_id = preferred.id.clone();
+                candidate.healthy_since = now;
+            }
+            candidate.provider_name = preferred.name.clone();
+            candidate.ready = now - candidate.healthy_since >= window_ms;
+            candidate.clone()
+        };
+
+        if candidate.ready && config.mode == SwitchBackMode::Auto {
+            let reason = format!(
+                "Switched back: {} healthy for {}s",
+                candidate.provider_name, config.healthy_window_seconds
+            );
+            self.switch_back(&candidate, &reason).await?;
+        }
+        Ok(())
+    }
+
+    /// 需要切回时返回首选供应商：代理已接管且开启故障转移、按队列顺序路由、当前供应商
+    /// 是队列中排在后面的供应商（不在队列中说明是用户手动选择的，不切走）
+    async fn preferred_provider(
+        &self,
+        app_type: &str,
+        mode: SwitchBackMode,
+    ) -> Result<Option<Provider>, AppError> {
+        if mode == SwitchBackMode::Never {
+            return Ok(None);
+        }
+        let proxy_config = self.db.get_proxy_config_for_app(app_type).await?;
+        if !proxy_config.enabled || !proxy_config.auto_failover_enabled {
+            return Ok(None);
+        }
+        if self.db.get_load_balance_strategy(app_type)? != LoadBalanceStrategy::Failover {
+            return Ok(None);
+        }
+
+        let queue = self.db.get_failover_queue(app_type)?;
+        let Some(first) = queue.first() else {
+            return Ok(None);
+        };
+        let failed_over = self
+            .db
+            .get_current_provider(app_type)?
+            .is_some_and(|current| {
+                current != first.provider_id && queue.iter().any(|item| item.provider_id == current)
+            });
+        if !failed_over {
+            return Ok(None);
+        }
+        self.db.get_provider_by_id(&first.provider_id, app_type)
+    }
+
+    async fn switch_back(
+        &self,
+        candidate: &SwitchBackCandidate,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        let switched = self
+            .failover_manager
+            .try_switch(
+                &candidate.app_type,
+                &candidate.provider_id,
+                &candidate.provider_name,
+                Some(reason),
+            )
+            .await?;
+        self.clear(&candidate.app_type);
+        if !switched {
+            return Err(AppError::Message(format!(
+                "Switch back to {} was skipped",
+                candidate.provider_name
+            )));
+        }
+        log::info!(
+            "[SwitchBack] [{}] 已切回首选供应商 {}",
+            candidate.app_type,
+            candidate.provider_name
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 启动只返回固定状态码的本地服务，并返回其地址
+    async fn upstream(status: u16) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    async fn monitor_with_failed_over_claude(preferred_url: &str) -> SwitchBackMonitor {
+        let db = Arc::new(Database::memory().unwrap());
+        for (id, url) in [("a", preferred_url), ("b", "http://127.0.0.1:9")] {
+            let provider = Provider::with_id(
+                id.to_string(),
+                format!("Provider {}", id.to_uppercase()),
+                json!({"env": {"ANTHROPIC_BASE_URL": url, "ANTHROPIC_AUTH_TOKEN": "sk-test"}}),
+                None,
+            );
+            db.save_provider("claude", &provider).unwrap();
+            db.add_to_failover_queue("claude", id).unwrap();
+        }
+        db.set_current_provider("claude", "b").unwrap();
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.enabled = true;
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = Arc::new(ProviderRouter::new(db.clone()));
+        let failover = Arc::new(FailoverSwitchManager::new(db.clone()));
+        SwitchBackMonitor::new(db, router, failover)
+    }
+
+    #[tokio::test]
+    async fn healthy_preferred_provider_becomes_ready_after_the_window() {
+        let monitor = monitor_with_failed_over_claude(&upstream(200).await).await;
+        monitor
+            .db
+            .set_switch_back_config(
+                "claude",
+                &SwitchBackConfig {
+                    mode: SwitchBackMode::Manual,
+                    healthy_window_seconds: 0,
+                },
+            )
+            .unwrap();
+
+        monitor.check("claude").await.unwrap();
+        let candidates = monitor.candidates();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].provider_id, "a");
+        assert!(candidates[0].ready);
+        // manual 模式不会自动切回
+        assert_eq!(
+            monitor
+                .db
+                .get_current_provider("claude")
+                .unwrap()
+                .as_deref(),
+            Some("b")
+        );
+
+        // 设定时长未满时只记录开始健康的时间
+        monitor
+            .db
+            .set_switch_back_config("claude", &SwitchBackConfig::default())
+            .unwrap();
+        monitor.clear("claude");
+        monitor.check("claude").await.unwrap();
+        assert!(!monitor.candidates()[0].ready);
+    }
+
+    #[tokio::test]
+    async fn unhealthy_or_disabled_preferred_provider_is_not_a_candidate() {
+        let monitor = monitor_with_failed_over_claude(&upstream(503).await).await;
+        monitor.check("claude").await.unwrap();
+        assert!(monitor.candidates().is_empty());
+        assert!(monitor.switch_now("claude").await.is_err());
+
+        let monitor = monitor_with_failed_over_claude(&upstream(200).await).await;
+        monitor
+            .db
+            .set_switch_back_config(
+                "claude",
+                &SwitchBackConfig {
+                    mode: SwitchBackMode::Never,
+                    healthy_window_seconds: 0,
+                },
+            )
+            .unwrap();
+        monitor.check("claude").await.unwrap();
+        assert!(monitor.candidates().is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! 代理监听器的 HTTPS 支持
+//!
+//! 开启后代理只接受 TLS 连接。证书与私钥可以指定 PEM 文件路径；未指定时使用
+//! `~/.cc-switch/tls/` 下的自签名证书（首次使用时调用 `openssl` 生成，
+//! 覆盖 localhost / 127.0.0.1 / ::1），客户端需要自行信任该证书。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use serde::{Deserialize, Serialize};
+use tokio_rustls::TlsAcceptor;
+
+use super::ProxyError;
+
+/// 自签名证书文件名
+const SELF_SIGNED_CERT_FILE: &str = "proxy-cert.pem";
+const SELF_SIGNED_KEY_FILE: &str = "proxy-key.pem";
+
+/// 自签名证书有效期（天）
+const SELF_SIGNED_VALID_DAYS: &str = "825";
+
+/// 代理 HTTPS 设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyTlsConfig {
+    pub enabled: bool,
+    /// PEM 证书路径，与 `key_path` 均为空时使用自签名证书
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    /// PEM 私钥路径
+    #[serde(default)]
+    pub key_path: Option<String>,
+}
+
+impl ProxyTlsConfig {
+    /// 是否使用自动生成的自签名证书
+    pub fn uses_self_signed(&self) -> bool {
+        self.cert_path.is_none() && self.key_path.is_none()
+    }
+
+    /// 实际使用的证书与私钥路径
+    pub fn resolved_paths(&self) -> Result<(PathBuf, PathBuf), ProxyError> {
+        match (&self.cert_path, &self.key_path) {
+            (Some(cert), Some(key)) => Ok((PathBuf::from(cert), PathBuf::from(key))),
+            (None, None) => Ok(self_signed_paths()),
+            _ => Err(ProxyError::ConfigError(
+                "证书与私钥路径需要同时指定".to_string(),
+            )),
+        }
+    }
+}
+
+/// 自签名证书与私钥的存放位置
+pub fn self_signed_paths() -> (PathBuf, PathBuf) {
+    let dir = crate::config::get_app_config_dir().join("tls");
+    (
+        dir.join(SELF_SIGNED_CERT_FILE),
+        dir.join(SELF_SIGNED_KEY_FILE),
+    )
+}
+
+/// 按配置加载证书（需要时先生成自签名证书）并构造 TLS 接收器
+pub fn build_acceptor(config: &ProxyTlsConfig) -> Result<TlsAcceptor, ProxyError> {
+    let (cert_path, key_path) = config.resolved_paths()?;
+    if config.uses_self_signed() && !(cert_path.exists() && key_path.exists()) {
+        generate_self_signed(&cert_path, &key_path)?;
+    }
+    load_acceptor(&cert_path, &key_path)
+}
+
+fn load_acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor, ProxyError> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            ProxyError::ConfigError(format!("读取证书 {} 失败: {e}", cert_path.display()))
+        })?;
+    if certs.is_empty() {
+        return Err(ProxyError::ConfigError(format!(
+            "证书文件 {} 中没有证书",
+            cert_path.display()
+        )));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        ProxyError::ConfigError(format!("读取私钥 {} 失败: {e}", key_path.display()))
+    })?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut server_config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| ProxyError::ConfigError(format!("证书与私钥无效: {e}")))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// 调用 openssl 生成覆盖本机地址的自签名证书
+fn generate_self_signed(cert_path: &Path, key_path: &Path) -> Result<(), ProxyError> {
+    if let Some(dir) = cert_path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| ProxyError::ConfigError(format!("创建证书目录失败: {e}")))?;
+    }
+    let output = Command::new("openssl")
+        .args(["req", "-x509", "-nodes", "-newkey", "ec"])
+        .args(["-pkeyopt", "ec_paramgen_curve:prime256v1"])
+        .args([
+            "-days",
+            SELF_SIGNED_VALID_DAYS,
+            "-subj",
+            "/CN=cc-switch local proxy",
+        ])
+        .args([
+            "-addext",
+            "subjectAltName=DNS:localhost,IP:127.0.0.1,IP:::1",
+        ])
+        .arg("-keyout")
+        .arg(key_path)
+        .arg("-out")
+        .arg(cert_path)
+        .output()
+        .map_err(|e| {
+            ProxyError::ConfigError(format!(
+                "无法运行 openssl 生成自签名证书（{e}），请改为指定证书与私钥路径"
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(ProxyError::ConfigError(format!(
+            "生成自签名证书失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(key_path, std::fs::Permissions::from_mode(0o600));
+    }
+    log::info!("已生成代理自签名证书: {}", cert_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cert_and_key_paths_must_be_given_together() {
+        let config = ProxyTlsConfig {
+            enabled: true,
+            cert_path: Some("/tmp/cert.pem".to_string()),
+            key_path: None,
+        };
+        assert!(matches!(
+            config.resolved_paths(),
+            Err(ProxyError::ConfigError(_))
+        ));
+        assert!(ProxyTlsConfig::default().uses_self_signed());
+    }
+
+    #[test]
+    fn self_signed_certificate_is_generated_and_loaded() {
+        if Command::new("openssl").arg("version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join(SELF_SIGNED_CERT_FILE);
+        let key = dir.path().join(SELF_SIGNED_KEY_FILE);
+
+        generate_self_signed(&cert, &key).unwrap();
+        assert!(load_acceptor(&cert, &key).is_ok());
+        // 证书与私钥互换时报错
+        assert!(load_acceptor(&key, &cert).is_err());
+    }
+}
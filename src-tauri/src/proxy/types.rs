@@ -1,4 +1,21 @@
+use super::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+use super::dns::DnsCacheStats;
+use super::outlier::EndpointEjection;
+use super::response_cache::ResponseCacheStats;
+use super::shadow::ShadowStats;
+use super::size_limits::SizeLimitStats;
+use super::switch_back::SwitchBackCandidate;
+use super::url_router::EndpointOutage;
+use super::usage::parser::TokenUsage;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 每个 Provider 用于计算延迟分位数的最近样本数
+const LATENCY_SAMPLE_WINDOW: usize = 500;
+
+/// 健康分中 p95 延迟满分 / 零分的阈值（毫秒）
+const HEALTH_LATENCY_GOOD_MS: u64 = 2_000;
+const HEALTH_LATENCY_BAD_MS: u64 = 10_000;
 
 /// 代理服务器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +81,18 @@ pub struct ProxyStatus {
     pub address: String,
     /// 监听端口
     pub port: u16,
+    /// 是否以 HTTPS 监听
+    #[serde(default)]
+    pub tls: bool,
+    /// 设置了独立端口的应用及其端口 (app_type, port)
+    #[serde(default)]
+    pub app_ports: Vec<(String, u16)>,
+    /// 同时监听的 Unix socket 路径
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// 只监听 Unix socket（未监听 TCP 端口）
+    #[serde(default)]
+    pub socket_only: bool,
     /// 活跃连接数
     pub active_connections: usize,
     /// 总请求数
@@ -89,6 +118,257 @@ pub struct ProxyStatus {
     /// 当前活跃的代理目标列表
     #[serde(default)]
     pub active_targets: Vec<ActiveTarget>,
+    /// 正在进行的流式响应数
+    #[serde(default)]
+    pub active_streams: usize,
+    /// 本次运行中各 Provider 的请求统计
+    #[serde(default)]
+    pub provider_stats: Vec<ProviderRequestStats>,
+    /// 本次运行中各流量镜像目标的统计
+    #[serde(default)]
+    pub shadow_stats: Vec<ShadowStats>,
+    /// 非流式响应缓存的命中统计
+    #[serde(default)]
+    pub response_cache: ResponseCacheStats,
+    /// DNS 缓存的命中统计
+    #[serde(default)]
+    pub dns_cache: DnsCacheStats,
+    /// 请求与响应超过大小上限的次数
+    #[serde(default)]
+    pub size_limits: SizeLimitStats,
+    /// 故障转移后正在恢复的首选供应商
+    #[serde(default)]
+    pub switch_back: Vec<SwitchBackCandidate>,
+    /// 所有端点都不可用的供应商
+    #[serde(default)]
+    pub endpoint_outages: Vec<EndpointOutage>,
+}
+
+impl ProxyStatus {
+    /// 获取（必要时创建）指定镜像目标的统计
+    pub fn shadow_stats_mut(
+        &mut self,
+        app_type: &str,
+        provider_id: &str,
+        provider_name: &str,
+    ) -> &mut ShadowStats {
+        let index = match self
+            .shadow_stats
+            .iter()
+            .position(|s| s.app_type == app_type && s.provider_id == provider_id)
+        {
+            Some(index) => index,
+            None => {
+                self.shadow_stats
+                    .push(ShadowStats::new(app_type, provider_id, provider_name));
+                self.shadow_stats.len() - 1
+            }
+        };
+        &mut self.shadow_stats[index]
+    }
+
+    /// 把一次响应的 token 用量累加到对应 Provider 的统计中
+    pub fn record_token_usage(&mut self, app_type: &str, provider_id: &str, usage: &TokenUsage) {
+        if let Some(stats) = self
+            .provider_stats
+            .iter_mut()
+            .find(|s| s.app_type == app_type && s.provider_id == provider_id)
+        {
+            stats.input_tokens += u64::from(usage.input_tokens);
+            stats.output_tokens += u64::from(usage.output_tokens);
+            stats.cache_read_tokens += u64::from(usage.cache_read_tokens);
+            stats.cache_creation_tokens += u64::from(usage.cache_creation_tokens);
+        }
+    }
+
+    /// 获取（必要时创建）指定 Provider 的请求统计
+    pub fn provider_stats_mut(
+        &mut self,
+        app_type: &str,
+        provider_id: &str,
+        provider_name: &str,
+    ) -> &mut ProviderRequestStats {
+        let index = match self
+            .provider_stats
+            .iter()
+            .position(|s| s.app_type == app_type && s.provider_id == provider_id)
+        {
+            Some(index) => index,
+            None => {
+                self.provider_stats.push(ProviderRequestStats {
+                    app_type: app_type.to_string(),
+                    provider_id: provider_id.to_string(),
+                    provider_name: provider_name.to_string(),
+                    ..Default::default()
+                });
+                self.provider_stats.len() - 1
+            }
+        };
+        &mut self.provider_stats[index]
+    }
+}
+
+/// 单个 Provider 的请求统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderRequestStats {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub requests: u64,
+    pub failures: u64,
+    /// 发往上游的请求体字节数
+    #[serde(default)]
+    pub bytes_sent: u64,
+    /// 从上游接收的响应体字节数
+    #[serde(default)]
+    pub bytes_received: u64,
+    #[serde(default)]
+    pub p50_latency_ms: Option<u64>,
+    #[serde(default)]
+    pub p95_latency_ms: Option<u64>,
+    /// 429 / overloaded 限流响应统计
+    #[serde(default)]
+    pub rate_limits: RateLimitStats,
+    /// 按端点（base URL）拆分的限流统计
+    #[serde(default)]
+    pub endpoint_rate_limits: HashMap<String, RateLimitStats>,
+    /// 各端点最近一次离群剔除记录，由 `get_status` 填充
+    #[serde(default)]
+    pub endpoint_ejections: HashMap<String, EndpointEjection>,
+    /// 综合健康分（0-100），由 `get_status` 结合熔断器状态填充，无请求时为 None
+    #[serde(default)]
+    pub health_score: Option<u8>,
+    /// 当前进行中的请求数，由 `get_status` 填充
+    #[serde(default)]
+    pub in_flight: usize,
+    /// 发出对冲请求的次数
+    #[serde(default)]
+    pub hedged_requests: u64,
+    /// 其中采用对冲端点响应的次数
+    #[serde(default)]
+    pub hedge_wins: u64,
+    /// 本次运行累计的 token 数，解析自上游响应（含流式响应的最终事件）中的 usage
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_tokens: u64,
+    /// 最近的延迟样本（仅用于计算分位数）
+    #[serde(skip)]
+    latency_samples: VecDeque<u64>,
+}
+
+impl ProviderRequestStats {
+    /// 记录一次请求延迟并更新 p50/p95
+    pub fn record_latency(&mut self, latency_ms: u64) {
+        if self.latency_samples.len() >= LATENCY_SAMPLE_WINDOW {
+            self.latency_samples.pop_front();
+        }
+        self.latency_samples.push_back(latency_ms);
+
+        let mut sorted: Vec<u64> = self.latency_samples.iter().copied().collect();
+        sorted.sort_unstable();
+        self.p50_latency_ms = percentile(&sorted, 50);
+        self.p95_latency_ms = percentile(&sorted, 95);
+    }
+
+    /// 最近 `n` 个延迟样本，按时间先后排列
+    pub fn recent_latencies(&self, n: usize) -> Vec<u64> {
+        let skip = self.latency_samples.len().saturating_sub(n);
+        self.latency_samples.iter().skip(skip).copied().collect()
+    }
+
+    /// 错误率（百分比）
+    pub fn error_rate(&self) -> f32 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failures as f32 / self.requests as f32 * 100.0
+        }
+    }
+
+    /// 综合健康分：成功率占 60 分、p95 延迟占 25 分、熔断器状态占 15 分；
+    /// 熔断打开时最多 20 分
+    pub fn compute_health_score(&self, circuit: CircuitState) -> Option<u8> {
+        if self.requests == 0 {
+            return None;
+        }
+
+        let success = (100.0 - self.error_rate()) / 100.0 * 60.0;
+        let latency = match self.p95_latency_ms {
+            None => 25.0,
+            Some(ms) if ms <= HEALTH_LATENCY_GOOD_MS => 25.0,
+            Some(ms) if ms >= HEALTH_LATENCY_BAD_MS => 0.0,
+            Some(ms) => {
+                let span = (HEALTH_LATENCY_BAD_MS - HEALTH_LATENCY_GOOD_MS) as f32;
+                (HEALTH_LATENCY_BAD_MS - ms) as f32 / span * 25.0
+            }
+        };
+        let breaker = match circuit {
+            CircuitState::Closed => 15.0,
+            CircuitState::HalfOpen => 5.0,
+            CircuitState::Open => 0.0,
+        };
+
+        let score = (success + latency + breaker).round() as u8;
+        Some(if circuit == CircuitState::Open {
+            score.min(20)
+        } else {
+            score
+        })
+    }
+}
+
+/// 上游限流（HTTP 429 或 overloaded）的计数与最近一次 Retry-After
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitStats {
+    pub count: u64,
+    /// 最近一次响应中 `Retry-After` 头的原始值
+    pub last_retry_after: Option<String>,
+    /// 最近一次限流的时间（毫秒时间戳）
+    pub last_seen_at: Option<i64>,
+}
+
+impl RateLimitStats {
+    /// 429、Anthropic 的 529 以及响应体中的 `overloaded` 均视为限流而非故障
+    pub fn is_rate_limited(status: u16, body: Option<&str>) -> bool {
+        status == 429
+            || status == 529
+            || body.is_some_and(|b| b.to_ascii_lowercase().contains("overloaded"))
+    }
+
+    pub fn record(&mut self, retry_after: Option<String>, at: i64) {
+        self.count += 1;
+        if retry_after.is_some() {
+            self.last_retry_after = retry_after;
+        }
+        self.last_seen_at = Some(at);
+    }
+}
+
+/// 最近秩法计算分位数，输入需已升序排列
+pub(crate) fn percentile(sorted: &[u64], p: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// 端点延迟指数加权移动平均的平滑系数，越小越不受单次尖峰影响
+pub(crate) const LATENCY_EWMA_ALPHA: f64 = 0.3;
+
+/// 把一次新的延迟采样并入指数加权移动平均，尚无历史值时直接采用该采样
+pub(crate) fn ewma(previous: Option<u64>, sample: u64) -> u64 {
+    match previous {
+        Some(prev) => (prev as f64 * (1.0 - LATENCY_EWMA_ALPHA)
+            + sample as f64 * LATENCY_EWMA_ALPHA)
+            .round() as u64,
+        None => sample,
+    }
 }
 
 /// 活跃的代理目标信息
@@ -192,6 +472,20 @@ pub struct AppProxyConfig {
     pub circuit_min_requests: u32,
 }
 
+impl AppProxyConfig {
+    /// 该应用的默认熔断器配置（供应商可单独覆盖）
+    pub fn circuit_breaker_config(&self) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: self.circuit_failure_threshold,
+            success_threshold: self.circuit_success_threshold,
+            timeout_seconds: self.circuit_timeout_seconds as u64,
+            error_rate_threshold: self.circuit_error_rate_threshold,
+            min_requests: self.circuit_min_requests,
+            ..Default::default()
+        }
+    }
+}
+
 /// Provider 端点（带健康追踪）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderEndpoint {
@@ -204,12 +498,367 @@ pub struct ProviderEndpoint {
     pub is_healthy: bool,
     pub consecutive_failures: u32,
     pub is_primary: bool,
+    /// 手动固定为主端点，后台测速不再按延迟改选
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// 加权策略下的流量权重，未设置时按 1 计
+    #[serde(default)]
+    pub weight: Option<u32>,
+    /// 近期测速采样的延迟中位数
+    #[serde(default)]
+    pub latency_p50_ms: Option<u64>,
+    /// 近期测速采样的 p95 延迟
+    #[serde(default)]
+    pub latency_p95_ms: Option<u64>,
+    /// 近期相邻成功采样的平均延迟抖动
+    #[serde(default)]
+    pub jitter_ms: Option<u64>,
+    /// 近期采样中健康状态的切换次数
+    #[serde(default)]
+    pub recent_flaps: u32,
+    /// 稳定性评分（0-100），采样不足时为 None
+    #[serde(default)]
+    pub stability_score: Option<u8>,
+    /// 地区标签（小写，如 `us`、`asia`）
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+impl ProviderEndpoint {
+    /// 按延迟排序时使用的值：优先 p95，尚无分位数时取最近一次测速
+    pub fn ranking_latency_ms(&self) -> Option<u64> {
+        self.latency_p95_ms.or(self.latency_ms)
+    }
+
+    /// 按稳定性放大后的排序延迟：评分 100 不变，评分 0 时翻倍；尚无评分时不调整
+    pub fn stability_adjusted_latency_ms(&self) -> Option<u64> {
+        let latency = self.ranking_latency_ms()?;
+        let score = u64::from(self.stability_score.unwrap_or(100).min(100));
+        Some(latency * (200 - score) / 100)
+    }
+}
+
+/// 计算稳定性评分所需的最少采样数
+const STABILITY_MIN_SAMPLES: usize = 3;
+
+/// 端点稳定性统计（由近期测速采样计算）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndpointStability {
+    pub jitter_ms: Option<u64>,
+    pub flaps: u32,
+    pub score: Option<u8>,
+}
+
+impl EndpointStability {
+    /// 按时间升序的 (延迟, 是否健康) 采样计算抖动、状态切换次数与评分。
+    ///
+    /// 评分从 100 起扣分：相对抖动（抖动占平均延迟的百分比）与每次切换 10 分各自最多扣 50 分。
+    pub fn from_samples(samples: &[(Option<u64>, bool)]) -> Self {
+        let flaps = samples.windows(2).filter(|w| w[0].1 != w[1].1).count() as u32;
+        let latencies: Vec<u64> = samples
+            .iter()
+            .filter(|(_, healthy)| *healthy)
+            .filter_map(|(latency, _)| *latency)
+            .collect();
+        let jitter_ms = (latencies.len() >= 2).then(|| {
+            let total: u64 = latencies.windows(2).map(|w| w[0].abs_diff(w[1])).sum();
+            total / (latencies.len() as u64 - 1)
+        });
+        if samples.len() < STABILITY_MIN_SAMPLES {
+            return Self {
+                jitter_ms,
+                flaps,
+                score: None,
+            };
+        }
+
+        let mean = latencies.iter().sum::<u64>() / (latencies.len() as u64).max(1);
+        let jitter_penalty = match jitter_ms {
+            Some(jitter) if mean > 0 => (jitter * 100 / mean).min(50),
+            _ => 0,
+        };
+        let flap_penalty = u64::from(flaps * 10).min(50);
+        Self {
+            jitter_ms,
+            flaps,
+            score: Some((100 - jitter_penalty - flap_penalty) as u8),
+        }
+    }
+}
+
+/// 端点延迟历史采样（由后台测速任务定期写入）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointLatencySample {
+    pub url: String,
+    /// 采样时间（Unix 秒）
+    pub tested_at: i64,
+    /// 测速失败时为 None
+    pub latency_ms: Option<u64>,
+    pub is_healthy: bool,
 }
 
 /// 混合模式配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct HybridModeConfig {
     pub enabled: bool,
     pub latency_test_interval: u64,
     pub url_circuit_failure_threshold: u32,
+    /// 延迟测试同时测试的 URL 数
+    #[serde(default = "default_speedtest_concurrency")]
+    pub speedtest_concurrency: u32,
+    /// 延迟测试每个 URL 的超时（秒）
+    #[serde(default = "default_speedtest_timeout_secs")]
+    pub speedtest_timeout_secs: u64,
+    /// 延迟测试每个 URL 的采样次数
+    #[serde(default = "default_speedtest_samples")]
+    pub speedtest_samples: u32,
+}
+
+fn default_speedtest_concurrency() -> u32 {
+    8
+}
+
+fn default_speedtest_timeout_secs() -> u64 {
+    8
+}
+
+fn default_speedtest_samples() -> u32 {
+    1
+}
+
+impl Default for HybridModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            latency_test_interval: 300,
+            url_circuit_failure_threshold: 3,
+            speedtest_concurrency: default_speedtest_concurrency(),
+            speedtest_timeout_secs: default_speedtest_timeout_secs(),
+            speedtest_samples: default_speedtest_samples(),
+        }
+    }
+}
+
+/// 故障转移队列的负载均衡策略（按应用存储在 proxy_config 中）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalanceStrategy {
+    /// 严格按队列顺序，前一个不可用时才转移到下一个
+    #[default]
+    Failover,
+    /// 依次轮换首选供应商
+    RoundRobin,
+    /// 按权重平滑轮换；未配置权重时队列越靠前权重越高
+    Weighted,
+    /// 按权重随机挑选首选供应商
+    WeightedRandom,
+    /// 优先选择近期平均延迟最低的供应商
+    LeastLatency,
+    /// 优先选择进行中请求最少的供应商
+    LeastOutstanding,
+}
+
+impl LoadBalanceStrategy {
+    pub const ALL: [LoadBalanceStrategy; 6] = [
+        Self::Failover,
+        Self::RoundRobin,
+        Self::Weighted,
+        Self::WeightedRandom,
+        Self::LeastLatency,
+        Self::LeastOutstanding,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Failover => "failover",
+            Self::RoundRobin => "round_robin",
+            Self::Weighted => "weighted",
+            Self::WeightedRandom => "weighted_random",
+            Self::LeastLatency => "least_latency",
+            Self::LeastOutstanding => "least_outstanding",
+        }
+    }
+
+    /// 界面展示用名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Failover => "failover only",
+            Self::RoundRobin => "round-robin",
+            Self::Weighted => "weighted",
+            Self::WeightedRandom => "weighted random",
+            Self::LeastLatency => "least latency",
+            Self::LeastOutstanding => "least outstanding",
+        }
+    }
+
+    /// 循环切换到下一个策略
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl std::str::FromStr for LoadBalanceStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|strategy| strategy.as_str() == s)
+            .ok_or_else(|| format!("Invalid load balance strategy: {s}"))
+    }
+}
+
+/// 客户端侧限流配置（按应用存储在 proxy_config 中），0 表示不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientRateLimit {
+    /// 每分钟最多接受的请求数
+    pub requests_per_minute: u32,
+    /// 同时处理中的最大请求数
+    pub max_concurrent: u32,
+}
+
+impl ClientRateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_minute == 0 && self.max_concurrent == 0
+    }
+
+    /// 界面展示用文本
+    pub fn label(&self) -> String {
+        let rpm = match self.requests_per_minute {
+            0 => "∞".to_string(),
+            n => n.to_string(),
+        };
+        let concurrent = match self.max_concurrent {
+            0 => "∞".to_string(),
+            n => n.to_string(),
+        };
+        format!("{rpm} rpm / {concurrent} concurrent")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_usage_accumulates_per_provider() {
+        let mut status = ProxyStatus::default();
+        status.provider_stats_mut("claude", "a", "A");
+        status.provider_stats_mut("claude", "b", "B");
+
+        let usage = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            cache_read_tokens: 300,
+            cache_creation_tokens: 40,
+            model: None,
+        };
+        status.record_token_usage("claude", "a", &usage);
+        status.record_token_usage("claude", "a", &usage);
+        // 未建立统计的供应商不会新增条目
+        status.record_token_usage("codex", "a", &usage);
+
+        let a = &status.provider_stats[0];
+        assert_eq!(
+            (
+                a.input_tokens,
+                a.output_tokens,
+                a.cache_read_tokens,
+                a.cache_creation_tokens
+            ),
+            (200, 40, 600, 80)
+        );
+        assert_eq!(status.provider_stats[1].cache_read_tokens, 0);
+        assert_eq!(status.provider_stats.len(), 2);
+    }
+
+    #[test]
+    fn provider_stats_track_latency_percentiles() {
+        let mut stats = ProviderRequestStats::default();
+        assert_eq!(stats.p50_latency_ms, None);
+
+        for ms in (1..=100).rev() {
+            stats.record_latency(ms);
+        }
+        assert_eq!(stats.p50_latency_ms, Some(50));
+        assert_eq!(stats.p95_latency_ms, Some(95));
+        assert_eq!(stats.recent_latencies(3), vec![3, 2, 1]);
+
+        stats.requests = 4;
+        stats.failures = 1;
+        assert_eq!(stats.error_rate(), 25.0);
+    }
+
+    #[test]
+    fn health_score_combines_success_latency_and_breaker() {
+        let mut stats = ProviderRequestStats::default();
+        assert_eq!(stats.compute_health_score(CircuitState::Closed), None);
+
+        stats.requests = 10;
+        stats.record_latency(500);
+        assert_eq!(stats.compute_health_score(CircuitState::Closed), Some(100));
+        assert_eq!(stats.compute_health_score(CircuitState::HalfOpen), Some(90));
+        assert_eq!(stats.compute_health_score(CircuitState::Open), Some(20));
+
+        // 一半失败、p95 落在阈值中点
+        stats.failures = 5;
+        for _ in 0..100 {
+            stats.record_latency(6_000);
+        }
+        assert_eq!(stats.compute_health_score(CircuitState::Closed), Some(58));
+    }
+
+    #[test]
+    fn stability_penalizes_jitter_and_flaps() {
+        let steady = EndpointStability::from_samples(&[
+            (Some(200), true),
+            (Some(210), true),
+            (Some(200), true),
+        ]);
+        assert_eq!(steady.jitter_ms, Some(10));
+        assert_eq!(steady.score, Some(96));
+
+        let flappy = EndpointStability::from_samples(&[
+            (Some(50), true),
+            (None, false),
+            (Some(60), true),
+            (None, false),
+        ]);
+        assert_eq!(flappy.flaps, 3);
+        assert_eq!(flappy.score, Some(52));
+
+        assert_eq!(
+            EndpointStability::from_samples(&[(Some(50), true)]).score,
+            None
+        );
+    }
+
+    #[test]
+    fn ewma_damps_single_spikes() {
+        assert_eq!(ewma(None, 200), 200);
+        assert_eq!(ewma(Some(100), 1100), 400);
+        assert_eq!(ewma(Some(400), 100), 310);
+    }
+
+    #[test]
+    fn rate_limit_detection_and_recording() {
+        assert!(RateLimitStats::is_rate_limited(429, None));
+        assert!(RateLimitStats::is_rate_limited(529, None));
+        assert!(RateLimitStats::is_rate_limited(
+            500,
+            Some(r#"{"type":"error","error":{"type":"overloaded_error"}}"#)
+        ));
+        assert!(!RateLimitStats::is_rate_limited(502, Some("Bad Gateway")));
+
+        let mut stats = RateLimitStats::default();
+        stats.record(Some("30".to_string()), 1);
+        stats.record(None, 2);
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.last_retry_after.as_deref(), Some("30"));
+        assert_eq!(stats.last_seen_at, Some(2));
+    }
 }
@@ -0,0 +1,142 @@
+//! 代理的 Unix socket 监听
+//!
+//! 多用户机器上固定的 TCP 端口容易与其他用户冲突，也可能被其他用户访问。开启后代理
+//! 额外监听一个 Unix domain socket（权限为仅当前用户可读写），也可以只监听 socket、
+//! 完全不占用 TCP 端口。
+//!
+//! Claude Code、Codex 与 Gemini CLI 目前只接受 http(s) 形式的 Base URL，无法直接连接
+//! Unix socket：接管时仍写入 TCP 地址，仅 socket 模式下无法接管这些应用。
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::ProxyError;
+
+/// 默认 socket 文件名（位于应用配置目录下）
+const DEFAULT_SOCKET_FILE: &str = "proxy.sock";
+
+/// 代理 Unix socket 设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnixSocketConfig {
+    pub enabled: bool,
+    /// socket 文件路径，为空时使用 `~/.cc-switch/proxy.sock`
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 只监听 socket，不再监听 TCP 端口（包括应用独立端口）
+    #[serde(default)]
+    pub socket_only: bool,
+}
+
+impl UnixSocketConfig {
+    /// 实际使用的 socket 路径
+    pub fn resolved_path(&self) -> PathBuf {
+        self.path
+            .as_deref()
+            .map(PathBuf::from)
+            .unwrap_or_else(default_socket_path)
+    }
+
+    /// 是否仍需监听 TCP 端口
+    pub fn listens_on_tcp(&self) -> bool {
+        !(self.enabled && self.socket_only)
+    }
+}
+
+/// 默认的 socket 路径
+pub fn default_socket_path() -> PathBuf {
+    crate::config::get_app_config_dir().join(DEFAULT_SOCKET_FILE)
+}
+
+/// 当前平台不支持 Unix socket 时返回错误
+pub fn ensure_supported() -> Result<(), ProxyError> {
+    if cfg!(unix) {
+        Ok(())
+    } else {
+        Err(ProxyError::ConfigError(
+            "当前平台不支持 Unix socket".to_string(),
+        ))
+    }
+}
+
+/// 绑定 socket 并把权限限制为仅当前用户可访问
+///
+/// 上次异常退出残留的 socket 文件会被清理；路径已被其他进程监听或不是 socket 时报错
+#[cfg(unix)]
+pub fn bind(path: &std::path::Path) -> Result<tokio::net::UnixListener, ProxyError> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let display = path.display();
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(ProxyError::BindFailed(format!(
+                "{display} 已存在且不是 socket 文件"
+            )));
+        }
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(ProxyError::BindFailed(format!(
+                "{display} 已被其他进程监听"
+            )));
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| ProxyError::BindFailed(format!("清理残留的 {display} 失败: {e}")))?;
+    }
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    std::fs::create_dir_all(parent)
+        .map_err(|e| ProxyError::BindFailed(format!("创建目录 {} 失败: {e}", parent.display())))?;
+
+    // bind 按 umask 创建 socket，先在仅自己可进入的临时目录中绑定并收紧权限，再移动到目标路径，
+    // 避免其他用户在权限生效前连接
+    let staging = tempfile::Builder::new()
+        .prefix(".cc-switch-sock")
+        .permissions(std::fs::Permissions::from_mode(0o700))
+        .tempdir_in(parent)
+        .map_err(|e| {
+            ProxyError::BindFailed(format!("在 {} 创建临时目录失败: {e}", parent.display()))
+        })?;
+    let staged = staging.path().join(DEFAULT_SOCKET_FILE);
+    let listener = tokio::net::UnixListener::bind(&staged)
+        .map_err(|e| ProxyError::BindFailed(format!("{display}: {e}")))?;
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| ProxyError::BindFailed(format!("设置 {display} 权限失败: {e}")))?;
+    std::fs::rename(&staged, path)
+        .map_err(|e| ProxyError::BindFailed(format!("移动 socket 到 {display} 失败: {e}")))?;
+    Ok(listener)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stale_socket_is_replaced_but_live_socket_and_plain_files_are_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("proxy.sock");
+
+        let listener = bind(&path).unwrap();
+        let mode = std::os::unix::fs::PermissionsExt::mode(
+            &std::fs::metadata(&path).unwrap().permissions(),
+        );
+        assert_eq!(mode & 0o777, 0o600);
+        // 绑定用的临时目录不会残留
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+        assert!(matches!(bind(&path), Err(ProxyError::BindFailed(_))));
+
+        // 监听者退出后残留的 socket 文件可以重新绑定
+        drop(listener);
+        assert!(path.exists());
+        drop(bind(&path).unwrap());
+
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "keep me").unwrap();
+        assert!(bind(&file).is_err());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+    }
+}
@@ -4,97 +4,261 @@
 
 use super::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use super::error::ProxyError;
+use super::load_balancer::pick_weighted;
+use super::outlier::{EndpointEjection, OutlierDetectionConfig, OutlierDetector};
 use super::types::{HybridModeConfig, ProviderEndpoint};
+use super::webhook::{WebhookEventKind, WebhookNotifier};
 use crate::database::Database;
+use crate::provider::Provider;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+/// 重试等待时长上限（秒），避免请求被长时间挂起
+pub const MAX_FALLBACK_RETRY_DELAY_SECONDS: u64 = 60;
+
+/// 供应商多个端点之间的选择策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UrlStrategy {
+    /// 主端点优先，其余按最近测得的延迟升序
+    #[default]
+    LowestLatency,
+    /// 主端点优先，其余按添加顺序
+    Priority,
+    /// 随机挑选首选端点
+    Random,
+    /// 在健康端点间依次轮换
+    RoundRobin,
+    /// 按端点权重随机分配流量
+    Weighted,
+    /// 主端点优先，其余按稳定性折算后的延迟升序，稍慢但稳定的端点优先于快但频繁抖动的端点
+    Stable,
+}
+
+impl UrlStrategy {
+    pub const ALL: [UrlStrategy; 6] = [
+        Self::LowestLatency,
+        Self::Priority,
+        Self::Random,
+        Self::RoundRobin,
+        Self::Weighted,
+        Self::Stable,
+    ];
+
+    /// 界面展示用名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LowestLatency => "lowest latency",
+            Self::Priority => "priority order",
+            Self::Random => "random",
+            Self::RoundRobin => "round-robin",
+            Self::Weighted => "weighted",
+            Self::Stable => "stable latency",
+        }
+    }
+
+    /// 循环切换到下一个策略
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// 解析逗号分隔的地区偏好顺序：去除空白、转为小写并去重
+pub fn parse_region_order(value: &str) -> Vec<String> {
+    let mut regions: Vec<String> = Vec::new();
+    for region in value.split(',').map(|r| r.trim().to_lowercase()) {
+        if !region.is_empty() && !regions.contains(&region) {
+            regions.push(region);
+        }
+    }
+    regions
+}
+
+/// 供应商所有端点都不可用时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum EndpointFallback {
+    /// 降级到配置中的 base_url 继续请求
+    #[default]
+    Degrade,
+    /// 直接返回错误，由故障转移尝试下一个供应商
+    FailFast,
+    /// 等待一段时间后重新检查一次端点，仍不可用则返回错误
+    Retry {
+        #[serde(rename = "delaySeconds")]
+        delay_seconds: u64,
+    },
+}
+
+impl EndpointFallback {
+    /// TUI 中循环切换的预设
+    pub const PRESETS: [EndpointFallback; 5] = [
+        Self::Degrade,
+        Self::FailFast,
+        Self::Retry { delay_seconds: 2 },
+        Self::Retry { delay_seconds: 5 },
+        Self::Retry { delay_seconds: 10 },
+    ];
+
+    /// 界面展示用名称
+    pub fn label(&self) -> String {
+        match self {
+            Self::Degrade => "degrade to base URL".to_string(),
+            Self::FailFast => "fail fast".to_string(),
+            Self::Retry { delay_seconds } => format!("retry after {delay_seconds}s"),
+        }
+    }
+
+    /// 循环切换到下一个预设，非预设值回到第一个
+    pub fn next(self) -> Self {
+        match Self::PRESETS.iter().position(|f| *f == self) {
+            Some(index) => Self::PRESETS[(index + 1) % Self::PRESETS.len()],
+            None => Self::PRESETS[0],
+        }
+    }
+}
+
+/// 某个供应商所有端点都不可用的状态，任一端点恢复后清除
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointOutage {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 首次发现的时间（毫秒时间戳）
+    pub since: i64,
+    /// 发现时采用的处理方式
+    pub fallback: EndpointFallback,
+}
+
 /// URL 路由器
 ///
 /// 负责在单个 Provider 内的多个 URL 之间进行选择和熔断
 pub struct UrlRouter {
     db: Arc<Database>,
-    /// URL 级别熔断器: key = "provider_id:url_hash"
+    /// URL 级别熔断器: key = "app_type:provider_id:url_hash"
     circuit_breakers: Arc<RwLock<HashMap<String, Arc<CircuitBreaker>>>>,
-    /// 默认熔断器配置
-    default_config: CircuitBreakerConfig,
+    /// 熔断器配置已同步到的路由配置版本
+    synced_version: AtomicU64,
+    /// 按真实请求结果做的端点离群检测
+    outliers: OutlierDetector,
+    outlier_config: std::sync::RwLock<OutlierDetectionConfig>,
+    /// 供应商所有端点都不可用时的事件通知
+    webhooks: Option<Arc<WebhookNotifier>>,
+    /// 轮询计数器: key = (app_type, provider_id)
+    round_robin: Mutex<HashMap<(String, String), usize>>,
+    /// 所有端点都不可用的供应商: key = (app_type, provider_id)
+    outages: Mutex<HashMap<(String, String), EndpointOutage>>,
 }
 
 impl UrlRouter {
     /// 创建新的 URL 路由器
     pub fn new(db: Arc<Database>) -> Self {
         Self {
+            synced_version: AtomicU64::new(db.config_version()),
+            outliers: OutlierDetector::new(),
+            outlier_config: std::sync::RwLock::new(load_outlier_config(&db)),
             db,
             circuit_breakers: Arc::new(RwLock::new(HashMap::new())),
-            default_config: CircuitBreakerConfig {
-                failure_threshold: 3,
-                success_threshold: 2,
-                timeout_seconds: 30,
-                error_rate_threshold: 0.5,
-                min_requests: 5,
-            },
+            webhooks: None,
+            round_robin: Mutex::new(HashMap::new()),
+            outages: Mutex::new(HashMap::new()),
         }
     }
 
-    /// 选择最佳 URL
+    /// 供应商所有端点都不可用时发送 Webhook 通知
+    pub fn with_webhooks(mut self, webhooks: Arc<WebhookNotifier>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// 按优先级选择 URL
     ///
     /// 选择逻辑：
     /// 1. 获取所有 URL（config base_url + custom endpoints）
-    /// 2. 过滤掉 Circuit Breaker 处于 Open 状态的 URL
-    /// 3. 按延迟升序排序
-    /// 4. 返回延迟最低的健康 URL
-    /// 5. 若所有 URL 都不可用，返回 config base_url（降级）
-    pub async fn select_url(
+    /// 2. 过滤掉 Circuit Breaker 处于 Open 状态或被离群检测剔除的 URL
+    /// 3. 按供应商的 [`UrlStrategy`] 排序（默认主端点优先、其余按延迟升序）
+    /// 4. 返回健康 URL 列表，第一个为首选，第二个为对冲请求使用的次优端点
+    /// 5. 若所有 URL 都不可用，按供应商的 [`EndpointFallback`] 降级到 config base_url、
+    ///    直接报错或等待后重试一次
+    pub async fn select_urls(
         &self,
-        provider_id: &str,
+        provider: &Provider,
         app_type: &str,
         config_base_url: &str,
-    ) -> Result<String, ProxyError> {
+    ) -> Result<Vec<String>, ProxyError> {
+        self.sync_configs().await;
+        let provider_id = provider.id.as_str();
+
         // 获取所有端点
         let endpoints = self.get_all_urls(provider_id, app_type, config_base_url)?;
 
         if endpoints.is_empty() {
-            return Ok(config_base_url.to_string());
+            return Ok(vec![config_base_url.to_string()]);
         }
 
-        // 过滤可用的 URL
-        let mut available_urls = Vec::new();
-        for endpoint in &endpoints {
-            let breaker = self
-                .get_or_create_circuit_breaker(provider_id, &endpoint.url)
-                .await;
-            if breaker.is_available().await {
-                available_urls.push(endpoint.clone());
-            }
-        }
+        let mut available_urls = self
+            .available_endpoints(provider_id, app_type, &endpoints)
+            .await;
 
-        // 如果没有可用的 URL，降级到 config base_url
         if available_urls.is_empty() {
-            log::warn!(
-                "[UrlRouter] 所有 URL 都不可用，降级到 config base_url: {}",
-                config_base_url
-            );
-            return Ok(config_base_url.to_string());
-        }
-
-        // 按延迟排序（主端点优先，然后按延迟升序）
-        available_urls.sort_by(|a, b| {
-            // 主端点优先
-            if a.is_primary && !b.is_primary {
-                return std::cmp::Ordering::Less;
-            }
-            if !a.is_primary && b.is_primary {
-                return std::cmp::Ordering::Greater;
+            let fallback = provider
+                .meta
+                .as_ref()
+                .and_then(|m| m.endpoint_fallback)
+                .unwrap_or_default();
+            self.mark_outage(provider, app_type, fallback);
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.notify_provider(WebhookEventKind::EndpointsDown, app_type, provider_id);
             }
-            // 按延迟排序
-            match (a.latency_ms, b.latency_ms) {
-                (Some(a_lat), Some(b_lat)) => a_lat.cmp(&b_lat),
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                (None, None) => std::cmp::Ordering::Equal,
+            let unhealthy = || ProxyError::AllEndpointsUnhealthy {
+                provider: provider.name.clone(),
+            };
+            match fallback {
+                EndpointFallback::Degrade => {
+                    log::warn!(
+                        "[UrlRouter] 所有 URL 都不可用，降级到 config base_url: {}",
+                        config_base_url
+                    );
+                    return Ok(vec![config_base_url.to_string()]);
+                }
+                EndpointFallback::FailFast => {
+                    log::warn!(
+                        "[UrlRouter] {} 的所有 URL 都不可用，直接返回错误",
+                        provider.name
+                    );
+                    return Err(unhealthy());
+                }
+                EndpointFallback::Retry { delay_seconds } => {
+                    let delay = delay_seconds.min(MAX_FALLBACK_RETRY_DELAY_SECONDS);
+                    log::warn!(
+                        "[UrlRouter] {} 的所有 URL 都不可用，{}s 后重试",
+                        provider.name,
+                        delay
+                    );
+                    tokio::time::sleep(Duration::from_secs(delay)).await;
+                    available_urls = self
+                        .available_endpoints(provider_id, app_type, &endpoints)
+                        .await;
+                    if available_urls.is_empty() {
+                        return Err(unhealthy());
+                    }
+                }
             }
-        });
+        }
+        self.clear_outage(provider_id, app_type);
+
+        let strategy = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.url_strategy)
+            .unwrap_or_default();
+        self.order_by_region(strategy, provider_id, app_type, &mut available_urls);
 
         let selected = &available_urls[0];
         log::info!(
@@ -104,7 +268,160 @@ impl UrlRouter {
             selected.is_primary
         );
 
-        Ok(selected.url.clone())
+        Ok(available_urls.into_iter().map(|e| e.url).collect())
+    }
+
+    /// 按地区偏好分层后排序：偏好靠前地区的端点整体排在前面，未标记或不在偏好中的排在最后。
+    ///
+    /// 只有首个非空层按供应商策略排序，其余层仅作为后备按延迟排序，避免轮询计数被多次推进。
+    fn order_by_region(
+        &self,
+        strategy: UrlStrategy,
+        provider_id: &str,
+        app_type: &str,
+        endpoints: &mut [ProviderEndpoint],
+    ) {
+        let regions = self.db.get_endpoint_region_order().unwrap_or_else(|e| {
+            log::warn!("[UrlRouter] 读取地区偏好失败: {}", e);
+            Vec::new()
+        });
+        if regions.is_empty() {
+            self.order_endpoints(strategy, provider_id, app_type, endpoints);
+            return;
+        }
+
+        let rank = |e: &ProviderEndpoint| {
+            e.region
+                .as_ref()
+                .and_then(|r| regions.iter().position(|p| p == r))
+                .unwrap_or(regions.len())
+        };
+        endpoints.sort_by_key(rank);
+        for (i, tier) in endpoints
+            .chunk_by_mut(|a, b| rank(a) == rank(b))
+            .enumerate()
+        {
+            let strategy = if i == 0 {
+                strategy
+            } else {
+                UrlStrategy::LowestLatency
+            };
+            self.order_endpoints(strategy, provider_id, app_type, tier);
+        }
+    }
+
+    /// 按策略排序可用端点，第一个为首选
+    fn order_endpoints(
+        &self,
+        strategy: UrlStrategy,
+        provider_id: &str,
+        app_type: &str,
+        endpoints: &mut [ProviderEndpoint],
+    ) {
+        if matches!(strategy, UrlStrategy::LowestLatency | UrlStrategy::Stable) {
+            let latency = |e: &ProviderEndpoint| match strategy {
+                UrlStrategy::Stable => e.stability_adjusted_latency_ms(),
+                _ => e.ranking_latency_ms(),
+            };
+            // 主端点优先，然后按 p95（或按稳定性折算后的）延迟升序，尚未测速的排在最后
+            endpoints.sort_by(|a, b| {
+                b.is_primary
+                    .cmp(&a.is_primary)
+                    .then_with(|| match (latency(a), latency(b)) {
+                        (Some(a_lat), Some(b_lat)) => a_lat.cmp(&b_lat),
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => std::cmp::Ordering::Equal,
+                    })
+            });
+            return;
+        }
+
+        // 其余策略以固定优先级为基础：主端点优先，然后按添加顺序（config base_url 虚拟端点 ID 为 0）
+        endpoints.sort_by_key(|e| (!e.is_primary, e.id));
+        if endpoints.len() < 2 {
+            return;
+        }
+        let index = match strategy {
+            UrlStrategy::Random => {
+                (uuid::Uuid::new_v4().as_u128() % endpoints.len() as u128) as usize
+            }
+            UrlStrategy::RoundRobin => {
+                let mut counters = self.round_robin.lock().unwrap_or_else(|e| e.into_inner());
+                let counter = counters
+                    .entry((app_type.to_string(), provider_id.to_string()))
+                    .or_insert(0);
+                let index = *counter % endpoints.len();
+                *counter = counter.wrapping_add(1);
+                index
+            }
+            UrlStrategy::Weighted => {
+                let weights: Vec<u32> = endpoints.iter().map(|e| e.weight.unwrap_or(1)).collect();
+                pick_weighted(&weights, uuid::Uuid::new_v4().as_u128() as u64)
+            }
+            UrlStrategy::LowestLatency | UrlStrategy::Priority | UrlStrategy::Stable => 0,
+        };
+        // 保持循环顺序，次优端点仍按优先级紧随其后
+        endpoints.rotate_left(index);
+    }
+
+    /// 过滤掉熔断中或被离群检测剔除的 URL
+    async fn available_endpoints(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        endpoints: &[ProviderEndpoint],
+    ) -> Vec<ProviderEndpoint> {
+        let mut available = Vec::new();
+        for endpoint in endpoints {
+            let breaker = self
+                .get_or_create_circuit_breaker(provider_id, app_type, &endpoint.url)
+                .await;
+            if self.is_ejected(provider_id, app_type, &endpoint.url) {
+                log::debug!("[UrlRouter] 跳过离群端点: {}", endpoint.url);
+                continue;
+            }
+            if breaker.is_available().await {
+                available.push(endpoint.clone());
+            }
+        }
+        available
+    }
+
+    /// 记录供应商所有端点都不可用，已记录时保留首次发现的时间
+    fn mark_outage(&self, provider: &Provider, app_type: &str, fallback: EndpointFallback) {
+        let mut outages = self.outages.lock().unwrap_or_else(|e| e.into_inner());
+        let outage = outages
+            .entry((app_type.to_string(), provider.id.clone()))
+            .or_insert_with(|| EndpointOutage {
+                app_type: app_type.to_string(),
+                provider_id: provider.id.clone(),
+                provider_name: provider.name.clone(),
+                since: chrono::Utc::now().timestamp_millis(),
+                fallback,
+            });
+        outage.provider_name = provider.name.clone();
+        outage.fallback = fallback;
+    }
+
+    fn clear_outage(&self, provider_id: &str, app_type: &str) {
+        self.outages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(app_type.to_string(), provider_id.to_string()));
+    }
+
+    /// 当前所有端点都不可用的供应商，按发现时间排序
+    pub fn endpoint_outages(&self) -> Vec<EndpointOutage> {
+        let mut outages: Vec<_> = self
+            .outages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        outages.sort_by_key(|o| o.since);
+        outages
     }
 
     /// 获取所有 URL（config base_url + custom endpoints）
@@ -140,6 +457,14 @@ impl UrlRouter {
                     is_healthy: true,
                     consecutive_failures: 0,
                     is_primary: endpoints.is_empty(), // 如果没有其他端点，设为主端点
+                    is_pinned: false,
+                    weight: None,
+                    latency_p50_ms: None,
+                    latency_p95_ms: None,
+                    jitter_ms: None,
+                    recent_flaps: 0,
+                    stability_score: None,
+                    region: None,
                 },
             );
         }
@@ -157,11 +482,14 @@ impl UrlRouter {
         latency_ms: Option<u64>,
     ) {
         // 更新熔断器状态
-        let breaker = self.get_or_create_circuit_breaker(provider_id, url).await;
+        let breaker = self
+            .get_or_create_circuit_breaker(provider_id, app_type, url)
+            .await;
 
         // URL 级别的熔断器不使用 HalfOpen permit 机制
         if success {
             breaker.record_success(false).await;
+            self.clear_outage(provider_id, app_type);
         } else {
             breaker.record_failure(false).await;
         }
@@ -183,13 +511,58 @@ impl UrlRouter {
         }
     }
 
+    /// 记录一次真实请求的结果，用于离群检测（不影响熔断器），成功请求的延迟并入端点的平滑延迟
+    pub fn record_request(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        url: &str,
+        success: bool,
+        latency_ms: u64,
+    ) {
+        let config = self
+            .outlier_config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        self.outliers
+            .record(&config, app_type, provider_id, url, success, latency_ms);
+        if success {
+            if let Err(e) =
+                self.db
+                    .record_endpoint_request_latency(app_type, provider_id, url, latency_ms)
+            {
+                log::warn!("[UrlRouter] 更新端点延迟失败: {}", e);
+            }
+        }
+    }
+
+    /// 端点当前是否被离群检测剔除
+    fn is_ejected(&self, provider_id: &str, app_type: &str, url: &str) -> bool {
+        self.outlier_config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .enabled
+            && self.outliers.is_ejected(app_type, provider_id, url)
+    }
+
+    /// 供应商各端点最近一次的离群剔除记录
+    pub fn endpoint_ejections(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> HashMap<String, EndpointEjection> {
+        self.outliers.ejections(app_type, provider_id)
+    }
+
     /// 获取或创建 URL 级别的熔断器
     async fn get_or_create_circuit_breaker(
         &self,
         provider_id: &str,
+        app_type: &str,
         url: &str,
     ) -> Arc<CircuitBreaker> {
-        let key = format!("{}:{}", provider_id, Self::hash_url(url));
+        let key = format!("{app_type}:{provider_id}:{}", Self::hash_url(url));
 
         // 先尝试读取
         {
@@ -206,11 +579,63 @@ impl UrlRouter {
             return breaker.clone();
         }
 
-        let breaker = Arc::new(CircuitBreaker::new(self.default_config.clone()));
+        let config = self.breaker_config(app_type, provider_id).await;
+        let breaker = Arc::new(CircuitBreaker::new(config));
         breakers.insert(key, breaker.clone());
         breaker
     }
 
+    /// URL 熔断器配置：失败阈值取混合模式设置，其余沿用应用默认值，再叠加供应商覆盖项
+    async fn breaker_config(&self, app_type: &str, provider_id: &str) -> CircuitBreakerConfig {
+        let mut config = match self.db.get_proxy_config_for_app(app_type).await {
+            Ok(app_config) => app_config.circuit_breaker_config(),
+            Err(e) => {
+                log::warn!("[UrlRouter] 读取熔断器配置失败: {}, 使用默认值", e);
+                CircuitBreakerConfig::default()
+            }
+        };
+        config.failure_threshold = self
+            .get_hybrid_config(app_type)
+            .url_circuit_failure_threshold;
+
+        match self.db.get_provider_by_id(provider_id, app_type) {
+            Ok(provider) => match provider
+                .and_then(|p| p.meta)
+                .and_then(|m| m.circuit_breaker)
+            {
+                Some(tuning) => tuning.apply(config),
+                None => config,
+            },
+            Err(e) => {
+                log::warn!("[UrlRouter] 读取供应商熔断参数失败: {}", e);
+                config
+            }
+        }
+    }
+
+    /// 路由配置变化后把新的熔断参数应用到已有的 URL 熔断器（不重置状态），并重新读取离群检测设置
+    async fn sync_configs(&self) {
+        let version = self.db.config_version();
+        if self.synced_version.swap(version, Ordering::AcqRel) == version {
+            return;
+        }
+        *self
+            .outlier_config
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = load_outlier_config(&self.db);
+        let breakers = self.circuit_breakers.read().await;
+        for (key, breaker) in breakers.iter() {
+            let Some((app_type, rest)) = key.split_once(':') else {
+                continue;
+            };
+            let Some((provider_id, _)) = rest.rsplit_once(':') else {
+                continue;
+            };
+            let config = self.breaker_config(app_type, provider_id).await;
+            breaker.update_config(config).await;
+        }
+    }
+
     /// 计算 URL 的哈希值（用于熔断器 key）
     fn hash_url(url: &str) -> u64 {
         use std::collections::hash_map::DefaultHasher;
@@ -227,11 +652,7 @@ impl UrlRouter {
             Ok(config) => config,
             Err(e) => {
                 log::warn!("[UrlRouter] 读取混合模式配置失败: {}, 使用默认值", e);
-                HybridModeConfig {
-                    enabled: true,
-                    latency_test_interval: 300,
-                    url_circuit_failure_threshold: 3,
-                }
+                HybridModeConfig::default()
             }
         }
     }
@@ -241,3 +662,286 @@ impl UrlRouter {
         self.get_hybrid_config(app_type).enabled
     }
 }
+
+/// 读取离群检测设置，失败时使用默认值
+fn load_outlier_config(db: &Database) -> OutlierDetectionConfig {
+    db.get_proxy_outlier_config().unwrap_or_else(|e| {
+        log::warn!("[UrlRouter] 读取离群检测设置失败: {}, 使用默认值", e);
+        OutlierDetectionConfig::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BASE_URL: &str = "https://api.example.com";
+
+    fn provider(fallback: Option<EndpointFallback>) -> Provider {
+        let mut provider = Provider::with_id(
+            "a".to_string(),
+            "Provider A".to_string(),
+            json!({"env": {"ANTHROPIC_BASE_URL": BASE_URL}}),
+            None,
+        );
+        provider.meta = Some(crate::provider::ProviderMeta {
+            endpoint_fallback: fallback,
+            ..Default::default()
+        });
+        provider
+    }
+
+    fn router() -> UrlRouter {
+        let db = Database::memory().unwrap();
+        db.apply_schema_migrations().unwrap();
+        UrlRouter::new(Arc::new(db))
+    }
+
+    /// 连续失败直到 base_url 的熔断器打开
+    async fn open_breaker(router: &UrlRouter) {
+        let threshold = router
+            .get_hybrid_config("claude")
+            .url_circuit_failure_threshold;
+        for _ in 0..threshold {
+            router
+                .record_url_result("a", "claude", BASE_URL, false, None)
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn degrade_falls_back_to_config_base_url() {
+        let router = router();
+        let provider = provider(None);
+        open_breaker(&router).await;
+
+        let urls = router
+            .select_urls(&provider, "claude", BASE_URL)
+            .await
+            .unwrap();
+        assert_eq!(urls, vec![BASE_URL.to_string()]);
+        let outages = router.endpoint_outages();
+        assert_eq!(outages.len(), 1);
+        assert_eq!(outages[0].fallback, EndpointFallback::Degrade);
+    }
+
+    #[tokio::test]
+    async fn fail_fast_returns_error_until_an_endpoint_recovers() {
+        let router = router();
+        let provider = provider(Some(EndpointFallback::FailFast));
+        open_breaker(&router).await;
+
+        let err = router
+            .select_urls(&provider, "claude", BASE_URL)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProxyError::AllEndpointsUnhealthy { .. }));
+        assert_eq!(router.endpoint_outages()[0].provider_name, "Provider A");
+
+        router
+            .record_url_result("a", "claude", BASE_URL, true, Some(100))
+            .await;
+        assert!(router.endpoint_outages().is_empty());
+    }
+
+    async fn select(router: &UrlRouter, provider: &Provider) -> Vec<String> {
+        router
+            .select_urls(provider, "claude", BASE_URL)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn lowest_latency_ranks_by_p95() {
+        let router = router();
+        let provider = provider(None);
+        router.db.save_provider("claude", &provider).unwrap();
+        // c 最近一次测速更快但抖动大，b 稳定
+        for (url, samples) in [
+            ("https://b.example.com", vec![200, 210, 190]),
+            ("https://c.example.com", vec![900, 40, 50]),
+        ] {
+            router.db.add_custom_endpoint("claude", "a", url).unwrap();
+            for ms in &samples {
+                router
+                    .db
+                    .record_endpoint_latency("claude", "a", url, Some(*ms), true)
+                    .unwrap();
+            }
+            router
+                .db
+                .update_endpoint_health("claude", "a", url, samples.last().copied(), true, 0)
+                .unwrap();
+            router
+                .db
+                .refresh_endpoint_percentiles("claude", "a", url)
+                .unwrap();
+        }
+        let urls = select(&router, &provider).await;
+        assert_eq!(urls[0], "https://b.example.com");
+    }
+
+    #[tokio::test]
+    async fn stable_strategy_prefers_steady_endpoint() {
+        let router = router();
+        let mut provider = provider(None);
+        router.db.save_provider("claude", &provider).unwrap();
+        for (url, samples) in [
+            (
+                "https://b.example.com",
+                vec![Some(200), Some(210), Some(200)],
+            ),
+            (
+                "https://c.example.com",
+                vec![Some(150), None, Some(170), None, Some(180)],
+            ),
+        ] {
+            router.db.add_custom_endpoint("claude", "a", url).unwrap();
+            for latency in &samples {
+                router
+                    .db
+                    .record_endpoint_latency("claude", "a", url, *latency, latency.is_some())
+                    .unwrap();
+            }
+            router
+                .db
+                .update_endpoint_health("claude", "a", url, Some(200), true, 0)
+                .unwrap();
+            router
+                .db
+                .refresh_endpoint_percentiles("claude", "a", url)
+                .unwrap();
+            router
+                .db
+                .refresh_endpoint_stability("claude", "a", url)
+                .unwrap();
+        }
+        assert_eq!(select(&router, &provider).await[0], "https://c.example.com");
+
+        provider.meta.as_mut().unwrap().url_strategy = Some(UrlStrategy::Stable);
+        assert_eq!(select(&router, &provider).await[0], "https://b.example.com");
+    }
+
+    #[tokio::test]
+    async fn region_order_takes_precedence_over_latency() {
+        let router = router();
+        let provider = provider(None);
+        router.db.save_provider("claude", &provider).unwrap();
+        for (url, latency, region) in [
+            ("https://b.example.com", 50, "us"),
+            ("https://c.example.com", 200, "asia"),
+        ] {
+            router.db.add_custom_endpoint("claude", "a", url).unwrap();
+            router
+                .db
+                .update_endpoint_health("claude", "a", url, Some(latency), true, 0)
+                .unwrap();
+            router
+                .db
+                .set_endpoint_region("claude", "a", url, Some(region))
+                .unwrap();
+        }
+        assert_eq!(select(&router, &provider).await[0], "https://b.example.com");
+
+        router
+            .db
+            .set_endpoint_region_order(&parse_region_order(" Asia, us,asia"))
+            .unwrap();
+        assert_eq!(
+            router.db.get_endpoint_region_order().unwrap(),
+            vec!["asia", "us"]
+        );
+        assert_eq!(
+            select(&router, &provider).await,
+            vec!["https://c.example.com", "https://b.example.com", BASE_URL]
+        );
+    }
+
+    #[tokio::test]
+    async fn url_strategy_controls_endpoint_order() {
+        let router = router();
+        let mut provider = provider(None);
+        router.db.save_provider("claude", &provider).unwrap();
+        for (url, latency) in [
+            ("https://b.example.com", 200),
+            ("https://c.example.com", 50),
+        ] {
+            router.db.add_custom_endpoint("claude", "a", url).unwrap();
+            router
+                .db
+                .update_endpoint_health("claude", "a", url, Some(latency), true, 0)
+                .unwrap();
+        }
+        let urls = select(&router, &provider).await;
+        assert_eq!(urls[0], "https://c.example.com");
+
+        provider.meta.as_mut().unwrap().url_strategy = Some(UrlStrategy::Priority);
+        let urls = select(&router, &provider).await;
+        assert_eq!(
+            urls,
+            vec![BASE_URL, "https://b.example.com", "https://c.example.com"]
+        );
+
+        provider.meta.as_mut().unwrap().url_strategy = Some(UrlStrategy::RoundRobin);
+        let firsts: Vec<String> = [
+            select(&router, &provider).await,
+            select(&router, &provider).await,
+            select(&router, &provider).await,
+            select(&router, &provider).await,
+        ]
+        .into_iter()
+        .map(|urls| urls[0].clone())
+        .collect();
+        assert_eq!(
+            firsts,
+            vec![
+                BASE_URL,
+                "https://b.example.com",
+                "https://c.example.com",
+                BASE_URL
+            ]
+        );
+
+        // 权重为 0 的端点不分配流量
+        router
+            .db
+            .add_custom_endpoint("claude", "a", BASE_URL)
+            .unwrap();
+        for (url, weight) in [
+            (BASE_URL, 0),
+            ("https://b.example.com", 0),
+            ("https://c.example.com", 3),
+        ] {
+            router
+                .db
+                .set_endpoint_weight("claude", "a", url, Some(weight))
+                .unwrap();
+        }
+        provider.meta.as_mut().unwrap().url_strategy = Some(UrlStrategy::Weighted);
+        for _ in 0..5 {
+            assert_eq!(select(&router, &provider).await[0], "https://c.example.com");
+        }
+    }
+
+    #[test]
+    fn fallback_serializes_with_mode_tag_and_cycles_presets() {
+        let retry = EndpointFallback::Retry { delay_seconds: 5 };
+        assert_eq!(
+            serde_json::to_value(retry).unwrap(),
+            json!({"mode": "retry", "delaySeconds": 5})
+        );
+        assert_eq!(
+            serde_json::from_value::<EndpointFallback>(json!({"mode": "failFast"})).unwrap(),
+            EndpointFallback::FailFast
+        );
+        assert_eq!(
+            EndpointFallback::Retry { delay_seconds: 10 }.next(),
+            EndpointFallback::Degrade
+        );
+        assert_eq!(
+            EndpointFallback::Retry { delay_seconds: 7 }.next(),
+            EndpointFallback::Degrade
+        );
+    }
+}
@@ -103,6 +103,61 @@ impl<'a> UsageLogger<'a> {
         )
         .map_err(|e| AppError::Database(format!("记录请求日志失败: {e}")))?;
 
+        // 同步累加到按天汇总的统计表
+        let is_error = !(200..300).contains(&log.status_code);
+        let cost_usd = total_cost.parse::<f64>().unwrap_or(0.0);
+        conn.execute(
+            "INSERT INTO provider_usage_daily (
+                app_type, provider_id, day, request_count, error_count, input_tokens, output_tokens,
+                cache_read_tokens, cache_creation_tokens, cost_usd
+            ) VALUES (?1, ?2, date(?3, 'unixepoch', 'localtime'), 1, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(app_type, provider_id, day) DO UPDATE SET
+                request_count = request_count + 1,
+                error_count = error_count + excluded.error_count,
+                input_tokens = input_tokens + excluded.input_tokens,
+                output_tokens = output_tokens + excluded.output_tokens,
+                cache_read_tokens = cache_read_tokens + excluded.cache_read_tokens,
+                cache_creation_tokens = cache_creation_tokens + excluded.cache_creation_tokens,
+                cost_usd = cost_usd + excluded.cost_usd",
+            rusqlite::params![
+                log.app_type,
+                log.provider_id,
+                created_at,
+                is_error as i64,
+                log.usage.input_tokens,
+                log.usage.output_tokens,
+                log.usage.cache_read_tokens,
+                log.usage.cache_creation_tokens,
+                cost_usd,
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("更新使用统计失败: {e}")))?;
+
+        conn.execute(
+            "INSERT INTO model_usage_daily (
+                app_type, model, day, request_count, input_tokens, output_tokens,
+                cache_read_tokens, cache_creation_tokens, cost_usd
+            ) VALUES (?1, ?2, date(?3, 'unixepoch', 'localtime'), 1, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(app_type, model, day) DO UPDATE SET
+                request_count = request_count + 1,
+                input_tokens = input_tokens + excluded.input_tokens,
+                output_tokens = output_tokens + excluded.output_tokens,
+                cache_read_tokens = cache_read_tokens + excluded.cache_read_tokens,
+                cache_creation_tokens = cache_creation_tokens + excluded.cache_creation_tokens,
+                cost_usd = cost_usd + excluded.cost_usd",
+            rusqlite::params![
+                log.app_type,
+                log.model,
+                created_at,
+                log.usage.input_tokens,
+                log.usage.output_tokens,
+                log.usage.cache_read_tokens,
+                log.usage.cache_creation_tokens,
+                cost_usd,
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("更新模型使用统计失败: {e}")))?;
+
         Ok(())
     }
 
@@ -294,6 +349,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_daily_usage_sums_cache_tokens() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let logger = UsageLogger::new(&db);
+
+        for (request_id, cache_read, cache_creation) in [("req-1", 100, 10), ("req-2", 250, 5)] {
+            let usage = TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cache_read_tokens: cache_read,
+                cache_creation_tokens: cache_creation,
+                model: None,
+            };
+            logger.log_with_calculation(
+                request_id.to_string(),
+                "provider-1".to_string(),
+                "claude".to_string(),
+                "unknown-model".to_string(),
+                usage,
+                Decimal::from(1),
+                100,
+                None,
+                200,
+                None,
+                Some("claude".to_string()),
+                false,
+            )?;
+        }
+
+        let conn = crate::database::lock_conn!(db.conn);
+        let (requests, cache_read, cache_creation): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT request_count, cache_read_tokens, cache_creation_tokens
+                 FROM provider_usage_daily WHERE app_type = 'claude' AND provider_id = 'provider-1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((requests, cache_read, cache_creation), (2, 350, 15));
+        Ok(())
+    }
+
     #[test]
     fn test_log_error() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -0,0 +1,401 @@
+//! 故障事件 Webhook 通知
+//!
+//! 发生故障转移切换、供应商熔断器打开、或供应商全部端点都不可用时，向配置的地址 POST 一条
+//! 通知，便于值班工具接入。每个地址可使用通用 JSON、Slack 或 Discord 格式；按
+//! `格式=URL` 书写，省略格式时按域名识别 Slack / Discord，其余使用通用 JSON。
+//!
+//! 同一事件（类型 + 应用 + 供应商）在冷却时间内只通知一次，避免持续故障时刷屏。发送在后台
+//! 进行，经全局上游代理与共享 DNS 解析，失败只记录日志。
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{dns, upstream_proxy::build_client};
+use crate::database::Database;
+
+/// 同一事件两次通知之间的最短间隔
+const COOLDOWN: Duration = Duration::from_secs(300);
+
+/// 单次发送的超时时间
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Webhook 负载格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// 通用 JSON：事件类型、应用、供应商与说明文字
+    Json,
+    /// Slack Incoming Webhook（`text` 字段）
+    Slack,
+    /// Discord Webhook（`content` 字段）
+    Discord,
+}
+
+impl WebhookFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Slack => "slack",
+            Self::Discord => "discord",
+        }
+    }
+
+    /// 未指定格式时按域名识别
+    fn detect(url: &Url) -> Self {
+        match url.host_str().unwrap_or_default() {
+            "hooks.slack.com" => Self::Slack,
+            "discord.com" | "discordapp.com" if url.path().starts_with("/api/webhooks/") => {
+                Self::Discord
+            }
+            _ => Self::Json,
+        }
+    }
+}
+
+impl FromStr for WebhookFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "slack" => Ok(Self::Slack),
+            "discord" => Ok(Self::Discord),
+            other => Err(format!(
+                "Unknown webhook format '{other}', use json, slack or discord"
+            )),
+        }
+    }
+}
+
+/// 通知地址
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookTarget {
+    pub url: String,
+    pub format: WebhookFormat,
+}
+
+/// 会触发通知的事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// 故障转移切换了当前供应商
+    Failover,
+    /// 供应商熔断器打开
+    CircuitOpen,
+    /// 供应商的所有端点都不可用
+    EndpointsDown,
+}
+
+impl WebhookEventKind {
+    pub const ALL: [WebhookEventKind; 3] = [Self::Failover, Self::CircuitOpen, Self::EndpointsDown];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Failover => "failover",
+            Self::CircuitOpen => "circuit_open",
+            Self::EndpointsDown => "endpoints_down",
+        }
+    }
+}
+
+impl FromStr for WebhookEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s.trim())
+            .ok_or_else(|| format!("Unknown webhook event '{s}'"))
+    }
+}
+
+/// Webhook 设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookConfig {
+    pub targets: Vec<WebhookTarget>,
+    /// 需要通知的事件类型
+    pub events: Vec<WebhookEventKind>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            events: WebhookEventKind::ALL.to_vec(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn notifies(&self, kind: WebhookEventKind) -> bool {
+        !self.targets.is_empty() && self.events.contains(&kind)
+    }
+}
+
+/// 解析 `[格式=]URL` 列表，条目以逗号或空白分隔
+pub fn parse_webhook_targets(text: &str) -> Result<Vec<WebhookTarget>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (format, url) = match entry.split_once('=') {
+                Some((format, url)) if !format.contains(':') => {
+                    (Some(format.parse::<WebhookFormat>()?), url)
+                }
+                _ => (None, entry),
+            };
+            let parsed =
+                Url::parse(url).map_err(|e| format!("Invalid webhook URL '{url}': {e}"))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(format!("Webhook URL '{url}' must use http or https"));
+            }
+            Ok(WebhookTarget {
+                url: url.to_string(),
+                format: format.unwrap_or_else(|| WebhookFormat::detect(&parsed)),
+            })
+        })
+        .collect()
+}
+
+/// 格式化为 [`parse_webhook_targets`] 可解析的文本，可自动识别的格式省略不写
+pub fn format_webhook_targets(targets: &[WebhookTarget]) -> String {
+    targets
+        .iter()
+        .map(|target| {
+            let detected = Url::parse(&target.url)
+                .map(|url| WebhookFormat::detect(&url))
+                .unwrap_or(WebhookFormat::Json);
+            if detected == target.format {
+                target.url.clone()
+            } else {
+                format!("{}={}", target.format.as_str(), target.url)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 一次需要通知的事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    pub kind: WebhookEventKind,
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 故障转移前的供应商名称
+    pub previous_provider: Option<String>,
+}
+
+impl WebhookEvent {
+    fn message(&self) -> String {
+        let provider = &self.provider_name;
+        match self.kind {
+            WebhookEventKind::Failover => match &self.previous_provider {
+                Some(previous) => format!(
+                    "[cc-switch] {}: failed over from {previous} to {provider}",
+                    self.app_type
+                ),
+                None => format!("[cc-switch] {}: failed over to {provider}", self.app_type),
+            },
+            WebhookEventKind::CircuitOpen => format!(
+                "[cc-switch] {}: circuit breaker opened for {provider}",
+                self.app_type
+            ),
+            WebhookEventKind::EndpointsDown => format!(
+                "[cc-switch] {}: all endpoints of {provider} are unhealthy",
+                self.app_type
+            ),
+        }
+    }
+
+    /// 按目标格式生成请求体
+    pub fn payload(&self, format: WebhookFormat) -> Value {
+        let message = self.message();
+        match format {
+            WebhookFormat::Slack => json!({ "text": message }),
+            WebhookFormat::Discord => json!({ "content": message }),
+            WebhookFormat::Json => json!({
+                "event": self.kind.as_str(),
+                "app": self.app_type,
+                "providerId": self.provider_id,
+                "providerName": self.provider_name,
+                "previousProvider": self.previous_provider,
+                "message": message,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }),
+        }
+    }
+
+    fn dedupe_key(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.kind.as_str(),
+            self.app_type,
+            self.provider_id
+        )
+    }
+}
+
+/// 事件通知器，代理各组件共享一个实例
+pub struct WebhookNotifier {
+    db: Arc<Database>,
+    /// 各事件最近一次通知的时间
+    last_sent: Mutex<HashMap<String, Instant>>,
+}
+
+impl WebhookNotifier {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            last_sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 供应商级事件：按 ID 查出供应商名称后通知
+    pub fn notify_provider(&self, kind: WebhookEventKind, app_type: &str, provider_id: &str) {
+        let provider_name = self
+            .db
+            .get_provider_by_id(provider_id, app_type)
+            .ok()
+            .flatten()
+            .map_or_else(|| provider_id.to_string(), |p| p.name);
+        self.notify(WebhookEvent {
+            kind,
+            app_type: app_type.to_string(),
+            provider_id: provider_id.to_string(),
+            provider_name,
+            previous_provider: None,
+        });
+    }
+
+    /// 在后台向所有地址发送通知，冷却时间内的重复事件直接丢弃
+    pub fn notify(&self, event: WebhookEvent) {
+        let config = match self.db.get_proxy_webhook_config() {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("[Webhook] 读取设置失败: {e}");
+                return;
+            }
+        };
+        if !config.notifies(event.kind) || !self.should_send(&event.dedupe_key(), Instant::now()) {
+            return;
+        }
+        let upstream_proxy = self.db.get_proxy_upstream_url().unwrap_or_default();
+        let client = match build_client(
+            dns::client_builder().timeout(SEND_TIMEOUT),
+            upstream_proxy.as_deref(),
+        ) {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("[Webhook] 创建客户端失败: {e}");
+                return;
+            }
+        };
+
+        log::info!("[Webhook] 发送通知: {}", event.message());
+        for target in config.targets {
+            let client = client.clone();
+            let payload = event.payload(target.format);
+            tokio::spawn(async move {
+                let result = client.post(&target.url).json(&payload).send().await;
+                match result.and_then(|r| r.error_for_status()) {
+                    Ok(_) => log::debug!("[Webhook] 已通知 {}", target.url),
+                    Err(e) => log::warn!(
+                        "[Webhook] 通知 {} 失败: {}",
+                        target.url,
+                        crate::redact::redact(&e.to_string())
+                    ),
+                }
+            });
+        }
+    }
+
+    /// 冷却时间内同一事件只返回一次 true
+    fn should_send(&self, key: &str, now: Instant) -> bool {
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        last_sent.retain(|_, sent| now.duration_since(*sent) < COOLDOWN);
+        if last_sent.contains_key(key) {
+            return false;
+        }
+        last_sent.insert(key.to_string(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: WebhookEventKind) -> WebhookEvent {
+        WebhookEvent {
+            kind,
+            app_type: "claude".to_string(),
+            provider_id: "p1".to_string(),
+            provider_name: "Relay".to_string(),
+            previous_provider: Some("Official".to_string()),
+        }
+    }
+
+    #[test]
+    fn targets_round_trip_and_detect_the_format() {
+        let targets = parse_webhook_targets(
+            "https://hooks.slack.com/services/T0/B0/x, \
+             https://discord.com/api/webhooks/1/abc\njson=https://hooks.slack.com/x \
+             discord=http://10.0.0.5:8080/hook",
+        )
+        .unwrap();
+        let formats: Vec<_> = targets.iter().map(|t| t.format).collect();
+        assert_eq!(
+            formats,
+            vec![
+                WebhookFormat::Slack,
+                WebhookFormat::Discord,
+                WebhookFormat::Json,
+                WebhookFormat::Discord
+            ]
+        );
+        assert_eq!(
+            parse_webhook_targets(&format_webhook_targets(&targets)).unwrap(),
+            targets
+        );
+        assert!(parse_webhook_targets("teams=https://x.dev").is_err());
+        assert!(parse_webhook_targets("ftp://x.dev/hook").is_err());
+        assert!(parse_webhook_targets("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn payloads_follow_the_target_format() {
+        let event = event(WebhookEventKind::Failover);
+        let message = "[cc-switch] claude: failed over from Official to Relay";
+        assert_eq!(
+            event.payload(WebhookFormat::Slack),
+            json!({ "text": message })
+        );
+        assert_eq!(
+            event.payload(WebhookFormat::Discord),
+            json!({ "content": message })
+        );
+        let generic = event.payload(WebhookFormat::Json);
+        assert_eq!(generic["event"], "failover");
+        assert_eq!(generic["providerName"], "Relay");
+        assert_eq!(generic["message"], message);
+    }
+
+    #[test]
+    fn repeated_events_are_throttled() {
+        let notifier = WebhookNotifier::new(Arc::new(Database::memory().unwrap()));
+        let now = Instant::now();
+        let key = event(WebhookEventKind::CircuitOpen).dedupe_key();
+        assert!(notifier.should_send(&key, now));
+        assert!(!notifier.should_send(&key, now + Duration::from_secs(60)));
+        assert!(notifier.should_send("endpoints_down:claude:p1", now));
+        assert!(notifier.should_send(&key, now + COOLDOWN));
+    }
+}
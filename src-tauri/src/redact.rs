@@ -0,0 +1,180 @@
+//! 敏感信息脱敏
+//!
+//! 日志（包括代理日志与 TUI 日志面板）、访问日志、提示消息和返回给客户端的错误信息在写出前
+//! 统一经过 [`redact`]，把看起来像 API Key 或 Bearer Token 的内容替换为 `[redacted]`。
+//! 识别规则：
+//! - `Bearer ` 之后的令牌；
+//! - URL 中 `key=` 查询参数的值；
+//! - 以常见密钥前缀（`sk-`、`AIza`、`ghp_` 等）开头的长串；
+//! - 32 个字符以上、同时包含大写、小写字母与数字的连续串（UUID、十六进制哈希不在此列）。
+
+/// 替换敏感内容的占位符
+pub const REDACTED: &str = "[redacted]";
+
+/// 常见的 API Key 前缀
+const KEY_PREFIXES: &[&str] = &[
+    "sk-",
+    "AIza",
+    "ghp_",
+    "gho_",
+    "github_pat_",
+    "xai-",
+    "hf_",
+    "glpat-",
+];
+
+/// 带前缀的密钥，前缀之后至少还有这么多字符才视为密钥
+const MIN_PREFIXED_TAIL: usize = 16;
+
+/// 无前缀的随机串，至少这么长才视为密钥
+const MIN_GENERIC_LEN: usize = 32;
+
+/// 抹去文本中看起来像密钥或令牌的内容
+pub fn redact(text: &str) -> String {
+    redact_with(text, &[])
+}
+
+/// 先抹去已知的密钥值（如供应商配置的 API Key），再按通用规则脱敏
+pub fn redact_with(text: &str, secrets: &[&str]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets.iter().filter(|s| !s.is_empty()) {
+        text = text.replace(secret, REDACTED);
+    }
+    let text = redact_after(&text, "key=", |prefix| {
+        prefix.is_empty() || prefix.ends_with(['?', '&'])
+    });
+    let text = redact_after(&text, "bearer ", |_| true);
+    redact_tokens(&text)
+}
+
+/// 界面上展示密钥时只保留首尾各 4 个字符，过短的密钥整体以 `*` 代替
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        "*".repeat(chars.len())
+    } else {
+        let head: String = chars[..4].iter().collect();
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{head}...{tail}")
+    }
+}
+
+/// 把 `marker`（不区分大小写）之后的值替换为占位符；`accept` 根据 marker 之前的文本
+/// 决定这一处是否需要脱敏
+fn redact_after(text: &str, marker: &str, accept: impl Fn(&str) -> bool) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(marker) {
+        let start = search + found + marker.len();
+        search = start;
+        if !accept(&text[..search - marker.len()]) {
+            continue;
+        }
+        let end = text[start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.~%+/=".contains(c)))
+            .map_or(text.len(), |i| start + i);
+        if end > start && &text[start..end] != REDACTED {
+            result.push_str(&text[last..start]);
+            result.push_str(REDACTED);
+            last = end;
+            search = end;
+        }
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// 逐个检查由字母、数字、`-`、`_` 组成的连续串，像密钥的整体替换
+fn redact_tokens(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = rest.find(is_token_char).unwrap_or(rest.len());
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_token_char(c)).unwrap_or(rest.len());
+        let token = &rest[..end];
+        if looks_like_secret(token) {
+            result.push_str(REDACTED);
+        } else {
+            result.push_str(token);
+        }
+        rest = &rest[end..];
+    }
+    result
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+fn looks_like_secret(token: &str) -> bool {
+    if KEY_PREFIXES
+        .iter()
+        .any(|prefix| token.starts_with(prefix) && token.len() >= prefix.len() + MIN_PREFIXED_TAIL)
+    {
+        return true;
+    }
+    token.len() >= MIN_GENERIC_LEN
+        && token.bytes().any(|b| b.is_ascii_uppercase())
+        && token.bytes().any(|b| b.is_ascii_lowercase())
+        && token.bytes().any(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_secrets_and_key_params_are_redacted() {
+        let text =
+            "error sending request for url (https://x.dev/v1?alt=sse&key=AIzaSecret): sk-live-123";
+        assert_eq!(
+            redact_with(text, &["sk-live-123"]),
+            "error sending request for url (https://x.dev/v1?alt=sse&key=[redacted]): [redacted]"
+        );
+        assert_eq!(redact("monkey=1"), "monkey=1");
+    }
+
+    #[test]
+    fn tokens_that_look_like_keys_are_redacted() {
+        assert_eq!(
+            redact("Authorization: Bearer abc.def-123"),
+            "Authorization: Bearer [redacted]"
+        );
+        assert_eq!(
+            redact("invalid x-api-key sk-ant-REDACTED given"),
+            "invalid x-api-key [redacted] given"
+        );
+        assert_eq!(
+            redact("token=ghp_0123456789abcdefABCDEF"),
+            "token=[redacted]"
+        );
+        assert_eq!(
+            redact("secret \"Zm9vYmFyYmF6cXV4MTIzNDU2Nzg5MEFCQ0RFRg\""),
+            "secret \"[redacted]\""
+        );
+    }
+
+    #[test]
+    fn ordinary_text_is_left_alone() {
+        for text in [
+            "request 6f1c2b0e-8a4d-4c7e-9b1a-2d3e4f5a6b7c failed",
+            "sha256 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+            "ask-me-anything and task-runner",
+            "claude-3-5-sonnet-20241022 returned 429",
+            "上游错误 (401): invalid api key",
+        ] {
+            assert_eq!(redact(text), text);
+        }
+    }
+
+    #[test]
+    fn mask_secret_keeps_head_and_tail() {
+        assert_eq!(mask_secret("sk-1234567890abcd"), "sk-1...abcd");
+        assert_eq!(mask_secret("short"), "*****");
+        assert_eq!(mask_secret("密钥密钥密钥密钥密钥"), "密钥密钥...密钥密钥");
+    }
+}
@@ -0,0 +1,97 @@
+//! 定时数据库备份
+//!
+//! 后台任务按设置的间隔生成压缩备份并轮转；每次调度都重新读取设置，修改后无需重启。
+//! 距上次备份的时间以 backups 目录中最新的定时备份为准，重启程序不会重置计时。
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+
+use crate::database::Database;
+use crate::error::AppError;
+
+/// 调度检查的粒度
+const TICK: Duration = Duration::from_secs(60);
+
+/// 定时备份后台任务
+pub struct BackupScheduler {
+    db: Arc<Database>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BackupScheduler {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台任务，重复调用无副作用
+    pub fn start(self: &Arc<Self>) {
+        let mut task = self.task.lock().unwrap_or_else(|e| e.into_inner());
+        if task.is_some() {
+            return;
+        }
+        let scheduler = self.clone();
+        *task = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(TICK);
+            loop {
+                ticker.tick().await;
+                let db = scheduler.db.clone();
+                match tokio::task::spawn_blocking(move || run_if_due(&db)).await {
+                    Ok(Ok(Some(path))) => log::info!("[Backup] 已生成定时备份: {}", path.display()),
+                    Ok(Ok(None)) => {}
+                    Ok(Err(e)) => log::warn!("[Backup] 定时备份失败: {e}"),
+                    Err(e) => log::warn!("[Backup] 定时备份任务异常: {e}"),
+                }
+            }
+        }));
+    }
+
+    /// 停止后台任务
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+    }
+}
+
+/// 已启用且距最新定时备份超过间隔时生成备份
+fn run_if_due(db: &Database) -> Result<Option<std::path::PathBuf>, AppError> {
+    let config = db.get_auto_backup_config()?;
+    if !config.enabled {
+        return Ok(None);
+    }
+    let last = Database::list_backups()?
+        .into_iter()
+        .find(|backup| backup.automatic)
+        .map(|backup| backup.modified_at);
+    if !is_due(last, config.interval_hours, Utc::now().timestamp()) {
+        return Ok(None);
+    }
+    db.create_auto_backup(config.retain).map(Some)
+}
+
+/// 从未备份过，或距上次备份已满间隔
+fn is_due(last_backup_at: Option<i64>, interval_hours: u64, now: i64) -> bool {
+    let interval = interval_hours.max(1).saturating_mul(3600) as i64;
+    last_backup_at.is_none_or(|last| now - last >= interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_is_due_after_interval() {
+        let now = 1_000_000;
+        assert!(is_due(None, 24, now));
+        assert!(!is_due(Some(now - 3600), 24, now));
+        assert!(is_due(Some(now - 24 * 3600), 24, now));
+        // 间隔为 0 时按 1 小时处理，避免每分钟都备份
+        assert!(!is_due(Some(now - 60), 0, now));
+    }
+}
@@ -1,14 +1,158 @@
 use super::provider::ProviderService;
 use crate::app_config::{AppType, MultiAppConfig};
+use crate::database::Database;
 use crate::error::AppError;
 use crate::provider::Provider;
+use crate::proxy::cors::CorsConfig;
+use crate::proxy::dns::DnsConfig;
+use crate::proxy::hedging::HedgingConfig;
+use crate::proxy::outlier::OutlierDetectionConfig;
+use crate::proxy::response_cache::ResponseCacheConfig;
+use crate::proxy::shadow::ShadowConfig;
+use crate::proxy::size_limits::SizeLimitConfig;
+use crate::proxy::switch_back::SwitchBackConfig;
+use crate::proxy::tls::ProxyTlsConfig;
+use crate::proxy::types::{ClientRateLimit, LoadBalanceStrategy};
+use crate::proxy::unix_socket::UnixSocketConfig;
+use crate::proxy::webhook::WebhookConfig;
+use crate::store::AppState;
 use chrono::Utc;
-use serde_json::Value;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 use std::fs;
 use std::path::Path;
 
 const MAX_BACKUPS: usize = 10;
 
+/// 导出文件格式版本
+///
+/// - 1：供应商（含端点与 meta）、MCP 服务器、提示词、代理及混合模式配置
+/// - 2：新增 `ui`（TUI 主题与自定义按键）
+/// - 3：端点新增权重、固定与地区，新增 settings 表中的代理设置（`proxySettings`）
+pub const EXPORT_FORMAT_VERSION: u32 = 3;
+
+/// 导出时替换密钥的占位符
+pub(super) const REDACTED_PLACEHOLDER: &str = crate::redact::REDACTED;
+
+/// 键名包含这些片段（不区分大小写）的字符串值视为密钥
+const SECRET_KEY_MARKERS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "token",
+    "secret",
+    "password",
+    "authorization",
+];
+
+/// 随导出文件一起迁移的界面偏好，由 TUI 读取和应用
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UiPreferences {
+    /// 主题预设名
+    pub theme: Option<String>,
+    /// `keys.toml` 中覆盖的按键，动作名到按键（字符串或数组）
+    pub keybindings: IndexMap<String, Value>,
+}
+
+impl UiPreferences {
+    pub fn is_empty(&self) -> bool {
+        self.theme.is_none() && self.keybindings.is_empty()
+    }
+}
+
+/// settings 表中的全局代理设置，导出文件中缺少的项按默认值处理
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxySettings {
+    pub tls: ProxyTlsConfig,
+    pub auth_token: Option<String>,
+    pub ip_allowlist: Vec<String>,
+    pub cors: CorsConfig,
+    pub unix_socket: UnixSocketConfig,
+    pub outlier: OutlierDetectionConfig,
+    pub hedging: HedgingConfig,
+    pub response_cache: ResponseCacheConfig,
+    pub upstream_url: Option<String>,
+    pub region_order: Vec<String>,
+    pub dns: DnsConfig,
+    pub size_limits: SizeLimitConfig,
+    pub webhook: WebhookConfig,
+}
+
+impl ProxySettings {
+    pub(super) fn load(db: &Database) -> Result<Self, AppError> {
+        Ok(Self {
+            tls: db.get_proxy_tls_config()?,
+            auth_token: db.get_proxy_auth_token()?,
+            ip_allowlist: db.get_proxy_ip_allowlist()?,
+            cors: db.get_proxy_cors_config()?,
+            unix_socket: db.get_proxy_unix_socket_config()?,
+            outlier: db.get_proxy_outlier_config()?,
+            hedging: db.get_proxy_hedging_config()?,
+            response_cache: db.get_proxy_response_cache_config()?,
+            upstream_url: db.get_proxy_upstream_url()?,
+            region_order: db.get_endpoint_region_order()?,
+            dns: db.get_proxy_dns_config()?,
+            size_limits: db.get_proxy_size_limits()?,
+            webhook: db.get_proxy_webhook_config()?,
+        })
+    }
+
+    /// 写入 settings 表；导出时被隐去的访问令牌保留现有值
+    pub(super) fn apply(&self, db: &Database) -> Result<(), AppError> {
+        db.set_proxy_tls_config(&self.tls)?;
+        if self.auth_token.as_deref() != Some(REDACTED_PLACEHOLDER) {
+            db.set_proxy_auth_token(self.auth_token.as_deref())?;
+        }
+        db.set_proxy_ip_allowlist(&self.ip_allowlist)?;
+        db.set_proxy_cors_config(&self.cors)?;
+        db.set_proxy_unix_socket_config(&self.unix_socket)?;
+        db.set_proxy_outlier_config(&self.outlier)?;
+        db.set_proxy_hedging_config(&self.hedging)?;
+        db.set_proxy_response_cache_config(&self.response_cache)?;
+        db.set_proxy_upstream_url(self.upstream_url.as_deref())?;
+        db.set_endpoint_region_order(&self.region_order)?;
+        db.set_proxy_dns_config(&self.dns)?;
+        db.set_proxy_size_limits(&self.size_limits)?;
+        db.set_proxy_webhook_config(&self.webhook)
+    }
+}
+
+/// 应用级代理设置中不属于 [`AppProxyConfig`](crate::proxy::types::AppProxyConfig) 的部分
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppProxySettings {
+    pub rate_limit: ClientRateLimit,
+    pub load_balance_strategy: LoadBalanceStrategy,
+    pub stream_failover: bool,
+    pub dedicated_port: Option<u16>,
+    pub shadow: ShadowConfig,
+    pub switch_back: SwitchBackConfig,
+}
+
+impl AppProxySettings {
+    pub(super) fn load(db: &Database, app_type: &str) -> Result<Self, AppError> {
+        Ok(Self {
+            rate_limit: db.get_client_rate_limit(app_type)?,
+            load_balance_strategy: db.get_load_balance_strategy(app_type)?,
+            stream_failover: db.get_stream_failover(app_type)?,
+            dedicated_port: db.get_dedicated_port(app_type)?,
+            shadow: db.get_shadow_config(app_type)?,
+            switch_back: db.get_switch_back_config(app_type)?,
+        })
+    }
+
+    pub(super) fn apply(&self, db: &Database, app_type: &str) -> Result<(), AppError> {
+        db.set_client_rate_limit(app_type, self.rate_limit)?;
+        db.set_load_balance_strategy(app_type, self.load_balance_strategy)?;
+        db.set_stream_failover(app_type, self.stream_failover)?;
+        db.set_dedicated_port(app_type, self.dedicated_port)?;
+        db.set_shadow_config(app_type, &self.shadow)?;
+        db.set_switch_back_config(app_type, &self.switch_back)
+    }
+}
+
 /// 配置导入导出相关业务逻辑
 pub struct ConfigService;
 
@@ -83,6 +227,76 @@ impl ConfigService {
         Ok(())
     }
 
+    /// 将供应商、MCP 服务器、提示词、端点、代理配置及界面偏好导出为单个 JSON 文件
+    ///
+    /// `redact_secrets` 为 true 时 API Key 等密钥会被替换为 `[redacted]`，
+    /// 导出文件可通过 [`ConfigService::read_import_bundle`] 重新导入
+    pub async fn export_to_file(
+        state: &AppState,
+        path: &Path,
+        redact_secrets: bool,
+        ui: &UiPreferences,
+    ) -> Result<(), AppError> {
+        let mut export = Self::build_export(&state.db).await?;
+        if redact_secrets {
+            redact_secret_values(&mut export);
+        }
+        // 按键动作名（如 set_master_password）会被误判为密钥，界面偏好在隐去之后再写入
+        export["ui"] =
+            serde_json::to_value(ui).map_err(|e| AppError::JsonSerialize { source: e })?;
+        crate::config::write_json_file(path, &export)
+    }
+
+    pub(super) async fn build_export(db: &Database) -> Result<Value, AppError> {
+        let mut apps = Map::new();
+        for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app_type = app.as_str();
+            let providers = db.get_all_providers(app_type)?;
+
+            let mut endpoints = Map::new();
+            for id in providers.keys() {
+                let urls = db.get_provider_endpoints_with_health(app_type, id)?;
+                if !urls.is_empty() {
+                    endpoints.insert(
+                        id.clone(),
+                        json!(urls
+                            .iter()
+                            .map(|e| json!({
+                                "url": e.url,
+                                "isPrimary": e.is_primary,
+                                "isPinned": e.is_pinned,
+                                "weight": e.weight,
+                                "region": e.region,
+                            }))
+                            .collect::<Vec<_>>()),
+                    );
+                }
+            }
+
+            apps.insert(
+                app_type.to_string(),
+                json!({
+                    "currentProvider": db.get_current_provider(app_type)?,
+                    "providers": providers,
+                    "endpoints": endpoints,
+                    "prompts": db.get_prompts(app_type)?,
+                    "proxy": db.get_proxy_config_for_app(app_type).await?,
+                    "hybridMode": db.get_hybrid_mode_config(app_type)?,
+                    "proxySettings": AppProxySettings::load(db, app_type)?,
+                }),
+            );
+        }
+
+        Ok(json!({
+            "version": EXPORT_FORMAT_VERSION,
+            "exportedAt": Utc::now().to_rfc3339(),
+            "apps": apps,
+            "mcpServers": db.get_all_mcp_servers()?,
+            "proxy": db.get_global_proxy_config().await?,
+            "proxySettings": ProxySettings::load(db)?,
+        }))
+    }
+
     /// 同步当前供应商到对应的 live 配置。
     pub fn sync_current_providers_to_live(config: &mut MultiAppConfig) -> Result<(), AppError> {
         Self::sync_current_provider_for_app(config, &AppType::Claude)?;
@@ -220,3 +434,55 @@ impl ConfigService {
         Ok(())
     }
 }
+
+/// 递归地将密钥字段的字符串值替换为 `[redacted]`
+fn redact_secret_values(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                let is_secret = SECRET_KEY_MARKERS.iter().any(|m| key.contains(m));
+                match value {
+                    Value::String(s) if is_secret && !s.is_empty() => {
+                        *s = REDACTED_PLACEHOLDER.to_string();
+                    }
+                    _ => redact_secret_values(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secret_values),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_secret_values_masks_nested_credentials() {
+        let mut value = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "sk-ant",
+                "ANTHROPIC_BASE_URL": "https://api.example.com"
+            },
+            "auth": { "OPENAI_API_KEY": "sk-openai" },
+            "servers": [{ "headers": { "Authorization": "Bearer x" }, "maxTokens": 10 }],
+            "emptyApiKey": ""
+        });
+        redact_secret_values(&mut value);
+
+        assert_eq!(value["env"]["ANTHROPIC_AUTH_TOKEN"], "[redacted]");
+        assert_eq!(
+            value["env"]["ANTHROPIC_BASE_URL"],
+            "https://api.example.com"
+        );
+        assert_eq!(value["auth"]["OPENAI_API_KEY"], "[redacted]");
+        assert_eq!(
+            value["servers"][0]["headers"]["Authorization"],
+            "[redacted]"
+        );
+        assert_eq!(value["servers"][0]["maxTokens"], 10);
+        assert_eq!(value["emptyApiKey"], "");
+    }
+}
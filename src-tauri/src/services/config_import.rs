@@ -0,0 +1,703 @@
+//! 配置导入
+//!
+//! 读取 [`ConfigService::export_to_file`] 生成的 JSON 文件，按分类的冲突处理方式
+//! 生成导入计划供预览，确认后再通过各业务服务写入。
+//! 旧版本的导出文件会先按 [`BUNDLE_MIGRATIONS`] 逐级升级到当前格式再解析。
+//! 也可以直接读取桌面 GUI 的数据库，按同样的流程合并；
+//! YAML/TOML 供应商清单（见 [`super::provider_roster`]）则按名称更新或新增供应商
+
+use super::config::{
+    AppProxySettings, ConfigService, ProxySettings, UiPreferences, EXPORT_FORMAT_VERSION,
+    REDACTED_PLACEHOLDER,
+};
+use super::provider_roster;
+use super::{McpService, PromptService, ProviderService};
+use crate::app_config::{AppType, McpServer};
+use crate::database::{database_file_state, Database, DatabaseFileState};
+use crate::error::AppError;
+use crate::prompt::Prompt;
+use crate::provider::Provider;
+use crate::proxy::types::{AppProxyConfig, GlobalProxyConfig, HybridModeConfig};
+use crate::store::AppState;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+/// 导入时与现有条目冲突的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// 保留现有条目
+    #[default]
+    Skip,
+    /// 用导入的条目覆盖
+    Overwrite,
+    /// 以新 ID 另存为副本
+    Rename,
+}
+
+impl ConflictStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::Rename => "duplicate",
+        }
+    }
+}
+
+/// 导入内容的分类，每类可单独选择冲突处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportCategory {
+    Providers,
+    McpServers,
+    Prompts,
+    /// 代理及混合模式配置
+    Proxy,
+    /// TUI 主题与自定义按键
+    Preferences,
+}
+
+impl ImportCategory {
+    pub const ALL: [ImportCategory; 5] = [
+        Self::Providers,
+        Self::McpServers,
+        Self::Prompts,
+        Self::Proxy,
+        Self::Preferences,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Providers => "Providers",
+            Self::McpServers => "MCP servers",
+            Self::Prompts => "Prompts",
+            Self::Proxy => "Proxy settings",
+            Self::Preferences => "Theme & keys",
+        }
+    }
+
+    /// 该分类下依次可选的冲突处理方式；代理配置与界面偏好只有一份，不能另存副本
+    pub fn strategies(&self) -> &'static [ConflictStrategy] {
+        match self {
+            Self::Proxy | Self::Preferences => {
+                &[ConflictStrategy::Skip, ConflictStrategy::Overwrite]
+            }
+            _ => &[
+                ConflictStrategy::Skip,
+                ConflictStrategy::Overwrite,
+                ConflictStrategy::Rename,
+            ],
+        }
+    }
+}
+
+/// 各分类的冲突处理方式
+#[derive(Debug, Clone, Default)]
+pub struct ImportStrategies(HashMap<ImportCategory, ConflictStrategy>);
+
+impl ImportStrategies {
+    pub fn get(&self, category: ImportCategory) -> ConflictStrategy {
+        self.0.get(&category).copied().unwrap_or_default()
+    }
+
+    /// 切换到该分类的下一个冲突处理方式
+    pub fn cycle(&mut self, category: ImportCategory) -> ConflictStrategy {
+        let choices = category.strategies();
+        let current = choices
+            .iter()
+            .position(|s| *s == self.get(category))
+            .unwrap_or(0);
+        let next = choices[(current + 1) % choices.len()];
+        self.0.insert(category, next);
+        next
+    }
+}
+
+/// 单个条目的导入动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportAction {
+    Add,
+    Overwrite,
+    Skip,
+    /// 以新 ID 导入
+    Rename(String),
+}
+
+/// 导入计划中的一项
+#[derive(Debug, Clone)]
+pub struct ImportItem {
+    pub category: ImportCategory,
+    /// 所属应用，MCP 服务器与全局代理配置为 None
+    pub app: Option<AppType>,
+    pub id: String,
+    pub name: String,
+    pub action: ImportAction,
+}
+
+/// 导入结果统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+    /// 选择覆盖时需由 TUI 应用的界面偏好
+    pub preferences: Option<UiPreferences>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointEntry {
+    url: String,
+    #[serde(default)]
+    is_primary: bool,
+    #[serde(default)]
+    is_pinned: bool,
+    #[serde(default)]
+    weight: Option<u32>,
+    #[serde(default)]
+    region: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AppBundle {
+    providers: IndexMap<String, Provider>,
+    endpoints: HashMap<String, Vec<EndpointEntry>>,
+    prompts: IndexMap<String, Prompt>,
+    proxy: Option<AppProxyConfig>,
+    hybrid_mode: Option<HybridModeConfig>,
+    proxy_settings: Option<AppProxySettings>,
+}
+
+/// 解析后的导出文件
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBundle {
+    version: u32,
+    #[serde(default)]
+    apps: IndexMap<String, AppBundle>,
+    #[serde(default)]
+    mcp_servers: IndexMap<String, McpServer>,
+    #[serde(default)]
+    proxy: Option<GlobalProxyConfig>,
+    #[serde(default)]
+    proxy_settings: Option<ProxySettings>,
+    #[serde(default)]
+    ui: UiPreferences,
+    /// 文件中是否含有导出时隐去的密钥
+    #[serde(skip)]
+    redacted: bool,
+    /// 是否由供应商清单转换而来
+    #[serde(skip)]
+    roster: bool,
+}
+
+impl ImportBundle {
+    /// 导出时隐去了密钥，导入覆盖会丢失现有 API Key
+    pub fn has_redacted_secrets(&self) -> bool {
+        self.redacted
+    }
+
+    /// 文件原本的格式版本（升级前）
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// 来自供应商清单，同名供应商按覆盖处理
+    pub fn is_roster(&self) -> bool {
+        self.roster
+    }
+
+    /// 预览时的初始冲突处理方式：清单默认覆盖同名供应商，其余跳过
+    pub fn default_strategies(&self) -> ImportStrategies {
+        let mut strategies = ImportStrategies::default();
+        if self.roster {
+            strategies
+                .0
+                .insert(ImportCategory::Providers, ConflictStrategy::Overwrite);
+        }
+        strategies
+    }
+
+    fn app_bundles(&self) -> Result<Vec<(AppType, &AppBundle)>, AppError> {
+        self.apps
+            .iter()
+            .map(|(name, bundle)| Ok((AppType::from_str(name)?, bundle)))
+            .collect()
+    }
+}
+
+impl ConfigService {
+    /// 读取并校验导出文件
+    pub fn read_import_bundle(path: &Path) -> Result<ImportBundle, AppError> {
+        let mut value: Value = crate::config::read_json_file(path)?;
+        let version = migrate_bundle(&mut value)?;
+        let redacted = contains_redacted(&value);
+        let mut bundle: ImportBundle =
+            serde_json::from_value(value).map_err(|e| AppError::json(path, e))?;
+        bundle.app_bundles()?;
+        bundle.version = version;
+        bundle.redacted = redacted;
+        Ok(bundle)
+    }
+
+    /// 读取 cc-switch 桌面 GUI 的数据库（文件或其所在目录），转为导入内容，不修改原库
+    pub async fn read_gui_database(path: &Path) -> Result<ImportBundle, AppError> {
+        let db = Database::open_external(path)?;
+        let value = Self::build_export(&db).await?;
+        let bundle: ImportBundle =
+            serde_json::from_value(value).map_err(|e| AppError::json(path, e))?;
+        bundle.app_bundles()?;
+        Ok(bundle)
+    }
+
+    /// 读取 YAML/TOML 供应商清单，同名供应商沿用现有 ID 并保留清单未涉及的配置
+    pub fn read_provider_roster(state: &AppState, path: &Path) -> Result<ImportBundle, AppError> {
+        let value = provider_roster::read_roster(state, path)?;
+        let mut bundle: ImportBundle =
+            serde_json::from_value(value).map_err(|e| AppError::json(path, e))?;
+        bundle.app_bundles()?;
+        bundle.roster = true;
+        Ok(bundle)
+    }
+
+    /// 按扩展名与文件内容识别供应商清单、导出文件或 GUI 数据库并读取
+    pub async fn read_import_source(
+        state: &AppState,
+        path: &Path,
+    ) -> Result<ImportBundle, AppError> {
+        if provider_roster::is_roster_path(path) {
+            Self::read_provider_roster(state, path)
+        } else if path.is_dir() || database_file_state(path)? == DatabaseFileState::Plaintext {
+            Self::read_gui_database(path).await
+        } else {
+            Self::read_import_bundle(path)
+        }
+    }
+
+    /// 按冲突处理方式生成导入计划（不修改任何数据）
+    pub fn plan_import(
+        state: &AppState,
+        bundle: &ImportBundle,
+        strategies: &ImportStrategies,
+    ) -> Result<Vec<ImportItem>, AppError> {
+        let mut plan = Vec::new();
+
+        for (app, app_bundle) in bundle.app_bundles()? {
+            let existing: HashSet<String> = state
+                .db
+                .get_all_providers(app.as_str())?
+                .into_keys()
+                .collect();
+            let strategy = strategies.get(ImportCategory::Providers);
+            for (id, provider) in &app_bundle.providers {
+                plan.push(ImportItem {
+                    category: ImportCategory::Providers,
+                    app: Some(app.clone()),
+                    id: id.clone(),
+                    name: provider.name.clone(),
+                    action: plan_action(id, &existing, strategy),
+                });
+            }
+
+            let existing: HashSet<String> =
+                state.db.get_prompts(app.as_str())?.into_keys().collect();
+            let strategy = strategies.get(ImportCategory::Prompts);
+            for (id, prompt) in &app_bundle.prompts {
+                plan.push(ImportItem {
+                    category: ImportCategory::Prompts,
+                    app: Some(app.clone()),
+                    id: id.clone(),
+                    name: prompt.name.clone(),
+                    action: plan_action(id, &existing, strategy),
+                });
+            }
+
+            if app_bundle.proxy.is_some()
+                || app_bundle.hybrid_mode.is_some()
+                || app_bundle.proxy_settings.is_some()
+            {
+                plan.push(proxy_item(
+                    Some(app.clone()),
+                    format!("{} proxy", app.as_str()),
+                    strategies,
+                ));
+            }
+        }
+
+        let existing: HashSet<String> = state.db.get_all_mcp_servers()?.into_keys().collect();
+        let strategy = strategies.get(ImportCategory::McpServers);
+        for (id, server) in &bundle.mcp_servers {
+            plan.push(ImportItem {
+                category: ImportCategory::McpServers,
+                app: None,
+                id: id.clone(),
+                name: server.name.clone(),
+                action: plan_action(id, &existing, strategy),
+            });
+        }
+
+        if bundle.proxy.is_some() || bundle.proxy_settings.is_some() {
+            plan.push(proxy_item(None, "global proxy".to_string(), strategies));
+        }
+
+        if !bundle.ui.is_empty() {
+            plan.push(ImportItem {
+                category: ImportCategory::Preferences,
+                app: None,
+                id: "ui".to_string(),
+                name: "theme & key bindings".to_string(),
+                action: single_action(strategies.get(ImportCategory::Preferences)),
+            });
+        }
+
+        Ok(plan)
+    }
+
+    /// 按导入计划写入数据
+    pub async fn apply_import(
+        state: &AppState,
+        bundle: &ImportBundle,
+        plan: &[ImportItem],
+    ) -> Result<ImportSummary, AppError> {
+        let mut summary = ImportSummary::default();
+
+        for item in plan {
+            let new_id = match &item.action {
+                ImportAction::Skip => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                ImportAction::Add => {
+                    summary.added += 1;
+                    item.id.clone()
+                }
+                ImportAction::Overwrite => {
+                    summary.overwritten += 1;
+                    item.id.clone()
+                }
+                ImportAction::Rename(new_id) => {
+                    summary.renamed += 1;
+                    new_id.clone()
+                }
+            };
+            let renamed = matches!(item.action, ImportAction::Rename(_));
+
+            match (item.category, &item.app) {
+                (ImportCategory::Providers, Some(app)) => {
+                    let app_bundle = &bundle.apps[app.as_str()];
+                    let mut provider = app_bundle.providers[&item.id].clone();
+                    provider.id = new_id.clone();
+                    if renamed {
+                        provider.name = format!("{} (imported)", provider.name);
+                    }
+                    if item.action == ImportAction::Overwrite {
+                        // 更新会保留现有的队列状态，覆盖时改为与导入内容一致
+                        let in_queue = provider.in_failover_queue;
+                        ProviderService::update(state, app.clone(), provider)?;
+                        if in_queue {
+                            state.db.add_to_failover_queue(app.as_str(), &new_id)?;
+                        } else if state.db.is_in_failover_queue(app.as_str(), &new_id)? {
+                            state.db.remove_from_failover_queue(app.as_str(), &new_id)?;
+                        }
+                    } else {
+                        ProviderService::add(state, app.clone(), provider)?;
+                    }
+                    import_endpoints(state, app, &new_id, app_bundle.endpoints.get(&item.id))?;
+                }
+                (ImportCategory::Prompts, Some(app)) => {
+                    let mut prompt = bundle.apps[app.as_str()].prompts[&item.id].clone();
+                    // 仅在覆盖已启用的提示词时保持启用，避免导入后同时存在多个启用项
+                    prompt.enabled = item.action == ImportAction::Overwrite
+                        && state
+                            .db
+                            .get_prompts(app.as_str())?
+                            .get(&item.id)
+                            .is_some_and(|p| p.enabled);
+                    prompt.id = new_id.clone();
+                    if renamed {
+                        prompt.name = format!("{} (imported)", prompt.name);
+                    }
+                    PromptService::upsert_prompt(state, app.clone(), &new_id, prompt)?;
+                }
+                (ImportCategory::McpServers, _) => {
+                    let mut server = bundle.mcp_servers[&item.id].clone();
+                    server.id = new_id;
+                    if renamed {
+                        server.name = format!("{} (imported)", server.name);
+                    }
+                    McpService::upsert_server(state, server)?;
+                }
+                (ImportCategory::Proxy, Some(app)) => {
+                    let app_bundle = &bundle.apps[app.as_str()];
+                    if let Some(proxy) = &app_bundle.proxy {
+                        let mut proxy = proxy.clone();
+                        proxy.app_type = app.as_str().to_string();
+                        state.db.update_proxy_config_for_app(proxy).await?;
+                    }
+                    if let Some(hybrid) = &app_bundle.hybrid_mode {
+                        state.db.update_hybrid_mode_config(app.as_str(), hybrid)?;
+                    }
+                    if let Some(settings) = &app_bundle.proxy_settings {
+                        settings.apply(&state.db, app.as_str())?;
+                    }
+                }
+                (ImportCategory::Proxy, None) => {
+                    if let Some(proxy) = &bundle.proxy {
+                        state.db.update_global_proxy_config(proxy.clone()).await?;
+                    }
+                    if let Some(settings) = &bundle.proxy_settings {
+                        settings.apply(&state.db)?;
+                    }
+                }
+                (ImportCategory::Preferences, _) => {
+                    summary.preferences = Some(bundle.ui.clone());
+                }
+                (_, None) => {}
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+fn plan_action(id: &str, existing: &HashSet<String>, strategy: ConflictStrategy) -> ImportAction {
+    if !existing.contains(id) {
+        return ImportAction::Add;
+    }
+    match strategy {
+        ConflictStrategy::Skip => ImportAction::Skip,
+        ConflictStrategy::Overwrite => ImportAction::Overwrite,
+        ConflictStrategy::Rename => {
+            let mut candidate = format!("{id}-imported");
+            let mut n = 2;
+            while existing.contains(&candidate) {
+                candidate = format!("{id}-imported-{n}");
+                n += 1;
+            }
+            ImportAction::Rename(candidate)
+        }
+    }
+}
+
+/// 只有一份、无法另存副本的条目的导入动作
+fn single_action(strategy: ConflictStrategy) -> ImportAction {
+    match strategy {
+        ConflictStrategy::Overwrite => ImportAction::Overwrite,
+        _ => ImportAction::Skip,
+    }
+}
+
+fn proxy_item(app: Option<AppType>, name: String, strategies: &ImportStrategies) -> ImportItem {
+    ImportItem {
+        category: ImportCategory::Proxy,
+        app,
+        id: "proxy".to_string(),
+        name,
+        action: single_action(strategies.get(ImportCategory::Proxy)),
+    }
+}
+
+/// 导出文件格式升级步骤，第 i 项把版本 i+1 的文件升级到 i+2
+const BUNDLE_MIGRATIONS: [fn(&mut Value); (EXPORT_FORMAT_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// v1 没有界面偏好
+fn migrate_v1_to_v2(value: &mut Value) {
+    if let Value::Object(map) = value {
+        map.entry("ui")
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+}
+
+/// v2 的端点没有权重、固定与地区，也没有 settings 表中的代理设置：
+/// 端点补上空值，代理设置缺省时导入不会改动现有值
+fn migrate_v2_to_v3(value: &mut Value) {
+    let Some(apps) = value.get_mut("apps").and_then(Value::as_object_mut) else {
+        return;
+    };
+    let entries = apps
+        .values_mut()
+        .filter_map(|app| app.get_mut("endpoints").and_then(Value::as_object_mut))
+        .flat_map(|endpoints| endpoints.values_mut())
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(Value::as_object_mut);
+    for entry in entries {
+        entry.entry("isPinned").or_insert(Value::Bool(false));
+        entry.entry("weight").or_insert(Value::Null);
+        entry.entry("region").or_insert(Value::Null);
+    }
+}
+
+/// 校验格式版本并原地升级到当前版本，返回文件原本的版本
+fn migrate_bundle(value: &mut Value) -> Result<u32, AppError> {
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .filter(|v| *v >= 1)
+        .ok_or_else(|| AppError::Config("不是有效的导出文件：缺少格式版本".to_string()))?;
+    if version > EXPORT_FORMAT_VERSION as u64 {
+        return Err(AppError::Config(format!(
+            "导出文件版本过新（{version}），当前仅支持 {EXPORT_FORMAT_VERSION}"
+        )));
+    }
+    for migrate in &BUNDLE_MIGRATIONS[version as usize - 1..] {
+        migrate(value);
+    }
+    value["version"] = Value::from(EXPORT_FORMAT_VERSION);
+    Ok(version as u32)
+}
+
+/// 补充导入供应商的端点，已存在的 URL 不重复添加；主端点、固定、权重与地区按导入内容设置
+fn import_endpoints(
+    state: &AppState,
+    app: &AppType,
+    provider_id: &str,
+    endpoints: Option<&Vec<EndpointEntry>>,
+) -> Result<(), AppError> {
+    let Some(endpoints) = endpoints else {
+        return Ok(());
+    };
+    let existing: HashSet<String> = state
+        .db
+        .get_provider_endpoints_with_health(app.as_str(), provider_id)?
+        .into_iter()
+        .map(|e| e.url)
+        .collect();
+
+    for endpoint in endpoints {
+        if !existing.contains(&endpoint.url) {
+            ProviderService::add_custom_endpoint(
+                state,
+                app.clone(),
+                provider_id,
+                endpoint.url.clone(),
+            )?;
+        }
+        if endpoint.is_pinned {
+            ProviderService::set_endpoint_pinned(
+                state,
+                app.clone(),
+                provider_id,
+                endpoint.url.clone(),
+                true,
+            )?;
+        } else if endpoint.is_primary {
+            ProviderService::set_primary_endpoint(
+                state,
+                app.clone(),
+                provider_id,
+                endpoint.url.clone(),
+            )?;
+        }
+        ProviderService::set_endpoint_weight(
+            state,
+            app.clone(),
+            provider_id,
+            endpoint.url.clone(),
+            endpoint.weight,
+        )?;
+        ProviderService::set_endpoint_region(
+            state,
+            app.clone(),
+            provider_id,
+            endpoint.url.clone(),
+            endpoint.region.clone(),
+        )?;
+    }
+    Ok(())
+}
+
+fn contains_redacted(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s == REDACTED_PLACEHOLDER,
+        Value::Array(items) => items.iter().any(contains_redacted),
+        Value::Object(map) => map.values().any(contains_redacted),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_action_follows_strategy_for_conflicts() {
+        let existing: HashSet<String> = ["a", "a-imported"].map(String::from).into();
+
+        assert_eq!(
+            plan_action("b", &existing, ConflictStrategy::Skip),
+            ImportAction::Add
+        );
+        assert_eq!(
+            plan_action("a", &existing, ConflictStrategy::Skip),
+            ImportAction::Skip
+        );
+        assert_eq!(
+            plan_action("a", &existing, ConflictStrategy::Overwrite),
+            ImportAction::Overwrite
+        );
+        assert_eq!(
+            plan_action("a", &existing, ConflictStrategy::Rename),
+            ImportAction::Rename("a-imported-2".to_string())
+        );
+    }
+
+    #[test]
+    fn old_bundles_are_migrated_and_newer_ones_rejected() {
+        let mut v1 = serde_json::json!({ "version": 1, "apps": {} });
+        assert_eq!(migrate_bundle(&mut v1).unwrap(), 1);
+        assert_eq!(v1["version"], EXPORT_FORMAT_VERSION);
+        let bundle: ImportBundle = serde_json::from_value(v1).unwrap();
+        assert!(bundle.ui.is_empty());
+
+        let mut current = serde_json::json!({
+            "version": EXPORT_FORMAT_VERSION,
+            "ui": { "theme": "nord", "keybindings": { "quit": "ctrl+q" } },
+        });
+        migrate_bundle(&mut current).unwrap();
+        let bundle: ImportBundle = serde_json::from_value(current).unwrap();
+        assert_eq!(bundle.ui.theme.as_deref(), Some("nord"));
+        assert_eq!(bundle.ui.keybindings["quit"], "ctrl+q");
+
+        let mut v2 = serde_json::json!({
+            "version": 2,
+            "apps": { "claude": { "endpoints": { "p": [{ "url": "https://a", "isPrimary": true }] } } },
+        });
+        assert_eq!(migrate_bundle(&mut v2).unwrap(), 2);
+        assert_eq!(
+            v2["apps"]["claude"]["endpoints"]["p"][0],
+            serde_json::json!({
+                "url": "https://a", "isPrimary": true, "isPinned": false, "weight": null, "region": null,
+            })
+        );
+        let bundle: ImportBundle = serde_json::from_value(v2).unwrap();
+        assert!(bundle.proxy_settings.is_none());
+
+        let mut newer = serde_json::json!({ "version": EXPORT_FORMAT_VERSION + 1 });
+        assert!(migrate_bundle(&mut newer).is_err());
+        assert!(migrate_bundle(&mut serde_json::json!({ "apps": {} })).is_err());
+    }
+
+    #[test]
+    fn proxy_strategies_do_not_offer_rename() {
+        let mut strategies = ImportStrategies::default();
+        assert_eq!(
+            strategies.cycle(ImportCategory::Proxy),
+            ConflictStrategy::Overwrite
+        );
+        assert_eq!(
+            strategies.cycle(ImportCategory::Proxy),
+            ConflictStrategy::Skip
+        );
+        assert_eq!(
+            strategies.get(ImportCategory::Providers),
+            ConflictStrategy::Skip
+        );
+    }
+}
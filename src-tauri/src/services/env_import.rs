@@ -0,0 +1,239 @@
+//! 从环境变量导入供应商
+//!
+//! 在新服务器上初始化时，读取当前环境（以及可选的 `.env` 文件）中各应用的 API Key 与地址，
+//! 生成候选供应商供确认后创建；配置结构与深链接导入一致
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::deeplink::{build_provider_from_request, DeepLinkImportRequest};
+use crate::error::AppError;
+use crate::store::AppState;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 某个应用对应的环境变量
+struct EnvSpec {
+    app: AppType,
+    /// 按优先级排列的 API Key 变量
+    key_vars: &'static [&'static str],
+    url_var: &'static str,
+    model_var: Option<&'static str>,
+    /// 未设置地址变量时使用的官方地址
+    default_url: &'static str,
+}
+
+const ENV_SPECS: [EnvSpec; 3] = [
+    EnvSpec {
+        app: AppType::Claude,
+        key_vars: &["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"],
+        url_var: "ANTHROPIC_BASE_URL",
+        model_var: Some("ANTHROPIC_MODEL"),
+        default_url: "https://api.anthropic.com",
+    },
+    EnvSpec {
+        app: AppType::Codex,
+        key_vars: &["OPENAI_API_KEY"],
+        url_var: "OPENAI_BASE_URL",
+        model_var: None,
+        default_url: "https://api.openai.com/v1",
+    },
+    EnvSpec {
+        app: AppType::Gemini,
+        key_vars: &["GEMINI_API_KEY"],
+        url_var: "GOOGLE_GEMINI_BASE_URL",
+        model_var: Some("GEMINI_MODEL"),
+        default_url: "https://generativelanguage.googleapis.com",
+    },
+];
+
+/// 从环境变量识别出的候选供应商
+#[derive(Debug, Clone)]
+pub struct EnvProviderCandidate {
+    pub app: AppType,
+    /// 来源：`environment` 或 `.env` 文件路径
+    pub source: String,
+    /// 读取 API Key 的变量名
+    pub key_var: &'static str,
+    pub api_key: String,
+    pub base_url: String,
+    /// 未设置地址变量，使用官方地址
+    pub default_url: bool,
+    pub model: Option<String>,
+    /// 已有相同 Key 与地址的供应商名称
+    pub existing: Option<String>,
+}
+
+impl EnvProviderCandidate {
+    /// 新供应商的名称，如 `api.anthropic.com (env)`
+    pub fn provider_name(&self) -> String {
+        let host = self
+            .base_url
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        format!("{host} (env)")
+    }
+}
+
+/// 环境变量导入相关业务逻辑
+pub struct EnvImportService;
+
+impl EnvImportService {
+    /// 检测当前环境及 `.env` 文件中的凭据，同一应用相同 Key 与地址只保留一项
+    pub fn detect(
+        state: &AppState,
+        dotenv: Option<&Path>,
+    ) -> Result<Vec<EnvProviderCandidate>, AppError> {
+        let mut candidates = candidates_from(&std::env::vars().collect(), "environment");
+        if let Some(path) = dotenv {
+            let content = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+            for candidate in candidates_from(&parse_dotenv(&content), &path.display().to_string()) {
+                let duplicate = candidates.iter().any(|c| {
+                    c.app == candidate.app
+                        && c.api_key == candidate.api_key
+                        && c.base_url == candidate.base_url
+                });
+                if !duplicate {
+                    candidates.push(candidate);
+                }
+            }
+        }
+
+        for candidate in &mut candidates {
+            let providers = state.db.get_all_providers(candidate.app.as_str())?;
+            candidate.existing = providers
+                .values()
+                .find(|provider| {
+                    let (api_key, base_url) =
+                        ProviderService::extract_credentials_lenient(provider, &candidate.app);
+                    api_key == candidate.api_key && normalize_url(&base_url) == candidate.base_url
+                })
+                .map(|provider| provider.name.clone());
+        }
+        Ok(candidates)
+    }
+
+    /// 按候选项创建供应商，返回新供应商 ID
+    pub fn create(state: &AppState, candidate: &EnvProviderCandidate) -> Result<String, AppError> {
+        let request = DeepLinkImportRequest {
+            version: "v1".to_string(),
+            resource: "provider".to_string(),
+            app: Some(candidate.app.as_str().to_string()),
+            name: Some(candidate.provider_name()),
+            endpoint: Some(candidate.base_url.clone()),
+            api_key: Some(candidate.api_key.clone()),
+            model: candidate.model.clone(),
+            notes: Some(format!(
+                "Imported from {} in {}",
+                candidate.key_var, candidate.source
+            )),
+            ..Default::default()
+        };
+        let mut provider = build_provider_from_request(&candidate.app, &request)?;
+        provider.id = uuid::Uuid::new_v4().to_string();
+        provider.created_at = Some(chrono::Utc::now().timestamp());
+
+        let id = provider.id.clone();
+        ProviderService::add(state, candidate.app.clone(), provider)?;
+        Ok(id)
+    }
+}
+
+fn candidates_from(vars: &HashMap<String, String>, source: &str) -> Vec<EnvProviderCandidate> {
+    let get = |name: &str| {
+        vars.get(name)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    ENV_SPECS
+        .iter()
+        .filter_map(|spec| {
+            let (key_var, api_key) = spec
+                .key_vars
+                .iter()
+                .find_map(|var| get(var).map(|key| (*var, key)))?;
+            let base_url = get(spec.url_var).map(|url| normalize_url(&url));
+            Some(EnvProviderCandidate {
+                app: spec.app.clone(),
+                source: source.to_string(),
+                key_var,
+                api_key,
+                default_url: base_url.is_none(),
+                base_url: base_url.unwrap_or_else(|| spec.default_url.to_string()),
+                model: spec.model_var.and_then(get),
+                existing: None,
+            })
+        })
+        .collect()
+}
+
+/// 解析 `.env`：`KEY=VALUE`，支持 `export` 前缀、引号及 `#` 注释
+fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+                _ => value.split(" #").next().unwrap_or_default().trim_end(),
+            };
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dotenv_lines() {
+        let vars = parse_dotenv(
+            "# keys\nexport ANTHROPIC_AUTH_TOKEN=\"sk-ant # not a comment\"\n\
+             OPENAI_API_KEY='sk-openai'\nGEMINI_API_KEY=gm-key # trailing\nBROKEN\n",
+        );
+        assert_eq!(vars["ANTHROPIC_AUTH_TOKEN"], "sk-ant # not a comment");
+        assert_eq!(vars["OPENAI_API_KEY"], "sk-openai");
+        assert_eq!(vars["GEMINI_API_KEY"], "gm-key");
+        assert_eq!(vars.len(), 3);
+    }
+
+    #[test]
+    fn candidates_use_official_url_when_base_url_is_unset() {
+        let vars: HashMap<String, String> = [
+            ("ANTHROPIC_API_KEY", "sk-ant"),
+            ("ANTHROPIC_BASE_URL", "https://relay.example.com/"),
+            ("OPENAI_API_KEY", "sk-openai"),
+            ("GEMINI_API_KEY", " "),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let candidates = candidates_from(&vars, "environment");
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].app, AppType::Claude);
+        assert_eq!(candidates[0].key_var, "ANTHROPIC_API_KEY");
+        assert_eq!(candidates[0].base_url, "https://relay.example.com");
+        assert_eq!(candidates[0].provider_name(), "relay.example.com (env)");
+        assert_eq!(candidates[1].app, AppType::Codex);
+        assert!(candidates[1].default_url);
+        assert_eq!(candidates[1].base_url, "https://api.openai.com/v1");
+    }
+}
@@ -1,5 +1,7 @@
 use indexmap::IndexMap;
+use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
 
 use crate::app_config::{AppType, McpServer};
 use crate::error::AppError;
@@ -83,6 +85,47 @@ impl McpService {
         Ok(())
     }
 
+    /// 批量切换多个服务器在指定应用的启用状态，最后只同步一次 live 配置
+    ///
+    /// 返回状态实际发生变化的服务器数量
+    pub fn toggle_app_bulk(
+        state: &AppState,
+        server_ids: &[String],
+        app: AppType,
+        enabled: bool,
+    ) -> Result<usize, AppError> {
+        let mut servers = state.db.get_all_mcp_servers()?;
+        let mut changed = Vec::new();
+
+        for id in server_ids {
+            let Some(server) = servers.get_mut(id) else {
+                continue;
+            };
+            if server.apps.is_enabled_for(&app) == enabled {
+                continue;
+            }
+            server.apps.set_enabled_for(&app, enabled);
+            state.db.save_mcp_server(server)?;
+            changed.push((id.clone(), server.server.clone()));
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<String> = changed.iter().map(|(id, _)| id.clone()).collect();
+        let (synced, removed) = if enabled {
+            (changed.as_slice(), &[][..])
+        } else {
+            (&[][..], ids.as_slice())
+        };
+        match app {
+            AppType::Claude => mcp::apply_servers_to_claude(synced, removed)?,
+            AppType::Codex => mcp::apply_servers_to_codex(synced, removed)?,
+            AppType::Gemini => mcp::apply_servers_to_gemini(synced, removed)?,
+        }
+        Ok(changed.len())
+    }
+
     /// 将 MCP 服务器同步到所有启用的应用
     fn sync_server_to_apps(_state: &AppState, server: &McpServer) -> Result<(), AppError> {
         for app in server.apps.enabled_apps() {
@@ -311,4 +354,101 @@ impl McpService {
 
         Ok(new_count)
     }
+
+    /// 解析粘贴的 MCP 配置片段，返回 (id, 连接定义) 列表
+    ///
+    /// 支持 `{"mcpServers": {...}}`、以 id 为键的对象，以及缺少最外层花括号的 `"mcpServers": {...}` 片段
+    pub fn parse_servers_snippet(text: &str) -> Result<Vec<(String, Value)>, AppError> {
+        let text = text.trim().trim_end_matches(',');
+        let value: Value = serde_json::from_str(text)
+            .or_else(|e| serde_json::from_str(&format!("{{{text}}}")).map_err(|_| e))
+            .map_err(|e| AppError::McpValidation(format!("Invalid JSON: {e}")))?;
+
+        let map = value
+            .get("mcpServers")
+            .or_else(|| value.get("servers"))
+            .unwrap_or(&value)
+            .as_object()
+            .ok_or_else(|| AppError::McpValidation("Expected a JSON object".to_string()))?;
+        if map.contains_key("command") || map.contains_key("url") {
+            return Err(AppError::McpValidation(
+                "Server definition needs a name, e.g. {\"my-server\": {...}}".to_string(),
+            ));
+        }
+
+        let mut servers = Vec::new();
+        for (id, spec) in map {
+            mcp::validate_server_spec(spec)
+                .map_err(|e| AppError::McpValidation(format!("{id}: {e}")))?;
+            servers.push((id.clone(), spec.clone()));
+        }
+        if servers.is_empty() {
+            return Err(AppError::McpValidation(
+                "No MCP servers found in snippet".to_string(),
+            ));
+        }
+        Ok(servers)
+    }
+
+    /// 校验单个服务器连接定义（stdio 需 command，http/sse 需 url）
+    pub fn validate_server_spec(spec: &Value) -> Result<(), AppError> {
+        mcp::validate_server_spec(spec)
+    }
+
+    /// 生成标准 `{"mcpServers": {...}}` 文档，可直接粘贴到各客户端配置
+    pub fn export_servers_snippet<'a>(servers: impl IntoIterator<Item = &'a McpServer>) -> Value {
+        let map: serde_json::Map<String, Value> = servers
+            .into_iter()
+            .map(|server| (server.id.clone(), server.server.clone()))
+            .collect();
+        serde_json::json!({ "mcpServers": map })
+    }
+
+    /// 将服务器导出为 `mcpServers` JSON 文件
+    pub fn export_servers_to_file<'a>(
+        servers: impl IntoIterator<Item = &'a McpServer>,
+        path: &Path,
+    ) -> Result<(), AppError> {
+        crate::config::write_json_file(path, &Self::export_servers_snippet(servers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_common_snippet_shapes() {
+        let full = r#"{"mcpServers": {"fetch": {"command": "uvx", "args": ["mcp-server-fetch"]}}}"#;
+        let servers = McpService::parse_servers_snippet(full).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].0, "fetch");
+        assert_eq!(servers[0].1["command"], json!("uvx"));
+
+        let bare = r#""docs": {"type": "http", "url": "https://example.com/mcp"},"#;
+        let servers = McpService::parse_servers_snippet(bare).unwrap();
+        assert_eq!(servers[0].0, "docs");
+
+        assert!(McpService::parse_servers_snippet(r#"{"command": "npx"}"#).is_err());
+        assert!(McpService::parse_servers_snippet(r#"{"bad": {"type": "http"}}"#).is_err());
+        assert!(McpService::parse_servers_snippet("not json").is_err());
+    }
+
+    #[test]
+    fn exported_snippet_parses_back() {
+        let server = McpServer {
+            id: "fetch".to_string(),
+            name: "Fetch".to_string(),
+            server: json!({"command": "uvx", "args": ["mcp-server-fetch"]}),
+            apps: Default::default(),
+            description: None,
+            homepage: None,
+            docs: None,
+            tags: Vec::new(),
+        };
+        let snippet = McpService::export_servers_snippet([&server]);
+        let parsed = McpService::parse_servers_snippet(&snippet.to_string()).unwrap();
+        assert_eq!(parsed, vec![("fetch".to_string(), server.server.clone())]);
+    }
 }
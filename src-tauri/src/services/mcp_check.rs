@@ -0,0 +1,616 @@
+//! MCP 服务器连通性检查
+//!
+//! 按服务器定义启动 stdio 进程或连接 HTTP/SSE 地址，完成 MCP initialize 握手并读取工具列表。
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::error::AppError;
+
+/// 握手使用的协议版本
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// 失败时附带的 stderr 最大长度（取末尾）
+const STDERR_TAIL_BYTES: usize = 2000;
+
+/// 最多跟随的 tools/list 分页数
+const MAX_TOOL_PAGES: usize = 20;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// 检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpCheckResult {
+    /// initialize 响应中 serverInfo.name
+    pub server_name: Option<String>,
+    pub tools_count: usize,
+    pub elapsed_ms: u128,
+}
+
+pub struct McpCheckService;
+
+impl McpCheckService {
+    /// 对一个 MCP 服务器定义执行握手检查，整体耗时不超过 `timeout`
+    pub async fn check(spec: &Value, timeout: Duration) -> Result<McpCheckResult, AppError> {
+        let started = Instant::now();
+        let kind = spec.get("type").and_then(Value::as_str).unwrap_or("stdio");
+        let (server_name, tools_count) = match kind {
+            "stdio" => {
+                let spec = spec.clone();
+                tokio::task::spawn_blocking(move || check_stdio(&spec, timeout))
+                    .await
+                    .map_err(|e| AppError::Message(format!("Check task failed: {e}")))??
+            }
+            "http" => tokio::time::timeout(timeout, check_http(spec, timeout))
+                .await
+                .map_err(|_| timeout_error(timeout))??,
+            "sse" => tokio::time::timeout(timeout, check_sse(spec, timeout))
+                .await
+                .map_err(|_| timeout_error(timeout))??,
+            other => {
+                return Err(AppError::McpValidation(format!(
+                    "Unsupported MCP server type: {other}"
+                )))
+            }
+        };
+        Ok(McpCheckResult {
+            server_name,
+            tools_count,
+            elapsed_ms: started.elapsed().as_millis(),
+        })
+    }
+}
+
+fn timeout_error(timeout: Duration) -> AppError {
+    AppError::Message(format!("No response within {}s", timeout.as_secs()))
+}
+
+fn initialize_request() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "cc-switch", "version": env!("CARGO_PKG_VERSION") },
+        },
+    })
+}
+
+fn initialized_notification() -> Value {
+    json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+}
+
+fn tools_list_request(id: u64, cursor: Option<&str>) -> Value {
+    let params = match cursor {
+        Some(cursor) => json!({ "cursor": cursor }),
+        None => json!({}),
+    };
+    json!({ "jsonrpc": "2.0", "id": id, "method": "tools/list", "params": params })
+}
+
+/// 取出 JSON-RPC 响应的 result，错误响应转为 AppError
+fn response_result(response: Value) -> Result<Value, AppError> {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(AppError::Message(format!(
+            "Server returned error: {message}"
+        )));
+    }
+    Ok(response.get("result").cloned().unwrap_or(Value::Null))
+}
+
+fn server_name(initialize_result: &Value) -> Option<String> {
+    initialize_result
+        .pointer("/serverInfo/name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn tools_in_page(result: &Value) -> usize {
+    result
+        .get("tools")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+fn next_cursor(result: &Value) -> Option<String> {
+    result
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+}
+
+// ---------------------------------------------------------------------------
+// stdio
+// ---------------------------------------------------------------------------
+
+/// 子进程的 stdin 及按行读取的 stdout
+struct StdioSession {
+    child: Child,
+    stdin: ChildStdin,
+    lines: mpsc::Receiver<String>,
+    deadline: Instant,
+}
+
+impl StdioSession {
+    fn send(&mut self, message: &Value) -> Result<(), AppError> {
+        writeln!(self.stdin, "{message}")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| AppError::Message(format!("Failed to write to server stdin: {e}")))
+    }
+
+    /// 读取 stdout 直到出现指定 id 的响应，跳过日志行与通知
+    fn wait_response(&mut self, id: u64) -> Result<Value, AppError> {
+        loop {
+            let remaining = self.deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    return Err(AppError::Message("No response before timeout".to_string()))
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    let status = self
+                        .child
+                        .wait()
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|_| "unknown status".to_string());
+                    return Err(AppError::Message(format!("Server exited ({status})")));
+                }
+            };
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+
+    fn request(&mut self, message: Value) -> Result<Value, AppError> {
+        let id = message
+            .get("id")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        self.send(&message)?;
+        self.wait_response(id)
+    }
+}
+
+fn check_stdio(spec: &Value, timeout: Duration) -> Result<(Option<String>, usize), AppError> {
+    let command = spec
+        .get("command")
+        .and_then(Value::as_str)
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| AppError::McpValidation("stdio server is missing command".to_string()))?;
+    let args: Vec<&str> = spec
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| args.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let env: HashMap<&str, &str> = spec
+        .get("env")
+        .and_then(Value::as_object)
+        .map(|env| {
+            env.iter()
+                .filter_map(|(k, v)| Some((k.as_str(), v.as_str()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut cmd = Command::new(command);
+    cmd.args(&args)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = spec.get("cwd").and_then(Value::as_str) {
+        cmd.current_dir(cwd);
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| AppError::Message(format!("Failed to start {command}: {e}")))?;
+
+    let stderr = Arc::new(Mutex::new(String::new()));
+    if let Some(mut pipe) = child.stderr.take() {
+        let stderr = stderr.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = pipe.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                let mut captured = stderr.lock().unwrap_or_else(|e| e.into_inner());
+                captured.push_str(&String::from_utf8_lossy(&buf[..n]));
+                if captured.len() > STDERR_TAIL_BYTES * 2 {
+                    let cut = captured.len() - STDERR_TAIL_BYTES;
+                    let cut = (cut..captured.len())
+                        .find(|i| captured.is_char_boundary(*i))
+                        .unwrap_or(cut);
+                    captured.drain(..cut);
+                }
+            }
+        });
+    }
+
+    let (tx, lines) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let Some(stdin) = child.stdin.take() else {
+        let _ = child.kill();
+        return Err(AppError::Message("Failed to open server stdin".to_string()));
+    };
+    let mut session = StdioSession {
+        child,
+        stdin,
+        lines,
+        deadline: Instant::now() + timeout,
+    };
+
+    let result = run_stdio_handshake(&mut session);
+    let _ = session.child.kill();
+    let _ = session.child.wait();
+
+    result.map_err(|e| {
+        // 给 stderr 读取线程一点时间收尾
+        std::thread::sleep(Duration::from_millis(50));
+        let captured = stderr.lock().unwrap_or_else(|e| e.into_inner());
+        let tail = stderr_tail(&captured);
+        if tail.is_empty() {
+            e
+        } else {
+            AppError::Message(format!("{e}; stderr: {tail}"))
+        }
+    })
+}
+
+fn run_stdio_handshake(session: &mut StdioSession) -> Result<(Option<String>, usize), AppError> {
+    let initialized = response_result(session.request(initialize_request())?)?;
+    session.send(&initialized_notification())?;
+
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    for page in 0..MAX_TOOL_PAGES {
+        let id = 2 + page as u64;
+        let result = response_result(session.request(tools_list_request(id, cursor.as_deref()))?)?;
+        count += tools_in_page(&result);
+        cursor = next_cursor(&result);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok((server_name(&initialized), count))
+}
+
+/// stderr 末尾的非空内容，合并为单行
+fn stderr_tail(captured: &str) -> String {
+    let joined = captured
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let start = joined.len().saturating_sub(STDERR_TAIL_BYTES);
+    let start = (start..=joined.len())
+        .find(|i| joined.is_char_boundary(*i))
+        .unwrap_or(0);
+    joined[start..].to_string()
+}
+
+// ---------------------------------------------------------------------------
+// HTTP / SSE
+// ---------------------------------------------------------------------------
+
+fn spec_url(spec: &Value) -> Result<String, AppError> {
+    spec.get("url")
+        .and_then(Value::as_str)
+        .filter(|u| !u.trim().is_empty())
+        .map(|u| u.trim().to_string())
+        .ok_or_else(|| AppError::McpValidation("Server is missing url".to_string()))
+}
+
+fn spec_headers(spec: &Value) -> Result<HeaderMap, AppError> {
+    let mut headers = HeaderMap::new();
+    let Some(map) = spec.get("headers").and_then(Value::as_object) else {
+        return Ok(headers);
+    };
+    for (name, value) in map {
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| AppError::McpValidation(format!("Invalid header {name}: {e}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| AppError::McpValidation(format!("Invalid header value: {e}")))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+fn http_client(timeout: Duration) -> Result<Client, AppError> {
+    Client::builder()
+        .connect_timeout(timeout)
+        .build()
+        .map_err(|e| AppError::Message(format!("Failed to create HTTP client: {e}")))
+}
+
+fn request_error(e: reqwest::Error) -> AppError {
+    AppError::Message(format!("Request failed: {e}"))
+}
+
+/// 非 2xx 响应转为错误，附带响应体开头
+async fn ensure_success(response: reqwest::Response) -> Result<reqwest::Response, AppError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let snippet: String = body.chars().take(200).collect();
+    Err(AppError::Message(format!(
+        "HTTP {status}: {}",
+        snippet.trim()
+    )))
+}
+
+/// Streamable HTTP 传输：每个请求单独 POST，响应为 JSON 或 SSE 流
+async fn check_http(spec: &Value, timeout: Duration) -> Result<(Option<String>, usize), AppError> {
+    let url = spec_url(spec)?;
+    let client = http_client(timeout)?;
+    let mut headers = spec_headers(spec)?;
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("application/json, text/event-stream"),
+    );
+
+    let response = post_json(&client, &url, &headers, &initialize_request()).await?;
+    if let Some(session) = response.headers().get(SESSION_HEADER).cloned() {
+        headers.insert(SESSION_HEADER, session);
+    }
+    let initialized = response_result(read_http_response(response, 1).await?)?;
+
+    post_json(&client, &url, &headers, &initialized_notification()).await?;
+
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    for page in 0..MAX_TOOL_PAGES {
+        let id = 2 + page as u64;
+        let request = tools_list_request(id, cursor.as_deref());
+        let response = post_json(&client, &url, &headers, &request).await?;
+        let result = response_result(read_http_response(response, id).await?)?;
+        count += tools_in_page(&result);
+        cursor = next_cursor(&result);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok((server_name(&initialized), count))
+}
+
+async fn post_json(
+    client: &Client,
+    url: &str,
+    headers: &HeaderMap,
+    body: &Value,
+) -> Result<reqwest::Response, AppError> {
+    let response = client
+        .post(url)
+        .headers(headers.clone())
+        .header(CONTENT_TYPE, "application/json")
+        .json(body)
+        .send()
+        .await
+        .map_err(request_error)?;
+    ensure_success(response).await
+}
+
+async fn read_http_response(response: reqwest::Response, id: u64) -> Result<Value, AppError> {
+    let is_sse = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_sse {
+        return response
+            .json()
+            .await
+            .map_err(|e| AppError::Message(format!("Invalid JSON response: {e}")));
+    }
+
+    let mut events = SseReader::new(response.bytes_stream());
+    while let Some((_, data)) = events.next_event().await? {
+        if let Some(message) = match_response(&data, id) {
+            return Ok(message);
+        }
+    }
+    Err(AppError::Message(
+        "Stream ended without a response".to_string(),
+    ))
+}
+
+/// 旧版 SSE 传输：GET 建立事件流，从 endpoint 事件取得 POST 地址，响应经事件流返回
+async fn check_sse(spec: &Value, timeout: Duration) -> Result<(Option<String>, usize), AppError> {
+    let url = spec_url(spec)?;
+    let client = http_client(timeout)?;
+    let headers = spec_headers(spec)?;
+
+    let response = client
+        .get(&url)
+        .headers(headers.clone())
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .await
+        .map_err(request_error)?;
+    let mut events = SseReader::new(ensure_success(response).await?.bytes_stream());
+
+    let endpoint = loop {
+        match events.next_event().await? {
+            Some((event, data)) if event == "endpoint" => break data,
+            Some(_) => continue,
+            None => {
+                return Err(AppError::Message(
+                    "Stream ended before endpoint event".to_string(),
+                ))
+            }
+        }
+    };
+    let endpoint = url::Url::parse(&url)
+        .and_then(|base| base.join(endpoint.trim()))
+        .map_err(|e| AppError::Message(format!("Invalid endpoint URL: {e}")))?
+        .to_string();
+
+    post_json(&client, &endpoint, &headers, &initialize_request()).await?;
+    let initialized = response_result(wait_sse_response(&mut events, 1).await?)?;
+    post_json(&client, &endpoint, &headers, &initialized_notification()).await?;
+
+    let mut count = 0;
+    let mut cursor: Option<String> = None;
+    for page in 0..MAX_TOOL_PAGES {
+        let id = 2 + page as u64;
+        let request = tools_list_request(id, cursor.as_deref());
+        post_json(&client, &endpoint, &headers, &request).await?;
+        let result = response_result(wait_sse_response(&mut events, id).await?)?;
+        count += tools_in_page(&result);
+        cursor = next_cursor(&result);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok((server_name(&initialized), count))
+}
+
+async fn wait_sse_response<S>(events: &mut SseReader<S>, id: u64) -> Result<Value, AppError>
+where
+    S: Stream<Item = reqwest::Result<bytes::Bytes>> + Unpin,
+{
+    while let Some((_, data)) = events.next_event().await? {
+        if let Some(message) = match_response(&data, id) {
+            return Ok(message);
+        }
+    }
+    Err(AppError::Message(
+        "Stream ended without a response".to_string(),
+    ))
+}
+
+fn match_response(data: &str, id: u64) -> Option<Value> {
+    let message: Value = serde_json::from_str(data).ok()?;
+    (message.get("id").and_then(Value::as_u64) == Some(id)).then_some(message)
+}
+
+/// 增量解析 SSE 字节流，逐个返回 (event, data)
+struct SseReader<S> {
+    stream: S,
+    buffer: String,
+}
+
+impl<S> SseReader<S>
+where
+    S: Stream<Item = reqwest::Result<bytes::Bytes>> + Unpin,
+{
+    fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buffer: String::new(),
+        }
+    }
+
+    async fn next_event(&mut self) -> Result<Option<(String, String)>, AppError> {
+        loop {
+            if let Some(event) = take_sse_event(&mut self.buffer) {
+                return Ok(Some(event));
+            }
+            match self.stream.next().await {
+                Some(Ok(chunk)) => {
+                    self.buffer
+                        .push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+                }
+                Some(Err(e)) => return Err(request_error(e)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// 从缓冲区取出一个完整事件（以空行结束），事件名缺省为 "message"
+fn take_sse_event(buffer: &mut String) -> Option<(String, String)> {
+    let end = buffer.find("\n\n")?;
+    let block: String = buffer.drain(..end + 2).collect();
+
+    let mut event = "message".to_string();
+    let mut data = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    Some((event, data.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_events_are_split_on_blank_lines() {
+        let mut buffer =
+            "event: endpoint\ndata: /messages?session=1\n\ndata: {\"id\":1}\n\ndata: partial"
+                .to_string();
+        assert_eq!(
+            take_sse_event(&mut buffer),
+            Some(("endpoint".to_string(), "/messages?session=1".to_string()))
+        );
+        let (event, data) = take_sse_event(&mut buffer).unwrap();
+        assert_eq!(event, "message");
+        assert_eq!(match_response(&data, 1), Some(json!({"id": 1})));
+        assert_eq!(take_sse_event(&mut buffer), None);
+        assert_eq!(buffer, "data: partial");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_check_counts_tools_and_reports_stderr() {
+        let script = r#"read line
+echo 'starting up'
+echo '{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"fake"}}}'
+read line
+read line
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"a"},{"name":"b"}]}}'"#;
+        let spec = json!({ "command": "sh", "args": ["-c", script] });
+        let result = McpCheckService::check(&spec, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(result.server_name.as_deref(), Some("fake"));
+        assert_eq!(result.tools_count, 2);
+
+        let spec = json!({ "type": "stdio", "command": "sh", "args": ["-c", "echo 'missing API key' >&2; exit 3"] });
+        let err = McpCheckService::check(&spec, Duration::from_secs(5))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing API key"), "{err}");
+    }
+}
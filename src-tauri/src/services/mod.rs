@@ -1,9 +1,14 @@
+pub mod backup;
 pub mod config;
+pub mod config_import;
 pub mod env_checker;
+pub mod env_import;
 pub mod env_manager;
 pub mod mcp;
+pub mod mcp_check;
 pub mod prompt;
 pub mod provider;
+mod provider_roster;
 pub mod proxy;
 pub mod skill;
 pub mod speedtest;
@@ -11,20 +16,29 @@ pub mod stream_check;
 pub mod url_latency;
 pub mod usage_stats;
 
-pub use config::ConfigService;
+pub use backup::BackupScheduler;
+pub use config::{ConfigService, UiPreferences, EXPORT_FORMAT_VERSION};
+pub use config_import::{
+    ConflictStrategy, ImportAction, ImportBundle, ImportCategory, ImportItem, ImportStrategies,
+    ImportSummary,
+};
+pub use env_import::{EnvImportService, EnvProviderCandidate};
 pub use mcp::McpService;
+pub use mcp_check::{McpCheckResult, McpCheckService};
 pub use prompt::PromptService;
-pub use provider::ProviderService;
 #[allow(unused_imports)]
 pub use provider::ProviderSortUpdate;
+pub use provider::{LiveFileChange, ProviderService};
 pub use proxy::ProxyService;
 #[allow(unused_imports)]
 pub use skill::{DiscoverableSkill, Skill, SkillRepo, SkillService};
-pub use speedtest::{EndpointLatency, SpeedtestService};
+pub use speedtest::{
+    EndpointLatency, SpeedtestMethod, SpeedtestOptions, SpeedtestProbe, SpeedtestService,
+};
 #[allow(unused_imports)]
 pub use url_latency::UrlLatencyService;
 #[allow(unused_imports)]
 pub use usage_stats::{
-    DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus, ProviderStats,
-    RequestLogDetail, UsageSummary,
+    DailyStats, DailyUsage, LogFilters, ModelPrice, ModelStats, ModelUsage, PaginatedLogs,
+    ProviderLimitStatus, ProviderStats, ProviderUsage, RequestLogDetail, UsageSummary,
 };
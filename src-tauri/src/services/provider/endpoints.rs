@@ -67,6 +67,71 @@ pub fn remove_custom_endpoint(
     Ok(())
 }
 
+/// Mark a custom endpoint as the provider's primary endpoint
+pub fn set_primary_endpoint(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: String,
+) -> Result<(), AppError> {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    state
+        .db
+        .set_primary_endpoint(app_type.as_str(), provider_id, &normalized)?;
+    Ok(())
+}
+
+/// Pin or unpin the provider's primary endpoint so latency tests leave it alone
+pub fn set_endpoint_pinned(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: String,
+    pinned: bool,
+) -> Result<(), AppError> {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    state
+        .db
+        .set_endpoint_pinned(app_type.as_str(), provider_id, &normalized, pinned)?;
+    Ok(())
+}
+
+/// Set the endpoint's share of traffic under the weighted URL strategy
+pub fn set_endpoint_weight(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: String,
+    weight: Option<u32>,
+) -> Result<(), AppError> {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    state
+        .db
+        .set_endpoint_weight(app_type.as_str(), provider_id, &normalized, weight)?;
+    Ok(())
+}
+
+/// Tag the endpoint with a region label used by the region routing preference
+pub fn set_endpoint_region(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: String,
+    region: Option<String>,
+) -> Result<(), AppError> {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    let region = region
+        .map(|r| r.trim().to_lowercase())
+        .filter(|r| !r.is_empty());
+    state.db.set_endpoint_region(
+        app_type.as_str(),
+        provider_id,
+        &normalized,
+        region.as_deref(),
+    )?;
+    Ok(())
+}
+
 /// Update endpoint last used timestamp
 pub fn update_endpoint_last_used(
     state: &AppState,
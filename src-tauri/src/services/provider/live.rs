@@ -3,6 +3,7 @@
 //! Handles reading and writing live configuration files for Claude, Codex, and Gemini.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use serde_json::{json, Value};
 
@@ -104,8 +105,77 @@ pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Re
     }
 }
 
+/// A single live config file change produced by switching providers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveFileChange {
+    pub path: PathBuf,
+    /// Current file content, `None` if the file does not exist
+    pub before: Option<String>,
+    /// Content that would be written
+    pub after: String,
+}
+
+impl LiveFileChange {
+    fn new(path: PathBuf, after: String) -> Self {
+        let before = std::fs::read_to_string(&path).ok();
+        Self {
+            path,
+            before,
+            after,
+        }
+    }
+
+    /// Whether writing would change the file
+    pub fn is_changed(&self) -> bool {
+        self.before.as_deref() != Some(self.after.as_str())
+    }
+}
+
+/// Compute the live file changes `write_live_snapshot` would make, without writing anything
+pub fn preview_live_snapshot(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Vec<LiveFileChange>, AppError> {
+    let pretty = |value: &Value| {
+        serde_json::to_string_pretty(value).map_err(|e| AppError::JsonSerialize { source: e })
+    };
+    match app_type {
+        AppType::Claude => Ok(vec![LiveFileChange::new(
+            get_claude_settings_path(),
+            pretty(&render_claude_live(provider))?,
+        )]),
+        AppType::Codex => {
+            let (auth, config) = render_codex_live(provider)?;
+            Ok(vec![
+                LiveFileChange::new(get_codex_auth_path(), pretty(&auth)?),
+                LiveFileChange::new(get_codex_config_path(), config),
+            ])
+        }
+        AppType::Gemini => {
+            use crate::gemini_config::{
+                apply_selected_type, get_gemini_env_path, get_gemini_settings_path,
+                serialize_env_file,
+            };
+
+            let auth_type = detect_gemini_auth_type(provider);
+            let (env_map, settings) = render_gemini_live(provider, auth_type)?;
+            let mut settings = settings.unwrap_or_else(|| json!({}));
+            apply_selected_type(&mut settings, gemini_selected_type(auth_type));
+            Ok(vec![
+                LiveFileChange::new(get_gemini_env_path(), serialize_env_file(&env_map)),
+                LiveFileChange::new(get_gemini_settings_path(), pretty(&settings)?),
+            ])
+        }
+    }
+}
+
 /// Write Claude live configuration, merging only credential fields
 fn write_claude_live(provider: &Provider) -> Result<(), AppError> {
+    write_json_file(&get_claude_settings_path(), &render_claude_live(provider))
+}
+
+/// Merge provider credential fields into the current Claude settings
+fn render_claude_live(provider: &Provider) -> Value {
     const CRED_KEYS: &[&str] = &[
         "ANTHROPIC_AUTH_TOKEN",
         "ANTHROPIC_API_KEY",
@@ -135,11 +205,19 @@ fn write_claude_live(provider: &Provider) -> Result<(), AppError> {
             live_env.remove(*key);
         }
     }
-    write_json_file(&path, &live)
+    live
 }
 
 /// Write Codex live configuration, merging only credential fields
 fn write_codex_live(provider: &Provider) -> Result<(), AppError> {
+    let (auth, config) = render_codex_live(provider)?;
+    write_json_file(&get_codex_auth_path(), &auth)?;
+    let config_path = get_codex_config_path();
+    std::fs::write(&config_path, &config).map_err(|e| AppError::io(&config_path, e))
+}
+
+/// Merge provider credential fields into the current Codex auth.json and config.toml
+fn render_codex_live(provider: &Provider) -> Result<(Value, String), AppError> {
     let obj = provider
         .settings_config
         .as_object()
@@ -167,7 +245,6 @@ fn write_codex_live(provider: &Provider) -> Result<(), AppError> {
             live_obj.remove("OPENAI_API_KEY");
         }
     }
-    write_json_file(&auth_path, &live_auth)?;
 
     // Merge config.toml - only base_url
     let config_path = get_codex_config_path();
@@ -195,7 +272,7 @@ fn write_codex_live(provider: &Provider) -> Result<(), AppError> {
     } else if base_url_re.is_match(&live_config) {
         live_config = base_url_re.replace(&live_config, "").into();
     }
-    std::fs::write(&config_path, &live_config).map_err(|e| AppError::io(&config_path, e))
+    Ok((live_auth, live_config))
 }
 
 /// Sync current provider to live configuration
@@ -381,12 +458,62 @@ pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool
 /// Write Gemini live configuration with authentication handling
 pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
     use crate::gemini_config::{
-        get_gemini_settings_path, json_to_env, validate_gemini_settings_strict,
-        write_gemini_env_atomic,
+        get_gemini_settings_path, validate_gemini_settings_strict, write_gemini_env_atomic,
     };
 
     // One-time auth type detection to avoid repeated detection
     let auth_type = detect_gemini_auth_type(provider);
+    let (env_map, config_to_write) = render_gemini_live(provider, auth_type)?;
+
+    match auth_type {
+        GeminiAuthType::GoogleOfficial => {
+            // Google official uses OAuth, env already cleared
+            write_gemini_env_atomic(&env_map)?;
+        }
+        GeminiAuthType::Packycode => {
+            // PackyCode provider, uses API Key (strict validation on switch)
+            validate_gemini_settings_strict(&provider.settings_config)?;
+            write_gemini_env_atomic(&env_map)?;
+        }
+        GeminiAuthType::Generic => {
+            // Generic provider, uses API Key (strict validation on switch)
+            validate_gemini_settings_strict(&provider.settings_config)?;
+            write_gemini_env_atomic(&env_map)?;
+        }
+    }
+
+    if let Some(config_value) = config_to_write {
+        write_json_file(&get_gemini_settings_path(), &config_value)?;
+    }
+
+    // Set security.auth.selectedType based on auth type
+    // - Google Official: OAuth mode
+    // - All others: API Key mode
+    match auth_type {
+        GeminiAuthType::GoogleOfficial => ensure_google_oauth_security_flag(provider)?,
+        GeminiAuthType::Packycode | GeminiAuthType::Generic => {
+            crate::gemini_config::write_packycode_settings()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `security.auth.selectedType` value written for each auth type
+fn gemini_selected_type(auth_type: GeminiAuthType) -> &'static str {
+    match auth_type {
+        GeminiAuthType::GoogleOfficial => "oauth-personal",
+        GeminiAuthType::Packycode | GeminiAuthType::Generic => "gemini-api-key",
+    }
+}
+
+/// Compute the Gemini `.env` map and `settings.json` content to write
+/// (without `security.auth.selectedType`); `None` leaves settings.json untouched
+fn render_gemini_live(
+    provider: &Provider,
+    auth_type: GeminiAuthType,
+) -> Result<(HashMap<String, String>, Option<Value>), AppError> {
+    use crate::gemini_config::{get_gemini_settings_path, json_to_env};
 
     // Read existing env first, then merge provider's env on top
     // This preserves user presets like GEMINI_MODEL that aren't in provider config
@@ -436,37 +563,10 @@ pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
         config_to_write = Some(read_json_file(&settings_path)?);
     }
 
-    match auth_type {
-        GeminiAuthType::GoogleOfficial => {
-            // Google official uses OAuth, clear env
-            env_map.clear();
-            write_gemini_env_atomic(&env_map)?;
-        }
-        GeminiAuthType::Packycode => {
-            // PackyCode provider, uses API Key (strict validation on switch)
-            validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
-        }
-        GeminiAuthType::Generic => {
-            // Generic provider, uses API Key (strict validation on switch)
-            validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
-        }
-    }
-
-    if let Some(config_value) = config_to_write {
-        write_json_file(&settings_path, &config_value)?;
+    // Google official uses OAuth, clear env
+    if auth_type == GeminiAuthType::GoogleOfficial {
+        env_map.clear();
     }
 
-    // Set security.auth.selectedType based on auth type
-    // - Google Official: OAuth mode
-    // - All others: API Key mode
-    match auth_type {
-        GeminiAuthType::GoogleOfficial => ensure_google_oauth_security_flag(provider)?,
-        GeminiAuthType::Packycode | GeminiAuthType::Generic => {
-            crate::gemini_config::write_packycode_settings()?;
-        }
-    }
-
-    Ok(())
+    Ok((env_map, config_to_write))
 }
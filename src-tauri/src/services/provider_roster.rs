@@ -0,0 +1,285 @@
+//! 供应商清单导入
+//!
+//! 团队共享的 YAML/TOML 清单只列出名称、Key、地址、分类与是否加入故障转移队列，
+//! 按应用与名称匹配现有供应商：匹配到的只改写清单中给出的字段，其余新建。
+//! 清单会转换为导出文件格式，复用配置导入的预览与冲突处理流程。
+//!
+//! ```yaml
+//! providers:
+//!   - app: claude
+//!     name: Team relay
+//!     key: sk-xxx
+//!     urls: [https://relay.example.com, https://backup.example.com]
+//!     category: third_party
+//!     failover: true
+//! ```
+
+use super::config::EXPORT_FORMAT_VERSION;
+use super::ProxyService;
+use crate::app_config::AppType;
+use crate::deeplink::{build_provider_from_request, DeepLinkImportRequest};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProviderRoster {
+    #[serde(default)]
+    providers: Vec<RosterEntry>,
+}
+
+/// 清单中的一个供应商；更新现有供应商时未填写的字段保持不变
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RosterEntry {
+    app: AppType,
+    name: String,
+    #[serde(default)]
+    key: Option<String>,
+    /// 第一个为主地址，其余作为自定义端点
+    #[serde(default)]
+    urls: Vec<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    failover: Option<bool>,
+}
+
+/// 按扩展名判断是否为供应商清单（`.yaml`、`.yml`、`.toml`）
+pub(super) fn is_roster_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "yaml" | "yml" | "toml"))
+}
+
+/// 读取清单并转换为导出文件格式，匹配到的现有供应商沿用其 ID
+pub(super) fn read_roster(state: &AppState, path: &Path) -> Result<Value, AppError> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+    let roster = parse_roster(path, &content)?;
+
+    let mut existing: HashMap<String, IndexMap<String, Provider>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut apps = Map::new();
+    for entry in &roster.providers {
+        let app = entry.app.as_str();
+        let name = entry.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Config("清单中有供应商未填写名称".to_string()));
+        }
+        if !seen.insert((app, name)) {
+            return Err(AppError::Config(format!("清单中 {app} 供应商 {name} 重复")));
+        }
+        let urls: Vec<String> = entry
+            .urls
+            .iter()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        if !existing.contains_key(app) {
+            existing.insert(app.to_string(), state.db.get_all_providers(app)?);
+        }
+        let provider = match existing[app].values().find(|p| p.name.trim() == name) {
+            Some(current) => update_provider(current.clone(), entry, &urls),
+            None => new_provider(entry, name, &urls)?,
+        };
+
+        let bundle = apps
+            .entry(app)
+            .or_insert_with(|| json!({ "providers": {}, "endpoints": {} }));
+        if !urls.is_empty() {
+            let endpoints: Vec<Value> = urls
+                .iter()
+                .enumerate()
+                .map(|(i, url)| json!({ "url": url, "isPrimary": i == 0 }))
+                .collect();
+            bundle["endpoints"][&provider.id] = Value::Array(endpoints);
+        }
+        bundle["providers"][&provider.id] =
+            serde_json::to_value(&provider).map_err(|e| AppError::JsonSerialize { source: e })?;
+    }
+
+    Ok(json!({ "version": EXPORT_FORMAT_VERSION, "apps": apps }))
+}
+
+fn parse_roster(path: &Path, content: &str) -> Result<ProviderRoster, AppError> {
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        toml::from_str(content).map_err(|e| AppError::toml(path, e))
+    } else {
+        serde_yaml::from_str(content)
+            .map_err(|e| AppError::Config(format!("解析 {} 失败: {e}", path.display())))
+    }
+}
+
+fn new_provider(entry: &RosterEntry, name: &str, urls: &[String]) -> Result<Provider, AppError> {
+    let key = entry
+        .key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty());
+    let (Some(key), Some(url)) = (key, urls.first()) else {
+        return Err(AppError::Config(format!(
+            "新供应商 {name} 需要填写 key 与 urls"
+        )));
+    };
+
+    let request = DeepLinkImportRequest {
+        version: "v1".to_string(),
+        resource: "provider".to_string(),
+        app: Some(entry.app.as_str().to_string()),
+        name: Some(name.to_string()),
+        endpoint: Some(url.clone()),
+        api_key: Some(key.to_string()),
+        notes: Some("Imported from provider roster".to_string()),
+        ..Default::default()
+    };
+    let mut provider = build_provider_from_request(&entry.app, &request)?;
+    provider.id = uuid::Uuid::new_v4().to_string();
+    provider.created_at = Some(chrono::Utc::now().timestamp());
+    provider.category = entry.category.clone();
+    provider.in_failover_queue = entry.failover.unwrap_or(false);
+    Ok(provider)
+}
+
+fn update_provider(mut provider: Provider, entry: &RosterEntry, urls: &[String]) -> Provider {
+    let key = entry
+        .key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty());
+    apply_credentials(
+        &mut provider.settings_config,
+        &entry.app,
+        key,
+        urls.first().map(String::as_str),
+    );
+    if entry.category.is_some() {
+        provider.category = entry.category.clone();
+    }
+    if let Some(failover) = entry.failover {
+        provider.in_failover_queue = failover;
+    }
+    provider
+}
+
+/// 在现有配置中改写 API Key 与主地址，其它配置保持不变
+fn apply_credentials(
+    settings: &mut Value,
+    app: &AppType,
+    key: Option<&str>,
+    base_url: Option<&str>,
+) {
+    if !settings.is_object() {
+        *settings = json!({});
+    }
+    match app {
+        AppType::Claude | AppType::Gemini => {
+            let env = settings
+                .as_object_mut()
+                .map(|s| s.entry("env").or_insert_with(|| json!({})))
+                .and_then(Value::as_object_mut);
+            let Some(env) = env else {
+                return;
+            };
+            let (key_var, url_var) = match app {
+                // 沿用现有的 Key 变量名
+                AppType::Claude if env.contains_key("ANTHROPIC_API_KEY") => {
+                    ("ANTHROPIC_API_KEY", "ANTHROPIC_BASE_URL")
+                }
+                AppType::Claude => ("ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_BASE_URL"),
+                _ => ("GEMINI_API_KEY", "GOOGLE_GEMINI_BASE_URL"),
+            };
+            if let Some(key) = key {
+                env.insert(key_var.to_string(), json!(key));
+            }
+            if let Some(url) = base_url {
+                env.insert(url_var.to_string(), json!(url));
+            }
+        }
+        AppType::Codex => {
+            if let Some(key) = key {
+                if !settings["auth"].is_object() {
+                    settings["auth"] = json!({});
+                }
+                settings["auth"]["OPENAI_API_KEY"] = json!(key);
+            }
+            if let Some(url) = base_url {
+                let config = settings["config"].as_str().unwrap_or_default();
+                settings["config"] = json!(ProxyService::update_toml_base_url(config, url));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_yaml_and_toml_rosters() {
+        let yaml = parse_roster(
+            Path::new("team.yaml"),
+            "providers:\n  - app: claude\n    name: Relay\n    key: sk-a\n    \
+             urls: [https://a.example.com/]\n    failover: true\n",
+        )
+        .unwrap();
+        let toml = parse_roster(
+            Path::new("team.TOML"),
+            "[[providers]]\napp = \"codex\"\nname = \"Relay\"\ncategory = \"custom\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(yaml.providers[0].app, AppType::Claude);
+        assert_eq!(yaml.providers[0].failover, Some(true));
+        assert_eq!(toml.providers[0].app, AppType::Codex);
+        assert!(toml.providers[0].key.is_none());
+        assert!(parse_roster(
+            Path::new("team.yml"),
+            "providers:\n  - app: claude\n    name: x\n    token: y\n"
+        )
+        .is_err());
+        assert!(is_roster_path(Path::new("team.yml")));
+        assert!(!is_roster_path(Path::new("export.json")));
+    }
+
+    #[test]
+    fn credentials_are_patched_in_place() {
+        let mut claude = json!({ "env": { "ANTHROPIC_API_KEY": "old", "KEEP": "1" } });
+        apply_credentials(
+            &mut claude,
+            &AppType::Claude,
+            Some("new"),
+            Some("https://b.example.com"),
+        );
+        assert_eq!(claude["env"]["ANTHROPIC_API_KEY"], "new");
+        assert_eq!(claude["env"]["ANTHROPIC_BASE_URL"], "https://b.example.com");
+        assert_eq!(claude["env"]["KEEP"], "1");
+        assert!(claude["env"].get("ANTHROPIC_AUTH_TOKEN").is_none());
+
+        let mut codex = json!({
+            "auth": { "OPENAI_API_KEY": "old" },
+            "config": "model_provider = \"relay\"\n\n[model_providers.relay]\nbase_url = \"https://a.example.com/v1\"\n",
+        });
+        apply_credentials(
+            &mut codex,
+            &AppType::Codex,
+            None,
+            Some("https://b.example.com/v1"),
+        );
+        assert_eq!(codex["auth"]["OPENAI_API_KEY"], "old");
+        assert!(codex["config"]
+            .as_str()
+            .unwrap()
+            .contains("base_url = \"https://b.example.com/v1\""));
+    }
+}
@@ -6,9 +6,20 @@ use crate::app_config::AppType;
 use crate::config::{get_claude_settings_path, read_json_file, write_json_file};
 use crate::database::Database;
 use crate::provider::Provider;
+use crate::proxy::access_log::{AccessLog, AccessLogConfig};
+use crate::proxy::auth;
+use crate::proxy::cors::CorsConfig;
+use crate::proxy::dns::{self, DnsConfig};
+use crate::proxy::ip_allowlist::IpAllowlist;
+use crate::proxy::replay::{replay_request, ReplayResponse};
+use crate::proxy::request_log::{RequestCapture, RequestLog, DEFAULT_REQUEST_LOG_CAPACITY};
 use crate::proxy::server::ProxyServer;
+use crate::proxy::tls::{self, ProxyTlsConfig};
 use crate::proxy::types::*;
+use crate::proxy::unix_socket::{self, UnixSocketConfig};
+use crate::proxy::url_router::UrlRouter;
 use crate::services::provider::write_live_snapshot;
+use crate::services::url_latency::UrlLatencyService;
 use serde_json::{json, Value};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -35,16 +46,73 @@ const CLAUDE_MODEL_OVERRIDE_ENV_KEYS: [&str; 6] = [
 pub struct ProxyService {
     db: Arc<Database>,
     server: Arc<RwLock<Option<ProxyServer>>>,
+    request_log: Arc<RequestLog>,
+    access_log: Arc<AccessLog>,
 }
 
 impl ProxyService {
     pub fn new(db: Arc<Database>) -> Self {
+        let request_log = Arc::new(RequestLog::with_store(
+            DEFAULT_REQUEST_LOG_CAPACITY,
+            db.clone(),
+        ));
+        let access_log = Arc::new(AccessLog::new(
+            AccessLog::default_path(),
+            db.get_access_log_config().unwrap_or_default(),
+        ));
+        dns::shared_resolver().configure(db.get_proxy_dns_config().unwrap_or_default());
         Self {
             db,
             server: Arc::new(RwLock::new(None)),
+            request_log,
+            access_log,
         }
     }
 
+    /// 结构化访问日志（JSONL）
+    pub fn access_log(&self) -> Arc<AccessLog> {
+        self.access_log.clone()
+    }
+
+    /// 保存访问日志设置并立即生效
+    pub fn set_access_log_config(&self, config: AccessLogConfig) -> Result<(), String> {
+        self.db
+            .set_access_log_config(config)
+            .map_err(|e| format!("保存访问日志设置失败: {e}"))?;
+        self.access_log.configure(config);
+        Ok(())
+    }
+
+    /// 保存 DNS 设置并立即生效（同时清空 DNS 缓存）
+    pub fn set_dns_config(&self, config: DnsConfig) -> Result<(), String> {
+        self.db
+            .set_proxy_dns_config(&config)
+            .map_err(|e| format!("保存 DNS 设置失败: {e}"))?;
+        dns::shared_resolver().configure(config);
+        Ok(())
+    }
+
+    /// 最近转发的请求记录（代理重启后仍保留）
+    pub fn request_log(&self) -> Arc<RequestLog> {
+        self.request_log.clone()
+    }
+
+    /// 通过指定供应商重放捕获的请求，不要求代理正在运行
+    pub async fn replay_request(
+        &self,
+        app_type: &AppType,
+        provider: &Provider,
+        capture: &RequestCapture,
+    ) -> Result<ReplayResponse, String> {
+        let upstream_proxy = self
+            .db
+            .get_proxy_upstream_url()
+            .map_err(|e| e.to_string())?;
+        replay_request(provider, app_type, capture, upstream_proxy.as_deref())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     /// 清理接管模式下 Claude Live 配置中的模型覆盖字段。
     ///
     /// 这可以避免“接管开启后切换供应商仍使用旧模型”的问题。
@@ -106,7 +174,12 @@ impl ProxyService {
         }
 
         // 4. 创建并启动服务器
-        let server = ProxyServer::new(config.clone(), self.db.clone());
+        let server = ProxyServer::new(
+            config.clone(),
+            self.db.clone(),
+            self.request_log.clone(),
+            self.access_log.clone(),
+        );
         let info = server
             .start()
             .await
@@ -121,6 +194,8 @@ impl ProxyService {
 
     /// 启动代理服务器（带 Live 配置接管）
     pub async fn start_with_takeover(&self) -> Result<ProxyServerInfo, String> {
+        self.ensure_takeover_reachable()?;
+
         // 1. 备份各应用的 Live 配置
         self.backup_live_configs().await?;
 
@@ -216,6 +291,8 @@ impl ProxyService {
         let app_type_str = app.as_str();
 
         if enabled {
+            self.ensure_takeover_reachable()?;
+
             // 1) 代理服务未运行则自动启动
             if !self.is_running().await {
                 self.start().await?;
@@ -391,9 +468,7 @@ impl ProxyService {
                                     .and_then(|v| v.as_str())
                                     .map(|s| (key, s.trim()))
                             })
-                            .filter(|(_, token)| {
-                                !token.is_empty() && *token != PROXY_TOKEN_PLACEHOLDER
-                            });
+                            .filter(|(_, token)| !token.is_empty() && !Self::is_proxy_token(token));
 
                             if let Some((token_key, token)) = token_pair {
                                 let env_obj = provider
@@ -465,7 +540,7 @@ impl ProxyService {
                             .and_then(|v| v.get("OPENAI_API_KEY"))
                             .and_then(|v| v.as_str())
                             .map(|s| s.trim())
-                            .filter(|s| !s.is_empty() && *s != PROXY_TOKEN_PLACEHOLDER)
+                            .filter(|s| !s.is_empty() && !Self::is_proxy_token(s))
                         {
                             if let Some(auth_obj) = provider
                                 .settings_config
@@ -506,7 +581,7 @@ impl ProxyService {
                             .and_then(|v| v.get("GEMINI_API_KEY"))
                             .and_then(|v| v.as_str())
                             .map(|s| s.trim())
-                            .filter(|s| !s.is_empty() && *s != PROXY_TOKEN_PLACEHOLDER)
+                            .filter(|s| !s.is_empty() && !Self::is_proxy_token(s))
                         {
                             if let Some(env_obj) = provider
                                 .settings_config
@@ -725,8 +800,23 @@ impl ProxyService {
         Ok(())
     }
 
-    /// 构造写入 Live 的代理地址（处理 0.0.0.0 / IPv6 等特殊情况）
-    async fn build_proxy_urls(&self) -> Result<(String, String), String> {
+    /// 仅监听 Unix socket 时拒绝接管：各 CLI 只支持 http(s) 形式的 Base URL
+    fn ensure_takeover_reachable(&self) -> Result<(), String> {
+        let socket_config = self.get_unix_socket_config()?;
+        if socket_config.listens_on_tcp() {
+            Ok(())
+        } else {
+            Err(
+                "代理仅监听 Unix socket，客户端无法通过 Base URL 连接，请先恢复 TCP 监听再接管"
+                    .to_string(),
+            )
+        }
+    }
+
+    /// 构造写入指定应用 Live 的代理地址（处理 0.0.0.0 / IPv6 等特殊情况）
+    ///
+    /// 应用设置了独立端口时使用该端口，否则使用共享监听端口
+    async fn build_proxy_urls(&self, app_type: &AppType) -> Result<(String, String), String> {
         let config = self
             .db
             .get_proxy_config()
@@ -746,7 +836,22 @@ impl ProxyService {
             connect_host
         };
 
-        let proxy_origin = format!("http://{}:{}", connect_host_for_url, config.listen_port);
+        let scheme = if self
+            .db
+            .get_proxy_tls_config()
+            .map_err(|e| format!("获取 HTTPS 设置失败: {e}"))?
+            .enabled
+        {
+            "https"
+        } else {
+            "http"
+        };
+        let port = self
+            .db
+            .get_dedicated_port(app_type.as_str())
+            .map_err(|e| format!("获取独立端口失败: {e}"))?
+            .unwrap_or(config.listen_port);
+        let proxy_origin = format!("{scheme}://{connect_host_for_url}:{port}");
         let proxy_url = proxy_origin.clone();
         let proxy_codex_base_url = format!("{}/v1", proxy_origin.trim_end_matches('/'));
 
@@ -760,14 +865,17 @@ impl ProxyService {
     /// - `/v1/chat/completions`, `/v1/responses` → Codex
     /// - `/v1beta/*` → Gemini
     ///
-    /// 因此不需要在 URL 中添加应用前缀。
+    /// 因此不需要在 URL 中添加应用前缀；设置了独立端口的应用写入各自的端口。
     async fn takeover_live_configs(&self) -> Result<(), String> {
-        let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls().await?;
+        let client_token = self.client_token();
+        let (claude_url, _) = self.build_proxy_urls(&AppType::Claude).await?;
+        let (_, proxy_codex_base_url) = self.build_proxy_urls(&AppType::Codex).await?;
+        let (gemini_url, _) = self.build_proxy_urls(&AppType::Gemini).await?;
 
         // Claude: 修改 ANTHROPIC_BASE_URL，使用占位符替代真实 Token（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_claude_live() {
             if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                env.insert("ANTHROPIC_BASE_URL".to_string(), json!(&proxy_url));
+                env.insert("ANTHROPIC_BASE_URL".to_string(), json!(&claude_url));
                 // 关键：接管模式下移除模型覆盖字段，避免切换供应商后仍用旧模型名发起请求
                 for key in CLAUDE_MODEL_OVERRIDE_ENV_KEYS {
                     env.remove(key);
@@ -784,32 +892,29 @@ impl ProxyService {
                 let mut replaced_any = false;
                 for key in token_keys {
                     if env.contains_key(key) {
-                        env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                        env.insert(key.to_string(), json!(client_token));
                         replaced_any = true;
                     }
                 }
 
                 if !replaced_any {
-                    env.insert(
-                        "ANTHROPIC_AUTH_TOKEN".to_string(),
-                        json!(PROXY_TOKEN_PLACEHOLDER),
-                    );
+                    env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), json!(client_token));
                 }
             } else {
                 live_config["env"] = json!({
-                    "ANTHROPIC_BASE_URL": &proxy_url,
-                    "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
+                    "ANTHROPIC_BASE_URL": &claude_url,
+                    "ANTHROPIC_AUTH_TOKEN": client_token
                 });
             }
             self.write_claude_live(&live_config)?;
-            log::info!("Claude Live 配置已接管，代理地址: {proxy_url}");
+            log::info!("Claude Live 配置已接管，代理地址: {claude_url}");
         }
 
         // Codex: 修改 config.toml 的 base_url，auth.json 的 OPENAI_API_KEY（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_codex_live() {
             // 1. 修改 auth.json 中的 OPENAI_API_KEY（使用占位符）
             if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut()) {
-                auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                auth.insert("OPENAI_API_KEY".to_string(), json!(client_token));
             }
 
             // 2. 修改 config.toml 中的 base_url
@@ -827,17 +932,17 @@ impl ProxyService {
         // Gemini: 修改 GOOGLE_GEMINI_BASE_URL，使用占位符替代真实 Token（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_gemini_live() {
             if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
+                env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&gemini_url));
                 // 使用占位符，避免显示缺少 key 的警告
-                env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                env.insert("GEMINI_API_KEY".to_string(), json!(client_token));
             } else {
                 live_config["env"] = json!({
-                    "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                    "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
+                    "GOOGLE_GEMINI_BASE_URL": &gemini_url,
+                    "GEMINI_API_KEY": client_token
                 });
             }
             self.write_gemini_live(&live_config)?;
-            log::info!("Gemini Live 配置已接管，代理地址: {proxy_url}");
+            log::info!("Gemini Live 配置已接管，代理地址: {gemini_url}");
         }
 
         Ok(())
@@ -845,7 +950,8 @@ impl ProxyService {
 
     /// 接管指定应用的 Live 配置（严格模式：目标配置不存在则返回错误）
     async fn takeover_live_config_strict(&self, app_type: &AppType) -> Result<(), String> {
-        let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls().await?;
+        let client_token = self.client_token();
+        let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls(app_type).await?;
 
         match app_type {
             AppType::Claude => {
@@ -867,21 +973,18 @@ impl ProxyService {
                     let mut replaced_any = false;
                     for key in token_keys {
                         if env.contains_key(key) {
-                            env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                            env.insert(key.to_string(), json!(client_token));
                             replaced_any = true;
                         }
                     }
 
                     if !replaced_any {
-                        env.insert(
-                            "ANTHROPIC_AUTH_TOKEN".to_string(),
-                            json!(PROXY_TOKEN_PLACEHOLDER),
-                        );
+                        env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), json!(client_token));
                     }
                 } else {
                     live_config["env"] = json!({
                         "ANTHROPIC_BASE_URL": &proxy_url,
-                        "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
+                        "ANTHROPIC_AUTH_TOKEN": client_token
                     });
                 }
 
@@ -892,7 +995,7 @@ impl ProxyService {
                 let mut live_config = self.read_codex_live()?;
 
                 if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut()) {
-                    auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                    auth.insert("OPENAI_API_KEY".to_string(), json!(client_token));
                 }
 
                 let config_str = live_config
@@ -910,11 +1013,11 @@ impl ProxyService {
 
                 if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
                     env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
-                    env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                    env.insert("GEMINI_API_KEY".to_string(), json!(client_token));
                 } else {
                     live_config["env"] = json!({
                         "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                        "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
+                        "GEMINI_API_KEY": client_token
                     });
                 }
 
@@ -928,7 +1031,8 @@ impl ProxyService {
 
     /// 接管指定应用的 Live 配置（尽力而为：配置不存在/读取失败则跳过）
     async fn takeover_live_config_best_effort(&self, app_type: &AppType) -> Result<(), String> {
-        let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls().await?;
+        let client_token = self.client_token();
+        let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls(app_type).await?;
 
         match app_type {
             AppType::Claude => {
@@ -950,21 +1054,18 @@ impl ProxyService {
                         let mut replaced_any = false;
                         for key in token_keys {
                             if env.contains_key(key) {
-                                env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                                env.insert(key.to_string(), json!(client_token));
                                 replaced_any = true;
                             }
                         }
 
                         if !replaced_any {
-                            env.insert(
-                                "ANTHROPIC_AUTH_TOKEN".to_string(),
-                                json!(PROXY_TOKEN_PLACEHOLDER),
-                            );
+                            env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), json!(client_token));
                         }
                     } else {
                         live_config["env"] = json!({
                             "ANTHROPIC_BASE_URL": &proxy_url,
-                            "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
+                            "ANTHROPIC_AUTH_TOKEN": client_token
                         });
                     }
 
@@ -975,7 +1076,7 @@ impl ProxyService {
                 if let Ok(mut live_config) = self.read_codex_live() {
                     if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut())
                     {
-                        auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                        auth.insert("OPENAI_API_KEY".to_string(), json!(client_token));
                     }
 
                     let config_str = live_config
@@ -993,11 +1094,11 @@ impl ProxyService {
                 if let Ok(mut live_config) = self.read_gemini_live() {
                     if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
                         env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
-                        env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                        env.insert("GEMINI_API_KEY".to_string(), json!(client_token));
                     } else {
                         live_config["env"] = json!({
                             "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                            "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
+                            "GEMINI_API_KEY": client_token
                         });
                     }
 
@@ -1174,12 +1275,28 @@ impl ProxyService {
         }
     }
 
+    /// Live 配置中的 Token 是否由代理写入（占位符或代理访问令牌）
+    fn is_proxy_token(value: &str) -> bool {
+        value == PROXY_TOKEN_PLACEHOLDER || auth::is_generated_token(value)
+    }
+
+    /// 接管 Live 配置时写入客户端的 Token：设置了访问令牌时使用令牌，否则使用占位符
+    fn client_token(&self) -> String {
+        self.db
+            .get_proxy_auth_token()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| PROXY_TOKEN_PLACEHOLDER.to_string())
+    }
+
     fn is_local_proxy_url(url: &str) -> bool {
         let url = url.trim();
-        if !url.starts_with("http://") {
+        let Some(rest) = url
+            .strip_prefix("http://")
+            .or_else(|| url.strip_prefix("https://"))
+        else {
             return false;
-        }
-        let rest = &url["http://".len()..];
+        };
         rest.starts_with("127.0.0.1")
             || rest.starts_with("localhost")
             || rest.starts_with("0.0.0.0")
@@ -1202,7 +1319,11 @@ impl ProxyService {
             "OPENROUTER_API_KEY",
             "OPENAI_API_KEY",
         ] {
-            if env.get(key).and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER) {
+            if env
+                .get(key)
+                .and_then(|v| v.as_str())
+                .is_some_and(Self::is_proxy_token)
+            {
                 env.remove(key);
             }
         }
@@ -1224,7 +1345,10 @@ impl ProxyService {
         let mut config = self.read_codex_live()?;
 
         if let Some(auth) = config.get_mut("auth").and_then(|v| v.as_object_mut()) {
-            if auth.get("OPENAI_API_KEY").and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER)
+            if auth
+                .get("OPENAI_API_KEY")
+                .and_then(|v| v.as_str())
+                .is_some_and(Self::is_proxy_token)
             {
                 auth.remove("OPENAI_API_KEY");
             }
@@ -1293,7 +1417,11 @@ impl ProxyService {
             return Ok(());
         };
 
-        if env.get("GEMINI_API_KEY").and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER) {
+        if env
+            .get("GEMINI_API_KEY")
+            .and_then(|v| v.as_str())
+            .is_some_and(Self::is_proxy_token)
+        {
             env.remove("GEMINI_API_KEY");
         }
 
@@ -1378,7 +1506,11 @@ impl ProxyService {
             "OPENROUTER_API_KEY",
             "OPENAI_API_KEY",
         ] {
-            if env.get(key).and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER) {
+            if env
+                .get(key)
+                .and_then(|v| v.as_str())
+                .is_some_and(Self::is_proxy_token)
+            {
                 return true;
             }
         }
@@ -1391,7 +1523,9 @@ impl ProxyService {
             Some(auth) => auth,
             None => return false,
         };
-        auth.get("OPENAI_API_KEY").and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER)
+        auth.get("OPENAI_API_KEY")
+            .and_then(|v| v.as_str())
+            .is_some_and(Self::is_proxy_token)
     }
 
     fn is_gemini_live_taken_over(config: &Value) -> bool {
@@ -1399,7 +1533,9 @@ impl ProxyService {
             Some(env) => env,
             None => return false,
         };
-        env.get("GEMINI_API_KEY").and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER)
+        env.get("GEMINI_API_KEY")
+            .and_then(|v| v.as_str())
+            .is_some_and(Self::is_proxy_token)
     }
 
     /// 从供应商配置更新 Live 备份（用于代理模式下的热切换）
@@ -1466,7 +1602,7 @@ impl ProxyService {
     // ==================== Live 配置读写辅助方法 ====================
 
     /// 更新 TOML 字符串中的 base_url
-    fn update_toml_base_url(toml_str: &str, new_url: &str) -> String {
+    pub(crate) fn update_toml_base_url(toml_str: &str, new_url: &str) -> String {
         use toml_edit::DocumentMut;
 
         let mut doc = match toml_str.parse::<DocumentMut>() {
@@ -1599,15 +1735,18 @@ impl ProxyService {
 
     /// 获取服务器状态
     pub async fn get_status(&self) -> Result<ProxyStatus, String> {
-        if let Some(server) = self.server.read().await.as_ref() {
-            Ok(server.get_status().await)
+        let mut status = if let Some(server) = self.server.read().await.as_ref() {
+            server.get_status().await
         } else {
             // 服务器未运行时返回默认状态
-            Ok(ProxyStatus {
+            ProxyStatus {
                 running: false,
                 ..Default::default()
-            })
-        }
+            }
+        };
+        // 测速等功能在代理停止时同样使用 DNS 缓存
+        status.dns_cache = dns::shared_resolver().stats();
+        Ok(status)
     }
 
     /// 获取代理配置
@@ -1637,7 +1776,7 @@ impl ProxyService {
             .map_err(|e| format!("保存代理配置失败: {e}"))?;
 
         // 检查服务器当前状态
-        let mut server_guard = self.server.write().await;
+        let server_guard = self.server.read().await;
         if server_guard.is_none() {
             return Ok(());
         }
@@ -1647,6 +1786,21 @@ impl ProxyService {
             || new_config.listen_port != previous.listen_port;
 
         if require_restart {
+            drop(server_guard);
+            self.restart_server(new_config).await?;
+            log::info!("代理配置已更新，服务器已自动重启应用最新配置");
+        } else if let Some(server) = server_guard.as_ref() {
+            server.apply_runtime_config(&new_config).await;
+            log::info!("代理配置已实时应用，无需重启代理服务器");
+        }
+
+        Ok(())
+    }
+
+    /// 以新配置重启运行中的代理服务器，并同步 Live 配置中的代理地址
+    async fn restart_server(&self, config: ProxyConfig) -> Result<(), String> {
+        {
+            let mut server_guard = self.server.write().await;
             if let Some(server) = server_guard.take() {
                 server
                     .stop()
@@ -1654,50 +1808,275 @@ impl ProxyService {
                     .map_err(|e| format!("重启前停止代理服务器失败: {e}"))?;
             }
 
-            let new_server = ProxyServer::new(new_config, self.db.clone());
+            let new_server = ProxyServer::new(
+                config,
+                self.db.clone(),
+                self.request_log.clone(),
+                self.access_log.clone(),
+            );
             new_server
                 .start()
                 .await
                 .map_err(|e| format!("重启代理服务器失败: {e}"))?;
 
             *server_guard = Some(new_server);
-            log::info!("代理配置已更新，服务器已自动重启应用最新配置");
+        }
 
-            // 如果当前存在任意 app 的 Live 接管，需要同步更新 Live 中的代理地址（否则客户端仍指向旧端口）
-            drop(server_guard);
-            if let Ok(takeover) = self.get_takeover_status().await {
-                let mut updated_any = false;
+        // 如果当前存在任意 app 的 Live 接管，需要同步更新 Live 中的代理地址（否则客户端仍指向旧地址）
+        self.resync_taken_over_live_configs().await
+    }
 
-                if takeover.claude {
-                    self.takeover_live_config_best_effort(&AppType::Claude)
-                        .await?;
-                    updated_any = true;
-                }
-                if takeover.codex {
-                    self.takeover_live_config_best_effort(&AppType::Codex)
-                        .await?;
-                    updated_any = true;
-                }
-                if takeover.gemini {
-                    self.takeover_live_config_best_effort(&AppType::Gemini)
-                        .await?;
-                    updated_any = true;
-                }
+    /// 重新写入所有已接管应用的 Live 配置，使其使用最新的代理地址与 Token
+    async fn resync_taken_over_live_configs(&self) -> Result<(), String> {
+        if let Ok(takeover) = self.get_takeover_status().await {
+            let mut updated_any = false;
+
+            if takeover.claude {
+                self.takeover_live_config_best_effort(&AppType::Claude)
+                    .await?;
+                updated_any = true;
+            }
+            if takeover.codex {
+                self.takeover_live_config_best_effort(&AppType::Codex)
+                    .await?;
+                updated_any = true;
+            }
+            if takeover.gemini {
+                self.takeover_live_config_best_effort(&AppType::Gemini)
+                    .await?;
+                updated_any = true;
+            }
+
+            if updated_any {
+                log::info!("已同步更新 Live 配置中的代理地址与 Token");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取代理 HTTPS 设置
+    pub fn get_tls_config(&self) -> Result<ProxyTlsConfig, String> {
+        self.db
+            .get_proxy_tls_config()
+            .map_err(|e| format!("获取 HTTPS 设置失败: {e}"))
+    }
+
+    /// 获取代理访问令牌，未开启时返回 None
+    pub fn get_auth_token(&self) -> Result<Option<String>, String> {
+        self.db
+            .get_proxy_auth_token()
+            .map_err(|e| format!("获取访问令牌失败: {e}"))
+    }
+
+    /// 开启（生成新令牌）或关闭代理访问令牌
+    ///
+    /// 令牌在每个请求上实时校验，无需重启；已接管的 Live 配置会同步写入新令牌
+    pub async fn set_auth_token_enabled(&self, enabled: bool) -> Result<Option<String>, String> {
+        let token = enabled.then(auth::generate_token);
+        self.db
+            .set_proxy_auth_token(token.as_deref())
+            .map_err(|e| format!("保存访问令牌失败: {e}"))?;
+        self.resync_taken_over_live_configs().await?;
+        Ok(token)
+    }
+
+    /// 获取应用的独立监听端口，None 表示使用共享端口
+    pub fn get_dedicated_port(&self, app_type: &AppType) -> Result<Option<u16>, String> {
+        self.db
+            .get_dedicated_port(app_type.as_str())
+            .map_err(|e| format!("获取独立端口失败: {e}"))
+    }
+
+    /// 修改应用的独立监听端口，传入 None 恢复使用共享端口
+    ///
+    /// 端口不能与共享端口或其他应用的独立端口重复；代理运行中时会自动重启，
+    /// 已接管的 Live 配置会改写为新端口
+    pub async fn set_dedicated_port(
+        &self,
+        app_type: &AppType,
+        port: Option<u16>,
+    ) -> Result<(), String> {
+        let previous = self.get_dedicated_port(app_type)?;
+        if previous == port {
+            return Ok(());
+        }
 
-                if updated_any {
-                    log::info!("已同步更新 Live 配置中的代理地址");
+        if let Some(port) = port {
+            let config = self.get_config().await?;
+            if port == config.listen_port {
+                return Err(format!("端口 {port} 与共享监听端口相同"));
+            }
+            for other in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+                if other != *app_type && self.get_dedicated_port(&other)? == Some(port) {
+                    return Err(format!(
+                        "端口 {port} 已被 {} 的独立端口占用",
+                        other.as_str()
+                    ));
                 }
             }
+            let ip: std::net::IpAddr = config
+                .listen_address
+                .parse()
+                .map_err(|_| format!("无效的监听地址: {}", config.listen_address))?;
+            std::net::TcpListener::bind((ip, port))
+                .map_err(|e| format!("端口 {port} 不可用: {e}"))?;
+        }
+
+        self.db
+            .set_dedicated_port(app_type.as_str(), port)
+            .map_err(|e| format!("保存独立端口失败: {e}"))?;
+
+        if self.is_running().await {
+            let proxy_config = self.get_config().await?;
+            self.restart_server(proxy_config).await?;
+            log::info!("{} 独立端口已更新，服务器已自动重启", app_type.as_str());
+        }
+        Ok(())
+    }
+
+    /// 获取代理 CORS 设置
+    pub fn get_cors_config(&self) -> Result<CorsConfig, String> {
+        self.db
+            .get_proxy_cors_config()
+            .map_err(|e| format!("获取 CORS 设置失败: {e}"))
+    }
 
+    /// 修改代理 CORS 设置
+    ///
+    /// 保存前校验来源、方法与请求头格式；代理运行中时会自动重启以重建路由
+    pub async fn set_cors_config(&self, config: CorsConfig) -> Result<(), String> {
+        config.layer().map_err(|e| e.to_string())?;
+        if self.get_cors_config()? == config {
             return Ok(());
-        } else if let Some(server) = server_guard.as_ref() {
-            server.apply_runtime_config(&new_config).await;
-            log::info!("代理配置已实时应用，无需重启代理服务器");
         }
+        self.db
+            .set_proxy_cors_config(&config)
+            .map_err(|e| format!("保存 CORS 设置失败: {e}"))?;
 
+        if self.is_running().await {
+            let proxy_config = self.get_config().await?;
+            self.restart_server(proxy_config).await?;
+            log::info!("CORS 设置已更新，服务器已自动重启");
+        }
         Ok(())
     }
 
+    /// 获取代理 Unix socket 设置
+    pub fn get_unix_socket_config(&self) -> Result<UnixSocketConfig, String> {
+        self.db
+            .get_proxy_unix_socket_config()
+            .map_err(|e| format!("获取 Unix socket 设置失败: {e}"))
+    }
+
+    /// 修改代理 Unix socket 设置
+    ///
+    /// 仍有应用处于接管状态时不能切换为仅 socket 模式；代理运行中时会自动重启监听
+    pub async fn set_unix_socket_config(&self, config: UnixSocketConfig) -> Result<(), String> {
+        if config.enabled {
+            unix_socket::ensure_supported().map_err(|e| e.to_string())?;
+        }
+        if self.get_unix_socket_config()? == config {
+            return Ok(());
+        }
+        if !config.listens_on_tcp() && self.is_takeover_active().await? {
+            return Err("仍有应用接管了代理，请先关闭接管再切换为仅 Unix socket".to_string());
+        }
+        self.db
+            .set_proxy_unix_socket_config(&config)
+            .map_err(|e| format!("保存 Unix socket 设置失败: {e}"))?;
+
+        if self.is_running().await {
+            let proxy_config = self.get_config().await?;
+            self.restart_server(proxy_config).await?;
+            log::info!("Unix socket 设置已更新，服务器已自动重启");
+        }
+        Ok(())
+    }
+
+    /// 获取允许连接代理的网段列表
+    pub fn get_ip_allowlist(&self) -> Result<Vec<String>, String> {
+        self.db
+            .get_proxy_ip_allowlist()
+            .map_err(|e| format!("获取 IP 白名单失败: {e}"))
+    }
+
+    /// 修改允许连接代理的网段列表，返回规范化后的网段
+    ///
+    /// 运行中的代理立即对新连接生效，无需重启；已建立的连接不受影响
+    pub async fn set_ip_allowlist(&self, entries: &[String]) -> Result<Vec<String>, String> {
+        let allowlist = IpAllowlist::parse(entries).map_err(|e| e.to_string())?;
+        let entries = allowlist.entries();
+        self.db
+            .set_proxy_ip_allowlist(&entries)
+            .map_err(|e| format!("保存 IP 白名单失败: {e}"))?;
+        if let Some(server) = self.server.read().await.as_ref() {
+            server.set_ip_allowlist(allowlist);
+        }
+        Ok(entries)
+    }
+
+    /// 修改代理 HTTPS 设置
+    ///
+    /// 开启时先加载（或生成）证书校验可用性；代理运行中时会自动重启监听
+    pub async fn set_tls_config(&self, config: ProxyTlsConfig) -> Result<(), String> {
+        if config.enabled {
+            tls::build_acceptor(&config).map_err(|e| e.to_string())?;
+        }
+        let previous = self.get_tls_config()?;
+        if previous == config {
+            return Ok(());
+        }
+        self.db
+            .set_proxy_tls_config(&config)
+            .map_err(|e| format!("保存 HTTPS 设置失败: {e}"))?;
+
+        if self.is_running().await {
+            let proxy_config = self.get_config().await?;
+            self.restart_server(proxy_config).await?;
+            log::info!("HTTPS 设置已更新，服务器已自动重启");
+        }
+        Ok(())
+    }
+
+    /// 修改代理监听地址与端口
+    ///
+    /// 校验地址格式与端口可用性后写入配置；代理运行中时会自动重启监听
+    pub async fn set_listen_address(&self, address: &str, port: u16) -> Result<(), String> {
+        let ip: std::net::IpAddr = address
+            .trim()
+            .parse()
+            .map_err(|_| format!("无效的监听地址: {address}"))?;
+        if port == 0 {
+            return Err("端口不能为 0".to_string());
+        }
+
+        let mut config = self.get_config().await?;
+        let address = ip.to_string();
+        if config.listen_address == address && config.listen_port == port {
+            return Ok(());
+        }
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            if self.get_dedicated_port(&app_type)? == Some(port) {
+                return Err(format!(
+                    "端口 {port} 已被 {} 的独立端口占用",
+                    app_type.as_str()
+                ));
+            }
+        }
+
+        // 运行中且端口未变时端口被自身占用，跳过可用性检测
+        let held_by_self = self.is_running().await && config.listen_port == port;
+        if !held_by_self {
+            std::net::TcpListener::bind((ip, port))
+                .map_err(|e| format!("端口 {port} 不可用: {e}"))?;
+        }
+
+        config.listen_address = address;
+        config.listen_port = port;
+        self.update_config(&config).await
+    }
+
     /// 检查服务器是否正在运行
     pub async fn is_running(&self) -> bool {
         self.server.read().await.is_some()
@@ -1719,6 +2098,63 @@ impl ProxyService {
         Ok(())
     }
 
+    /// 获取处于打开状态的熔断器数量（代理未运行时为 0）
+    pub async fn open_circuit_breaker_count(&self) -> usize {
+        match self.server.read().await.as_ref() {
+            Some(server) => server.open_circuit_breaker_count().await,
+            None => 0,
+        }
+    }
+
+    /// 立即测试指定应用所有端点的延迟并更新健康状态
+    ///
+    /// 代理未运行时使用临时的延迟测试服务，结果同样写入数据库
+    pub async fn test_endpoint_latency_now(&self, app_type: &str) -> Result<(), String> {
+        if let Some(server) = self.server.read().await.as_ref() {
+            return server
+                .test_latency_now(app_type)
+                .await
+                .map_err(|e| e.to_string());
+        }
+
+        let url_router = Arc::new(UrlRouter::new(self.db.clone()));
+        UrlLatencyService::new(self.db.clone(), url_router)
+            .test_now(app_type)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 指定 Provider 最近一次主动健康检查结果，代理未运行时为 None
+    pub async fn health_check_result(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Option<crate::proxy::health::HealthProbeResult> {
+        self.server
+            .read()
+            .await
+            .as_ref()
+            .and_then(|server| server.health_check_result(app_type, provider_id))
+    }
+
+    /// 清空运行中代理的响应缓存
+    pub async fn clear_response_cache(&self) {
+        if let Some(server) = self.server.read().await.as_ref() {
+            server.clear_response_cache();
+        }
+    }
+
+    /// 立即切回指定应用已恢复的首选供应商，返回其名称
+    pub async fn switch_back_now(&self, app_type: &str) -> Result<String, String> {
+        match self.server.read().await.as_ref() {
+            Some(server) => server
+                .switch_back_now(app_type)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("Proxy is not running".to_string()),
+        }
+    }
+
     /// 重置指定 Provider 的熔断器
     ///
     /// 如果代理服务器正在运行，立即重置内存中的熔断器状态
@@ -1955,4 +2391,30 @@ model = "gpt-5.1-codex"
             "should not add ANTHROPIC_AUTH_TOKEN when absent"
         );
     }
+
+    #[tokio::test]
+    async fn set_listen_address_validates_and_persists() {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db.clone());
+
+        assert!(service.set_listen_address("not-an-ip", 5000).await.is_err());
+        assert!(service.set_listen_address("127.0.0.1", 0).await.is_err());
+
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").expect("bind test port");
+        let busy_port = occupied.local_addr().unwrap().port();
+        let err = service
+            .set_listen_address("127.0.0.1", busy_port)
+            .await
+            .expect_err("occupied port should be rejected");
+        assert!(err.contains(&busy_port.to_string()));
+
+        drop(occupied);
+        service
+            .set_listen_address(" 127.0.0.1 ", busy_port)
+            .await
+            .expect("free port should be accepted");
+        let config = service.get_config().await.expect("read config");
+        assert_eq!(config.listen_address, "127.0.0.1");
+        assert_eq!(config.listen_port, busy_port);
+    }
 }
@@ -1,13 +1,111 @@
-use futures::future::join_all;
+use futures::{stream, Future, StreamExt};
 use reqwest::{Client, Url};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::time::{Duration, Instant};
 
+use crate::app_config::AppType;
 use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::dns;
+use crate::proxy::header_rules::apply_header_rules;
+use crate::proxy::providers::get_adapter;
 
 const DEFAULT_TIMEOUT_SECS: u64 = 8;
 const MAX_TIMEOUT_SECS: u64 = 30;
 const MIN_TIMEOUT_SECS: u64 = 2;
+/// 供应商探测超时上限（秒），completion 探测可能需要较长时间
+const MAX_PROBE_TIMEOUT_SECS: u64 = 60;
+
+/// 供应商测速的探测方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpeedtestMethod {
+    /// 只统计 TCP 连接建立耗时
+    TcpConnect,
+    /// 发送 HTTP HEAD 请求，任意响应都视为可达
+    Head,
+    /// 带认证请求模型列表接口
+    Models,
+    /// 发送一次输出上限为 1 token 的真实请求
+    Completion,
+}
+
+impl SpeedtestMethod {
+    pub const ALL: [SpeedtestMethod; 4] =
+        [Self::TcpConnect, Self::Head, Self::Models, Self::Completion];
+
+    /// 界面展示用名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TcpConnect => "TCP connect",
+            Self::Head => "HTTP HEAD",
+            Self::Models => "GET /models with auth",
+            Self::Completion => "tiny completion",
+        }
+    }
+
+    /// 未单独设置超时时使用的默认值（秒）
+    pub fn default_timeout_secs(&self) -> u64 {
+        match self {
+            Self::TcpConnect => 3,
+            Self::Head => 5,
+            Self::Models => 8,
+            Self::Completion => 30,
+        }
+    }
+}
+
+/// 供应商的测速探测设置，未设置时沿用预热后 GET 端点的默认测速
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedtestProbe {
+    pub method: SpeedtestMethod,
+    /// 超时（秒），未设置时使用探测方式的默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// completion 探测使用的模型，未设置时使用各应用的轻量模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl SpeedtestProbe {
+    pub fn new(method: SpeedtestMethod) -> Self {
+        Self {
+            method,
+            timeout_secs: None,
+            model: None,
+        }
+    }
+
+    /// 实际使用的超时（秒）
+    pub fn effective_timeout_secs(&self) -> u64 {
+        self.timeout_secs
+            .unwrap_or_else(|| self.method.default_timeout_secs())
+            .clamp(1, MAX_PROBE_TIMEOUT_SECS)
+    }
+}
+
+/// 批量测速参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedtestOptions {
+    /// 同时测试的 URL 数
+    pub concurrency: usize,
+    /// 每个 URL 的请求超时（秒）
+    pub timeout_secs: u64,
+    /// 每个 URL 计时的次数，延迟取成功样本的平均值
+    pub samples: u32,
+}
+
+impl Default for SpeedtestOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            samples: 1,
+        }
+    }
+}
 
 /// 端点测速结果
 #[derive(Debug, Clone, Serialize)]
@@ -16,6 +114,9 @@ pub struct EndpointLatency {
     pub latency: Option<u128>,
     pub status: Option<u16>,
     pub error: Option<String>,
+    /// 本轮各次成功计时的延迟（毫秒），`latency` 为其平均值
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<u128>,
 }
 
 /// 网络测速相关业务
@@ -26,6 +127,19 @@ impl SpeedtestService {
     pub async fn test_endpoints(
         urls: Vec<String>,
         timeout_secs: Option<u64>,
+    ) -> Result<Vec<EndpointLatency>, AppError> {
+        let options = SpeedtestOptions {
+            concurrency: urls.len(),
+            timeout_secs: Self::sanitize_timeout(timeout_secs),
+            samples: 1,
+        };
+        Self::test_endpoints_with(urls, &options).await
+    }
+
+    /// 按给定的并发数、超时与采样次数测试一组端点的响应延迟。
+    pub async fn test_endpoints_with(
+        urls: Vec<String>,
+        options: &SpeedtestOptions,
     ) -> Result<Vec<EndpointLatency>, AppError> {
         if urls.is_empty() {
             return Ok(vec![]);
@@ -43,6 +157,7 @@ impl SpeedtestService {
                     latency: None,
                     status: None,
                     error: Some("URL 不能为空".to_string()),
+                    samples: Vec::new(),
                 });
                 continue;
             }
@@ -55,6 +170,7 @@ impl SpeedtestService {
                         latency: None,
                         status: None,
                         error: Some(format!("URL 无效: {err}")),
+                        samples: Vec::new(),
                     });
                 }
             }
@@ -64,7 +180,7 @@ impl SpeedtestService {
             return Ok(results.into_iter().flatten().collect::<Vec<_>>());
         }
 
-        let timeout = Self::sanitize_timeout(timeout_secs);
+        let timeout = Self::sanitize_timeout(Some(options.timeout_secs));
         let client = Self::build_client(timeout)?;
 
         let tasks = valid_targets.into_iter().map(|(idx, trimmed, parsed_url)| {
@@ -73,47 +189,346 @@ impl SpeedtestService {
                 // 先进行一次热身请求，忽略结果，仅用于复用连接/绕过首包惩罚。
                 let _ = client.get(parsed_url.clone()).send().await;
 
-                // 第二次请求开始计时，并将其作为结果返回。
-                let start = Instant::now();
-                let latency = match client.get(parsed_url).send().await {
-                    Ok(resp) => EndpointLatency {
-                        url: trimmed,
-                        latency: Some(start.elapsed().as_millis()),
-                        status: Some(resp.status().as_u16()),
-                        error: None,
-                    },
-                    Err(err) => {
-                        let status = err.status().map(|s| s.as_u16());
-                        let error_message = if err.is_timeout() {
-                            "请求超时".to_string()
-                        } else if err.is_connect() {
-                            "连接失败".to_string()
-                        } else {
-                            err.to_string()
-                        };
-
-                        EndpointLatency {
-                            url: trimmed,
+                // 之后的请求开始计时，并将其作为结果返回。
+                let latency = Self::sample(options.samples, || async {
+                    let start = Instant::now();
+                    match client.get(parsed_url.clone()).send().await {
+                        Ok(resp) => EndpointLatency {
+                            url: trimmed.clone(),
+                            latency: Some(start.elapsed().as_millis()),
+                            status: Some(resp.status().as_u16()),
+                            error: None,
+                            samples: Vec::new(),
+                        },
+                        Err(err) => EndpointLatency {
+                            url: trimmed.clone(),
                             latency: None,
-                            status,
-                            error: Some(error_message),
-                        }
+                            status: err.status().map(|s| s.as_u16()),
+                            error: Some(Self::request_error(&err)),
+                            samples: Vec::new(),
+                        },
                     }
-                };
+                })
+                .await;
 
                 (idx, latency)
             }
         });
 
-        for (idx, latency) in join_all(tasks).await {
+        let measured: Vec<_> = stream::iter(tasks)
+            .buffer_unordered(options.concurrency.max(1))
+            .collect()
+            .await;
+        for (idx, latency) in measured {
             results[idx] = Some(latency);
         }
 
         Ok(results.into_iter().flatten().collect::<Vec<_>>())
     }
 
+    /// 使用供应商凭据请求模型列表接口，检测连通性与密钥是否有效。
+    ///
+    /// 非 2xx 响应同样返回延迟，并在 `error` 中记录 HTTP 状态。
+    pub async fn test_provider(
+        app_type: &AppType,
+        base_url: &str,
+        api_key: &str,
+        timeout_secs: Option<u64>,
+    ) -> Result<EndpointLatency, AppError> {
+        let url = Self::probe_url(app_type, base_url);
+        let parsed_url = match Url::parse(&url) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                return Ok(EndpointLatency {
+                    url,
+                    latency: None,
+                    status: None,
+                    error: Some(format!("URL 无效: {err}")),
+                    samples: Vec::new(),
+                })
+            }
+        };
+
+        let client = Self::build_client(Self::sanitize_timeout(timeout_secs))?;
+        let request = match app_type {
+            AppType::Claude => client
+                .get(parsed_url)
+                .header("x-api-key", api_key)
+                .bearer_auth(api_key)
+                .header("anthropic-version", "2023-06-01"),
+            AppType::Codex => client.get(parsed_url).bearer_auth(api_key),
+            AppType::Gemini => client.get(parsed_url).header("x-goog-api-key", api_key),
+        };
+
+        let start = Instant::now();
+        let result = match request.send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                EndpointLatency {
+                    url,
+                    latency: Some(start.elapsed().as_millis()),
+                    status: Some(status.as_u16()),
+                    error: (!status.is_success()).then(|| format!("HTTP {}", status.as_u16())),
+                    samples: Vec::new(),
+                }
+            }
+            Err(err) => EndpointLatency {
+                url,
+                latency: None,
+                status: err.status().map(|s| s.as_u16()),
+                error: Some(Self::request_error(&err)),
+                samples: Vec::new(),
+            },
+        };
+        Ok(result)
+    }
+
+    /// 按供应商的探测设置测试一组端点，每个 URL 都作为该供应商的 base_url 使用
+    pub async fn probe_endpoints(
+        app_type: &AppType,
+        provider: &Provider,
+        urls: Vec<String>,
+        probe: &SpeedtestProbe,
+        options: &SpeedtestOptions,
+    ) -> Result<Vec<EndpointLatency>, AppError> {
+        // 超时以探测设置为准，并发数与采样次数沿用批量测速参数
+        let timeout = probe.effective_timeout_secs();
+        let client = Self::build_client(timeout)?;
+        let tasks = urls.into_iter().map(|url| {
+            let client = &client;
+            Self::sample(options.samples, move || {
+                Self::probe_endpoint(client, app_type, provider, url.clone(), probe, timeout)
+            })
+        });
+        Ok(stream::iter(tasks)
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await)
+    }
+
+    /// 重复计时并取成功样本的平均延迟；全部失败时返回最后一次的结果
+    async fn sample<F, Fut>(samples: u32, mut measure: F) -> EndpointLatency
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = EndpointLatency>,
+    {
+        let mut succeeded = Vec::new();
+        let mut failed = None;
+        for _ in 0..samples.max(1) {
+            let result = measure().await;
+            if result.error.is_none() && result.latency.is_some() {
+                succeeded.push(result);
+            } else {
+                failed = Some(result);
+            }
+        }
+        let samples: Vec<u128> = succeeded.iter().filter_map(|r| r.latency).collect();
+        match succeeded.pop() {
+            Some(mut result) => {
+                result.latency = Some(samples.iter().sum::<u128>() / samples.len() as u128);
+                result.samples = samples;
+                result
+            }
+            None => failed.expect("at least one sample is taken"),
+        }
+    }
+
+    async fn probe_endpoint(
+        client: &Client,
+        app_type: &AppType,
+        provider: &Provider,
+        url: String,
+        probe: &SpeedtestProbe,
+        timeout_secs: u64,
+    ) -> EndpointLatency {
+        let url = url.trim().to_string();
+        let failed = |url: String, error: String| EndpointLatency {
+            url,
+            latency: None,
+            status: None,
+            error: Some(error),
+            samples: Vec::new(),
+        };
+        let parsed_url = match Url::parse(&url) {
+            Ok(parsed) => parsed,
+            Err(err) => return failed(url, format!("URL 无效: {err}")),
+        };
+
+        if probe.method == SpeedtestMethod::TcpConnect {
+            return match Self::tcp_connect(&parsed_url, Duration::from_secs(timeout_secs)).await {
+                Ok(latency) => EndpointLatency {
+                    url,
+                    latency: Some(latency),
+                    status: None,
+                    error: None,
+                    samples: Vec::new(),
+                },
+                Err(error) => failed(url, error),
+            };
+        }
+
+        let request = match Self::probe_request(client, app_type, provider, &url, probe) {
+            Ok(request) => request,
+            Err(error) => return failed(url, error),
+        };
+        let start = Instant::now();
+        match client.execute(request).await {
+            Ok(resp) => {
+                let status = resp.status();
+                // HEAD 只关心端点是否可达，其余方式要求 2xx
+                let ok = probe.method == SpeedtestMethod::Head || status.is_success();
+                EndpointLatency {
+                    url,
+                    latency: Some(start.elapsed().as_millis()),
+                    status: Some(status.as_u16()),
+                    error: (!ok).then(|| format!("HTTP {}", status.as_u16())),
+                    samples: Vec::new(),
+                }
+            }
+            Err(err) => EndpointLatency {
+                url,
+                latency: None,
+                status: err.status().map(|s| s.as_u16()),
+                error: Some(Self::request_error(&err)),
+                samples: Vec::new(),
+            },
+        }
+    }
+
+    /// 先解析地址，只统计建立 TCP 连接的耗时
+    async fn tcp_connect(url: &Url, timeout: Duration) -> Result<u128, String> {
+        let host = url
+            .host_str()
+            .map(|h| h.trim_start_matches('[').trim_end_matches(']'))
+            .ok_or_else(|| "URL 缺少主机名".to_string())?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| "URL 缺少端口".to_string())?;
+        let addr = tokio::time::timeout(timeout, tokio::net::lookup_host((host, port)))
+            .await
+            .map_err(|_| "DNS 解析超时".to_string())?
+            .map_err(|e| format!("DNS 解析失败: {e}"))?
+            .next()
+            .ok_or_else(|| "DNS 解析无结果".to_string())?;
+
+        let start = Instant::now();
+        match tokio::time::timeout(timeout, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => Ok(start.elapsed().as_millis()),
+            Ok(Err(e)) => Err(format!("连接失败: {e}")),
+            Err(_) => Err("连接超时".to_string()),
+        }
+    }
+
+    /// 构建 HTTP 探测请求，使用与转发相同的认证方式与请求头改写规则
+    fn probe_request(
+        client: &Client,
+        app_type: &AppType,
+        provider: &Provider,
+        base_url: &str,
+        probe: &SpeedtestProbe,
+    ) -> Result<reqwest::Request, String> {
+        let request = match probe.method {
+            SpeedtestMethod::TcpConnect | SpeedtestMethod::Head => client.head(base_url),
+            SpeedtestMethod::Models => client.get(Self::probe_url(app_type, base_url)),
+            SpeedtestMethod::Completion => {
+                let (url, body) =
+                    Self::completion_request(app_type, base_url, probe.model.as_deref());
+                client.post(url).json(&body)
+            }
+        };
+        let adapter = get_adapter(app_type);
+        let request = match adapter.extract_auth(provider) {
+            Some(auth) => adapter.add_auth_headers(request, &auth),
+            None => request,
+        };
+        let mut request = request.build().map_err(|e| e.to_string())?;
+        if let Some(meta) = &provider.meta {
+            apply_header_rules(request.headers_mut(), &meta.header_rules);
+        }
+        Ok(request)
+    }
+
+    /// 各应用输出上限为 1 token 的最小请求
+    fn completion_request(
+        app_type: &AppType,
+        base_url: &str,
+        model: Option<&str>,
+    ) -> (String, Value) {
+        let base = base_url.trim().trim_end_matches('/');
+        match app_type {
+            AppType::Claude => (
+                format!("{}/v1/messages", base.trim_end_matches("/v1")),
+                json!({
+                    "model": model.unwrap_or("claude-haiku-4-5"),
+                    "max_tokens": 1,
+                    "messages": [{"role": "user", "content": "ping"}],
+                }),
+            ),
+            AppType::Codex => (
+                format!("{base}/chat/completions"),
+                json!({
+                    "model": model.unwrap_or("gpt-4o-mini"),
+                    "max_tokens": 1,
+                    "messages": [{"role": "user", "content": "ping"}],
+                }),
+            ),
+            AppType::Gemini => (
+                format!(
+                    "{}/v1beta/models/{}:generateContent",
+                    base.trim_end_matches("/v1beta"),
+                    model.unwrap_or("gemini-2.0-flash")
+                ),
+                json!({
+                    "contents": [{"parts": [{"text": "ping"}]}],
+                    "generationConfig": {"maxOutputTokens": 1},
+                }),
+            ),
+        }
+    }
+
+    fn request_error(err: &reqwest::Error) -> String {
+        if err.is_timeout() {
+            "请求超时".to_string()
+        } else if err.is_connect() {
+            "连接失败".to_string()
+        } else {
+            err.to_string()
+        }
+    }
+
+    /// 各应用的模型列表接口，base_url 为空时使用官方地址
+    fn probe_url(app_type: &AppType, base_url: &str) -> String {
+        let base = base_url.trim().trim_end_matches('/');
+        match app_type {
+            AppType::Claude => {
+                let base = if base.is_empty() {
+                    "https://api.anthropic.com"
+                } else {
+                    base
+                };
+                format!("{}/v1/models", base.trim_end_matches("/v1"))
+            }
+            AppType::Codex => {
+                let base = if base.is_empty() {
+                    "https://api.openai.com/v1"
+                } else {
+                    base
+                };
+                format!("{base}/models")
+            }
+            AppType::Gemini => {
+                let base = if base.is_empty() {
+                    "https://generativelanguage.googleapis.com"
+                } else {
+                    base
+                };
+                format!("{}/v1beta/models", base.trim_end_matches("/v1beta"))
+            }
+        }
+    }
+
     fn build_client(timeout_secs: u64) -> Result<Client, AppError> {
-        Client::builder()
+        dns::client_builder()
             .timeout(Duration::from_secs(timeout_secs))
             .redirect(reqwest::redirect::Policy::limited(5))
             .user_agent("cc-switch-speedtest/1.0")
@@ -157,6 +572,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn probe_url_uses_models_endpoint() {
+        assert_eq!(
+            SpeedtestService::probe_url(&AppType::Claude, ""),
+            "https://api.anthropic.com/v1/models"
+        );
+        assert_eq!(
+            SpeedtestService::probe_url(&AppType::Claude, "https://relay.example.com/v1/"),
+            "https://relay.example.com/v1/models"
+        );
+        assert_eq!(
+            SpeedtestService::probe_url(&AppType::Codex, "https://relay.example.com/v1"),
+            "https://relay.example.com/v1/models"
+        );
+        assert_eq!(
+            SpeedtestService::probe_url(&AppType::Gemini, ""),
+            "https://generativelanguage.googleapis.com/v1beta/models"
+        );
+    }
+
+    #[test]
+    fn completion_request_targets_each_api() {
+        let (url, body) = SpeedtestService::completion_request(
+            &AppType::Claude,
+            "https://relay.example.com/v1",
+            None,
+        );
+        assert_eq!(url, "https://relay.example.com/v1/messages");
+        assert_eq!(body["max_tokens"], 1);
+        let (url, _) = SpeedtestService::completion_request(
+            &AppType::Gemini,
+            "https://relay.example.com",
+            Some("gemini-pro"),
+        );
+        assert_eq!(
+            url,
+            "https://relay.example.com/v1beta/models/gemini-pro:generateContent"
+        );
+    }
+
+    #[test]
+    fn sample_averages_successful_measurements() {
+        let mut latencies = vec![None, Some(30), Some(10)].into_iter();
+        let result = futures::executor::block_on(SpeedtestService::sample(3, || {
+            let latency = latencies.next().unwrap();
+            async move {
+                EndpointLatency {
+                    url: "https://relay.example.com".to_string(),
+                    latency,
+                    status: None,
+                    error: latency.is_none().then(|| "请求超时".to_string()),
+                    samples: Vec::new(),
+                }
+            }
+        }));
+        assert_eq!(result.latency, Some(20));
+        assert_eq!(result.samples, vec![30, 10]);
+        assert!(result.error.is_none());
+    }
+
+    #[test]
+    fn probe_timeout_defaults_per_method() {
+        let mut probe = SpeedtestProbe::new(SpeedtestMethod::TcpConnect);
+        assert_eq!(probe.effective_timeout_secs(), 3);
+        probe.method = SpeedtestMethod::Completion;
+        assert_eq!(probe.effective_timeout_secs(), 30);
+        probe.timeout_secs = Some(600);
+        assert_eq!(probe.effective_timeout_secs(), MAX_PROBE_TIMEOUT_SECS);
+    }
+
+    #[tokio::test]
+    async fn tcp_connect_probe_measures_local_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let provider = Provider::with_id(
+            "p".to_string(),
+            "P".to_string(),
+            serde_json::json!({}),
+            None,
+        );
+        let results = SpeedtestService::probe_endpoints(
+            &AppType::Claude,
+            &provider,
+            vec![url, "http://127.0.0.1:1".to_string()],
+            &SpeedtestProbe::new(SpeedtestMethod::TcpConnect),
+            &SpeedtestOptions {
+                concurrency: 1,
+                samples: 3,
+                ..SpeedtestOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(results[0].error.is_none() && results[0].latency.is_some());
+        assert!(results[1].error.is_some());
+    }
+
     #[test]
     fn test_endpoints_handles_empty_list() {
         let result =
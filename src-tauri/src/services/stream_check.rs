@@ -12,6 +12,7 @@ use std::time::{Duration, Instant};
 use crate::app_config::AppType;
 use crate::error::AppError;
 use crate::provider::Provider;
+use crate::proxy::dns;
 use crate::proxy::providers::{get_adapter, AuthInfo};
 
 /// 健康状态枚举
@@ -136,7 +137,7 @@ impl StreamCheckService {
             .extract_auth(provider)
             .ok_or_else(|| AppError::Message("未找到 API Key".to_string()))?;
 
-        let client = Client::builder()
+        let client = dns::client_builder()
             .timeout(Duration::from_secs(config.timeout_secs))
             .user_agent("cc-switch/1.0")
             .build()
@@ -2,10 +2,12 @@
 //!
 //! 后台定期测试 URL 延迟，更新端点健康状态
 
+use crate::app_config::AppType;
 use crate::database::Database;
 use crate::error::AppError;
+use crate::provider::Provider;
 use crate::proxy::url_router::UrlRouter;
-use crate::services::speedtest::SpeedtestService;
+use crate::services::speedtest::{SpeedtestOptions, SpeedtestService};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration};
@@ -86,10 +88,16 @@ impl UrlLatencyService {
     ) -> Result<(), AppError> {
         // 获取所有启用代理的 provider
         let providers = db.get_failover_providers(app_type)?;
+        let config = db.get_hybrid_mode_config(app_type)?;
+        let options = SpeedtestOptions {
+            concurrency: config.speedtest_concurrency as usize,
+            timeout_secs: config.speedtest_timeout_secs,
+            samples: config.speedtest_samples,
+        };
 
         for provider in providers {
             if let Err(e) =
-                Self::test_provider_endpoints(db, url_router, app_type, &provider.id).await
+                Self::test_provider_endpoints(db, url_router, app_type, &provider, &options).await
             {
                 log::warn!(
                     "[UrlLatencyService] 测试 provider {} 端点失败: {}",
@@ -107,8 +115,10 @@ impl UrlLatencyService {
         db: &Database,
         url_router: &UrlRouter,
         app_type: &str,
-        provider_id: &str,
+        provider: &Provider,
+        options: &SpeedtestOptions,
     ) -> Result<(), AppError> {
+        let provider_id = provider.id.as_str();
         // 获取所有端点
         let endpoints = db.get_provider_endpoints_with_health(app_type, provider_id)?;
 
@@ -119,8 +129,14 @@ impl UrlLatencyService {
         // 收集 URL 列表
         let urls: Vec<String> = endpoints.iter().map(|e| e.url.clone()).collect();
 
-        // 执行测速
-        let results = SpeedtestService::test_endpoints(urls, Some(8)).await?;
+        // 执行测速：供应商设置了探测方式时按其设置探测
+        let probe = provider.meta.as_ref().and_then(|m| m.speedtest.as_ref());
+        let results = match (probe, app_type.parse::<AppType>()) {
+            (Some(probe), Ok(app)) => {
+                SpeedtestService::probe_endpoints(&app, provider, urls, probe, options).await?
+            }
+            _ => SpeedtestService::test_endpoints_with(urls, options).await?,
+        };
 
         // 更新端点健康状态
         for result in results {
@@ -128,32 +144,47 @@ impl UrlLatencyService {
             let is_healthy = result.error.is_none() && result.latency.is_some();
 
             // 查找对应的端点
-            if let Some(endpoint) = endpoints.iter().find(|e| e.url == result.url) {
-                let consecutive_failures = if is_healthy {
-                    0
+            if endpoints.iter().any(|e| e.url == result.url) {
+                // 本轮每次成功计时都写入历史，失败时记录一条失败采样
+                if is_healthy && !result.samples.is_empty() {
+                    for sample in &result.samples {
+                        db.record_endpoint_latency(
+                            app_type,
+                            provider_id,
+                            &result.url,
+                            Some(*sample as u64),
+                            true,
+                        )?;
+                    }
                 } else {
-                    endpoint.consecutive_failures + 1
-                };
-
-                // 更新数据库
-                db.update_endpoint_health(
-                    app_type,
-                    provider_id,
-                    &result.url,
-                    latency_ms,
-                    is_healthy,
-                    consecutive_failures,
-                )?;
-
-                // 同步更新 UrlRouter 的熔断器状态
+                    db.record_endpoint_latency(
+                        app_type,
+                        provider_id,
+                        &result.url,
+                        latency_ms,
+                        is_healthy,
+                    )?;
+                }
+                db.refresh_endpoint_percentiles(app_type, provider_id, &result.url)?;
+                db.refresh_endpoint_stability(app_type, provider_id, &result.url)?;
+
+                // 同步更新 UrlRouter 的熔断器状态，并写入健康状态与 EWMA 平滑后的延迟
                 url_router
                     .record_url_result(provider_id, app_type, &result.url, is_healthy, latency_ms)
                     .await;
             }
         }
 
-        // 更新主端点（选择延迟最低的健康端点）
-        Self::update_primary_endpoint(db, app_type, provider_id)?;
+        // 更新主端点（选择 p95 延迟最低的健康端点），手动固定的主端点保持不变
+        if endpoints.iter().any(|e| e.is_pinned) {
+            log::debug!(
+                "[UrlLatencyService] {} provider {} 已固定主端点，跳过改选",
+                app_type,
+                provider_id
+            );
+        } else {
+            Self::update_primary_endpoint(db, app_type, provider_id)?;
+        }
 
         Ok(())
     }
@@ -51,6 +51,77 @@ pub struct ProviderStats {
     pub avg_latency_ms: u64,
 }
 
+/// 指定时间范围内单个供应商的代理流量汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderUsage {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_tokens: u64,
+    /// 按请求发生时的模型定价估算的花费（美元）
+    pub cost_usd: f64,
+}
+
+/// 指定时间范围内某一天的代理流量汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// 本地日期，格式 YYYY-MM-DD
+    pub day: String,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// 指定时间范围内某个模型的 token 汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub model: String,
+    pub request_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl ModelUsage {
+    /// 缓存命中率：缓存读取 token 占全部输入 token 的百分比
+    pub fn cache_hit_rate(&self) -> f64 {
+        let total = self.input_tokens + self.cache_read_tokens + self.cache_creation_tokens;
+        if total == 0 {
+            return 0.0;
+        }
+        self.cache_read_tokens as f64 * 100.0 / total as f64
+    }
+}
+
+/// 模型定价（每百万 token 的美元价格）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub model_id: String,
+    pub display_name: String,
+    pub input_cost_per_million: String,
+    pub output_cost_per_million: String,
+    pub cache_read_cost_per_million: String,
+    pub cache_creation_cost_per_million: String,
+}
+
 /// 模型统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -339,6 +410,188 @@ impl Database {
         Ok(stats)
     }
 
+    /// 从按天汇总表获取最近 `days` 天（含今天）各供应商的流量，按请求数倒序
+    pub fn get_provider_usage(
+        &self,
+        app_type: &str,
+        days: u32,
+    ) -> Result<Vec<ProviderUsage>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let offset = format!("-{} days", days.saturating_sub(1));
+
+        let mut stmt = conn.prepare(
+            "SELECT u.provider_id, p.name,
+                SUM(u.request_count), SUM(u.error_count), SUM(u.input_tokens), SUM(u.output_tokens),
+                SUM(u.cache_read_tokens), SUM(u.cache_creation_tokens), SUM(u.cost_usd)
+             FROM provider_usage_daily u
+             LEFT JOIN providers p ON u.provider_id = p.id AND u.app_type = p.app_type
+             WHERE u.app_type = ?1 AND u.day >= date('now', 'localtime', ?2)
+             GROUP BY u.provider_id
+             ORDER BY SUM(u.request_count) DESC, u.provider_id ASC",
+        )?;
+        let rows = stmt.query_map(params![app_type, offset], |row| {
+            Ok(ProviderUsage {
+                provider_id: row.get(0)?,
+                provider_name: row
+                    .get::<_, Option<String>>(1)?
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                request_count: row.get::<_, i64>(2)? as u64,
+                error_count: row.get::<_, i64>(3)? as u64,
+                input_tokens: row.get::<_, i64>(4)? as u64,
+                output_tokens: row.get::<_, i64>(5)? as u64,
+                cache_read_tokens: row.get::<_, i64>(6)? as u64,
+                cache_creation_tokens: row.get::<_, i64>(7)? as u64,
+                cost_usd: row.get(8)?,
+            })
+        })?;
+
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+        Ok(usage)
+    }
+
+    /// 从按天汇总表获取最近 `days` 天（含今天）每天的流量，按日期倒序
+    pub fn get_daily_usage(&self, app_type: &str, days: u32) -> Result<Vec<DailyUsage>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let offset = format!("-{} days", days.saturating_sub(1));
+
+        let mut stmt = conn.prepare(
+            "SELECT day, SUM(request_count), SUM(error_count), SUM(input_tokens),
+                SUM(output_tokens), SUM(cache_read_tokens), SUM(cache_creation_tokens),
+                SUM(cost_usd)
+             FROM provider_usage_daily
+             WHERE app_type = ?1 AND day >= date('now', 'localtime', ?2)
+             GROUP BY day
+             ORDER BY day DESC",
+        )?;
+        let rows = stmt.query_map(params![app_type, offset], |row| {
+            Ok(DailyUsage {
+                day: row.get(0)?,
+                request_count: row.get::<_, i64>(1)? as u64,
+                error_count: row.get::<_, i64>(2)? as u64,
+                input_tokens: row.get::<_, i64>(3)? as u64,
+                output_tokens: row.get::<_, i64>(4)? as u64,
+                cache_read_tokens: row.get::<_, i64>(5)? as u64,
+                cache_creation_tokens: row.get::<_, i64>(6)? as u64,
+                cost_usd: row.get(7)?,
+            })
+        })?;
+
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+        Ok(usage)
+    }
+
+    /// 按模型汇总最近 `days` 天（含今天）的 token 用量，按请求数降序
+    pub fn get_model_usage(&self, app_type: &str, days: u32) -> Result<Vec<ModelUsage>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let offset = format!("-{} days", days.saturating_sub(1));
+
+        let mut stmt = conn.prepare(
+            "SELECT model, SUM(request_count), SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_read_tokens), SUM(cache_creation_tokens), SUM(cost_usd)
+             FROM model_usage_daily
+             WHERE app_type = ?1 AND day >= date('now', 'localtime', ?2)
+             GROUP BY model
+             ORDER BY SUM(request_count) DESC, model",
+        )?;
+        let rows = stmt.query_map(params![app_type, offset], |row| {
+            Ok(ModelUsage {
+                model: row.get(0)?,
+                request_count: row.get::<_, i64>(1)? as u64,
+                input_tokens: row.get::<_, i64>(2)? as u64,
+                output_tokens: row.get::<_, i64>(3)? as u64,
+                cache_read_tokens: row.get::<_, i64>(4)? as u64,
+                cache_creation_tokens: row.get::<_, i64>(5)? as u64,
+                cost_usd: row.get(6)?,
+            })
+        })?;
+
+        let mut usage = Vec::new();
+        for row in rows {
+            usage.push(row?);
+        }
+        Ok(usage)
+    }
+
+    /// 列出全部模型定价，按显示名称排序
+    pub fn list_model_prices(&self) -> Result<Vec<ModelPrice>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn.prepare(
+            "SELECT model_id, display_name, input_cost_per_million, output_cost_per_million,
+                cache_read_cost_per_million, cache_creation_cost_per_million
+             FROM model_pricing
+             ORDER BY display_name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ModelPrice {
+                model_id: row.get(0)?,
+                display_name: row.get(1)?,
+                input_cost_per_million: row.get(2)?,
+                output_cost_per_million: row.get(3)?,
+                cache_read_cost_per_million: row.get(4)?,
+                cache_creation_cost_per_million: row.get(5)?,
+            })
+        })?;
+
+        let mut prices = Vec::new();
+        for row in rows {
+            prices.push(row?);
+        }
+        Ok(prices)
+    }
+
+    /// 新增或覆盖模型定价；价格必须是非负数字
+    pub fn upsert_model_price(&self, price: &ModelPrice) -> Result<(), AppError> {
+        if price.model_id.trim().is_empty() {
+            return Err(AppError::InvalidInput("模型 ID 不能为空".to_string()));
+        }
+        for value in [
+            &price.input_cost_per_million,
+            &price.output_cost_per_million,
+            &price.cache_read_cost_per_million,
+            &price.cache_creation_cost_per_million,
+        ] {
+            match rust_decimal::Decimal::from_str(value.trim()) {
+                Ok(d) if !d.is_sign_negative() => {}
+                _ => return Err(AppError::InvalidInput(format!("无效的价格: {value}"))),
+            }
+        }
+
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO model_pricing (
+                model_id, display_name, input_cost_per_million, output_cost_per_million,
+                cache_read_cost_per_million, cache_creation_cost_per_million
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                price.model_id.trim(),
+                price.display_name.trim(),
+                price.input_cost_per_million.trim(),
+                price.output_cost_per_million.trim(),
+                price.cache_read_cost_per_million.trim(),
+                price.cache_creation_cost_per_million.trim(),
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("更新模型定价失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 删除模型定价
+    pub fn delete_model_price(&self, model_id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "DELETE FROM model_pricing WHERE model_id = ?1",
+            params![model_id],
+        )
+        .map_err(|e| AppError::Database(format!("删除模型定价失败: {e}")))?;
+        Ok(())
+    }
+
     /// 获取模型统计
     pub fn get_model_stats(&self) -> Result<Vec<ModelStats>, AppError> {
         let conn = lock_conn!(self.conn);
@@ -571,72 +824,124 @@ impl Database {
     ) -> Result<ProviderLimitStatus, AppError> {
         let conn = lock_conn!(self.conn);
 
-        // 获取 provider 的限额设置
-        let (limit_daily, limit_monthly) = conn
+        // 获取 provider 的名称与限额设置
+        let (provider_name, meta) = conn
             .query_row(
-                "SELECT meta FROM providers WHERE id = ? AND app_type = ?",
+                "SELECT name, meta FROM providers WHERE id = ? AND app_type = ?",
                 params![provider_id, app_type],
-                |row| {
-                    let meta_str: String = row.get(0)?;
-                    Ok(meta_str)
-                },
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
             )
-            .ok()
-            .and_then(|meta_str| serde_json::from_str::<serde_json::Value>(&meta_str).ok())
-            .map(|meta| {
-                let daily = meta
-                    .get("limitDailyUsd")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| s.parse::<f64>().ok());
-                let monthly = meta
-                    .get("limitMonthlyUsd")
-                    .and_then(|v| v.as_str())
-                    .and_then(|s| s.parse::<f64>().ok());
-                (daily, monthly)
+            .map(|(name, meta_str)| {
+                let meta = serde_json::from_str::<crate::provider::ProviderMeta>(&meta_str)
+                    .unwrap_or_default();
+                (name, meta)
             })
-            .unwrap_or((None, None));
+            .unwrap_or_else(|_| (provider_id.to_string(), Default::default()));
+        let limit_daily = meta
+            .limit_daily_usd
+            .as_deref()
+            .and_then(|s| s.parse::<f64>().ok());
+        let limit_monthly = meta
+            .limit_monthly_usd
+            .as_deref()
+            .and_then(|s| s.parse::<f64>().ok());
 
         // 计算今日使用量
-        let daily_usage: f64 = conn
+        let (daily_usage, daily_tokens): (f64, i64) = conn
             .query_row(
-                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                    COALESCE(SUM(input_tokens + output_tokens), 0)
              FROM proxy_request_logs
              WHERE provider_id = ? AND app_type = ?
                AND date(datetime(created_at, 'unixepoch', 'localtime')) = date('now', 'localtime')",
                 params![provider_id, app_type],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
-            .unwrap_or(0.0);
+            .unwrap_or((0.0, 0));
 
         // 计算本月使用量
-        let monthly_usage: f64 = conn
+        let (monthly_usage, monthly_tokens): (f64, i64) = conn
             .query_row(
-                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                    COALESCE(SUM(input_tokens + output_tokens), 0)
              FROM proxy_request_logs
              WHERE provider_id = ? AND app_type = ?
                AND strftime('%Y-%m', datetime(created_at, 'unixepoch', 'localtime')) = strftime('%Y-%m', 'now', 'localtime')",
                 params![provider_id, app_type],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
-            .unwrap_or(0.0);
+            .unwrap_or((0.0, 0));
+        let daily_tokens = daily_tokens as u64;
+        let monthly_tokens = monthly_tokens as u64;
 
         let daily_exceeded = limit_daily
             .map(|limit| daily_usage >= limit)
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || meta
+                .limit_daily_tokens
+                .is_some_and(|limit| daily_tokens >= limit);
         let monthly_exceeded = limit_monthly
             .map(|limit| monthly_usage >= limit)
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || meta
+                .limit_monthly_tokens
+                .is_some_and(|limit| monthly_tokens >= limit);
 
         Ok(ProviderLimitStatus {
             provider_id: provider_id.to_string(),
+            provider_name,
             daily_usage: format!("{daily_usage:.6}"),
             daily_limit: limit_daily.map(|l| format!("{l:.2}")),
+            daily_tokens,
+            daily_token_limit: meta.limit_daily_tokens,
             daily_exceeded,
             monthly_usage: format!("{monthly_usage:.6}"),
             monthly_limit: limit_monthly.map(|l| format!("{l:.2}")),
+            monthly_tokens,
+            monthly_token_limit: meta.limit_monthly_tokens,
             monthly_exceeded,
+            pause_on_limit: meta.pause_on_limit.unwrap_or(false),
         })
     }
+
+    /// 列出设置了任一限额的供应商及其当前限额状态
+    pub fn list_provider_limit_statuses(
+        &self,
+        app_type: &str,
+    ) -> Result<Vec<ProviderLimitStatus>, AppError> {
+        let ids: Vec<String> = self
+            .get_all_providers(app_type)?
+            .into_values()
+            .filter(|p| p.meta.as_ref().is_some_and(has_limits))
+            .map(|p| p.id)
+            .collect();
+        ids.iter()
+            .map(|id| self.check_provider_limits(id, app_type))
+            .collect()
+    }
+
+    /// 供应商开启了“超限暂停”且已超出限额时返回 true，代理据此跳过该供应商
+    pub fn is_provider_paused_by_limit(
+        &self,
+        provider: &crate::provider::Provider,
+        app_type: &str,
+    ) -> bool {
+        let pause = provider
+            .meta
+            .as_ref()
+            .is_some_and(|m| m.pause_on_limit == Some(true) && has_limits(m));
+        pause
+            && self
+                .check_provider_limits(&provider.id, app_type)
+                .is_ok_and(|status| status.is_exceeded())
+    }
+}
+
+fn has_limits(meta: &crate::provider::ProviderMeta) -> bool {
+    meta.limit_daily_usd.is_some()
+        || meta.limit_monthly_usd.is_some()
+        || meta.limit_daily_tokens.is_some()
+        || meta.limit_monthly_tokens.is_some()
 }
 
 /// Provider 限额状态
@@ -644,12 +949,25 @@ impl Database {
 #[serde(rename_all = "camelCase")]
 pub struct ProviderLimitStatus {
     pub provider_id: String,
+    pub provider_name: String,
     pub daily_usage: String,
     pub daily_limit: Option<String>,
+    pub daily_tokens: u64,
+    pub daily_token_limit: Option<u64>,
     pub daily_exceeded: bool,
     pub monthly_usage: String,
     pub monthly_limit: Option<String>,
+    pub monthly_tokens: u64,
+    pub monthly_token_limit: Option<u64>,
     pub monthly_exceeded: bool,
+    /// 超限后是否暂停路由
+    pub pause_on_limit: bool,
+}
+
+impl ProviderLimitStatus {
+    pub fn is_exceeded(&self) -> bool {
+        self.daily_exceeded || self.monthly_exceeded
+    }
 }
 
 #[derive(Clone)]
@@ -876,6 +1194,174 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_provider_usage_from_daily_rollup() -> Result<(), AppError> {
+        use crate::proxy::usage::logger::UsageLogger;
+        use crate::proxy::usage::parser::TokenUsage;
+
+        let db = Database::memory()?;
+        let logger = UsageLogger::new(&db);
+        let usage = TokenUsage {
+            input_tokens: 100,
+            output_tokens: 40,
+            cache_read_tokens: 300,
+            ..Default::default()
+        };
+        for request_id in ["req1", "req2"] {
+            logger.log_with_calculation(
+                request_id.to_string(),
+                "p1".to_string(),
+                "claude".to_string(),
+                "claude-3".to_string(),
+                usage.clone(),
+                rust_decimal::Decimal::from(1),
+                100,
+                None,
+                200,
+                None,
+                None,
+                false,
+            )?;
+        }
+        logger.log_error(
+            "req3".to_string(),
+            "p1".to_string(),
+            "claude".to_string(),
+            "claude-3".to_string(),
+            502,
+            "Bad Gateway".to_string(),
+            50,
+        )?;
+        logger.log_error(
+            "req4".to_string(),
+            "p2".to_string(),
+            "codex".to_string(),
+            "gpt-5".to_string(),
+            500,
+            "boom".to_string(),
+            50,
+        )?;
+
+        let usage = db.get_provider_usage("claude", 1)?;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].provider_id, "p1");
+        assert_eq!(usage[0].request_count, 3);
+        assert_eq!(usage[0].error_count, 1);
+        assert_eq!(usage[0].input_tokens, 200);
+        assert_eq!(usage[0].output_tokens, 80);
+        assert_eq!(usage[0].cache_read_tokens, 600);
+        assert_eq!(usage[0].cache_creation_tokens, 0);
+
+        let daily = db.get_daily_usage("claude", 7)?;
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].request_count, 3);
+        assert_eq!(daily[0].error_count, 1);
+        assert_eq!(daily[0].cache_read_tokens, 600);
+
+        let models = db.get_model_usage("claude", 1)?;
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model, "claude-3");
+        assert_eq!(models[0].request_count, 3);
+        assert_eq!(models[0].cache_read_tokens, 600);
+        assert_eq!(models[0].cache_hit_rate(), 75.0);
+
+        // 日志清理后汇总仍保留
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute("DELETE FROM proxy_request_logs", [])?;
+        }
+        assert_eq!(db.get_provider_usage("claude", 30)?[0].request_count, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_upsert_model_price_validates_and_replaces() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let mut price = ModelPrice {
+            model_id: "my-relay-model".to_string(),
+            display_name: "My Relay Model".to_string(),
+            input_cost_per_million: "1.5".to_string(),
+            output_cost_per_million: "6".to_string(),
+            cache_read_cost_per_million: "0".to_string(),
+            cache_creation_cost_per_million: "0".to_string(),
+        };
+        db.upsert_model_price(&price)?;
+        price.output_cost_per_million = "7.5".to_string();
+        db.upsert_model_price(&price)?;
+
+        let prices = db.list_model_prices()?;
+        let saved = prices
+            .iter()
+            .find(|p| p.model_id == "my-relay-model")
+            .expect("price saved");
+        assert_eq!(saved.output_cost_per_million, "7.5");
+
+        price.input_cost_per_million = "-1".to_string();
+        assert!(db.upsert_model_price(&price).is_err());
+        price.input_cost_per_million = "abc".to_string();
+        assert!(db.upsert_model_price(&price).is_err());
+
+        db.delete_model_price("my-relay-model")?;
+        assert!(db
+            .list_model_prices()?
+            .iter()
+            .all(|p| p.model_id != "my-relay-model"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_budget_pauses_provider() -> Result<(), AppError> {
+        use crate::provider::{Provider, ProviderMeta};
+        use crate::proxy::usage::logger::UsageLogger;
+        use crate::proxy::usage::parser::TokenUsage;
+
+        let db = Database::memory()?;
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "Relay".to_string(),
+            serde_json::json!({}),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            limit_daily_tokens: Some(100),
+            ..Default::default()
+        });
+        db.save_provider("claude", &provider)?;
+
+        UsageLogger::new(&db).log_with_calculation(
+            "req1".to_string(),
+            "p1".to_string(),
+            "claude".to_string(),
+            "claude-3".to_string(),
+            TokenUsage {
+                input_tokens: 80,
+                output_tokens: 40,
+                ..Default::default()
+            },
+            rust_decimal::Decimal::from(1),
+            100,
+            None,
+            200,
+            None,
+            None,
+            false,
+        )?;
+
+        let statuses = db.list_provider_limit_statuses("claude")?;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].provider_name, "Relay");
+        assert_eq!(statuses[0].daily_tokens, 120);
+        assert!(statuses[0].daily_exceeded);
+        assert!(!statuses[0].monthly_exceeded);
+
+        // 仅提醒时不影响路由
+        assert!(!db.is_provider_paused_by_limit(&provider, "claude"));
+        provider.meta.as_mut().unwrap().pause_on_limit = Some(true);
+        assert!(db.is_provider_paused_by_limit(&provider, "claude"));
+        Ok(())
+    }
+
     #[test]
     fn test_get_model_stats() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -1,22 +1,136 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use indexmap::IndexMap;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Paragraph, Tabs};
+use ratatui::widgets::{Block, Borders, Paragraph, Tabs, Wrap};
+use tokio::sync::mpsc;
 
+use super::command::{self, Command};
+use super::keymap::{self, Action, KeyScope, Keymap};
 use super::terminal::{self, Tui};
-use super::theme::Theme;
-use super::views::{McpView, ProviderForm, ProvidersView, ProxyView, SettingsView, View};
-use cc_switch_lib::{AppState, AppType};
+use super::theme::{ColorSupport, Theme, ThemePreset};
+use super::views::{
+    access_log_label, auto_backup_label, auto_refresh_label, desktop_notifications_label,
+    history_limit_label, idle_lock_label, is_http_url, load_budgets, log_destination_label,
+    AppPortForm, BackupForm, BudgetForm, CircuitBreakerForm, ConcurrencyForm, ConfirmDialog,
+    Connectivity, CorsForm, DnsForm, EndpointsView, EnvImportDialog, ExportForm,
+    FailoverEventsView, HeaderRulesForm, HealthCheckForm, HistoryPage, HistoryView, HybridForm,
+    ImportForm, ListenForm, LockScreen, LogsView, MaintenanceDialog, MasterPasswordChange,
+    MasterPasswordForm, McpCheck, McpExportForm, McpForm, McpPasteForm, McpView, ModelAliasForm,
+    PricingEditor, PromptEditor, PromptsView, ProviderEnvForm, ProviderForm, ProvidersData,
+    ProvidersView, ProxyData, ProxyView, RateLimitForm, ReplayDialog, ReplayRequest,
+    ResponseCacheForm, RestoreDialog, SettingsView, ShadowForm, SizeLimitForm, SpeedtestForm,
+    SwitchBackForm, SwitchPreview, TlsForm, UnixSocketForm, UpstreamProxyForm, UsageData,
+    UsageExportForm, UsageView, View, WebhookForm, WeightForm,
+};
+use super::widgets::TextInput;
+use cc_switch_lib::{
+    AppState, AppType, CircuitBreakerOverride, ConfigService, DnsConfig, HealthCheckConfig,
+    ImportSummary, McpServer, McpService, Prompt, PromptService, Provider, ProviderService,
+    ProxyTlsConfig, ReplayResponse, ResponseCacheConfig, SizeLimitConfig, SwitchBackConfig,
+    SwitchBackMode, UiPreferences, UnixSocketConfig, WebhookConfig,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+const TAB_TITLES: [&str; 8] = [
+    "[1]Providers",
+    "[2]MCP",
+    "[3]Proxy",
+    "[4]Settings",
+    "[H]History",
+    "[L]Logs",
+    "[P]Prompts",
+    "[U]Usage",
+];
+
+/// 两次点击被视为双击的最大间隔
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// 状态栏提示消息的显示时长
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// 正常布局所需的最小终端尺寸
+const MIN_WIDTH: u16 = 80;
+const MIN_HEIGHT: u16 = 24;
+
+/// 代理面板统计的刷新间隔
+const DASHBOARD_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 状态栏实时状态的刷新间隔
+const STATUS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 状态栏右侧展示的实时状态
+#[derive(Default)]
+struct LiveStatus {
+    provider_name: Option<String>,
+    proxy_running: bool,
+    proxy_port: u16,
+    open_breakers: usize,
+}
+
+/// 状态栏中短暂显示的提示消息
+struct Toast {
+    message: String,
+    is_error: bool,
+    expires_at: Instant,
+}
+
+/// 后台刷新任务的结果
+enum RefreshData {
+    Providers(ProvidersData),
+    Mcp(IndexMap<String, McpServer>),
+    Proxy(Box<ProxyData>),
+    History(HistoryPage),
+    Prompts(IndexMap<String, Prompt>),
+    Usage(UsageData),
+}
+
+/// 后台任务通过 channel 发回事件循环的结果
+enum BackgroundEvent {
+    /// 视图数据刷新完成，`seq` 用于丢弃过期结果
+    Refresh {
+        seq: u64,
+        view: ActiveView,
+        data: RefreshData,
+    },
+    /// 供应商连通性测试完成
+    Connectivity {
+        app_type: AppType,
+        provider_id: String,
+        result: Connectivity,
+    },
+    /// 端点延迟测试完成
+    LatencyTested(Result<(), String>),
+    /// MCP 服务器连通性检查完成
+    McpChecked { server_id: String, result: McpCheck },
+    /// 请求重放完成
+    Replayed(Result<ReplayResponse, String>),
+}
+
+/// 需要用户二次确认后才执行的操作
+enum Confirmation {
+    /// 批量启用/停用当前应用下列出的所有 MCP 服务器
+    McpBulkToggle { app: AppType, enabled: bool },
+    /// 用选中的备份替换当前数据库
+    RestoreBackup { path: PathBuf, name: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ActiveView {
     Providers,
     Mcp,
     Proxy,
     Settings,
+    History,
+    Logs,
+    Prompts,
+    Usage,
 }
 
 impl ActiveView {
@@ -26,6 +140,10 @@ impl ActiveView {
             Self::Mcp => 1,
             Self::Proxy => 2,
             Self::Settings => 3,
+            Self::History => 4,
+            Self::Logs => 5,
+            Self::Prompts => 6,
+            Self::Usage => 7,
         }
     }
 
@@ -35,48 +153,743 @@ impl ActiveView {
             1 => Self::Mcp,
             2 => Self::Proxy,
             3 => Self::Settings,
+            4 => Self::History,
+            5 => Self::Logs,
+            6 => Self::Prompts,
+            7 => Self::Usage,
             _ => Self::Providers,
         }
     }
 }
 
+/// 列表导航动作（由按键序列解析得到）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavAction {
+    Up(usize),
+    Down(usize),
+    Top,
+    Bottom,
+}
+
+impl NavAction {
+    /// 根据当前选中项与列表长度计算新的选中索引
+    pub fn apply(self, selected: Option<usize>, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        // 尚未选中时，`j` 选中第一行，`5j` 选中第五行
+        let Some(current) = selected.map(|i| i.min(len - 1)) else {
+            return Some(match self {
+                Self::Down(n) => n.saturating_sub(1).min(len - 1),
+                Self::Up(_) | Self::Top => 0,
+                Self::Bottom => len - 1,
+            });
+        };
+        let next = match self {
+            Self::Up(n) => current.saturating_sub(n),
+            Self::Down(n) => current.saturating_add(n).min(len - 1),
+            Self::Top => 0,
+            Self::Bottom => len - 1,
+        };
+        Some(next)
+    }
+}
+
+/// 按键序列解析结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySeqResult {
+    /// 序列尚未完成，等待更多按键
+    Pending,
+    /// 解析出一个导航动作
+    Nav(NavAction),
+    /// 不属于导航序列，交由后续逻辑处理
+    Passthrough,
+    /// 计数开头的数字后跟的不是移动，先执行该数字绑定的动作，再处理当前按键
+    Interrupted(Action),
+}
+
+/// 弹窗内按键的解析结果
+enum PopupKey {
+    Nav(NavAction),
+    Action(Action),
+    /// 等待后续按键或未绑定动作
+    None,
+}
+
+/// 绑定了动作的数字等待后续按键的时长，超时后执行其原本的动作
+pub const DIGIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Vim 风格按键序列解析器，支持 `gg` 及数字前缀（如 `5j`）
+///
+/// 任意非零数字都会开始计数，之后的数字追加到计数中。数字本身绑定了动作时
+/// （默认 1-4 切换视图），若 [`DIGIT_TIMEOUT`] 内未跟上移动键，仍执行原动作。
+#[derive(Debug, Default)]
+pub struct KeySequence {
+    count: Option<usize>,
+    pending_g: bool,
+    /// 开始计数的数字原本绑定的动作及按下时间
+    deferred: Option<(Action, Instant)>,
+}
+
+impl KeySequence {
+    /// `action` 为该按键在 [`Keymap`] 中解析出的动作
+    pub fn feed(&mut self, key: KeyCode, action: Option<Action>) -> KeySeqResult {
+        if let KeyCode::Char(c) = key {
+            if let Some(digit) = c.to_digit(10) {
+                let digit = digit as usize;
+                if let Some(count) = self.count {
+                    self.count = Some(count.saturating_mul(10).saturating_add(digit));
+                    self.pending_g = false;
+                    self.deferred = None;
+                    return KeySeqResult::Pending;
+                }
+                if digit > 0 {
+                    self.count = Some(digit);
+                    self.pending_g = false;
+                    self.deferred = action.map(|action| (action, Instant::now()));
+                    return KeySeqResult::Pending;
+                }
+            }
+        }
+
+        let count = self.count.take().unwrap_or(1);
+        let pending_g = std::mem::take(&mut self.pending_g);
+        if let Some((deferred, _)) = self.deferred.take() {
+            let is_motion = matches!(
+                action,
+                Some(Action::Up | Action::Down | Action::Top | Action::Bottom)
+            ) || (key == KeyCode::Char('g') && action.is_none());
+            if !is_motion {
+                return KeySeqResult::Interrupted(deferred);
+            }
+        }
+
+        match (key, action) {
+            (KeyCode::Char('g'), None) if pending_g => KeySeqResult::Nav(NavAction::Top),
+            (KeyCode::Char('g'), None) => {
+                self.pending_g = true;
+                KeySeqResult::Pending
+            }
+            (_, Some(Action::Top)) => KeySeqResult::Nav(NavAction::Top),
+            (_, Some(Action::Bottom)) => KeySeqResult::Nav(NavAction::Bottom),
+            (_, Some(Action::Down)) => KeySeqResult::Nav(NavAction::Down(count)),
+            (_, Some(Action::Up)) => KeySeqResult::Nav(NavAction::Up(count)),
+            _ => KeySeqResult::Passthrough,
+        }
+    }
+
+    /// 数字等待超时后取出其原本的动作，同时放弃计数
+    pub fn expire(&mut self, now: Instant) -> Option<Action> {
+        let (_, pressed_at) = self.deferred?;
+        if now.duration_since(pressed_at) < DIGIT_TIMEOUT {
+            return None;
+        }
+        self.count = None;
+        self.deferred.take().map(|(action, _)| action)
+    }
+
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.pending_g = false;
+        self.deferred = None;
+    }
+}
+
 pub struct App {
     pub state: Arc<AppState>,
     pub theme: Theme,
+    color_support: ColorSupport,
+    keymap: Keymap,
     pub active_view: ActiveView,
     pub active_app: AppType,
     pub should_quit: bool,
+    key_seq: KeySequence,
+    tabs_area: Rect,
+    /// 终端尺寸低于最小值时只显示提示界面
+    too_small: bool,
+    last_click: Option<(Instant, u16)>,
+    toast: Option<Toast>,
+    /// `:` 命令行输入，处于命令模式时为 Some
+    command_line: Option<TextInput>,
+    live_status: LiveStatus,
+    status_refreshed_at: Option<Instant>,
+    /// 已提示过超出预算的供应商（`app:provider_id`），恢复后移除以便再次提醒
+    over_budget: HashSet<String>,
+    events_tx: mpsc::UnboundedSender<BackgroundEvent>,
+    events_rx: mpsc::UnboundedReceiver<BackgroundEvent>,
+    /// 每个视图进行中的最近一次刷新序号，结果应用后移除
+    latest_refresh: HashMap<ActiveView, u64>,
+    refresh_seq: u64,
+    /// 最近一次发起数据刷新的时间，用于自动刷新计时
+    refreshed_at: Instant,
+    /// 最近一次键盘或鼠标输入的时间，用于空闲锁屏计时
+    last_input: Instant,
 
     pub providers_view: ProvidersView,
     pub mcp_view: McpView,
     pub proxy_view: ProxyView,
     pub settings_view: SettingsView,
+    pub history_view: HistoryView,
+    pub logs_view: LogsView,
+    pub prompts_view: PromptsView,
+    pub usage_view: UsageView,
     pub provider_form: ProviderForm,
+    pub endpoints_view: EndpointsView,
+    pub budget_form: BudgetForm,
+    pub weight_form: WeightForm,
+    pub concurrency_form: ConcurrencyForm,
+    pub circuit_breaker_form: CircuitBreakerForm,
+    pub health_check_form: HealthCheckForm,
+    pub shadow_form: ShadowForm,
+    pub response_cache_form: ResponseCacheForm,
+    pub upstream_proxy_form: UpstreamProxyForm,
+    pub speedtest_form: SpeedtestForm,
+    pub provider_env_form: ProviderEnvForm,
+    pub dns_form: DnsForm,
+    pub size_limit_form: SizeLimitForm,
+    pub switch_back_form: SwitchBackForm,
+    pub master_password_form: MasterPasswordForm,
+    pub backup_form: BackupForm,
+    pub restore_dialog: RestoreDialog,
+    pub maintenance_dialog: MaintenanceDialog,
+    pub env_import_dialog: EnvImportDialog,
+    pub lock_screen: LockScreen,
+    pub webhook_form: WebhookForm,
+    pub failover_events_view: FailoverEventsView,
+    pub model_alias_form: ModelAliasForm,
+    pub header_rules_form: HeaderRulesForm,
+    pub replay_dialog: ReplayDialog,
+    pub listen_form: ListenForm,
+    pub hybrid_form: HybridForm,
+    pub rate_limit_form: RateLimitForm,
+    pub tls_form: TlsForm,
+    pub cors_form: CorsForm,
+    pub app_port_form: AppPortForm,
+    pub unix_socket_form: UnixSocketForm,
+    pub export_form: ExportForm,
+    pub import_form: ImportForm,
+    pub prompt_editor: PromptEditor,
+    pub mcp_paste_form: McpPasteForm,
+    pub mcp_export_form: McpExportForm,
+    pub mcp_form: McpForm,
+    pub usage_export_form: UsageExportForm,
+    pub pricing_editor: PricingEditor,
+    confirm_dialog: ConfirmDialog<Confirmation>,
+    pub switch_preview: SwitchPreview,
+    /// 演练模式：切换供应商只显示将写入的配置，不修改文件和数据库
+    dry_run: bool,
 }
 
 impl App {
-    pub fn new(state: Arc<AppState>) -> Self {
-        Self {
+    pub fn new(state: Arc<AppState>, color_support: ColorSupport) -> Self {
+        let settings_view = SettingsView::new(state.clone());
+        let (theme, theme_error) = load_theme(settings_view.theme_preset(), color_support);
+        let (keymap, keymap_error) = match Keymap::load_user() {
+            Ok(keymap) => (keymap, None),
+            Err(e) => {
+                log::warn!("Failed to load key bindings, using defaults: {e}");
+                (Keymap::default(), Some(e))
+            }
+        };
+
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+        let mut app = Self {
             state: state.clone(),
-            theme: Theme::default(),
+            theme,
+            color_support,
+            keymap,
             active_view: ActiveView::Providers,
             active_app: AppType::Claude,
             should_quit: false,
+            key_seq: KeySequence::default(),
+            tabs_area: Rect::default(),
+            too_small: false,
+            last_click: None,
+            toast: None,
+            command_line: None,
+            live_status: LiveStatus::default(),
+            over_budget: HashSet::new(),
+            status_refreshed_at: None,
+            events_tx,
+            events_rx,
+            latest_refresh: HashMap::new(),
+            refresh_seq: 0,
+            refreshed_at: Instant::now(),
+            last_input: Instant::now(),
             providers_view: ProvidersView::new(state.clone()),
-            mcp_view: McpView::new(state.clone()),
+            mcp_view: McpView::new(),
             proxy_view: ProxyView::new(state.clone()),
-            settings_view: SettingsView::new(state.clone()),
+            settings_view,
+            history_view: HistoryView::new(),
+            logs_view: LogsView::new(),
+            prompts_view: PromptsView::new(),
+            usage_view: UsageView::new(),
             provider_form: ProviderForm::new(state.clone()),
+            endpoints_view: EndpointsView::new(state.clone()),
+            budget_form: BudgetForm::new(state.clone()),
+            weight_form: WeightForm::new(state.clone()),
+            concurrency_form: ConcurrencyForm::new(state.clone()),
+            circuit_breaker_form: CircuitBreakerForm::new(),
+            health_check_form: HealthCheckForm::new(),
+            shadow_form: ShadowForm::new(state.clone()),
+            response_cache_form: ResponseCacheForm::new(),
+            upstream_proxy_form: UpstreamProxyForm::new(state.clone()),
+            speedtest_form: SpeedtestForm::new(state.clone()),
+            provider_env_form: ProviderEnvForm::new(),
+            dns_form: DnsForm::new(),
+            size_limit_form: SizeLimitForm::new(),
+            switch_back_form: SwitchBackForm::new(),
+            master_password_form: MasterPasswordForm::new(),
+            backup_form: BackupForm::new(),
+            restore_dialog: RestoreDialog::new(state.clone()),
+            maintenance_dialog: MaintenanceDialog::new(state.clone()),
+            env_import_dialog: EnvImportDialog::new(state.clone()),
+            lock_screen: LockScreen::new(),
+            webhook_form: WebhookForm::new(),
+            failover_events_view: FailoverEventsView::new(state.clone()),
+            model_alias_form: ModelAliasForm::new(state.clone()),
+            header_rules_form: HeaderRulesForm::new(state.clone()),
+            replay_dialog: ReplayDialog::new(),
+            listen_form: ListenForm::new(),
+            hybrid_form: HybridForm::new(state.clone()),
+            rate_limit_form: RateLimitForm::new(state.clone()),
+            tls_form: TlsForm::new(),
+            cors_form: CorsForm::new(),
+            app_port_form: AppPortForm::new(),
+            unix_socket_form: UnixSocketForm::new(),
+            export_form: ExportForm::new(),
+            import_form: ImportForm::new(state.clone()),
+            prompt_editor: PromptEditor::new(state.clone()),
+            mcp_paste_form: McpPasteForm::new(state.clone()),
+            mcp_export_form: McpExportForm::new(),
+            mcp_form: McpForm::new(state.clone()),
+            usage_export_form: UsageExportForm::new(),
+            pricing_editor: PricingEditor::new(state.clone()),
+            confirm_dialog: ConfirmDialog::new(),
+            switch_preview: SwitchPreview::new(),
+            dry_run: false,
+        };
+
+        let logging_error = app
+            .settings_view
+            .apply_logging()
+            .err()
+            .map(|e| format!("Failed to set up logging: {e}"));
+        if let Some(e) = theme_error.or(keymap_error).or(logging_error) {
+            app.show_error(e);
         }
+        app
     }
 
-    pub async fn refresh_data(&mut self) {
-        match self.active_view {
-            ActiveView::Providers => self.providers_view.refresh(self.active_app.clone()).await,
-            ActiveView::Mcp => self.mcp_view.refresh().await,
-            ActiveView::Proxy => self.proxy_view.refresh().await,
-            ActiveView::Settings => {}
+    fn show_toast(&mut self, message: impl Into<String>) {
+        self.toast = Some(Toast {
+            message: cc_switch_lib::redact(&message.into()),
+            is_error: false,
+            expires_at: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    fn show_error(&mut self, message: impl Into<String>) {
+        self.toast = Some(Toast {
+            message: cc_switch_lib::redact(&message.into()),
+            is_error: true,
+            expires_at: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// 在后台任务中重新加载当前视图的数据，结果经 channel 回到事件循环
+    pub fn refresh_data(&mut self) {
+        self.status_refreshed_at = None;
+        self.refreshed_at = Instant::now();
+        self.spawn_refresh(self.active_view, true);
+    }
+
+    /// 重新加载所有视图的数据及状态栏状态（F5 / Ctrl+R）
+    pub fn reload_all(&mut self) {
+        self.status_refreshed_at = None;
+        self.refreshed_at = Instant::now();
+        for view in [ActiveView::Providers, ActiveView::Mcp, ActiveView::Proxy] {
+            self.spawn_refresh(view, true);
+        }
+        self.show_toast("Reloaded");
+    }
+
+    /// `show_loading` 为 false 时静默刷新（定时刷新不闪烁加载动画）
+    fn spawn_refresh(&mut self, view: ActiveView, show_loading: bool) {
+        if view == ActiveView::Settings {
+            return;
+        }
+
+        self.refresh_seq += 1;
+        let seq = self.refresh_seq;
+        self.latest_refresh.insert(view, seq);
+
+        let state = self.state.clone();
+        let tx = self.events_tx.clone();
+        match view {
+            ActiveView::Providers => {
+                self.providers_view.loading |= show_loading;
+                let app_type = self.active_app.clone();
+                tokio::task::spawn_blocking(move || {
+                    let data = RefreshData::Providers(ProvidersView::load(&state, app_type));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::Mcp => {
+                self.mcp_view.loading |= show_loading;
+                tokio::task::spawn_blocking(move || {
+                    let data = RefreshData::Mcp(McpView::load(&state));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::Proxy => {
+                self.proxy_view.loading |= show_loading;
+                tokio::spawn(async move {
+                    let data = RefreshData::Proxy(Box::new(ProxyView::load(&state).await));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::History => {
+                self.history_view.loading |= show_loading;
+                let (filter, page) = self.history_view.query();
+                tokio::task::spawn_blocking(move || {
+                    let data = RefreshData::History(HistoryView::load(&state, &filter, page));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::Prompts => {
+                self.prompts_view.loading |= show_loading;
+                let app_type = self.active_app.clone();
+                tokio::task::spawn_blocking(move || {
+                    let data = RefreshData::Prompts(PromptsView::load(&state, app_type));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::Usage => {
+                self.usage_view.loading |= show_loading;
+                let app_type = self.active_app.clone();
+                let range = self.usage_view.range();
+                tokio::task::spawn_blocking(move || {
+                    let data = RefreshData::Usage(UsageView::load(&state, app_type, range));
+                    let _ = tx.send(BackgroundEvent::Refresh { seq, view, data });
+                });
+            }
+            ActiveView::Settings | ActiveView::Logs => {}
+        }
+    }
+
+    /// 取出已完成的后台任务结果并应用到对应视图
+    fn drain_background_events(&mut self) {
+        while let Ok(event) = self.events_rx.try_recv() {
+            match event {
+                BackgroundEvent::Refresh { seq, view, data } => {
+                    if self.latest_refresh.get(&view) != Some(&seq) {
+                        continue;
+                    }
+                    self.latest_refresh.remove(&view);
+                    match data {
+                        RefreshData::Providers(data) => self.providers_view.apply(data),
+                        RefreshData::Mcp(servers) => self.mcp_view.apply(servers),
+                        RefreshData::Prompts(prompts) => self.prompts_view.apply(prompts),
+                        RefreshData::Usage(data) => self.usage_view.apply(data),
+                        RefreshData::Proxy(data) => self.proxy_view.apply(*data),
+                        RefreshData::History(page) => {
+                            if self.history_view.apply(page) {
+                                self.spawn_refresh(ActiveView::History, false);
+                            }
+                        }
+                    }
+                }
+                BackgroundEvent::Connectivity {
+                    app_type,
+                    provider_id,
+                    result,
+                } => self
+                    .providers_view
+                    .set_connectivity(&app_type, provider_id, result),
+                BackgroundEvent::LatencyTested(Ok(())) => {
+                    self.show_toast("Endpoint latency updated");
+                    self.refresh_data();
+                }
+                BackgroundEvent::LatencyTested(Err(e)) => {
+                    self.show_error(format!("Latency test failed: {e}"));
+                }
+                BackgroundEvent::Replayed(result) => self.replay_dialog.set_result(result),
+                BackgroundEvent::McpChecked { server_id, result } => {
+                    match &result {
+                        McpCheck::Ok { tools, .. } => {
+                            self.show_toast(format!("{server_id}: OK, {tools} tools"))
+                        }
+                        McpCheck::Failed(e) => self.show_error(format!("{server_id}: {e}")),
+                        McpCheck::Testing => {}
+                    }
+                    self.mcp_view.set_check(server_id, result);
+                }
+            }
+        }
+    }
+
+    /// 在后台测试当前应用所有端点的延迟，完成后刷新延迟列
+    fn test_endpoint_latency(&mut self) {
+        let state = self.state.clone();
+        let app_type = self.active_app.clone();
+        let tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = state
+                .proxy_service
+                .test_endpoint_latency_now(app_type.as_str())
+                .await;
+            let _ = tx.send(BackgroundEvent::LatencyTested(result));
+        });
+        self.show_toast("Testing endpoint latency…");
+    }
+
+    /// 打开重放弹窗，选择供应商重新发送选中的已捕获请求
+    fn open_replay(&mut self) {
+        let Some(entry) = self.history_view.selected() else {
+            return;
+        };
+        let Some(capture) = entry.capture.clone() else {
+            self.show_error("Request body was not captured; enable request capture in Settings");
+            return;
+        };
+        let Ok(app_type) = entry.app_type.parse::<AppType>() else {
+            self.show_error(format!("Unknown app type: {}", entry.app_type));
+            return;
+        };
+        let provider_id = entry.provider_id.clone();
+        let providers = match self.state.db.get_all_providers(app_type.as_str()) {
+            Ok(providers) => providers.into_values().collect(),
+            Err(e) => {
+                self.show_error(format!("Failed to load providers: {e}"));
+                return;
+            }
+        };
+        self.replay_dialog
+            .open(app_type, capture, providers, &provider_id);
+    }
+
+    /// 在后台通过选定的供应商重放请求，结果显示在重放弹窗中
+    fn spawn_replay(&mut self, request: ReplayRequest) {
+        let state = self.state.clone();
+        let tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = state
+                .proxy_service
+                .replay_request(&request.app_type, &request.provider, &request.capture)
+                .await;
+            let _ = tx.send(BackgroundEvent::Replayed(result));
+        });
+    }
+
+    /// 在后台对选中的 MCP 服务器执行握手检查
+    fn test_selected_mcp_server(&mut self) {
+        let Some((server_id, check)) = self.mcp_view.start_check() else {
+            return;
+        };
+        let tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = check.await;
+            let _ = tx.send(BackgroundEvent::McpChecked { server_id, result });
+        });
+    }
+
+    /// 弹出确认框，确认后批量切换列出的 MCP 服务器在当前应用的启用状态
+    fn confirm_mcp_bulk_toggle(&mut self, enabled: bool) {
+        let count = self.mcp_view.servers().len();
+        if count == 0 {
+            self.show_toast("No MCP servers to update");
+            return;
+        }
+        let (title, verb) = if enabled {
+            ("Enable All MCP Servers", "Enable")
+        } else {
+            ("Disable All MCP Servers", "Disable")
+        };
+        let app = self.active_app.clone();
+        let message = format!(
+            "{verb} all {count} MCP server(s) for {}? The live config will be updated.",
+            app_display_name(&app)
+        );
+        self.confirm_dialog
+            .open(title, message, Confirmation::McpBulkToggle { app, enabled });
+    }
+
+    fn run_confirmation(&mut self, confirmation: Confirmation) {
+        match confirmation {
+            Confirmation::McpBulkToggle { app, enabled } => {
+                let ids: Vec<String> = self.mcp_view.servers().into_iter().map(|s| s.id).collect();
+                let name = app_display_name(&app);
+                match McpService::toggle_app_bulk(&self.state, &ids, app, enabled) {
+                    Ok(changed) => {
+                        let verb = if enabled { "Enabled" } else { "Disabled" };
+                        self.show_toast(format!("{verb} {changed} MCP server(s) for {name}"));
+                    }
+                    Err(e) => self.show_error(format!("Failed to update MCP servers: {e}")),
+                }
+                // 失败时数据库可能已部分更新，同样刷新
+                self.refresh_data();
+            }
+            Confirmation::RestoreBackup { path, name } => {
+                match self.state.db.restore_backup(&path) {
+                    Ok(safety_id) => {
+                        self.restore_dialog.close();
+                        self.reload_after_restore();
+                        // live 配置仍指向恢复前的当前供应商，需按恢复后的数据库重写
+                        if let Err(e) = ProviderService::sync_current_to_live(&self.state) {
+                            self.show_error(format!(
+                                "Restored {name}, but failed to sync live config: {e}"
+                            ));
+                        } else if safety_id.is_empty() {
+                            self.show_toast(format!("Restored {name}"));
+                        } else {
+                            self.show_toast(format!(
+                                "Restored {name}; previous database saved as {safety_id}"
+                            ));
+                        }
+                    }
+                    Err(e) => self.show_error(format!("Failed to restore backup: {e}")),
+                }
+            }
+        }
+    }
+
+    /// 恢复备份后重新读取设置并刷新所有视图，无需重启
+    fn reload_after_restore(&mut self) {
+        self.settings_view = SettingsView::new(self.state.clone());
+        let (theme, theme_error) =
+            load_theme(self.settings_view.theme_preset(), self.color_support);
+        self.theme = theme;
+        if let Some(e) = theme_error {
+            log::warn!("{e}");
+        }
+        if let Err(e) = self.settings_view.apply_logging() {
+            log::warn!("Failed to set up logging: {e}");
         }
+
+        self.status_refreshed_at = None;
+        self.refreshed_at = Instant::now();
+        for view in [
+            ActiveView::Providers,
+            ActiveView::Mcp,
+            ActiveView::Proxy,
+            ActiveView::History,
+            ActiveView::Prompts,
+            ActiveView::Usage,
+        ] {
+            self.spawn_refresh(view, true);
+        }
+    }
+
+    /// 在后台测试选中供应商的连通性
+    fn test_selected_provider(&mut self) {
+        let app_type = self.providers_view.app_type();
+        let Some((provider_id, test)) = self.providers_view.start_connectivity_test() else {
+            return;
+        };
+        let tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let result = test.await;
+            let _ = tx.send(BackgroundEvent::Connectivity {
+                app_type,
+                provider_id,
+                result,
+            });
+        });
+    }
+
+    /// 每次事件循环调用，按间隔刷新状态栏实时状态及当前视图
+    pub async fn tick(&mut self) {
+        if let Some(timeout) = self.settings_view.idle_lock_timeout() {
+            if !self.lock_screen.visible && self.last_input.elapsed() >= timeout {
+                self.lock_screen.lock();
+            }
+        }
+
+        if let Some(action) = self.key_seq.expire(Instant::now()) {
+            self.dispatch_action(action).await;
+        }
+
+        self.drain_background_events();
+        self.proxy_view.poll_requests();
+        self.logs_view.poll();
+        self.maintenance_dialog.poll();
+
+        let interval = match self.active_view {
+            ActiveView::Proxy => Some(DASHBOARD_REFRESH_INTERVAL),
+            _ => self.settings_view.auto_refresh_interval(),
+        };
+        if let Some(interval) = interval {
+            let in_flight = self.latest_refresh.contains_key(&self.active_view);
+            if self.refreshed_at.elapsed() >= interval && !in_flight {
+                self.refreshed_at = Instant::now();
+                self.spawn_refresh(self.active_view, false);
+            }
+        }
+
+        // 维护期间数据库被锁定，同步读取状态会卡住界面
+        let due = self
+            .status_refreshed_at
+            .is_none_or(|at| at.elapsed() >= STATUS_REFRESH_INTERVAL)
+            && !self.maintenance_dialog.is_running();
+        if due {
+            self.refresh_live_status().await;
+            self.status_refreshed_at = Some(Instant::now());
+        }
+    }
+
+    async fn refresh_live_status(&mut self) {
+        let app = self.active_app.clone();
+        let provider_name = ProviderService::current(&self.state, app.clone())
+            .ok()
+            .filter(|id| !id.is_empty())
+            .and_then(|id| self.state.db.get_provider_by_id(&id, app.as_str()).ok()?)
+            .map(|p| p.name);
+        let proxy = self
+            .state
+            .proxy_service
+            .get_status()
+            .await
+            .unwrap_or_default();
+        self.providers_view.set_proxy_stats(&proxy);
+        if self.endpoints_view.visible {
+            self.endpoints_view.set_proxy_stats(&proxy);
+        }
+
+        self.live_status = LiveStatus {
+            provider_name,
+            proxy_running: proxy.running,
+            proxy_port: proxy.port,
+            open_breakers: self.state.proxy_service.open_circuit_breaker_count().await,
+        };
+        self.check_budgets();
+    }
+
+    /// 供应商新超出预算时提醒一次
+    fn check_budgets(&mut self) {
+        let exceeded: Vec<_> = load_budgets(&self.state)
+            .into_iter()
+            .filter(|(_, status)| status.is_exceeded())
+            .collect();
+        let mut over_budget = HashSet::new();
+        for (app_type, status) in exceeded {
+            let key = format!("{}:{}", app_type.as_str(), status.provider_id);
+            if !self.over_budget.contains(&key) {
+                let action = if status.pause_on_limit {
+                    "routing paused"
+                } else {
+                    "alert only"
+                };
+                self.show_error(format!(
+                    "{} ({}) exceeded its budget — {action}",
+                    status.provider_name,
+                    app_type.as_str()
+                ));
+            }
+            over_budget.insert(key);
+        }
+        self.over_budget = over_budget;
     }
 
     fn switch_app(&mut self, app: AppType) {
@@ -100,6 +913,19 @@ impl App {
     }
 
     fn render(&mut self, frame: &mut Frame) {
+        // 锁屏时不绘制任何视图，避免密钥等敏感信息留在屏幕上
+        if self.lock_screen.visible {
+            self.lock_screen.render(frame, &self.theme);
+            return;
+        }
+
+        let area = frame.area();
+        self.too_small = area.width < MIN_WIDTH || area.height < MIN_HEIGHT;
+        if self.too_small {
+            self.render_too_small(frame, area);
+            return;
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -117,6 +943,63 @@ impl App {
 
         // 渲染表单（如果可见）
         self.provider_form.render(frame, &self.theme);
+        self.endpoints_view.render(frame, &self.theme, &self.keymap);
+        self.budget_form.render(frame, &self.theme);
+        self.weight_form.render(frame, &self.theme);
+        self.concurrency_form.render(frame, &self.theme);
+        self.circuit_breaker_form.render(frame, &self.theme);
+        self.health_check_form.render(frame, &self.theme);
+        self.shadow_form.render(frame, &self.theme);
+        self.response_cache_form.render(frame, &self.theme);
+        self.upstream_proxy_form.render(frame, &self.theme);
+        self.speedtest_form.render(frame, &self.theme);
+        self.provider_env_form.render(frame, &self.theme);
+        self.dns_form.render(frame, &self.theme);
+        self.size_limit_form.render(frame, &self.theme);
+        self.switch_back_form.render(frame, &self.theme);
+        self.master_password_form.render(frame, &self.theme);
+        self.backup_form.render(frame, &self.theme);
+        self.restore_dialog.render(frame, &self.theme);
+        self.maintenance_dialog.render(frame, &self.theme);
+        self.env_import_dialog.render(frame, &self.theme);
+        self.webhook_form.render(frame, &self.theme);
+        self.failover_events_view
+            .render(frame, &self.theme, &self.keymap);
+        self.model_alias_form.render(frame, &self.theme);
+        self.header_rules_form.render(frame, &self.theme);
+        self.replay_dialog.render(frame, &self.theme);
+        self.listen_form.render(frame, &self.theme);
+        self.hybrid_form.render(frame, &self.theme);
+        self.rate_limit_form.render(frame, &self.theme);
+        self.tls_form.render(frame, &self.theme);
+        self.cors_form.render(frame, &self.theme);
+        self.app_port_form.render(frame, &self.theme);
+        self.unix_socket_form.render(frame, &self.theme);
+        self.export_form.render(frame, &self.theme);
+        self.import_form.render(frame, &self.theme);
+        self.prompt_editor.render(frame, &self.theme);
+        self.mcp_paste_form.render(frame, &self.theme);
+        self.mcp_export_form.render(frame, &self.theme);
+        self.mcp_form.render(frame, &self.theme);
+        self.usage_export_form.render(frame, &self.theme);
+        self.pricing_editor.render(frame, &self.theme);
+        self.switch_preview.render(frame, &self.theme);
+        self.confirm_dialog.render(frame, &self.theme);
+    }
+
+    fn render_too_small(&self, frame: &mut Frame, area: Rect) {
+        let text = format!(
+            "Terminal too small ({}x{})\nPlease enlarge terminal to {MIN_WIDTH}x{MIN_HEIGHT}",
+            area.width, area.height
+        );
+        let [row] = Layout::vertical([Constraint::Length(2)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+        let message = Paragraph::new(text)
+            .style(self.theme.error)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(message, row);
     }
 
     fn render_header(&self, frame: &mut Frame, area: Rect) {
@@ -149,9 +1032,9 @@ impl App {
         frame.render_widget(header, area);
     }
 
-    fn render_tabs(&self, frame: &mut Frame, area: Rect) {
-        let titles = vec!["[1]Providers", "[2]MCP", "[3]Proxy", "[4]Settings"];
-        let tabs = Tabs::new(titles)
+    fn render_tabs(&mut self, frame: &mut Frame, area: Rect) {
+        self.tabs_area = area;
+        let tabs = Tabs::new(TAB_TITLES)
             .select(self.active_view.index())
             .style(self.theme.normal)
             .highlight_style(self.theme.selected);
@@ -164,124 +1047,2036 @@ impl App {
             ActiveView::Mcp => self.mcp_view.render(frame, area, &self.theme),
             ActiveView::Proxy => self.proxy_view.render(frame, area, &self.theme),
             ActiveView::Settings => self.settings_view.render(frame, area, &self.theme),
+            ActiveView::History => self.history_view.render(frame, area, &self.theme),
+            ActiveView::Logs => self.logs_view.render(frame, area, &self.theme),
+            ActiveView::Prompts => self.prompts_view.render(frame, area, &self.theme),
+            ActiveView::Usage => self.usage_view.render(frame, area, &self.theme),
         }
     }
 
-    fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
-        let hints = match self.active_view {
-            ActiveView::Providers => {
-                "↑↓:Select  Enter:Switch  a:Add  e:Edit  d:Delete  ←→:App  q:Quit"
+    fn render_status_bar(&mut self, frame: &mut Frame, area: Rect) {
+        if let Some(input) = &self.command_line {
+            let line = Paragraph::new(format!(":{}", input.value)).style(self.theme.normal);
+            frame.render_widget(line, area);
+            let cursor_x = area.x + 1 + input.value[..input.cursor].chars().count() as u16;
+            frame.set_cursor_position(Position::new(cursor_x.min(area.right()), area.y));
+            return;
+        }
+
+        if let Some(toast) = &self.toast {
+            if Instant::now() < toast.expires_at {
+                let style = if toast.is_error {
+                    self.theme.error
+                } else {
+                    self.theme.success
+                };
+                frame.render_widget(Paragraph::new(toast.message.as_str()).style(style), area);
+                return;
             }
-            ActiveView::Mcp => "↑↓:Select  Space:Toggle  a:Add  e:Edit  d:Delete  q:Quit",
-            ActiveView::Proxy => "p:Start/Stop  t:Takeover  q:Quit",
-            ActiveView::Settings => "Enter:Select  q:Quit",
+            self.toast = None;
+        }
+
+        let key = |action| self.keymap.label(action);
+        let hints = match self.active_view {
+            ActiveView::Providers => format!(
+                "{}{}:Select  gg/{}:Top/Bottom  {}:{}  {}:Dry run {}  {}:Add  {}:Edit  {}:Delete  {}/{}:Test/Latency  {}:Endpoints  {}:Budget  {}:Website  {}:Failover  {}:Weight  {}:Concurrency  {}:Breaker  {}:Health check  {}:Aliases  {}:Headers  {}:Proxy  {}:Probe  {}:Env export  {}:Sort {}  {}{}:App  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::Bottom),
+                key(Action::Select),
+                if self.dry_run { "Dry-run switch" } else { "Switch" },
+                key(Action::ToggleDryRun),
+                if self.dry_run { "on" } else { "off" },
+                key(Action::Add),
+                key(Action::Edit),
+                key(Action::Delete),
+                key(Action::TestConnection),
+                key(Action::TestLatency),
+                key(Action::Endpoints),
+                key(Action::EditBudget),
+                key(Action::OpenWebsite),
+                key(Action::ToggleFailover),
+                key(Action::EditWeight),
+                key(Action::EditConcurrency),
+                key(Action::EditCircuitBreaker),
+                key(Action::EditHealthCheck),
+                key(Action::EditModelAliases),
+                key(Action::EditHeaderRules),
+                key(Action::EditProviderProxy),
+                key(Action::EditSpeedtest),
+                key(Action::ExportProviderEnv),
+                key(Action::SortByRecency),
+                if self.providers_view.sort_by_recency() { "recent" } else { "default" },
+                key(Action::PrevApp),
+                key(Action::NextApp),
+                key(Action::Quit)
+            ),
+            ActiveView::Mcp => format!(
+                "{}{}:Select  gg/{}:Top/Bottom  Space:Toggle  {}:Add  {}:Edit  {}:Delete  {}:Test  {}:Export  {}/{}:Enable/Disable all  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::Bottom),
+                key(Action::Add),
+                key(Action::Edit),
+                key(Action::Delete),
+                key(Action::TestMcpServer),
+                key(Action::ExportMcp),
+                key(Action::EnableAllMcp),
+                key(Action::DisableAllMcp),
+                key(Action::Quit)
+            ),
+            ActiveView::Proxy => format!(
+                "{}{}:Scroll requests  {}:Start/Stop  {}:Listen address  {}:Takeover {}  {}:Hybrid mode  {}:Balancing  {}:Rate limit  {}:Stream retry  {}:Switch back  {}:Switch back now  {}:Hedging  {}:Shadow  {}:Cache  {}:Upstream proxy  {}:DNS  {}:Size limits  {}:Webhooks  {}:Failover history  {}:HTTPS  {}:Auth token  {}:CORS  {}:App port  {}:Unix socket  {}:Breaker  {}{}:App  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::ToggleProxy),
+                key(Action::Edit),
+                key(Action::Takeover),
+                app_display_name(&self.active_app),
+                key(Action::HybridMode),
+                key(Action::CycleLoadBalance),
+                key(Action::EditRateLimit),
+                key(Action::ToggleStreamFailover),
+                key(Action::EditSwitchBack),
+                key(Action::SwitchBackNow),
+                key(Action::ToggleHedging),
+                key(Action::EditShadow),
+                key(Action::EditResponseCache),
+                key(Action::EditUpstreamProxy),
+                key(Action::EditDns),
+                key(Action::EditSizeLimits),
+                key(Action::EditWebhooks),
+                key(Action::ViewFailoverEvents),
+                key(Action::EditTls),
+                key(Action::ToggleAuthToken),
+                key(Action::EditCors),
+                key(Action::EditAppPort),
+                key(Action::EditUnixSocket),
+                key(Action::EditAppCircuitBreaker),
+                key(Action::PrevApp),
+                key(Action::NextApp),
+                key(Action::Quit)
+            ),
+            ActiveView::Settings => format!(
+                "Enter:Select  {}:Theme  {}:Auto refresh  {}:History size  {}:Capture  {}:Log level  {}:Log output  {}:Switch preview  {}:Access log  {}:Log rotation  {}:Notifications  {}:Master password  {}:Idle lock  {}:Backups  {}:Restore  {}:Maintenance  {}:Export  {}:Import  {}:Env import  {}:Quit",
+                key(Action::CycleTheme),
+                key(Action::CycleAutoRefresh),
+                key(Action::CycleHistoryLimit),
+                key(Action::CycleCaptureLimit),
+                key(Action::CycleLogLevel),
+                key(Action::CycleLogDestination),
+                key(Action::ToggleSwitchPreview),
+                key(Action::ToggleAccessLog),
+                key(Action::CycleAccessLogSize),
+                key(Action::ToggleDesktopNotifications),
+                key(Action::SetMasterPassword),
+                key(Action::CycleIdleLock),
+                key(Action::EditAutoBackup),
+                key(Action::RestoreBackup),
+                key(Action::DatabaseMaintenance),
+                key(Action::ExportConfig),
+                key(Action::ImportConfig),
+                key(Action::ImportEnvProviders),
+                key(Action::Quit)
+            ),
+            ActiveView::History => format!(
+                "{}{}:Select  {}:Detail  {}/{}:Page  {}:Filter  {}:Replay  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::Select),
+                key(Action::PrevPage),
+                key(Action::NextPage),
+                key(Action::Filter),
+                key(Action::ReplayRequest),
+                key(Action::Quit)
+            ),
+            ActiveView::Prompts => format!(
+                "{}{}:Select  {}/{}:Edit  {}:Add  {}:Delete  {}{}:App  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::Select),
+                key(Action::Edit),
+                key(Action::Add),
+                key(Action::Delete),
+                key(Action::PrevApp),
+                key(Action::NextApp),
+                key(Action::Quit)
+            ),
+            ActiveView::Logs => format!(
+                "{}{}:Scroll  {}:Level  {}:Search  {}:Follow  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::CycleLogFilter),
+                key(Action::SearchLogs),
+                key(Action::ToggleFollow),
+                key(Action::Quit)
+            ),
+            ActiveView::Usage => format!(
+                "{}{}:Select  {}:Range ({})  {}:Breakdown  {}:Export CSV  {}:Prices  {}{}:App  {}:Quit",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::CycleUsageRange),
+                self.usage_view.range().label(),
+                key(Action::CycleUsageBreakdown),
+                key(Action::ExportUsage),
+                key(Action::EditPricing),
+                key(Action::PrevApp),
+                key(Action::NextApp),
+                key(Action::Quit)
+            ),
         };
+        let live = self.live_status_line();
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(live.width() as u16)])
+            .split(area);
+
         let status = Paragraph::new(hints).style(self.theme.inactive);
-        frame.render_widget(status, area);
+        frame.render_widget(status, chunks[0]);
+        frame.render_widget(Paragraph::new(live), chunks[1]);
     }
 
-    async fn handle_key(&mut self, key: KeyCode) {
-        // 如果表单可见，优先处理表单事件
-        if self.provider_form.visible {
-            let should_refresh = self.provider_form.handle_key(key, self.active_app.clone());
-            if should_refresh {
-                self.refresh_data().await;
+    fn live_status_line(&self) -> Line<'static> {
+        let status = &self.live_status;
+        let separator = Span::styled(" │ ", self.theme.inactive);
+
+        let provider = status.provider_name.as_deref().unwrap_or("-");
+        let mut spans = vec![
+            Span::styled(
+                format!(" {}: ", app_display_name(&self.active_app)),
+                self.theme.inactive,
+            ),
+            Span::styled(provider.to_string(), self.theme.highlight),
+            separator.clone(),
+        ];
+
+        if status.proxy_running {
+            spans.push(Span::styled(
+                format!("Proxy :{}", status.proxy_port),
+                self.theme.success,
+            ));
+        } else {
+            spans.push(Span::styled("Proxy off", self.theme.inactive));
+        }
+
+        if status.open_breakers > 0 {
+            spans.push(separator);
+            spans.push(Span::styled(
+                format!("Open breakers: {}", status.open_breakers),
+                self.theme.error,
+            ));
+        }
+        spans.push(Span::raw(" "));
+        Line::from(spans)
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) {
+        self.last_input = Instant::now();
+        if self.lock_screen.visible {
+            if let Some(password) = self.lock_screen.handle_key(key.code) {
+                if self.settings_view.verify_master_password(&password) {
+                    self.lock_screen.unlock();
+                } else {
+                    self.lock_screen.reject();
+                }
             }
             return;
         }
 
-        // Global keys
-        match key {
-            KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Char('1') => {
-                self.active_view = ActiveView::Providers;
-                self.refresh_data().await;
-            }
-            KeyCode::Char('2') => {
-                self.active_view = ActiveView::Mcp;
-                self.refresh_data().await;
-            }
-            KeyCode::Char('3') => {
-                self.active_view = ActiveView::Proxy;
-                self.refresh_data().await;
+        // 确认弹窗位于最上层，优先处理
+        if self.confirm_dialog.visible {
+            if let Some(confirmation) = self.confirm_dialog.handle_key(key.code) {
+                self.run_confirmation(confirmation);
             }
-            KeyCode::Char('4') => self.active_view = ActiveView::Settings,
-            KeyCode::Left => {
-                self.prev_app();
-                self.refresh_data().await;
-            }
-            KeyCode::Right => {
-                self.next_app();
-                self.refresh_data().await;
+            return;
+        }
+
+        if self.switch_preview.visible {
+            if let Some((app_type, id)) = self.switch_preview.handle_key(key.code) {
+                self.confirm_switch(app_type, &id);
             }
-            _ => {
-                // Delegate to active view
-                self.handle_view_key(key).await;
+            return;
+        }
+
+        // 如果表单可见，优先处理表单事件
+        if self.provider_form.visible {
+            let should_refresh = self
+                .provider_form
+                .handle_key(key.code, self.active_app.clone());
+            if should_refresh {
+                self.refresh_data();
             }
+            return;
         }
-    }
 
-    async fn handle_view_key(&mut self, key: KeyCode) {
-        match self.active_view {
-            ActiveView::Providers => match key {
-                KeyCode::Char('a') => {
-                    self.provider_form.open_add(self.active_app.clone());
-                }
-                KeyCode::Char('e') => {
-                    if let Some(provider) = self.providers_view.get_selected() {
-                        self.provider_form
-                            .open_edit(&provider, self.active_app.clone());
+        if self.endpoints_view.visible {
+            let changed = if self.endpoints_view.captures_input() || key.code == KeyCode::Esc {
+                self.endpoints_view.handle_key(key.code)
+            } else {
+                match self.resolve_popup_key(&key, KeyScope::Endpoints) {
+                    PopupKey::Nav(nav) => {
+                        self.endpoints_view.navigate(nav);
+                        false
                     }
+                    PopupKey::Action(action) => self.endpoints_view.handle_action(action),
+                    PopupKey::None => false,
                 }
-                KeyCode::Char('d') => {
-                    self.delete_selected_provider().await;
-                }
-                _ => {
-                    self.providers_view
-                        .handle_key(key, self.active_app.clone())
-                        .await;
-                }
-            },
-            ActiveView::Mcp => self.mcp_view.handle_key(key).await,
-            ActiveView::Proxy => self.proxy_view.handle_key(key).await,
-            ActiveView::Settings => self.settings_view.handle_key(key).await,
+            };
+            if changed {
+                self.refresh_data();
+            }
+            return;
         }
-    }
 
-    async fn delete_selected_provider(&mut self) {
-        use cc_switch_lib::ProviderService;
+        if self.budget_form.visible {
+            if self.budget_form.handle_key(key.code) {
+                self.show_toast("Budget saved");
+                self.refresh_data();
+                // 立即重新评估，使新预算下的超限状态尽快提示
+                self.status_refreshed_at = None;
+            }
+            return;
+        }
 
-        if let Some(provider) = self.providers_view.get_selected() {
-            if ProviderService::delete(&self.state, self.active_app.clone(), &provider.id).is_ok() {
-                self.refresh_data().await;
+        if self.weight_form.visible {
+            if self.weight_form.handle_key(key.code) {
+                self.show_toast("Routing weight saved");
+                self.refresh_data();
             }
+            return;
         }
-    }
-}
 
-pub async fn run(state: Arc<AppState>) -> Result<()> {
-    let mut terminal = terminal::init()?;
-    let mut app = App::new(state);
+        if self.shadow_form.visible {
+            if self.shadow_form.handle_key(key.code) {
+                let name = app_display_name(self.shadow_form.app_type());
+                self.show_toast(format!("{name}: shadowing saved"));
+                self.refresh_data();
+            }
+            return;
+        }
 
-    // Initial data load
-    app.refresh_data().await;
+        if self.response_cache_form.visible {
+            if let Some(config) = self.response_cache_form.handle_key(key.code) {
+                self.save_response_cache(config).await;
+            }
+            return;
+        }
 
-    loop {
-        terminal.draw(|frame| app.render(frame))?;
+        if self.upstream_proxy_form.visible {
+            if self.upstream_proxy_form.handle_key(key.code) {
+                self.show_toast("Upstream proxy saved");
+                self.refresh_data();
+            }
+            return;
+        }
 
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    app.handle_key(key.code).await;
-                }
+        if self.speedtest_form.visible {
+            if self.speedtest_form.handle_key(key.code) {
+                self.show_toast("Speedtest probe saved");
+                self.refresh_data();
             }
+            return;
         }
 
-        if app.should_quit {
-            break;
+        if self.provider_env_form.visible {
+            if let Some(message) = self.provider_env_form.handle_key(key.code) {
+                self.show_toast(message);
+            }
+            return;
         }
-    }
 
-    terminal::restore(&mut terminal)?;
-    Ok(())
+        if self.dns_form.visible {
+            if let Some(config) = self.dns_form.handle_key(key.code) {
+                self.save_dns(config);
+            }
+            return;
+        }
+
+        if self.size_limit_form.visible {
+            if let Some(config) = self.size_limit_form.handle_key(key.code) {
+                self.save_size_limits(config);
+            }
+            return;
+        }
+
+        if self.switch_back_form.visible {
+            if let Some(config) = self.switch_back_form.handle_key(key.code) {
+                self.save_switch_back(config);
+            }
+            return;
+        }
+
+        if self.master_password_form.visible {
+            if let Some(change) = self.master_password_form.handle_key(key.code) {
+                self.save_master_password(change);
+            }
+            return;
+        }
+
+        if self.maintenance_dialog.visible {
+            self.maintenance_dialog.handle_key(key.code);
+            return;
+        }
+
+        if self.env_import_dialog.visible {
+            if let Some(count) = self.env_import_dialog.handle_key(key.code) {
+                self.show_toast(format!("Created {count} provider(s) from environment"));
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.restore_dialog.visible {
+            if let Some(backup) = self.restore_dialog.handle_key(key.code) {
+                self.confirm_dialog.open(
+                    "Restore backup",
+                    format!(
+                        "Replace the current database with {}? The current database is backed up first.",
+                        backup.name
+                    ),
+                    Confirmation::RestoreBackup {
+                        path: backup.path,
+                        name: backup.name,
+                    },
+                );
+            }
+            return;
+        }
+
+        if self.backup_form.visible {
+            if let Some(config) = self.backup_form.handle_key(key.code) {
+                match self.settings_view.save_auto_backup(config) {
+                    Ok(()) => {
+                        self.backup_form.close();
+                        self.show_toast(format!(
+                            "Automatic backups: {}",
+                            auto_backup_label(&config)
+                        ));
+                    }
+                    Err(e) => self.backup_form.set_error(e.to_string()),
+                }
+            }
+            return;
+        }
+
+        if self.failover_events_view.visible {
+            if key.code == KeyCode::Esc {
+                self.failover_events_view.close();
+                return;
+            }
+            match self.resolve_popup_key(&key, KeyScope::FailoverEvents) {
+                PopupKey::Nav(nav) => self.failover_events_view.navigate(nav),
+                PopupKey::Action(action) => self.failover_events_view.handle_action(action),
+                PopupKey::None => {}
+            }
+            return;
+        }
+
+        if self.webhook_form.visible {
+            if let Some(config) = self.webhook_form.handle_key(key.code) {
+                self.save_webhooks(config);
+            }
+            return;
+        }
+
+        if self.concurrency_form.visible {
+            if self.concurrency_form.handle_key(key.code) {
+                self.show_toast("Concurrency cap saved");
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.circuit_breaker_form.visible {
+            if let Some(tuning) = self.circuit_breaker_form.handle_key(key.code) {
+                self.save_circuit_breaker(tuning).await;
+            }
+            return;
+        }
+
+        if self.health_check_form.visible {
+            if let Some(config) = self.health_check_form.handle_key(key.code) {
+                self.save_health_check(config);
+            }
+            return;
+        }
+
+        if self.model_alias_form.visible {
+            if self.model_alias_form.handle_key(key.code) {
+                self.show_toast("Model aliases saved");
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.header_rules_form.visible {
+            if self.header_rules_form.handle_key(key.code) {
+                self.show_toast("Header rules saved");
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.replay_dialog.visible {
+            if let Some(request) = self.replay_dialog.handle_key(key.code) {
+                self.spawn_replay(request);
+            }
+            return;
+        }
+
+        if self.listen_form.visible {
+            if let Some((address, port, allowlist)) = self.listen_form.handle_key(key.code) {
+                self.save_listen_address(&address, port, &allowlist).await;
+            }
+            return;
+        }
+
+        if self.hybrid_form.visible {
+            if self.hybrid_form.handle_key(key.code) {
+                let name = app_display_name(&self.active_app);
+                self.show_toast(format!("Hybrid mode settings saved for {name}"));
+            }
+            return;
+        }
+
+        if self.rate_limit_form.visible {
+            if self.rate_limit_form.handle_key(key.code) {
+                let name = app_display_name(&self.active_app);
+                self.show_toast(format!("Rate limit saved for {name}"));
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.tls_form.visible {
+            if let Some(config) = self.tls_form.handle_key(key.code) {
+                self.save_tls_config(config).await;
+            }
+            return;
+        }
+
+        if self.cors_form.visible {
+            if let Some(config) = self.cors_form.handle_key(key.code) {
+                match self.state.proxy_service.set_cors_config(config).await {
+                    Ok(()) => {
+                        self.cors_form.close();
+                        self.show_toast("Proxy CORS settings saved");
+                    }
+                    Err(e) => self.cors_form.set_error(e),
+                }
+            }
+            return;
+        }
+
+        if self.unix_socket_form.visible {
+            if let Some(config) = self.unix_socket_form.handle_key(key.code) {
+                self.save_unix_socket_config(config).await;
+            }
+            return;
+        }
+
+        if self.app_port_form.visible {
+            if let Some(port) = self.app_port_form.handle_key(key.code) {
+                let app = self.app_port_form.app_type().clone();
+                match self
+                    .state
+                    .proxy_service
+                    .set_dedicated_port(&app, port)
+                    .await
+                {
+                    Ok(()) => {
+                        self.app_port_form.close();
+                        let name = app_display_name(&app);
+                        self.show_toast(match port {
+                            Some(port) => format!("{name} now listens on port {port}"),
+                            None => format!("{name} uses the shared proxy port"),
+                        });
+                        self.status_refreshed_at = None;
+                        self.refresh_data();
+                    }
+                    Err(e) => self.app_port_form.set_error(e),
+                }
+            }
+            return;
+        }
+
+        if self.export_form.visible {
+            if let Some((path, redact_secrets)) = self.export_form.handle_key(key.code) {
+                let ui = self.ui_preferences();
+                match ConfigService::export_to_file(&self.state, &path, redact_secrets, &ui).await {
+                    Ok(()) => {
+                        self.export_form.close();
+                        self.show_toast(format!("Configuration exported to {}", path.display()));
+                    }
+                    Err(e) => self.export_form.set_error(e.to_string()),
+                }
+            }
+            return;
+        }
+
+        if self.import_form.visible {
+            if let Some(summary) = self.import_form.handle_key(key.code).await {
+                self.finish_import(summary);
+            }
+            return;
+        }
+
+        if self.mcp_paste_form.visible {
+            if let Some(count) = self.mcp_paste_form.handle_key(key) {
+                self.show_toast(format!("Added {count} MCP server(s)"));
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.mcp_export_form.visible {
+            if let Some(message) = self.mcp_export_form.handle_key(key.code) {
+                self.show_toast(message);
+            }
+            return;
+        }
+
+        if self.usage_export_form.visible {
+            if let Some(path) = self.usage_export_form.handle_key(key.code) {
+                match std::fs::write(&path, self.usage_view.to_csv()) {
+                    Ok(()) => {
+                        self.usage_export_form.close();
+                        self.show_toast(format!("Usage exported to {}", path.display()));
+                    }
+                    Err(e) => self.usage_export_form.set_error(e.to_string()),
+                }
+            }
+            return;
+        }
+
+        if self.pricing_editor.visible {
+            // 花费在请求发生时按当时价格计算，修改定价只影响之后的请求，无需刷新
+            self.pricing_editor.handle_key(key.code);
+            return;
+        }
+
+        if self.mcp_form.visible {
+            if self.mcp_form.handle_key(key.code) {
+                self.show_toast("MCP server saved");
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.prompt_editor.visible {
+            if self.prompt_editor.handle_key(key) {
+                self.show_toast("Prompt saved");
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.active_view == ActiveView::History && self.history_view.captures_input() {
+            if self.history_view.detail_open() && key.code != KeyCode::Esc {
+                match self.resolve_popup_key(&key, KeyScope::RequestDetail) {
+                    PopupKey::Nav(nav) => self.history_view.scroll_detail(nav),
+                    PopupKey::Action(Action::Quit | Action::Select) => {
+                        self.history_view.close_detail()
+                    }
+                    _ => {}
+                }
+            } else if self.history_view.handle_key(key.code) {
+                self.refresh_data();
+            }
+            return;
+        }
+
+        if self.active_view == ActiveView::Logs && self.logs_view.captures_input() {
+            self.logs_view.handle_key(key.code);
+            return;
+        }
+
+        if self.command_line.is_some() {
+            self.handle_command_key(key).await;
+            return;
+        }
+
+        let action = self.keymap.resolve(&key, self.active_view);
+
+        // 列表视图的 Vim 风格导航（gg / G / 数字前缀）
+        if matches!(
+            self.active_view,
+            ActiveView::Providers
+                | ActiveView::Mcp
+                | ActiveView::History
+                | ActiveView::Logs
+                | ActiveView::Prompts
+                | ActiveView::Usage
+        ) {
+            match self.key_seq.feed(key.code, action) {
+                KeySeqResult::Pending => return,
+                KeySeqResult::Nav(nav) => {
+                    self.navigate(nav);
+                    return;
+                }
+                KeySeqResult::Interrupted(deferred) => {
+                    self.dispatch_action(deferred).await;
+                    // 数字的动作可能切换了视图，按新视图重新处理当前按键
+                    Box::pin(self.handle_key(key)).await;
+                    return;
+                }
+                KeySeqResult::Passthrough => {}
+            }
+        } else {
+            self.key_seq.reset();
+        }
+
+        if let Some(action) = action {
+            self.dispatch_action(action).await;
+        }
+    }
+
+    /// 执行按键解析出的动作：全局动作直接处理，其余交给当前视图
+    async fn dispatch_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::ViewProviders => self.set_active_view(ActiveView::Providers),
+            Action::ViewMcp => self.set_active_view(ActiveView::Mcp),
+            Action::ViewProxy => self.set_active_view(ActiveView::Proxy),
+            Action::ViewSettings => self.set_active_view(ActiveView::Settings),
+            Action::ViewHistory => self.set_active_view(ActiveView::History),
+            Action::ViewLogs => self.set_active_view(ActiveView::Logs),
+            Action::ViewPrompts => self.set_active_view(ActiveView::Prompts),
+            Action::ViewUsage => self.set_active_view(ActiveView::Usage),
+            Action::PrevApp => {
+                self.prev_app();
+                self.refresh_data();
+            }
+            Action::NextApp => {
+                self.next_app();
+                self.refresh_data();
+            }
+            Action::CommandMode => self.command_line = Some(TextInput::new(":")),
+            Action::Reload => self.reload_all(),
+            _ => {
+                // Delegate to active view
+                self.handle_view_action(action).await;
+            }
+        }
+    }
+
+    /// 终端粘贴（bracketed paste）：多行编辑框整体插入，其余输入框按字符逐个输入
+    pub async fn handle_paste(&mut self, text: &str) {
+        self.last_input = Instant::now();
+        if self.lock_screen.visible || self.master_password_form.visible {
+            // 密码不接受粘贴，避免剪贴板内容意外填入
+        } else if self.mcp_paste_form.visible {
+            self.mcp_paste_form.paste(text);
+        } else if self.prompt_editor.visible {
+            self.prompt_editor.paste(text);
+        } else if self.mcp_form.visible {
+            self.mcp_form.paste(text);
+        } else {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                self.handle_key(KeyEvent::from(KeyCode::Char(c))).await;
+            }
+        }
+    }
+
+    async fn handle_command_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.command_line.as_mut() else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.command_line = None,
+            KeyCode::Enter => {
+                let line = input.value.clone();
+                self.command_line = None;
+                self.run_command(&line).await;
+            }
+            KeyCode::Tab => {
+                let names = self.providers_view.provider_names();
+                if let Some(completed) = command::complete(&input.value, &names) {
+                    *input = TextInput::with_value(":", &completed);
+                }
+            }
+            KeyCode::Backspace if input.value.is_empty() => self.command_line = None,
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+    }
+
+    async fn run_command(&mut self, line: &str) {
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(e) => {
+                self.show_error(e);
+                return;
+            }
+        };
+
+        match command {
+            Command::Quit => self.should_quit = true,
+            Command::Switch(name) => self.switch_provider_by_name(&name).await,
+            Command::App(app) => {
+                self.switch_app(app);
+                self.refresh_data();
+            }
+            Command::ProxyStart => match self.state.proxy_service.start().await {
+                Ok(info) => {
+                    self.show_toast(format!("Proxy started on {}:{}", info.address, info.port));
+                    self.refresh_data();
+                }
+                Err(e) => self.show_error(format!("Failed to start proxy: {e}")),
+            },
+            Command::ProxyStop => match self.state.proxy_service.stop().await {
+                Ok(()) => {
+                    self.show_toast("Proxy stopped");
+                    self.refresh_data();
+                }
+                Err(e) => self.show_error(format!("Failed to stop proxy: {e}")),
+            },
+            // 与设置页导出相同的 JSON 格式，默认隐去密钥
+            Command::Export(path) => {
+                let ui = self.ui_preferences();
+                match ConfigService::export_to_file(&self.state, &path, true, &ui).await {
+                    Ok(()) => {
+                        self.show_toast(format!("Configuration exported to {}", path.display()))
+                    }
+                    Err(e) => self.show_error(format!("Export failed: {e}")),
+                }
+            }
+        }
+    }
+
+    /// 在浏览器中打开选中供应商的官网
+    fn open_selected_website(&mut self) {
+        let Some(provider) = self.providers_view.get_selected() else {
+            return;
+        };
+        let url = provider
+            .website_url
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default();
+        if url.is_empty() {
+            self.show_error(format!("{} has no website set", provider.name));
+            return;
+        }
+        if !is_http_url(url) {
+            self.show_error(format!("Refusing to open non-http URL: {url}"));
+            return;
+        }
+        match terminal::open_url(url) {
+            Ok(()) => self.show_toast(format!("Opening {url}")),
+            Err(e) => self.show_error(format!("Failed to open browser: {e}")),
+        }
+    }
+
+    /// 按名称切换当前应用的供应商（不区分大小写，允许唯一前缀）
+    async fn switch_provider_by_name(&mut self, name: &str) {
+        let providers = match ProviderService::list(&self.state, self.active_app.clone()) {
+            Ok(providers) => providers,
+            Err(e) => {
+                self.show_error(e.to_string());
+                return;
+            }
+        };
+
+        let lower = name.to_lowercase();
+        let exact = providers.values().find(|p| p.name.to_lowercase() == lower);
+        let target = match exact {
+            Some(p) => Some(p),
+            None => {
+                let mut prefixed = providers
+                    .values()
+                    .filter(|p| p.name.to_lowercase().starts_with(&lower));
+                match (prefixed.next(), prefixed.next()) {
+                    (Some(p), None) => Some(p),
+                    (Some(_), Some(_)) => {
+                        self.show_error(format!("Ambiguous provider name: {name}"));
+                        return;
+                    }
+                    _ => None,
+                }
+            }
+        };
+
+        let Some(provider) = target else {
+            self.show_error(format!("Provider not found: {name}"));
+            return;
+        };
+
+        if self.dry_run || self.settings_view.switch_preview() {
+            let provider = provider.clone();
+            self.preview_switch(&provider);
+            return;
+        }
+        match ProviderService::switch(&self.state, self.active_app.clone(), &provider.id) {
+            Ok(()) => {
+                self.show_toast(format!("Switched to {}", provider.name));
+                self.refresh_data();
+            }
+            Err(e) => self.show_error(format!("Switch failed: {e}")),
+        }
+    }
+
+    /// 切换到选中的供应商；开启切换预览时先显示 live 配置改动
+    async fn switch_selected_provider(&mut self) {
+        if !self.dry_run && !self.settings_view.switch_preview() {
+            self.providers_view
+                .switch_provider(self.active_app.clone())
+                .await;
+            return;
+        }
+        if let Some(provider) = self.providers_view.get_selected() {
+            self.preview_switch(&provider);
+        }
+    }
+
+    fn preview_switch(&mut self, provider: &Provider) {
+        let app_type = self.active_app.clone();
+        match ProviderService::preview_switch(&self.state, app_type.clone(), &provider.id) {
+            Ok(changes) if self.dry_run => {
+                for change in &changes {
+                    log::info!(
+                        "Dry run: switching to {} would write {}",
+                        provider.name,
+                        change.path.display()
+                    );
+                }
+                self.switch_preview
+                    .open_dry_run(app_type, provider, changes);
+            }
+            Ok(changes) => self.switch_preview.open(app_type, provider, changes),
+            Err(e) => self.show_error(format!("Failed to preview switch: {e}")),
+        }
+    }
+
+    /// 在切换预览中确认后执行切换
+    fn confirm_switch(&mut self, app_type: AppType, id: &str) {
+        match ProviderService::switch(&self.state, app_type, id) {
+            Ok(()) => {
+                self.show_toast("Provider switched");
+                self.refresh_data();
+            }
+            Err(e) => self.show_error(format!("Switch failed: {e}")),
+        }
+    }
+
+    fn edit_selected_prompt(&mut self) {
+        if let Some(prompt) = self.prompts_view.get_selected() {
+            self.prompt_editor
+                .open_edit(&prompt, self.active_app.clone());
+        }
+    }
+
+    fn delete_selected_prompt(&mut self) {
+        let Some(prompt) = self.prompts_view.get_selected() else {
+            return;
+        };
+        match PromptService::delete_prompt(&self.state, self.active_app.clone(), &prompt.id) {
+            Ok(()) => {
+                self.show_toast(format!("Deleted prompt {}", prompt.name));
+                self.refresh_data();
+            }
+            Err(e) => self.show_error(format!("Delete failed: {e}")),
+        }
+    }
+
+    fn set_active_view(&mut self, view: ActiveView) {
+        self.active_view = view;
+        self.key_seq.reset();
+        self.refresh_data();
+    }
+
+    async fn handle_mouse(&mut self, mouse: MouseEvent) {
+        self.last_input = Instant::now();
+        if self.lock_screen.visible
+            || self.master_password_form.visible
+            || self.backup_form.visible
+            || self.restore_dialog.visible
+            || self.maintenance_dialog.visible
+            || self.env_import_dialog.visible
+            || self.provider_form.visible
+            || self.endpoints_view.visible
+            || self.budget_form.visible
+            || self.weight_form.visible
+            || self.concurrency_form.visible
+            || self.circuit_breaker_form.visible
+            || self.health_check_form.visible
+            || self.shadow_form.visible
+            || self.response_cache_form.visible
+            || self.upstream_proxy_form.visible
+            || self.speedtest_form.visible
+            || self.provider_env_form.visible
+            || self.dns_form.visible
+            || self.size_limit_form.visible
+            || self.switch_back_form.visible
+            || self.webhook_form.visible
+            || self.failover_events_view.visible
+            || self.model_alias_form.visible
+            || self.header_rules_form.visible
+            || self.replay_dialog.visible
+            || self.listen_form.visible
+            || self.hybrid_form.visible
+            || self.rate_limit_form.visible
+            || self.tls_form.visible
+            || self.cors_form.visible
+            || self.app_port_form.visible
+            || self.unix_socket_form.visible
+            || self.export_form.visible
+            || self.import_form.visible
+            || self.prompt_editor.visible
+            || self.mcp_paste_form.visible
+            || self.mcp_export_form.visible
+            || self.mcp_form.visible
+            || self.usage_export_form.visible
+            || self.pricing_editor.visible
+            || self.confirm_dialog.visible
+            || self.switch_preview.visible
+            || self.history_view.captures_input()
+            || self.logs_view.captures_input()
+            || self.too_small
+        {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(view) = self.tab_at(mouse.column, mouse.row) {
+                    self.set_active_view(view);
+                    return;
+                }
+
+                let double_click = self.register_click(mouse.row);
+                match self.active_view {
+                    ActiveView::Providers => {
+                        let hit = self.providers_view.select_at(mouse.column, mouse.row);
+                        if hit.is_some() && double_click {
+                            self.switch_selected_provider().await;
+                        }
+                    }
+                    ActiveView::Mcp => {
+                        self.mcp_view.select_at(mouse.column, mouse.row);
+                    }
+                    ActiveView::Prompts => {
+                        let hit = self.prompts_view.select_at(mouse.column, mouse.row);
+                        if hit.is_some() && double_click {
+                            self.edit_selected_prompt();
+                        }
+                    }
+                    ActiveView::Proxy
+                    | ActiveView::Settings
+                    | ActiveView::History
+                    | ActiveView::Logs
+                    | ActiveView::Usage => {}
+                }
+            }
+            MouseEventKind::ScrollDown => self.navigate(NavAction::Down(1)),
+            MouseEventKind::ScrollUp => self.navigate(NavAction::Up(1)),
+            _ => {}
+        }
+    }
+
+    /// 根据 Tabs 组件的布局（左右各 1 列内边距，标题间 1 列分隔符）定位被点击的标签
+    fn tab_at(&self, column: u16, row: u16) -> Option<ActiveView> {
+        if !self.tabs_area.contains(Position::new(column, row)) {
+            return None;
+        }
+        let mut x = self.tabs_area.x;
+        for (i, title) in TAB_TITLES.iter().enumerate() {
+            let width = title.len() as u16 + 2;
+            if column >= x && column < x + width {
+                return Some(ActiveView::from_index(i));
+            }
+            x += width + 1;
+        }
+        None
+    }
+
+    /// 记录一次点击，返回是否与上一次点击构成双击
+    fn register_click(&mut self, row: u16) -> bool {
+        let now = Instant::now();
+        let double_click = matches!(
+            self.last_click,
+            Some((at, last_row)) if last_row == row && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+        );
+        self.last_click = if double_click { None } else { Some((now, row)) };
+        double_click
+    }
+
+    /// 解析弹窗内的按键：导航序列交给 [`KeySequence`]，其余按弹窗范围解析为动作
+    fn resolve_popup_key(&mut self, key: &KeyEvent, scope: KeyScope) -> PopupKey {
+        let action = self.keymap.resolve_in(key, scope);
+        // 弹窗中数字只作计数，不触发其绑定的切换视图等全局动作
+        let seq_action =
+            action.filter(|_| !matches!(key.code, KeyCode::Char(c) if c.is_ascii_digit()));
+        match self.key_seq.feed(key.code, seq_action) {
+            KeySeqResult::Nav(nav) => PopupKey::Nav(nav),
+            KeySeqResult::Passthrough => action.map_or(PopupKey::None, PopupKey::Action),
+            KeySeqResult::Pending | KeySeqResult::Interrupted(_) => PopupKey::None,
+        }
+    }
+
+    fn navigate(&mut self, action: NavAction) {
+        match self.active_view {
+            ActiveView::Providers => self.providers_view.navigate(action),
+            ActiveView::Mcp => self.mcp_view.navigate(action),
+            ActiveView::Proxy => self.proxy_view.navigate(action),
+            ActiveView::History => self.history_view.navigate(action),
+            ActiveView::Logs => self.logs_view.navigate(action),
+            ActiveView::Prompts => self.prompts_view.navigate(action),
+            ActiveView::Usage => self.usage_view.navigate(action),
+            ActiveView::Settings => {}
+        }
+    }
+
+    async fn handle_view_action(&mut self, action: Action) {
+        match self.active_view {
+            ActiveView::Providers => match action {
+                Action::Select => self.switch_selected_provider().await,
+                Action::ToggleDryRun => {
+                    self.dry_run = !self.dry_run;
+                    self.show_toast(if self.dry_run {
+                        "Dry run on: switching only reports what would be written"
+                    } else {
+                        "Dry run off"
+                    });
+                }
+                Action::Add => {
+                    self.provider_form.open_add(self.active_app.clone());
+                }
+                Action::Edit => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.provider_form
+                            .open_edit(&provider, self.active_app.clone());
+                    }
+                }
+                Action::Delete => {
+                    self.delete_selected_provider().await;
+                }
+                Action::TestConnection => self.test_selected_provider(),
+                Action::TestLatency => self.test_endpoint_latency(),
+                Action::Endpoints => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.endpoints_view.open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditBudget => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.budget_form.open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditWeight => match self.providers_view.get_selected() {
+                    Some(provider) if provider.in_failover_queue => {
+                        self.weight_form.open(&provider, self.active_app.clone())
+                    }
+                    Some(provider) => self.show_error(format!(
+                        "Add {} to the failover queue before setting a weight",
+                        provider.name
+                    )),
+                    None => {}
+                },
+                Action::EditConcurrency => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.concurrency_form
+                            .open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditCircuitBreaker => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        match self
+                            .state
+                            .db
+                            .get_proxy_config_for_app(self.active_app.as_str())
+                            .await
+                        {
+                            Ok(config) => self.circuit_breaker_form.open_provider(
+                                &provider,
+                                self.active_app.clone(),
+                                config.circuit_breaker_config(),
+                            ),
+                            Err(e) => self.show_error(format!(
+                                "Failed to load circuit breaker settings: {e}"
+                            )),
+                        }
+                    }
+                }
+                Action::EditHealthCheck => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        let last_result = self
+                            .state
+                            .proxy_service
+                            .health_check_result(self.active_app.as_str(), &provider.id)
+                            .await;
+                        self.health_check_form.open(
+                            &provider,
+                            self.active_app.clone(),
+                            last_result,
+                        );
+                    }
+                }
+                Action::EditModelAliases => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.model_alias_form
+                            .open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditHeaderRules => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.header_rules_form
+                            .open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditProviderProxy => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.upstream_proxy_form
+                            .open_provider(&provider, self.active_app.clone());
+                    }
+                }
+                Action::EditSpeedtest => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.speedtest_form
+                            .open(&provider, self.active_app.clone());
+                    }
+                }
+                Action::ExportProviderEnv => {
+                    if let Some(provider) = self.providers_view.get_selected() {
+                        self.provider_env_form
+                            .open(provider, self.active_app.clone());
+                    }
+                }
+                Action::OpenWebsite => self.open_selected_website(),
+                Action::ToggleFailover => match self.providers_view.toggle_failover() {
+                    Ok(Some((name, true))) => {
+                        self.show_toast(format!("Added {name} to failover queue"))
+                    }
+                    Ok(Some((name, false))) => {
+                        self.show_toast(format!("Removed {name} from failover queue"))
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.show_error(format!("Failed to update failover queue: {e}")),
+                },
+                Action::SortByRecency => match self.providers_view.toggle_sort_by_recency() {
+                    Ok(true) => self.show_toast("Sorting providers by last used"),
+                    Ok(false) => self.show_toast("Sorting providers by default order"),
+                    Err(e) => self.show_error(format!("Failed to save sort order: {e}")),
+                },
+                _ => {
+                    self.providers_view
+                        .handle_action(action, self.active_app.clone())
+                        .await;
+                }
+            },
+            ActiveView::Mcp => match action {
+                Action::TestMcpServer => self.test_selected_mcp_server(),
+                Action::Add => self.mcp_paste_form.open(self.active_app.clone()),
+                Action::Edit => {
+                    if let Some(server) = self.mcp_view.get_selected() {
+                        self.mcp_form.open_edit(&server);
+                    }
+                }
+                Action::EnableAllMcp => self.confirm_mcp_bulk_toggle(true),
+                Action::DisableAllMcp => self.confirm_mcp_bulk_toggle(false),
+                Action::ExportMcp => self
+                    .mcp_export_form
+                    .open(self.mcp_view.servers(), self.mcp_view.get_selected()),
+                _ => self.mcp_view.handle_action(action).await,
+            },
+            ActiveView::Proxy => match action {
+                Action::Edit => {
+                    let service = &self.state.proxy_service;
+                    match service.get_config().await.and_then(|config| {
+                        service
+                            .get_ip_allowlist()
+                            .map(|allowlist| (config, allowlist))
+                    }) {
+                        Ok((config, allowlist)) => self.listen_form.open(
+                            &config.listen_address,
+                            config.listen_port,
+                            &allowlist,
+                        ),
+                        Err(e) => self.show_error(format!("Failed to load proxy config: {e}")),
+                    }
+                }
+                Action::HybridMode => {
+                    if let Err(e) = self.hybrid_form.open(self.active_app.clone()) {
+                        self.show_error(format!("Failed to load hybrid mode config: {e}"));
+                    }
+                }
+                Action::EditRateLimit => {
+                    if let Err(e) = self.rate_limit_form.open(self.active_app.clone()) {
+                        self.show_error(format!("Failed to load rate limit: {e}"));
+                    }
+                }
+                Action::Takeover => {
+                    let app = self.active_app.clone();
+                    let name = app_display_name(&app);
+                    match self.proxy_view.toggle_takeover(&app).await {
+                        Ok(true) => {
+                            self.show_toast(format!("{name} is now routed through the proxy"))
+                        }
+                        Ok(false) => self.show_toast(format!("{name} live config restored")),
+                        Err(e) => self.show_error(format!("Takeover failed for {name}: {e}")),
+                    }
+                    self.status_refreshed_at = None;
+                }
+                Action::CycleLoadBalance => {
+                    let app = self.active_app.clone();
+                    let name = app_display_name(&app);
+                    match self.proxy_view.cycle_load_balance(&app) {
+                        Ok(strategy) => {
+                            self.show_toast(format!("{name} routing: {}", strategy.label()))
+                        }
+                        Err(e) => self.show_error(format!("Failed to update routing: {e}")),
+                    }
+                }
+                Action::ToggleAuthToken => self.toggle_auth_token().await,
+                Action::EditAppPort => {
+                    let app = self.active_app.clone();
+                    match self.state.proxy_service.get_dedicated_port(&app) {
+                        Ok(port) => self.app_port_form.open(app, port),
+                        Err(e) => self.show_error(format!("Failed to load app port: {e}")),
+                    }
+                }
+                Action::EditUnixSocket => match self.state.proxy_service.get_unix_socket_config() {
+                    Ok(config) => self.unix_socket_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load Unix socket settings: {e}")),
+                },
+                Action::EditAppCircuitBreaker => match self
+                    .state
+                    .db
+                    .get_proxy_config_for_app(self.active_app.as_str())
+                    .await
+                {
+                    Ok(config) => self
+                        .circuit_breaker_form
+                        .open_app(self.active_app.clone(), config.circuit_breaker_config()),
+                    Err(e) => {
+                        self.show_error(format!("Failed to load circuit breaker settings: {e}"))
+                    }
+                },
+                Action::EditCors => match self.state.proxy_service.get_cors_config() {
+                    Ok(config) => self.cors_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load CORS settings: {e}")),
+                },
+                Action::EditTls => match self.state.proxy_service.get_tls_config() {
+                    Ok(config) => self.tls_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load HTTPS settings: {e}")),
+                },
+                Action::ToggleStreamFailover => {
+                    let app = self.active_app.clone();
+                    let name = app_display_name(&app);
+                    match self.proxy_view.toggle_stream_failover(&app) {
+                        Ok(true) => self.show_toast(format!(
+                            "{name}: streams that break before the first event retry on the next provider"
+                        )),
+                        Ok(false) => self.show_toast(format!("{name}: stream retry off")),
+                        Err(e) => self.show_error(format!("Failed to update stream retry: {e}")),
+                    }
+                }
+                Action::ToggleHedging => match self.proxy_view.toggle_hedging() {
+                    Ok(config) if config.enabled => self.show_toast(format!(
+                        "Hedging on: requests slower than p{} (at least {}ms) are also sent to the next-best endpoint",
+                        config.percentile, config.min_delay_ms
+                    )),
+                    Ok(_) => self.show_toast("Hedging off"),
+                    Err(e) => self.show_error(format!("Failed to update hedging: {e}")),
+                },
+                Action::EditShadow => self.shadow_form.open(self.active_app.clone()),
+                Action::EditResponseCache => {
+                    match self.state.db.get_proxy_response_cache_config() {
+                        Ok(config) => self.response_cache_form.open(&config),
+                        Err(e) => self.show_error(format!("Failed to load cache settings: {e}")),
+                    }
+                }
+                Action::EditDns => match self.state.db.get_proxy_dns_config() {
+                    Ok(config) => self.dns_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load DNS settings: {e}")),
+                },
+                Action::EditSizeLimits => match self.state.db.get_proxy_size_limits() {
+                    Ok(config) => self.size_limit_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load size limits: {e}")),
+                },
+                Action::EditWebhooks => match self.state.db.get_proxy_webhook_config() {
+                    Ok(config) => self.webhook_form.open(&config),
+                    Err(e) => self.show_error(format!("Failed to load webhooks: {e}")),
+                },
+                Action::ViewFailoverEvents => self.failover_events_view.open(),
+                Action::EditSwitchBack => {
+                    let app = self.active_app.clone();
+                    match self.state.db.get_switch_back_config(app.as_str()) {
+                        Ok(config) => self.switch_back_form.open(app, &config),
+                        Err(e) => self.show_error(format!("Failed to load switch-back settings: {e}")),
+                    }
+                }
+                Action::SwitchBackNow => {
+                    match self
+                        .state
+                        .proxy_service
+                        .switch_back_now(self.active_app.as_str())
+                        .await
+                    {
+                        Ok(name) => {
+                            self.show_toast(format!("Switched back to {name}"));
+                            self.status_refreshed_at = None;
+                            self.refresh_data();
+                        }
+                        Err(e) => self.show_error(format!("Cannot switch back: {e}")),
+                    }
+                }
+                Action::EditUpstreamProxy => {
+                    if let Err(e) = self.upstream_proxy_form.open_global() {
+                        self.show_error(format!("Failed to load upstream proxy: {e}"));
+                    }
+                }
+                _ => self.proxy_view.handle_action(action).await,
+            },
+            ActiveView::History => match action {
+                Action::Select => self.history_view.open_detail(),
+                Action::Filter => self.history_view.open_filter(),
+                Action::ReplayRequest => self.open_replay(),
+                Action::NextPage => {
+                    if self.history_view.next_page() {
+                        self.refresh_data();
+                    }
+                }
+                Action::PrevPage => {
+                    if self.history_view.prev_page() {
+                        self.refresh_data();
+                    }
+                }
+                _ => {}
+            },
+            ActiveView::Prompts => match action {
+                Action::Add => self.prompt_editor.open_add(self.active_app.clone()),
+                Action::Edit | Action::Select => self.edit_selected_prompt(),
+                Action::Delete => self.delete_selected_prompt(),
+                _ => {}
+            },
+            ActiveView::Usage => match action {
+                Action::CycleUsageRange => {
+                    self.usage_view.cycle_range();
+                    self.refresh_data();
+                }
+                Action::CycleUsageBreakdown => {
+                    let breakdown = self.usage_view.cycle_breakdown();
+                    self.show_toast(format!("Showing usage {}", breakdown.label()));
+                }
+                Action::ExportUsage => self.usage_export_form.open(),
+                Action::EditPricing => self.pricing_editor.open(),
+                _ => {}
+            },
+            ActiveView::Logs => match action {
+                Action::CycleLogFilter => {
+                    let level = self.logs_view.cycle_level();
+                    self.show_toast(format!("Showing {level} and above"));
+                }
+                Action::ToggleFollow => {
+                    if self.logs_view.toggle_follow() {
+                        self.show_toast("Following new log entries");
+                    } else {
+                        self.show_toast("Stopped following");
+                    }
+                }
+                Action::SearchLogs => self.logs_view.open_search(),
+                _ => {}
+            },
+            ActiveView::Settings => match action {
+                Action::CycleTheme => self.cycle_theme(),
+                Action::CycleAutoRefresh => match self.settings_view.cycle_auto_refresh() {
+                    Ok(secs) => {
+                        self.show_toast(format!("Auto refresh: {}", auto_refresh_label(secs)))
+                    }
+                    Err(e) => self.show_error(format!("Failed to save auto refresh: {e}")),
+                },
+                Action::CycleHistoryLimit => match self.settings_view.cycle_history_limit() {
+                    Ok(limit) => {
+                        self.show_toast(format!("Request history: {}", history_limit_label(limit)))
+                    }
+                    Err(e) => self.show_error(format!("Failed to save history size: {e}")),
+                },
+                Action::CycleCaptureLimit => match self.settings_view.cycle_capture_limit() {
+                    Ok(limit) => {
+                        self.show_toast(format!("Request capture: {}", history_limit_label(limit)))
+                    }
+                    Err(e) => self.show_error(format!("Failed to save capture size: {e}")),
+                },
+                Action::CycleLogLevel => match self.settings_view.cycle_log_level() {
+                    Ok(level) => {
+                        self.show_toast(format!("Log level: {}", level.to_string().to_lowercase()))
+                    }
+                    Err(e) => self.show_error(format!("Failed to save log level: {e}")),
+                },
+                Action::CycleLogDestination => match self.settings_view.cycle_log_destination() {
+                    Ok(destination) => self.show_toast(format!(
+                        "Log output: {}",
+                        log_destination_label(destination)
+                    )),
+                    Err(e) => self.show_error(format!("Failed to change log output: {e}")),
+                },
+                Action::ToggleSwitchPreview => match self.settings_view.toggle_switch_preview() {
+                    Ok(enabled) => self.show_toast(if enabled {
+                        "Provider switches will show a preview first"
+                    } else {
+                        "Provider switch preview disabled"
+                    }),
+                    Err(e) => self.show_error(format!("Failed to save setting: {e}")),
+                },
+                Action::ToggleAccessLog => match self.settings_view.toggle_access_log() {
+                    Ok(enabled) => self.show_toast(format!(
+                        "Access log: {}",
+                        access_log_label(&self.state, enabled)
+                    )),
+                    Err(e) => self.show_error(format!("Failed to save access log setting: {e}")),
+                },
+                Action::CycleAccessLogSize => match self.settings_view.cycle_access_log_size() {
+                    Ok(mb) => self.show_toast(format!("Access log rotates at {mb} MB")),
+                    Err(e) => self.show_error(format!("Failed to save access log setting: {e}")),
+                },
+                Action::ToggleDesktopNotifications => {
+                    match self.settings_view.toggle_desktop_notifications() {
+                        Ok(enabled) => self.show_toast(format!(
+                            "Failover desktop notifications: {}",
+                            desktop_notifications_label(enabled)
+                        )),
+                        Err(e) => self.show_error(format!("Failed to save setting: {e}")),
+                    }
+                }
+                Action::SetMasterPassword => self
+                    .master_password_form
+                    .open(self.settings_view.has_master_password()),
+                Action::CycleIdleLock => match self.settings_view.cycle_idle_lock() {
+                    Ok(minutes) => {
+                        self.show_toast(format!("Idle lock: {}", idle_lock_label(minutes)))
+                    }
+                    Err(e) => self.show_error(format!("Failed to enable idle lock: {e}")),
+                },
+                Action::EditAutoBackup => self.backup_form.open(self.settings_view.auto_backup()),
+                Action::RestoreBackup => self.restore_dialog.open(),
+                Action::DatabaseMaintenance => self.maintenance_dialog.open(),
+                Action::ImportEnvProviders => self.env_import_dialog.open(),
+                Action::ExportConfig => self.export_form.open(),
+                Action::ImportConfig => self.import_form.open(),
+                _ => self.settings_view.handle_action(action).await,
+            },
+        }
+    }
+
+    /// 随配置导出的主题与自定义按键；`keys.toml` 读取失败时只导出主题
+    fn ui_preferences(&self) -> UiPreferences {
+        let keybindings = keymap::user_overrides().unwrap_or_else(|e| {
+            log::warn!("Skipping key bindings in export: {e}");
+            Default::default()
+        });
+        UiPreferences {
+            theme: Some(self.settings_view.theme_preset().name().to_string()),
+            keybindings,
+        }
+    }
+
+    /// 导入完成后应用界面偏好并刷新数据
+    fn finish_import(&mut self, summary: ImportSummary) {
+        let mut message = format!(
+            "Imported: {} added, {} overwritten, {} duplicated, {} skipped",
+            summary.added, summary.overwritten, summary.renamed, summary.skipped
+        );
+        if let Some(ui) = &summary.preferences {
+            match self.apply_ui_preferences(ui) {
+                Ok(()) => message.push_str(", theme & keys applied"),
+                Err(e) => {
+                    self.show_error(format!("Failed to apply theme & keys: {e}"));
+                    self.refresh_data();
+                    return;
+                }
+            }
+        }
+        self.show_toast(message);
+        self.refresh_data();
+    }
+
+    fn apply_ui_preferences(&mut self, ui: &UiPreferences) -> Result<(), String> {
+        if let Some(name) = &ui.theme {
+            let preset =
+                ThemePreset::from_name(name).ok_or_else(|| format!("unknown theme '{name}'"))?;
+            self.settings_view
+                .set_theme_preset(preset)
+                .map_err(|e| e.to_string())?;
+            let (theme, error) = load_theme(preset, self.color_support);
+            self.theme = theme;
+            if let Some(e) = error {
+                log::warn!("{e}");
+            }
+        }
+        if !ui.keybindings.is_empty() {
+            self.keymap = keymap::save_user_overrides(&ui.keybindings)?;
+        }
+        Ok(())
+    }
+
+    fn cycle_theme(&mut self) {
+        match self.settings_view.cycle_theme() {
+            Ok(preset) => {
+                let (theme, error) = load_theme(preset, self.color_support);
+                self.theme = theme;
+                match error {
+                    Some(e) => self.show_error(e),
+                    None => self.show_toast(format!("Theme: {}", preset.name())),
+                }
+            }
+            Err(e) => self.show_error(format!("Failed to save theme: {e}")),
+        }
+    }
+
+    /// 保存代理监听地址与 IP 白名单，运行中的代理会按新地址重启
+    async fn save_listen_address(&mut self, address: &str, port: u16, allowlist: &[String]) {
+        let service = &self.state.proxy_service;
+        let result = match service.set_ip_allowlist(allowlist).await {
+            Ok(_) => service.set_listen_address(address, port).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => {
+                self.listen_form.close();
+                self.show_toast(format!("Proxy listen address set to {address}:{port}"));
+                self.status_refreshed_at = None;
+                self.refresh_data();
+            }
+            Err(e) => self.listen_form.set_error(e),
+        }
+    }
+
+    /// 开启（生成新令牌）或关闭代理访问令牌
+    async fn toggle_auth_token(&mut self) {
+        let enable = self
+            .state
+            .proxy_service
+            .get_auth_token()
+            .ok()
+            .flatten()
+            .is_none();
+        match self
+            .state
+            .proxy_service
+            .set_auth_token_enabled(enable)
+            .await
+        {
+            Ok(Some(_)) => self.show_toast(
+                "Auth token generated; taken-over clients were updated, restart them to pick it up",
+            ),
+            Ok(None) => self.show_toast("Auth token removed"),
+            Err(e) => self.show_error(format!("Failed to update auth token: {e}")),
+        }
+        self.refresh_data();
+    }
+
+    /// 保存代理 HTTPS 设置，运行中的代理会立即重启生效
+    async fn save_tls_config(&mut self, config: ProxyTlsConfig) {
+        let enabled = config.enabled;
+        match self.state.proxy_service.set_tls_config(config).await {
+            Ok(()) => {
+                self.tls_form.close();
+                self.show_toast(if enabled {
+                    "Proxy now serves HTTPS".to_string()
+                } else {
+                    "Proxy HTTPS turned off".to_string()
+                });
+                self.status_refreshed_at = None;
+                self.refresh_data();
+            }
+            Err(e) => self.tls_form.set_error(e),
+        }
+    }
+
+    /// 保存代理 Unix socket 设置，运行中的代理会立即重启生效
+    async fn save_unix_socket_config(&mut self, config: UnixSocketConfig) {
+        let message = match (config.enabled, config.socket_only) {
+            (false, _) => "Proxy Unix socket turned off",
+            (true, false) => "Proxy now also listens on a Unix socket",
+            (true, true) => "Proxy now listens only on a Unix socket",
+        };
+        match self
+            .state
+            .proxy_service
+            .set_unix_socket_config(config)
+            .await
+        {
+            Ok(()) => {
+                self.unix_socket_form.close();
+                self.show_toast(message);
+                self.status_refreshed_at = None;
+                self.refresh_data();
+            }
+            Err(e) => self.unix_socket_form.set_error(e),
+        }
+    }
+
+    /// 保存熔断参数：供应商覆盖项写入供应商元数据，应用默认值写入 proxy_config，运行中的代理自动生效
+    async fn save_circuit_breaker(&mut self, tuning: CircuitBreakerOverride) {
+        let app = self.circuit_breaker_form.app_type().clone();
+        let result = match self.circuit_breaker_form.provider().cloned() {
+            Some(mut provider) => {
+                let name = provider.name.clone();
+                provider
+                    .meta
+                    .get_or_insert_with(Default::default)
+                    .circuit_breaker = (!tuning.is_empty()).then_some(tuning);
+                self.state
+                    .db
+                    .save_provider(app.as_str(), &provider)
+                    .map(|()| format!("Circuit breaker settings saved for {name}"))
+            }
+            None => match self.state.db.get_proxy_config_for_app(app.as_str()).await {
+                Ok(mut config) => {
+                    let tuned = tuning.apply(config.circuit_breaker_config());
+                    config.circuit_failure_threshold = tuned.failure_threshold;
+                    config.circuit_timeout_seconds = tuned.timeout_seconds as u32;
+                    config.circuit_error_rate_threshold = tuned.error_rate_threshold;
+                    config.circuit_min_requests = tuned.min_requests;
+                    self.state
+                        .db
+                        .update_proxy_config_for_app(config)
+                        .await
+                        .map(|()| {
+                            format!(
+                                "Circuit breaker defaults saved for {}",
+                                app_display_name(&app)
+                            )
+                        })
+                }
+                Err(e) => Err(e),
+            },
+        };
+        match result {
+            Ok(message) => {
+                self.circuit_breaker_form.close();
+                self.show_toast(message);
+                self.refresh_data();
+            }
+            Err(e) => self.circuit_breaker_form.set_error(e.to_string()),
+        }
+    }
+
+    /// 保存供应商健康检查设置，运行中的代理在下一轮调度时读取
+    fn save_health_check(&mut self, config: HealthCheckConfig) {
+        let Some(mut provider) = self.health_check_form.provider().cloned() else {
+            return;
+        };
+        let app = self.health_check_form.app_type().clone();
+        let enabled = config.enabled;
+        provider
+            .meta
+            .get_or_insert_with(Default::default)
+            .health_check = Some(config);
+        match self.state.db.save_provider(app.as_str(), &provider) {
+            Ok(()) => {
+                self.health_check_form.close();
+                self.show_toast(if enabled {
+                    format!("Health check enabled for {}", provider.name)
+                } else {
+                    format!("Health check disabled for {}", provider.name)
+                });
+                self.refresh_data();
+            }
+            Err(e) => self.health_check_form.set_error(e.to_string()),
+        }
+    }
+
+    /// 保存 DNS 设置，立即作用于之后的所有上游请求
+    fn save_dns(&mut self, config: DnsConfig) {
+        let hosts = config.overrides.len();
+        let cache_enabled = config.cache_enabled;
+        match self.state.proxy_service.set_dns_config(config) {
+            Ok(()) => {
+                self.dns_form.close();
+                self.show_toast(format!(
+                    "DNS saved: cache {}, {hosts} static host(s)",
+                    if cache_enabled { "on" } else { "off" }
+                ));
+                self.refresh_data();
+            }
+            Err(e) => self.dns_form.set_error(e),
+        }
+    }
+
+    /// 保存请求与响应体大小上限，之后的新请求即按新上限检查
+    fn save_size_limits(&mut self, config: SizeLimitConfig) {
+        if let Err(e) = self.state.db.set_proxy_size_limits(&config) {
+            self.size_limit_form.set_error(e.to_string());
+            return;
+        }
+        self.size_limit_form.close();
+        let limit = |kb: u64| match kb {
+            0 => "unlimited".to_string(),
+            kb => format!("{kb} KB"),
+        };
+        self.show_toast(format!(
+            "Size limits saved: request {}, response {}",
+            limit(config.max_request_kb),
+            limit(config.max_response_kb)
+        ));
+        self.refresh_data();
+    }
+
+    /// 保存 Webhook 设置，之后发生的故障事件即按新设置通知
+    fn save_switch_back(&mut self, config: SwitchBackConfig) {
+        let app = self.switch_back_form.app_type().clone();
+        if let Err(e) = self.state.db.set_switch_back_config(app.as_str(), &config) {
+            self.switch_back_form.set_error(e.to_string());
+            return;
+        }
+        self.switch_back_form.close();
+        let name = app_display_name(&app);
+        self.show_toast(match config.mode {
+            SwitchBackMode::Never => format!("{name}: never switch back to the preferred provider"),
+            mode => format!(
+                "{name} switch back: {} once the preferred provider is healthy for {}s",
+                mode.label(),
+                config.healthy_window_seconds
+            ),
+        });
+        self.refresh_data();
+    }
+
+    /// 校验当前密码后保存主密码，清除主密码会同时关闭空闲锁屏
+    fn save_master_password(&mut self, change: MasterPasswordChange) {
+        if !self.settings_view.verify_master_password(&change.current) {
+            self.master_password_form
+                .set_error("Current password is wrong".to_string());
+            return;
+        }
+        let removed = change.new.is_none();
+        if let Err(e) = self
+            .settings_view
+            .set_master_password(change.new.as_deref())
+        {
+            self.master_password_form.set_error(e.to_string());
+            return;
+        }
+        self.master_password_form.close();
+        self.show_toast(if removed {
+            "Master password removed; idle lock off"
+        } else {
+            "Master password saved"
+        });
+    }
+
+    fn save_webhooks(&mut self, config: WebhookConfig) {
+        if let Err(e) = self.state.db.set_proxy_webhook_config(&config) {
+            self.webhook_form.set_error(e.to_string());
+            return;
+        }
+        self.webhook_form.close();
+        if config.targets.is_empty() {
+            self.show_toast("Webhooks off");
+        } else {
+            self.show_toast(format!(
+                "Webhooks saved: {} URL(s), {} event type(s)",
+                config.targets.len(),
+                config.events.len()
+            ));
+        }
+        self.refresh_data();
+    }
+
+    async fn save_response_cache(&mut self, config: ResponseCacheConfig) {
+        if let Err(e) = self.state.db.set_proxy_response_cache_config(&config) {
+            self.response_cache_form.set_error(e.to_string());
+            return;
+        }
+        self.response_cache_form.close();
+        if config.enabled {
+            self.show_toast(format!(
+                "Response cache on: identical non-streaming requests reuse responses for {}s",
+                config.ttl_seconds
+            ));
+        } else {
+            self.state.proxy_service.clear_response_cache().await;
+            self.show_toast("Response cache off and cleared");
+        }
+        self.refresh_data();
+    }
+
+    async fn delete_selected_provider(&mut self) {
+        use cc_switch_lib::ProviderService;
+
+        if let Some(provider) = self.providers_view.get_selected() {
+            if ProviderService::delete(&self.state, self.active_app.clone(), &provider.id).is_ok() {
+                self.refresh_data();
+            }
+        }
+    }
+}
+
+fn app_display_name(app: &AppType) -> &'static str {
+    match app {
+        AppType::Claude => "Claude",
+        AppType::Codex => "Codex",
+        AppType::Gemini => "Gemini",
+    }
+}
+
+/// 加载预设主题与用户覆盖项并按终端颜色能力降级，失败时回退到预设主题
+fn load_theme(preset: ThemePreset, color_support: ColorSupport) -> (Theme, Option<String>) {
+    let (theme, error) = match Theme::load_user(preset) {
+        Ok(theme) => (theme, None),
+        Err(e) => {
+            log::warn!("Failed to load user theme, using preset: {e}");
+            (preset.theme(), Some(e))
+        }
+    };
+    (theme.degrade(color_support), error)
+}
+
+pub async fn run(state: Arc<AppState>, color_override: Option<ColorSupport>) -> Result<()> {
+    let color_support = color_override.unwrap_or_else(ColorSupport::detect);
+    log::info!("Terminal color support: {color_support:?}");
+
+    // 先应用日志设置再进入备用屏幕，避免 stderr 日志破坏界面
+    let mut app = App::new(state, color_support);
+    let mut terminal = terminal::init()?;
+
+    // Initial data load
+    app.refresh_data();
+
+    loop {
+        terminal.draw(|frame| app.render(frame))?;
+
+        if event::poll(Duration::from_millis(100))? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    app.handle_key(key).await;
+                }
+                Event::Mouse(mouse) => app.handle_mouse(mouse).await,
+                Event::Paste(text) => app.handle_paste(&text).await,
+                _ => {}
+            }
+        }
+
+        app.tick().await;
+
+        if app.should_quit {
+            break;
+        }
+    }
+
+    terminal::restore(&mut terminal)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(seq: &mut KeySequence, keys: &str) -> Vec<KeySeqResult> {
+        let keymap = Keymap::default();
+        keys.chars()
+            .map(|c| {
+                let key = KeyEvent::from(KeyCode::Char(c));
+                seq.feed(key.code, keymap.resolve(&key, ActiveView::Providers))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gg_and_shift_g_jump_to_edges() {
+        let mut seq = KeySequence::default();
+        assert_eq!(
+            feed_all(&mut seq, "gg"),
+            vec![KeySeqResult::Pending, KeySeqResult::Nav(NavAction::Top)]
+        );
+        assert_eq!(
+            feed_all(&mut seq, "G"),
+            vec![KeySeqResult::Nav(NavAction::Bottom)]
+        );
+    }
+
+    #[test]
+    fn count_prefix_applies_to_motion() {
+        let mut seq = KeySequence::default();
+        assert_eq!(
+            feed_all(&mut seq, "5j"),
+            vec![KeySeqResult::Pending, KeySeqResult::Nav(NavAction::Down(5))]
+        );
+        assert_eq!(
+            feed_all(&mut seq, "52k"),
+            vec![
+                KeySeqResult::Pending,
+                KeySeqResult::Pending,
+                KeySeqResult::Nav(NavAction::Up(52))
+            ]
+        );
+        // 计数不会残留到下一次移动
+        assert_eq!(
+            seq.feed(KeyCode::Down, Some(Action::Down)),
+            KeySeqResult::Nav(NavAction::Down(1))
+        );
+    }
+
+    #[test]
+    fn bound_digit_counts_before_motion() {
+        let mut seq = KeySequence::default();
+        assert_eq!(
+            feed_all(&mut seq, "3j"),
+            vec![KeySeqResult::Pending, KeySeqResult::Nav(NavAction::Down(3))]
+        );
+        assert_eq!(seq.expire(Instant::now() + DIGIT_TIMEOUT), None);
+    }
+
+    #[test]
+    fn bound_digit_runs_its_action_when_not_followed_by_motion() {
+        let mut seq = KeySequence::default();
+        assert_eq!(
+            feed_all(&mut seq, "2a"),
+            vec![
+                KeySeqResult::Pending,
+                KeySeqResult::Interrupted(Action::ViewMcp)
+            ]
+        );
+
+        assert_eq!(feed_all(&mut seq, "2"), vec![KeySeqResult::Pending]);
+        assert_eq!(seq.expire(Instant::now()), None);
+        assert_eq!(
+            seq.expire(Instant::now() + DIGIT_TIMEOUT),
+            Some(Action::ViewMcp)
+        );
+        // 超时后计数被放弃
+        assert_eq!(
+            feed_all(&mut seq, "j"),
+            vec![KeySeqResult::Nav(NavAction::Down(1))]
+        );
+    }
+
+    #[test]
+    fn other_keys_pass_through() {
+        let mut seq = KeySequence::default();
+        assert_eq!(
+            feed_all(&mut seq, "gaj"),
+            vec![
+                KeySeqResult::Pending,
+                KeySeqResult::Passthrough,
+                KeySeqResult::Nav(NavAction::Down(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn nav_action_clamps_to_list_bounds() {
+        assert_eq!(NavAction::Down(10).apply(Some(1), 4), Some(3));
+        assert_eq!(NavAction::Up(10).apply(Some(1), 4), Some(0));
+        assert_eq!(NavAction::Bottom.apply(None, 4), Some(3));
+        assert_eq!(NavAction::Top.apply(Some(3), 4), Some(0));
+        assert_eq!(NavAction::Down(1).apply(None, 0), None);
+    }
+
+    #[test]
+    fn nav_action_down_without_selection_counts_from_first_row() {
+        assert_eq!(NavAction::Down(1).apply(None, 4), Some(0));
+        assert_eq!(NavAction::Down(3).apply(None, 4), Some(2));
+        assert_eq!(NavAction::Down(10).apply(None, 4), Some(3));
+        assert_eq!(NavAction::Up(1).apply(None, 4), Some(0));
+    }
 }
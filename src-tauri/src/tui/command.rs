@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use cc_switch_lib::AppType;
+
+const COMMANDS: [&str; 6] = ["app", "export", "proxy", "q", "quit", "switch"];
+const APP_NAMES: [&str; 3] = ["claude", "codex", "gemini"];
+const PROXY_ARGS: [&str; 2] = ["start", "stop"];
+
+/// `:` 命令行解析出的命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Quit,
+    Switch(String),
+    App(AppType),
+    ProxyStart,
+    ProxyStop,
+    Export(PathBuf),
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let (name, arg) = match input.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (input, ""),
+        };
+
+        match name {
+            "q" | "q!" | "quit" => Ok(Self::Quit),
+            "switch" if arg.is_empty() => Err("Usage: :switch <provider name>".to_string()),
+            "switch" => Ok(Self::Switch(arg.to_string())),
+            "app" => AppType::from_str(arg)
+                .map(Self::App)
+                .map_err(|_| format!("Unknown app '{arg}' (claude, codex, gemini)")),
+            "proxy" => match arg {
+                "start" => Ok(Self::ProxyStart),
+                "stop" => Ok(Self::ProxyStop),
+                _ => Err("Usage: :proxy start|stop".to_string()),
+            },
+            "export" if arg.is_empty() => Err("Usage: :export <path>".to_string()),
+            "export" => Ok(Self::Export(expand_home(arg))),
+            "" => Err("Empty command".to_string()),
+            other => Err(format!("Unknown command: {other}")),
+        }
+    }
+}
+
+/// Tab 补全：命令名、`:app` / `:proxy` 参数及 `:switch` 的供应商名称
+///
+/// 多个候选时补全到最长公共前缀，前缀无法再延长时取第一个候选。
+pub fn complete(input: &str, provider_names: &[String]) -> Option<String> {
+    match input.split_once(' ') {
+        None => complete_word(input, COMMANDS.iter().copied()),
+        Some((name, arg)) => {
+            let completed = match name {
+                "switch" => complete_word(arg, provider_names.iter().map(String::as_str)),
+                "app" => complete_word(arg, APP_NAMES.iter().copied()),
+                "proxy" => complete_word(arg, PROXY_ARGS.iter().copied()),
+                _ => None,
+            }?;
+            Some(format!("{name} {completed}"))
+        }
+    }
+}
+
+fn complete_word<'a>(prefix: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let lower = prefix.to_lowercase();
+    let matches: Vec<&str> = candidates
+        .filter(|c| c.to_lowercase().starts_with(&lower))
+        .collect();
+
+    match matches.as_slice() {
+        [] => None,
+        [only] => Some(only.to_string()),
+        _ => {
+            let common = longest_common_prefix(&matches);
+            if common != prefix {
+                Some(common)
+            } else {
+                Some(matches[0].to_string())
+            }
+        }
+    }
+}
+
+fn longest_common_prefix(words: &[&str]) -> String {
+    let Some(first) = words.first() else {
+        return String::new();
+    };
+    let mut prefix: Vec<char> = first.chars().collect();
+    for word in &words[1..] {
+        let len = prefix
+            .iter()
+            .zip(word.chars())
+            .take_while(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+            .count();
+        prefix.truncate(len);
+    }
+    prefix.into_iter().collect()
+}
+
+pub(crate) fn expand_home(raw: &str) -> PathBuf {
+    if let Some(stripped) = raw.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(stripped);
+        }
+    }
+    PathBuf::from(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse("q"), Ok(Command::Quit));
+        assert_eq!(
+            Command::parse("switch My Relay "),
+            Ok(Command::Switch("My Relay".to_string()))
+        );
+        assert_eq!(
+            Command::parse("app Codex"),
+            Ok(Command::App(AppType::Codex))
+        );
+        assert_eq!(Command::parse("proxy stop"), Ok(Command::ProxyStop));
+        assert_eq!(
+            Command::parse("export /tmp/out.json"),
+            Ok(Command::Export(PathBuf::from("/tmp/out.json")))
+        );
+        assert!(Command::parse("app vim").is_err());
+        assert!(Command::parse("switch").is_err());
+        assert!(Command::parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn completes_commands_and_arguments() {
+        let names = vec![
+            "OpenRouter".to_string(),
+            "Official".to_string(),
+            "Kimi".to_string(),
+        ];
+        assert_eq!(complete("sw", &names), Some("switch".to_string()));
+        assert_eq!(complete("app co", &names), Some("app codex".to_string()));
+        assert_eq!(
+            complete("switch k", &names),
+            Some("switch Kimi".to_string())
+        );
+        assert_eq!(complete("switch o", &names), Some("switch O".to_string()));
+        assert_eq!(
+            complete("switch O", &names),
+            Some("switch OpenRouter".to_string())
+        );
+        assert_eq!(complete("switch z", &names), None);
+    }
+}
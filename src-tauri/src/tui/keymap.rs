@@ -0,0 +1,923 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::app::ActiveView;
+
+const KEYS_FILE: &str = "keys.toml";
+
+/// 可绑定按键的 TUI 动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    ViewProviders,
+    ViewMcp,
+    ViewProxy,
+    ViewSettings,
+    ViewHistory,
+    ViewLogs,
+    ViewPrompts,
+    ViewUsage,
+    TestMcpServer,
+    ExportMcp,
+    EnableAllMcp,
+    DisableAllMcp,
+    PrevApp,
+    NextApp,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    Select,
+    Add,
+    Edit,
+    Delete,
+    ToggleProxy,
+    CycleTheme,
+    CycleAutoRefresh,
+    CommandMode,
+    Reload,
+    TestConnection,
+    TestLatency,
+    Endpoints,
+    ToggleDryRun,
+    OpenWebsite,
+    ToggleFailover,
+    SortByRecency,
+    NextPage,
+    PrevPage,
+    Filter,
+    CycleHistoryLimit,
+    Takeover,
+    HybridMode,
+    ExportConfig,
+    ImportConfig,
+    CycleLogLevel,
+    CycleLogDestination,
+    ToggleSwitchPreview,
+    CycleLogFilter,
+    ToggleFollow,
+    SearchLogs,
+    CycleUsageRange,
+    CycleUsageBreakdown,
+    ExportUsage,
+    EditPricing,
+    EditBudget,
+    CycleLoadBalance,
+    EditWeight,
+    EditModelAliases,
+    EditHeaderRules,
+    ToggleAccessLog,
+    CycleAccessLogSize,
+    CycleCaptureLimit,
+    ReplayRequest,
+    EditRateLimit,
+    EditConcurrency,
+    EditCircuitBreaker,
+    EditHealthCheck,
+    ToggleStreamFailover,
+    ToggleHedging,
+    EditShadow,
+    EditResponseCache,
+    EditTls,
+    ToggleAuthToken,
+    EditCors,
+    EditAppPort,
+    EditUnixSocket,
+    EditAppCircuitBreaker,
+    EditUpstreamProxy,
+    EditProviderProxy,
+    EditDns,
+    EditSizeLimits,
+    EditWebhooks,
+    ToggleDesktopNotifications,
+    ViewFailoverEvents,
+    EditSwitchBack,
+    SwitchBackNow,
+    EditSpeedtest,
+    SetMasterPassword,
+    CycleIdleLock,
+    EditAutoBackup,
+    RestoreBackup,
+    DatabaseMaintenance,
+    ImportEnvProviders,
+    ExportProviderEnv,
+    SetPrimaryEndpoint,
+    PinEndpoint,
+    EditEndpointWeight,
+    EditEndpointRegion,
+    EditRegionOrder,
+    CycleUrlStrategy,
+    CycleEndpointFallback,
+    RefreshFailoverEvents,
+}
+
+impl Action {
+    const ALL: [Action; 101] = [
+        Self::Quit,
+        Self::ViewProviders,
+        Self::ViewMcp,
+        Self::ViewProxy,
+        Self::ViewSettings,
+        Self::ViewHistory,
+        Self::ViewLogs,
+        Self::ViewPrompts,
+        Self::ViewUsage,
+        Self::TestMcpServer,
+        Self::ExportMcp,
+        Self::EnableAllMcp,
+        Self::DisableAllMcp,
+        Self::PrevApp,
+        Self::NextApp,
+        Self::Up,
+        Self::Down,
+        Self::Top,
+        Self::Bottom,
+        Self::Select,
+        Self::Add,
+        Self::Edit,
+        Self::Delete,
+        Self::ToggleProxy,
+        Self::CycleTheme,
+        Self::CycleAutoRefresh,
+        Self::CommandMode,
+        Self::Reload,
+        Self::TestConnection,
+        Self::TestLatency,
+        Self::Endpoints,
+        Self::ToggleDryRun,
+        Self::OpenWebsite,
+        Self::ToggleFailover,
+        Self::SortByRecency,
+        Self::NextPage,
+        Self::PrevPage,
+        Self::Filter,
+        Self::CycleHistoryLimit,
+        Self::Takeover,
+        Self::HybridMode,
+        Self::ExportConfig,
+        Self::ImportConfig,
+        Self::CycleLogLevel,
+        Self::CycleLogDestination,
+        Self::ToggleSwitchPreview,
+        Self::CycleLogFilter,
+        Self::ToggleFollow,
+        Self::SearchLogs,
+        Self::CycleUsageRange,
+        Self::CycleUsageBreakdown,
+        Self::ExportUsage,
+        Self::EditPricing,
+        Self::EditBudget,
+        Self::CycleLoadBalance,
+        Self::EditWeight,
+        Self::EditModelAliases,
+        Self::EditHeaderRules,
+        Self::ToggleAccessLog,
+        Self::CycleAccessLogSize,
+        Self::CycleCaptureLimit,
+        Self::ReplayRequest,
+        Self::EditRateLimit,
+        Self::EditConcurrency,
+        Self::EditCircuitBreaker,
+        Self::EditHealthCheck,
+        Self::ToggleStreamFailover,
+        Self::ToggleHedging,
+        Self::EditShadow,
+        Self::EditResponseCache,
+        Self::EditTls,
+        Self::ToggleAuthToken,
+        Self::EditCors,
+        Self::EditAppPort,
+        Self::EditUnixSocket,
+        Self::EditAppCircuitBreaker,
+        Self::EditUpstreamProxy,
+        Self::EditProviderProxy,
+        Self::EditDns,
+        Self::EditSizeLimits,
+        Self::EditWebhooks,
+        Self::ToggleDesktopNotifications,
+        Self::ViewFailoverEvents,
+        Self::EditSwitchBack,
+        Self::SwitchBackNow,
+        Self::EditSpeedtest,
+        Self::SetMasterPassword,
+        Self::CycleIdleLock,
+        Self::EditAutoBackup,
+        Self::RestoreBackup,
+        Self::DatabaseMaintenance,
+        Self::ImportEnvProviders,
+        Self::ExportProviderEnv,
+        Self::SetPrimaryEndpoint,
+        Self::PinEndpoint,
+        Self::EditEndpointWeight,
+        Self::EditEndpointRegion,
+        Self::EditRegionOrder,
+        Self::CycleUrlStrategy,
+        Self::CycleEndpointFallback,
+        Self::RefreshFailoverEvents,
+    ];
+
+    /// `keys.toml` 中使用的动作名
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::ViewProviders => "view_providers",
+            Self::ViewMcp => "view_mcp",
+            Self::ViewProxy => "view_proxy",
+            Self::ViewSettings => "view_settings",
+            Self::ViewHistory => "view_history",
+            Self::ViewLogs => "view_logs",
+            Self::ViewPrompts => "view_prompts",
+            Self::ViewUsage => "view_usage",
+            Self::TestMcpServer => "test_mcp_server",
+            Self::ExportMcp => "export_mcp",
+            Self::EnableAllMcp => "enable_all_mcp",
+            Self::DisableAllMcp => "disable_all_mcp",
+            Self::PrevApp => "prev_app",
+            Self::NextApp => "next_app",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Select => "select",
+            Self::Add => "add",
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::ToggleProxy => "toggle_proxy",
+            Self::CycleTheme => "cycle_theme",
+            Self::CycleAutoRefresh => "cycle_auto_refresh",
+            Self::CommandMode => "command_mode",
+            Self::Reload => "reload",
+            Self::TestConnection => "test_connection",
+            Self::TestLatency => "test_latency",
+            Self::Endpoints => "endpoints",
+            Self::ToggleDryRun => "toggle_dry_run",
+            Self::OpenWebsite => "open_website",
+            Self::ToggleFailover => "toggle_failover",
+            Self::SortByRecency => "sort_by_recency",
+            Self::NextPage => "next_page",
+            Self::PrevPage => "prev_page",
+            Self::Filter => "filter",
+            Self::CycleHistoryLimit => "cycle_history_limit",
+            Self::Takeover => "takeover",
+            Self::HybridMode => "hybrid_mode",
+            Self::ExportConfig => "export_config",
+            Self::ImportConfig => "import_config",
+            Self::CycleLogLevel => "cycle_log_level",
+            Self::CycleLogDestination => "cycle_log_destination",
+            Self::ToggleSwitchPreview => "toggle_switch_preview",
+            Self::CycleLogFilter => "cycle_log_filter",
+            Self::ToggleFollow => "toggle_follow",
+            Self::SearchLogs => "search_logs",
+            Self::CycleUsageRange => "cycle_usage_range",
+            Self::CycleUsageBreakdown => "cycle_usage_breakdown",
+            Self::ExportUsage => "export_usage",
+            Self::EditPricing => "edit_pricing",
+            Self::EditBudget => "edit_budget",
+            Self::CycleLoadBalance => "cycle_load_balance",
+            Self::EditWeight => "edit_weight",
+            Self::EditConcurrency => "edit_concurrency",
+            Self::EditCircuitBreaker => "edit_circuit_breaker",
+            Self::EditHealthCheck => "edit_health_check",
+            Self::ToggleStreamFailover => "toggle_stream_failover",
+            Self::ToggleHedging => "toggle_hedging",
+            Self::EditShadow => "edit_shadow",
+            Self::EditResponseCache => "edit_response_cache",
+            Self::EditUpstreamProxy => "edit_upstream_proxy",
+            Self::EditProviderProxy => "edit_provider_proxy",
+            Self::EditDns => "edit_dns",
+            Self::EditSizeLimits => "edit_size_limits",
+            Self::EditWebhooks => "edit_webhooks",
+            Self::ToggleDesktopNotifications => "toggle_desktop_notifications",
+            Self::ViewFailoverEvents => "view_failover_events",
+            Self::EditSwitchBack => "edit_switch_back",
+            Self::SwitchBackNow => "switch_back_now",
+            Self::EditSpeedtest => "edit_speedtest",
+            Self::SetMasterPassword => "set_master_password",
+            Self::CycleIdleLock => "cycle_idle_lock",
+            Self::EditAutoBackup => "edit_auto_backup",
+            Self::RestoreBackup => "restore_backup",
+            Self::DatabaseMaintenance => "database_maintenance",
+            Self::ImportEnvProviders => "import_env_providers",
+            Self::ExportProviderEnv => "export_provider_env",
+            Self::EditModelAliases => "edit_model_aliases",
+            Self::EditHeaderRules => "edit_header_rules",
+            Self::ToggleAccessLog => "toggle_access_log",
+            Self::CycleAccessLogSize => "cycle_access_log_size",
+            Self::CycleCaptureLimit => "cycle_capture_limit",
+            Self::ReplayRequest => "replay_request",
+            Self::EditRateLimit => "edit_rate_limit",
+            Self::EditTls => "edit_tls",
+            Self::ToggleAuthToken => "toggle_auth_token",
+            Self::EditCors => "edit_cors",
+            Self::EditAppPort => "edit_app_port",
+            Self::EditUnixSocket => "edit_unix_socket",
+            Self::EditAppCircuitBreaker => "edit_app_circuit_breaker",
+            Self::SetPrimaryEndpoint => "set_primary_endpoint",
+            Self::PinEndpoint => "pin_endpoint",
+            Self::EditEndpointWeight => "edit_endpoint_weight",
+            Self::EditEndpointRegion => "edit_endpoint_region",
+            Self::EditRegionOrder => "edit_region_order",
+            Self::CycleUrlStrategy => "cycle_url_strategy",
+            Self::CycleEndpointFallback => "cycle_endpoint_fallback",
+            Self::RefreshFailoverEvents => "refresh_failover_events",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Quit => &["q"],
+            Self::ViewProviders => &["1"],
+            Self::ViewMcp => &["2"],
+            Self::ViewProxy => &["3"],
+            Self::ViewSettings => &["4"],
+            Self::ViewHistory => &["H"],
+            Self::ViewLogs => &["L"],
+            Self::ViewPrompts => &["P"],
+            Self::ViewUsage => &["U"],
+            Self::TestMcpServer => &["t"],
+            Self::ExportMcp => &["E"],
+            Self::EnableAllMcp => &["A"],
+            Self::DisableAllMcp => &["X"],
+            Self::PrevApp => &["left"],
+            Self::NextApp => &["right"],
+            Self::Up => &["up", "k"],
+            Self::Down => &["down", "j"],
+            Self::Top => &["home"],
+            Self::Bottom => &["G", "end"],
+            Self::Select => &["enter"],
+            Self::Add => &["a"],
+            Self::Edit => &["e"],
+            Self::Delete => &["d"],
+            Self::ToggleProxy => &["p"],
+            Self::CycleTheme => &["t"],
+            Self::CycleAutoRefresh => &["r"],
+            Self::CommandMode => &[":"],
+            Self::Reload => &["f5", "ctrl+r"],
+            Self::TestConnection => &["t"],
+            Self::TestLatency => &["T"],
+            Self::Endpoints => &["E"],
+            Self::ToggleDryRun => &["D"],
+            Self::OpenWebsite => &["o"],
+            Self::ToggleFailover => &["f"],
+            Self::SortByRecency => &["s"],
+            Self::NextPage => &["]", "pagedown"],
+            Self::PrevPage => &["[", "pageup"],
+            Self::Filter => &["/"],
+            Self::CycleHistoryLimit => &["h"],
+            Self::Takeover => &["t"],
+            Self::HybridMode => &["m"],
+            Self::ExportConfig => &["e"],
+            Self::ImportConfig => &["i"],
+            Self::CycleLogLevel => &["l"],
+            Self::CycleLogDestination => &["o"],
+            Self::ToggleSwitchPreview => &["v"],
+            Self::CycleLogFilter => &["f"],
+            Self::ToggleFollow => &["F"],
+            Self::SearchLogs => &["/"],
+            Self::CycleUsageRange => &["r"],
+            Self::CycleUsageBreakdown => &["b"],
+            Self::ExportUsage => &["E"],
+            Self::EditPricing => &["$"],
+            Self::EditBudget => &["B"],
+            Self::CycleLoadBalance => &["b"],
+            Self::EditWeight => &["w"],
+            Self::EditConcurrency => &["C"],
+            Self::EditCircuitBreaker => &["x"],
+            Self::EditHealthCheck => &["h"],
+            Self::ToggleStreamFailover => &["s"],
+            Self::ToggleHedging => &["h"],
+            Self::EditShadow => &["M"],
+            Self::EditResponseCache => &["C"],
+            Self::EditUpstreamProxy => &["O"],
+            Self::EditProviderProxy => &["O"],
+            Self::EditDns => &["N"],
+            Self::EditSizeLimits => &["Z"],
+            Self::EditWebhooks => &["W"],
+            Self::ToggleDesktopNotifications => &["n"],
+            Self::ViewFailoverEvents => &["F"],
+            Self::EditSwitchBack => &["R"],
+            Self::SwitchBackNow => &["B"],
+            Self::EditSpeedtest => &["S"],
+            Self::SetMasterPassword => &["m"],
+            Self::CycleIdleLock => &["x"],
+            Self::EditAutoBackup => &["b"],
+            Self::RestoreBackup => &["u"],
+            Self::DatabaseMaintenance => &["D"],
+            Self::ImportEnvProviders => &["E"],
+            Self::ExportProviderEnv => &["y"],
+            Self::EditModelAliases => &["M"],
+            Self::EditHeaderRules => &["R"],
+            Self::ToggleAccessLog => &["a"],
+            Self::CycleAccessLogSize => &["z"],
+            Self::CycleCaptureLimit => &["c"],
+            Self::ReplayRequest => &["r"],
+            Self::EditRateLimit => &["l"],
+            Self::EditTls => &["S"],
+            Self::ToggleAuthToken => &["K"],
+            Self::EditCors => &["c"],
+            Self::EditAppPort => &["o"],
+            Self::EditUnixSocket => &["u"],
+            Self::EditAppCircuitBreaker => &["x"],
+            Self::SetPrimaryEndpoint => &["enter", "p"],
+            Self::PinEndpoint => &["P"],
+            Self::EditEndpointWeight => &["w"],
+            Self::EditEndpointRegion => &["r"],
+            Self::EditRegionOrder => &["R"],
+            Self::CycleUrlStrategy => &["s"],
+            Self::CycleEndpointFallback => &["f"],
+            Self::RefreshFailoverEvents => &["r"],
+        }
+    }
+
+    /// 动作生效的范围，None 表示全局；不同范围的动作可以共用同一按键
+    fn scope(&self) -> Option<KeyScope> {
+        match self {
+            Self::CycleTheme
+            | Self::CycleAutoRefresh
+            | Self::CycleHistoryLimit
+            | Self::ExportConfig
+            | Self::ImportConfig
+            | Self::CycleLogLevel
+            | Self::CycleLogDestination
+            | Self::ToggleSwitchPreview
+            | Self::ToggleAccessLog
+            | Self::CycleAccessLogSize
+            | Self::ToggleDesktopNotifications
+            | Self::SetMasterPassword
+            | Self::CycleIdleLock
+            | Self::EditAutoBackup
+            | Self::RestoreBackup
+            | Self::DatabaseMaintenance
+            | Self::ImportEnvProviders
+            | Self::CycleCaptureLimit => Some(KeyScope::View(ActiveView::Settings)),
+            Self::TestConnection
+            | Self::TestLatency
+            | Self::Endpoints
+            | Self::ToggleDryRun
+            | Self::OpenWebsite
+            | Self::ToggleFailover
+            | Self::SortByRecency
+            | Self::EditBudget
+            | Self::EditWeight
+            | Self::EditConcurrency
+            | Self::EditCircuitBreaker
+            | Self::EditHealthCheck
+            | Self::EditModelAliases
+            | Self::EditHeaderRules
+            | Self::EditProviderProxy
+            | Self::EditSpeedtest
+            | Self::ExportProviderEnv => Some(KeyScope::View(ActiveView::Providers)),
+            Self::NextPage | Self::PrevPage | Self::Filter | Self::ReplayRequest => {
+                Some(KeyScope::View(ActiveView::History))
+            }
+            Self::Takeover
+            | Self::HybridMode
+            | Self::CycleLoadBalance
+            | Self::EditRateLimit
+            | Self::ToggleStreamFailover
+            | Self::ToggleHedging
+            | Self::EditShadow
+            | Self::EditResponseCache
+            | Self::EditUpstreamProxy
+            | Self::EditDns
+            | Self::EditSizeLimits
+            | Self::EditWebhooks
+            | Self::ViewFailoverEvents
+            | Self::EditSwitchBack
+            | Self::SwitchBackNow
+            | Self::EditTls
+            | Self::ToggleAuthToken
+            | Self::EditCors
+            | Self::EditAppPort
+            | Self::EditUnixSocket
+            | Self::EditAppCircuitBreaker => Some(KeyScope::View(ActiveView::Proxy)),
+            Self::TestMcpServer | Self::ExportMcp | Self::EnableAllMcp | Self::DisableAllMcp => {
+                Some(KeyScope::View(ActiveView::Mcp))
+            }
+            Self::CycleLogFilter | Self::ToggleFollow | Self::SearchLogs => {
+                Some(KeyScope::View(ActiveView::Logs))
+            }
+            Self::CycleUsageRange
+            | Self::CycleUsageBreakdown
+            | Self::ExportUsage
+            | Self::EditPricing => Some(KeyScope::View(ActiveView::Usage)),
+            Self::SetPrimaryEndpoint
+            | Self::PinEndpoint
+            | Self::EditEndpointWeight
+            | Self::EditEndpointRegion
+            | Self::EditRegionOrder
+            | Self::CycleUrlStrategy
+            | Self::CycleEndpointFallback => Some(KeyScope::Endpoints),
+            Self::RefreshFailoverEvents => Some(KeyScope::FailoverEvents),
+            _ => None,
+        }
+    }
+}
+
+/// 按键生效的范围：主视图，或接管按键的弹窗
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyScope {
+    View(ActiveView),
+    /// 供应商端点管理弹窗
+    Endpoints,
+    /// 故障转移历史弹窗
+    FailoverEvents,
+    /// 请求历史的详情弹窗，只使用全局的导航与关闭按键
+    RequestDetail,
+}
+
+/// 单个按键（键码 + Ctrl/Alt 修饰键）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift 已体现在字符大小写或 BackTab 中，忽略以便 `G` 与 `Shift+g` 等价
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        Self { code, modifiers }
+    }
+
+    /// 解析 `ctrl+q`、`shift+tab`、`F5`、`G`、`space` 等写法
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut shift = false;
+        let parts: Vec<&str> = spec.split('+').collect();
+        let (key, mods) = match parts.split_last() {
+            // 单独的 "+" 键
+            Some((last, rest)) if last.is_empty() && !rest.is_empty() => {
+                ("+", &rest[..rest.len() - 1])
+            }
+            Some((last, rest)) => (*last, rest),
+            None => return Err(format!("{KEYS_FILE}: empty key binding")),
+        };
+        for m in mods {
+            match m.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => shift = true,
+                other => {
+                    return Err(format!(
+                        "{KEYS_FILE}: unknown modifier '{other}' in '{spec}'"
+                    ))
+                }
+            }
+        }
+
+        let lower = key.to_lowercase();
+        let code = match lower.as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if shift => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n) if (1..=24).contains(&n) => KeyCode::F(n),
+                        _ => return Err(format!("{KEYS_FILE}: unknown key '{spec}'")),
+                    },
+                }
+            }
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// 用于状态栏提示的简短展示
+    fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            other => format!("{other:?}"),
+        };
+
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        label.push_str(&key);
+        label
+    }
+}
+
+/// `keys.toml` 中单个动作的绑定，可写成字符串或字符串数组
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// 按键到动作的映射，默认值可被 `~/.config/cc-switch/keys.toml` 覆盖
+///
+/// ```toml
+/// quit = "ctrl+q"
+/// down = ["down", "j", "ctrl+n"]
+/// ```
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+    lookup: HashMap<(Option<KeyScope>, KeyBinding), Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|k| KeyBinding::parse(k).expect("default key binding must be valid"))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self::from_bindings(bindings, &[])
+    }
+}
+
+impl Keymap {
+    /// 加载用户按键配置，文件不存在时返回默认映射
+    pub fn load_user() -> Result<Self, String> {
+        let Some(path) = keys_file_path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::from_toml(&content)
+    }
+
+    fn from_toml(content: &str) -> Result<Self, String> {
+        let file: HashMap<String, KeySpec> =
+            toml::from_str(content).map_err(|e| format!("{KEYS_FILE}: {}", e.message()))?;
+
+        let mut bindings = Self::default().bindings;
+        let mut overridden = Vec::new();
+        for (name, spec) in file {
+            let action = Action::from_name(&name)
+                .ok_or_else(|| format!("{KEYS_FILE}: unknown action '{name}'"))?;
+            let specs = match spec {
+                KeySpec::One(s) => vec![s],
+                KeySpec::Many(v) => v,
+            };
+            let keys = specs
+                .iter()
+                .map(|s| KeyBinding::parse(s))
+                .collect::<Result<Vec<_>, _>>()?;
+            bindings.insert(action, keys);
+            overridden.push(action);
+        }
+        Ok(Self::from_bindings(bindings, &overridden))
+    }
+
+    /// 构建反向索引；同一视图内按键冲突时用户自定义的动作优先
+    fn from_bindings(bindings: HashMap<Action, Vec<KeyBinding>>, overridden: &[Action]) -> Self {
+        let mut lookup = HashMap::new();
+        let defaults = Action::ALL.into_iter().filter(|a| !overridden.contains(a));
+        for action in defaults.chain(overridden.iter().copied()) {
+            for key in bindings.get(&action).into_iter().flatten() {
+                lookup.insert((action.scope(), *key), action);
+            }
+        }
+        Self { bindings, lookup }
+    }
+
+    /// 解析按键，当前视图专属的绑定优先于全局绑定
+    pub fn resolve(&self, key: &KeyEvent, view: ActiveView) -> Option<Action> {
+        self.resolve_in(key, KeyScope::View(view))
+    }
+
+    /// 按指定范围解析按键，范围专属的绑定优先于全局绑定
+    pub fn resolve_in(&self, key: &KeyEvent, scope: KeyScope) -> Option<Action> {
+        let binding = KeyBinding::new(key.code, key.modifiers);
+        self.lookup
+            .get(&(Some(scope), binding))
+            .or_else(|| self.lookup.get(&(None, binding)))
+            .copied()
+    }
+
+    /// 动作的首个绑定按键，用于状态栏提示
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .and_then(|keys| keys.first())
+            .map(KeyBinding::label)
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+fn keys_file_path() -> Option<PathBuf> {
+    super::config_dir().map(|dir| dir.join(KEYS_FILE))
+}
+
+/// 读取 `keys.toml` 中的自定义按键，用于随配置导出
+pub fn user_overrides() -> Result<IndexMap<String, Value>, String> {
+    let Some(path) = keys_file_path().filter(|p| p.exists()) else {
+        return Ok(IndexMap::new());
+    };
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| format!("{KEYS_FILE}: {}", e.message()))
+}
+
+/// 校验后写入导入的自定义按键，返回生效的新映射
+pub fn save_user_overrides(overrides: &IndexMap<String, Value>) -> Result<Keymap, String> {
+    let content = toml::to_string(overrides).map_err(|e| format!("{KEYS_FILE}: {e}"))?;
+    let keymap = Keymap::from_toml(&content)?;
+    let path = keys_file_path().ok_or("Cannot locate the home directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(keymap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(
+            KeyBinding::parse("ctrl+q").unwrap(),
+            KeyBinding::new(KeyCode::Char('q'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyBinding::parse("shift+tab").unwrap(),
+            KeyBinding::new(KeyCode::BackTab, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            KeyBinding::parse("shift+g").unwrap(),
+            KeyBinding::parse("G").unwrap()
+        );
+        assert_eq!(
+            KeyBinding::parse("F5").unwrap(),
+            KeyBinding::new(KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            KeyBinding::parse("ctrl++").unwrap(),
+            KeyBinding::new(KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("nope").is_err());
+    }
+
+    #[test]
+    fn default_keymap_resolves_shifted_chars() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('G'), KeyModifiers::SHIFT),
+                ActiveView::Providers
+            ),
+            Some(Action::Bottom)
+        );
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('q'), KeyModifiers::NONE),
+                ActiveView::Providers
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('q'), KeyModifiers::CONTROL),
+                ActiveView::Providers
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn user_bindings_replace_defaults() {
+        let keymap = Keymap::from_toml("quit = \"ctrl+q\"\nadd = [\"a\", \"n\"]\n").unwrap();
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('q'), KeyModifiers::CONTROL),
+                ActiveView::Providers
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('q'), KeyModifiers::NONE),
+                ActiveView::Providers
+            ),
+            None
+        );
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('n'), KeyModifiers::NONE),
+                ActiveView::Providers
+            ),
+            Some(Action::Add)
+        );
+        assert_eq!(keymap.label(Action::Quit), "Ctrl+q");
+    }
+
+    #[test]
+    fn user_bindings_win_conflicts() {
+        let keymap = Keymap::from_toml("delete = \"a\"\n").unwrap();
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('a'), KeyModifiers::NONE),
+                ActiveView::Providers
+            ),
+            Some(Action::Delete)
+        );
+        assert!(Keymap::from_toml("launch_rockets = \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn view_scoped_actions_share_keys() {
+        let keymap = Keymap::default();
+        let t = key(KeyCode::Char('t'), KeyModifiers::NONE);
+        assert_eq!(
+            keymap.resolve(&t, ActiveView::Providers),
+            Some(Action::TestConnection)
+        );
+        assert_eq!(
+            keymap.resolve(&t, ActiveView::Settings),
+            Some(Action::CycleTheme)
+        );
+        assert_eq!(
+            keymap.resolve(&t, ActiveView::Mcp),
+            Some(Action::TestMcpServer)
+        );
+        assert_eq!(keymap.resolve(&t, ActiveView::History), None);
+    }
+
+    #[test]
+    fn popup_scopes_fall_back_to_global_bindings() {
+        let keymap = Keymap::default();
+        let resolve = |c| {
+            keymap.resolve_in(
+                &key(KeyCode::Char(c), KeyModifiers::NONE),
+                KeyScope::Endpoints,
+            )
+        };
+        assert_eq!(resolve('r'), Some(Action::EditEndpointRegion));
+        assert_eq!(resolve('R'), Some(Action::EditRegionOrder));
+        assert_eq!(resolve('P'), Some(Action::PinEndpoint));
+        assert_eq!(resolve('d'), Some(Action::Delete));
+        assert_eq!(resolve('G'), Some(Action::Bottom));
+        assert_eq!(resolve('g'), None);
+        assert_eq!(
+            keymap.resolve(
+                &key(KeyCode::Char('r'), KeyModifiers::NONE),
+                ActiveView::Providers
+            ),
+            None
+        );
+    }
+}
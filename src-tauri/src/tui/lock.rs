@@ -0,0 +1,89 @@
+//! 空闲锁屏的主密码
+//!
+//! 主密码以 PBKDF2-HMAC-SHA256 加盐哈希保存在数据库 settings 表中，格式为
+//! `pbkdf2-sha256$<迭代次数>$<盐 base64>$<哈希 base64>`。
+
+use std::num::NonZeroU32;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+
+const SCHEME: &str = "pbkdf2-sha256";
+const ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// 生成随机盐并哈希主密码
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| "Failed to generate a random salt".to_string())?;
+    let iterations = NonZeroU32::new(ITERATIONS).expect("非零迭代次数");
+    let mut hash = [0u8; HASH_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        password.as_bytes(),
+        &mut hash,
+    );
+    Ok(format!(
+        "{SCHEME}${ITERATIONS}${}${}",
+        STANDARD.encode(salt),
+        STANDARD.encode(hash)
+    ))
+}
+
+/// 校验主密码，哈希格式无法识别时视为不匹配
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some(SCHEME), Some(iterations), Some(salt), Some(hash), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return false;
+    };
+    let Some(iterations) = iterations.parse().ok().and_then(NonZeroU32::new) else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (STANDARD.decode(salt), STANDARD.decode(hash)) else {
+        return false;
+    };
+    pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        password.as_bytes(),
+        &hash,
+    )
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_password_verifies_only_with_same_password() {
+        let stored = hash_password("correct horse").unwrap();
+        assert!(stored.starts_with("pbkdf2-sha256$100000$"));
+        assert!(verify_password("correct horse", &stored));
+        assert!(!verify_password("wrong", &stored));
+        // 每次哈希使用不同的盐
+        assert_ne!(stored, hash_password("correct horse").unwrap());
+    }
+
+    #[test]
+    fn malformed_hash_never_verifies() {
+        assert!(!verify_password("", ""));
+        assert!(!verify_password("x", "plain-text"));
+        assert!(!verify_password("x", "pbkdf2-sha256$0$AAAA$AAAA"));
+        assert!(!verify_password("x", "md5$1$AAAA$AAAA"));
+    }
+}
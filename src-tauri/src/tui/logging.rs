@@ -0,0 +1,381 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use chrono::{DateTime, Local};
+use env_filter::Filter;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+const LOG_FILE: &str = "tui.log";
+
+/// 内存中保留的最近日志条数，供日志视图显示
+pub const LOG_BUFFER_CAPACITY: usize = 2000;
+
+/// 输出量很大的依赖库，未在 `RUST_LOG` 中单独指定时最多输出 warn（按目标前缀匹配）
+const NOISY_TARGETS: [&str; 5] = ["hyper", "reqwest", "rustls", "h2", "hickory"];
+
+/// 可循环选择的日志级别
+const LEVEL_CHOICES: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+/// 日志输出位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogDestination {
+    /// 写入 `~/.config/cc-switch/tui.log`，不干扰终端界面
+    #[default]
+    File,
+    /// 写入 stderr，TUI 运行时会破坏界面显示
+    Stderr,
+    Off,
+}
+
+impl LogDestination {
+    const ALL: [LogDestination; 3] = [Self::File, Self::Stderr, Self::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Stderr => "stderr",
+            Self::Off => "off",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// 一条已捕获的日志
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// 递增序号，用于增量拉取
+    pub seq: u64,
+    pub timestamp: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// 最近日志的环形缓冲
+#[derive(Default)]
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    next_seq: u64,
+}
+
+impl LogBuffer {
+    fn push(&mut self, timestamp: DateTime<Local>, record: &Record, message: String) {
+        if self.entries.len() >= LOG_BUFFER_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.next_seq += 1;
+        self.entries.push_back(LogEntry {
+            seq: self.next_seq,
+            timestamp,
+            level: record.level(),
+            target: record.target().to_string(),
+            message,
+        });
+    }
+
+    fn since(&self, seq: u64) -> Vec<LogEntry> {
+        // 序号连续递增，可直接定位起点
+        let start = self
+            .entries
+            .front()
+            .map(|first| seq.saturating_sub(first.seq - 1) as usize)
+            .unwrap_or(0);
+        self.entries.iter().skip(start).cloned().collect()
+    }
+}
+
+/// 当前输出位置及已打开的日志文件
+struct Sink {
+    destination: LogDestination,
+    file: Option<File>,
+}
+
+/// `RUST_LOG` 指令与运行时选择的全局级别
+struct Filtering {
+    /// `RUST_LOG` 原文，切换级别时据此重建过滤器
+    spec: String,
+    level: LevelFilter,
+    filter: Filter,
+}
+
+impl Filtering {
+    fn new(spec: String, level: LevelFilter) -> Self {
+        let filter = build_filter(&spec, level);
+        Self {
+            spec,
+            level,
+            filter,
+        }
+    }
+}
+
+/// 可在运行时切换级别与输出位置的日志器，同时将日志保存在内存缓冲中
+struct TuiLogger {
+    filtering: RwLock<Filtering>,
+    sink: Mutex<Sink>,
+    buffer: Mutex<LogBuffer>,
+}
+
+impl Log for TuiLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filtering
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter
+            .enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let matched = self
+            .filtering
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter
+            .matches(record);
+        if !matched {
+            return;
+        }
+        let now = Local::now();
+        // 日志可能带出请求 URL 或上游错误体，写出前统一脱敏
+        let message = cc_switch_lib::redact(&record.args().to_string());
+        let line = format!(
+            "[{} {:<5} {}] {message}\n",
+            now.format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+        );
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(now, record, message);
+
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        match sink.destination {
+            LogDestination::File => {
+                if let Some(file) = sink.file.as_mut() {
+                    let _ = file.write_all(line.as_bytes());
+                }
+            }
+            LogDestination::Stderr => {
+                let _ = std::io::stderr().write_all(line.as_bytes());
+            }
+            LogDestination::Off => {}
+        }
+    }
+
+    fn flush(&self) {
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(file) = sink.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+static LOGGER: OnceLock<TuiLogger> = OnceLock::new();
+
+/// 安装全局日志器，启动阶段输出到 stderr
+///
+/// `RUST_LOG` 支持 `target=level` 指令（如 `cc_switch=debug,hyper=warn`），
+/// 其中不带目标的级别作为全局级别，默认 info。
+pub fn init() {
+    let spec = std::env::var("RUST_LOG").unwrap_or_default();
+    let level = global_level(&spec).unwrap_or(LevelFilter::Info);
+    let logger = LOGGER.get_or_init(|| TuiLogger {
+        filtering: RwLock::new(Filtering::new(spec, level)),
+        sink: Mutex::new(Sink {
+            destination: LogDestination::Stderr,
+            file: None,
+        }),
+        buffer: Mutex::new(LogBuffer::default()),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(
+            logger
+                .filtering
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .filter
+                .filter(),
+        );
+    }
+}
+
+/// 依次应用噪声库上限、`RUST_LOG` 指令与全局级别，后者覆盖 `RUST_LOG` 中的全局级别
+fn build_filter(spec: &str, level: LevelFilter) -> Filter {
+    let mut builder = env_filter::Builder::new();
+    for target in NOISY_TARGETS {
+        builder.filter_module(target, level.min(LevelFilter::Warn));
+    }
+    builder.parse(spec);
+    builder.filter_level(level);
+    builder.build()
+}
+
+/// `RUST_LOG` 中不带目标的级别
+fn global_level(spec: &str) -> Option<LevelFilter> {
+    let directives = spec.split('/').next().unwrap_or_default();
+    directives
+        .rsplit(',')
+        .find_map(|directive| directive.trim().parse().ok())
+}
+
+/// 日志文件路径（`~/.config/cc-switch/tui.log`）
+pub fn log_file_path() -> Option<PathBuf> {
+    super::config_dir().map(|dir| dir.join(LOG_FILE))
+}
+
+/// 当前的全局日志级别，`RUST_LOG` 中按目标指定的级别不受影响
+pub fn level() -> LevelFilter {
+    match LOGGER.get() {
+        Some(logger) => {
+            logger
+                .filtering
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .level
+        }
+        None => log::max_level(),
+    }
+}
+
+/// 修改全局日志级别，`RUST_LOG` 中按目标指定的级别保持不变
+pub fn set_level(level: LevelFilter) {
+    let Some(logger) = LOGGER.get() else {
+        log::set_max_level(level);
+        return;
+    };
+    let mut filtering = logger.filtering.write().unwrap_or_else(|e| e.into_inner());
+    *filtering = Filtering::new(std::mem::take(&mut filtering.spec), level);
+    log::set_max_level(filtering.filter.filter());
+}
+
+/// 切换日志输出位置，输出到文件时以追加方式打开日志文件
+pub fn set_destination(destination: LogDestination) -> Result<(), String> {
+    let Some(logger) = LOGGER.get() else {
+        return Ok(());
+    };
+
+    let file = match destination {
+        LogDestination::File => {
+            let path = log_file_path().ok_or("Cannot determine home directory")?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+            Some(file)
+        }
+        _ => None,
+    };
+
+    let mut sink = logger.sink.lock().unwrap_or_else(|e| e.into_inner());
+    sink.destination = destination;
+    sink.file = file;
+    Ok(())
+}
+
+/// 返回序号大于 `seq` 的已捕获日志
+pub fn entries_since(seq: u64) -> Vec<LogEntry> {
+    match LOGGER.get() {
+        Some(logger) => logger
+            .buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .since(seq),
+        None => Vec::new(),
+    }
+}
+
+/// 下一个日志级别
+pub fn next_level(level: LevelFilter) -> LevelFilter {
+    let index = LEVEL_CHOICES.iter().position(|l| *l == level).unwrap_or(0);
+    LEVEL_CHOICES[(index + 1) % LEVEL_CHOICES.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_and_destination_cycle_wrap_around() {
+        assert_eq!(next_level(LevelFilter::Info), LevelFilter::Debug);
+        assert_eq!(next_level(LevelFilter::Trace), LevelFilter::Off);
+        assert_eq!(LogDestination::Off.next(), LogDestination::File);
+        assert_eq!(
+            LogDestination::from_name("stderr"),
+            Some(LogDestination::Stderr)
+        );
+        assert_eq!(LogDestination::from_name("syslog"), None);
+    }
+
+    #[test]
+    fn rust_log_directives_apply_per_target() {
+        let enabled = |filter: &Filter, target: &str, level: Level| {
+            filter.enabled(&Metadata::builder().target(target).level(level).build())
+        };
+
+        let filter = build_filter("", LevelFilter::Debug);
+        assert!(enabled(&filter, "cc_switch_lib::proxy", Level::Debug));
+        assert!(!enabled(&filter, "hyper_util::client", Level::Debug));
+        assert!(enabled(&filter, "rustls::conn", Level::Warn));
+
+        let filter = build_filter("cc_switch_lib=trace,hyper=debug", LevelFilter::Info);
+        assert!(enabled(&filter, "cc_switch_lib::proxy", Level::Trace));
+        assert!(enabled(&filter, "hyper::proto", Level::Debug));
+        assert!(!enabled(&filter, "reqwest::connect", Level::Info));
+        assert!(!enabled(&filter, "cc_switch_tui", Level::Debug));
+
+        let filter = build_filter("", LevelFilter::Off);
+        assert!(!enabled(&filter, "hyper", Level::Error));
+
+        assert_eq!(
+            global_level("cc_switch_lib=debug,warn"),
+            Some(LevelFilter::Warn)
+        );
+        assert_eq!(global_level("hyper=warn/foo"), None);
+    }
+
+    #[test]
+    fn buffer_returns_entries_after_seq_and_drops_oldest() {
+        let mut buffer = LogBuffer::default();
+        for i in 0..LOG_BUFFER_CAPACITY + 2 {
+            buffer.push(
+                Local::now(),
+                &Record::builder()
+                    .args(format_args!("line {i}"))
+                    .level(Level::Info)
+                    .build(),
+                format!("line {i}"),
+            );
+        }
+
+        assert_eq!(buffer.entries.len(), LOG_BUFFER_CAPACITY);
+        assert_eq!(buffer.since(0).len(), LOG_BUFFER_CAPACITY);
+        let tail = buffer.since(buffer.next_seq - 1);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail[0].message, format!("line {}", LOG_BUFFER_CAPACITY + 1));
+        assert!(buffer.since(buffer.next_seq).is_empty());
+    }
+}
@@ -1,7 +1,20 @@
 mod app;
+mod command;
+mod keymap;
+mod lock;
+pub mod logging;
 mod terminal;
 mod theme;
 pub mod views;
 pub mod widgets;
 
+use std::path::PathBuf;
+
 pub use app::run;
+pub use terminal::read_passphrase;
+pub use theme::ColorSupport;
+
+/// TUI 用户配置目录（`~/.config/cc-switch`），存放主题等个性化文件
+fn config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("cc-switch"))
+}
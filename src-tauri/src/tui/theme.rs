@@ -1,5 +1,174 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
 use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+const THEME_FILE: &str = "theme.toml";
+
+/// 内置主题预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    Solarized,
+    Gruvbox,
+    HighContrast,
+    Monochrome,
+}
+
+impl ThemePreset {
+    pub const ALL: [ThemePreset; 5] = [
+        Self::Default,
+        Self::Solarized,
+        Self::Gruvbox,
+        Self::HighContrast,
+        Self::Monochrome,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Solarized => "solarized",
+            Self::Gruvbox => "gruvbox",
+            Self::HighContrast => "high-contrast",
+            Self::Monochrome => "monochrome",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn theme(&self) -> Theme {
+        match self {
+            Self::Default => Theme::default(),
+            Self::Solarized => Theme {
+                title: Style::default()
+                    .fg(Color::Rgb(0x26, 0x8b, 0xd2))
+                    .add_modifier(Modifier::BOLD),
+                selected: Style::default()
+                    .fg(Color::Rgb(0xb5, 0x89, 0x00))
+                    .add_modifier(Modifier::BOLD),
+                normal: Style::default().fg(Color::Rgb(0x83, 0x94, 0x96)),
+                highlight: Style::default().fg(Color::Rgb(0x2a, 0xa1, 0x98)),
+                inactive: Style::default().fg(Color::Rgb(0x58, 0x6e, 0x75)),
+                success: Style::default().fg(Color::Rgb(0x85, 0x99, 0x00)),
+                warning: Style::default().fg(Color::Rgb(0xb5, 0x89, 0x00)),
+                error: Style::default().fg(Color::Rgb(0xdc, 0x32, 0x2f)),
+                border: Style::default().fg(Color::Rgb(0x65, 0x7b, 0x83)),
+                ..Theme::default()
+            },
+            Self::Gruvbox => Theme {
+                title: Style::default()
+                    .fg(Color::Rgb(0xfe, 0x80, 0x19))
+                    .add_modifier(Modifier::BOLD),
+                selected: Style::default()
+                    .fg(Color::Rgb(0xfa, 0xbd, 0x2f))
+                    .add_modifier(Modifier::BOLD),
+                normal: Style::default().fg(Color::Rgb(0xeb, 0xdb, 0xb2)),
+                highlight: Style::default().fg(Color::Rgb(0x8e, 0xc0, 0x7c)),
+                inactive: Style::default().fg(Color::Rgb(0x92, 0x83, 0x74)),
+                success: Style::default().fg(Color::Rgb(0xb8, 0xbb, 0x26)),
+                warning: Style::default().fg(Color::Rgb(0xfa, 0xbd, 0x2f)),
+                error: Style::default().fg(Color::Rgb(0xfb, 0x49, 0x34)),
+                border: Style::default().fg(Color::Rgb(0xa8, 0x99, 0x84)),
+                ..Theme::default()
+            },
+            Self::HighContrast => Theme {
+                title: Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                selected: Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                normal: Style::default().fg(Color::White),
+                highlight: Style::default()
+                    .fg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD),
+                inactive: Style::default().fg(Color::Gray),
+                success: Style::default().fg(Color::LightGreen),
+                warning: Style::default().fg(Color::LightYellow),
+                error: Style::default()
+                    .fg(Color::LightRed)
+                    .add_modifier(Modifier::BOLD),
+                border: Style::default().fg(Color::White),
+                ..Theme::default()
+            },
+            Self::Monochrome => Theme {
+                title: Style::default().add_modifier(Modifier::BOLD),
+                selected: Style::default().add_modifier(Modifier::REVERSED),
+                normal: Style::default(),
+                highlight: Style::default().add_modifier(Modifier::BOLD),
+                inactive: Style::default().add_modifier(Modifier::DIM),
+                success: Style::default(),
+                warning: Style::default().add_modifier(Modifier::ITALIC),
+                error: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                border: Style::default(),
+                ..Theme::default()
+            },
+        }
+    }
+}
+
+/// 终端颜色能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    NoColor,
+}
+
+impl ColorSupport {
+    /// 根据 `NO_COLOR` / `COLORTERM` / `TERM` 环境变量推断终端颜色能力
+    pub fn detect() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env(no_color, colorterm.as_deref(), term.as_deref())
+    }
+
+    fn from_env(no_color: bool, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color {
+            return Self::NoColor;
+        }
+        let term = term.unwrap_or_default().to_lowercase();
+        if term == "dumb" {
+            return Self::NoColor;
+        }
+        let colorterm = colorterm.unwrap_or_default().to_lowercase();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.contains("truecolor")
+            || term.contains("direct")
+        {
+            return Self::TrueColor;
+        }
+        if term.contains("256color") {
+            return Self::Ansi256;
+        }
+        Self::Ansi16
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "truecolor" | "24bit" => Some(Self::TrueColor),
+            "256" => Some(Self::Ansi256),
+            "16" => Some(Self::Ansi16),
+            "none" | "never" => Some(Self::NoColor),
+            _ => None,
+        }
+    }
+}
 
+#[derive(Clone)]
 pub struct Theme {
     pub title: Style,
     pub selected: Style,
@@ -7,8 +176,11 @@ pub struct Theme {
     pub highlight: Style,
     pub inactive: Style,
     pub success: Style,
+    pub warning: Style,
     pub error: Style,
     pub border: Style,
+    /// 降级后的终端颜色能力，用于运行时颜色（如供应商图标色）
+    color_support: ColorSupport,
 }
 
 impl Default for Theme {
@@ -24,8 +196,356 @@ impl Default for Theme {
             highlight: Style::default().fg(Color::Green),
             inactive: Style::default().fg(Color::DarkGray),
             success: Style::default().fg(Color::Green),
+            warning: Style::default().fg(Color::Yellow),
             error: Style::default().fg(Color::Red),
             border: Style::default().fg(Color::Gray),
+            color_support: ColorSupport::TrueColor,
+        }
+    }
+}
+
+/// `theme.toml` 中单个样式的覆盖项，未填写的字段保持原样
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleOverride {
+    fg: Option<String>,
+    bg: Option<String>,
+    modifiers: Option<Vec<String>>,
+}
+
+impl StyleOverride {
+    fn apply(&self, name: &str, style: &mut Style) -> Result<(), String> {
+        if let Some(fg) = &self.fg {
+            style.fg = Some(parse_color(name, fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style.bg = Some(parse_color(name, bg)?);
+        }
+        if let Some(modifiers) = &self.modifiers {
+            let mut parsed = Modifier::empty();
+            for m in modifiers {
+                parsed |= parse_modifier(name, m)?;
+            }
+            style.add_modifier = parsed;
+            style.sub_modifier = Modifier::empty();
+        }
+        Ok(())
+    }
+}
+
+/// 用户主题文件结构
+///
+/// ```toml
+/// [selected]
+/// fg = "#fabd2f"
+/// bg = "black"
+/// modifiers = ["bold", "underlined"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    title: Option<StyleOverride>,
+    selected: Option<StyleOverride>,
+    normal: Option<StyleOverride>,
+    highlight: Option<StyleOverride>,
+    inactive: Option<StyleOverride>,
+    success: Option<StyleOverride>,
+    warning: Option<StyleOverride>,
+    error: Option<StyleOverride>,
+    border: Option<StyleOverride>,
+}
+
+impl Theme {
+    /// 在预设主题上叠加 `~/.config/cc-switch/theme.toml` 中的覆盖项
+    ///
+    /// 文件不存在时直接返回预设主题；读取或解析失败时返回错误信息，
+    /// 由调用方回退到预设主题并提示用户。
+    pub fn load_user(preset: ThemePreset) -> Result<Self, String> {
+        let mut theme = preset.theme();
+        let Some(path) = theme_file_path() else {
+            return Ok(theme);
+        };
+        if !path.exists() {
+            return Ok(theme);
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        theme.apply_toml(&content)?;
+        Ok(theme)
+    }
+
+    /// 解析 `#RRGGBB` 或颜色名并降级到终端支持的范围，无法解析或无颜色时为 None
+    pub fn custom_color(&self, value: &str) -> Option<Color> {
+        let color = Color::from_str(value.trim()).ok()?;
+        degrade_color(color, self.color_support)
+    }
+
+    /// 将主题降级到终端支持的颜色范围
+    pub fn degrade(mut self, support: ColorSupport) -> Self {
+        self.color_support = support;
+        if support == ColorSupport::TrueColor {
+            return self;
+        }
+        let styles = [
+            &mut self.title,
+            &mut self.selected,
+            &mut self.normal,
+            &mut self.highlight,
+            &mut self.inactive,
+            &mut self.success,
+            &mut self.warning,
+            &mut self.error,
+            &mut self.border,
+        ];
+        for style in styles {
+            style.fg = style.fg.and_then(|c| degrade_color(c, support));
+            style.bg = style.bg.and_then(|c| degrade_color(c, support));
+        }
+        // 无颜色时依靠反色区分选中项
+        if support == ColorSupport::NoColor {
+            self.selected = self.selected.add_modifier(Modifier::REVERSED);
+        }
+        self
+    }
+
+    fn apply_toml(&mut self, content: &str) -> Result<(), String> {
+        let file: ThemeFile =
+            toml::from_str(content).map_err(|e| format!("{THEME_FILE}: {}", e.message()))?;
+
+        let overrides = [
+            ("title", &file.title, &mut self.title),
+            ("selected", &file.selected, &mut self.selected),
+            ("normal", &file.normal, &mut self.normal),
+            ("highlight", &file.highlight, &mut self.highlight),
+            ("inactive", &file.inactive, &mut self.inactive),
+            ("success", &file.success, &mut self.success),
+            ("warning", &file.warning, &mut self.warning),
+            ("error", &file.error, &mut self.error),
+            ("border", &file.border, &mut self.border),
+        ];
+        for (name, spec, style) in overrides {
+            if let Some(spec) = spec {
+                spec.apply(name, style)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// xterm 默认 16 色调色板，顺序与 `ANSI_16` 一致
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const ANSI_16: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+fn degrade_color(color: Color, support: ColorSupport) -> Option<Color> {
+    match support {
+        ColorSupport::TrueColor => Some(color),
+        ColorSupport::NoColor => None,
+        ColorSupport::Ansi256 => Some(match color {
+            Color::Rgb(r, g, b) => Color::Indexed(rgb_to_256(r, g, b)),
+            other => other,
+        }),
+        ColorSupport::Ansi16 => Some(match color {
+            Color::Rgb(r, g, b) => nearest_ansi16(r, g, b),
+            Color::Indexed(i) if i < 16 => ANSI_16[i as usize],
+            Color::Indexed(i) => {
+                let (r, g, b) = indexed_to_rgb(i);
+                nearest_ansi16(r, g, b)
+            }
+            other => other,
+        }),
+    }
+}
+
+fn nearest_ansi16(r: u8, g: u8, b: u8) -> Color {
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let dr = i32::from(r) - i32::from(cr);
+        let dg = i32::from(g) - i32::from(cg);
+        let db = i32::from(b) - i32::from(cb);
+        dr * dr + dg * dg + db * db
+    };
+    let index = (0..ANSI_16_RGB.len())
+        .min_by_key(|&i| distance(ANSI_16_RGB[i]))
+        .unwrap_or(7);
+    ANSI_16[index]
+}
+
+/// 映射到 xterm 256 色中的 6x6x6 色立方
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| ((u16::from(v) * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_16_RGB[i as usize],
+        16..=231 => {
+            let i = i - 16;
+            let scale = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (scale(i / 36), scale((i / 6) % 6), scale(i % 6))
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn theme_file_path() -> Option<PathBuf> {
+    super::config_dir().map(|dir| dir.join(THEME_FILE))
+}
+
+fn parse_color(name: &str, value: &str) -> Result<Color, String> {
+    Color::from_str(value).map_err(|_| format!("{THEME_FILE}: invalid color '{value}' in [{name}]"))
+}
+
+fn parse_modifier(name: &str, value: &str) -> Result<Modifier, String> {
+    let modifier = match value.to_lowercase().as_str() {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" | "underline" => Modifier::UNDERLINED,
+        "slow_blink" | "blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reversed" | "reverse" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed_out" | "strikethrough" => Modifier::CROSSED_OUT,
+        _ => {
+            return Err(format!(
+                "{THEME_FILE}: invalid modifier '{value}' in [{name}]"
+            ))
+        }
+    };
+    Ok(modifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_only_specified_fields() {
+        let mut theme = Theme::default();
+        theme
+            .apply_toml(
+                r##"
+[selected]
+fg = "#fabd2f"
+modifiers = ["underlined", "italic"]
+
+[error]
+bg = "black"
+"##,
+            )
+            .unwrap();
+
+        assert_eq!(theme.selected.fg, Some(Color::Rgb(0xfa, 0xbd, 0x2f)));
+        assert_eq!(
+            theme.selected.add_modifier,
+            Modifier::UNDERLINED | Modifier::ITALIC
+        );
+        assert_eq!(theme.error.fg, Some(Color::Red));
+        assert_eq!(theme.error.bg, Some(Color::Black));
+        assert_eq!(theme.title, Theme::default().title);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let mut theme = Theme::default();
+        let err = theme
+            .apply_toml("[title]\nfg = \"not-a-color\"\n")
+            .unwrap_err();
+        assert!(err.contains("not-a-color"));
+
+        let err = theme
+            .apply_toml("[title]\nmodifiers = [\"sparkly\"]\n")
+            .unwrap_err();
+        assert!(err.contains("sparkly"));
+
+        assert!(theme.apply_toml("[unknown]\nfg = \"red\"\n").is_err());
+    }
+
+    #[test]
+    fn detects_color_support_from_env() {
+        assert_eq!(
+            ColorSupport::from_env(false, Some("truecolor"), Some("xterm")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(false, None, Some("screen-256color")),
+            ColorSupport::Ansi256
+        );
+        assert_eq!(
+            ColorSupport::from_env(false, None, Some("xterm")),
+            ColorSupport::Ansi16
+        );
+        assert_eq!(
+            ColorSupport::from_env(false, None, Some("dumb")),
+            ColorSupport::NoColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(true, Some("truecolor"), Some("xterm-256color")),
+            ColorSupport::NoColor
+        );
+    }
+
+    #[test]
+    fn degrades_rgb_colors() {
+        let theme = ThemePreset::Gruvbox.theme().degrade(ColorSupport::Ansi16);
+        assert_eq!(theme.error.fg, Some(Color::LightRed));
+        assert!(!matches!(theme.normal.fg, Some(Color::Rgb(..))));
+
+        let theme = ThemePreset::Gruvbox.theme().degrade(ColorSupport::Ansi256);
+        assert!(matches!(theme.normal.fg, Some(Color::Indexed(_))));
+
+        let theme = Theme::default().degrade(ColorSupport::NoColor);
+        assert_eq!(theme.normal.fg, None);
+        assert!(theme.selected.add_modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn presets_cycle_and_round_trip_names() {
+        let mut preset = ThemePreset::default();
+        for _ in 0..ThemePreset::ALL.len() {
+            assert_eq!(ThemePreset::from_name(preset.name()), Some(preset));
+            preset = preset.next();
         }
+        assert_eq!(preset, ThemePreset::Default);
+        assert_eq!(ThemePreset::from_name("nope"), None);
     }
 }
@@ -0,0 +1,109 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::AppType;
+
+/// 应用独立监听端口弹窗：留空表示使用共享端口
+pub struct AppPortForm {
+    pub visible: bool,
+    app_type: AppType,
+    port: TextInput,
+    message: Option<String>,
+}
+
+impl AppPortForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            port: TextInput::new("Dedicated port"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType, port: Option<u16>) {
+        let value = port.map(|p| p.to_string()).unwrap_or_default();
+        self.port = TextInput::with_value("Dedicated port", &value);
+        self.app_type = app_type;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    pub fn app_type(&self) -> &AppType {
+        &self.app_type
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的端口（None 表示使用共享端口）
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<Option<u16>> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let value = self.port.value.trim();
+                if value.is_empty() {
+                    return Some(None);
+                }
+                match value.parse::<u16>() {
+                    Ok(port) if port > 0 => return Some(Some(port)),
+                    _ => {
+                        self.message = Some("Port must be a number between 1 and 65535".to_string())
+                    }
+                }
+            }
+            KeyCode::Backspace => self.port.backspace(),
+            KeyCode::Delete => self.port.delete(),
+            KeyCode::Left => self.port.move_left(),
+            KeyCode::Right => self.port.move_right(),
+            KeyCode::Home => self.port.home(),
+            KeyCode::End => self.port.end(),
+            KeyCode::Char(c) if c.is_ascii_digit() => self.port.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(50, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Dedicated Port — {}", self.app_type.as_str()))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.port.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new("Serves only this app's endpoints (empty = shared port)")
+                .style(theme.inactive),
+            chunks[1],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
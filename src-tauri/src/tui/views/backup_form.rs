@@ -0,0 +1,97 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField};
+use cc_switch_lib::AutoBackupConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Interval,
+    Retain,
+}
+
+/// 定时备份弹窗：开关、备份间隔（小时）与保留份数
+pub struct BackupForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl BackupForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &AutoBackupConfig) {
+        self.form = Form::new(vec![
+            (Field::Enabled, FormField::toggle("Enabled", config.enabled)),
+            (
+                Field::Interval,
+                FormField::number("Every (hours)", config.interval_hours),
+            ),
+            (
+                Field::Retain,
+                FormField::number("Keep (backups)", config.retain),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    fn parse(&self) -> Result<AutoBackupConfig, String> {
+        let interval = self.form.input(Field::Interval);
+        let interval_hours = match interval.value.trim().parse::<u64>() {
+            Ok(hours) if (1..=24 * 30).contains(&hours) => hours,
+            _ => return Err(format!("{} must be 1-720", interval.label)),
+        };
+        let retain = self.form.input(Field::Retain);
+        let retain = match retain.value.trim().parse::<usize>() {
+            Ok(count) if (1..=100).contains(&count) => count,
+            _ => return Err(format!("{} must be 1-100", retain.label)),
+        };
+        Ok(AutoBackupConfig {
+            enabled: self.form.is_on(Field::Enabled),
+            interval_hours,
+            retain,
+        })
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的定时备份设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<AutoBackupConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => match self.parse() {
+                Ok(config) => return Some(config),
+                Err(e) => self.form.set_error(e),
+            },
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Automatic backups",
+            "Compressed copies of the database go to ~/.cc-switch/backups",
+        );
+    }
+}
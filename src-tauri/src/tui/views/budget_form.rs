@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    DailyUsd,
+    MonthlyUsd,
+    DailyTokens,
+    MonthlyTokens,
+    Pause,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [
+        Field::DailyUsd,
+        Field::MonthlyUsd,
+        Field::DailyTokens,
+        Field::MonthlyTokens,
+        Field::Pause,
+    ];
+
+    fn offset(self, delta: isize) -> Self {
+        let index = Self::ALL.iter().position(|f| *f == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+        Self::ALL[(index + delta).rem_euclid(len) as usize]
+    }
+}
+
+/// 供应商预算弹窗：按日/按月的花费与 token 限额，留空表示不限
+pub struct BudgetForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    daily_usd: TextInput,
+    monthly_usd: TextInput,
+    daily_tokens: TextInput,
+    monthly_tokens: TextInput,
+    pause_on_limit: bool,
+    field: Field,
+    message: Option<String>,
+}
+
+impl BudgetForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            daily_usd: TextInput::new("Daily cost (USD)"),
+            monthly_usd: TextInput::new("Monthly cost (USD)"),
+            daily_tokens: TextInput::new("Daily tokens"),
+            monthly_tokens: TextInput::new("Monthly tokens"),
+            pause_on_limit: false,
+            field: Field::DailyUsd,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let meta = provider.meta.clone().unwrap_or_default();
+        let tokens = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+        self.daily_usd = TextInput::with_value(
+            "Daily cost (USD)",
+            meta.limit_daily_usd.as_deref().unwrap_or(""),
+        );
+        self.monthly_usd = TextInput::with_value(
+            "Monthly cost (USD)",
+            meta.limit_monthly_usd.as_deref().unwrap_or(""),
+        );
+        self.daily_tokens = TextInput::with_value("Daily tokens", &tokens(meta.limit_daily_tokens));
+        self.monthly_tokens =
+            TextInput::with_value("Monthly tokens", &tokens(meta.limit_monthly_tokens));
+        self.pause_on_limit = meta.pause_on_limit.unwrap_or(false);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.field = Field::DailyUsd;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+        self.message = None;
+    }
+
+    /// 返回 true 表示预算已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = self.field.offset(1),
+            KeyCode::BackTab | KeyCode::Up => self.field = self.field.offset(-1),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Char(' ') if self.field == Field::Pause => {
+                self.pause_on_limit = !self.pause_on_limit;
+            }
+            _ => {
+                let allow_dot = matches!(self.field, Field::DailyUsd | Field::MonthlyUsd);
+                if let Some(input) = self.active_input() {
+                    match key {
+                        KeyCode::Backspace => input.backspace(),
+                        KeyCode::Delete => input.delete(),
+                        KeyCode::Left => input.move_left(),
+                        KeyCode::Right => input.move_right(),
+                        KeyCode::Home => input.home(),
+                        KeyCode::End => input.end(),
+                        KeyCode::Char(c) if c.is_ascii_digit() || (allow_dot && c == '.') => {
+                            input.insert(c)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn active_input(&mut self) -> Option<&mut TextInput> {
+        match self.field {
+            Field::DailyUsd => Some(&mut self.daily_usd),
+            Field::MonthlyUsd => Some(&mut self.monthly_usd),
+            Field::DailyTokens => Some(&mut self.daily_tokens),
+            Field::MonthlyTokens => Some(&mut self.monthly_tokens),
+            Field::Pause => None,
+        }
+    }
+
+    fn save(&mut self) -> bool {
+        let Some(mut provider) = self.provider.clone() else {
+            return false;
+        };
+        let result = self.apply_limits(&mut provider).and_then(|()| {
+            self.state
+                .db
+                .save_provider(self.app_type.as_str(), &provider)
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e);
+                false
+            }
+        }
+    }
+
+    fn apply_limits(&self, provider: &mut Provider) -> Result<(), String> {
+        let meta = provider.meta.get_or_insert_with(Default::default);
+        meta.limit_daily_usd = parse_usd(&self.daily_usd)?;
+        meta.limit_monthly_usd = parse_usd(&self.monthly_usd)?;
+        meta.limit_daily_tokens = parse_tokens(&self.daily_tokens)?;
+        meta.limit_monthly_tokens = parse_tokens(&self.monthly_tokens)?;
+        meta.pause_on_limit = self.pause_on_limit.then_some(true);
+        Ok(())
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self.provider.as_ref().map_or("", |p| p.name.as_str());
+
+        let area = centered_rect(50, 11, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Budget — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 7])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+
+        for (i, (field, input)) in [
+            (Field::DailyUsd, &self.daily_usd),
+            (Field::MonthlyUsd, &self.monthly_usd),
+            (Field::DailyTokens, &self.daily_tokens),
+            (Field::MonthlyTokens, &self.monthly_tokens),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let text = if self.field == field {
+                input.display(true)
+            } else if input.value.is_empty() {
+                format!("{}: unlimited", input.label)
+            } else {
+                format!("{}: {}", input.label, input.value)
+            };
+            frame.render_widget(Paragraph::new(text).style(style(field)), chunks[i]);
+        }
+
+        let checkbox = if self.pause_on_limit { "[x]" } else { "[ ]" };
+        frame.render_widget(
+            Paragraph::new(format!("{checkbox} Pause routing when exceeded"))
+                .style(style(Field::Pause)),
+            chunks[4],
+        );
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[5]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space:Toggle  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[6],
+        );
+    }
+}
+
+fn parse_usd(input: &TextInput) -> Result<Option<String>, String> {
+    let value = input.value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<f64>() {
+        Ok(v) if v > 0.0 => Ok(Some(value.to_string())),
+        _ => Err(format!("{} must be a positive amount", input.label)),
+    }
+}
+
+fn parse_tokens(input: &TextInput) -> Result<Option<u64>, String> {
+    let value = input.value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<u64>() {
+        Ok(v) if v > 0 => Ok(Some(v)),
+        _ => Err(format!("{} must be a positive number", input.label)),
+    }
+}
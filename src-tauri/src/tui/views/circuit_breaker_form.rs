@@ -0,0 +1,280 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppType, CircuitBreakerConfig, CircuitBreakerOverride, Provider};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    FailureThreshold,
+    Timeout,
+    ErrorRate,
+    MinRequests,
+}
+
+const FIELDS: [Field; 4] = [
+    Field::FailureThreshold,
+    Field::Timeout,
+    Field::ErrorRate,
+    Field::MinRequests,
+];
+
+/// 熔断参数弹窗：编辑应用默认值，或编辑单个供应商的覆盖项（留空沿用应用默认值）
+pub struct CircuitBreakerForm {
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    defaults: CircuitBreakerConfig,
+    failure_threshold: TextInput,
+    timeout: TextInput,
+    error_rate: TextInput,
+    min_requests: TextInput,
+    field: Field,
+    message: Option<String>,
+}
+
+impl CircuitBreakerForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            defaults: CircuitBreakerConfig::default(),
+            failure_threshold: TextInput::new("Failure threshold"),
+            timeout: TextInput::new("Open timeout (s)"),
+            error_rate: TextInput::new("Error rate (0-1)"),
+            min_requests: TextInput::new("Min requests"),
+            field: Field::FailureThreshold,
+            message: None,
+        }
+    }
+
+    /// 编辑应用默认值，输入框预填当前配置
+    pub fn open_app(&mut self, app_type: AppType, config: CircuitBreakerConfig) {
+        self.fill(&CircuitBreakerOverride {
+            failure_threshold: Some(config.failure_threshold),
+            timeout_seconds: Some(config.timeout_seconds),
+            error_rate_threshold: Some(config.error_rate_threshold),
+            min_requests: Some(config.min_requests),
+        });
+        self.app_type = app_type;
+        self.provider = None;
+        self.defaults = config;
+    }
+
+    /// 编辑供应商覆盖项，`defaults` 为该应用的默认配置
+    pub fn open_provider(
+        &mut self,
+        provider: &Provider,
+        app_type: AppType,
+        defaults: CircuitBreakerConfig,
+    ) {
+        let tuning = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.circuit_breaker.clone())
+            .unwrap_or_default();
+        self.fill(&tuning);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.defaults = defaults;
+    }
+
+    fn fill(&mut self, tuning: &CircuitBreakerOverride) {
+        let text = |value: Option<String>| value.unwrap_or_default();
+        self.failure_threshold = TextInput::with_value(
+            "Failure threshold",
+            &text(tuning.failure_threshold.map(|v| v.to_string())),
+        );
+        self.timeout = TextInput::with_value(
+            "Open timeout (s)",
+            &text(tuning.timeout_seconds.map(|v| v.to_string())),
+        );
+        self.error_rate = TextInput::with_value(
+            "Error rate (0-1)",
+            &text(tuning.error_rate_threshold.map(|v| v.to_string())),
+        );
+        self.min_requests = TextInput::with_value(
+            "Min requests",
+            &text(tuning.min_requests.map(|v| v.to_string())),
+        );
+        self.field = Field::FailureThreshold;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    pub fn app_type(&self) -> &AppType {
+        &self.app_type
+    }
+
+    /// 正在编辑的供应商，None 表示编辑应用默认值
+    pub fn provider(&self) -> Option<&Provider> {
+        self.provider.as_ref()
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 且输入合法时返回填写的参数（空白项为 None）
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<CircuitBreakerOverride> {
+        let index = FIELDS.iter().position(|f| *f == self.field).unwrap_or(0);
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = FIELDS[(index + 1) % FIELDS.len()],
+            KeyCode::BackTab | KeyCode::Up => {
+                self.field = FIELDS[(index + FIELDS.len() - 1) % FIELDS.len()]
+            }
+            KeyCode::Enter => match self.parse() {
+                Ok(tuning) => return Some(tuning),
+                Err(e) => self.message = Some(e),
+            },
+            code => {
+                let allow_dot = self.field == Field::ErrorRate;
+                let input = match self.field {
+                    Field::FailureThreshold => &mut self.failure_threshold,
+                    Field::Timeout => &mut self.timeout,
+                    Field::ErrorRate => &mut self.error_rate,
+                    Field::MinRequests => &mut self.min_requests,
+                };
+                match code {
+                    KeyCode::Backspace => input.backspace(),
+                    KeyCode::Delete => input.delete(),
+                    KeyCode::Left => input.move_left(),
+                    KeyCode::Right => input.move_right(),
+                    KeyCode::Home => input.home(),
+                    KeyCode::End => input.end(),
+                    KeyCode::Char(c) if c.is_ascii_digit() || (allow_dot && c == '.') => {
+                        input.insert(c)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<CircuitBreakerOverride, String> {
+        fn positive(input: &TextInput) -> Result<Option<u32>, String> {
+            let value = input.value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            match value.parse::<u32>() {
+                Ok(v) if v > 0 => Ok(Some(v)),
+                _ => Err(format!("{} must be a positive number", input.label)),
+            }
+        }
+
+        let timeout = self.timeout.value.trim();
+        let timeout_seconds = if timeout.is_empty() {
+            None
+        } else {
+            Some(
+                timeout
+                    .parse::<u32>()
+                    .map(u64::from)
+                    .map_err(|_| "Open timeout must be a number of seconds".to_string())?,
+            )
+        };
+        let error_rate = self.error_rate.value.trim();
+        let error_rate_threshold = if error_rate.is_empty() {
+            None
+        } else {
+            match error_rate.parse::<f64>() {
+                Ok(v) if v > 0.0 && v <= 1.0 => Some(v),
+                _ => return Err("Error rate must be between 0 and 1".to_string()),
+            }
+        };
+
+        let tuning = CircuitBreakerOverride {
+            failure_threshold: positive(&self.failure_threshold)?,
+            timeout_seconds,
+            error_rate_threshold,
+            min_requests: positive(&self.min_requests)?,
+        };
+        // 应用默认值没有“沿用”一说，每项都必须填写
+        if self.provider.is_none()
+            && (tuning.failure_threshold.is_none()
+                || tuning.timeout_seconds.is_none()
+                || tuning.error_rate_threshold.is_none()
+                || tuning.min_requests.is_none())
+        {
+            return Err("All fields are required for app defaults".to_string());
+        }
+        Ok(tuning)
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let title = match &self.provider {
+            Some(provider) => format!("Circuit breaker — {}", provider.name),
+            None => format!("Circuit breaker defaults — {}", self.app_type.as_str()),
+        };
+        let area = centered_rect(60, 11, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 7])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let defaults = [
+            self.defaults.failure_threshold.to_string(),
+            self.defaults.timeout_seconds.to_string(),
+            self.defaults.error_rate_threshold.to_string(),
+            self.defaults.min_requests.to_string(),
+        ];
+        for (i, ((field, input), default)) in [
+            (Field::FailureThreshold, &self.failure_threshold),
+            (Field::Timeout, &self.timeout),
+            (Field::ErrorRate, &self.error_rate),
+            (Field::MinRequests, &self.min_requests),
+        ]
+        .into_iter()
+        .zip(defaults)
+        .enumerate()
+        {
+            let mut text = input.display(self.field == field);
+            if self.provider.is_some() && input.value.trim().is_empty() {
+                text.push_str(&format!("  (app default: {default})"));
+            }
+            let style = if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            frame.render_widget(Paragraph::new(text).style(style), chunks[i]);
+        }
+
+        let hint = if self.provider.is_some() {
+            "Empty = use the app default"
+        } else {
+            "Providers without their own values use these"
+        };
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[4]);
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[5]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[6],
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider};
+
+/// 供应商并发上限弹窗：留空表示不限制
+pub struct ConcurrencyForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    max_concurrent: TextInput,
+    message: Option<String>,
+}
+
+impl ConcurrencyForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            max_concurrent: TextInput::new("Max in-flight"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let value = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.max_concurrent)
+            .map(|max| max.to_string())
+            .unwrap_or_default();
+        self.max_concurrent = TextInput::with_value("Max in-flight", &value);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    /// 返回 true 表示上限已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Backspace => self.max_concurrent.backspace(),
+            KeyCode::Delete => self.max_concurrent.delete(),
+            KeyCode::Left => self.max_concurrent.move_left(),
+            KeyCode::Right => self.max_concurrent.move_right(),
+            KeyCode::Home => self.max_concurrent.home(),
+            KeyCode::End => self.max_concurrent.end(),
+            KeyCode::Char(c) if c.is_ascii_digit() => self.max_concurrent.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let Some(mut provider) = self.provider.clone() else {
+            return false;
+        };
+        let value = self.max_concurrent.value.trim();
+        let max_concurrent = if value.is_empty() {
+            None
+        } else {
+            match value.parse::<u32>() {
+                Ok(max) if max > 0 => Some(max),
+                _ => {
+                    self.message = Some("Max in-flight must be a positive number".to_string());
+                    return false;
+                }
+            }
+        };
+        provider
+            .meta
+            .get_or_insert_with(Default::default)
+            .max_concurrent = max_concurrent;
+
+        match self
+            .state
+            .db
+            .save_provider(self.app_type.as_str(), &provider)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self.provider.as_ref().map_or("", |p| p.name.as_str());
+
+        let area = centered_rect(50, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Concurrency cap — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.max_concurrent.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new("When full, requests spill over to the next provider (empty = no cap)")
+                .style(theme.inactive),
+            chunks[1],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
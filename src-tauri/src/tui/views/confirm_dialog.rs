@@ -0,0 +1,80 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use super::{centered_rect, Theme};
+
+/// 二次确认弹窗：确认后返回打开时携带的待执行动作
+pub struct ConfirmDialog<T> {
+    pub visible: bool,
+    title: String,
+    message: String,
+    pending: Option<T>,
+}
+
+impl<T> ConfirmDialog<T> {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            title: String::new(),
+            message: String::new(),
+            pending: None,
+        }
+    }
+
+    pub fn open(&mut self, title: impl Into<String>, message: impl Into<String>, action: T) {
+        self.title = title.into();
+        self.message = message.into();
+        self.pending = Some(action);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.pending = None;
+    }
+
+    /// 按 y/Enter 确认时返回待执行动作，n/Esc/q 取消
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<T> {
+        match key {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.visible = false;
+                self.pending.take()
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') | KeyCode::Esc => {
+                self.close();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 7, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(self.title.as_str())
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area.inner(Margin::new(2, 1)));
+        frame.render_widget(
+            Paragraph::new(self.message.as_str())
+                .style(theme.warning)
+                .wrap(Wrap { trim: true }),
+            chunks[0],
+        );
+        frame.render_widget(
+            Paragraph::new("y/Enter:Confirm  n/Esc:Cancel").style(theme.inactive),
+            chunks[1],
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField, TextInput};
+use cc_switch_lib::CorsConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Origins,
+    Methods,
+    Headers,
+}
+
+/// 代理 CORS 设置弹窗：列表以逗号分隔，留空表示不限制
+pub struct CorsForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl CorsForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &CorsConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::Enabled,
+                FormField::toggle("Send CORS headers", config.enabled),
+            ),
+            (
+                Field::Origins,
+                FormField::text("Allowed origins", &config.allowed_origins.join(", ")),
+            ),
+            (
+                Field::Methods,
+                FormField::text("Allowed methods", &config.allowed_methods.join(", ")),
+            ),
+            (
+                Field::Headers,
+                FormField::text("Allowed headers", &config.allowed_headers.join(", ")),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 时返回待保存的 CORS 设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<CorsConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => return Some(self.config()),
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn config(&self) -> CorsConfig {
+        let list = |input: &TextInput| {
+            input
+                .value
+                .split([',', ' '])
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        CorsConfig {
+            enabled: self.form.is_on(Field::Enabled),
+            allowed_origins: list(self.form.input(Field::Origins)),
+            allowed_methods: list(self.form.input(Field::Methods)),
+            allowed_headers: list(self.form.input(Field::Headers)),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Proxy CORS",
+            "Comma-separated; empty = allow any",
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField};
+use cc_switch_lib::{format_dns_overrides, parse_dns_overrides, DnsConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    CacheEnabled,
+    Ttl,
+    Overrides,
+}
+
+/// DNS 弹窗：解析缓存开关、缓存 TTL 上限与静态解析（`host=ip`，逗号分隔）
+pub struct DnsForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl DnsForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &DnsConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::CacheEnabled,
+                FormField::toggle("Cache DNS lookups", config.cache_enabled),
+            ),
+            (
+                Field::Ttl,
+                FormField::number("Max TTL (s)", config.cache_ttl_seconds),
+            ),
+            (
+                Field::Overrides,
+                FormField::text("Static hosts", &format_dns_overrides(&config.overrides)),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的 DNS 设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<DnsConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => match self.parse() {
+                Ok(config) => return Some(config),
+                Err(e) => self.form.set_error(e),
+            },
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<DnsConfig, String> {
+        let ttl = self.form.input(Field::Ttl);
+        let cache_ttl_seconds = match ttl.value.trim().parse::<u64>() {
+            Ok(v) if v > 0 => v,
+            _ => return Err(format!("{} must be a positive number", ttl.label)),
+        };
+        Ok(DnsConfig {
+            cache_enabled: self.form.is_on(Field::CacheEnabled),
+            cache_ttl_seconds,
+            overrides: parse_dns_overrides(&self.form.input(Field::Overrides).value)?,
+        })
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(70, self.form.height(), frame.area()),
+            "DNS",
+            "Static hosts: host=ip, comma separated (always applied)",
+        );
+    }
+}
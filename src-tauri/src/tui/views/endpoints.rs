@@ -0,0 +1,762 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{Local, TimeZone};
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::symbols::Marker;
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table, TableState,
+};
+
+use super::providers::rate_limit_label;
+use super::{centered_rect, Theme};
+use crate::tui::app::NavAction;
+use crate::tui::keymap::{Action, Keymap};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{
+    parse_region_order, AppError, AppState, AppType, EndpointEjection, EndpointFallback,
+    EndpointLatencySample, EndpointOutage, Provider, ProviderEndpoint, ProviderMeta,
+    ProviderService, ProxyStatus, RateLimitStats, UrlStrategy,
+};
+
+/// 延迟历史图表覆盖的时间窗口（小时）
+const HISTORY_HOURS: i64 = 24;
+
+/// 弹窗底部行内输入正在编辑的内容
+enum Edit {
+    /// 端点权重
+    Weight(String),
+    /// 端点地区标签
+    Region(String),
+    /// 所有供应商共用的地区偏好顺序
+    RegionOrder,
+}
+
+/// 供应商端点管理弹窗：查看健康状态并增删端点、设置主端点
+pub struct EndpointsView {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider_id: String,
+    provider_name: String,
+    endpoints: Vec<ProviderEndpoint>,
+    /// 按端点 URL 记录的限流统计，来自代理状态
+    rate_limits: HashMap<String, RateLimitStats>,
+    /// 按端点 URL 记录的最近一次离群剔除，来自代理状态
+    ejections: HashMap<String, EndpointEjection>,
+    /// 最近 24 小时的延迟采样
+    history: Vec<EndpointLatencySample>,
+    /// 端点选择策略
+    strategy: UrlStrategy,
+    /// 所有端点都不可用时的处理方式
+    fallback: EndpointFallback,
+    /// 代理当前记录的所有端点不可用状态
+    outage: Option<EndpointOutage>,
+    table_state: TableState,
+    /// 正在输入的新端点 URL，处于添加模式时为 Some
+    adding: Option<TextInput>,
+    /// 地区偏好顺序（所有供应商共用）
+    region_order: Vec<String>,
+    /// 正在编辑的内容与输入框
+    editing: Option<(Edit, TextInput)>,
+    message: Option<String>,
+}
+
+impl EndpointsView {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider_id: String::new(),
+            provider_name: String::new(),
+            endpoints: Vec::new(),
+            rate_limits: HashMap::new(),
+            ejections: HashMap::new(),
+            history: Vec::new(),
+            strategy: UrlStrategy::default(),
+            fallback: EndpointFallback::default(),
+            outage: None,
+            table_state: TableState::default(),
+            adding: None,
+            region_order: Vec::new(),
+            editing: None,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        self.app_type = app_type;
+        self.provider_id = provider.id.clone();
+        self.provider_name = provider.name.clone();
+        let meta = provider.meta.as_ref();
+        self.strategy = meta.and_then(|m| m.url_strategy).unwrap_or_default();
+        self.fallback = meta.and_then(|m| m.endpoint_fallback).unwrap_or_default();
+        self.outage = None;
+        self.adding = None;
+        self.editing = None;
+        self.message = None;
+        self.table_state = TableState::default();
+        self.reload();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.adding = None;
+        self.editing = None;
+    }
+
+    fn reload(&mut self) {
+        match self
+            .state
+            .db
+            .get_provider_endpoints_with_health(self.app_type.as_str(), &self.provider_id)
+        {
+            Ok(endpoints) => self.endpoints = endpoints,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        match self.state.db.get_endpoint_region_order() {
+            Ok(regions) => self.region_order = regions,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+
+        let since = chrono::Utc::now().timestamp() - HISTORY_HOURS * 3600;
+        match self.state.db.get_endpoint_latency_history(
+            self.app_type.as_str(),
+            &self.provider_id,
+            since,
+        ) {
+            Ok(history) => self.history = history,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+
+        let selected = match self.table_state.selected() {
+            _ if self.endpoints.is_empty() => None,
+            Some(i) => Some(i.min(self.endpoints.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    /// 从代理状态中取出当前供应商各端点的限流统计与离群剔除记录
+    pub fn set_proxy_stats(&mut self, status: &ProxyStatus) {
+        let stats = status
+            .provider_stats
+            .iter()
+            .find(|s| s.app_type == self.app_type.as_str() && s.provider_id == self.provider_id);
+        self.rate_limits = stats
+            .map(|s| s.endpoint_rate_limits.clone())
+            .unwrap_or_default();
+        self.ejections = stats
+            .map(|s| s.endpoint_ejections.clone())
+            .unwrap_or_default();
+        self.outage = status
+            .endpoint_outages
+            .iter()
+            .find(|o| o.app_type == self.app_type.as_str() && o.provider_id == self.provider_id)
+            .cloned();
+    }
+
+    /// 切换到下一个端点选择策略并保存到供应商
+    fn cycle_strategy(&mut self) -> bool {
+        let strategy = self.strategy.next();
+        let saved = self.update_meta(|meta| {
+            meta.url_strategy = (strategy != UrlStrategy::LowestLatency).then_some(strategy);
+        });
+        if saved {
+            self.strategy = strategy;
+            self.message = Some(format!("URL strategy: {}", strategy.label()));
+        }
+        saved
+    }
+
+    /// 切换到下一个处理方式预设并保存到供应商
+    fn cycle_fallback(&mut self) -> bool {
+        let fallback = self.fallback.next();
+        let saved = self.update_meta(|meta| {
+            meta.endpoint_fallback = (fallback != EndpointFallback::Degrade).then_some(fallback);
+        });
+        if saved {
+            self.fallback = fallback;
+            self.message = Some(format!("All endpoints down: {}", fallback.label()));
+        }
+        saved
+    }
+
+    /// 重新读取供应商、修改元数据后保存，失败时在弹窗内显示错误
+    fn update_meta(&mut self, apply: impl FnOnce(&mut ProviderMeta)) -> bool {
+        let mut provider = match self
+            .state
+            .db
+            .get_provider_by_id(&self.provider_id, self.app_type.as_str())
+        {
+            Ok(Some(provider)) => provider,
+            Ok(None) => {
+                self.message = Some("Provider not found".to_string());
+                return false;
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                return false;
+            }
+        };
+        apply(provider.meta.get_or_insert_with(Default::default));
+        match self
+            .state
+            .db
+            .save_provider(self.app_type.as_str(), &provider)
+        {
+            Ok(()) => true,
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    /// 选中端点的离群剔除说明
+    fn ejection_label(&self) -> Option<String> {
+        let url = self.selected_url()?;
+        let ejection = self.ejections.get(url.trim_end_matches('/'))?;
+        let now = chrono::Utc::now().timestamp_millis();
+        Some(if ejection.is_active(now) {
+            format!(
+                "Ejected as outlier: {} (back in {}s, ejection #{})",
+                ejection.reason,
+                (ejection.until - now).max(0) / 1000 + 1,
+                ejection.count
+            )
+        } else {
+            let at = Local
+                .timestamp_millis_opt(ejection.ejected_at)
+                .single()
+                .map(|t| t.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            format!("Last ejected at {at}: {}", ejection.reason)
+        })
+    }
+
+    fn selected_endpoint(&self) -> Option<&ProviderEndpoint> {
+        self.endpoints.get(self.table_state.selected()?)
+    }
+
+    fn selected_url(&self) -> Option<String> {
+        self.selected_endpoint().map(|e| e.url.clone())
+    }
+
+    /// 添加或编辑输入框打开时，按键由弹窗直接处理
+    pub fn captures_input(&self) -> bool {
+        self.adding.is_some() || self.editing.is_some()
+    }
+
+    /// 处理输入框中的按键及 Esc；返回 true 表示端点已变更，需要刷新供应商列表
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.adding.is_some() {
+            return self.handle_add_key(key);
+        }
+        if self.editing.is_some() {
+            return self.handle_edit_key(key);
+        }
+        if key == KeyCode::Esc {
+            self.close();
+        }
+        false
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.endpoints.len()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    /// 执行弹窗范围内解析出的动作；返回 true 表示端点已变更，需要刷新供应商列表
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => self.close(),
+            Action::Add => {
+                self.message = None;
+                self.adding = Some(TextInput::new("URL"));
+            }
+            Action::Delete => {
+                if let Some(url) = self.selected_url() {
+                    let result = ProviderService::remove_custom_endpoint(
+                        &self.state,
+                        self.app_type.clone(),
+                        &self.provider_id,
+                        url,
+                    );
+                    return self.finish(result, "Endpoint removed");
+                }
+            }
+            Action::SetPrimaryEndpoint => {
+                if let Some(url) = self.selected_url() {
+                    let result = ProviderService::set_primary_endpoint(
+                        &self.state,
+                        self.app_type.clone(),
+                        &self.provider_id,
+                        url,
+                    );
+                    return self.finish(result, "Primary endpoint updated");
+                }
+            }
+            Action::PinEndpoint => {
+                if let Some(endpoint) = self.selected_endpoint() {
+                    let pinned = !endpoint.is_pinned;
+                    let result = ProviderService::set_endpoint_pinned(
+                        &self.state,
+                        self.app_type.clone(),
+                        &self.provider_id,
+                        endpoint.url.clone(),
+                        pinned,
+                    );
+                    let success = if pinned {
+                        "Pinned as primary endpoint"
+                    } else {
+                        "Endpoint unpinned"
+                    };
+                    return self.finish(result, success);
+                }
+            }
+            Action::EditEndpointWeight => {
+                if let Some(endpoint) = self.selected_endpoint() {
+                    let value = endpoint.weight.map(|w| w.to_string()).unwrap_or_default();
+                    let input = TextInput::with_value("Weight", &value);
+                    self.editing = Some((Edit::Weight(endpoint.url.clone()), input));
+                    self.message = None;
+                }
+            }
+            Action::EditEndpointRegion => {
+                if let Some(endpoint) = self.selected_endpoint() {
+                    let value = endpoint.region.clone().unwrap_or_default();
+                    let input = TextInput::with_value("Region", &value);
+                    self.editing = Some((Edit::Region(endpoint.url.clone()), input));
+                    self.message = None;
+                }
+            }
+            Action::EditRegionOrder => {
+                let input = TextInput::with_value(
+                    "Region order (all providers)",
+                    &self.region_order.join(","),
+                );
+                self.editing = Some((Edit::RegionOrder, input));
+                self.message = None;
+            }
+            Action::CycleUrlStrategy => return self.cycle_strategy(),
+            Action::CycleEndpointFallback => return self.cycle_fallback(),
+            _ => {}
+        }
+        false
+    }
+
+    fn handle_add_key(&mut self, key: KeyCode) -> bool {
+        let Some(input) = self.adding.as_mut() else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Esc => self.adding = None,
+            KeyCode::Enter => {
+                let url = input.value.clone();
+                self.adding = None;
+                let result = ProviderService::add_custom_endpoint(
+                    &self.state,
+                    self.app_type.clone(),
+                    &self.provider_id,
+                    url,
+                );
+                return self.finish(result, "Endpoint added");
+            }
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn handle_edit_key(&mut self, key: KeyCode) -> bool {
+        let Some((edit, input)) = self.editing.as_mut() else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Esc => self.editing = None,
+            KeyCode::Enter => {
+                let value = input.value.trim().to_string();
+                let weight = match edit {
+                    Edit::Weight(_) if !value.is_empty() => match value.parse::<u32>() {
+                        Ok(w) => Some(w),
+                        Err(_) => {
+                            self.message = Some("Weight must be a whole number".to_string());
+                            return false;
+                        }
+                    },
+                    _ => None,
+                };
+                let Some((edit, _)) = self.editing.take() else {
+                    return false;
+                };
+                return match edit {
+                    Edit::Weight(url) => {
+                        let result = ProviderService::set_endpoint_weight(
+                            &self.state,
+                            self.app_type.clone(),
+                            &self.provider_id,
+                            url,
+                            weight,
+                        );
+                        let success = if self.strategy == UrlStrategy::Weighted {
+                            "Weight saved"
+                        } else {
+                            "Weight saved (used by the weighted URL strategy)"
+                        };
+                        self.finish(result, success)
+                    }
+                    Edit::Region(url) => {
+                        let result = ProviderService::set_endpoint_region(
+                            &self.state,
+                            self.app_type.clone(),
+                            &self.provider_id,
+                            url,
+                            Some(value),
+                        );
+                        self.finish(result, "Region saved")
+                    }
+                    Edit::RegionOrder => {
+                        let result = self
+                            .state
+                            .db
+                            .set_endpoint_region_order(&parse_region_order(&value));
+                        self.finish(result, "Region order saved")
+                    }
+                };
+            }
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) if !matches!(edit, Edit::Weight(_)) || c.is_ascii_digit() => {
+                input.insert(c)
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn finish(&mut self, result: Result<(), AppError>, success: &str) -> bool {
+        match result {
+            Ok(()) => {
+                self.message = Some(success.to_string());
+                self.reload();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme, keymap: &Keymap) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(80, 30.min(frame.area().height), frame.area());
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!("Endpoints: {}", self.provider_name))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let inner = area.inner(Margin::new(1, 1));
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(4),
+                Constraint::Length(14),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        self.render_table(frame, chunks[0], theme);
+        self.render_history(frame, chunks[1], theme);
+
+        let mut settings = vec![
+            Span::styled("URL strategy: ", theme.inactive),
+            Span::raw(self.strategy.label()),
+            Span::raw("   "),
+        ];
+        settings.push(match &self.outage {
+            Some(outage) => Span::styled(
+                format!(
+                    "⚠ All endpoints unhealthy since {} — {}",
+                    Local
+                        .timestamp_millis_opt(outage.since)
+                        .single()
+                        .map(|t| t.format("%H:%M:%S").to_string())
+                        .unwrap_or_default(),
+                    outage.fallback.label()
+                ),
+                theme.error.add_modifier(Modifier::BOLD),
+            ),
+            None => Span::styled(
+                format!("When all endpoints are down: {}", self.fallback.label()),
+                theme.normal,
+            ),
+        });
+        if !self.region_order.is_empty() {
+            settings.push(Span::raw("   "));
+            settings.push(Span::styled("Regions: ", theme.inactive));
+            settings.push(Span::raw(self.region_order.join(" > ")));
+        }
+        frame.render_widget(Paragraph::new(Line::from(settings)), chunks[2]);
+
+        let input = self
+            .adding
+            .as_ref()
+            .or(self.editing.as_ref().map(|(_, input)| input));
+        if let Some(input) = input {
+            let display = input.display(true);
+            frame.render_widget(Paragraph::new(display).style(theme.selected), chunks[3]);
+        } else if let Some(msg) = &self.message {
+            frame.render_widget(
+                Paragraph::new(msg.as_str()).style(theme.inactive),
+                chunks[3],
+            );
+        } else if let Some(label) = self.ejection_label() {
+            frame.render_widget(Paragraph::new(label).style(theme.warning), chunks[3]);
+        }
+
+        let hints = match &self.editing {
+            _ if self.adding.is_some() => "Enter:Add  Esc:Cancel".to_string(),
+            Some((Edit::Weight(_), _)) => "Enter:Save (empty = 1)  Esc:Cancel".to_string(),
+            Some((Edit::Region(_), _)) => {
+                "Enter:Save (e.g. us, asia; empty = none)  Esc:Cancel".to_string()
+            }
+            Some((Edit::RegionOrder, _)) => {
+                "Enter:Save (comma-separated, preferred first; empty = off)  Esc:Cancel".to_string()
+            }
+            None => {
+                let key = |action| keymap.label(action);
+                format!(
+                    "{}{}:Navigate  {}:Add  {}:Remove  {}:Set primary  {}:Pin  {}:Weight  {}/{}:Region/Order  {}:Strategy  {}:All-down fallback  {}/Esc:Close",
+                    key(Action::Up),
+                    key(Action::Down),
+                    key(Action::Add),
+                    key(Action::Delete),
+                    key(Action::SetPrimaryEndpoint),
+                    key(Action::PinEndpoint),
+                    key(Action::EditEndpointWeight),
+                    key(Action::EditEndpointRegion),
+                    key(Action::EditRegionOrder),
+                    key(Action::CycleUrlStrategy),
+                    key(Action::CycleEndpointFallback),
+                    key(Action::Quit),
+                )
+            }
+        };
+        frame.render_widget(Paragraph::new(hints).style(theme.inactive), chunks[4]);
+    }
+
+    /// 按 URL 绘制最近 24 小时的延迟折线，x 轴为距今小时数，失败采样不画点
+    fn render_history(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::default()
+            .title("Latency — last 24h")
+            .borders(Borders::TOP)
+            .style(theme.border);
+
+        let now = chrono::Utc::now().timestamp();
+        let series: Vec<(&str, Vec<(f64, f64)>)> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let points = self
+                    .history
+                    .iter()
+                    .filter(|s| s.url == endpoint.url)
+                    .filter_map(|s| {
+                        let hours_ago = (s.tested_at - now) as f64 / 3600.0;
+                        s.latency_ms.map(|ms| (hours_ago, ms as f64))
+                    })
+                    .collect();
+                (endpoint.url.as_str(), points)
+            })
+            .filter(|(_, points): &(&str, Vec<(f64, f64)>)| !points.is_empty())
+            .collect();
+
+        if series.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No latency samples yet. Enable hybrid mode to test endpoints.")
+                    .style(theme.inactive)
+                    .block(block),
+                area,
+            );
+            return;
+        }
+
+        let max_ms = series
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|(_, ms)| *ms))
+            .fold(100.0, f64::max)
+            * 1.1;
+        let palette = [
+            theme.highlight,
+            theme.selected,
+            theme.title,
+            theme.warning,
+            theme.success,
+            theme.normal,
+        ];
+        let datasets: Vec<Dataset> = series
+            .iter()
+            .enumerate()
+            .map(|(i, (url, points))| {
+                Dataset::default()
+                    .name(url.to_string())
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(palette[i % palette.len()])
+                    .data(points)
+            })
+            .collect();
+
+        let chart = Chart::new(datasets)
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .style(theme.inactive)
+                    .bounds([-(HISTORY_HOURS as f64), 0.0])
+                    .labels(vec![Span::raw("-24h"), Span::raw("-12h"), Span::raw("now")]),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(theme.inactive)
+                    .bounds([0.0, max_ms])
+                    .labels(vec![
+                        Span::raw("0"),
+                        Span::raw(format!("{:.0}ms", max_ms / 2.0)),
+                        Span::raw(format!("{max_ms:.0}ms")),
+                    ]),
+            );
+        frame.render_widget(chart, area);
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if self.endpoints.is_empty() {
+            let empty =
+                Paragraph::new("No custom endpoints. Press a to add one.").style(theme.inactive);
+            frame.render_widget(empty, area);
+            return;
+        }
+
+        let header = Row::new(vec![
+            "",
+            "URL",
+            "Health",
+            "p50/p95",
+            "Stability",
+            "Failures",
+            "Weight",
+            "Region",
+            "Rate limits",
+        ])
+        .style(theme.title);
+        let now = chrono::Utc::now().timestamp_millis();
+        let rows: Vec<Row> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let ejected = self
+                    .ejections
+                    .get(endpoint.url.trim_end_matches('/'))
+                    .is_some_and(|e| e.is_active(now));
+                let (health, health_style) = match endpoint.last_tested_at {
+                    _ if ejected => ("ejected", theme.warning),
+                    None => ("untested", theme.inactive),
+                    Some(_) if endpoint.is_healthy => ("healthy", theme.success),
+                    Some(_) => ("down", theme.error),
+                };
+                let latency = match (endpoint.latency_p50_ms, endpoint.latency_p95_ms) {
+                    (Some(p50), Some(p95)) => format!("{p50}/{p95}ms"),
+                    _ => endpoint
+                        .latency_ms
+                        .map(|ms| format!("{ms}ms"))
+                        .unwrap_or_else(|| "-".to_string()),
+                };
+                let stability = match endpoint.stability_score {
+                    Some(score) => {
+                        let style = match score {
+                            80.. => theme.success,
+                            50..80 => theme.warning,
+                            _ => theme.error,
+                        };
+                        let text = match endpoint.jitter_ms {
+                            Some(jitter) => format!("{score} ±{jitter}ms"),
+                            None => score.to_string(),
+                        };
+                        Line::styled(text, style)
+                    }
+                    None => Line::styled("-", theme.inactive),
+                };
+                let rate_limits = match self.rate_limits.get(endpoint.url.trim_end_matches('/')) {
+                    Some(stats) => Line::styled(rate_limit_label(stats), theme.warning),
+                    None => Line::styled("-", theme.inactive),
+                };
+                Row::new(vec![
+                    Line::from(match (endpoint.is_pinned, endpoint.is_primary) {
+                        (true, _) => "⚑",
+                        (false, true) => "★",
+                        (false, false) => "",
+                    }),
+                    Line::from(endpoint.url.clone()),
+                    Line::styled(health, health_style),
+                    Line::from(latency),
+                    stability,
+                    Line::from(endpoint.consecutive_failures.to_string()),
+                    match endpoint.weight {
+                        Some(weight) => Line::from(weight.to_string()),
+                        None => Line::styled("-", theme.inactive),
+                    },
+                    match &endpoint.region {
+                        Some(region) => Line::from(region.clone()),
+                        None => Line::styled("-", theme.inactive),
+                    },
+                    rate_limits,
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(2),
+                Constraint::Min(20),
+                Constraint::Length(9),
+                Constraint::Length(13),
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(7),
+                Constraint::Length(28),
+            ],
+        )
+        .header(header)
+        .highlight_style(theme.selected);
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{mask_secret, AppState, EnvImportService, EnvProviderCandidate};
+
+/// 环境变量导入弹窗：列出当前环境及可选 `.env` 文件中检测到的凭据，勾选后创建供应商
+pub struct EnvImportDialog {
+    state: Arc<AppState>,
+    pub visible: bool,
+    dotenv: TextInput,
+    /// 焦点是否在 `.env` 路径输入框
+    path_active: bool,
+    candidates: Vec<EnvProviderCandidate>,
+    /// 与 candidates 一一对应的勾选状态
+    checked: Vec<bool>,
+    table_state: TableState,
+    message: Option<String>,
+}
+
+impl EnvImportDialog {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            dotenv: TextInput::new(".env file"),
+            path_active: false,
+            candidates: Vec::new(),
+            checked: Vec::new(),
+            table_state: TableState::default(),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.path_active = false;
+        self.detect();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 重新检测；已存在相同 Key 与地址的供应商默认不勾选
+    fn detect(&mut self) {
+        self.message = None;
+        let path = self.dotenv.value.trim();
+        let dotenv = (!path.is_empty()).then(|| expand_home(path));
+        match EnvImportService::detect(&self.state, dotenv.as_deref()) {
+            Ok(candidates) => self.candidates = candidates,
+            Err(e) => {
+                self.candidates = Vec::new();
+                self.message = Some(e.to_string());
+            }
+        }
+        self.checked = self
+            .candidates
+            .iter()
+            .map(|c| c.existing.is_none())
+            .collect();
+        self.table_state
+            .select((!self.candidates.is_empty()).then_some(0));
+    }
+
+    /// 创建完成时返回新建的供应商数量
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<usize> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab => self.path_active = !self.path_active,
+            KeyCode::Enter if self.path_active => {
+                self.detect();
+                self.path_active = false;
+            }
+            KeyCode::Enter => return self.create_checked(),
+            _ if self.path_active => match key {
+                KeyCode::Backspace => self.dotenv.backspace(),
+                KeyCode::Delete => self.dotenv.delete(),
+                KeyCode::Left => self.dotenv.move_left(),
+                KeyCode::Right => self.dotenv.move_right(),
+                KeyCode::Home => self.dotenv.home(),
+                KeyCode::End => self.dotenv.end(),
+                KeyCode::Char(c) => self.dotenv.insert(c),
+                _ => {}
+            },
+            KeyCode::Down | KeyCode::Char('j') => self.select_offset(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select_offset(-1),
+            KeyCode::Char(' ') => {
+                if let Some(checked) = self
+                    .table_state
+                    .selected()
+                    .and_then(|i| self.checked.get_mut(i))
+                {
+                    *checked = !*checked;
+                }
+            }
+            KeyCode::Char('q') => self.close(),
+            _ => {}
+        }
+        None
+    }
+
+    fn create_checked(&mut self) -> Option<usize> {
+        let selected: Vec<EnvProviderCandidate> = self
+            .candidates
+            .iter()
+            .zip(&self.checked)
+            .filter(|(_, checked)| **checked)
+            .map(|(c, _)| c.clone())
+            .collect();
+        if selected.is_empty() {
+            self.message = Some("Nothing selected".to_string());
+            return None;
+        }
+        for (created, candidate) in selected.iter().enumerate() {
+            if let Err(e) = EnvImportService::create(&self.state, candidate) {
+                self.message = Some(format!(
+                    "Failed to create {}: {e} ({created} created)",
+                    candidate.provider_name()
+                ));
+                self.detect();
+                return (created > 0).then_some(created);
+            }
+        }
+        self.close();
+        Some(selected.len())
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        if self.candidates.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, self.candidates.len() as isize - 1);
+        self.table_state.select(Some(next as usize));
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(90, 16.min(frame.area().height), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Import providers from environment")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Min(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(area.inner(Margin::new(1, 1)));
+
+        let value = if self.path_active {
+            self.dotenv.display_value(true)
+        } else if self.dotenv.value.is_empty() {
+            "(none)".to_string()
+        } else {
+            self.dotenv.value.clone()
+        };
+        frame.render_widget(
+            Paragraph::new(format!("{}: {value}", self.dotenv.label)).style(if self.path_active {
+                theme.selected
+            } else {
+                theme.normal
+            }),
+            chunks[0],
+        );
+
+        self.render_table(frame, chunks[1], theme);
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new(
+                "j/k:Navigate  Space:Toggle  Tab:Edit .env path  Enter:Create providers  Esc:Cancel",
+            )
+            .style(theme.inactive),
+            chunks[3],
+        );
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if self.candidates.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No ANTHROPIC_AUTH_TOKEN, OPENAI_API_KEY or GEMINI_API_KEY found.")
+                    .style(theme.inactive),
+                area,
+            );
+            return;
+        }
+
+        let header =
+            Row::new(vec!["", "App", "Key", "Base URL", "Source", "Status"]).style(theme.title);
+        let rows: Vec<Row> = self
+            .candidates
+            .iter()
+            .zip(&self.checked)
+            .map(|(candidate, checked)| {
+                let url = if candidate.default_url {
+                    format!("{} (default)", candidate.base_url)
+                } else {
+                    candidate.base_url.clone()
+                };
+                let status = match &candidate.existing {
+                    Some(name) => Line::styled(format!("exists: {name}"), theme.warning),
+                    None => Line::styled("new", theme.success),
+                };
+                Row::new(vec![
+                    Line::from(if *checked { "[x]" } else { "[ ]" }),
+                    Line::from(candidate.app.as_str()),
+                    Line::from(format!(
+                        "{}={}",
+                        candidate.key_var,
+                        mask_secret(&candidate.api_key)
+                    )),
+                    Line::from(url),
+                    Line::styled(candidate.source.clone(), theme.inactive),
+                    status,
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(7),
+                Constraint::Length(34),
+                Constraint::Min(20),
+                Constraint::Length(14),
+                Constraint::Length(18),
+            ],
+        )
+        .header(header)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
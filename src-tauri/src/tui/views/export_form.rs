@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::widgets::TextInput;
+
+/// 配置导出弹窗：输入目标路径并选择是否隐去 API Key
+pub struct ExportForm {
+    pub visible: bool,
+    path: TextInput,
+    redact_secrets: bool,
+    /// 当前聚焦的是否为“隐去密钥”选项
+    redact_active: bool,
+    message: Option<String>,
+}
+
+impl ExportForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            path: TextInput::new("Path"),
+            redact_secrets: true,
+            redact_active: false,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        let default_path = format!(
+            "~/cc-switch-export-{}.json",
+            Local::now().format("%Y%m%d-%H%M%S")
+        );
+        self.path = TextInput::with_value("Path", &default_path);
+        self.redact_active = false;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 导出失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 时返回导出路径及是否隐去密钥
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<(PathBuf, bool)> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.redact_active = !self.redact_active;
+            }
+            KeyCode::Enter => {
+                let path = self.path.value.trim();
+                if path.is_empty() {
+                    self.message = Some("Path cannot be empty".to_string());
+                } else {
+                    return Some((expand_home(path), self.redact_secrets));
+                }
+            }
+            KeyCode::Char(' ') if self.redact_active => {
+                self.redact_secrets = !self.redact_secrets;
+            }
+            _ if self.redact_active => {}
+            KeyCode::Backspace => self.path.backspace(),
+            KeyCode::Delete => self.path.delete(),
+            KeyCode::Left => self.path.move_left(),
+            KeyCode::Right => self.path.move_right(),
+            KeyCode::Home => self.path.home(),
+            KeyCode::End => self.path.end(),
+            KeyCode::Char(c) => self.path.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Export Configuration")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let path_style = if self.redact_active {
+            theme.normal
+        } else {
+            theme.selected
+        };
+        frame.render_widget(
+            Paragraph::new(self.path.display(!self.redact_active)).style(path_style),
+            chunks[0],
+        );
+
+        let checkbox = if self.redact_secrets { "[x]" } else { "[ ]" };
+        let redact_style = if self.redact_active {
+            theme.selected
+        } else {
+            theme.normal
+        };
+        frame.render_widget(
+            Paragraph::new(format!("{checkbox} Redact API keys")).style(redact_style),
+            chunks[1],
+        );
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Switch field  Space:Toggle  Enter:Export  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use chrono::{Local, TimeZone};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState, Wrap};
+
+use super::{centered_rect, Theme};
+use crate::tui::app::NavAction;
+use crate::tui::keymap::{Action, Keymap};
+use cc_switch_lib::{AppState, FailoverEvent};
+
+/// 弹窗中展示的最近切换记录条数
+const EVENTS_SHOWN: usize = 100;
+
+/// 故障转移历史弹窗：最近的自动切换记录及切换原因
+pub struct FailoverEventsView {
+    state: Arc<AppState>,
+    pub visible: bool,
+    events: Vec<FailoverEvent>,
+    table_state: TableState,
+    message: Option<String>,
+}
+
+impl FailoverEventsView {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            events: Vec::new(),
+            table_state: TableState::default(),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.table_state = TableState::default();
+        self.reload();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    fn reload(&mut self) {
+        self.message = None;
+        match self.state.db.get_failover_events(EVENTS_SHOWN) {
+            Ok(events) => self.events = events,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+        let selected = match self.table_state.selected() {
+            _ if self.events.is_empty() => None,
+            Some(i) => Some(i.min(self.events.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    /// 执行弹窗范围内解析出的动作
+    pub fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.close(),
+            Action::RefreshFailoverEvents => self.reload(),
+            _ => {}
+        }
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.events.len()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme, keymap: &Keymap) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(90, 30.min(frame.area().height), frame.area());
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!("Failover history (last {EVENTS_SHOWN})"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let inner = area.inner(Margin::new(1, 1));
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(4),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        self.render_table(frame, chunks[0], theme);
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[1]);
+        } else if let Some(event) = self.table_state.selected().and_then(|i| self.events.get(i)) {
+            let reason = event.error.as_deref().unwrap_or(
+                "No upstream error (previous providers were skipped by the circuit breaker)",
+            );
+            frame.render_widget(
+                Paragraph::new(format!("Reason: {reason}"))
+                    .style(theme.warning)
+                    .wrap(Wrap { trim: true }),
+                chunks[1],
+            );
+        }
+
+        let hints = format!(
+            "{}{}:Navigate  {}:Reload  {}/Esc:Close",
+            keymap.label(Action::Up),
+            keymap.label(Action::Down),
+            keymap.label(Action::RefreshFailoverEvents),
+            keymap.label(Action::Quit),
+        );
+        frame.render_widget(Paragraph::new(hints).style(theme.inactive), chunks[2]);
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if self.events.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No automatic failover has happened yet.").style(theme.inactive),
+                area,
+            );
+            return;
+        }
+
+        let header = Row::new(vec!["Time", "App", "From", "To", "Reason"]).style(theme.title);
+        let rows: Vec<Row> = self
+            .events
+            .iter()
+            .map(|event| {
+                let time = Local
+                    .timestamp_opt(event.created_at, 0)
+                    .single()
+                    .map(|t| t.format("%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                let from = event
+                    .from_provider_name
+                    .clone()
+                    .or_else(|| event.from_provider_id.clone())
+                    .unwrap_or_else(|| "-".to_string());
+                Row::new(vec![
+                    Line::from(time),
+                    Line::from(event.app_type.clone()),
+                    Line::from(from),
+                    Line::from(event.to_provider_name.clone()),
+                    Line::styled(
+                        event.error.clone().unwrap_or_else(|| "-".to_string()),
+                        theme.inactive,
+                    ),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(14),
+                Constraint::Length(8),
+                Constraint::Length(18),
+                Constraint::Length(18),
+                Constraint::Min(10),
+            ],
+        )
+        .header(header)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{format_header_rules, parse_header_rules, AppState, AppType, Provider};
+
+/// 请求头改写规则弹窗：`;` 分隔，留空表示清除
+pub struct HeaderRulesForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    rules: TextInput,
+    message: Option<String>,
+}
+
+impl HeaderRulesForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            rules: TextInput::new("Rules"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let value = provider
+            .meta
+            .as_ref()
+            .map(|meta| format_header_rules(&meta.header_rules))
+            .unwrap_or_default();
+        self.rules = TextInput::with_value("Rules", &value);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    /// 返回 true 表示规则已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Backspace => self.rules.backspace(),
+            KeyCode::Delete => self.rules.delete(),
+            KeyCode::Left => self.rules.move_left(),
+            KeyCode::Right => self.rules.move_right(),
+            KeyCode::Home => self.rules.home(),
+            KeyCode::End => self.rules.end(),
+            KeyCode::Char(c) => self.rules.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let Some(mut provider) = self.provider.clone() else {
+            return false;
+        };
+        let rules = match parse_header_rules(&self.rules.value) {
+            Ok(rules) => rules,
+            Err(e) => {
+                self.message = Some(e);
+                return false;
+            }
+        };
+        provider
+            .meta
+            .get_or_insert_with(Default::default)
+            .header_rules = rules;
+
+        match self
+            .state
+            .db
+            .save_provider(self.app_type.as_str(), &provider)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self.provider.as_ref().map_or("", |p| p.name.as_str());
+
+        let area = centered_rect(70, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Header rules — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.rules.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(
+                "Name: value overrides, +Name: value appends, -Name removes; separate with ;",
+            )
+            .style(theme.inactive),
+            chunks[1],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use chrono::{Local, TimeZone};
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppType, HealthCheckConfig, HealthProbeResult, Provider};
+
+/// 探测间隔下限（秒），与代理端一致
+const MIN_INTERVAL_SECONDS: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Method,
+    Path,
+    ExpectedStatus,
+    Interval,
+}
+
+const FIELDS: [Field; 5] = [
+    Field::Enabled,
+    Field::Method,
+    Field::Path,
+    Field::ExpectedStatus,
+    Field::Interval,
+];
+
+/// 供应商主动健康检查弹窗：代理运行期间按间隔发送探测请求，结果计入熔断器
+pub struct HealthCheckForm {
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    enabled: bool,
+    method: TextInput,
+    path: TextInput,
+    expected_status: TextInput,
+    interval: TextInput,
+    last_result: Option<HealthProbeResult>,
+    field: Field,
+    message: Option<String>,
+}
+
+impl HealthCheckForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            enabled: false,
+            method: TextInput::new("Method"),
+            path: TextInput::new("Path"),
+            expected_status: TextInput::new("Expected status"),
+            interval: TextInput::new("Interval (s)"),
+            last_result: None,
+            field: Field::Enabled,
+            message: None,
+        }
+    }
+
+    /// 打开弹窗；未配置过检查的供应商预填默认值（默认关闭）
+    pub fn open(
+        &mut self,
+        provider: &Provider,
+        app_type: AppType,
+        last_result: Option<HealthProbeResult>,
+    ) {
+        let existing = provider.meta.as_ref().and_then(|m| m.health_check.clone());
+        let config = existing.clone().unwrap_or_default();
+        self.enabled = existing.is_some() && config.enabled;
+        self.method = TextInput::with_value("Method", &config.method);
+        self.path = TextInput::with_value("Path", &config.path);
+        self.expected_status =
+            TextInput::with_value("Expected status", &config.expected_status.to_string());
+        self.interval = TextInput::with_value("Interval (s)", &config.interval_seconds.to_string());
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.last_result = last_result;
+        self.field = Field::Enabled;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    pub fn app_type(&self) -> &AppType {
+        &self.app_type
+    }
+
+    pub fn provider(&self) -> Option<&Provider> {
+        self.provider.as_ref()
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的检查设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<HealthCheckConfig> {
+        let index = FIELDS.iter().position(|f| *f == self.field).unwrap_or(0);
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = FIELDS[(index + 1) % FIELDS.len()],
+            KeyCode::BackTab | KeyCode::Up => {
+                self.field = FIELDS[(index + FIELDS.len() - 1) % FIELDS.len()]
+            }
+            KeyCode::Enter => match self.parse() {
+                Ok(config) => return Some(config),
+                Err(e) => self.message = Some(e),
+            },
+            KeyCode::Char(' ') if self.field == Field::Enabled => self.enabled = !self.enabled,
+            code => {
+                let numeric = matches!(self.field, Field::ExpectedStatus | Field::Interval);
+                let input = match self.field {
+                    Field::Enabled => return None,
+                    Field::Method => &mut self.method,
+                    Field::Path => &mut self.path,
+                    Field::ExpectedStatus => &mut self.expected_status,
+                    Field::Interval => &mut self.interval,
+                };
+                match code {
+                    KeyCode::Backspace => input.backspace(),
+                    KeyCode::Delete => input.delete(),
+                    KeyCode::Left => input.move_left(),
+                    KeyCode::Right => input.move_right(),
+                    KeyCode::Home => input.home(),
+                    KeyCode::End => input.end(),
+                    KeyCode::Char(c) if !numeric || c.is_ascii_digit() => input.insert(c),
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<HealthCheckConfig, String> {
+        let method = self.method.value.trim().to_ascii_uppercase();
+        if method.is_empty() || !method.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err("Method must be an HTTP method such as GET".to_string());
+        }
+        let path = self.path.value.trim();
+        if !path.starts_with('/') {
+            return Err("Path must start with /".to_string());
+        }
+        let expected_status = match self.expected_status.value.trim().parse::<u16>() {
+            Ok(v) if (100..=599).contains(&v) => v,
+            _ => return Err("Expected status must be an HTTP status code".to_string()),
+        };
+        let interval_seconds = match self.interval.value.trim().parse::<u64>() {
+            Ok(v) if v >= MIN_INTERVAL_SECONDS => v,
+            _ => {
+                return Err(format!(
+                    "Interval must be at least {MIN_INTERVAL_SECONDS} seconds"
+                ))
+            }
+        };
+        Ok(HealthCheckConfig {
+            enabled: self.enabled,
+            method,
+            path: path.to_string(),
+            expected_status,
+            interval_seconds,
+        })
+    }
+
+    fn last_result_label(&self) -> String {
+        let Some(result) = &self.last_result else {
+            return "Last check: none yet (runs while the proxy is on)".to_string();
+        };
+        let at = Local
+            .timestamp_millis_opt(result.checked_at)
+            .single()
+            .map(|t| t.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        match (&result.status, &result.error) {
+            (Some(status), _) if result.healthy => {
+                format!("Last check {at}: {status} in {}ms", result.latency_ms)
+            }
+            (_, Some(error)) => format!("Last check {at}: {error}"),
+            _ => format!("Last check {at}: failed"),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let title = match &self.provider {
+            Some(provider) => format!("Health check — {}", provider.name),
+            None => "Health check".to_string(),
+        };
+        let area = centered_rect(60, 12, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 8])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "[{}] Probe this provider in the background",
+                if self.enabled { "x" } else { " " }
+            ))
+            .style(style(Field::Enabled)),
+            chunks[0],
+        );
+        for (i, (field, input)) in [
+            (Field::Method, &self.method),
+            (Field::Path, &self.path),
+            (Field::ExpectedStatus, &self.expected_status),
+            (Field::Interval, &self.interval),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            frame.render_widget(
+                Paragraph::new(input.display(self.field == field)).style(style(field)),
+                chunks[i + 1],
+            );
+        }
+
+        let (status, status_style) = match &self.message {
+            Some(msg) => (msg.clone(), theme.error),
+            None => (
+                self.last_result_label(),
+                match &self.last_result {
+                    Some(result) if !result.healthy => theme.error,
+                    _ => theme.inactive,
+                },
+            ),
+        };
+        frame.render_widget(Paragraph::new(status).style(status_style), chunks[5]);
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space:Toggle  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[7],
+        );
+    }
+}
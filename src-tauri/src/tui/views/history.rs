@@ -0,0 +1,396 @@
+use chrono::{Local, TimeZone};
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState, Wrap};
+
+use super::{centered_rect, Theme, View};
+use crate::tui::app::NavAction;
+use crate::tui::widgets::{loading_title, TextInput};
+use cc_switch_lib::{AppState, RequestLogEntry, RequestLogFilter};
+
+/// 每页显示的请求条数
+const PAGE_SIZE: usize = 50;
+
+/// 后台任务加载的一页请求历史
+pub struct HistoryPage {
+    page: usize,
+    entries: Vec<RequestLogEntry>,
+    total: usize,
+}
+
+/// 持久化请求历史浏览：分页、过滤及详情弹窗
+pub struct HistoryView {
+    pub loading: bool,
+    page: usize,
+    entries: Vec<RequestLogEntry>,
+    total: usize,
+    filter: RequestLogFilter,
+    /// 正在编辑的过滤条件，处于输入模式时为 Some
+    filter_input: Option<TextInput>,
+    /// 过滤条件解析错误
+    filter_error: Option<String>,
+    /// 详情弹窗是否可见及其滚动偏移
+    detail: Option<u16>,
+    table_state: TableState,
+}
+
+impl HistoryView {
+    pub fn new() -> Self {
+        Self {
+            loading: false,
+            page: 0,
+            entries: Vec::new(),
+            total: 0,
+            filter: RequestLogFilter::default(),
+            filter_input: None,
+            filter_error: None,
+            detail: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// 当前的过滤条件与页码，供后台加载使用
+    pub fn query(&self) -> (RequestLogFilter, usize) {
+        (self.filter.clone(), self.page)
+    }
+
+    pub fn load(state: &AppState, filter: &RequestLogFilter, page: usize) -> HistoryPage {
+        let (entries, total) = state
+            .db
+            .query_request_logs(filter, page * PAGE_SIZE, PAGE_SIZE)
+            .unwrap_or_default();
+        HistoryPage {
+            page,
+            entries,
+            total,
+        }
+    }
+
+    /// 返回 true 表示当前页已超出范围（旧记录被清理），需要按新页码重新加载
+    pub fn apply(&mut self, data: HistoryPage) -> bool {
+        self.loading = false;
+        let last_page = self.page_count(data.total) - 1;
+        if data.page > last_page {
+            self.page = last_page;
+            return true;
+        }
+        self.entries = data.entries;
+        self.total = data.total;
+
+        let selected = match self.table_state.selected() {
+            _ if self.entries.is_empty() => None,
+            Some(i) => Some(i.min(self.entries.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+        false
+    }
+
+    fn page_count(&self, total: usize) -> usize {
+        total.div_ceil(PAGE_SIZE).max(1)
+    }
+
+    /// 返回 true 表示页码已变化，需要重新加载
+    pub fn next_page(&mut self) -> bool {
+        if self.page + 1 >= self.page_count(self.total) {
+            return false;
+        }
+        self.page += 1;
+        self.table_state.select(Some(0));
+        true
+    }
+
+    /// 返回 true 表示页码已变化，需要重新加载
+    pub fn prev_page(&mut self) -> bool {
+        if self.page == 0 {
+            return false;
+        }
+        self.page -= 1;
+        self.table_state.select(Some(0));
+        true
+    }
+
+    pub fn open_filter(&mut self) {
+        self.filter_error = None;
+        self.filter_input = Some(TextInput::with_value("Filter", &self.filter.to_string()));
+    }
+
+    pub fn selected(&self) -> Option<&RequestLogEntry> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.entries.get(i))
+    }
+
+    pub fn open_detail(&mut self) {
+        if self.table_state.selected().is_some() {
+            self.detail = Some(0);
+        }
+    }
+
+    /// 过滤输入或详情弹窗打开时，按键由本视图直接处理
+    pub fn captures_input(&self) -> bool {
+        self.filter_input.is_some() || self.detail.is_some()
+    }
+
+    pub fn detail_open(&self) -> bool {
+        self.detail.is_some()
+    }
+
+    pub fn close_detail(&mut self) {
+        self.detail = None;
+    }
+
+    /// 滚动详情弹窗，滚到底部时由绘制按内容高度截断
+    pub fn scroll_detail(&mut self, action: NavAction) {
+        if let Some(scroll) = self.detail.as_mut() {
+            *scroll = match action {
+                NavAction::Up(n) => scroll.saturating_sub(n.min(u16::MAX as usize) as u16),
+                NavAction::Down(n) => scroll.saturating_add(n.min(u16::MAX as usize) as u16),
+                NavAction::Top => 0,
+                NavAction::Bottom => u16::MAX,
+            };
+        }
+    }
+
+    /// 返回 true 表示过滤条件已变化，需要重新加载
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.detail.is_some() {
+            if key == KeyCode::Esc {
+                self.detail = None;
+            }
+            return false;
+        }
+
+        let Some(input) = self.filter_input.as_mut() else {
+            return false;
+        };
+        match key {
+            KeyCode::Esc => self.filter_input = None,
+            KeyCode::Enter => match RequestLogFilter::parse(&input.value) {
+                Ok(filter) => {
+                    self.filter_input = None;
+                    self.filter_error = None;
+                    if filter != self.filter {
+                        self.filter = filter;
+                        self.page = 0;
+                        self.table_state.select(Some(0));
+                        return true;
+                    }
+                }
+                Err(e) => self.filter_error = Some(e),
+            },
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.entries.len()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if self.entries.is_empty() {
+            let text = if self.filter.is_empty() {
+                "No request history yet"
+            } else {
+                "No requests match the filter"
+            };
+            frame.render_widget(Paragraph::new(text).style(theme.inactive), area);
+            return;
+        }
+
+        let header = Row::new(vec!["Time", "App", "Provider", "Status", "Latency", "URL"])
+            .style(theme.title);
+        let rows: Vec<Row> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let (status, status_style) = status_cell(entry, theme);
+                Row::new(vec![
+                    Line::from(format_time(entry.timestamp, "%m-%d %H:%M:%S")),
+                    Line::from(entry.app_type.clone()),
+                    Line::from(entry.provider_name.clone()),
+                    Line::styled(status, status_style),
+                    Line::from(format!("{}ms", entry.latency_ms)),
+                    Line::from(entry.url.clone()),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(14),
+                Constraint::Length(7),
+                Constraint::Length(16),
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Min(20),
+            ],
+        )
+        .header(header)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    /// 绘制详情弹窗，返回可滚动到的最大偏移
+    fn render_detail(&self, frame: &mut Frame, theme: &Theme, scroll: u16) -> u16 {
+        let Some(entry) = self.selected() else {
+            return 0;
+        };
+
+        let label = |text: &'static str| Span::styled(text, theme.inactive);
+        let (status, status_style) = status_cell(entry, theme);
+        let mut lines = vec![
+            Line::from(vec![
+                label("Time:     "),
+                Span::raw(format_time(entry.timestamp, "%Y-%m-%d %H:%M:%S%.3f")),
+            ]),
+            Line::from(vec![label("App:      "), Span::raw(entry.app_type.clone())]),
+            Line::from(vec![
+                label("Provider: "),
+                Span::raw(format!("{} ({})", entry.provider_name, entry.provider_id)),
+            ]),
+            Line::from(vec![label("URL:      "), Span::raw(entry.url.clone())]),
+            Line::from(vec![
+                label("Status:   "),
+                Span::styled(status, status_style),
+            ]),
+        ];
+        if let Some(error) = &entry.error {
+            lines.push(Line::from(vec![
+                label("Error:    "),
+                Span::styled(error.clone(), theme.error),
+            ]));
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("Timing", theme.title));
+        lines.push(Line::raw(format!("  Before send:   {}ms", entry.queued_ms)));
+        lines.push(Line::raw(format!(
+            "  Upstream:      {}ms",
+            entry.latency_ms
+        )));
+        lines.push(Line::raw(format!(
+            "  Total:         {}ms",
+            entry.queued_ms + entry.latency_ms
+        )));
+
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("Captured request", theme.title));
+        match &entry.capture {
+            Some(capture) => lines.push(Line::raw(format!(
+                "  {} ({} bytes, replayable)",
+                capture.endpoint,
+                capture.body.len()
+            ))),
+            None => lines.push(Line::styled("  (not captured)", theme.inactive)),
+        }
+
+        for (title, headers) in [
+            ("Request headers", &entry.request_headers),
+            ("Response headers", &entry.response_headers),
+        ] {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(title, theme.title));
+            if headers.is_empty() {
+                lines.push(Line::styled("  (none)", theme.inactive));
+            }
+            for (name, value) in headers {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {name}: "), theme.inactive),
+                    Span::raw(value.clone()),
+                ]));
+            }
+        }
+
+        let area = centered_rect(80, frame.area().height.saturating_sub(4), frame.area());
+        frame.render_widget(Clear, area);
+        let inner = area.inner(Margin::new(1, 1));
+        let rows: usize = lines
+            .iter()
+            .map(|line| line.width().max(1).div_ceil(inner.width.max(1) as usize))
+            .sum();
+        let max_scroll = rows
+            .saturating_sub(inner.height as usize)
+            .min(u16::MAX as usize) as u16;
+        let detail = Paragraph::new(lines)
+            .style(theme.normal)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll.min(max_scroll), 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Request detail (j/k:Scroll  Esc:Close)")
+                    .style(theme.border),
+            );
+        frame.render_widget(detail, area);
+        max_scroll
+    }
+}
+
+impl View for HistoryView {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let mut title = format!(
+            "History — page {}/{} ({} requests)",
+            self.page + 1,
+            self.page_count(self.total),
+            self.total
+        );
+        if !self.filter.is_empty() {
+            title.push_str(&format!(" [{}]", self.filter));
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title(&title, self.loading));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        self.render_table(frame, chunks[0], theme);
+
+        if let Some(input) = &self.filter_input {
+            let display = input.display(true);
+            frame.render_widget(Paragraph::new(display).style(theme.selected), chunks[1]);
+        } else if let Some(error) = &self.filter_error {
+            frame.render_widget(Paragraph::new(error.as_str()).style(theme.error), chunks[1]);
+        } else {
+            let hint = "Filter syntax: app:claude provider:name status:429|5xx|err";
+            frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[1]);
+        }
+
+        if let Some(scroll) = self.detail {
+            let max_scroll = self.render_detail(frame, theme, scroll);
+            self.detail = Some(scroll.min(max_scroll));
+        }
+    }
+}
+
+fn status_cell(entry: &RequestLogEntry, theme: &Theme) -> (String, Style) {
+    match entry.status {
+        Some(code) if entry.is_success() => (code.to_string(), theme.success),
+        Some(code) => (code.to_string(), theme.error),
+        None => ("ERR".to_string(), theme.error),
+    }
+}
+
+fn format_time(timestamp_ms: i64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_default()
+}
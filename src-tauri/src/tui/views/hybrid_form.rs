@@ -0,0 +1,297 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, HybridModeConfig};
+
+/// 延迟测试间隔允许的范围（秒）
+const INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 10..=86400;
+/// 延迟测试并发数允许的范围
+const CONCURRENCY_RANGE: std::ops::RangeInclusive<u32> = 1..=32;
+/// 延迟测试每个 URL 超时允许的范围（秒）
+const TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 2..=30;
+/// 延迟测试采样次数允许的范围
+const SAMPLES_RANGE: std::ops::RangeInclusive<u32> = 1..=10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Interval,
+    Threshold,
+    Concurrency,
+    Timeout,
+    Samples,
+}
+
+impl Field {
+    const ALL: [Field; 6] = [
+        Field::Enabled,
+        Field::Interval,
+        Field::Threshold,
+        Field::Concurrency,
+        Field::Timeout,
+        Field::Samples,
+    ];
+
+    fn offset(self, delta: isize) -> Self {
+        let index = Self::ALL.iter().position(|f| *f == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+        Self::ALL[(index + delta).rem_euclid(len) as usize]
+    }
+}
+
+/// 按应用编辑混合模式（多端点自动选优）配置的弹窗
+pub struct HybridForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    enabled: bool,
+    interval: TextInput,
+    threshold: TextInput,
+    concurrency: TextInput,
+    timeout: TextInput,
+    samples: TextInput,
+    field: Field,
+    message: Option<String>,
+}
+
+impl HybridForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            enabled: true,
+            interval: TextInput::new("Latency test interval (s)"),
+            threshold: TextInput::new("URL failure threshold"),
+            concurrency: TextInput::new("Speedtest concurrency"),
+            timeout: TextInput::new("Per-URL timeout (s)"),
+            samples: TextInput::new("Samples per URL"),
+            field: Field::Enabled,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType) -> Result<(), String> {
+        let config = self
+            .state
+            .db
+            .get_hybrid_mode_config(app_type.as_str())
+            .map_err(|e| e.to_string())?;
+        self.app_type = app_type;
+        self.enabled = config.enabled;
+        self.interval = TextInput::with_value(
+            "Latency test interval (s)",
+            &config.latency_test_interval.to_string(),
+        );
+        self.threshold = TextInput::with_value(
+            "URL failure threshold",
+            &config.url_circuit_failure_threshold.to_string(),
+        );
+        self.concurrency = TextInput::with_value(
+            "Speedtest concurrency",
+            &config.speedtest_concurrency.to_string(),
+        );
+        self.timeout = TextInput::with_value(
+            "Per-URL timeout (s)",
+            &config.speedtest_timeout_secs.to_string(),
+        );
+        self.samples =
+            TextInput::with_value("Samples per URL", &config.speedtest_samples.to_string());
+        self.field = Field::Enabled;
+        self.message = None;
+        self.visible = true;
+        Ok(())
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.message = None;
+    }
+
+    /// 返回 true 表示配置已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = self.field.offset(1),
+            KeyCode::BackTab | KeyCode::Up => self.field = self.field.offset(-1),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Char(' ') if self.field == Field::Enabled => self.enabled = !self.enabled,
+            _ => {
+                if let Some(input) = self.active_input() {
+                    match key {
+                        KeyCode::Backspace => input.backspace(),
+                        KeyCode::Delete => input.delete(),
+                        KeyCode::Left => input.move_left(),
+                        KeyCode::Right => input.move_right(),
+                        KeyCode::Home => input.home(),
+                        KeyCode::End => input.end(),
+                        KeyCode::Char(c) if c.is_ascii_digit() => input.insert(c),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn active_input(&mut self) -> Option<&mut TextInput> {
+        match self.field {
+            Field::Enabled => None,
+            Field::Interval => Some(&mut self.interval),
+            Field::Threshold => Some(&mut self.threshold),
+            Field::Concurrency => Some(&mut self.concurrency),
+            Field::Timeout => Some(&mut self.timeout),
+            Field::Samples => Some(&mut self.samples),
+        }
+    }
+
+    fn parse(&self) -> Result<HybridModeConfig, String> {
+        let latency_test_interval = self
+            .interval
+            .value
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|v| INTERVAL_RANGE.contains(v))
+            .ok_or_else(|| {
+                format!(
+                    "Interval must be between {} and {} seconds",
+                    INTERVAL_RANGE.start(),
+                    INTERVAL_RANGE.end()
+                )
+            })?;
+        let url_circuit_failure_threshold = self
+            .threshold
+            .value
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or("Failure threshold must be at least 1")?;
+        let speedtest_concurrency = parse_in_range(&self.concurrency, &CONCURRENCY_RANGE)
+            .ok_or_else(|| {
+                format!(
+                    "Concurrency must be between {} and {}",
+                    CONCURRENCY_RANGE.start(),
+                    CONCURRENCY_RANGE.end()
+                )
+            })?;
+        let speedtest_timeout_secs =
+            parse_in_range(&self.timeout, &TIMEOUT_RANGE).ok_or_else(|| {
+                format!(
+                    "Timeout must be between {} and {} seconds",
+                    TIMEOUT_RANGE.start(),
+                    TIMEOUT_RANGE.end()
+                )
+            })?;
+        let speedtest_samples = parse_in_range(&self.samples, &SAMPLES_RANGE).ok_or_else(|| {
+            format!(
+                "Samples must be between {} and {}",
+                SAMPLES_RANGE.start(),
+                SAMPLES_RANGE.end()
+            )
+        })?;
+
+        Ok(HybridModeConfig {
+            enabled: self.enabled,
+            latency_test_interval,
+            url_circuit_failure_threshold,
+            speedtest_concurrency,
+            speedtest_timeout_secs,
+            speedtest_samples,
+        })
+    }
+
+    fn save(&mut self) -> bool {
+        let result = self.parse().and_then(|config| {
+            self.state
+                .db
+                .update_hybrid_mode_config(self.app_type.as_str(), &config)
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e);
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(50, 12, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Hybrid Mode — {}", self.app_type.as_str()))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 8])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        let checkbox = if self.enabled { "[x]" } else { "[ ]" };
+        frame.render_widget(
+            Paragraph::new(format!("{checkbox} Enabled")).style(style(Field::Enabled)),
+            chunks[0],
+        );
+
+        for (i, (field, input)) in [
+            (Field::Interval, &self.interval),
+            (Field::Threshold, &self.threshold),
+            (Field::Concurrency, &self.concurrency),
+            (Field::Timeout, &self.timeout),
+            (Field::Samples, &self.samples),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            frame.render_widget(
+                Paragraph::new(input.display(self.field == field)).style(style(field)),
+                chunks[i + 1],
+            );
+        }
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[6]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space:Toggle  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[7],
+        );
+    }
+}
+
+fn parse_in_range<T>(input: &TextInput, range: &std::ops::RangeInclusive<T>) -> Option<T>
+where
+    T: std::str::FromStr + PartialOrd,
+{
+    input
+        .value
+        .trim()
+        .parse::<T>()
+        .ok()
+        .filter(|v| range.contains(v))
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{
+    AppState, ConfigService, ImportAction, ImportBundle, ImportCategory, ImportItem,
+    ImportStrategies, ImportSummary, EXPORT_FORMAT_VERSION,
+};
+
+/// 已读取的导出文件及其导入计划
+struct Preview {
+    bundle: ImportBundle,
+    strategies: ImportStrategies,
+    plan: Vec<ImportItem>,
+    /// 当前选中的分类（用于切换冲突处理方式）
+    category: usize,
+    scroll: u16,
+}
+
+/// 配置导入弹窗：先输入导出文件或桌面版数据库的路径，再预览导入计划并按分类选择冲突处理方式
+pub struct ImportForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    path: TextInput,
+    preview: Option<Preview>,
+    message: Option<String>,
+}
+
+impl ImportForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            path: TextInput::new("Path"),
+            preview: None,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.path = TextInput::with_value("Path", &self.path.value.clone());
+        self.preview = None;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.preview = None;
+    }
+
+    /// 导入完成时返回结果统计
+    pub async fn handle_key(&mut self, key: KeyCode) -> Option<ImportSummary> {
+        if self.preview.is_some() {
+            return self.handle_preview_key(key).await;
+        }
+
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => self.load_preview().await,
+            KeyCode::Backspace => self.path.backspace(),
+            KeyCode::Delete => self.path.delete(),
+            KeyCode::Left => self.path.move_left(),
+            KeyCode::Right => self.path.move_right(),
+            KeyCode::Home => self.path.home(),
+            KeyCode::End => self.path.end(),
+            KeyCode::Char(c) => self.path.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    async fn load_preview(&mut self) {
+        let path = self.path.value.trim();
+        if path.is_empty() {
+            self.message = Some("Path cannot be empty".to_string());
+            return;
+        }
+
+        let result = ConfigService::read_import_source(&self.state, &expand_home(path))
+            .await
+            .and_then(|bundle| {
+                let strategies = bundle.default_strategies();
+                let plan = ConfigService::plan_import(&self.state, &bundle, &strategies)?;
+                Ok(Preview {
+                    bundle,
+                    strategies,
+                    plan,
+                    category: 0,
+                    scroll: 0,
+                })
+            });
+        match result {
+            Ok(preview) => {
+                self.message = None;
+                self.preview = Some(preview);
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    async fn handle_preview_key(&mut self, key: KeyCode) -> Option<ImportSummary> {
+        let preview = self.preview.as_mut()?;
+        let categories = ImportCategory::ALL.len();
+
+        match key {
+            KeyCode::Esc => {
+                // 返回路径输入
+                self.preview = None;
+                self.message = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                preview.category = (preview.category + categories - 1) % categories;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                preview.category = (preview.category + 1) % categories;
+            }
+            KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => {
+                preview
+                    .strategies
+                    .cycle(ImportCategory::ALL[preview.category]);
+                match ConfigService::plan_import(&self.state, &preview.bundle, &preview.strategies)
+                {
+                    Ok(plan) => preview.plan = plan,
+                    Err(e) => self.message = Some(e.to_string()),
+                }
+            }
+            KeyCode::PageDown => preview.scroll = preview.scroll.saturating_add(10),
+            KeyCode::PageUp => preview.scroll = preview.scroll.saturating_sub(10),
+            KeyCode::Enter => {
+                match ConfigService::apply_import(&self.state, &preview.bundle, &preview.plan).await
+                {
+                    Ok(summary) => {
+                        self.close();
+                        return Some(summary);
+                    }
+                    Err(e) => self.message = Some(format!("Import failed: {e}")),
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        match &self.preview {
+            Some(preview) => self.render_preview(frame, theme, preview),
+            None => self.render_path(frame, theme),
+        }
+    }
+
+    fn render_path(&self, frame: &mut Frame, theme: &Theme) {
+        let area = centered_rect(64, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Import Configuration")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let display = self.path.display(true);
+        frame.render_widget(Paragraph::new(display).style(theme.selected), chunks[0]);
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[1]);
+        } else {
+            frame.render_widget(
+                Paragraph::new("Export file, roster (.yaml/.toml) or GUI database (~/.cc-switch)")
+                    .style(theme.inactive),
+                chunks[1],
+            );
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Preview  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+
+    fn render_preview(&self, frame: &mut Frame, theme: &Theme, preview: &Preview) {
+        let area = centered_rect(80, frame.area().height.saturating_sub(4), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Import Preview")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let categories = ImportCategory::ALL.len() as u16;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(categories + 1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let mut strategy_lines = vec![Line::styled("On conflict", theme.title)];
+        for (i, category) in ImportCategory::ALL.iter().enumerate() {
+            let count = preview
+                .plan
+                .iter()
+                .filter(|item| item.category == *category)
+                .count();
+            let text = format!(
+                "  {:<16}{:<10} ({count} in file)",
+                category.label(),
+                preview.strategies.get(*category).label()
+            );
+            let style = if i == preview.category {
+                theme.selected
+            } else {
+                theme.normal
+            };
+            strategy_lines.push(Line::styled(text, style));
+        }
+        frame.render_widget(Paragraph::new(strategy_lines), chunks[0]);
+
+        let status = match (&self.message, preview.bundle.has_redacted_secrets()) {
+            (Some(msg), _) => Line::styled(msg.clone(), theme.error),
+            (None, true) => Line::styled(
+                "File contains redacted API keys; overwriting will replace existing keys",
+                theme.warning,
+            ),
+            (None, false) if preview.bundle.is_roster() => Line::styled(
+                "Provider roster: providers with the same name are updated in place",
+                theme.inactive,
+            ),
+            (None, false) if preview.bundle.version() < EXPORT_FORMAT_VERSION => Line::styled(
+                format!(
+                    "File uses export format v{}; it was upgraded to v{EXPORT_FORMAT_VERSION}",
+                    preview.bundle.version()
+                ),
+                theme.inactive,
+            ),
+            (None, false) => Line::raw(""),
+        };
+        frame.render_widget(Paragraph::new(status), chunks[1]);
+
+        let items: Vec<Line> = preview
+            .plan
+            .iter()
+            .map(|item| {
+                let (action, style) = match &item.action {
+                    ImportAction::Add => ("add".to_string(), theme.success),
+                    ImportAction::Overwrite => ("overwrite".to_string(), theme.warning),
+                    ImportAction::Skip => ("skip".to_string(), theme.inactive),
+                    ImportAction::Rename(id) => (format!("duplicate as {id}"), theme.highlight),
+                };
+                let app = item.app.as_ref().map(|a| a.as_str()).unwrap_or("-");
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<14}{:<8}{:<28}", item.category.label(), app, item.name),
+                        theme.normal,
+                    ),
+                    Span::styled(action, style),
+                ])
+            })
+            .collect();
+        let items = if items.is_empty() {
+            Paragraph::new("Nothing to import").style(theme.inactive)
+        } else {
+            Paragraph::new(items)
+                .wrap(Wrap { trim: false })
+                .scroll((preview.scroll, 0))
+        };
+        frame.render_widget(items, chunks[2]);
+
+        frame.render_widget(
+            Paragraph::new(
+                "j/k:Category  Space:Change strategy  PgUp/PgDn:Scroll  Enter:Import  Esc:Back",
+            )
+            .style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
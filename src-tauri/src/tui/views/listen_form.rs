@@ -0,0 +1,89 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Address,
+    Port,
+    /// 逗号分隔的 CIDR 网段
+    Allowlist,
+}
+
+/// 代理监听地址、端口与 IP 白名单编辑弹窗
+pub struct ListenForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl ListenForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, address: &str, port: u16, allowlist: &[String]) {
+        self.form = Form::new(vec![
+            (Field::Address, FormField::text("Listen address", address)),
+            (Field::Port, FormField::text("Port", &port.to_string())),
+            (
+                Field::Allowlist,
+                FormField::text("Allowed networks", &allowlist.join(", ")),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的地址、端口与白名单网段
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<(String, u16, Vec<String>)> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => match self.form.input(Field::Port).value.trim().parse::<u16>() {
+                Ok(port) if port > 0 => {
+                    let allowlist = self
+                        .form
+                        .input(Field::Allowlist)
+                        .value
+                        .split([',', ' '])
+                        .filter(|entry| !entry.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    let address = self.form.input(Field::Address).value.trim().to_string();
+                    return Some((address, port, allowlist));
+                }
+                _ => self
+                    .form
+                    .set_error("Port must be a number between 1 and 65535".to_string()),
+            },
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Proxy Listen Address",
+            "CIDRs allowed to connect, e.g. 192.168.1.0/24 (empty = anyone)",
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+
+/// 空闲锁屏：覆盖整个界面隐藏敏感信息，输入主密码后解锁
+pub struct LockScreen {
+    pub visible: bool,
+    password: String,
+    message: Option<String>,
+}
+
+impl LockScreen {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            password: String::new(),
+            message: None,
+        }
+    }
+
+    pub fn lock(&mut self) {
+        self.password.clear();
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn unlock(&mut self) {
+        self.password.clear();
+        self.visible = false;
+    }
+
+    /// 密码错误时清空输入并提示
+    pub fn reject(&mut self) {
+        self.password.clear();
+        self.message = Some("Wrong password".to_string());
+    }
+
+    /// 按 Enter 时返回输入的密码
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<String> {
+        match key {
+            KeyCode::Enter => return Some(std::mem::take(&mut self.password)),
+            KeyCode::Esc => self.password.clear(),
+            KeyCode::Backspace => {
+                self.password.pop();
+            }
+            KeyCode::Char(c) => self.password.push(c),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        frame.render_widget(Clear, frame.area());
+        let area = centered_rect(50, 7, frame.area());
+        let block = Block::default()
+            .title("CC Switch — locked")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 5])
+            .split(area.inner(Margin::new(2, 1)));
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Master password: {}│",
+                "•".repeat(self.password.chars().count())
+            ))
+            .style(theme.selected),
+            chunks[0],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Unlock  Esc:Clear").style(theme.inactive),
+            chunks[4],
+        );
+    }
+}
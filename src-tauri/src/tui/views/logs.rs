@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+
+use crossterm::event::KeyCode;
+use log::Level;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+
+use super::{Theme, View};
+use crate::tui::app::NavAction;
+use crate::tui::logging::{self, LogEntry, LOG_BUFFER_CAPACITY};
+use crate::tui::widgets::TextInput;
+
+/// 应用日志查看：按级别过滤、关键字搜索，跟随模式下自动滚动到最新日志
+pub struct LogsView {
+    entries: VecDeque<LogEntry>,
+    last_seq: u64,
+    /// 显示的最低级别（含更严重的级别）
+    min_level: Level,
+    search: String,
+    /// 正在编辑的搜索词，处于输入模式时为 Some
+    search_input: Option<TextInput>,
+    /// 是否自动滚动到最新日志
+    follow: bool,
+    list_state: ListState,
+}
+
+impl LogsView {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            last_seq: 0,
+            min_level: Level::Trace,
+            search: String::new(),
+            search_input: None,
+            follow: true,
+            list_state: ListState::default(),
+        }
+    }
+
+    /// 拉取新捕获的日志
+    pub fn poll(&mut self) {
+        let new_entries = logging::entries_since(self.last_seq);
+        let Some(last) = new_entries.last() else {
+            return;
+        };
+        self.last_seq = last.seq;
+        self.entries.extend(new_entries);
+        let overflow = self.entries.len().saturating_sub(LOG_BUFFER_CAPACITY);
+        self.entries.drain(..overflow);
+        self.sync_selection();
+    }
+
+    fn filtered(&self) -> Vec<&LogEntry> {
+        let needle = self.search.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|e| e.level <= self.min_level)
+            .filter(|e| {
+                needle.is_empty()
+                    || e.message.to_lowercase().contains(&needle)
+                    || e.target.to_lowercase().contains(&needle)
+            })
+            .collect()
+    }
+
+    /// 过滤条件或日志变化后校正选中项；跟随模式下选中最新一条
+    fn sync_selection(&mut self) {
+        let len = self.filtered().len();
+        let selected = match self.list_state.selected() {
+            _ if len == 0 => None,
+            _ if self.follow => Some(len - 1),
+            Some(i) => Some(i.min(len - 1)),
+            None => Some(len - 1),
+        };
+        self.list_state.select(selected);
+    }
+
+    /// 切换显示的最低级别：TRACE → ERROR → WARN → INFO → DEBUG → TRACE
+    pub fn cycle_level(&mut self) -> Level {
+        self.min_level = match self.min_level {
+            Level::Trace => Level::Error,
+            Level::Error => Level::Warn,
+            Level::Warn => Level::Info,
+            Level::Info => Level::Debug,
+            Level::Debug => Level::Trace,
+        };
+        self.sync_selection();
+        self.min_level
+    }
+
+    pub fn toggle_follow(&mut self) -> bool {
+        self.follow = !self.follow;
+        self.sync_selection();
+        self.follow
+    }
+
+    pub fn open_search(&mut self) {
+        self.search_input = Some(TextInput::with_value("Search", &self.search));
+    }
+
+    /// 搜索输入时按键由本视图直接处理
+    pub fn captures_input(&self) -> bool {
+        self.search_input.is_some()
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        let Some(input) = self.search_input.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.search_input = None,
+            KeyCode::Enter => {
+                self.search = input.value.trim().to_string();
+                self.search_input = None;
+                self.sync_selection();
+            }
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+    }
+
+    /// 手动向上浏览时暂停跟随，跳到底部时恢复
+    pub fn navigate(&mut self, action: NavAction) {
+        let len = self.filtered().len();
+        if let Some(i) = action.apply(self.list_state.selected(), len) {
+            self.list_state.select(Some(i));
+            self.follow = i + 1 == len;
+        }
+    }
+}
+
+fn level_style(level: Level, theme: &Theme) -> Style {
+    match level {
+        Level::Error => theme.error,
+        Level::Warn => theme.warning,
+        Level::Info => theme.normal,
+        Level::Debug | Level::Trace => theme.inactive,
+    }
+}
+
+impl View for LogsView {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let entries = self.filtered();
+        let mut title = format!(
+            "Logs — {} / {} entries, {} and above",
+            entries.len(),
+            self.entries.len(),
+            self.min_level
+        );
+        if !self.search.is_empty() {
+            title.push_str(&format!(" [{}]", self.search));
+        }
+        if self.follow {
+            title.push_str(" (following)");
+        }
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", entry.timestamp.format("%H:%M:%S%.3f")),
+                        theme.inactive,
+                    ),
+                    Span::styled(
+                        format!("{:<5} ", entry.level),
+                        level_style(entry.level, theme),
+                    ),
+                    Span::styled(format!("{} ", entry.target), theme.inactive),
+                    Span::styled(entry.message.clone(), level_style(entry.level, theme)),
+                ]))
+            })
+            .collect();
+        let is_empty = items.is_empty();
+
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        if is_empty {
+            frame.render_widget(
+                Paragraph::new("No log entries match").style(theme.inactive),
+                chunks[0],
+            );
+        } else {
+            let list = List::new(items).highlight_style(theme.selected);
+            frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+        }
+
+        if let Some(input) = &self.search_input {
+            let display = input.display(true);
+            frame.render_widget(Paragraph::new(display).style(theme.selected), chunks[1]);
+        } else {
+            let hint = format!(
+                "Capturing {} and above (change log level in Settings)",
+                logging::level()
+            );
+            frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[1]);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use tokio::sync::mpsc;
+
+use super::settings::format_size;
+use super::{centered_rect, Theme};
+use crate::tui::widgets::spinner_frame;
+use cc_switch_lib::{AppState, DatabaseStats, MaintenanceOutcome, MaintenanceTask};
+
+/// 统计信息中展示的最大表数
+const TABLES_SHOWN: usize = 4;
+
+/// 后台维护任务发回的进度
+enum Progress {
+    Started {
+        task: MaintenanceTask,
+        step: usize,
+        total: usize,
+    },
+    Finished {
+        task: MaintenanceTask,
+        result: Result<MaintenanceOutcome, String>,
+        elapsed: Duration,
+    },
+    Done(Result<DatabaseStats, String>),
+}
+
+struct Running {
+    task: MaintenanceTask,
+    step: usize,
+    total: usize,
+    started_at: Instant,
+}
+
+/// 数据库维护弹窗：查看数据库大小与各表行数，执行完整性检查、WAL 检查点或 VACUUM
+pub struct MaintenanceDialog {
+    state: Arc<AppState>,
+    pub visible: bool,
+    stats: Option<Result<DatabaseStats, String>>,
+    list_state: ListState,
+    /// 当前执行中的步骤，全部完成后为 None
+    running: Option<Running>,
+    results: Vec<(
+        MaintenanceTask,
+        Result<MaintenanceOutcome, String>,
+        Duration,
+    )>,
+    progress_rx: Option<mpsc::UnboundedReceiver<Progress>>,
+}
+
+impl MaintenanceDialog {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            stats: None,
+            list_state: ListState::default(),
+            running: None,
+            results: Vec::new(),
+            progress_rx: None,
+        }
+    }
+
+    /// 打开弹窗；维护进行中时只重新显示进度
+    pub fn open(&mut self) {
+        if self.progress_rx.is_none() {
+            self.stats = Some(self.state.db.database_stats().map_err(|e| e.to_string()));
+            self.list_state.select(Some(0));
+        }
+        self.visible = true;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress_rx.is_some()
+    }
+
+    /// 关闭弹窗，进行中的维护在后台继续
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 选项：各维护操作，最后一项为依次执行全部
+    fn selected_tasks(&self) -> Vec<MaintenanceTask> {
+        match self.list_state.selected() {
+            Some(i) if i < MaintenanceTask::ALL.len() => vec![MaintenanceTask::ALL[i]],
+            _ => MaintenanceTask::ALL.to_vec(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        let options = MaintenanceTask::ALL.len() + 1;
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some((i + 1).min(options - 1)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+            }
+            KeyCode::Enter if self.progress_rx.is_none() => self.start(self.selected_tasks()),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tasks: Vec<MaintenanceTask>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.progress_rx = Some(rx);
+        self.results.clear();
+        let db = self.state.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let total = tasks.len();
+            for (i, task) in tasks.into_iter().enumerate() {
+                let _ = tx.send(Progress::Started {
+                    task,
+                    step: i + 1,
+                    total,
+                });
+                let started_at = Instant::now();
+                let result = db.run_maintenance(task).map_err(|e| e.to_string());
+                let _ = tx.send(Progress::Finished {
+                    task,
+                    result,
+                    elapsed: started_at.elapsed(),
+                });
+            }
+            let _ = tx.send(Progress::Done(
+                db.database_stats().map_err(|e| e.to_string()),
+            ));
+        });
+    }
+
+    /// 每次事件循环调用，接收后台维护进度
+    pub fn poll(&mut self) {
+        let Some(rx) = self.progress_rx.as_mut() else {
+            return;
+        };
+        while let Ok(progress) = rx.try_recv() {
+            match progress {
+                Progress::Started { task, step, total } => {
+                    self.running = Some(Running {
+                        task,
+                        step,
+                        total,
+                        started_at: Instant::now(),
+                    })
+                }
+                Progress::Finished {
+                    task,
+                    result,
+                    elapsed,
+                } => {
+                    self.running = None;
+                    self.results.push((task, result, elapsed));
+                }
+                Progress::Done(stats) => {
+                    self.stats = Some(stats);
+                    self.progress_rx = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(76, 20.min(frame.area().height), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Database maintenance")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(MaintenanceTask::ALL.len() as u16 + 2),
+                Constraint::Min(2),
+                Constraint::Length(1),
+            ])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let stats = match &self.stats {
+            Some(Ok(stats)) => Paragraph::new(stats_text(stats)).style(theme.normal),
+            Some(Err(e)) => {
+                Paragraph::new(format!("Failed to read database stats: {e}")).style(theme.error)
+            }
+            None => Paragraph::new(""),
+        };
+        frame.render_widget(stats.wrap(Wrap { trim: true }), chunks[0]);
+
+        let items: Vec<ListItem> = MaintenanceTask::ALL
+            .iter()
+            .map(|task| ListItem::new(task.label()))
+            .chain(std::iter::once(ListItem::new("Run all")))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::TOP | Borders::BOTTOM))
+            .highlight_style(theme.selected)
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, chunks[1], &mut self.list_state);
+
+        let mut lines: Vec<Line> = self
+            .results
+            .iter()
+            .map(|(task, result, elapsed)| match result {
+                Ok(outcome) => Line::styled(
+                    format!(
+                        "✓ {}: {} ({:.1}s)",
+                        task.label(),
+                        outcome_text(outcome),
+                        elapsed.as_secs_f64()
+                    ),
+                    match outcome {
+                        MaintenanceOutcome::IntegrityCheck { problems } if !problems.is_empty() => {
+                            theme.warning
+                        }
+                        _ => theme.success,
+                    },
+                ),
+                Err(e) => Line::styled(format!("✗ {}: {e}", task.label()), theme.error),
+            })
+            .collect();
+        if let Some(running) = &self.running {
+            lines.push(Line::styled(
+                format!(
+                    "{} [{}/{}] {}… {}s",
+                    spinner_frame(),
+                    running.step,
+                    running.total,
+                    running.task.label(),
+                    running.started_at.elapsed().as_secs()
+                ),
+                theme.highlight,
+            ));
+        } else if self.progress_rx.is_some() {
+            lines.push(Line::styled(
+                format!("{} Starting…", spinner_frame()),
+                theme.highlight,
+            ));
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), chunks[2]);
+
+        let hint = if self.progress_rx.is_some() {
+            "Running — the database is locked until maintenance finishes  Esc:Hide"
+        } else {
+            "j/k:Navigate  Enter:Run  q/Esc:Close"
+        };
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[3]);
+    }
+}
+
+fn stats_text(stats: &DatabaseStats) -> String {
+    let tables = stats
+        .tables
+        .iter()
+        .take(TABLES_SHOWN)
+        .map(|(name, rows)| format!("{name} {rows}"))
+        .collect::<Vec<_>>()
+        .join(" · ");
+    format!(
+        "Size: {} ({} reclaimable by VACUUM), journal mode: {}\nLargest tables (rows): {tables}",
+        format_size(
+            stats
+                .file_size_bytes
+                .max(stats.page_count * stats.page_size)
+        ),
+        format_size(stats.reclaimable_bytes()),
+        stats.journal_mode
+    )
+}
+
+fn outcome_text(outcome: &MaintenanceOutcome) -> String {
+    match outcome {
+        MaintenanceOutcome::IntegrityCheck { problems } if problems.is_empty() => "ok".to_string(),
+        MaintenanceOutcome::IntegrityCheck { problems } => {
+            format!("{} problem(s): {}", problems.len(), problems[0])
+        }
+        MaintenanceOutcome::WalCheckpoint {
+            checkpointed_pages: Some(pages),
+        } => format!("{pages} page(s) written back"),
+        MaintenanceOutcome::WalCheckpoint {
+            checkpointed_pages: None,
+        } => "not in WAL mode, nothing to do".to_string(),
+        MaintenanceOutcome::Vacuum {
+            size_before_bytes,
+            size_after_bytes,
+        } => format!(
+            "{} → {}",
+            format_size(*size_before_bytes),
+            format_size(*size_after_bytes)
+        ),
+    }
+}
@@ -0,0 +1,167 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Current,
+    New,
+    Confirm,
+}
+
+/// 主密码弹窗返回的修改：校验当前密码后设置新密码，`new` 为 None 表示清除
+pub struct MasterPasswordChange {
+    pub current: String,
+    pub new: Option<String>,
+}
+
+/// 主密码弹窗：已设置时需先输入当前密码，新密码留空则清除主密码
+pub struct MasterPasswordForm {
+    pub visible: bool,
+    has_password: bool,
+    current: String,
+    new: String,
+    confirm: String,
+    field: Field,
+    message: Option<String>,
+}
+
+impl MasterPasswordForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            has_password: false,
+            current: String::new(),
+            new: String::new(),
+            confirm: String::new(),
+            field: Field::New,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, has_password: bool) {
+        self.has_password = has_password;
+        self.current.clear();
+        self.new.clear();
+        self.confirm.clear();
+        self.field = if has_password {
+            Field::Current
+        } else {
+            Field::New
+        };
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    fn fields(&self) -> &'static [Field] {
+        if self.has_password {
+            &[Field::Current, Field::New, Field::Confirm]
+        } else {
+            &[Field::New, Field::Confirm]
+        }
+    }
+
+    /// 按 Enter 且两次输入一致时返回待保存的修改
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<MasterPasswordChange> {
+        let fields = self.fields();
+        let index = fields.iter().position(|f| *f == self.field).unwrap_or(0);
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = fields[(index + 1) % fields.len()],
+            KeyCode::BackTab | KeyCode::Up => {
+                self.field = fields[(index + fields.len() - 1) % fields.len()]
+            }
+            KeyCode::Enter => {
+                if self.new != self.confirm {
+                    self.message = Some("Passwords do not match".to_string());
+                } else if self.new.is_empty() && !self.has_password {
+                    self.message = Some("Password must not be empty".to_string());
+                } else {
+                    return Some(MasterPasswordChange {
+                        current: self.current.clone(),
+                        new: (!self.new.is_empty()).then(|| self.new.clone()),
+                    });
+                }
+            }
+            code => {
+                let value = match self.field {
+                    Field::Current => &mut self.current,
+                    Field::New => &mut self.new,
+                    Field::Confirm => &mut self.confirm,
+                };
+                match code {
+                    KeyCode::Backspace => {
+                        value.pop();
+                    }
+                    KeyCode::Char(c) => value.push(c),
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 10, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Master password")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 6])
+            .split(area.inner(Margin::new(2, 1)));
+
+        for (i, field) in self.fields().iter().enumerate() {
+            let (label, value) = match field {
+                Field::Current => ("Current password", &self.current),
+                Field::New => ("New password", &self.new),
+                Field::Confirm => ("Confirm", &self.confirm),
+            };
+            let (style, cursor) = if self.field == *field {
+                (theme.selected, "│")
+            } else {
+                (theme.normal, "")
+            };
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "{label}: {}{cursor}",
+                    "•".repeat(value.chars().count())
+                ))
+                .style(style),
+                chunks[i],
+            );
+        }
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[3]);
+        } else if self.has_password {
+            frame.render_widget(
+                Paragraph::new("Leave the new password empty to remove it").style(theme.inactive),
+                chunks[3],
+            );
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[5],
+        );
+    }
+}
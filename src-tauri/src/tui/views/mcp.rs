@@ -1,69 +1,140 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
 
-use crossterm::event::KeyCode;
 use indexmap::IndexMap;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Row, Table, TableState};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
 
 use super::{Theme, View};
-use cc_switch_lib::{AppState, McpServer, McpService};
+use crate::tui::app::NavAction;
+use crate::tui::keymap::Action;
+use crate::tui::widgets::{loading_title, spinner_frame};
+use cc_switch_lib::{AppState, McpCheckService, McpServer, McpService};
+
+/// 连通性检查的超时时间（含 npx 等首次下载依赖的启动耗时）
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// MCP 服务器连通性检查状态
+pub enum McpCheck {
+    Testing,
+    Ok { tools: usize, elapsed_ms: u128 },
+    Failed(String),
+}
 
 pub struct McpView {
-    state: Arc<AppState>,
+    pub loading: bool,
     servers: IndexMap<String, McpServer>,
+    /// 按服务器 ID 记录的连通性检查结果
+    checks: HashMap<String, McpCheck>,
     table_state: TableState,
+    /// 最近一次渲染区域，用于鼠标点击定位
+    area: Rect,
 }
 
 impl McpView {
-    pub fn new(state: Arc<AppState>) -> Self {
+    pub fn new() -> Self {
         Self {
-            state,
+            loading: false,
             servers: IndexMap::new(),
+            checks: HashMap::new(),
             table_state: TableState::default(),
+            area: Rect::default(),
         }
     }
 
-    pub async fn refresh(&mut self) {
-        self.servers = McpService::get_all_servers(&self.state).unwrap_or_default();
-        if !self.servers.is_empty() && self.table_state.selected().is_none() {
-            self.table_state.select(Some(0));
-        }
+    pub fn load(state: &AppState) -> IndexMap<String, McpServer> {
+        McpService::get_all_servers(state).unwrap_or_default()
     }
 
-    pub async fn handle_key(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Up => self.select_prev(),
-            KeyCode::Down => self.select_next(),
-            _ => {}
-        }
+    pub fn apply(&mut self, servers: IndexMap<String, McpServer>) {
+        self.servers = servers;
+        self.loading = false;
+
+        let selected = match self.table_state.selected() {
+            _ if self.servers.is_empty() => None,
+            Some(i) => Some(i.min(self.servers.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
     }
 
-    fn select_prev(&mut self) {
-        if self.servers.is_empty() {
-            return;
-        }
-        let i = match self.table_state.selected() {
-            Some(i) => i.saturating_sub(1),
-            None => 0,
+    pub fn get_selected(&self) -> Option<McpServer> {
+        let index = self.table_state.selected()?;
+        self.servers.get_index(index).map(|(_, s)| s.clone())
+    }
+
+    pub fn servers(&self) -> Vec<McpServer> {
+        self.servers.values().cloned().collect()
+    }
+
+    /// 标记选中服务器为检查中，返回其 ID 及检查任务
+    pub fn start_check(
+        &mut self,
+    ) -> Option<(String, impl Future<Output = McpCheck> + Send + 'static)> {
+        let index = self.table_state.selected()?;
+        let (id, server) = self.servers.get_index(index)?;
+        let (id, spec) = (id.clone(), server.server.clone());
+        self.checks.insert(id.clone(), McpCheck::Testing);
+
+        let check = async move {
+            match McpCheckService::check(&spec, CHECK_TIMEOUT).await {
+                Ok(result) => McpCheck::Ok {
+                    tools: result.tools_count,
+                    elapsed_ms: result.elapsed_ms,
+                },
+                Err(e) => McpCheck::Failed(e.to_string()),
+            }
         };
-        self.table_state.select(Some(i));
+        Some((id, check))
     }
 
-    fn select_next(&mut self) {
-        if self.servers.is_empty() {
-            return;
+    pub fn set_check(&mut self, id: String, result: McpCheck) {
+        self.checks.insert(id, result);
+    }
+
+    pub async fn handle_action(&mut self, _action: Action) {
+        // 导航键由 App 的按键序列解析器统一处理
+    }
+
+    /// 选中鼠标点击位置对应的行（跳过表头）
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) || row == inner.y {
+            return None;
+        }
+        let index = self.table_state.offset() + (row - inner.y - 1) as usize;
+        if index >= self.servers.len() {
+            return None;
+        }
+        self.table_state.select(Some(index));
+        Some(index)
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.servers.len()) {
+            self.table_state.select(Some(i));
         }
-        let i = match self.table_state.selected() {
-            Some(i) => (i + 1).min(self.servers.len() - 1),
-            None => 0,
-        };
-        self.table_state.select(Some(i));
     }
 }
 
 impl View for McpView {
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
-        let header = Row::new(vec!["Name", "Claude", "Codex", "Gemini"]).style(theme.title);
+        self.area = area;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title("MCP Servers", self.loading));
+
+        if self.loading && self.servers.is_empty() {
+            let placeholder = Paragraph::new(format!("{} Loading…", spinner_frame()))
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(placeholder, area);
+            return;
+        }
+
+        let header =
+            Row::new(vec!["Name", "Claude", "Codex", "Gemini", "Check"]).style(theme.title);
 
         let rows: Vec<Row> = self
             .servers
@@ -72,21 +143,40 @@ impl View for McpView {
                 let claude = if server.apps.claude { "[x]" } else { "[ ]" };
                 let codex = if server.apps.codex { "[x]" } else { "[ ]" };
                 let gemini = if server.apps.gemini { "[x]" } else { "[ ]" };
-                Row::new(vec![id.as_str(), claude, codex, gemini])
+                let check = match self.checks.get(id) {
+                    Some(McpCheck::Testing) => {
+                        Cell::from(format!("{} testing", spinner_frame())).style(theme.inactive)
+                    }
+                    Some(McpCheck::Ok { tools, elapsed_ms }) => {
+                        Cell::from(format!("✓ {tools} tools, {elapsed_ms}ms")).style(theme.success)
+                    }
+                    Some(McpCheck::Failed(error)) => {
+                        Cell::from(format!("✗ {error}")).style(theme.error)
+                    }
+                    None => Cell::from(""),
+                };
+                Row::new(vec![
+                    Cell::from(id.as_str()),
+                    Cell::from(claude),
+                    Cell::from(codex),
+                    Cell::from(gemini),
+                    check,
+                ])
             })
             .collect();
 
         let table = Table::new(
             rows,
             [
-                Constraint::Percentage(40),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
+                Constraint::Percentage(30),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Min(0),
             ],
         )
         .header(header)
-        .block(Block::default().borders(Borders::ALL).title("MCP Servers"))
+        .block(block)
         .highlight_style(theme.selected);
 
         frame.render_stateful_widget(table, area, &mut self.table_state);
@@ -0,0 +1,203 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::terminal::copy_to_clipboard;
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{McpServer, McpService};
+
+const DEFAULT_PATH: &str = "~/mcp-servers.json";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Path,
+    OnlySelected,
+    Clipboard,
+}
+
+impl Field {
+    fn next(self) -> Self {
+        match self {
+            Self::Path => Self::OnlySelected,
+            Self::OnlySelected => Self::Clipboard,
+            Self::Clipboard => Self::Path,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            Self::Path => Self::Clipboard,
+            Self::OnlySelected => Self::Path,
+            Self::Clipboard => Self::OnlySelected,
+        }
+    }
+}
+
+/// MCP 服务器导出弹窗：将选中或全部服务器导出为 `mcpServers` JSON 文件或复制到剪贴板
+pub struct McpExportForm {
+    pub visible: bool,
+    path: TextInput,
+    servers: Vec<McpServer>,
+    selected: Option<McpServer>,
+    only_selected: bool,
+    to_clipboard: bool,
+    field: Field,
+    message: Option<String>,
+}
+
+impl McpExportForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            path: TextInput::with_value("Path", DEFAULT_PATH),
+            servers: Vec::new(),
+            selected: None,
+            only_selected: false,
+            to_clipboard: false,
+            field: Field::Path,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, servers: Vec<McpServer>, selected: Option<McpServer>) {
+        self.only_selected = selected.is_some();
+        self.servers = servers;
+        self.selected = selected;
+        self.field = Field::Path;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 导出成功时返回结果提示
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<String> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = self.field.next(),
+            KeyCode::BackTab | KeyCode::Up => self.field = self.field.prev(),
+            KeyCode::Enter => match self.export() {
+                Ok(message) => {
+                    self.close();
+                    return Some(message);
+                }
+                Err(e) => self.message = Some(e),
+            },
+            KeyCode::Char(' ') if self.field == Field::OnlySelected => {
+                self.only_selected = !self.only_selected && self.selected.is_some();
+            }
+            KeyCode::Char(' ') if self.field == Field::Clipboard => {
+                self.to_clipboard = !self.to_clipboard;
+            }
+            _ if self.field != Field::Path => {}
+            KeyCode::Backspace => self.path.backspace(),
+            KeyCode::Delete => self.path.delete(),
+            KeyCode::Left => self.path.move_left(),
+            KeyCode::Right => self.path.move_right(),
+            KeyCode::Home => self.path.home(),
+            KeyCode::End => self.path.end(),
+            KeyCode::Char(c) => self.path.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn export(&self) -> Result<String, String> {
+        let servers: Vec<&McpServer> = match (&self.selected, self.only_selected) {
+            (Some(selected), true) => vec![selected],
+            _ => self.servers.iter().collect(),
+        };
+        if servers.is_empty() {
+            return Err("No MCP servers to export".to_string());
+        }
+        let count = servers.len();
+
+        if self.to_clipboard {
+            let snippet = McpService::export_servers_snippet(servers);
+            let text = serde_json::to_string_pretty(&snippet).map_err(|e| e.to_string())?;
+            copy_to_clipboard(&text).map_err(|e| format!("Failed to copy: {e}"))?;
+            return Ok(format!("Copied {count} MCP server(s) to clipboard"));
+        }
+
+        let path = self.path.value.trim();
+        if path.is_empty() {
+            return Err("Path cannot be empty".to_string());
+        }
+        let path = expand_home(path);
+        McpService::export_servers_to_file(servers, &path).map_err(|e| e.to_string())?;
+        Ok(format!(
+            "Exported {count} MCP server(s) to {}",
+            path.display()
+        ))
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 9, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Export MCP Servers")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 5])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        let checkbox = |checked| if checked { "[x]" } else { "[ ]" };
+
+        let path_text = self.path.display(self.field == Field::Path);
+        let path_style = if self.to_clipboard {
+            theme.inactive
+        } else {
+            style(Field::Path)
+        };
+        frame.render_widget(Paragraph::new(path_text).style(path_style), chunks[0]);
+
+        let scope = match &self.selected {
+            Some(server) => format!(
+                "{} Only selected server ({})",
+                checkbox(self.only_selected),
+                server.id
+            ),
+            None => "[ ] Only selected server (none selected)".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(scope).style(style(Field::OnlySelected)),
+            chunks[1],
+        );
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} Copy to clipboard instead of file",
+                checkbox(self.to_clipboard)
+            ))
+            .style(style(Field::Clipboard)),
+            chunks[2],
+        );
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[3]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Switch field  Space:Toggle  Enter:Export  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[4],
+        );
+    }
+}
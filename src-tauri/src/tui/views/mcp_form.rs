@@ -0,0 +1,535 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use serde_json::{Map, Value};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, McpServer, McpService};
+
+const KINDS: [&str; 3] = ["stdio", "http", "sse"];
+
+/// 键名包含这些片段的环境变量在非编辑状态下脱敏显示
+const SECRET_ENV_MARKERS: [&str; 3] = ["TOKEN", "KEY", "SECRET"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Kind,
+    Command,
+    Args,
+    Url,
+    Env(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EnvColumn {
+    Key,
+    Value,
+}
+
+#[derive(Clone, Default)]
+struct EnvVar {
+    key: String,
+    value: String,
+}
+
+/// MCP 服务器编辑弹窗：按字段编辑连接定义，环境变量以键值表格编辑
+pub struct McpForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    original: Option<McpServer>,
+    kind: usize,
+    command: String,
+    /// 以空格分隔、含空格的参数用双引号包裹
+    args: String,
+    url: String,
+    env: Vec<EnvVar>,
+    active: usize,
+    env_column: EnvColumn,
+    message: Option<String>,
+    /// 正在编辑当前字段时为 Some
+    popup: Option<TextInput>,
+}
+
+impl McpForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            original: None,
+            kind: 0,
+            command: String::new(),
+            args: String::new(),
+            url: String::new(),
+            env: Vec::new(),
+            active: 0,
+            env_column: EnvColumn::Key,
+            message: None,
+            popup: None,
+        }
+    }
+
+    pub fn open_edit(&mut self, server: &McpServer) {
+        let spec = &server.server;
+        let str_field = |name| {
+            spec.get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let kind = spec.get("type").and_then(Value::as_str).unwrap_or("stdio");
+        self.kind = KINDS.iter().position(|k| *k == kind).unwrap_or(0);
+        self.command = str_field("command");
+        self.url = str_field("url");
+        let args: Vec<String> = spec
+            .get("args")
+            .and_then(Value::as_array)
+            .map(|args| {
+                args.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.args = join_args(&args);
+        self.env = spec
+            .get("env")
+            .and_then(Value::as_object)
+            .map(|env| {
+                env.iter()
+                    .map(|(key, value)| EnvVar {
+                        key: key.clone(),
+                        value: value.as_str().unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.original = Some(server.clone());
+        self.active = 0;
+        self.env_column = EnvColumn::Key;
+        self.message = None;
+        self.popup = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.original = None;
+    }
+
+    fn is_stdio(&self) -> bool {
+        KINDS[self.kind] == "stdio"
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![Row::Kind];
+        if self.is_stdio() {
+            rows.extend([Row::Command, Row::Args]);
+            rows.extend((0..self.env.len()).map(Row::Env));
+        } else {
+            rows.push(Row::Url);
+        }
+        rows
+    }
+
+    fn active_row(&self) -> Row {
+        let rows = self.rows();
+        rows[self.active.min(rows.len() - 1)]
+    }
+
+    /// 当前字段（环境变量按所选列）的可编辑值
+    fn active_value(&mut self) -> Option<&mut String> {
+        match self.active_row() {
+            Row::Kind => None,
+            Row::Command => Some(&mut self.command),
+            Row::Args => Some(&mut self.args),
+            Row::Url => Some(&mut self.url),
+            Row::Env(i) => {
+                let var = &mut self.env[i];
+                Some(match self.env_column {
+                    EnvColumn::Key => &mut var.key,
+                    EnvColumn::Value => &mut var.value,
+                })
+            }
+        }
+    }
+
+    fn field_label(&self) -> &'static str {
+        match self.active_row() {
+            Row::Kind => "Type",
+            Row::Command => "Command",
+            Row::Args => "Args",
+            Row::Url => "URL",
+            Row::Env(_) if self.env_column == EnvColumn::Key => "Variable name",
+            Row::Env(_) => "Variable value",
+        }
+    }
+
+    /// 保存成功时返回 true
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.popup.is_some() {
+            self.handle_popup_key(key);
+            return false;
+        }
+
+        let rows = self.rows().len();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Tab | KeyCode::Down | KeyCode::Char('j') => {
+                self.active = (self.active + 1) % rows;
+            }
+            KeyCode::BackTab | KeyCode::Up | KeyCode::Char('k') => {
+                self.active = (self.active + rows - 1) % rows;
+            }
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Char('h')
+            | KeyCode::Char('l')
+            | KeyCode::Char(' ') => match self.active_row() {
+                Row::Kind => self.cycle_kind(key),
+                Row::Env(_) => {
+                    self.env_column = match self.env_column {
+                        EnvColumn::Key => EnvColumn::Value,
+                        EnvColumn::Value => EnvColumn::Key,
+                    };
+                }
+                _ => {}
+            },
+            KeyCode::Char('e') | KeyCode::F(2) => self.open_popup(),
+            KeyCode::Char('a') if self.is_stdio() => {
+                self.env.push(EnvVar::default());
+                self.active = self.rows().len() - 1;
+                self.env_column = EnvColumn::Key;
+                self.open_popup();
+            }
+            KeyCode::Char('d') => {
+                if let Row::Env(i) = self.active_row() {
+                    self.env.remove(i);
+                    self.active = self.active.min(self.rows().len() - 1);
+                }
+            }
+            KeyCode::Enter => return self.submit(),
+            _ => {}
+        }
+        false
+    }
+
+    /// 粘贴文本仅在编辑字段时插入，避免被当作导航按键
+    pub fn paste(&mut self, text: &str) {
+        if let Some(input) = self.popup.as_mut() {
+            text.chars()
+                .filter(|c| !c.is_control())
+                .for_each(|c| input.insert(c));
+        }
+    }
+
+    fn cycle_kind(&mut self, key: KeyCode) {
+        self.kind = match key {
+            KeyCode::Left | KeyCode::Char('h') => (self.kind + KINDS.len() - 1) % KINDS.len(),
+            _ => (self.kind + 1) % KINDS.len(),
+        };
+    }
+
+    fn open_popup(&mut self) {
+        let label = self.field_label();
+        if let Some(value) = self.active_value() {
+            let input = TextInput::with_value(label, value);
+            self.popup = Some(input);
+        }
+    }
+
+    fn handle_popup_key(&mut self, key: KeyCode) {
+        let Some(input) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Enter => {
+                let value = input.value.trim().to_string();
+                self.popup = None;
+                if let Some(target) = self.active_value() {
+                    *target = value;
+                }
+            }
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+    }
+
+    fn submit(&mut self) -> bool {
+        match self.save() {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e);
+                false
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let original = self.original.as_ref().ok_or("No MCP server selected")?;
+        let spec = self.build_spec(&original.server)?;
+        McpService::validate_server_spec(&spec).map_err(|e| e.to_string())?;
+
+        let server = McpServer {
+            server: spec,
+            ..original.clone()
+        };
+        McpService::upsert_server(&self.state, server).map_err(|e| e.to_string())
+    }
+
+    /// 在原连接定义上覆盖表单字段，保留 cwd、headers 等未编辑的字段
+    fn build_spec(&self, original: &Value) -> Result<Value, String> {
+        let mut spec = original.as_object().cloned().unwrap_or_default();
+        spec.insert("type".to_string(), Value::from(KINDS[self.kind]));
+
+        if !self.is_stdio() {
+            for key in ["command", "args", "env"] {
+                spec.remove(key);
+            }
+            spec.insert("url".to_string(), Value::from(self.url.trim()));
+            return Ok(Value::Object(spec));
+        }
+
+        spec.remove("url");
+        spec.insert("command".to_string(), Value::from(self.command.trim()));
+        spec.insert("args".to_string(), Value::from(split_args(&self.args)));
+
+        let mut env = Map::new();
+        for var in &self.env {
+            match (var.key.trim(), var.value.as_str()) {
+                ("", "") => continue,
+                ("", _) => return Err("Environment variable name cannot be empty".to_string()),
+                (key, value) => {
+                    env.insert(key.to_string(), Value::from(value));
+                }
+            }
+        }
+        if env.is_empty() {
+            spec.remove("env");
+        } else {
+            spec.insert("env".to_string(), Value::Object(env));
+        }
+        Ok(Value::Object(spec))
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let rows = self.rows();
+        let height = rows.len() as u16 + 7;
+        let area = centered_rect(70, height, frame.area());
+        frame.render_widget(Clear, area);
+        let title = match &self.original {
+            Some(server) => format!("Edit MCP Server — {}", server.id),
+            None => "Edit MCP Server".to_string(),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let active = self.active_row();
+        let style = |row| {
+            if row == active {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+
+        let mut lines = Vec::new();
+        for row in rows {
+            match row {
+                Row::Kind => lines.push(Line::styled(
+                    format!("Type: ◂ {} ▸", KINDS[self.kind]),
+                    style(row),
+                )),
+                Row::Command => lines.push(Line::styled(
+                    format!("Command: {}", self.command),
+                    style(row),
+                )),
+                Row::Args => lines.push(Line::styled(format!("Args: {}", self.args), style(row))),
+                Row::Url => lines.push(Line::styled(format!("URL: {}", self.url), style(row))),
+                Row::Env(i) => {
+                    if i == 0 {
+                        lines.push(Line::styled("Environment", theme.title));
+                    }
+                    lines.push(self.env_line(i, row == active, theme));
+                }
+            }
+        }
+        if self.is_stdio() && self.env.is_empty() {
+            lines.push(Line::styled("Environment: none (a:Add)", theme.inactive));
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(area.inner(Margin::new(2, 1)));
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[1]);
+        }
+        let hints = if self.is_stdio() {
+            "j/k:Navigate  h/l:Type/Column  e:Edit  a:Add var  d:Remove var  Enter:Save  q/Esc:Cancel"
+        } else {
+            "j/k:Navigate  h/l:Type  e:Edit  Enter:Save  q/Esc:Cancel"
+        };
+        frame.render_widget(Paragraph::new(hints).style(theme.inactive), chunks[2]);
+
+        if let Some(input) = &self.popup {
+            self.render_popup(frame, input, theme);
+        }
+    }
+
+    fn env_line(&self, index: usize, is_active: bool, theme: &Theme) -> Line<'static> {
+        let var = &self.env[index];
+        let cell_style = |column| {
+            if is_active && self.env_column == column {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        // 选中值单元格时显示明文以便核对
+        let value =
+            if is_secret_env_key(&var.key) && !(is_active && self.env_column == EnvColumn::Value) {
+                mask_value(&var.value)
+            } else {
+                var.value.clone()
+            };
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled(format!("{:<24}", var.key), cell_style(EnvColumn::Key)),
+            Span::styled(" = ", theme.inactive),
+            Span::styled(value, cell_style(EnvColumn::Value)),
+        ])
+    }
+
+    fn render_popup(&self, frame: &mut Frame, input: &TextInput, theme: &Theme) {
+        let area = centered_rect(70, 6, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Edit {}", input.label))
+            .borders(Borders::ALL)
+            .style(theme.highlight);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Length(1)])
+            .split(area.inner(Margin::new(2, 1)));
+        let display = input.display_value(true);
+        frame.render_widget(Paragraph::new(display).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new("Enter:Confirm  Esc:Cancel").style(theme.inactive),
+            chunks[1],
+        );
+    }
+}
+
+fn is_secret_env_key(key: &str) -> bool {
+    let key = key.to_uppercase();
+    SECRET_ENV_MARKERS.iter().any(|m| key.contains(m))
+}
+
+fn mask_value(value: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        "•".repeat(value.chars().count().min(12))
+    }
+}
+
+/// 按空白拆分参数，双引号内的空白保留
+fn split_args(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_round_trip_through_quoting() {
+        let args = vec![
+            "-y".to_string(),
+            "@scope/server".to_string(),
+            "/path with spaces".to_string(),
+            String::new(),
+        ];
+        let text = join_args(&args);
+        assert_eq!(text, r#"-y @scope/server "/path with spaces" """#);
+        assert_eq!(split_args(&text), args);
+        assert!(split_args("   ").is_empty());
+    }
+
+    #[test]
+    fn secret_env_values_are_masked() {
+        assert!(is_secret_env_key("GITHUB_TOKEN"));
+        assert!(is_secret_env_key("openai_api_key"));
+        assert!(is_secret_env_key("CLIENT_SECRET"));
+        assert!(!is_secret_env_key("HOME"));
+        assert_eq!(mask_value("abc"), "•••");
+        assert_eq!(mask_value(""), "");
+    }
+}
@@ -0,0 +1,326 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use serde_json::Value;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextArea;
+use cc_switch_lib::{AppState, AppType, McpApps, McpServer, McpService};
+
+const APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+/// 从片段中解析出的待添加服务器
+struct Candidate {
+    id: String,
+    spec: Value,
+    apps: McpApps,
+    /// 已存在同名服务器时为其原记录，添加时覆盖连接定义
+    existing: Option<McpServer>,
+}
+
+/// 粘贴 `mcpServers` JSON 片段添加 MCP 服务器：先粘贴，再逐个选择启用的应用
+pub struct McpPasteForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    input: TextArea,
+    /// 解析成功后进入预览阶段
+    candidates: Option<Vec<Candidate>>,
+    selected: usize,
+    /// 预览中选中的应用列
+    app_index: usize,
+    message: Option<String>,
+}
+
+impl McpPasteForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            input: TextArea::new(""),
+            candidates: None,
+            selected: 0,
+            app_index: 0,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType) {
+        self.app_index = APPS.iter().position(|a| *a == app_type).unwrap_or(0);
+        self.app_type = app_type;
+        self.input = TextArea::new("");
+        self.candidates = None;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.candidates = None;
+    }
+
+    /// 粘贴文本后立即尝试解析
+    pub fn paste(&mut self, text: &str) {
+        if self.candidates.is_none() {
+            self.input.insert_str(text);
+            self.parse();
+        }
+    }
+
+    fn parse(&mut self) {
+        let servers = match McpService::parse_servers_snippet(&self.input.text()) {
+            Ok(servers) => servers,
+            Err(e) => {
+                self.message = Some(e.to_string());
+                return;
+            }
+        };
+        let mut existing = McpService::get_all_servers(&self.state).unwrap_or_default();
+
+        let candidates = servers
+            .into_iter()
+            .map(|(id, spec)| {
+                let mut apps = McpApps::default();
+                apps.set_enabled_for(&self.app_type, true);
+                Candidate {
+                    existing: existing.shift_remove(&id),
+                    id,
+                    spec,
+                    apps,
+                }
+            })
+            .collect();
+        self.candidates = Some(candidates);
+        self.selected = 0;
+        self.message = None;
+    }
+
+    /// 添加完成时返回添加的服务器数量
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<usize> {
+        if self.candidates.is_some() {
+            return self.handle_preview_key(key.code);
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            if key.code == KeyCode::Char('s') {
+                self.parse();
+            }
+            return None;
+        }
+        let input = &mut self.input;
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => input.newline(),
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Up => input.move_up(1),
+            KeyCode::Down => input.move_down(1),
+            KeyCode::Home => input.home(),
+            KeyCode::End => input.end(),
+            KeyCode::Char(c) => input.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_preview_key(&mut self, key: KeyCode) -> Option<usize> {
+        let candidates = self.candidates.as_mut()?;
+        match key {
+            KeyCode::Esc => {
+                // 返回编辑片段
+                self.candidates = None;
+                self.message = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(candidates.len() - 1);
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.app_index = (self.app_index + APPS.len() - 1) % APPS.len();
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.app_index = (self.app_index + 1) % APPS.len();
+            }
+            KeyCode::Char(' ') => {
+                let app = &APPS[self.app_index];
+                let apps = &mut candidates[self.selected].apps;
+                apps.set_enabled_for(app, !apps.is_enabled_for(app));
+            }
+            KeyCode::Enter => match self.add_all() {
+                Ok(count) => {
+                    self.close();
+                    return Some(count);
+                }
+                Err(e) => self.message = Some(e),
+            },
+            _ => {}
+        }
+        None
+    }
+
+    fn add_all(&self) -> Result<usize, String> {
+        let candidates = self.candidates.as_deref().unwrap_or_default();
+        for candidate in candidates {
+            let server = match &candidate.existing {
+                Some(existing) => McpServer {
+                    server: candidate.spec.clone(),
+                    apps: candidate.apps.clone(),
+                    ..existing.clone()
+                },
+                None => McpServer {
+                    id: candidate.id.clone(),
+                    name: candidate.id.clone(),
+                    server: candidate.spec.clone(),
+                    apps: candidate.apps.clone(),
+                    description: None,
+                    homepage: None,
+                    docs: None,
+                    tags: Vec::new(),
+                },
+            };
+            McpService::upsert_server(&self.state, server)
+                .map_err(|e| format!("Failed to add {}: {e}", candidate.id))?;
+        }
+        Ok(candidates.len())
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(80, frame.area().height.saturating_sub(4), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Add MCP Servers from JSON")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner.inner(Margin::new(1, 0)));
+
+        let hint = match &self.candidates {
+            Some(candidates) => {
+                self.render_candidates(frame, chunks[0], theme, candidates);
+                "j/k:Server  h/l:App  Space:Toggle  Enter:Add  Esc:Back"
+            }
+            None => {
+                self.render_input(frame, chunks[0], theme);
+                "Paste an mcpServers snippet  Ctrl+S:Parse  Esc:Cancel"
+            }
+        };
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[1]);
+        }
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[2]);
+    }
+
+    fn render_input(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let height = area.height as usize;
+        let scroll = (self.input.row + 1).saturating_sub(height);
+        let lines: Vec<Line> = self
+            .input
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(height)
+            .map(|(i, line)| {
+                if i != self.input.row {
+                    return Line::styled(line.clone(), theme.normal);
+                }
+                let before: String = line.chars().take(self.input.col).collect();
+                let after: String = line.chars().skip(self.input.col).collect();
+                Line::styled(format!("{before}│{after}"), theme.normal)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
+    fn render_candidates(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        theme: &Theme,
+        candidates: &[Candidate],
+    ) {
+        let mut lines = vec![Line::styled(
+            format!("{} server(s) found", candidates.len()),
+            theme.title,
+        )];
+        for (i, candidate) in candidates.iter().enumerate() {
+            let mut spans = vec![Span::styled(
+                format!("{:<24}", candidate.id),
+                if i == self.selected {
+                    theme.highlight
+                } else {
+                    theme.normal
+                },
+            )];
+            for (j, app) in APPS.iter().enumerate() {
+                let checkbox = if candidate.apps.is_enabled_for(app) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                let style = if i == self.selected && j == self.app_index {
+                    theme.selected
+                } else {
+                    theme.normal
+                };
+                spans.push(Span::styled(
+                    format!("{checkbox} {:<8}", app.as_str()),
+                    style,
+                ));
+            }
+            spans.push(Span::styled(
+                format!("  {}", spec_summary(&candidate.spec)),
+                theme.inactive,
+            ));
+            if candidate.existing.is_some() {
+                spans.push(Span::styled("  (exists, will overwrite)", theme.warning));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let scroll = (self.selected + 2).saturating_sub(area.height as usize);
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), area);
+    }
+}
+
+/// 连接定义的单行摘要：stdio 显示命令行，http/sse 显示地址
+fn spec_summary(spec: &Value) -> String {
+    let kind = spec.get("type").and_then(Value::as_str).unwrap_or("stdio");
+    if kind != "stdio" {
+        let url = spec.get("url").and_then(Value::as_str).unwrap_or_default();
+        return format!("{kind}: {url}");
+    }
+    let command = spec
+        .get("command")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let args: Vec<&str> = spec
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| args.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    format!("stdio: {command} {}", args.join(" "))
+        .trim_end()
+        .to_string()
+}
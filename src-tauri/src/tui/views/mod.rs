@@ -1,14 +1,109 @@
+mod app_port_form;
+mod backup_form;
+mod budget_form;
+mod circuit_breaker_form;
+mod concurrency_form;
+mod confirm_dialog;
+mod cors_form;
+mod dns_form;
+mod endpoints;
+mod env_import_dialog;
+mod export_form;
+mod failover_events;
+mod header_rules_form;
+mod health_check_form;
+mod history;
+mod hybrid_form;
+mod import_form;
+mod listen_form;
+mod lock_screen;
+mod logs;
+mod maintenance_dialog;
+mod master_password_form;
 mod mcp;
+mod mcp_export_form;
+mod mcp_form;
+mod mcp_paste_form;
+mod model_alias_form;
+mod pricing_editor;
+mod prompt_editor;
+mod prompts;
+mod provider_env_form;
 mod provider_form;
 mod providers;
 mod proxy;
+mod rate_limit_form;
+mod replay_dialog;
+mod response_cache_form;
+mod restore_dialog;
 mod settings;
+mod shadow_form;
+mod size_limit_form;
+mod speedtest_form;
+mod switch_back_form;
+mod switch_preview;
+mod tls_form;
+mod unix_socket_form;
+mod upstream_proxy_form;
+mod usage;
+mod usage_export_form;
+mod webhook_form;
+mod weight_form;
 
-pub use mcp::McpView;
-pub use provider_form::{FormMode, ProviderForm};
-pub use providers::ProvidersView;
-pub use proxy::ProxyView;
-pub use settings::SettingsView;
+pub use app_port_form::AppPortForm;
+pub use backup_form::BackupForm;
+pub use budget_form::BudgetForm;
+pub use circuit_breaker_form::CircuitBreakerForm;
+pub use concurrency_form::ConcurrencyForm;
+pub use confirm_dialog::ConfirmDialog;
+pub use cors_form::CorsForm;
+pub use dns_form::DnsForm;
+pub use endpoints::EndpointsView;
+pub use env_import_dialog::EnvImportDialog;
+pub use export_form::ExportForm;
+pub use failover_events::FailoverEventsView;
+pub use header_rules_form::HeaderRulesForm;
+pub use health_check_form::HealthCheckForm;
+pub use history::{HistoryPage, HistoryView};
+pub use hybrid_form::HybridForm;
+pub use import_form::ImportForm;
+pub use listen_form::ListenForm;
+pub use lock_screen::LockScreen;
+pub use logs::LogsView;
+pub use maintenance_dialog::MaintenanceDialog;
+pub use master_password_form::{MasterPasswordChange, MasterPasswordForm};
+pub use mcp::{McpCheck, McpView};
+pub use mcp_export_form::McpExportForm;
+pub use mcp_form::McpForm;
+pub use mcp_paste_form::McpPasteForm;
+pub use model_alias_form::ModelAliasForm;
+pub use pricing_editor::PricingEditor;
+pub use prompt_editor::PromptEditor;
+pub use prompts::PromptsView;
+pub use provider_env_form::ProviderEnvForm;
+pub use provider_form::{is_http_url, FormMode, ProviderForm};
+pub use providers::{Connectivity, ProvidersData, ProvidersView};
+pub use proxy::{load_budgets, ProxyData, ProxyView};
+pub use rate_limit_form::RateLimitForm;
+pub use replay_dialog::{ReplayDialog, ReplayRequest};
+pub use response_cache_form::ResponseCacheForm;
+pub use restore_dialog::RestoreDialog;
+pub use settings::{
+    access_log_label, auto_backup_label, auto_refresh_label, desktop_notifications_label,
+    history_limit_label, idle_lock_label, log_destination_label, SettingsView,
+};
+pub use shadow_form::ShadowForm;
+pub use size_limit_form::SizeLimitForm;
+pub use speedtest_form::SpeedtestForm;
+pub use switch_back_form::SwitchBackForm;
+pub use switch_preview::SwitchPreview;
+pub use tls_form::TlsForm;
+pub use unix_socket_form::UnixSocketForm;
+pub use upstream_proxy_form::UpstreamProxyForm;
+pub use usage::{UsageData, UsageView};
+pub use usage_export_form::UsageExportForm;
+pub use webhook_form::WebhookForm;
+pub use weight_form::WeightForm;
 
 use ratatui::prelude::*;
 
@@ -17,3 +112,12 @@ use super::theme::Theme;
 pub trait View {
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme);
 }
+
+/// 在 `r` 中居中放置宽度为百分比、高度固定的弹窗区域
+fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let popup_width = r.width * percent_x / 100;
+    let x = (r.width.saturating_sub(popup_width)) / 2;
+    let y = (r.height.saturating_sub(height)) / 2;
+
+    Rect::new(r.x + x, r.y + y, popup_width, height)
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider};
+
+/// 模型别名弹窗：以 `请求模型=上游模型` 逗号分隔填写，留空表示清除
+pub struct ModelAliasForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    aliases: TextInput,
+    message: Option<String>,
+}
+
+impl ModelAliasForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            aliases: TextInput::new("Aliases"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let value = provider
+            .meta
+            .as_ref()
+            .map(|meta| format_aliases(&meta.model_aliases))
+            .unwrap_or_default();
+        self.aliases = TextInput::with_value("Aliases", &value);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    /// 返回 true 表示别名已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Backspace => self.aliases.backspace(),
+            KeyCode::Delete => self.aliases.delete(),
+            KeyCode::Left => self.aliases.move_left(),
+            KeyCode::Right => self.aliases.move_right(),
+            KeyCode::Home => self.aliases.home(),
+            KeyCode::End => self.aliases.end(),
+            KeyCode::Char(c) => self.aliases.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let Some(mut provider) = self.provider.clone() else {
+            return false;
+        };
+        let aliases = match parse_aliases(&self.aliases.value) {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                self.message = Some(e);
+                return false;
+            }
+        };
+        provider
+            .meta
+            .get_or_insert_with(Default::default)
+            .model_aliases = aliases;
+
+        match self
+            .state
+            .db
+            .save_provider(self.app_type.as_str(), &provider)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self.provider.as_ref().map_or("", |p| p.name.as_str());
+
+        let area = centered_rect(70, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Model aliases — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.aliases.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new("requested=upstream, comma separated (e.g. claude-sonnet-4=glm-4.6)")
+                .style(theme.inactive),
+            chunks[1],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
+
+fn format_aliases(aliases: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<String> = aliases.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    pairs.sort();
+    pairs.join(", ")
+}
+
+fn parse_aliases(value: &str) -> Result<HashMap<String, String>, String> {
+    let mut aliases = HashMap::new();
+    for pair in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((from, to)) = pair.split_once('=') else {
+            return Err(format!("Expected requested=upstream, got \"{pair}\""));
+        };
+        let (from, to) = (from.trim(), to.trim());
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("Model names cannot be empty in \"{pair}\""));
+        }
+        aliases.insert(from.to_string(), to.to_string());
+    }
+    Ok(aliases)
+}
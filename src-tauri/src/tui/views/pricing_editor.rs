@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppError, AppState, ModelPrice};
+
+const FIELD_COUNT: usize = 6;
+
+/// 正在新增或编辑的一条定价
+struct PriceDraft {
+    fields: [TextInput; FIELD_COUNT],
+    focus: usize,
+}
+
+impl PriceDraft {
+    fn new(price: Option<&ModelPrice>) -> Self {
+        let values: [&str; FIELD_COUNT] = match price {
+            Some(p) => [
+                &p.model_id,
+                &p.display_name,
+                &p.input_cost_per_million,
+                &p.output_cost_per_million,
+                &p.cache_read_cost_per_million,
+                &p.cache_creation_cost_per_million,
+            ],
+            None => ["", "", "0", "0", "0", "0"],
+        };
+        let labels = [
+            "Model ID",
+            "Name",
+            "Input $/M",
+            "Output $/M",
+            "Cache read $/M",
+            "Cache write $/M",
+        ];
+        Self {
+            fields: std::array::from_fn(|i| TextInput::with_value(labels[i], values[i])),
+            // 编辑已有定价时模型 ID 即主键，直接聚焦价格
+            focus: if price.is_some() { 2 } else { 0 },
+        }
+    }
+
+    fn to_price(&self) -> ModelPrice {
+        let [id, name, input, output, cache_read, cache_creation] = &self.fields;
+        let display_name = if name.value.trim().is_empty() {
+            id.value.clone()
+        } else {
+            name.value.clone()
+        };
+        ModelPrice {
+            model_id: id.value.clone(),
+            display_name,
+            input_cost_per_million: input.value.clone(),
+            output_cost_per_million: output.value.clone(),
+            cache_read_cost_per_million: cache_read.value.clone(),
+            cache_creation_cost_per_million: cache_creation.value.clone(),
+        }
+    }
+}
+
+/// 模型定价表弹窗：用于估算代理流量花费，可增删改
+pub struct PricingEditor {
+    state: Arc<AppState>,
+    pub visible: bool,
+    prices: Vec<ModelPrice>,
+    table_state: TableState,
+    draft: Option<PriceDraft>,
+    message: Option<String>,
+}
+
+impl PricingEditor {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            prices: Vec::new(),
+            table_state: TableState::default(),
+            draft: None,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.draft = None;
+        self.message = None;
+        self.table_state = TableState::default();
+        self.reload();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.draft = None;
+    }
+
+    fn reload(&mut self) {
+        match self.state.db.list_model_prices() {
+            Ok(prices) => self.prices = prices,
+            Err(e) => self.message = Some(e.to_string()),
+        }
+
+        let selected = match self.table_state.selected() {
+            _ if self.prices.is_empty() => None,
+            Some(i) => Some(i.min(self.prices.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    fn selected(&self) -> Option<&ModelPrice> {
+        self.table_state.selected().and_then(|i| self.prices.get(i))
+    }
+
+    /// 返回 true 表示定价已变更
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if self.draft.is_some() {
+            return self.handle_draft_key(key);
+        }
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_offset(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select_offset(-1),
+            KeyCode::Char('a') => {
+                self.message = None;
+                self.draft = Some(PriceDraft::new(None));
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(price) = self.selected() {
+                    let draft = PriceDraft::new(Some(price));
+                    self.message = None;
+                    self.draft = Some(draft);
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(id) = self.selected().map(|p| p.model_id.clone()) {
+                    let result = self.state.db.delete_model_price(&id);
+                    return self.finish(result, &format!("Removed {id}"));
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn handle_draft_key(&mut self, key: KeyCode) -> bool {
+        let Some(draft) = self.draft.as_mut() else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Esc => self.draft = None,
+            KeyCode::Tab | KeyCode::Down => draft.focus = (draft.focus + 1) % FIELD_COUNT,
+            KeyCode::BackTab | KeyCode::Up => {
+                draft.focus = (draft.focus + FIELD_COUNT - 1) % FIELD_COUNT;
+            }
+            KeyCode::Enter => {
+                let price = draft.to_price();
+                let result = self.state.db.upsert_model_price(&price);
+                let saved = self.finish(result, &format!("Saved {}", price.model_id.trim()));
+                if saved {
+                    self.draft = None;
+                    if let Some(i) = self
+                        .prices
+                        .iter()
+                        .position(|p| p.model_id == price.model_id.trim())
+                    {
+                        self.table_state.select(Some(i));
+                    }
+                }
+                return saved;
+            }
+            _ => {
+                let input = &mut draft.fields[draft.focus];
+                match key {
+                    KeyCode::Backspace => input.backspace(),
+                    KeyCode::Delete => input.delete(),
+                    KeyCode::Left => input.move_left(),
+                    KeyCode::Right => input.move_right(),
+                    KeyCode::Home => input.home(),
+                    KeyCode::End => input.end(),
+                    KeyCode::Char(c) => input.insert(c),
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+
+    fn finish(&mut self, result: Result<(), AppError>, success: &str) -> bool {
+        match result {
+            Ok(()) => {
+                self.message = Some(success.to_string());
+                self.reload();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        if self.prices.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, self.prices.len() as isize - 1);
+        self.table_state.select(Some(next as usize));
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(80, 22, frame.area());
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title("Model Pricing (USD per 1M tokens)")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let inner = area.inner(Margin::new(1, 1));
+        let form_height = if self.draft.is_some() {
+            FIELD_COUNT as u16
+        } else {
+            0
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(form_height),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        self.render_table(frame, chunks[0], theme);
+
+        if let Some(draft) = &self.draft {
+            let lines: Vec<Line> = draft
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, input)| {
+                    let active = i == draft.focus;
+                    Line::styled(
+                        format!("{:>16}: {}", input.label, input.display_value(active)),
+                        if active { theme.selected } else { theme.normal },
+                    )
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines), chunks[1]);
+        }
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(
+                Paragraph::new(msg.as_str()).style(theme.inactive),
+                chunks[2],
+            );
+        }
+
+        let hints = if self.draft.is_some() {
+            "Tab:Next field  Enter:Save  Esc:Cancel"
+        } else {
+            "j/k:Navigate  a:Add  Enter/e:Edit  d:Delete  q/Esc:Close"
+        };
+        frame.render_widget(Paragraph::new(hints).style(theme.inactive), chunks[3]);
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if self.prices.is_empty() {
+            let empty =
+                Paragraph::new("No model prices. Press a to add one.").style(theme.inactive);
+            frame.render_widget(empty, area);
+            return;
+        }
+
+        let header = Row::new(vec![
+            "Model ID",
+            "Name",
+            "Input",
+            "Output",
+            "Cache read",
+            "Cache write",
+        ])
+        .style(theme.title);
+        let rows: Vec<Row> = self
+            .prices
+            .iter()
+            .map(|price| {
+                Row::new(vec![
+                    Line::from(price.model_id.clone()),
+                    Line::from(price.display_name.clone()),
+                    Line::from(price.input_cost_per_million.clone()).alignment(Alignment::Right),
+                    Line::from(price.output_cost_per_million.clone()).alignment(Alignment::Right),
+                    Line::from(price.cache_read_cost_per_million.clone())
+                        .alignment(Alignment::Right),
+                    Line::from(price.cache_creation_cost_per_million.clone())
+                        .alignment(Alignment::Right),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Min(14),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(11),
+                Constraint::Length(12),
+            ],
+        )
+        .header(header)
+        .highlight_style(theme.selected);
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
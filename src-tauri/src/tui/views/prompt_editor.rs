@@ -0,0 +1,323 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{markdown_lines, TextArea, TextInput};
+use cc_switch_lib::{AppState, AppType, Prompt, PromptService};
+
+/// PageUp / PageDown 每次移动的行数
+const PAGE_LINES: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Name,
+    Content,
+}
+
+/// 提示词编辑弹窗：左侧编辑内容，右侧实时显示 Markdown 渲染预览
+pub struct PromptEditor {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    /// 编辑已有提示词时保留原记录，新建时为 None
+    original: Option<Prompt>,
+    name: TextInput,
+    content: TextArea,
+    focus: Focus,
+    show_preview: bool,
+    /// 编辑区首行，预览区同步滚动
+    scroll: usize,
+    message: Option<String>,
+}
+
+impl PromptEditor {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            original: None,
+            name: TextInput::new("Name"),
+            content: TextArea::new(""),
+            focus: Focus::Name,
+            show_preview: true,
+            scroll: 0,
+            message: None,
+        }
+    }
+
+    pub fn open_add(&mut self, app_type: AppType) {
+        self.open(app_type, None);
+    }
+
+    pub fn open_edit(&mut self, prompt: &Prompt, app_type: AppType) {
+        self.open(app_type, Some(prompt.clone()));
+    }
+
+    fn open(&mut self, app_type: AppType, prompt: Option<Prompt>) {
+        let (name, content) = prompt
+            .as_ref()
+            .map(|p| (p.name.as_str(), p.content.as_str()))
+            .unwrap_or_default();
+        self.name = TextInput::with_value("Name", name);
+        self.content = TextArea::new(content);
+        self.focus = if prompt.is_some() {
+            Focus::Content
+        } else {
+            Focus::Name
+        };
+        self.app_type = app_type;
+        self.original = prompt;
+        self.scroll = 0;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.original = None;
+    }
+
+    /// 保存成功时返回 true
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('s') => return self.save(),
+                KeyCode::Char('p') => self.show_preview = !self.show_preview,
+                _ => {}
+            }
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Name => Focus::Content,
+                    Focus::Content => Focus::Name,
+                };
+            }
+            _ => match self.focus {
+                Focus::Name => self.handle_name_key(key.code),
+                Focus::Content => self.handle_content_key(key.code),
+            },
+        }
+        false
+    }
+
+    /// 粘贴文本插入到当前聚焦的字段
+    pub fn paste(&mut self, text: &str) {
+        match self.focus {
+            Focus::Name => text
+                .chars()
+                .filter(|c| !c.is_control())
+                .for_each(|c| self.name.insert(c)),
+            Focus::Content => self.content.insert_str(text),
+        }
+    }
+
+    fn handle_name_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Enter | KeyCode::Down => self.focus = Focus::Content,
+            KeyCode::Backspace => self.name.backspace(),
+            KeyCode::Delete => self.name.delete(),
+            KeyCode::Left => self.name.move_left(),
+            KeyCode::Right => self.name.move_right(),
+            KeyCode::Home => self.name.home(),
+            KeyCode::End => self.name.end(),
+            KeyCode::Char(c) => self.name.insert(c),
+            _ => {}
+        }
+    }
+
+    fn handle_content_key(&mut self, key: KeyCode) {
+        let content = &mut self.content;
+        match key {
+            KeyCode::Enter => content.newline(),
+            KeyCode::Backspace => content.backspace(),
+            KeyCode::Delete => content.delete(),
+            KeyCode::Left => content.move_left(),
+            KeyCode::Right => content.move_right(),
+            KeyCode::Up => content.move_up(1),
+            KeyCode::Down => content.move_down(1),
+            KeyCode::PageUp => content.move_up(PAGE_LINES),
+            KeyCode::PageDown => content.move_down(PAGE_LINES),
+            KeyCode::Home => content.home(),
+            KeyCode::End => content.end(),
+            KeyCode::Char(c) => content.insert(c),
+            _ => {}
+        }
+    }
+
+    fn save(&mut self) -> bool {
+        match self.do_save() {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e);
+                false
+            }
+        }
+    }
+
+    fn do_save(&self) -> Result<(), String> {
+        let name = self.name.value.trim().to_string();
+        if name.is_empty() {
+            return Err("Name cannot be empty".to_string());
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let content = self.content.text();
+        let prompt = match &self.original {
+            Some(original) => Prompt {
+                name,
+                content,
+                updated_at: Some(now),
+                ..original.clone()
+            },
+            None => Prompt {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                content,
+                description: None,
+                enabled: false,
+                created_at: Some(now),
+                updated_at: Some(now),
+            },
+        };
+
+        let id = prompt.id.clone();
+        PromptService::upsert_prompt(&self.state, self.app_type.clone(), &id, prompt)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(90, frame.area().height.saturating_sub(2), frame.area());
+        frame.render_widget(Clear, area);
+        let title = if self.original.is_some() {
+            "Edit Prompt"
+        } else {
+            "New Prompt"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let name_active = self.focus == Focus::Name;
+        let name_style = if name_active {
+            theme.selected
+        } else {
+            theme.normal
+        };
+        frame.render_widget(
+            Paragraph::new(self.name.display(name_active)).style(name_style),
+            chunks[0],
+        );
+
+        let panes = if self.show_preview {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunks[1])
+        } else {
+            Layout::default()
+                .constraints([Constraint::Percentage(100)])
+                .split(chunks[1])
+        };
+        self.render_content(frame, panes[0], theme);
+        if self.show_preview {
+            let preview = Paragraph::new(markdown_lines(&self.content.text(), theme))
+                .block(Block::default().title("Preview").borders(Borders::ALL))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll as u16, 0));
+            frame.render_widget(preview, panes[1]);
+        }
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        let preview_hint = if self.show_preview {
+            "Hide preview"
+        } else {
+            "Show preview"
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Tab:Switch field  Ctrl+S:Save  Ctrl+P:{preview_hint}  Esc:Cancel"
+            ))
+            .style(theme.inactive),
+            chunks[3],
+        );
+    }
+
+    /// 渲染编辑区，保持光标所在行及列在可见范围内
+    fn render_content(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let focused = self.focus == Focus::Content;
+        let block = Block::default()
+            .title(format!(
+                "Content — line {}/{}",
+                self.content.row + 1,
+                self.content.lines().len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(if focused {
+                theme.selected
+            } else {
+                theme.border
+            });
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let height = inner.height.max(1) as usize;
+        let width = inner.width.max(2) as usize;
+        let row = self.content.row;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+
+        let lines: Vec<Line> = self
+            .content
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .map(|(i, line)| {
+                if !focused || i != row {
+                    return Line::styled(line.clone(), theme.normal);
+                }
+                // 光标行：超出宽度时从光标附近开始显示
+                let col = self.content.col;
+                let skip = (col + 1).saturating_sub(width - 1);
+                let before: String = line.chars().skip(skip).take(col - skip).collect();
+                let after: String = line.chars().skip(col).collect();
+                Line::styled(format!("{before}│{after}"), theme.normal)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
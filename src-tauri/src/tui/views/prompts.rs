@@ -0,0 +1,121 @@
+use indexmap::IndexMap;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+
+use super::{Theme, View};
+use crate::tui::app::NavAction;
+use crate::tui::widgets::{loading_title, spinner_frame};
+use cc_switch_lib::{AppState, AppType, Prompt, PromptService};
+
+pub struct PromptsView {
+    pub loading: bool,
+    prompts: IndexMap<String, Prompt>,
+    table_state: TableState,
+    /// 最近一次渲染区域，用于鼠标点击定位
+    area: Rect,
+}
+
+impl PromptsView {
+    pub fn new() -> Self {
+        Self {
+            loading: false,
+            prompts: IndexMap::new(),
+            table_state: TableState::default(),
+            area: Rect::default(),
+        }
+    }
+
+    pub fn load(state: &AppState, app_type: AppType) -> IndexMap<String, Prompt> {
+        PromptService::get_prompts(state, app_type).unwrap_or_default()
+    }
+
+    pub fn apply(&mut self, prompts: IndexMap<String, Prompt>) {
+        self.prompts = prompts;
+        self.loading = false;
+
+        let selected = match self.table_state.selected() {
+            _ if self.prompts.is_empty() => None,
+            Some(i) => Some(i.min(self.prompts.len() - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    pub fn get_selected(&self) -> Option<Prompt> {
+        let index = self.table_state.selected()?;
+        self.prompts.get_index(index).map(|(_, p)| p.clone())
+    }
+
+    /// 选中鼠标点击位置对应的行（跳过表头）
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) || row == inner.y {
+            return None;
+        }
+        let index = self.table_state.offset() + (row - inner.y - 1) as usize;
+        if index >= self.prompts.len() {
+            return None;
+        }
+        self.table_state.select(Some(index));
+        Some(index)
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.prompts.len()) {
+            self.table_state.select(Some(i));
+        }
+    }
+}
+
+impl View for PromptsView {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        self.area = area;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title("Prompts", self.loading));
+
+        if self.loading && self.prompts.is_empty() {
+            let placeholder = Paragraph::new(format!("{} Loading…", spinner_frame()))
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(placeholder, area);
+            return;
+        }
+
+        let header = Row::new(vec!["", "Name", "Lines", "Description"]).style(theme.title);
+
+        let rows: Vec<Row> = self
+            .prompts
+            .values()
+            .map(|prompt| {
+                let marker = if prompt.enabled { "●" } else { " " };
+                let row = Row::new(vec![
+                    marker.to_string(),
+                    prompt.name.clone(),
+                    prompt.content.lines().count().to_string(),
+                    prompt.description.clone().unwrap_or_default(),
+                ]);
+                if prompt.enabled {
+                    row.style(theme.success)
+                } else {
+                    row
+                }
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(2),
+                Constraint::Percentage(35),
+                Constraint::Length(6),
+                Constraint::Min(0),
+            ],
+        )
+        .header(header)
+        .block(block)
+        .highlight_style(theme.selected);
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
@@ -0,0 +1,183 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::terminal::copy_to_clipboard;
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{mask_secret, AppType, Provider, ProviderService};
+
+const DEFAULT_PATH: &str = "~/cc-switch-provider.env";
+
+/// 供应商环境变量导出弹窗：将凭据生成 `export ...` 片段，复制到剪贴板或写入文件
+pub struct ProviderEnvForm {
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    path: TextInput,
+    to_clipboard: bool,
+    /// 当前聚焦的是否为“复制到剪贴板”选项
+    clipboard_active: bool,
+    message: Option<String>,
+}
+
+impl ProviderEnvForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            path: TextInput::with_value("Path", DEFAULT_PATH),
+            to_clipboard: true,
+            clipboard_active: true,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: Provider, app_type: AppType) {
+        self.message = ProviderService::shell_env_snippet(&provider, &app_type)
+            .err()
+            .map(|e| e.to_string());
+        self.provider = Some(provider);
+        self.app_type = app_type;
+        self.clipboard_active = true;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 导出成功时返回结果提示
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<String> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.clipboard_active = !self.clipboard_active;
+            }
+            KeyCode::Enter => match self.export() {
+                Ok(message) => {
+                    self.close();
+                    return Some(message);
+                }
+                Err(e) => self.message = Some(e),
+            },
+            KeyCode::Char(' ') if self.clipboard_active => {
+                self.to_clipboard = !self.to_clipboard;
+            }
+            _ if self.clipboard_active => {}
+            KeyCode::Backspace => self.path.backspace(),
+            KeyCode::Delete => self.path.delete(),
+            KeyCode::Left => self.path.move_left(),
+            KeyCode::Right => self.path.move_right(),
+            KeyCode::Home => self.path.home(),
+            KeyCode::End => self.path.end(),
+            KeyCode::Char(c) => self.path.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn export(&self) -> Result<String, String> {
+        let provider = self.provider.as_ref().ok_or("No provider selected")?;
+
+        if self.to_clipboard {
+            let snippet = ProviderService::shell_env_snippet(provider, &self.app_type)
+                .map_err(|e| e.to_string())?;
+            copy_to_clipboard(&snippet).map_err(|e| format!("Failed to copy: {e}"))?;
+            return Ok(format!("Copied {} env exports to clipboard", provider.name));
+        }
+
+        let path = self.path.value.trim();
+        if path.is_empty() {
+            return Err("Path cannot be empty".to_string());
+        }
+        let path = expand_home(path);
+        ProviderService::write_shell_env_snippet(provider, &self.app_type, &path)
+            .map_err(|e| e.to_string())?;
+        Ok(format!(
+            "Wrote {} env exports to {}",
+            provider.name,
+            path.display()
+        ))
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let Some(provider) = &self.provider else {
+            return;
+        };
+
+        let area = centered_rect(72, 12, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Export {} as shell env", provider.name))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(2),
+                Constraint::Length(1),
+            ])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |active: bool| {
+            if active {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} Copy to clipboard instead of file",
+                if self.to_clipboard { "[x]" } else { "[ ]" }
+            ))
+            .style(style(self.clipboard_active)),
+            chunks[0],
+        );
+
+        let path_text = self.path.display(!self.clipboard_active);
+        let path_style = if self.to_clipboard {
+            theme.inactive
+        } else {
+            style(!self.clipboard_active)
+        };
+        frame.render_widget(Paragraph::new(path_text).style(path_style), chunks[1]);
+
+        // 预览中隐去密钥，避免在屏幕上直接显示
+        let preview: Vec<Line> = ProviderService::env_vars(provider, &self.app_type)
+            .into_iter()
+            .map(|(key, value)| {
+                let shown = if key.contains("KEY") || key.contains("TOKEN") {
+                    mask_secret(&value)
+                } else {
+                    value
+                };
+                Line::styled(format!("  {key}={shown}"), theme.inactive)
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(preview).wrap(Wrap { trim: false }),
+            chunks[3],
+        );
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Switch field  Space:Toggle  Enter:Export  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[4],
+        );
+    }
+}
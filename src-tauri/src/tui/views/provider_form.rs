@@ -5,12 +5,19 @@ use crossterm::event::KeyCode;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 
-use super::Theme;
+use super::providers::{provider_glyph, provider_glyph_style};
+use super::{centered_rect, Theme};
 use crate::tui::widgets::TextInput;
 use cc_switch_lib::{AppState, AppType, Provider, ProviderMeta, ProviderService};
 
 const BASE_URL_LABEL: &str = "Base URLs (comma-separated)";
 
+/// 图标颜色可用 ←/→ 在这些预设之间切换，也可手动输入
+const ICON_COLORS: [&str; 9] = [
+    "#D97757", "#10A37F", "#4285F4", "#8B5CF6", "#EC4899", "#F59E0B", "#EF4444", "#14B8A6",
+    "#6B7280",
+];
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum FormMode {
     Add,
@@ -22,6 +29,9 @@ enum FormField {
     Name,
     ApiKey,
     BaseUrl,
+    Website,
+    Icon,
+    IconColor,
 }
 
 impl FormField {
@@ -29,15 +39,21 @@ impl FormField {
         match self {
             Self::Name => Self::ApiKey,
             Self::ApiKey => Self::BaseUrl,
-            Self::BaseUrl => Self::Name,
+            Self::BaseUrl => Self::Website,
+            Self::Website => Self::Icon,
+            Self::Icon => Self::IconColor,
+            Self::IconColor => Self::Name,
         }
     }
 
     fn prev(&self) -> Self {
         match self {
-            Self::Name => Self::BaseUrl,
+            Self::Name => Self::IconColor,
             Self::ApiKey => Self::Name,
             Self::BaseUrl => Self::ApiKey,
+            Self::Website => Self::BaseUrl,
+            Self::Icon => Self::Website,
+            Self::IconColor => Self::Icon,
         }
     }
 
@@ -46,6 +62,9 @@ impl FormField {
             Self::Name => "Name",
             Self::ApiKey => "API Key",
             Self::BaseUrl => BASE_URL_LABEL,
+            Self::Website => "Website",
+            Self::Icon => "Icon",
+            Self::IconColor => "Icon color",
         }
     }
 }
@@ -59,6 +78,11 @@ pub struct ProviderForm {
     name: TextInput,
     api_key: TextInput,
     base_url: TextInput,
+    website: TextInput,
+    icon: TextInput,
+    icon_color: TextInput,
+    /// 编辑时的原记录，保存时保留表单未涉及的字段
+    original: Option<Provider>,
     original_meta: Option<ProviderMeta>,
     pub message: Option<String>,
     // 编辑弹窗状态
@@ -77,6 +101,10 @@ impl ProviderForm {
             name: TextInput::new("Name"),
             api_key: TextInput::new("API Key"),
             base_url: TextInput::new(BASE_URL_LABEL),
+            website: TextInput::new("Website"),
+            icon: TextInput::new("Icon"),
+            icon_color: TextInput::new("Icon color"),
+            original: None,
             original_meta: None,
             message: None,
             popup_editing: false,
@@ -92,6 +120,10 @@ impl ProviderForm {
         self.name.clear();
         self.api_key.clear();
         self.base_url.clear();
+        self.website.clear();
+        self.icon.clear();
+        self.icon_color.clear();
+        self.original = None;
         self.original_meta = None;
         self.message = None;
 
@@ -110,9 +142,19 @@ impl ProviderForm {
         self.edit_id = Some(provider.id.clone());
         self.active_field = FormField::Name;
         self.message = None;
+        self.original = Some(provider.clone());
         self.original_meta = provider.meta.clone();
 
         self.name = TextInput::with_value("Name", &provider.name);
+        self.website = TextInput::with_value(
+            "Website",
+            provider.website_url.as_deref().unwrap_or_default(),
+        );
+        self.icon = TextInput::with_value("Icon", provider.icon.as_deref().unwrap_or_default());
+        self.icon_color = TextInput::with_value(
+            "Icon color",
+            provider.icon_color.as_deref().unwrap_or_default(),
+        );
 
         // 调用后端 Service 提取 API Key 和 Base URL
         let (api_key, base_url) = ProviderService::extract_credentials_lenient(provider, &app_type);
@@ -148,9 +190,25 @@ impl ProviderForm {
             FormField::Name => &mut self.name,
             FormField::ApiKey => &mut self.api_key,
             FormField::BaseUrl => &mut self.base_url,
+            FormField::Website => &mut self.website,
+            FormField::Icon => &mut self.icon,
+            FormField::IconColor => &mut self.icon_color,
         }
     }
 
+    /// 在预设颜色之间循环切换
+    fn cycle_icon_color(&mut self, forward: bool) {
+        let current = self.icon_color.value.trim().to_uppercase();
+        let len = ICON_COLORS.len();
+        let next = match ICON_COLORS.iter().position(|c| *c == current) {
+            Some(i) if forward => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+            None if forward => 0,
+            None => len - 1,
+        };
+        self.icon_color = TextInput::with_value("Icon color", ICON_COLORS[next]);
+    }
+
     fn parse_base_urls(&self) -> Vec<String> {
         let mut urls = Vec::new();
         for part in self
@@ -230,6 +288,16 @@ impl ProviderForm {
                 false
             }
             KeyCode::Enter => self.submit(app_type),
+            KeyCode::Left | KeyCode::Char('h') if self.active_field == FormField::IconColor => {
+                self.cycle_icon_color(false);
+                false
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ')
+                if self.active_field == FormField::IconColor =>
+            {
+                self.cycle_icon_color(true);
+                false
+            }
             KeyCode::Char('e') | KeyCode::F(2) => {
                 self.open_popup();
                 false
@@ -305,6 +373,16 @@ impl ProviderForm {
             self.message = Some("Base URL is required".to_string());
             return false;
         }
+        let website = self.website.value.trim();
+        if !website.is_empty() && !is_http_url(website) {
+            self.message = Some("Website must start with http:// or https://".to_string());
+            return false;
+        }
+        let icon_color = self.icon_color.value.trim();
+        if !icon_color.is_empty() && icon_color.parse::<Color>().is_err() {
+            self.message = Some("Icon color must be a hex color like #D97757".to_string());
+            return false;
+        }
 
         let result = match self.mode {
             FormMode::Add => self.do_add(app_type, &base_urls),
@@ -331,15 +409,17 @@ impl ProviderForm {
             id: provider_id.clone(),
             name: self.name.value.trim().to_string(),
             settings_config: config,
-            website_url: None,
+            website_url: non_empty(&self.website.value),
             category: None,
             created_at: Some(chrono::Utc::now().timestamp()),
             sort_index: None,
             notes: None,
             meta: self.original_meta.clone(),
-            icon: None,
-            icon_color: None,
+            icon: non_empty(&self.icon.value),
+            icon_color: non_empty(&self.icon_color.value),
             in_failover_queue: false,
+            last_used_at: None,
+            weight: None,
         };
 
         ProviderService::add(&self.state, app_type.clone(), provider).map_err(|e| e.to_string())?;
@@ -351,19 +431,32 @@ impl ProviderForm {
         let id = self.edit_id.as_ref().ok_or("No provider ID")?;
         let primary_base_url = base_urls.first().map(|s| s.as_str()).unwrap_or_default();
         let config = self.build_config(app_type.clone(), primary_base_url);
-        let provider = Provider {
-            id: id.clone(),
-            name: self.name.value.trim().to_string(),
-            settings_config: config,
-            website_url: None,
-            category: None,
-            created_at: None,
-            sort_index: None,
-            notes: None,
-            meta: self.original_meta.clone(),
-            icon: None,
-            icon_color: None,
-            in_failover_queue: false,
+        let provider = match &self.original {
+            Some(original) => Provider {
+                name: self.name.value.trim().to_string(),
+                settings_config: config,
+                website_url: non_empty(&self.website.value),
+                meta: self.original_meta.clone(),
+                icon: non_empty(&self.icon.value),
+                icon_color: non_empty(&self.icon_color.value),
+                ..original.clone()
+            },
+            None => Provider {
+                id: id.clone(),
+                name: self.name.value.trim().to_string(),
+                settings_config: config,
+                website_url: non_empty(&self.website.value),
+                category: None,
+                created_at: None,
+                sort_index: None,
+                notes: None,
+                meta: self.original_meta.clone(),
+                icon: non_empty(&self.icon.value),
+                icon_color: non_empty(&self.icon_color.value),
+                in_failover_queue: false,
+                last_used_at: None,
+                weight: None,
+            },
         };
 
         ProviderService::update(&self.state, app_type.clone(), provider)
@@ -432,7 +525,7 @@ requires_openai_auth = true
             return;
         }
 
-        let area = centered_rect(60, 14, frame.area());
+        let area = centered_rect(60, 20, frame.area());
         frame.render_widget(Clear, area);
 
         let title = match self.mode {
@@ -458,29 +551,54 @@ requires_openai_auth = true
     fn render_fields(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(2),
-                Constraint::Length(2),
-                Constraint::Length(2),
-                Constraint::Length(2),
-                Constraint::Length(2),
-            ])
+            .constraints([Constraint::Length(2); 8])
             .split(area);
 
         self.render_input(frame, chunks[0], &self.name, FormField::Name, theme);
         self.render_input(frame, chunks[1], &self.api_key, FormField::ApiKey, theme);
         self.render_input(frame, chunks[2], &self.base_url, FormField::BaseUrl, theme);
+        self.render_input(frame, chunks[3], &self.website, FormField::Website, theme);
+        self.render_icon(frame, chunks[4], theme);
+        self.render_input(
+            frame,
+            chunks[5],
+            &self.icon_color,
+            FormField::IconColor,
+            theme,
+        );
 
         // Message
         if let Some(msg) = &self.message {
             let p = Paragraph::new(msg.as_str()).style(theme.error);
-            frame.render_widget(p, chunks[3]);
+            frame.render_widget(p, chunks[6]);
         }
 
         // Hints
-        let hints =
-            Paragraph::new("j/k:Navigate  e:Edit  Enter:Save  q/Esc:Cancel").style(theme.inactive);
-        frame.render_widget(hints, chunks[4]);
+        let hints = if self.active_field == FormField::IconColor {
+            "j/k:Navigate  h/l:Preset color  e:Edit  Enter:Save  q/Esc:Cancel"
+        } else {
+            "j/k:Navigate  e:Edit  Enter:Save  q/Esc:Cancel"
+        };
+        frame.render_widget(Paragraph::new(hints).style(theme.inactive), chunks[7]);
+    }
+
+    /// 图标字段后附带按当前图标与颜色渲染的预览
+    fn render_icon(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let style = if self.active_field == FormField::Icon {
+            theme.selected
+        } else {
+            theme.normal
+        };
+        let glyph = provider_glyph(&self.name.value, Some(&self.icon.value));
+        let line = Line::from(vec![
+            Span::styled(format!("{}: {}", self.icon.label, self.icon.value), style),
+            Span::styled("  preview: ", theme.inactive),
+            Span::styled(
+                glyph,
+                provider_glyph_style(Some(&self.icon_color.value), theme),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
     }
 
     fn render_input(
@@ -500,7 +618,7 @@ requires_openai_auth = true
 
         // API Key: 激活时显示完整内容以便编辑，非激活时脱敏
         let display_value = if field == FormField::ApiKey && !input.value.is_empty() && !is_active {
-            mask_api_key(&input.value)
+            cc_switch_lib::mask_secret(&input.value)
         } else {
             input.value.clone()
         };
@@ -528,10 +646,7 @@ requires_openai_auth = true
             .split(inner);
 
         // 输入内容（带光标）
-        let value = &self.popup_input.value;
-        let cursor = self.popup_input.cursor;
-        let display = format!("{}│{}", &value[..cursor], &value[cursor..]);
-        let p = Paragraph::new(display).style(theme.selected);
+        let p = Paragraph::new(self.popup_input.display_value(true)).style(theme.selected);
         frame.render_widget(p, chunks[0]);
 
         // 提示
@@ -540,22 +655,20 @@ requires_openai_auth = true
     }
 }
 
-fn normalize_url(value: &str) -> String {
-    value.trim().trim_end_matches('/').to_string()
+/// 仅接受 http(s) 地址，避免把任意字符串交给系统打开
+pub fn is_http_url(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+        .is_some_and(|rest| !rest.is_empty())
 }
 
-fn mask_api_key(key: &str) -> String {
-    if key.len() <= 8 {
-        "*".repeat(key.len())
-    } else {
-        format!("{}...{}", &key[..4], &key[key.len() - 4..])
-    }
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
 }
 
-fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
-    let popup_width = r.width * percent_x / 100;
-    let x = (r.width.saturating_sub(popup_width)) / 2;
-    let y = (r.height.saturating_sub(height)) / 2;
-
-    Rect::new(r.x + x, r.y + y, popup_width, height)
+fn normalize_url(value: &str) -> String {
+    value.trim().trim_end_matches('/').to_string()
 }
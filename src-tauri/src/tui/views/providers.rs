@@ -1,80 +1,377 @@
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 
-use crossterm::event::KeyCode;
 use indexmap::IndexMap;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 
 use super::{Theme, View};
-use cc_switch_lib::{AppState, AppType, Provider, ProviderService};
+use crate::tui::app::NavAction;
+use crate::tui::keymap::Action;
+use crate::tui::widgets::{loading_title, spinner_frame};
+use cc_switch_lib::{
+    AppError, AppState, AppType, Provider, ProviderEndpoint, ProviderService, ProxyStatus,
+    RateLimitStats, SpeedtestOptions, SpeedtestService,
+};
+
+/// 数据库 settings 表中保存供应商列表是否按最近使用排序的键
+const SORT_BY_RECENCY_SETTING_KEY: &str = "tui_provider_sort_recency";
+
+/// 延迟低于该值显示为绿色
+const LATENCY_GOOD_MS: u64 = 300;
+/// 延迟低于该值显示为黄色，否则为红色
+const LATENCY_SLOW_MS: u64 = 1000;
+
+/// 健康分不低于该值显示为绿色
+const HEALTH_GOOD: u8 = 80;
+/// 健康分不低于该值显示为黄色，否则为红色
+const HEALTH_FAIR: u8 = 50;
+
+/// 供应商图标字符：图标为单个字符（含 emoji）时直接使用，否则取名称首字母
+pub fn provider_glyph(name: &str, icon: Option<&str>) -> String {
+    let icon = icon.map(str::trim).unwrap_or_default();
+    if !icon.is_empty() && icon.chars().count() <= 2 && Span::raw(icon).width() <= 2 {
+        return icon.to_string();
+    }
+    name.chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// 限流摘要，如 `rate-limited ×3 (retry 30s)`；Retry-After 为 HTTP 日期时原样显示
+pub fn rate_limit_label(stats: &RateLimitStats) -> String {
+    match stats.last_retry_after.as_deref() {
+        Some(value) if value.parse::<u64>().is_ok() => {
+            format!("rate-limited ×{} (retry {value}s)", stats.count)
+        }
+        Some(value) => format!("rate-limited ×{} (retry {value})", stats.count),
+        None => format!("rate-limited ×{}", stats.count),
+    }
+}
+
+/// 健康分徽标颜色
+fn health_score_style(score: u8, theme: &Theme) -> Style {
+    if score >= HEALTH_GOOD {
+        theme.success
+    } else if score >= HEALTH_FAIR {
+        theme.warning
+    } else {
+        theme.error
+    }
+}
+
+/// 图标颜色样式，未设置或无法解析时使用弱化样式
+pub fn provider_glyph_style(icon_color: Option<&str>, theme: &Theme) -> Style {
+    icon_color
+        .and_then(|value| theme.custom_color(value))
+        .map(|color| Style::default().fg(color).add_modifier(Modifier::BOLD))
+        .unwrap_or(theme.inactive)
+}
+
+/// 后台任务加载的供应商列表数据
+pub struct ProvidersData {
+    app_type: AppType,
+    providers: IndexMap<String, Provider>,
+    current_id: Option<String>,
+    latencies: HashMap<String, LatencySummary>,
+}
+
+/// 供应商端点的延迟摘要（取主端点或延迟最低的健康端点）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LatencySummary {
+    Healthy(u64),
+    Down,
+}
+
+impl LatencySummary {
+    /// 端点按主端点优先、延迟升序排列；尚未测试过的端点不计入
+    fn from_endpoints(endpoints: &[ProviderEndpoint]) -> Option<Self> {
+        let mut tested = endpoints
+            .iter()
+            .filter(|e| e.last_tested_at.is_some())
+            .peekable();
+        tested.peek()?;
+        Some(
+            tested
+                .find(|e| e.is_healthy)
+                .and_then(|e| e.latency_ms)
+                .map_or(Self::Down, Self::Healthy),
+        )
+    }
+}
+
+/// 供应商连通性测试状态
+pub enum Connectivity {
+    Testing,
+    Reachable { latency_ms: u128 },
+    Failed(String),
+}
 
 pub struct ProvidersView {
     state: Arc<AppState>,
+    pub loading: bool,
+    app_type: AppType,
     providers: IndexMap<String, Provider>,
     current_id: Option<String>,
+    /// 按供应商 ID 记录的连通性测试结果
+    connectivity: HashMap<String, Connectivity>,
+    latencies: HashMap<String, LatencySummary>,
+    /// 本次代理运行中各供应商的限流统计，来自代理状态
+    rate_limits: HashMap<String, RateLimitStats>,
+    /// 按 provider_id 记录的综合健康分，来自代理状态
+    health_scores: HashMap<String, u8>,
     list_state: ListState,
+    /// 按最近使用时间倒序排列，否则沿用数据库中的排序
+    sort_by_recency: bool,
+    /// 最近一次渲染区域，用于鼠标点击定位
+    area: Rect,
 }
 
 impl ProvidersView {
     pub fn new(state: Arc<AppState>) -> Self {
+        let sort_by_recency = state
+            .db
+            .get_setting(SORT_BY_RECENCY_SETTING_KEY)
+            .ok()
+            .flatten()
+            .is_some_and(|value| value == "true");
         Self {
             state,
+            loading: false,
+            app_type: AppType::Claude,
             providers: IndexMap::new(),
             current_id: None,
+            connectivity: HashMap::new(),
+            latencies: HashMap::new(),
+            rate_limits: HashMap::new(),
+            health_scores: HashMap::new(),
             list_state: ListState::default(),
+            sort_by_recency,
+            area: Rect::default(),
         }
     }
 
-    pub async fn refresh(&mut self, app_type: AppType) {
-        self.providers = ProviderService::list(&self.state, app_type.clone()).unwrap_or_default();
-        self.current_id = ProviderService::current(&self.state, app_type).ok();
+    pub fn load(state: &AppState, app_type: AppType) -> ProvidersData {
+        let providers = ProviderService::list(state, app_type.clone()).unwrap_or_default();
+        let latencies = providers
+            .keys()
+            .filter_map(|id| {
+                let endpoints = state
+                    .db
+                    .get_provider_endpoints_with_health(app_type.as_str(), id)
+                    .ok()?;
+                Some((id.clone(), LatencySummary::from_endpoints(&endpoints)?))
+            })
+            .collect();
+        ProvidersData {
+            current_id: ProviderService::current(state, app_type.clone()).ok(),
+            providers,
+            latencies,
+            app_type,
+        }
+    }
 
-        if !self.providers.is_empty() && self.list_state.selected().is_none() {
-            self.list_state.select(Some(0));
+    pub fn apply(&mut self, data: ProvidersData) {
+        if data.app_type != self.app_type {
+            self.connectivity.clear();
+            self.app_type = data.app_type;
+        }
+        self.providers = data.providers;
+        self.current_id = data.current_id;
+        self.latencies = data.latencies;
+        self.loading = false;
+        if self.sort_by_recency {
+            self.sort_providers();
         }
+
+        let selected = match self.list_state.selected() {
+            _ if self.providers.is_empty() => None,
+            Some(i) => Some(i.min(self.providers.len() - 1)),
+            None => Some(0),
+        };
+        self.list_state.select(selected);
+    }
+
+    /// 从代理状态中取出当前应用各供应商的限流统计与健康分
+    pub fn set_proxy_stats(&mut self, status: &ProxyStatus) {
+        let stats: Vec<_> = status
+            .provider_stats
+            .iter()
+            .filter(|s| s.app_type == self.app_type.as_str())
+            .collect();
+        self.rate_limits = stats
+            .iter()
+            .filter(|s| s.rate_limits.count > 0)
+            .map(|s| (s.provider_id.clone(), s.rate_limits.clone()))
+            .collect();
+        self.health_scores = stats
+            .iter()
+            .filter_map(|s| s.health_score.map(|score| (s.provider_id.clone(), score)))
+            .collect();
     }
 
-    pub async fn handle_key(&mut self, key: KeyCode, app_type: AppType) {
-        match key {
-            KeyCode::Up => self.select_prev(),
-            KeyCode::Down => self.select_next(),
-            KeyCode::Enter => self.switch_provider(app_type).await,
-            _ => {}
+    pub async fn handle_action(&mut self, action: Action, app_type: AppType) {
+        if action == Action::Select {
+            self.switch_provider(app_type).await;
         }
     }
 
-    fn select_prev(&mut self) {
-        if self.providers.is_empty() {
-            return;
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.list_state.selected(), self.providers.len()) {
+            self.list_state.select(Some(i));
         }
-        let i = match self.list_state.selected() {
-            Some(i) => i.saturating_sub(1),
-            None => 0,
-        };
-        self.list_state.select(Some(i));
     }
 
-    fn select_next(&mut self) {
-        if self.providers.is_empty() {
-            return;
+    /// 选中鼠标点击位置对应的行
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) {
+            return None;
         }
-        let i = match self.list_state.selected() {
-            Some(i) => (i + 1).min(self.providers.len() - 1),
-            None => 0,
-        };
-        self.list_state.select(Some(i));
+        let index = self.list_state.offset() + (row - inner.y) as usize;
+        if index >= self.providers.len() {
+            return None;
+        }
+        self.list_state.select(Some(index));
+        Some(index)
     }
 
-    async fn switch_provider(&mut self, app_type: AppType) {
+    pub async fn switch_provider(&mut self, app_type: AppType) {
         if let Some(i) = self.list_state.selected() {
             if let Some((id, _)) = self.providers.get_index(i) {
-                if ProviderService::switch(&self.state, app_type.clone(), id).is_ok() {
-                    self.current_id = Some(id.clone());
-                }
+                let id = id.clone();
+                self.switch_to(app_type, &id);
             }
         }
     }
 
+    /// 切换到指定供应商，成功时更新当前标记
+    pub fn switch_to(&mut self, app_type: AppType, id: &str) -> bool {
+        let switched = ProviderService::switch(&self.state, app_type, id).is_ok();
+        if switched {
+            self.current_id = Some(id.to_string());
+            if let Some(provider) = self.providers.get_mut(id) {
+                provider.last_used_at = Some(chrono::Utc::now().timestamp());
+            }
+            if self.sort_by_recency {
+                self.sort_providers();
+            }
+        }
+        switched
+    }
+
+    /// 标记选中供应商为测试中，返回其 ID 及执行测试的 future
+    pub fn start_connectivity_test(
+        &mut self,
+    ) -> Option<(String, impl Future<Output = Connectivity> + Send + 'static)> {
+        let provider = self.get_selected()?;
+        let app_type = self.app_type.clone();
+        let (api_key, base_url) =
+            ProviderService::extract_credentials_lenient(&provider, &app_type);
+        let provider_id = provider.id.clone();
+        self.connectivity
+            .insert(provider_id.clone(), Connectivity::Testing);
+
+        let probe = provider.meta.as_ref().and_then(|m| m.speedtest.clone());
+        let test = async move {
+            let result = match probe {
+                Some(probe) => SpeedtestService::probe_endpoints(
+                    &app_type,
+                    &provider,
+                    vec![base_url],
+                    &probe,
+                    &SpeedtestOptions::default(),
+                )
+                .await
+                .map(|mut results| results.remove(0)),
+                None => SpeedtestService::test_provider(&app_type, &base_url, &api_key, None).await,
+            };
+            match result {
+                Ok(result) => match (result.error, result.latency) {
+                    (Some(error), _) => Connectivity::Failed(error),
+                    (None, Some(latency_ms)) => Connectivity::Reachable { latency_ms },
+                    (None, None) => Connectivity::Failed("No response".to_string()),
+                },
+                Err(e) => Connectivity::Failed(e.to_string()),
+            }
+        };
+        Some((provider_id, test))
+    }
+
+    /// 记录测试结果；应用已切换时丢弃
+    pub fn set_connectivity(&mut self, app_type: &AppType, id: String, result: Connectivity) {
+        if *app_type == self.app_type {
+            self.connectivity.insert(id, result);
+        }
+    }
+
+    pub fn app_type(&self) -> AppType {
+        self.app_type.clone()
+    }
+
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers.values().map(|p| p.name.clone()).collect()
+    }
+
+    /// 切换选中供应商是否加入故障转移队列，返回供应商名称与新状态
+    pub fn toggle_failover(&mut self) -> Result<Option<(String, bool)>, String> {
+        let Some(i) = self.list_state.selected() else {
+            return Ok(None);
+        };
+        let Some((id, provider)) = self.providers.get_index_mut(i) else {
+            return Ok(None);
+        };
+        let app_type = self.app_type.as_str();
+        let enabled = !provider.in_failover_queue;
+        let result = if enabled {
+            self.state.db.add_to_failover_queue(app_type, id)
+        } else {
+            self.state.db.remove_from_failover_queue(app_type, id)
+        };
+        result.map_err(|e| e.to_string())?;
+        provider.in_failover_queue = enabled;
+        Ok(Some((provider.name.clone(), enabled)))
+    }
+
+    pub fn sort_by_recency(&self) -> bool {
+        self.sort_by_recency
+    }
+
+    /// 切换是否按最近使用排序并持久化，保持当前选中的供应商不变
+    pub fn toggle_sort_by_recency(&mut self) -> Result<bool, AppError> {
+        let next = !self.sort_by_recency;
+        self.state
+            .db
+            .set_setting(SORT_BY_RECENCY_SETTING_KEY, &next.to_string())?;
+        self.sort_by_recency = next;
+        self.sort_providers();
+        Ok(next)
+    }
+
+    fn sort_providers(&mut self) {
+        let selected_id = self
+            .list_state
+            .selected()
+            .and_then(|i| self.providers.get_index(i))
+            .map(|(id, _)| id.clone());
+        if self.sort_by_recency {
+            // 从未使用过的排在最后，其余按数据库顺序
+            self.providers.sort_by(|_, a, _, b| {
+                b.last_used_at
+                    .cmp(&a.last_used_at)
+                    .then_with(|| default_order(a).cmp(&default_order(b)))
+            });
+        } else {
+            self.providers
+                .sort_by(|_, a, _, b| default_order(a).cmp(&default_order(b)));
+        }
+        if let Some(i) = selected_id.and_then(|id| self.providers.get_index_of(&id)) {
+            self.list_state.select(Some(i));
+        }
+    }
+
     pub fn get_selected(&self) -> Option<Provider> {
         let i = self.list_state.selected()?;
         let (_, provider) = self.providers.get_index(i)?;
@@ -84,27 +381,229 @@ impl ProvidersView {
 
 impl View for ProvidersView {
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        self.area = area;
+        let title = if self.sort_by_recency {
+            "Providers (recent first)"
+        } else {
+            "Providers"
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title(title, self.loading));
+
+        if self.loading && self.providers.is_empty() {
+            let placeholder = Paragraph::new(format!("{} Loading…", spinner_frame()))
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(placeholder, area);
+            return;
+        }
+
+        let name_width = self
+            .providers
+            .values()
+            .map(|p| Span::raw(p.name.as_str()).width())
+            .max()
+            .unwrap_or(0);
+
+        let now = chrono::Utc::now().timestamp();
         let items: Vec<ListItem> = self
             .providers
             .iter()
             .map(|(id, provider)| {
                 let is_current = self.current_id.as_ref() == Some(id);
                 let marker = if is_current { "[*]" } else { "   " };
-                let text = format!("{} {}", marker, provider.name);
+                let name = Span::raw(provider.name.as_str());
+                let padding = " ".repeat(name_width - name.width());
+                let text = format!(" {}{}", provider.name, padding);
                 let style = if is_current {
                     theme.highlight
                 } else {
                     theme.normal
                 };
-                ListItem::new(text).style(style)
+                let glyph = provider_glyph(&provider.name, provider.icon.as_deref());
+                let glyph_padding = " ".repeat(2 - Span::raw(glyph.as_str()).width().min(2));
+                let glyph = Span::styled(
+                    format!("{glyph}{glyph_padding}"),
+                    provider_glyph_style(provider.icon_color.as_deref(), theme),
+                );
+                let latency = match self.latencies.get(id) {
+                    Some(LatencySummary::Healthy(ms)) => {
+                        let style = if *ms < LATENCY_GOOD_MS {
+                            theme.success
+                        } else if *ms < LATENCY_SLOW_MS {
+                            theme.warning
+                        } else {
+                            theme.error
+                        };
+                        Span::styled(format!("  {ms:>5}ms"), style)
+                    }
+                    Some(LatencySummary::Down) => Span::styled("     down", theme.error),
+                    None => Span::styled("        -", theme.inactive),
+                };
+                let health = match self.health_scores.get(id) {
+                    Some(score) => {
+                        Span::styled(format!("  ♥{score:>3}"), health_score_style(*score, theme))
+                    }
+                    None => Span::styled("     -", theme.inactive),
+                };
+                let last_used = Span::styled(
+                    format!("  {:>14}", last_used_label(provider.last_used_at, now)),
+                    theme.inactive,
+                );
+                let failover = if provider.in_failover_queue {
+                    Span::styled("[F] ", theme.warning)
+                } else {
+                    Span::raw("    ")
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{marker} "), style),
+                    failover,
+                    glyph,
+                    Span::styled(text, style),
+                    latency,
+                    health,
+                    last_used,
+                ];
+                if let (true, Some(weight)) = (provider.in_failover_queue, provider.weight) {
+                    spans.push(Span::styled(format!("  weight {weight}"), theme.inactive));
+                }
+                if let Some(stats) = self.rate_limits.get(id) {
+                    spans.push(Span::styled(
+                        format!("  {}", rate_limit_label(stats)),
+                        theme.warning,
+                    ));
+                }
+                match self.connectivity.get(id) {
+                    Some(Connectivity::Testing) => {
+                        spans.push(Span::styled(
+                            format!("  {} testing", spinner_frame()),
+                            theme.inactive,
+                        ));
+                    }
+                    Some(Connectivity::Reachable { latency_ms }) => {
+                        spans.push(Span::styled(format!("  ✓ {latency_ms}ms"), theme.success));
+                    }
+                    Some(Connectivity::Failed(error)) => {
+                        spans.push(Span::styled(format!("  ✗ {error}"), theme.error));
+                    }
+                    None => {}
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
         let list = List::new(items)
-            .block(Block::default().borders(Borders::ALL).title("Providers"))
+            .block(block)
             .highlight_style(theme.selected)
             .highlight_symbol("> ");
 
         frame.render_stateful_widget(list, area, &mut self.list_state);
     }
 }
+
+/// 与数据库 `get_all_providers` 相同的默认排序键
+fn default_order(provider: &Provider) -> (usize, Option<i64>, &str) {
+    (
+        provider.sort_index.unwrap_or(999999),
+        provider.created_at,
+        provider.id.as_str(),
+    )
+}
+
+/// 将最近使用时间渲染为 "last used 2d ago" 形式
+fn last_used_label(last_used_at: Option<i64>, now: i64) -> String {
+    let Some(ts) = last_used_at else {
+        return "never used".to_string();
+    };
+    let secs = (now - ts).max(0);
+    let ago = match secs {
+        0..=59 => return "used just now".to_string(),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        86400..=2591999 => format!("{}d", secs / 86400),
+        _ => format!("{}mo", secs / 2592000),
+    };
+    format!("last used {ago} ago")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_label_formats_retry_after() {
+        let mut stats = RateLimitStats::default();
+        stats.record(None, 0);
+        assert_eq!(rate_limit_label(&stats), "rate-limited ×1");
+        stats.record(Some("30".to_string()), 0);
+        assert_eq!(rate_limit_label(&stats), "rate-limited ×2 (retry 30s)");
+    }
+
+    fn endpoint(latency_ms: Option<u64>, is_healthy: bool, tested: bool) -> ProviderEndpoint {
+        ProviderEndpoint {
+            id: 0,
+            provider_id: "p".to_string(),
+            app_type: "claude".to_string(),
+            url: "https://example.com".to_string(),
+            latency_ms,
+            last_tested_at: tested.then_some(1),
+            is_healthy,
+            consecutive_failures: 0,
+            is_primary: false,
+            is_pinned: false,
+            weight: None,
+            latency_p50_ms: None,
+            latency_p95_ms: None,
+            jitter_ms: None,
+            recent_flaps: 0,
+            stability_score: None,
+            region: None,
+        }
+    }
+
+    #[test]
+    fn provider_glyph_uses_icon_character_or_initial() {
+        assert_eq!(provider_glyph("Anthropic", Some("★")), "★");
+        assert_eq!(provider_glyph("Anthropic", Some("🤖")), "🤖");
+        assert_eq!(provider_glyph("anthropic", Some("claude")), "A");
+        assert_eq!(provider_glyph("  deepseek", None), "D");
+        assert_eq!(provider_glyph("", Some("  ")), "?");
+    }
+
+    #[test]
+    fn last_used_label_is_relative_to_now() {
+        let now = 1_000_000_000;
+        assert_eq!(last_used_label(None, now), "never used");
+        assert_eq!(last_used_label(Some(now - 5), now), "used just now");
+        assert_eq!(last_used_label(Some(now - 300), now), "last used 5m ago");
+        assert_eq!(
+            last_used_label(Some(now - 2 * 86400), now),
+            "last used 2d ago"
+        );
+        assert_eq!(
+            last_used_label(Some(now - 90 * 86400), now),
+            "last used 3mo ago"
+        );
+    }
+
+    #[test]
+    fn latency_summary_prefers_first_healthy_endpoint() {
+        assert_eq!(LatencySummary::from_endpoints(&[]), None);
+        assert_eq!(
+            LatencySummary::from_endpoints(&[endpoint(None, true, false)]),
+            None
+        );
+        assert_eq!(
+            LatencySummary::from_endpoints(&[
+                endpoint(Some(900), false, true),
+                endpoint(Some(120), true, true),
+            ]),
+            Some(LatencySummary::Healthy(120))
+        );
+        assert_eq!(
+            LatencySummary::from_endpoints(&[endpoint(None, false, true)]),
+            Some(LatencySummary::Down)
+        );
+    }
+}
@@ -1,69 +1,991 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 
-use crossterm::event::KeyCode;
+use chrono::{Local, TimeZone};
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Sparkline, Table, TableState};
+use tokio::sync::broadcast::{error::TryRecvError, Receiver};
 
+use super::usage::format_tokens;
 use super::{Theme, View};
-use cc_switch_lib::{AppState, ProxyService};
+use crate::tui::app::NavAction;
+use crate::tui::keymap::Action;
+use crate::tui::widgets::loading_title;
+use cc_switch_lib::{
+    display_upstream_proxy, AppState, AppType, ClientRateLimit, DnsConfig, HedgingConfig,
+    LoadBalanceStrategy, ProviderLimitStatus, ProxyStatus, ProxyTakeoverStatus, RequestLogEntry,
+    ResponseCacheConfig, ShadowConfig, SizeLimitConfig, SwitchBackConfig, SwitchBackMode,
+    WebhookConfig,
+};
+
+/// 最近请求面板最多保留的条数
+const MAX_RECENT_REQUESTS: usize = 200;
+/// 延迟趋势列的宽度，即展示的最近请求数
+const SPARKLINE_WIDTH: u16 = 24;
+/// p95 延迟达到该值时迷你图显示为黄色
+const TREND_SLOW_MS: u64 = 2000;
+/// p95 延迟达到该值时迷你图显示为红色
+const TREND_BAD_MS: u64 = 5000;
+
+/// 后台任务加载的代理状态
+pub struct ProxyData {
+    status: ProxyStatus,
+    takeover: ProxyTakeoverStatus,
+    budgets: Vec<(AppType, ProviderLimitStatus)>,
+    strategies: Vec<(AppType, LoadBalanceStrategy)>,
+    rate_limits: Vec<(AppType, ClientRateLimit)>,
+    stream_failover: Vec<(AppType, bool)>,
+    switch_back: Vec<(AppType, SwitchBackConfig)>,
+    hedging: HedgingConfig,
+    shadows: Vec<(AppType, ShadowConfig, String)>,
+    response_cache: ResponseCacheConfig,
+    auth_token: Option<String>,
+    upstream_proxy: Option<String>,
+    dns: DnsConfig,
+    size_limits: SizeLimitConfig,
+    webhooks: WebhookConfig,
+}
 
 pub struct ProxyView {
     state: Arc<AppState>,
-    is_running: bool,
+    pub loading: bool,
+    status: ProxyStatus,
+    /// 各应用的 Live 配置接管状态
+    takeover: ProxyTakeoverStatus,
+    /// 设置了预算的供应商及其当前用量
+    budgets: Vec<(AppType, ProviderLimitStatus)>,
+    /// 各应用故障转移队列的负载均衡策略
+    strategies: Vec<(AppType, LoadBalanceStrategy)>,
+    /// 各应用的客户端侧限流配置
+    rate_limits: Vec<(AppType, ClientRateLimit)>,
+    /// 各应用流式响应在首个事件前中断时是否改用下一个供应商
+    stream_failover: Vec<(AppType, bool)>,
+    /// 各应用故障转移后切回首选供应商的设置
+    switch_back: Vec<(AppType, SwitchBackConfig)>,
+    /// 慢响应时向次优端点发送对冲请求的设置
+    hedging: HedgingConfig,
+    /// 开启了流量镜像的应用：(应用, 设置, 目标供应商名称)
+    shadows: Vec<(AppType, ShadowConfig, String)>,
+    /// 非流式响应缓存设置
+    response_cache: ResponseCacheConfig,
+    /// 客户端访问代理需要携带的令牌，None 表示不校验
+    auth_token: Option<String>,
+    /// 全局上游代理地址，None 表示直连
+    upstream_proxy: Option<String>,
+    /// DNS 缓存与静态解析设置
+    dns: DnsConfig,
+    /// 请求与响应体大小上限
+    size_limits: SizeLimitConfig,
+    /// 故障事件的 Webhook 通知设置
+    webhooks: WebhookConfig,
+    /// 最近转发的请求，最新的在前
+    requests: VecDeque<RequestLogEntry>,
+    request_rx: Receiver<RequestLogEntry>,
+    request_table: TableState,
 }
 
 impl ProxyView {
     pub fn new(state: Arc<AppState>) -> Self {
-        Self {
+        let log = state.proxy_service.request_log();
+        let request_rx = log.subscribe();
+        let mut view = Self {
             state,
-            is_running: false,
+            loading: false,
+            status: ProxyStatus::default(),
+            takeover: ProxyTakeoverStatus::default(),
+            budgets: Vec::new(),
+            strategies: Vec::new(),
+            rate_limits: Vec::new(),
+            stream_failover: Vec::new(),
+            switch_back: Vec::new(),
+            hedging: HedgingConfig::default(),
+            shadows: Vec::new(),
+            response_cache: ResponseCacheConfig::default(),
+            auth_token: None,
+            upstream_proxy: None,
+            dns: DnsConfig::default(),
+            size_limits: SizeLimitConfig::default(),
+            webhooks: WebhookConfig::default(),
+            requests: VecDeque::new(),
+            request_rx,
+            request_table: TableState::default(),
+        };
+        view.resync_requests();
+        view
+    }
+
+    /// 从代理层的环形缓冲重新拉取全部记录
+    fn resync_requests(&mut self) {
+        let recent = self.state.proxy_service.request_log().recent();
+        self.requests = recent.into_iter().rev().take(MAX_RECENT_REQUESTS).collect();
+    }
+
+    /// 取出广播通道中的新请求；落后过多时整体重新同步
+    pub fn poll_requests(&mut self) {
+        loop {
+            match self.request_rx.try_recv() {
+                Ok(entry) => {
+                    self.requests.push_front(entry);
+                    self.requests.truncate(MAX_RECENT_REQUESTS);
+                    // 保持选中行指向同一条记录
+                    if let Some(i) = self.request_table.selected() {
+                        self.request_table
+                            .select(Some((i + 1).min(self.requests.len() - 1)));
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => self.resync_requests(),
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.request_table.selected(), self.requests.len()) {
+            self.request_table.select(Some(i));
+        }
+    }
+
+    pub async fn load(state: &AppState) -> ProxyData {
+        ProxyData {
+            status: state.proxy_service.get_status().await.unwrap_or_default(),
+            takeover: state
+                .proxy_service
+                .get_takeover_status()
+                .await
+                .unwrap_or_default(),
+            budgets: load_budgets(state),
+            strategies: [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .map(|app_type| {
+                    let strategy = state
+                        .db
+                        .get_load_balance_strategy(app_type.as_str())
+                        .unwrap_or_default();
+                    (app_type, strategy)
+                })
+                .collect(),
+            rate_limits: [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .map(|app_type| {
+                    let limit = state
+                        .db
+                        .get_client_rate_limit(app_type.as_str())
+                        .unwrap_or_default();
+                    (app_type, limit)
+                })
+                .collect(),
+            stream_failover: [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .map(|app_type| {
+                    let enabled = state
+                        .db
+                        .get_stream_failover(app_type.as_str())
+                        .unwrap_or(false);
+                    (app_type, enabled)
+                })
+                .collect(),
+            switch_back: [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .map(|app_type| {
+                    let config = state
+                        .db
+                        .get_switch_back_config(app_type.as_str())
+                        .unwrap_or_default();
+                    (app_type, config)
+                })
+                .collect(),
+            hedging: state.db.get_proxy_hedging_config().unwrap_or_default(),
+            shadows: [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .filter_map(|app_type| {
+                    let config = state.db.get_shadow_config(app_type.as_str()).ok()?;
+                    let id = config.provider_id.clone().filter(|_| config.is_active())?;
+                    let name = state
+                        .db
+                        .get_provider_by_id(&id, app_type.as_str())
+                        .ok()
+                        .flatten()
+                        .map_or(id, |p| p.name);
+                    Some((app_type, config, name))
+                })
+                .collect(),
+            response_cache: state
+                .db
+                .get_proxy_response_cache_config()
+                .unwrap_or_default(),
+            auth_token: state.proxy_service.get_auth_token().unwrap_or_default(),
+            upstream_proxy: state.db.get_proxy_upstream_url().unwrap_or_default(),
+            dns: state.db.get_proxy_dns_config().unwrap_or_default(),
+            size_limits: state.db.get_proxy_size_limits().unwrap_or_default(),
+            webhooks: state.db.get_proxy_webhook_config().unwrap_or_default(),
+        }
+    }
+
+    pub fn apply(&mut self, data: ProxyData) {
+        self.status = data.status;
+        self.takeover = data.takeover;
+        self.budgets = data.budgets;
+        self.strategies = data.strategies;
+        self.rate_limits = data.rate_limits;
+        self.stream_failover = data.stream_failover;
+        self.switch_back = data.switch_back;
+        self.hedging = data.hedging;
+        self.shadows = data.shadows;
+        self.response_cache = data.response_cache;
+        self.auth_token = data.auth_token;
+        self.upstream_proxy = data.upstream_proxy;
+        self.dns = data.dns;
+        self.size_limits = data.size_limits;
+        self.webhooks = data.webhooks;
+        self.loading = false;
+    }
+
+    fn is_taken_over(&self, app_type: &AppType) -> bool {
+        match app_type {
+            AppType::Claude => self.takeover.claude,
+            AppType::Codex => self.takeover.codex,
+            AppType::Gemini => self.takeover.gemini,
         }
     }
 
-    pub async fn refresh(&mut self) {
-        self.is_running = self.state.proxy_service.is_running().await;
+    /// 切换指定应用的 Live 接管，返回切换后的状态
+    ///
+    /// 开启时会按需启动代理并改写 Live 配置指向本地代理；关闭时恢复原配置
+    pub async fn toggle_takeover(&mut self, app_type: &AppType) -> Result<bool, String> {
+        let enable = !self.is_taken_over(app_type);
+        let result = self
+            .state
+            .proxy_service
+            .set_takeover_for_app(app_type.as_str(), enable)
+            .await;
+        self.apply(Self::load(&self.state).await);
+        result.map(|()| enable)
+    }
+
+    /// 切换指定应用的负载均衡策略并保存，返回新策略
+    pub fn cycle_load_balance(
+        &mut self,
+        app_type: &AppType,
+    ) -> Result<LoadBalanceStrategy, String> {
+        let current = self
+            .strategies
+            .iter()
+            .find(|(app, _)| app == app_type)
+            .map(|(_, strategy)| *strategy)
+            .unwrap_or_default();
+        let next = current.next();
+        self.state
+            .db
+            .set_load_balance_strategy(app_type.as_str(), next)
+            .map_err(|e| e.to_string())?;
+        match self.strategies.iter_mut().find(|(app, _)| app == app_type) {
+            Some(entry) => entry.1 = next,
+            None => self.strategies.push((app_type.clone(), next)),
+        }
+        Ok(next)
     }
 
-    pub async fn handle_key(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Char('p') => self.toggle_proxy().await,
-            _ => {}
+    /// 切换指定应用的流式中断重试并保存，返回切换后的状态
+    pub fn toggle_stream_failover(&mut self, app_type: &AppType) -> Result<bool, String> {
+        let enabled = !self
+            .stream_failover
+            .iter()
+            .any(|(app, enabled)| app == app_type && *enabled);
+        self.state
+            .db
+            .set_stream_failover(app_type.as_str(), enabled)
+            .map_err(|e| e.to_string())?;
+        match self
+            .stream_failover
+            .iter_mut()
+            .find(|(app, _)| app == app_type)
+        {
+            Some(entry) => entry.1 = enabled,
+            None => self.stream_failover.push((app_type.clone(), enabled)),
+        }
+        Ok(enabled)
+    }
+
+    /// 切换对冲请求并保存，返回切换后的设置
+    pub fn toggle_hedging(&mut self) -> Result<HedgingConfig, String> {
+        let config = HedgingConfig {
+            enabled: !self.hedging.enabled,
+            ..self.hedging.clone()
+        };
+        self.state
+            .db
+            .set_proxy_hedging_config(&config)
+            .map_err(|e| e.to_string())?;
+        self.hedging = config.clone();
+        Ok(config)
+    }
+
+    pub async fn handle_action(&mut self, action: Action) {
+        if action == Action::ToggleProxy {
+            self.toggle_proxy().await;
         }
     }
 
     async fn toggle_proxy(&mut self) {
-        if self.is_running {
+        if self.status.running {
             let _ = self.state.proxy_service.stop().await;
         } else {
             let _ = self.state.proxy_service.start().await;
         }
-        self.refresh().await;
+        let data = Self::load(&self.state).await;
+        self.apply(data);
     }
-}
 
-impl View for ProxyView {
-    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
-        let status = if self.is_running {
-            "Running"
+    fn render_summary(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let status = &self.status;
+        let label = |text: &'static str| Span::styled(text, theme.inactive);
+
+        let state_line = if status.running {
+            Line::from(vec![
+                label("Status: "),
+                Span::styled("Running", theme.success),
+                Span::raw(listen_label(status)),
+                label("   Uptime: "),
+                Span::raw(format_uptime(status.uptime_seconds)),
+            ])
         } else {
-            "Stopped"
+            Line::from(vec![
+                label("Status: "),
+                Span::styled("Stopped", theme.inactive),
+            ])
         };
-        let style = if self.is_running {
-            theme.success
+
+        // 所有端点都不可用的供应商置顶显示
+        let mut lines: Vec<Line> = status
+            .endpoint_outages
+            .iter()
+            .map(|outage| {
+                let since = Local
+                    .timestamp_millis_opt(outage.since)
+                    .single()
+                    .map(|t| t.format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                Line::styled(
+                    format!(
+                        "⚠ All endpoints of {} ({}) unhealthy since {since} — {}",
+                        outage.provider_name,
+                        outage.app_type,
+                        outage.fallback.label()
+                    ),
+                    theme.error.add_modifier(Modifier::BOLD),
+                )
+            })
+            .collect();
+        lines.extend([
+            state_line,
+            Line::from(vec![
+                label("Requests: "),
+                Span::raw(status.total_requests.to_string()),
+                label("   Success: "),
+                Span::styled(status.success_requests.to_string(), theme.success),
+                label("   Errors: "),
+                Span::styled(status.failed_requests.to_string(), theme.error),
+                label("   Success rate: "),
+                Span::raw(format!("{:.1}%", status.success_rate)),
+            ]),
+            self.takeover_line(theme),
+            Line::from(vec![
+                label("Auth token: "),
+                match &self.auth_token {
+                    Some(token) => Span::raw(token.clone()),
+                    None => {
+                        Span::styled("off (any local client can use the proxy)", theme.inactive)
+                    }
+                },
+            ]),
+            Line::from(vec![
+                label("Upstream proxy: "),
+                match &self.upstream_proxy {
+                    Some(url) => Span::raw(display_upstream_proxy(url)),
+                    None => Span::styled("direct", theme.inactive),
+                },
+            ]),
+            Line::from(vec![
+                label("Active streams: "),
+                Span::raw(status.active_streams.to_string()),
+                label("   Failovers: "),
+                Span::raw(status.failover_count.to_string()),
+            ]),
+        ]);
+        if let Some(error) = &status.last_error {
+            lines.push(Line::from(vec![
+                label("Last error: "),
+                Span::styled(error.clone(), theme.error),
+            ]));
+        }
+
+        frame.render_widget(Paragraph::new(lines).style(theme.normal), area);
+    }
+
+    fn takeover_line(&self, theme: &Theme) -> Line<'static> {
+        let mut spans = vec![Span::styled("Takeover: ", theme.inactive)];
+        for (name, app_type) in [
+            ("Claude", AppType::Claude),
+            ("Codex", AppType::Codex),
+            ("Gemini", AppType::Gemini),
+        ] {
+            let (marker, style) = if self.is_taken_over(&app_type) {
+                ("✓", theme.success)
+            } else {
+                ("✗", theme.inactive)
+            };
+            spans.push(Span::styled(format!("{name} {marker}"), style));
+            spans.push(Span::raw("   "));
+        }
+        spans.pop();
+        Line::from(spans)
+    }
+
+    fn render_routing(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let mut lines = vec![Line::styled("Routing", theme.title)];
+        let mut strategy_spans = vec![Span::styled("  Balancing: ", theme.inactive)];
+        for (app_type, strategy) in &self.strategies {
+            let style = if *strategy == LoadBalanceStrategy::Failover {
+                theme.inactive
+            } else {
+                theme.highlight
+            };
+            strategy_spans.push(Span::raw(format!("{} ", app_type.as_str())));
+            strategy_spans.push(Span::styled(strategy.label(), style));
+            strategy_spans.push(Span::raw("   "));
+        }
+        strategy_spans.pop();
+        lines.push(Line::from(strategy_spans));
+        let mut limit_spans = vec![Span::styled("  Limits:    ", theme.inactive)];
+        for (app_type, limit) in &self.rate_limits {
+            limit_spans.push(Span::raw(format!("{} ", app_type.as_str())));
+            if limit.is_unlimited() {
+                limit_spans.push(Span::styled("unlimited", theme.inactive));
+            } else {
+                limit_spans.push(Span::styled(limit.label(), theme.highlight));
+            }
+            limit_spans.push(Span::raw("   "));
+        }
+        limit_spans.pop();
+        lines.push(Line::from(limit_spans));
+        let mut retry_spans = vec![Span::styled("  Stream retry: ", theme.inactive)];
+        for (app_type, enabled) in &self.stream_failover {
+            retry_spans.push(Span::raw(format!("{} ", app_type.as_str())));
+            if *enabled {
+                retry_spans.push(Span::styled("on", theme.highlight));
+            } else {
+                retry_spans.push(Span::styled("off", theme.inactive));
+            }
+            retry_spans.push(Span::raw("   "));
+        }
+        retry_spans.pop();
+        lines.push(Line::from(retry_spans));
+        let mut switch_back_spans = vec![Span::styled("  Switch back: ", theme.inactive)];
+        for (app_type, config) in &self.switch_back {
+            switch_back_spans.push(Span::raw(format!("{} ", app_type.as_str())));
+            if config.mode == SwitchBackMode::Never {
+                switch_back_spans.push(Span::styled("never", theme.inactive));
+            } else {
+                switch_back_spans.push(Span::styled(
+                    format!(
+                        "{} after {}s",
+                        config.mode.label(),
+                        config.healthy_window_seconds
+                    ),
+                    theme.highlight,
+                ));
+            }
+            switch_back_spans.push(Span::raw("   "));
+        }
+        switch_back_spans.pop();
+        lines.push(Line::from(switch_back_spans));
+        let now = chrono::Utc::now().timestamp_millis();
+        for candidate in &self.status.switch_back {
+            let healthy_for = format_uptime(((now - candidate.healthy_since).max(0) / 1000) as u64);
+            lines.push(Line::from(vec![
+                Span::raw(format!("    {:<8}", candidate.app_type)),
+                Span::styled(candidate.provider_name.clone(), theme.highlight),
+                Span::raw(format!(" healthy for {healthy_for}")),
+                if candidate.ready {
+                    Span::styled(" — ready to switch back", theme.success)
+                } else {
+                    Span::styled(" — waiting", theme.inactive)
+                },
+            ]));
+        }
+        lines.push(Line::from(vec![
+            Span::styled("  Hedging:   ", theme.inactive),
+            if self.hedging.enabled {
+                Span::styled(
+                    format!(
+                        "after p{} (min {}ms, hybrid mode only)",
+                        self.hedging.percentile, self.hedging.min_delay_ms
+                    ),
+                    theme.highlight,
+                )
+            } else {
+                Span::styled("off", theme.inactive)
+            },
+        ]));
+        let cache = &self.status.response_cache;
+        let mut cache_spans = vec![Span::styled("  Cache:     ", theme.inactive)];
+        if self.response_cache.enabled {
+            cache_spans.push(Span::styled(
+                format!(
+                    "ttl {}s, max {} entries / {} KB",
+                    self.response_cache.ttl_seconds,
+                    self.response_cache.max_entries,
+                    self.response_cache.max_size_kb
+                ),
+                theme.highlight,
+            ));
         } else {
-            theme.inactive
+            cache_spans.push(Span::styled("off", theme.inactive));
+        }
+        if cache.hits + cache.misses > 0 || cache.entries > 0 {
+            let lookups = (cache.hits + cache.misses).max(1);
+            cache_spans.push(Span::raw(format!(
+                "   {} hits / {} misses ({}%), {} cached ({} KB)",
+                cache.hits,
+                cache.misses,
+                cache.hits * 100 / lookups,
+                cache.entries,
+                cache.bytes.div_ceil(1024)
+            )));
+        }
+        lines.push(Line::from(cache_spans));
+        let dns = &self.status.dns_cache;
+        let mut dns_spans = vec![Span::styled("  DNS:       ", theme.inactive)];
+        if self.dns.cache_enabled {
+            dns_spans.push(Span::styled(
+                format!("TTL max {}s", self.dns.cache_ttl_seconds),
+                theme.highlight,
+            ));
+        } else {
+            dns_spans.push(Span::styled("cache off", theme.inactive));
+        }
+        if !self.dns.overrides.is_empty() {
+            dns_spans.push(Span::raw(format!(
+                ", {} static host(s)",
+                self.dns.overrides.len()
+            )));
+        }
+        if dns.hits + dns.misses > 0 {
+            dns_spans.push(Span::raw(format!(
+                "   {} hits / {} misses, {} cached",
+                dns.hits, dns.misses, dns.entries
+            )));
+        }
+        lines.push(Line::from(dns_spans));
+        let limit = |kb: u64| match kb {
+            0 => "unlimited".to_string(),
+            kb => format!("{kb} KB"),
         };
+        let oversized = &self.status.size_limits;
+        let mut size_spans = vec![
+            Span::styled("  Size cap:  ", theme.inactive),
+            Span::raw(format!(
+                "request {}, response {}",
+                limit(self.size_limits.max_request_kb),
+                limit(self.size_limits.max_response_kb)
+            )),
+        ];
+        if oversized.rejected_requests + oversized.rejected_responses > 0 {
+            size_spans.push(Span::styled(
+                format!(
+                    "   {} request(s) / {} response(s) over the cap",
+                    oversized.rejected_requests, oversized.rejected_responses
+                ),
+                theme.warning,
+            ));
+        }
+        lines.push(Line::from(size_spans));
+        let webhook_events: Vec<&str> = self.webhooks.events.iter().map(|k| k.as_str()).collect();
+        lines.push(Line::from(vec![
+            Span::styled("  Webhooks:  ", theme.inactive),
+            match (self.webhooks.targets.len(), webhook_events.is_empty()) {
+                (0, _) => Span::styled("off", theme.inactive),
+                (_, true) => Span::styled("all events muted", theme.inactive),
+                (n, false) => Span::styled(
+                    format!("{n} URL(s) on {}", webhook_events.join(", ")),
+                    theme.highlight,
+                ),
+            },
+        ]));
+        if self.shadows.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("  Shadow:    ", theme.inactive),
+                Span::styled("off", theme.inactive),
+            ]));
+        }
+        for (i, (app_type, config, name)) in self.shadows.iter().enumerate() {
+            let label = if i == 0 {
+                "  Shadow:    "
+            } else {
+                "             "
+            };
+            let mut spans = vec![
+                Span::styled(label, theme.inactive),
+                Span::raw(format!("{} ", app_type.as_str())),
+                Span::styled(format!("{}% → {name}", config.percent), theme.highlight),
+            ];
+            let stats = self.status.shadow_stats.iter().find(|s| {
+                s.app_type == app_type.as_str()
+                    && Some(&s.provider_id) == config.provider_id.as_ref()
+            });
+            match stats {
+                Some(stats) => {
+                    let ms = |v: Option<u64>| v.map_or("-".to_string(), |v| format!("{v}ms"));
+                    spans.push(Span::raw(format!(
+                        "   {} sent, {} failed, {} status mismatches, p50 {} p95 {}",
+                        stats.requests,
+                        stats.failures,
+                        stats.status_mismatches,
+                        ms(stats.p50_latency_ms),
+                        ms(stats.p95_latency_ms)
+                    )));
+                    if let Some(error) = &stats.last_error {
+                        spans.push(Span::styled(format!("   last error: {error}"), theme.error));
+                    }
+                }
+                None => spans.push(Span::styled("   no mirrored requests yet", theme.inactive)),
+            }
+            lines.push(Line::from(spans));
+        }
+        if self.status.active_targets.is_empty() {
+            lines.push(Line::styled("  No requests routed yet", theme.inactive));
+        }
+        let mut targets: Vec<_> = self.status.active_targets.iter().collect();
+        targets.sort_by(|a, b| a.app_type.cmp(&b.app_type));
+        for target in targets {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<8}", target.app_type)),
+                Span::styled("→ ", theme.inactive),
+                Span::styled(target.provider_name.clone(), theme.highlight),
+            ]));
+        }
+        frame.render_widget(Paragraph::new(lines).style(theme.normal), area);
+    }
 
-        let text = format!(
-            "Proxy Status: {}\n\n\
-             Press 'p' to start/stop proxy",
-            status
+    fn render_budgets(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let mut lines = vec![Line::styled("Budgets", theme.title)];
+        for (app_type, status) in &self.budgets {
+            let period = |label: &'static str,
+                          cost: &str,
+                          cost_limit: &Option<String>,
+                          tokens: u64,
+                          token_limit: Option<u64>,
+                          exceeded: bool| {
+                let mut text = format!("{label} ${:.2}", cost.parse::<f64>().unwrap_or(0.0));
+                if let Some(limit) = cost_limit {
+                    text.push_str(&format!("/${limit}"));
+                }
+                if let Some(limit) = token_limit {
+                    text.push_str(&format!(" · {tokens}/{limit} tok"));
+                }
+                let style = if exceeded { theme.error } else { theme.normal };
+                Span::styled(format!("{text:<34}"), style)
+            };
+            let state = match (status.is_exceeded(), status.pause_on_limit) {
+                (false, _) => Span::styled("ok", theme.success),
+                (true, true) => Span::styled("exceeded · paused", theme.error),
+                (true, false) => Span::styled("exceeded", theme.warning),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<8}", app_type.as_str())),
+                Span::styled(format!("{:<16} ", status.provider_name), theme.highlight),
+                period(
+                    "today",
+                    &status.daily_usage,
+                    &status.daily_limit,
+                    status.daily_tokens,
+                    status.daily_token_limit,
+                    status.daily_exceeded,
+                ),
+                period(
+                    "month",
+                    &status.monthly_usage,
+                    &status.monthly_limit,
+                    status.monthly_tokens,
+                    status.monthly_token_limit,
+                    status.monthly_exceeded,
+                ),
+                state,
+            ]));
+        }
+        frame.render_widget(Paragraph::new(lines).style(theme.normal), area);
+    }
+
+    fn render_provider_stats(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let header = Row::new(vec![
+            "App",
+            "Provider",
+            "In-flight",
+            "Requests",
+            "Errors",
+            "Err %",
+            "p50",
+            "p95",
+            "Hedged",
+            "Tokens in/out",
+            "Sent",
+            "Received",
+        ])
+        .style(theme.title);
+        let latency = |ms: Option<u64>| ms.map_or_else(|| "-".to_string(), |ms| format!("{ms}ms"));
+        let rows: Vec<Row> = self
+            .status
+            .provider_stats
+            .iter()
+            .map(|stats| {
+                let errors_style = if stats.failures > 0 {
+                    theme.error
+                } else {
+                    theme.normal
+                };
+                Row::new(vec![
+                    Line::from(stats.app_type.clone()),
+                    Line::from(stats.provider_name.clone()),
+                    Line::from(stats.in_flight.to_string()),
+                    Line::from(stats.requests.to_string()),
+                    Line::styled(stats.failures.to_string(), errors_style),
+                    Line::styled(format!("{:.1}%", stats.error_rate()), errors_style),
+                    Line::from(latency(stats.p50_latency_ms)),
+                    Line::from(latency(stats.p95_latency_ms)),
+                    Line::from(if stats.hedged_requests == 0 {
+                        "-".to_string()
+                    } else {
+                        format!("{} ({} won)", stats.hedged_requests, stats.hedge_wins)
+                    }),
+                    Line::from(format!(
+                        "{}/{}",
+                        format_tokens(stats.input_tokens),
+                        format_tokens(stats.output_tokens)
+                    )),
+                    Line::from(format_bytes(stats.bytes_sent)),
+                    Line::from(format_bytes(stats.bytes_received)),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let [table_area, trend_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(SPARKLINE_WIDTH + 1)])
+                .areas(area);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Min(16),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(12),
+                Constraint::Length(14),
+                Constraint::Length(9),
+                Constraint::Length(9),
+            ],
+        )
+        .header(header);
+        frame.render_widget(table, table_area);
+        self.render_latency_trends(frame, trend_area, theme);
+    }
+
+    /// 每个供应商一行延迟迷你图，与统计表的行对齐
+    fn render_latency_trends(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let area = area.inner(Margin::new(1, 0));
+        if area.height == 0 {
+            return;
+        }
+        frame.render_widget(
+            Paragraph::new("Latency trend").style(theme.title),
+            Rect::new(area.x, area.y, area.width, 1),
         );
+        for (i, stats) in self.status.provider_stats.iter().enumerate() {
+            let y = area.y + 1 + i as u16;
+            if y >= area.bottom() {
+                break;
+            }
+            let samples = stats.recent_latencies(area.width as usize);
+            let style = match stats.p95_latency_ms {
+                Some(ms) if ms >= TREND_BAD_MS => theme.error,
+                Some(ms) if ms >= TREND_SLOW_MS => theme.warning,
+                _ => theme.success,
+            };
+            let sparkline = Sparkline::default().data(&samples).style(style);
+            frame.render_widget(sparkline, Rect::new(area.x, y, area.width, 1));
+        }
+    }
+
+    fn render_requests(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::default()
+            .borders(Borders::TOP)
+            .title(format!("Recent requests ({})", self.requests.len()))
+            .style(theme.normal);
+        if self.requests.is_empty() {
+            let empty = Paragraph::new("No requests proxied yet")
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(empty, area);
+            return;
+        }
+
+        let header = Row::new(vec!["Time", "App", "Provider", "URL", "Status", "Latency"])
+            .style(theme.title);
+        let rows: Vec<Row> = self
+            .requests
+            .iter()
+            .map(|entry| {
+                let time = Local
+                    .timestamp_millis_opt(entry.timestamp)
+                    .single()
+                    .map(|t| t.format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                let (status, status_style) = match entry.status {
+                    Some(code) if entry.is_success() => (code.to_string(), theme.success),
+                    Some(code) => (code.to_string(), theme.error),
+                    None => ("ERR".to_string(), theme.error),
+                };
+                Row::new(vec![
+                    Line::from(time),
+                    Line::from(entry.app_type.clone()),
+                    Line::from(entry.provider_name.clone()),
+                    Line::from(entry.url.clone()),
+                    Line::styled(status, status_style),
+                    Line::from(format!("{}ms", entry.latency_ms)),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(16),
+                Constraint::Min(20),
+                Constraint::Length(6),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .block(block)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.request_table);
+    }
+}
+
+impl View for ProxyView {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title("Proxy", self.loading));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let routing_height = self.status.active_targets.len().max(1) as u16
+            + self.shadows.len().max(1) as u16
+            + self.status.switch_back.len() as u16
+            + 10;
+        let budgets_height = match self.budgets.len() {
+            0 => 0,
+            n => n as u16 + 2,
+        };
+        let stats_height = self.status.provider_stats.len() as u16 + 2;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(7 + self.status.endpoint_outages.len() as u16),
+                Constraint::Length(routing_height + 1),
+                Constraint::Length(budgets_height),
+                Constraint::Length(stats_height),
+                Constraint::Min(4),
+            ])
+            .split(inner.inner(Margin::new(1, 0)));
+
+        self.render_summary(frame, chunks[0], theme);
+        self.render_routing(frame, chunks[1], theme);
+        if !self.budgets.is_empty() {
+            self.render_budgets(frame, chunks[2], theme);
+        }
+        self.render_provider_stats(frame, chunks[3], theme);
+        self.render_requests(frame, chunks[4], theme);
+    }
+}
 
-        let paragraph = Paragraph::new(text)
-            .style(style)
-            .block(Block::default().borders(Borders::ALL).title("Proxy"));
+/// 读取各应用中设置了预算的供应商状态
+pub fn load_budgets(state: &AppState) -> Vec<(AppType, ProviderLimitStatus)> {
+    [AppType::Claude, AppType::Codex, AppType::Gemini]
+        .into_iter()
+        .flat_map(|app_type| {
+            let statuses = state
+                .db
+                .list_provider_limit_statuses(app_type.as_str())
+                .unwrap_or_default();
+            statuses.into_iter().map(move |s| (app_type.clone(), s))
+        })
+        .collect()
+}
 
-        frame.render_widget(paragraph, area);
+/// 监听位置摘要，例如 " on http://127.0.0.1:15721 (gemini :15731) + unix:/tmp/proxy.sock"
+fn listen_label(status: &ProxyStatus) -> String {
+    let socket = status.unix_socket.as_deref().unwrap_or_default();
+    if status.socket_only {
+        return format!(" on unix:{socket}");
+    }
+    let mut label = format!(
+        " on {}://{}:{}{}",
+        if status.tls { "https" } else { "http" },
+        status.address,
+        status.port,
+        app_ports_label(&status.app_ports)
+    );
+    if !socket.is_empty() {
+        label.push_str(&format!(" + unix:{socket}"));
+    }
+    label
+}
+
+/// 独立端口摘要，例如 " (claude :15722, gemini :15731)"
+fn app_ports_label(app_ports: &[(String, u16)]) -> String {
+    if app_ports.is_empty() {
+        return String::new();
+    }
+    let ports: Vec<String> = app_ports
+        .iter()
+        .map(|(app, port)| format!("{app} :{port}"))
+        .collect();
+    format!(" ({})", ports.join(", "))
+}
+
+/// 运行时长显示为 `1h 02m 03s` 形式
+fn format_uptime(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// 字节数显示为 `512 B`、`1.5 KB`、`2.0 MB` 形式
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
     }
 }
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField, TextInput};
+use cc_switch_lib::{AppState, AppType, ClientRateLimit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    RequestsPerMinute,
+    MaxConcurrent,
+}
+
+/// 按应用编辑客户端侧限流（每分钟请求数、并发数）的弹窗，0 表示不限制
+pub struct RateLimitForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    form: Form<Field>,
+}
+
+impl RateLimitForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType) -> Result<(), String> {
+        let limit = self
+            .state
+            .db
+            .get_client_rate_limit(app_type.as_str())
+            .map_err(|e| e.to_string())?;
+        self.app_type = app_type;
+        self.form = Form::new(vec![
+            (
+                Field::RequestsPerMinute,
+                FormField::number("Requests per minute", limit.requests_per_minute),
+            ),
+            (
+                Field::MaxConcurrent,
+                FormField::number("Concurrent requests", limit.max_concurrent),
+            ),
+        ]);
+        self.visible = true;
+        Ok(())
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 返回 true 表示配置已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => return self.save(),
+            FormEvent::None => {}
+        }
+        false
+    }
+
+    fn parse(&self) -> Result<ClientRateLimit, String> {
+        let number = |input: &TextInput| {
+            let value = input.value.trim();
+            if value.is_empty() {
+                return Ok(0);
+            }
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("{} must be a whole number", input.label))
+        };
+        Ok(ClientRateLimit {
+            requests_per_minute: number(self.form.input(Field::RequestsPerMinute))?,
+            max_concurrent: number(self.form.input(Field::MaxConcurrent))?,
+        })
+    }
+
+    fn save(&mut self) -> bool {
+        let result = self.parse().and_then(|limit| {
+            self.state
+                .db
+                .set_client_rate_limit(self.app_type.as_str(), limit)
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.form.set_error(e);
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(50, self.form.height(), frame.area()),
+            &format!("Rate Limit — {}", self.app_type.as_str()),
+            "0 = unlimited; excess requests get a local 429",
+        );
+    }
+}
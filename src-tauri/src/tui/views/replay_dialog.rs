@@ -0,0 +1,223 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+
+use super::{centered_rect, Theme};
+use cc_switch_lib::{AppType, Provider, ReplayResponse, RequestCapture};
+
+/// 待发送的重放请求
+pub struct ReplayRequest {
+    pub app_type: AppType,
+    pub provider: Provider,
+    pub capture: RequestCapture,
+}
+
+/// 请求重放弹窗：先选择供应商，发送后在同一弹窗中显示响应
+pub struct ReplayDialog {
+    pub visible: bool,
+    app_type: AppType,
+    capture: Option<RequestCapture>,
+    providers: Vec<Provider>,
+    list_state: ListState,
+    /// 请求已发出、尚未返回
+    sending: bool,
+    result: Option<Result<ReplayResponse, String>>,
+    scroll: u16,
+}
+
+impl ReplayDialog {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            capture: None,
+            providers: Vec::new(),
+            list_state: ListState::default(),
+            sending: false,
+            result: None,
+            scroll: 0,
+        }
+    }
+
+    /// 打开供应商选择，默认选中原请求使用的供应商
+    pub fn open(
+        &mut self,
+        app_type: AppType,
+        capture: RequestCapture,
+        providers: Vec<Provider>,
+        original_provider_id: &str,
+    ) {
+        let selected = providers
+            .iter()
+            .position(|p| p.id == original_provider_id)
+            .unwrap_or(0);
+        self.list_state
+            .select((!providers.is_empty()).then_some(selected));
+        self.app_type = app_type;
+        self.capture = Some(capture);
+        self.providers = providers;
+        self.sending = false;
+        self.result = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.capture = None;
+        self.providers.clear();
+        self.result = None;
+    }
+
+    pub fn set_result(&mut self, result: Result<ReplayResponse, String>) {
+        self.sending = false;
+        self.scroll = 0;
+        self.result = Some(result);
+    }
+
+    /// 在供应商列表中按 Enter 时返回待发送的请求
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<ReplayRequest> {
+        if self.sending {
+            if key == KeyCode::Esc {
+                self.close();
+            }
+            return None;
+        }
+
+        if self.result.is_some() {
+            match key {
+                KeyCode::Esc | KeyCode::Char('q') => self.close(),
+                // 返回供应商列表，换一个供应商再试
+                KeyCode::Backspace | KeyCode::Char('b') => self.result = None,
+                KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                _ => {}
+            }
+            return None;
+        }
+
+        let len = self.providers.len();
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                let i = self
+                    .list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1).min(len - 1));
+                self.list_state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                let i = self
+                    .list_state
+                    .selected()
+                    .map_or(0, |i| i.saturating_sub(1));
+                self.list_state.select(Some(i));
+            }
+            KeyCode::Enter => {
+                let provider = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.providers.get(i))?
+                    .clone();
+                self.sending = true;
+                return Some(ReplayRequest {
+                    app_type: self.app_type.clone(),
+                    provider,
+                    capture: self.capture.clone()?,
+                });
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let endpoint = self.capture.as_ref().map_or("", |c| c.endpoint.as_str());
+
+        let area = centered_rect(80, frame.area().height.saturating_sub(4), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Replay {endpoint}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        let hint = match &self.result {
+            _ if self.sending => "Sending…  Esc:Close",
+            Some(_) => "j/k:Scroll  b:Choose another provider  Esc:Close",
+            None => "Enter:Send  j/k:Select provider  Esc:Cancel",
+        };
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[1]);
+
+        match &self.result {
+            Some(result) if !self.sending => {
+                let paragraph = Paragraph::new(result_lines(result, theme))
+                    .style(theme.normal)
+                    .wrap(Wrap { trim: false })
+                    .scroll((self.scroll, 0));
+                frame.render_widget(paragraph, chunks[0]);
+            }
+            _ => {
+                let items: Vec<ListItem> = self
+                    .providers
+                    .iter()
+                    .map(|p| ListItem::new(p.name.clone()))
+                    .collect();
+                let list = List::new(items)
+                    .style(theme.normal)
+                    .highlight_style(theme.selected);
+                frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+            }
+        }
+    }
+}
+
+fn result_lines<'a>(result: &'a Result<ReplayResponse, String>, theme: &Theme) -> Vec<Line<'a>> {
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => return vec![Line::styled(format!("Replay failed: {e}"), theme.error)],
+    };
+
+    let label = |text: &'static str| Span::styled(text, theme.inactive);
+    let status_style = if (200..300).contains(&response.status) {
+        theme.success
+    } else {
+        theme.error
+    };
+    let mut lines = vec![
+        Line::from(vec![
+            label("Provider: "),
+            Span::raw(response.provider_name.as_str()),
+        ]),
+        Line::from(vec![label("URL:      "), Span::raw(response.url.as_str())]),
+        Line::from(vec![
+            label("Status:   "),
+            Span::styled(response.status.to_string(), status_style),
+            Span::raw(format!("  ({}ms)", response.latency_ms)),
+        ]),
+        Line::raw(""),
+        Line::styled("Response headers", theme.title),
+    ];
+    for (name, value) in &response.headers {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {name}: "), theme.inactive),
+            Span::raw(value.as_str()),
+        ]));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("Response body", theme.title));
+    let body = serde_json::from_str::<serde_json::Value>(&response.body)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| response.body.clone());
+    lines.extend(body.lines().map(|line| Line::raw(line.to_string())));
+    lines
+}
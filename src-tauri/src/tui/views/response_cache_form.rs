@@ -0,0 +1,103 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField, TextInput};
+use cc_switch_lib::ResponseCacheConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Ttl,
+    MaxEntries,
+    MaxSize,
+}
+
+/// 响应缓存弹窗：相同的非流式请求在有效期内直接返回缓存的响应
+pub struct ResponseCacheForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl ResponseCacheForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &ResponseCacheConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::Enabled,
+                FormField::toggle(
+                    "Serve repeated non-streaming requests from cache",
+                    config.enabled,
+                ),
+            ),
+            (Field::Ttl, FormField::number("TTL (s)", config.ttl_seconds)),
+            (
+                Field::MaxEntries,
+                FormField::number("Max entries", config.max_entries),
+            ),
+            (
+                Field::MaxSize,
+                FormField::number("Max size (KB)", config.max_size_kb),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的缓存设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<ResponseCacheConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => match self.parse() {
+                Ok(config) => return Some(config),
+                Err(e) => self.form.set_error(e),
+            },
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<ResponseCacheConfig, String> {
+        let positive = |field: Field| {
+            let input: &TextInput = self.form.input(field);
+            match input.value.trim().parse::<u64>() {
+                Ok(v) if v > 0 => Ok(v),
+                _ => Err(format!("{} must be a positive number", input.label)),
+            }
+        };
+        Ok(ResponseCacheConfig {
+            enabled: self.form.is_on(Field::Enabled),
+            ttl_seconds: positive(Field::Ttl)?,
+            max_entries: positive(Field::MaxEntries)? as usize,
+            max_size_kb: positive(Field::MaxSize)?,
+        })
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Response cache",
+            "Cache is cleared when turned off",
+        );
+    }
+}
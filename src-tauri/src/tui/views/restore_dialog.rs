@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{Local, TimeZone};
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+
+use super::{centered_rect, Theme};
+use cc_switch_lib::{AppState, BackupFile, BackupSummary, Database};
+
+/// 恢复备份弹窗：选择备份并查看其内容，Enter 后交由调用方确认并恢复
+pub struct RestoreDialog {
+    state: Arc<AppState>,
+    pub visible: bool,
+    backups: Vec<BackupFile>,
+    table_state: TableState,
+    /// 已读取的备份摘要，读取失败时保存错误信息
+    summaries: HashMap<PathBuf, Result<BackupSummary, String>>,
+    message: Option<String>,
+}
+
+impl RestoreDialog {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            backups: Vec::new(),
+            table_state: TableState::default(),
+            summaries: HashMap::new(),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.summaries.clear();
+        self.message = None;
+        match Database::list_backups() {
+            Ok(backups) => self.backups = backups,
+            Err(e) => {
+                self.backups = Vec::new();
+                self.message = Some(e.to_string());
+            }
+        }
+        self.table_state
+            .select((!self.backups.is_empty()).then_some(0));
+        self.load_selected_summary();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    fn selected(&self) -> Option<&BackupFile> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.backups.get(i))
+    }
+
+    fn load_selected_summary(&mut self) {
+        let Some(path) = self.selected().map(|b| b.path.clone()) else {
+            return;
+        };
+        if !self.summaries.contains_key(&path) {
+            let summary = self
+                .state
+                .db
+                .inspect_backup(&path)
+                .map_err(|e| e.to_string());
+            self.summaries.insert(path, summary);
+        }
+    }
+
+    /// 按 Enter 时返回选中的备份，内容无法读取的备份不可恢复
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<BackupFile> {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            KeyCode::Down | KeyCode::Char('j') => self.select_offset(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select_offset(-1),
+            KeyCode::Enter => {
+                let backup = self.selected()?.clone();
+                if matches!(self.summaries.get(&backup.path), Some(Ok(_))) {
+                    return Some(backup);
+                }
+                self.message = Some("This backup cannot be read".to_string());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn select_offset(&mut self, offset: isize) {
+        if self.backups.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, self.backups.len() as isize - 1);
+        self.table_state.select(Some(next as usize));
+        self.message = None;
+        self.load_selected_summary();
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(80, 24.min(frame.area().height), frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Restore from backup")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(4),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .split(area.inner(Margin::new(1, 1)));
+
+        if self.backups.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No backups found.").style(theme.inactive),
+                chunks[0],
+            );
+        } else {
+            let rows: Vec<Row> = self
+                .backups
+                .iter()
+                .map(|backup| {
+                    Row::new(vec![
+                        format_time(backup.modified_at),
+                        if backup.automatic {
+                            "scheduled"
+                        } else {
+                            "pre-import"
+                        }
+                        .to_string(),
+                        backup.name.clone(),
+                    ])
+                })
+                .collect();
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(19),
+                    Constraint::Length(11),
+                    Constraint::Min(10),
+                ],
+            )
+            .header(Row::new(vec!["Created", "Kind", "File"]).style(theme.title))
+            .highlight_style(theme.selected);
+            frame.render_stateful_widget(table, chunks[0], &mut self.table_state);
+        }
+
+        let detail = match (&self.message, self.selected()) {
+            (Some(msg), _) => Paragraph::new(msg.as_str()).style(theme.error),
+            (None, Some(backup)) => match self.summaries.get(&backup.path) {
+                Some(Ok(summary)) => Paragraph::new(format!(
+                    "Created {}\n{} provider(s), {} MCP server(s), {} prompt(s) — schema v{}",
+                    format_time(backup.modified_at),
+                    summary.providers,
+                    summary.mcp_servers,
+                    summary.prompts,
+                    summary.schema_version
+                ))
+                .style(theme.normal),
+                Some(Err(e)) => {
+                    Paragraph::new(format!("Cannot read backup: {e}")).style(theme.error)
+                }
+                None => Paragraph::new(""),
+            },
+            (None, None) => Paragraph::new(""),
+        };
+        frame.render_widget(detail, chunks[1]);
+
+        frame.render_widget(
+            Paragraph::new("j/k:Navigate  Enter:Restore  q/Esc:Close").style(theme.inactive),
+            chunks[2],
+        );
+    }
+}
+
+fn format_time(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
@@ -1,37 +1,570 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crossterm::event::KeyCode;
+use chrono::{Local, TimeZone};
+use log::LevelFilter;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
 
 use super::{Theme, View};
-use cc_switch_lib::AppState;
+use crate::tui::keymap::Action;
+use crate::tui::lock;
+use crate::tui::logging::{self, LogDestination};
+use crate::tui::theme::ThemePreset;
+use cc_switch_lib::{
+    AccessLogConfig, AppError, AppState, AutoBackupConfig, BackupFile, Database,
+    DEFAULT_REQUEST_HISTORY_LIMIT, DESKTOP_NOTIFICATIONS_AVAILABLE,
+};
+
+/// 数据库 settings 表中保存 TUI 主题预设的键
+const THEME_SETTING_KEY: &str = "tui_theme";
+
+/// 数据库 settings 表中保存自动刷新间隔（秒）的键
+const AUTO_REFRESH_SETTING_KEY: &str = "tui_auto_refresh_secs";
+
+/// 数据库 settings 表中保存日志级别的键
+const LOG_LEVEL_SETTING_KEY: &str = "tui_log_level";
+
+/// 数据库 settings 表中保存日志输出位置的键
+const LOG_DESTINATION_SETTING_KEY: &str = "tui_log_destination";
+
+/// 数据库 settings 表中保存切换供应商前是否预览改动的键
+const SWITCH_PREVIEW_SETTING_KEY: &str = "tui_switch_preview";
+
+/// 数据库 settings 表中保存主密码哈希的键
+const MASTER_PASSWORD_SETTING_KEY: &str = "tui_master_password";
+
+/// 数据库 settings 表中保存空闲锁屏时长（分钟）的键
+const IDLE_LOCK_SETTING_KEY: &str = "tui_idle_lock_minutes";
+
+/// 可循环选择的空闲锁屏时长（分钟），0 表示关闭
+const IDLE_LOCK_CHOICES: [u64; 5] = [0, 5, 15, 30, 60];
+
+/// 备份列表的重新读取间隔，后台定时备份完成后无需手动刷新
+const BACKUP_LIST_REFRESH: Duration = Duration::from_secs(10);
+
+/// 可循环选择的自动刷新间隔（秒），0 表示关闭
+const AUTO_REFRESH_CHOICES: [u64; 5] = [0, 5, 10, 30, 60];
+
+/// 可循环选择的请求历史保留条数，0 表示不记录
+const HISTORY_LIMIT_CHOICES: [usize; 5] = [0, 100, 500, 1000, 5000];
+
+/// 可循环选择的请求捕获条数，0 表示关闭
+const CAPTURE_LIMIT_CHOICES: [usize; 4] = [0, 10, 50, 100];
+
+/// 可循环选择的访问日志单文件大小上限（MB）
+const ACCESS_LOG_SIZE_CHOICES: [u64; 4] = [1, 10, 50, 100];
 
 pub struct SettingsView {
     state: Arc<AppState>,
+    theme_preset: ThemePreset,
+    auto_refresh_secs: u64,
+    history_limit: usize,
+    capture_limit: usize,
+    log_level: LevelFilter,
+    log_destination: LogDestination,
+    switch_preview: bool,
+    access_log: AccessLogConfig,
+    /// 故障转移切换供应商时是否弹出桌面通知
+    desktop_notifications: bool,
+    /// 主密码哈希，未设置时为 None
+    master_password: Option<String>,
+    /// 无输入多少分钟后锁屏，0 表示关闭
+    idle_lock_minutes: u64,
+    auto_backup: AutoBackupConfig,
+    /// backups 目录中的备份，最新的在前
+    backups: Vec<BackupFile>,
+    backups_loaded_at: Option<Instant>,
 }
 
 impl SettingsView {
     pub fn new(state: Arc<AppState>) -> Self {
-        Self { state }
+        let theme_preset = state
+            .db
+            .get_setting(THEME_SETTING_KEY)
+            .ok()
+            .flatten()
+            .and_then(|name| ThemePreset::from_name(&name))
+            .unwrap_or_default();
+        let auto_refresh_secs = state
+            .db
+            .get_setting(AUTO_REFRESH_SETTING_KEY)
+            .ok()
+            .flatten()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(0);
+        let history_limit = state
+            .db
+            .get_request_history_limit()
+            .unwrap_or(DEFAULT_REQUEST_HISTORY_LIMIT);
+        let capture_limit = state.db.get_request_capture_limit().unwrap_or(0);
+        let log_level = state
+            .db
+            .get_setting(LOG_LEVEL_SETTING_KEY)
+            .ok()
+            .flatten()
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(LevelFilter::Info);
+        let log_destination = state
+            .db
+            .get_setting(LOG_DESTINATION_SETTING_KEY)
+            .ok()
+            .flatten()
+            .and_then(|name| LogDestination::from_name(&name))
+            .unwrap_or_default();
+        let switch_preview = state
+            .db
+            .get_setting(SWITCH_PREVIEW_SETTING_KEY)
+            .ok()
+            .flatten()
+            .is_some_and(|value| value == "true");
+        let access_log = state.db.get_access_log_config().unwrap_or_default();
+        let desktop_notifications = state
+            .db
+            .get_failover_desktop_notifications()
+            .unwrap_or(true);
+        let master_password = state
+            .db
+            .get_setting(MASTER_PASSWORD_SETTING_KEY)
+            .ok()
+            .flatten()
+            .filter(|hash| !hash.is_empty());
+        let idle_lock_minutes = state
+            .db
+            .get_setting(IDLE_LOCK_SETTING_KEY)
+            .ok()
+            .flatten()
+            .and_then(|minutes| minutes.parse().ok())
+            .unwrap_or(0);
+        let auto_backup = state.db.get_auto_backup_config().unwrap_or_default();
+        Self {
+            state,
+            theme_preset,
+            auto_refresh_secs,
+            history_limit,
+            capture_limit,
+            log_level,
+            log_destination,
+            switch_preview,
+            access_log,
+            desktop_notifications,
+            master_password,
+            idle_lock_minutes,
+            auto_backup,
+            backups: Vec::new(),
+            backups_loaded_at: None,
+        }
+    }
+
+    /// 按已保存的设置应用日志级别与输出位置
+    pub fn apply_logging(&self) -> Result<(), String> {
+        logging::set_level(self.log_level);
+        logging::set_destination(self.log_destination)
+    }
+
+    pub fn theme_preset(&self) -> ThemePreset {
+        self.theme_preset
+    }
+
+    /// 切换到下一个主题预设并持久化到数据库
+    pub fn cycle_theme(&mut self) -> Result<ThemePreset, AppError> {
+        let next = self.theme_preset.next();
+        self.set_theme_preset(next)?;
+        Ok(next)
+    }
+
+    /// 设置主题预设并持久化到数据库
+    pub fn set_theme_preset(&mut self, preset: ThemePreset) -> Result<(), AppError> {
+        self.state
+            .db
+            .set_setting(THEME_SETTING_KEY, preset.name())?;
+        self.theme_preset = preset;
+        Ok(())
+    }
+
+    /// 自动刷新间隔，关闭时为 None
+    pub fn auto_refresh_interval(&self) -> Option<Duration> {
+        (self.auto_refresh_secs > 0).then(|| Duration::from_secs(self.auto_refresh_secs))
+    }
+
+    /// 切换到下一个自动刷新间隔并持久化到数据库
+    pub fn cycle_auto_refresh(&mut self) -> Result<u64, AppError> {
+        let next = AUTO_REFRESH_CHOICES
+            .iter()
+            .copied()
+            .find(|&secs| secs > self.auto_refresh_secs)
+            .unwrap_or(AUTO_REFRESH_CHOICES[0]);
+        self.state
+            .db
+            .set_setting(AUTO_REFRESH_SETTING_KEY, &next.to_string())?;
+        self.auto_refresh_secs = next;
+        Ok(next)
+    }
+
+    /// 切换到下一个请求历史保留条数并持久化到数据库
+    pub fn cycle_history_limit(&mut self) -> Result<usize, AppError> {
+        let next = HISTORY_LIMIT_CHOICES
+            .iter()
+            .copied()
+            .find(|&limit| limit > self.history_limit)
+            .unwrap_or(HISTORY_LIMIT_CHOICES[0]);
+        self.state.db.set_request_history_limit(next)?;
+        self.history_limit = next;
+        Ok(next)
+    }
+
+    /// 切换到下一个请求捕获条数并持久化到数据库
+    pub fn cycle_capture_limit(&mut self) -> Result<usize, AppError> {
+        let next = CAPTURE_LIMIT_CHOICES
+            .iter()
+            .copied()
+            .find(|&limit| limit > self.capture_limit)
+            .unwrap_or(CAPTURE_LIMIT_CHOICES[0]);
+        self.state.db.set_request_capture_limit(next)?;
+        self.capture_limit = next;
+        Ok(next)
+    }
+
+    /// 切换到下一个日志级别，立即生效并持久化到数据库
+    pub fn cycle_log_level(&mut self) -> Result<LevelFilter, AppError> {
+        let next = logging::next_level(self.log_level);
+        self.state
+            .db
+            .set_setting(LOG_LEVEL_SETTING_KEY, &next.to_string().to_lowercase())?;
+        logging::set_level(next);
+        self.log_level = next;
+        Ok(next)
+    }
+
+    /// 切换到下一个日志输出位置，立即生效并持久化到数据库
+    pub fn cycle_log_destination(&mut self) -> Result<LogDestination, AppError> {
+        let next = self.log_destination.next();
+        logging::set_destination(next).map_err(AppError::Message)?;
+        self.state
+            .db
+            .set_setting(LOG_DESTINATION_SETTING_KEY, next.name())?;
+        self.log_destination = next;
+        Ok(next)
+    }
+
+    /// 切换供应商前是否先预览 live 配置改动
+    pub fn switch_preview(&self) -> bool {
+        self.switch_preview
+    }
+
+    /// 开关切换预览并持久化到数据库
+    pub fn toggle_switch_preview(&mut self) -> Result<bool, AppError> {
+        let next = !self.switch_preview;
+        self.state
+            .db
+            .set_setting(SWITCH_PREVIEW_SETTING_KEY, &next.to_string())?;
+        self.switch_preview = next;
+        Ok(next)
+    }
+
+    /// 开关故障转移桌面通知并持久化到数据库
+    pub fn toggle_desktop_notifications(&mut self) -> Result<bool, AppError> {
+        let next = !self.desktop_notifications;
+        self.state.db.set_failover_desktop_notifications(next)?;
+        self.desktop_notifications = next;
+        Ok(next)
+    }
+
+    pub fn has_master_password(&self) -> bool {
+        self.master_password.is_some()
+    }
+
+    /// 校验主密码，未设置主密码时始终通过
+    pub fn verify_master_password(&self, password: &str) -> bool {
+        self.master_password
+            .as_deref()
+            .is_none_or(|hash| lock::verify_password(password, hash))
+    }
+
+    /// 设置主密码，传入 None 时清除主密码并关闭空闲锁屏
+    pub fn set_master_password(&mut self, password: Option<&str>) -> Result<(), AppError> {
+        match password {
+            Some(password) => {
+                let hash = lock::hash_password(password).map_err(AppError::Message)?;
+                self.state
+                    .db
+                    .set_setting(MASTER_PASSWORD_SETTING_KEY, &hash)?;
+                self.master_password = Some(hash);
+            }
+            None => {
+                self.state.db.set_setting(MASTER_PASSWORD_SETTING_KEY, "")?;
+                self.state.db.set_setting(IDLE_LOCK_SETTING_KEY, "0")?;
+                self.master_password = None;
+                self.idle_lock_minutes = 0;
+            }
+        }
+        Ok(())
     }
 
-    pub async fn handle_key(&mut self, _key: KeyCode) {
+    /// 空闲锁屏时长，关闭或未设置主密码时为 None
+    pub fn idle_lock_timeout(&self) -> Option<Duration> {
+        (self.idle_lock_minutes > 0 && self.master_password.is_some())
+            .then(|| Duration::from_secs(self.idle_lock_minutes * 60))
+    }
+
+    /// 切换到下一个空闲锁屏时长并持久化到数据库，需先设置主密码
+    pub fn cycle_idle_lock(&mut self) -> Result<u64, AppError> {
+        if self.master_password.is_none() {
+            return Err(AppError::Message("set a master password first".to_string()));
+        }
+        let next = IDLE_LOCK_CHOICES
+            .iter()
+            .copied()
+            .find(|&minutes| minutes > self.idle_lock_minutes)
+            .unwrap_or(IDLE_LOCK_CHOICES[0]);
+        self.state
+            .db
+            .set_setting(IDLE_LOCK_SETTING_KEY, &next.to_string())?;
+        self.idle_lock_minutes = next;
+        Ok(next)
+    }
+
+    pub fn auto_backup(&self) -> &AutoBackupConfig {
+        &self.auto_backup
+    }
+
+    /// 保存定时备份设置，后台任务下一次调度时生效
+    pub fn save_auto_backup(&mut self, config: AutoBackupConfig) -> Result<(), AppError> {
+        self.state.db.set_auto_backup_config(config)?;
+        self.auto_backup = config;
+        self.backups_loaded_at = None;
+        Ok(())
+    }
+
+    fn reload_backups_if_stale(&mut self) {
+        if self
+            .backups_loaded_at
+            .is_some_and(|at| at.elapsed() < BACKUP_LIST_REFRESH)
+        {
+            return;
+        }
+        self.backups_loaded_at = Some(Instant::now());
+        match Database::list_backups() {
+            Ok(backups) => self.backups = backups,
+            Err(e) => log::warn!("读取备份列表失败: {e}"),
+        }
+    }
+
+    fn render_backups(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Backups ({})", Database::backups_dir().display()));
+        if self.backups.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No backups yet.")
+                    .style(theme.inactive)
+                    .block(block),
+                area,
+            );
+            return;
+        }
+
+        let header = Row::new(vec!["Time", "Kind", "Size", "File"]).style(theme.title);
+        let rows: Vec<Row> = self
+            .backups
+            .iter()
+            .map(|backup| {
+                let time = Local
+                    .timestamp_opt(backup.modified_at, 0)
+                    .single()
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                Row::new(vec![
+                    time,
+                    if backup.automatic {
+                        "scheduled"
+                    } else {
+                        "pre-import"
+                    }
+                    .to_string(),
+                    format_size(backup.size_bytes),
+                    backup.name.clone(),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Length(11),
+                Constraint::Length(10),
+                Constraint::Min(10),
+            ],
+        )
+        .header(header)
+        .block(block);
+        frame.render_widget(table, area);
+    }
+
+    /// 开关代理访问日志，立即生效并持久化到数据库
+    pub fn toggle_access_log(&mut self) -> Result<bool, AppError> {
+        let next = AccessLogConfig {
+            enabled: !self.access_log.enabled,
+            ..self.access_log
+        };
+        self.save_access_log(next)?;
+        Ok(next.enabled)
+    }
+
+    /// 切换到下一个访问日志轮转大小并持久化到数据库
+    pub fn cycle_access_log_size(&mut self) -> Result<u64, AppError> {
+        let max_size_mb = ACCESS_LOG_SIZE_CHOICES
+            .iter()
+            .copied()
+            .find(|&mb| mb > self.access_log.max_size_mb)
+            .unwrap_or(ACCESS_LOG_SIZE_CHOICES[0]);
+        self.save_access_log(AccessLogConfig {
+            max_size_mb,
+            ..self.access_log
+        })?;
+        Ok(max_size_mb)
+    }
+
+    fn save_access_log(&mut self, config: AccessLogConfig) -> Result<(), AppError> {
+        self.state
+            .proxy_service
+            .set_access_log_config(config)
+            .map_err(AppError::Message)?;
+        self.access_log = config;
+        Ok(())
+    }
+
+    pub async fn handle_action(&mut self, _action: Action) {
         // TODO: Implement settings actions
     }
 }
 
 impl View for SettingsView {
     fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
-        let text = "Settings\n\n\
+        let text = format!(
+            "Settings\n\n\
+            [T] Theme: {}\n\
+            [R] Auto refresh: {}\n\
+            [H] Request history: {}\n\
+            [C] Request capture: {}\n\
+            [L] Log level: {}\n\
+            [O] Log output: {}\n\
+            [V] Preview provider switch: {}\n\
+            [A] Access log: {}\n\
+            [Z] Access log rotation: {} MB\n\
+            [N] Failover desktop notifications: {}\n\
+            [M] Master password: {}\n\
+            [X] Idle lock: {}\n\
+            [B] Automatic backups: {}\n\
+            [U] Restore from backup\n\
+            [D] Database maintenance\n\
             [E] Export configuration\n\
-            [I] Import configuration\n\n\
-            (More settings coming soon)";
+            [I] Import configuration\n\
+            [Shift+E] Import providers from environment\n\n\
+            (More settings coming soon)",
+            self.theme_preset.name(),
+            auto_refresh_label(self.auto_refresh_secs),
+            history_limit_label(self.history_limit),
+            history_limit_label(self.capture_limit),
+            self.log_level.to_string().to_lowercase(),
+            log_destination_label(self.log_destination),
+            if self.switch_preview { "on" } else { "off" },
+            access_log_label(&self.state, self.access_log.enabled),
+            self.access_log.max_size_mb,
+            desktop_notifications_label(self.desktop_notifications),
+            if self.master_password.is_some() {
+                "set"
+            } else {
+                "not set"
+            },
+            idle_lock_label(self.idle_lock_minutes),
+            auto_backup_label(&self.auto_backup)
+        );
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(24), Constraint::Min(4)])
+            .split(area);
 
         let paragraph = Paragraph::new(text)
             .style(theme.normal)
             .block(Block::default().borders(Borders::ALL).title("Settings"));
+        frame.render_widget(paragraph, chunks[0]);
+
+        self.reload_backups_if_stale();
+        self.render_backups(frame, chunks[1], theme);
+    }
+}
+
+/// 文件大小的显示文本
+pub(super) fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{b} B"),
+    }
+}
+
+/// 定时备份设置的显示文本
+pub fn auto_backup_label(config: &AutoBackupConfig) -> String {
+    if config.enabled {
+        format!("every {}h, keep {}", config.interval_hours, config.retain)
+    } else {
+        "off".to_string()
+    }
+}
+
+/// 桌面通知开关的显示文本，当前构建不支持时注明
+pub fn desktop_notifications_label(enabled: bool) -> String {
+    let state = if enabled { "on" } else { "off" };
+    if DESKTOP_NOTIFICATIONS_AVAILABLE {
+        state.to_string()
+    } else {
+        format!("{state} (build with the desktop-notifications feature)")
+    }
+}
+
+/// 空闲锁屏时长的显示文本
+pub fn idle_lock_label(minutes: u64) -> String {
+    if minutes == 0 {
+        "off".to_string()
+    } else {
+        format!("after {minutes} min idle")
+    }
+}
+
+/// 自动刷新间隔的显示文本
+pub fn auto_refresh_label(secs: u64) -> String {
+    if secs == 0 {
+        "off".to_string()
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// 请求历史保留条数的显示文本
+pub fn history_limit_label(limit: usize) -> String {
+    if limit == 0 {
+        "off".to_string()
+    } else {
+        format!("last {limit}")
+    }
+}
+
+/// 访问日志开关的显示文本，开启时附带文件路径
+pub fn access_log_label(state: &AppState, enabled: bool) -> String {
+    if enabled {
+        format!("on ({})", state.proxy_service.access_log().path().display())
+    } else {
+        "off".to_string()
+    }
+}
 
-        frame.render_widget(paragraph, area);
+/// 日志输出位置的显示文本，输出到文件时附带文件路径
+pub fn log_destination_label(destination: LogDestination) -> String {
+    match (destination, logging::log_file_path()) {
+        (LogDestination::File, Some(path)) => format!("file ({})", path.display()),
+        _ => destination.name().to_string(),
     }
 }
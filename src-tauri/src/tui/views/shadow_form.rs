@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider, ShadowConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Target,
+    Percent,
+}
+
+/// 流量镜像弹窗：选择镜像目标供应商与镜像比例，目标为空表示关闭
+pub struct ShadowForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    providers: Vec<Provider>,
+    /// 当前选中的目标，None 表示关闭镜像
+    target: Option<usize>,
+    percent: TextInput,
+    field: Field,
+    message: Option<String>,
+}
+
+impl ShadowForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            providers: Vec::new(),
+            target: None,
+            percent: TextInput::new("Percent"),
+            field: Field::Target,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType) {
+        let app = app_type.as_str();
+        let config = self.state.db.get_shadow_config(app).unwrap_or_default();
+        self.providers = self
+            .state
+            .db
+            .get_all_providers(app)
+            .map(|providers| providers.into_values().collect())
+            .unwrap_or_default();
+        self.target = config
+            .provider_id
+            .as_deref()
+            .and_then(|id| self.providers.iter().position(|p| p.id == id));
+        let percent = if config.percent == 0 {
+            10
+        } else {
+            config.percent
+        };
+        self.percent = TextInput::with_value("Percent", &percent.to_string());
+        self.app_type = app_type;
+        self.field = Field::Target;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.providers.clear();
+    }
+
+    pub fn app_type(&self) -> &AppType {
+        &self.app_type
+    }
+
+    /// 在「关闭」与各供应商之间循环切换目标
+    fn cycle_target(&mut self, forward: bool) {
+        let slots = self.providers.len() + 1;
+        let current = self.target.map_or(0, |i| i + 1);
+        let next = if forward {
+            (current + 1) % slots
+        } else {
+            (current + slots - 1) % slots
+        };
+        self.target = next.checked_sub(1);
+    }
+
+    /// 返回 true 表示设置已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match (self.field, key) {
+            (_, KeyCode::Esc) => self.close(),
+            (_, KeyCode::Enter) => return self.save(),
+            (_, KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down) => {
+                self.field = match self.field {
+                    Field::Target => Field::Percent,
+                    Field::Percent => Field::Target,
+                }
+            }
+            (Field::Target, KeyCode::Char(' ') | KeyCode::Right) => self.cycle_target(true),
+            (Field::Target, KeyCode::Left) => self.cycle_target(false),
+            (Field::Percent, KeyCode::Backspace) => self.percent.backspace(),
+            (Field::Percent, KeyCode::Delete) => self.percent.delete(),
+            (Field::Percent, KeyCode::Left) => self.percent.move_left(),
+            (Field::Percent, KeyCode::Right) => self.percent.move_right(),
+            (Field::Percent, KeyCode::Home) => self.percent.home(),
+            (Field::Percent, KeyCode::End) => self.percent.end(),
+            (Field::Percent, KeyCode::Char(c)) if c.is_ascii_digit() => self.percent.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let percent = match self.percent.value.trim().parse::<u32>() {
+            Ok(v) if v <= 100 => v,
+            _ => {
+                self.message = Some("Percent must be between 0 and 100".to_string());
+                return false;
+            }
+        };
+        let config = match self.target {
+            Some(i) => ShadowConfig {
+                provider_id: Some(self.providers[i].id.clone()),
+                percent,
+            },
+            None => ShadowConfig::default(),
+        };
+        match self
+            .state
+            .db
+            .set_shadow_config(self.app_type.as_str(), &config)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 10, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Traffic shadowing — {}", self.app_type.as_str()))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 6])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        let target = match self.target {
+            Some(i) => format!("◀ {} ▶", self.providers[i].name),
+            None => "◀ off ▶".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(format!("Mirror to: {target}")).style(style(Field::Target)),
+            chunks[0],
+        );
+        frame.render_widget(
+            Paragraph::new(self.percent.display(self.field == Field::Percent))
+                .style(style(Field::Percent)),
+            chunks[1],
+        );
+        frame.render_widget(
+            Paragraph::new(
+                "Mirrored responses are discarded; only status and latency are recorded",
+            )
+            .style(theme.inactive),
+            chunks[2],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[3]);
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space/←/→:Target  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[5],
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField, TextInput};
+use cc_switch_lib::SizeLimitConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Request,
+    Response,
+}
+
+/// 大小上限弹窗：请求体与上游响应体的最大大小（KB），0 表示不限制
+pub struct SizeLimitForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl SizeLimitForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &SizeLimitConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::Request,
+                FormField::number("Max request (KB)", config.max_request_kb),
+            ),
+            (
+                Field::Response,
+                FormField::number("Max response (KB)", config.max_response_kb),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的大小上限
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<SizeLimitConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => match self.parse() {
+                Ok(config) => return Some(config),
+                Err(e) => self.form.set_error(e),
+            },
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn parse(&self) -> Result<SizeLimitConfig, String> {
+        let kb = |input: &TextInput| {
+            input
+                .value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a number (0 = unlimited)", input.label))
+        };
+        Ok(SizeLimitConfig {
+            max_request_kb: kb(self.form.input(Field::Request))?,
+            max_response_kb: kb(self.form.input(Field::Response))?,
+        })
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Size limits",
+            "Oversized requests get 413, oversized responses 502 (0 = unlimited)",
+        );
+    }
+}
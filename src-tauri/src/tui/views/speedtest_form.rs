@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider, SpeedtestMethod, SpeedtestProbe};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Method,
+    Timeout,
+    Model,
+}
+
+const FIELDS: [Field; 3] = [Field::Method, Field::Timeout, Field::Model];
+
+/// 测速探测弹窗：供应商的探测方式、超时（留空使用默认）与 completion 探测的模型
+pub struct SpeedtestForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    /// None 表示沿用预热后 GET 端点的默认测速
+    method: Option<SpeedtestMethod>,
+    timeout: TextInput,
+    model: TextInput,
+    field: Field,
+    message: Option<String>,
+}
+
+impl SpeedtestForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            method: None,
+            timeout: TextInput::new("Timeout (s)"),
+            model: TextInput::new("Model"),
+            field: Field::Method,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let probe = provider.meta.as_ref().and_then(|m| m.speedtest.clone());
+        self.method = probe.as_ref().map(|p| p.method);
+        let timeout = probe
+            .as_ref()
+            .and_then(|p| p.timeout_secs)
+            .map(|t| t.to_string())
+            .unwrap_or_default();
+        self.timeout = TextInput::with_value("Timeout (s)", &timeout);
+        self.model = TextInput::with_value(
+            "Model",
+            probe
+                .as_ref()
+                .and_then(|p| p.model.as_deref())
+                .unwrap_or(""),
+        );
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.field = Field::Method;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    fn cycle_method(&mut self) {
+        self.method = match self.method {
+            None => Some(SpeedtestMethod::ALL[0]),
+            Some(method) => {
+                let index = SpeedtestMethod::ALL
+                    .iter()
+                    .position(|m| *m == method)
+                    .unwrap_or(0);
+                SpeedtestMethod::ALL.get(index + 1).copied()
+            }
+        };
+    }
+
+    /// 返回 true 表示设置已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        let index = FIELDS.iter().position(|f| *f == self.field).unwrap_or(0);
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Down => self.field = FIELDS[(index + 1) % FIELDS.len()],
+            KeyCode::BackTab | KeyCode::Up => {
+                self.field = FIELDS[(index + FIELDS.len() - 1) % FIELDS.len()]
+            }
+            KeyCode::Enter => return self.save(),
+            code => {
+                let input = match self.field {
+                    Field::Method => {
+                        if matches!(code, KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right) {
+                            self.cycle_method();
+                        }
+                        return false;
+                    }
+                    Field::Timeout => &mut self.timeout,
+                    Field::Model => &mut self.model,
+                };
+                match code {
+                    KeyCode::Backspace => input.backspace(),
+                    KeyCode::Delete => input.delete(),
+                    KeyCode::Left => input.move_left(),
+                    KeyCode::Right => input.move_right(),
+                    KeyCode::Home => input.home(),
+                    KeyCode::End => input.end(),
+                    KeyCode::Char(c) if self.field == Field::Model || c.is_ascii_digit() => {
+                        input.insert(c)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let timeout = self.timeout.value.trim();
+        let timeout_secs = if timeout.is_empty() {
+            None
+        } else {
+            match timeout.parse::<u64>() {
+                Ok(secs) if secs > 0 => Some(secs),
+                _ => {
+                    self.message = Some("Timeout must be a positive number".to_string());
+                    return false;
+                }
+            }
+        };
+        let model = self.model.value.trim();
+        let probe = self.method.map(|method| SpeedtestProbe {
+            method,
+            timeout_secs,
+            model: (!model.is_empty()).then(|| model.to_string()),
+        });
+
+        let Some(mut provider) = self.provider.clone() else {
+            return false;
+        };
+        provider.meta.get_or_insert_with(Default::default).speedtest = probe;
+        match self
+            .state
+            .db
+            .save_provider(self.app_type.as_str(), &provider)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self
+            .provider
+            .as_ref()
+            .map(|p| p.name.as_str())
+            .unwrap_or("");
+
+        let area = centered_rect(64, 10, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Speedtest probe — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 6])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        let method = self
+            .method
+            .map(|m| m.label())
+            .unwrap_or("default (warm-up GET)");
+        frame.render_widget(
+            Paragraph::new(format!("Method: ◀ {method} ▶")).style(style(Field::Method)),
+            chunks[0],
+        );
+        let default_timeout = self
+            .method
+            .map(|m| format!("empty = {}s", m.default_timeout_secs()))
+            .unwrap_or_else(|| "empty = 8s".to_string());
+        for (i, (field, input, placeholder)) in [
+            (Field::Timeout, &self.timeout, default_timeout),
+            (
+                Field::Model,
+                &self.model,
+                "completion only, empty = app default".to_string(),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let value = if self.field == field {
+                input.display_value(true)
+            } else if input.value.is_empty() {
+                format!("({placeholder})")
+            } else {
+                input.value.clone()
+            };
+            frame.render_widget(
+                Paragraph::new(format!("{}: {value}", input.label)).style(style(field)),
+                chunks[i + 1],
+            );
+        }
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[3]);
+        } else {
+            frame.render_widget(
+                Paragraph::new("Used by the connectivity test and endpoint latency tests")
+                    .style(theme.inactive),
+                chunks[3],
+            );
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space:Change method  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[5],
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppType, SwitchBackConfig, SwitchBackMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Mode,
+    Window,
+}
+
+/// 切回首选供应商弹窗：切回方式与首选供应商需要连续健康的时长（秒）
+pub struct SwitchBackForm {
+    pub visible: bool,
+    app_type: AppType,
+    mode: SwitchBackMode,
+    window: TextInput,
+    field: Field,
+    message: Option<String>,
+}
+
+impl SwitchBackForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            app_type: AppType::Claude,
+            mode: SwitchBackMode::default(),
+            window: TextInput::new("Healthy for (s)"),
+            field: Field::Mode,
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType, config: &SwitchBackConfig) {
+        self.app_type = app_type;
+        self.mode = config.mode;
+        self.window = TextInput::with_value(
+            "Healthy for (s)",
+            &config.healthy_window_seconds.to_string(),
+        );
+        self.field = Field::Mode;
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    pub fn app_type(&self) -> &AppType {
+        &self.app_type
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的切回设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<SwitchBackConfig> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.field = match self.field {
+                    Field::Mode => Field::Window,
+                    Field::Window => Field::Mode,
+                }
+            }
+            KeyCode::Enter => match self.window.value.trim().parse::<u64>() {
+                Ok(seconds) => {
+                    return Some(SwitchBackConfig {
+                        mode: self.mode,
+                        healthy_window_seconds: seconds,
+                    })
+                }
+                Err(_) => self.message = Some(format!("{} must be a number", self.window.label)),
+            },
+            code => match self.field {
+                Field::Mode => {
+                    if matches!(code, KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right) {
+                        self.mode = self.mode.next();
+                    }
+                }
+                Field::Window => match code {
+                    KeyCode::Backspace => self.window.backspace(),
+                    KeyCode::Delete => self.window.delete(),
+                    KeyCode::Left => self.window.move_left(),
+                    KeyCode::Right => self.window.move_right(),
+                    KeyCode::Home => self.window.home(),
+                    KeyCode::End => self.window.end(),
+                    KeyCode::Char(c) if c.is_ascii_digit() => self.window.insert(c),
+                    _ => {}
+                },
+            },
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(64, 9, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Switch back ({})", self.app_type.as_str()))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 5])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let style = |field: Field| {
+            if self.field == field {
+                theme.selected
+            } else {
+                theme.normal
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(format!("Mode: ◀ {} ▶", self.mode.label())).style(style(Field::Mode)),
+            chunks[0],
+        );
+        frame.render_widget(
+            Paragraph::new(self.window.display(self.field == Field::Window))
+                .style(style(Field::Window)),
+            chunks[1],
+        );
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        } else {
+            frame.render_widget(
+                Paragraph::new("Probes the first provider in the failover queue after a failover")
+                    .style(theme.inactive),
+                chunks[2],
+            );
+        }
+        frame.render_widget(
+            Paragraph::new("Tab:Next field  Space:Change mode  Enter:Save  Esc:Cancel")
+                .style(theme.inactive),
+            chunks[4],
+        );
+    }
+}
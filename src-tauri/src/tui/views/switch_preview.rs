@@ -0,0 +1,385 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use cc_switch_lib::{mask_secret, AppType, LiveFileChange, Provider};
+
+/// 未改动行超过该数量时折叠，只保留改动前后的上下文
+const CONTEXT_LINES: usize = 3;
+
+/// 行数乘积超过该值时不做逐行比对，整体显示为删除 + 新增
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// 键名包含这些片段的配置值在预览中脱敏
+const SECRET_KEY_MARKERS: [&str; 3] = ["KEY", "TOKEN", "SECRET"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+    /// 折叠的未改动行数
+    Skipped(usize),
+}
+
+/// 切换供应商前的改动预览：逐个文件显示切换前后的差异，确认后才执行切换
+///
+/// 演练模式下只显示将写入的完整配置，不提供确认切换
+pub struct SwitchPreview {
+    pub visible: bool,
+    dry_run: bool,
+    app_type: AppType,
+    provider_id: String,
+    provider_name: String,
+    changes: Vec<LiveFileChange>,
+    diffs: Vec<Vec<DiffLine>>,
+    file: usize,
+    scroll: usize,
+}
+
+impl SwitchPreview {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            dry_run: false,
+            app_type: AppType::Claude,
+            provider_id: String::new(),
+            provider_name: String::new(),
+            changes: Vec::new(),
+            diffs: Vec::new(),
+            file: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn open(&mut self, app_type: AppType, provider: &Provider, changes: Vec<LiveFileChange>) {
+        self.diffs = changes
+            .iter()
+            .map(|change| {
+                let before = change.before.as_deref().unwrap_or_default();
+                collapse_unchanged(line_diff(before, &change.after))
+            })
+            .collect();
+        self.dry_run = false;
+        self.show(app_type, provider, changes);
+    }
+
+    /// 演练模式：显示切换将写入的路径与完整内容
+    pub fn open_dry_run(
+        &mut self,
+        app_type: AppType,
+        provider: &Provider,
+        changes: Vec<LiveFileChange>,
+    ) {
+        self.diffs = changes
+            .iter()
+            .map(|change| {
+                change
+                    .after
+                    .lines()
+                    .map(|line| DiffLine::Same(line.to_string()))
+                    .collect()
+            })
+            .collect();
+        self.dry_run = true;
+        self.show(app_type, provider, changes);
+    }
+
+    fn show(&mut self, app_type: AppType, provider: &Provider, changes: Vec<LiveFileChange>) {
+        self.app_type = app_type;
+        self.provider_id = provider.id.clone();
+        self.provider_name = provider.name.clone();
+        // 默认显示第一个有改动的文件
+        self.file = changes.iter().position(|c| c.is_changed()).unwrap_or(0);
+        self.changes = changes;
+        self.scroll = 0;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.changes.clear();
+        self.diffs.clear();
+    }
+
+    /// 确认切换时返回应用类型与供应商 ID
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<(AppType, String)> {
+        let files = self.changes.len().max(1);
+        match key {
+            KeyCode::Char('y') | KeyCode::Enter if self.dry_run => self.close(),
+            KeyCode::Char('y') | KeyCode::Enter => {
+                let target = (self.app_type.clone(), self.provider_id.clone());
+                self.close();
+                return Some(target);
+            }
+            KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.file = (self.file + 1) % files;
+                self.scroll = 0;
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.file = (self.file + files - 1) % files;
+                self.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll_by(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            _ => {}
+        }
+        None
+    }
+
+    fn scroll_by(&mut self, lines: usize) {
+        let len = self.diffs.get(self.file).map_or(0, Vec::len);
+        self.scroll = (self.scroll + lines).min(len.saturating_sub(1));
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(90, frame.area().height.saturating_sub(2), frame.area());
+        frame.render_widget(Clear, area);
+        let title = if self.dry_run {
+            format!("Dry run — switching to {} would write", self.provider_name)
+        } else {
+            format!(
+                "Switch to {} — review live config changes",
+                self.provider_name
+            )
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(inner.inner(Margin::new(1, 0)));
+
+        let hint = if self.dry_run {
+            "Dry run: nothing is written  Tab:Next file  j/k:Scroll  Esc:Close"
+        } else {
+            "y/Enter:Switch  n/Esc:Cancel  Tab:Next file  j/k:Scroll"
+        };
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[3]);
+
+        let Some(change) = self.changes.get(self.file) else {
+            frame.render_widget(
+                Paragraph::new(
+                    "Proxy takeover is active: switching only changes the proxy target, no live files are written.",
+                )
+                .style(theme.warning),
+                chunks[2],
+            );
+            return;
+        };
+
+        let tabs: Vec<Span> = self
+            .changes
+            .iter()
+            .enumerate()
+            .map(|(i, change)| {
+                let name = change
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let status = match (&change.before, change.is_changed()) {
+                    (None, _) => "new",
+                    (Some(_), true) => "modified",
+                    (Some(_), false) => "unchanged",
+                };
+                let style = if i == self.file {
+                    theme.selected
+                } else {
+                    theme.normal
+                };
+                Span::styled(format!(" {name} ({status}) "), style)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(Line::from(tabs)), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(change.path.display().to_string()).style(theme.inactive),
+            chunks[1],
+        );
+
+        if !self.dry_run && !change.is_changed() {
+            frame.render_widget(
+                Paragraph::new("No changes").style(theme.inactive),
+                chunks[2],
+            );
+            return;
+        }
+        let lines: Vec<Line> = self.diffs[self.file]
+            .iter()
+            .skip(self.scroll)
+            .take(chunks[2].height as usize)
+            .map(|line| match line {
+                DiffLine::Same(text) => {
+                    Line::styled(format!("  {}", mask_secrets(text)), theme.normal)
+                }
+                DiffLine::Removed(text) => {
+                    Line::styled(format!("- {}", mask_secrets(text)), theme.error)
+                }
+                DiffLine::Added(text) => {
+                    Line::styled(format!("+ {}", mask_secrets(text)), theme.success)
+                }
+                DiffLine::Skipped(count) => {
+                    Line::styled(format!("  … {count} unchanged line(s)"), theme.inactive)
+                }
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), chunks[2]);
+    }
+}
+
+/// 基于最长公共子序列的逐行比对
+fn line_diff(before: &str, after: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let removed = |s: &&str| DiffLine::Removed(s.to_string());
+    let added = |s: &&str| DiffLine::Added(s.to_string());
+    if old.len() * new.len() > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(removed)
+            .chain(new.iter().map(added))
+            .collect();
+    }
+
+    // lcs[i][j]：old[i..] 与 new[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(removed(&old[i]));
+            i += 1;
+        } else {
+            diff.push(added(&new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(removed));
+    diff.extend(new[j..].iter().map(added));
+    diff
+}
+
+/// 折叠距离改动超过 `CONTEXT_LINES` 的未改动行
+fn collapse_unchanged(diff: Vec<DiffLine>) -> Vec<DiffLine> {
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= CONTEXT_LINES);
+
+    let mut out = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in diff.into_iter().enumerate() {
+        if matches!(line, DiffLine::Same(_)) && !near_change(i) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            out.push(DiffLine::Skipped(skipped));
+            skipped = 0;
+        }
+        out.push(line);
+    }
+    if skipped > 0 {
+        out.push(DiffLine::Skipped(skipped));
+    }
+    out
+}
+
+/// 以 [`mask_secret`] 显示 `"KEY": "value"`、`key = "value"` 与 `KEY=value` 形式中密钥字段的值
+fn mask_secrets(line: &str) -> String {
+    let Some(pos) = line.find([':', '=']) else {
+        return line.to_string();
+    };
+    let (key, rest) = line.split_at(pos + 1);
+    let key = key[..pos].trim().trim_matches('"').to_uppercase();
+    let value = rest.trim();
+    if value.is_empty()
+        || value.starts_with(['{', '['])
+        || !SECRET_KEY_MARKERS.iter().any(|m| key.contains(m))
+    {
+        return line.to_string();
+    }
+    let (value, comma) = match value.strip_suffix(',') {
+        Some(value) => (value, ","),
+        None => (value, ""),
+    };
+    let masked = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(secret) => format!("\"{}\"{comma}", mask_secret(secret)),
+        None => format!("{}{comma}", mask_secret(value)),
+    };
+    let indent = rest.len() - rest.trim_start().len();
+    format!("{}{}{masked}", &line[..=pos], &rest[..indent])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_diff_marks_changed_lines() {
+        let diff = line_diff("a\nb\nc", "a\nB\nc\nd");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Added("B".into()),
+                DiffLine::Same("c".into()),
+                DiffLine::Added("d".into()),
+            ]
+        );
+
+        let long: String = (0..20).map(|i| format!("line{i}\n")).collect();
+        let collapsed = collapse_unchanged(line_diff(&long, &long.replace("line10", "x")));
+        assert_eq!(collapsed.first(), Some(&DiffLine::Skipped(7)));
+        assert_eq!(collapsed.last(), Some(&DiffLine::Skipped(6)));
+    }
+
+    #[test]
+    fn mask_secrets_hides_credential_values() {
+        assert_eq!(
+            mask_secrets(r#"    "ANTHROPIC_AUTH_TOKEN": "sk-ant-123456","#),
+            r#"    "ANTHROPIC_AUTH_TOKEN": "sk-a...3456","#
+        );
+        assert_eq!(mask_secrets("GEMINI_API_KEY=abc"), "GEMINI_API_KEY=***");
+        assert_eq!(
+            mask_secrets(r#"base_url = "https://api.example.com""#),
+            r#"base_url = "https://api.example.com""#
+        );
+    }
+}
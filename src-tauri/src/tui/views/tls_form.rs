@@ -0,0 +1,97 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField, TextInput};
+use cc_switch_lib::ProxyTlsConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    CertPath,
+    KeyPath,
+}
+
+/// 代理 HTTPS 设置弹窗：证书与私钥路径留空时使用自签名证书
+pub struct TlsForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl TlsForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &ProxyTlsConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::Enabled,
+                FormField::toggle("Serve HTTPS", config.enabled),
+            ),
+            (
+                Field::CertPath,
+                FormField::text(
+                    "Certificate (PEM)",
+                    config.cert_path.as_deref().unwrap_or_default(),
+                ),
+            ),
+            (
+                Field::KeyPath,
+                FormField::text(
+                    "Private key (PEM)",
+                    config.key_path.as_deref().unwrap_or_default(),
+                ),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 时返回待保存的 HTTPS 设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<ProxyTlsConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => return Some(self.config()),
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn config(&self) -> ProxyTlsConfig {
+        let path = |input: &TextInput| {
+            let value = input.value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        ProxyTlsConfig {
+            enabled: self.form.is_on(Field::Enabled),
+            cert_path: path(self.form.input(Field::CertPath)),
+            key_path: path(self.form.input(Field::KeyPath)),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Proxy HTTPS",
+            "Leave paths empty for a self-signed cert (~/.cc-switch/tls)",
+        );
+    }
+}
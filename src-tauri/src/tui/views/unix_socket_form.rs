@@ -0,0 +1,91 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField};
+use cc_switch_lib::UnixSocketConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Enabled,
+    Path,
+    SocketOnly,
+}
+
+/// 代理 Unix socket 设置弹窗：路径留空时使用 ~/.cc-switch/proxy.sock
+pub struct UnixSocketForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl UnixSocketForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &UnixSocketConfig) {
+        self.form = Form::new(vec![
+            (
+                Field::Enabled,
+                FormField::toggle("Listen on a Unix socket", config.enabled),
+            ),
+            (
+                Field::Path,
+                FormField::text("Socket path", config.path.as_deref().unwrap_or_default()),
+            ),
+            (
+                Field::SocketOnly,
+                FormField::toggle(
+                    "Socket only (no TCP port; app takeover unavailable)",
+                    config.socket_only,
+                ),
+            ),
+        ]);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 时返回待保存的 Unix socket 设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<UnixSocketConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => return Some(self.config()),
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    fn config(&self) -> UnixSocketConfig {
+        let path = self.form.input(Field::Path).value.trim();
+        UnixSocketConfig {
+            enabled: self.form.is_on(Field::Enabled),
+            path: (!path.is_empty()).then(|| path.to_string()),
+            socket_only: self.form.is_on(Field::SocketOnly),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(60, self.form.height(), frame.area()),
+            "Proxy Unix Socket",
+            "Empty path = ~/.cc-switch/proxy.sock",
+        );
+    }
+}
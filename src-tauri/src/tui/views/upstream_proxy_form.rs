@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{parse_upstream_proxy, AppState, AppType, Provider, UPSTREAM_PROXY_DIRECT};
+
+/// 上游代理弹窗：编辑全局设置，或单个供应商的覆盖（留空沿用全局，`direct` 直连）
+pub struct UpstreamProxyForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    /// 正在编辑覆盖的供应商，None 表示编辑全局设置
+    provider: Option<Provider>,
+    url: TextInput,
+    message: Option<String>,
+}
+
+impl UpstreamProxyForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            url: TextInput::new("Proxy URL"),
+            message: None,
+        }
+    }
+
+    pub fn open_global(&mut self) -> Result<(), String> {
+        let url = self
+            .state
+            .db
+            .get_proxy_upstream_url()
+            .map_err(|e| e.to_string())?;
+        self.provider = None;
+        self.show(url.as_deref().unwrap_or(""));
+        Ok(())
+    }
+
+    pub fn open_provider(&mut self, provider: &Provider, app_type: AppType) {
+        let url = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.upstream_proxy.clone())
+            .unwrap_or_default();
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.show(&url);
+    }
+
+    fn show(&mut self, url: &str) {
+        self.url = TextInput::with_value("Proxy URL", url);
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    /// 返回 true 表示设置已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Backspace => self.url.backspace(),
+            KeyCode::Delete => self.url.delete(),
+            KeyCode::Left => self.url.move_left(),
+            KeyCode::Right => self.url.move_right(),
+            KeyCode::Home => self.url.home(),
+            KeyCode::End => self.url.end(),
+            KeyCode::Char(c) => self.url.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let value = self.url.value.trim();
+        let direct = value.eq_ignore_ascii_case(UPSTREAM_PROXY_DIRECT);
+        if direct && self.provider.is_none() {
+            self.message = Some("Leave empty to connect directly".to_string());
+            return false;
+        }
+        if !value.is_empty() && !direct {
+            if let Err(e) = parse_upstream_proxy(value) {
+                self.message = Some(e);
+                return false;
+            }
+        }
+        let url = (!value.is_empty()).then(|| value.to_string());
+
+        let result = match self.provider.clone() {
+            None => self.state.db.set_proxy_upstream_url(url.as_deref()),
+            Some(mut provider) => {
+                provider
+                    .meta
+                    .get_or_insert_with(Default::default)
+                    .upstream_proxy = url;
+                self.state
+                    .db
+                    .save_provider(self.app_type.as_str(), &provider)
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let (title, hint) = match &self.provider {
+            None => (
+                "Upstream proxy".to_string(),
+                "http(s)://, socks5:// or socks5h:// (empty = direct)",
+            ),
+            Some(provider) => (
+                format!("Upstream proxy — {}", provider.name),
+                "Overrides the global proxy (empty = use global, direct = no proxy)",
+            ),
+        };
+
+        let area = centered_rect(60, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.url.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), chunks[1]);
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
@@ -0,0 +1,496 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+
+use super::{Theme, View};
+use crate::tui::app::NavAction;
+use crate::tui::widgets::{loading_title, spinner_frame};
+use cc_switch_lib::{AppState, AppType, DailyUsage, ModelUsage, ProviderUsage};
+
+/// 用量统计的时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageRange {
+    #[default]
+    Today,
+    Week,
+    Month,
+}
+
+impl UsageRange {
+    /// 包含今天在内的天数
+    fn days(self) -> u32 {
+        match self {
+            Self::Today => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Today => "Today",
+            Self::Week => "7 days",
+            Self::Month => "30 days",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Today => Self::Week,
+            Self::Week => Self::Month,
+            Self::Month => Self::Today,
+        }
+    }
+}
+
+/// 用量表格的汇总维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageBreakdown {
+    #[default]
+    Providers,
+    Daily,
+    Models,
+}
+
+impl UsageBreakdown {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Providers => "by provider",
+            Self::Daily => "by day",
+            Self::Models => "by model",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Providers => Self::Daily,
+            Self::Daily => Self::Models,
+            Self::Models => Self::Providers,
+        }
+    }
+}
+
+/// 后台任务加载的用量数据
+pub struct UsageData {
+    range: UsageRange,
+    providers: Vec<ProviderUsage>,
+    daily: Vec<DailyUsage>,
+    models: Vec<ModelUsage>,
+}
+
+/// 按供应商、按天或按模型汇总代理流量：请求数、错误数、token（含缓存读写）与估算花费
+pub struct UsageView {
+    pub loading: bool,
+    range: UsageRange,
+    breakdown: UsageBreakdown,
+    providers: Vec<ProviderUsage>,
+    daily: Vec<DailyUsage>,
+    models: Vec<ModelUsage>,
+    table_state: TableState,
+}
+
+impl UsageView {
+    pub fn new() -> Self {
+        Self {
+            loading: false,
+            range: UsageRange::default(),
+            breakdown: UsageBreakdown::default(),
+            providers: Vec::new(),
+            daily: Vec::new(),
+            models: Vec::new(),
+            table_state: TableState::default(),
+        }
+    }
+
+    pub fn range(&self) -> UsageRange {
+        self.range
+    }
+
+    /// 切换到下一个时间范围，调用方需重新加载
+    pub fn cycle_range(&mut self) -> UsageRange {
+        self.range = self.range.next();
+        self.range
+    }
+
+    pub fn cycle_breakdown(&mut self) -> UsageBreakdown {
+        self.breakdown = self.breakdown.next();
+        self.table_state.select((self.row_count() > 0).then_some(0));
+        self.breakdown
+    }
+
+    pub fn load(state: &AppState, app_type: AppType, range: UsageRange) -> UsageData {
+        let days = range.days();
+        UsageData {
+            range,
+            providers: state
+                .db
+                .get_provider_usage(app_type.as_str(), days)
+                .unwrap_or_default(),
+            daily: state
+                .db
+                .get_daily_usage(app_type.as_str(), days)
+                .unwrap_or_default(),
+            models: state
+                .db
+                .get_model_usage(app_type.as_str(), days)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn apply(&mut self, data: UsageData) {
+        self.loading = false;
+        if data.range != self.range {
+            return;
+        }
+        self.providers = data.providers;
+        self.daily = data.daily;
+        self.models = data.models;
+
+        let len = self.row_count();
+        let selected = match self.table_state.selected() {
+            _ if len == 0 => None,
+            Some(i) => Some(i.min(len - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    fn row_count(&self) -> usize {
+        match self.breakdown {
+            UsageBreakdown::Providers => self.providers.len(),
+            UsageBreakdown::Daily => self.daily.len(),
+            UsageBreakdown::Models => self.models.len(),
+        }
+    }
+
+    fn total_cost(&self) -> f64 {
+        self.providers.iter().map(|u| u.cost_usd).sum()
+    }
+
+    pub fn navigate(&mut self, action: NavAction) {
+        if let Some(i) = action.apply(self.table_state.selected(), self.row_count()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    /// 将当前维度的表格导出为 CSV 文本
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        match self.breakdown {
+            UsageBreakdown::Providers => {
+                out.push_str(
+                    "provider_id,provider,requests,errors,input_tokens,output_tokens,cache_read_tokens,cache_creation_tokens,cost_usd\n",
+                );
+                for u in &self.providers {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{},{},{:.6}\n",
+                        csv_field(&u.provider_id),
+                        csv_field(&u.provider_name),
+                        u.request_count,
+                        u.error_count,
+                        u.input_tokens,
+                        u.output_tokens,
+                        u.cache_read_tokens,
+                        u.cache_creation_tokens,
+                        u.cost_usd
+                    ));
+                }
+            }
+            UsageBreakdown::Daily => {
+                out.push_str(
+                    "date,requests,errors,input_tokens,output_tokens,cache_read_tokens,cache_creation_tokens,cost_usd\n",
+                );
+                for u in &self.daily {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{},{:.6}\n",
+                        u.day,
+                        u.request_count,
+                        u.error_count,
+                        u.input_tokens,
+                        u.output_tokens,
+                        u.cache_read_tokens,
+                        u.cache_creation_tokens,
+                        u.cost_usd
+                    ));
+                }
+            }
+            UsageBreakdown::Models => {
+                out.push_str(
+                    "model,requests,input_tokens,output_tokens,cache_read_tokens,cache_creation_tokens,cost_usd\n",
+                );
+                for u in &self.models {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{:.6}\n",
+                        csv_field(&u.model),
+                        u.request_count,
+                        u.input_tokens,
+                        u.output_tokens,
+                        u.cache_read_tokens,
+                        u.cache_creation_tokens,
+                        u.cost_usd
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl View for UsageView {
+    fn render(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let title = format!(
+            "Usage — {} {} · est. {} ",
+            self.range.label(),
+            self.breakdown.label(),
+            format_cost(self.total_cost())
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(loading_title(&title, self.loading));
+
+        if self.loading && self.providers.is_empty() {
+            let placeholder = Paragraph::new(format!("{} Loading…", spinner_frame()))
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(placeholder, area);
+            return;
+        }
+
+        if self.row_count() == 0 {
+            let empty = Paragraph::new("No proxied requests in this period")
+                .style(theme.inactive)
+                .block(block);
+            frame.render_widget(empty, area);
+            return;
+        }
+
+        let (first_column, rows): (&str, Vec<Row>) = match self.breakdown {
+            UsageBreakdown::Providers => (
+                "Provider",
+                self.providers
+                    .iter()
+                    .map(|u| {
+                        usage_row(
+                            u.provider_name.clone(),
+                            u.request_count,
+                            u.error_count,
+                            [
+                                u.input_tokens,
+                                u.output_tokens,
+                                u.cache_read_tokens,
+                                u.cache_creation_tokens,
+                            ],
+                            u.cost_usd,
+                            theme,
+                        )
+                    })
+                    .collect(),
+            ),
+            UsageBreakdown::Daily => (
+                "Date",
+                self.daily
+                    .iter()
+                    .map(|u| {
+                        usage_row(
+                            u.day.clone(),
+                            u.request_count,
+                            u.error_count,
+                            [
+                                u.input_tokens,
+                                u.output_tokens,
+                                u.cache_read_tokens,
+                                u.cache_creation_tokens,
+                            ],
+                            u.cost_usd,
+                            theme,
+                        )
+                    })
+                    .collect(),
+            ),
+            UsageBreakdown::Models => return self.render_models(frame, area, block, theme),
+        };
+        let header = Row::new(vec![
+            first_column,
+            "Requests",
+            "Errors",
+            "Error %",
+            "Input",
+            "Output",
+            "Cache R",
+            "Cache W",
+            "Cost",
+        ])
+        .style(theme.title);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(16),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .block(block)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
+
+impl UsageView {
+    /// 模型维度展示缓存读写 token，便于观察 Claude prompt caching 的效果
+    fn render_models(&mut self, frame: &mut Frame, area: Rect, block: Block, theme: &Theme) {
+        let header = Row::new(vec![
+            "Model", "Requests", "Input", "Output", "Cache R", "Cache W", "Hit %", "Cost",
+        ])
+        .style(theme.title);
+        let rows: Vec<Row> = self
+            .models
+            .iter()
+            .map(|u| {
+                let hit_rate = u.cache_hit_rate();
+                let hit_style = if u.cache_read_tokens == 0 {
+                    theme.inactive
+                } else if hit_rate >= 50.0 {
+                    theme.success
+                } else {
+                    theme.normal
+                };
+                Row::new(vec![
+                    Line::from(u.model.clone()),
+                    Line::from(u.request_count.to_string()).alignment(Alignment::Right),
+                    Line::from(format_tokens(u.input_tokens)).alignment(Alignment::Right),
+                    Line::from(format_tokens(u.output_tokens)).alignment(Alignment::Right),
+                    Line::from(format_tokens(u.cache_read_tokens)).alignment(Alignment::Right),
+                    Line::from(format_tokens(u.cache_creation_tokens)).alignment(Alignment::Right),
+                    Line::styled(format!("{hit_rate:.1}%"), hit_style).alignment(Alignment::Right),
+                    Line::from(format_cost(u.cost_usd)).alignment(Alignment::Right),
+                ])
+                .style(theme.normal)
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .block(block)
+        .highlight_style(theme.selected);
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
+
+fn usage_row(
+    label: String,
+    requests: u64,
+    errors: u64,
+    tokens: [u64; 4],
+    cost_usd: f64,
+    theme: &Theme,
+) -> Row<'static> {
+    let [input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens] = tokens;
+    let error_rate = if requests == 0 {
+        0.0
+    } else {
+        errors as f64 * 100.0 / requests as f64
+    };
+    let error_style = if errors == 0 {
+        theme.normal
+    } else if error_rate < 5.0 {
+        theme.warning
+    } else {
+        theme.error
+    };
+    Row::new(vec![
+        Line::from(label),
+        Line::from(requests.to_string()).alignment(Alignment::Right),
+        Line::styled(errors.to_string(), error_style).alignment(Alignment::Right),
+        Line::styled(format!("{error_rate:.1}%"), error_style).alignment(Alignment::Right),
+        Line::from(format_tokens(input_tokens)).alignment(Alignment::Right),
+        Line::from(format_tokens(output_tokens)).alignment(Alignment::Right),
+        Line::from(format_tokens(cache_read_tokens)).alignment(Alignment::Right),
+        Line::from(format_tokens(cache_creation_tokens)).alignment(Alignment::Right),
+        Line::from(format_cost(cost_usd)).alignment(Alignment::Right),
+    ])
+    .style(theme.normal)
+}
+
+/// 以 k/M 为单位缩写 token 数
+pub(super) fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..=9_999 => tokens.to_string(),
+        10_000..=999_999 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.2}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+/// 小额花费保留更多小数位，便于区分廉价中转
+fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        format!("${cost:.4}")
+    } else {
+        format!("${cost:.2}")
+    }
+}
+
+/// 含逗号、引号或换行的字段按 RFC 4180 加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_tokens_abbreviates_large_counts() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(2_500_000), "2.50M");
+    }
+
+    #[test]
+    fn csv_export_quotes_provider_names() {
+        let mut view = UsageView::new();
+        view.apply(UsageData {
+            range: UsageRange::Today,
+            providers: vec![ProviderUsage {
+                provider_id: "p1".to_string(),
+                provider_name: "Relay, \"cheap\"".to_string(),
+                request_count: 3,
+                error_count: 1,
+                input_tokens: 200,
+                output_tokens: 80,
+                cache_read_tokens: 1000,
+                cache_creation_tokens: 0,
+                cost_usd: 0.0123,
+            }],
+            daily: Vec::new(),
+            models: Vec::new(),
+        });
+        assert_eq!(
+            view.to_csv(),
+            "provider_id,provider,requests,errors,input_tokens,output_tokens,cache_read_tokens,cache_creation_tokens,cost_usd\n\
+             p1,\"Relay, \"\"cheap\"\"\",3,1,200,80,1000,0,0.012300\n"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::command::expand_home;
+use crate::tui::widgets::TextInput;
+
+/// 用量导出弹窗：输入 CSV 目标路径
+pub struct UsageExportForm {
+    pub visible: bool,
+    path: TextInput,
+    message: Option<String>,
+}
+
+impl UsageExportForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            path: TextInput::new("Path"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self) {
+        let default_path = format!(
+            "~/cc-switch-usage-{}.csv",
+            Local::now().format("%Y%m%d-%H%M%S")
+        );
+        self.path = TextInput::with_value("Path", &default_path);
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 写入失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// 按 Enter 时返回导出路径
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<PathBuf> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                let path = self.path.value.trim();
+                if path.is_empty() {
+                    self.message = Some("Path cannot be empty".to_string());
+                } else {
+                    return Some(expand_home(path));
+                }
+            }
+            KeyCode::Backspace => self.path.backspace(),
+            KeyCode::Delete => self.path.delete(),
+            KeyCode::Left => self.path.move_left(),
+            KeyCode::Right => self.path.move_right(),
+            KeyCode::Home => self.path.home(),
+            KeyCode::End => self.path.end(),
+            KeyCode::Char(c) => self.path.insert(c),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let area = centered_rect(60, 7, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Export Usage (CSV)")
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 3])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let path_text = self.path.display(true);
+        frame.render_widget(Paragraph::new(path_text).style(theme.selected), chunks[0]);
+
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[1]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Export  Esc:Cancel").style(theme.inactive),
+            chunks[2],
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::{Form, FormEvent, FormField};
+use cc_switch_lib::{
+    format_webhook_targets, parse_webhook_targets, WebhookConfig, WebhookEventKind,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Targets,
+    Event(WebhookEventKind),
+}
+
+fn event_label(kind: WebhookEventKind) -> &'static str {
+    match kind {
+        WebhookEventKind::Failover => "Failover switched the current provider",
+        WebhookEventKind::CircuitOpen => "Circuit breaker opened",
+        WebhookEventKind::EndpointsDown => "All endpoints of a provider unhealthy",
+    }
+}
+
+/// Webhook 弹窗：通知地址（`[json|slack|discord=]URL`，逗号分隔）与需要通知的事件
+pub struct WebhookForm {
+    pub visible: bool,
+    form: Form<Field>,
+}
+
+impl WebhookForm {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            form: Form::new(Vec::new()),
+        }
+    }
+
+    pub fn open(&mut self, config: &WebhookConfig) {
+        let mut fields = vec![(
+            Field::Targets,
+            FormField::text("Webhook URLs", &format_webhook_targets(&config.targets)),
+        )];
+        fields.extend(WebhookEventKind::ALL.into_iter().map(|kind| {
+            (
+                Field::Event(kind),
+                FormField::toggle(event_label(kind), config.events.contains(&kind)),
+            )
+        }));
+        self.form = Form::new(fields);
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.form.set_error(message);
+    }
+
+    /// 按 Enter 且输入合法时返回待保存的 Webhook 设置
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<WebhookConfig> {
+        match self.form.handle_key(key) {
+            FormEvent::Cancel => self.close(),
+            FormEvent::Submit => {
+                match parse_webhook_targets(&self.form.input(Field::Targets).value) {
+                    Ok(targets) => {
+                        // 按固定顺序保存，与勾选先后无关
+                        let events = WebhookEventKind::ALL
+                            .into_iter()
+                            .filter(|kind| self.form.is_on(Field::Event(*kind)))
+                            .collect();
+                        return Some(WebhookConfig { targets, events });
+                    }
+                    Err(e) => self.form.set_error(e),
+                }
+            }
+            FormEvent::None => {}
+        }
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        self.form.render(
+            frame,
+            theme,
+            centered_rect(70, self.form.height(), frame.area()),
+            "Webhooks",
+            "Slack/Discord URLs are detected; prefix json=, slack= or discord= to override",
+        );
+    }
+}
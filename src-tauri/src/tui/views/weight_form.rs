@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::{centered_rect, Theme};
+use crate::tui::widgets::TextInput;
+use cc_switch_lib::{AppState, AppType, Provider};
+
+/// 故障转移队列权重弹窗：留空表示清除权重
+pub struct WeightForm {
+    state: Arc<AppState>,
+    pub visible: bool,
+    app_type: AppType,
+    provider: Option<Provider>,
+    weight: TextInput,
+    message: Option<String>,
+}
+
+impl WeightForm {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            visible: false,
+            app_type: AppType::Claude,
+            provider: None,
+            weight: TextInput::new("Weight"),
+            message: None,
+        }
+    }
+
+    pub fn open(&mut self, provider: &Provider, app_type: AppType) {
+        let value = provider.weight.map(|w| w.to_string()).unwrap_or_default();
+        self.weight = TextInput::with_value("Weight", &value);
+        self.app_type = app_type;
+        self.provider = Some(provider.clone());
+        self.message = None;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.provider = None;
+    }
+
+    /// 返回 true 表示权重已保存
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => return self.save(),
+            KeyCode::Backspace => self.weight.backspace(),
+            KeyCode::Delete => self.weight.delete(),
+            KeyCode::Left => self.weight.move_left(),
+            KeyCode::Right => self.weight.move_right(),
+            KeyCode::Home => self.weight.home(),
+            KeyCode::End => self.weight.end(),
+            KeyCode::Char(c) if c.is_ascii_digit() => self.weight.insert(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn save(&mut self) -> bool {
+        let Some(provider) = &self.provider else {
+            return false;
+        };
+        let value = self.weight.value.trim();
+        let weight = if value.is_empty() {
+            None
+        } else {
+            match value.parse::<u32>() {
+                Ok(w) => Some(w),
+                Err(_) => {
+                    self.message = Some("Weight must be a whole number".to_string());
+                    return false;
+                }
+            }
+        };
+
+        match self
+            .state
+            .db
+            .set_provider_weight(self.app_type.as_str(), &provider.id, weight)
+        {
+            Ok(()) => {
+                self.close();
+                true
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let name = self.provider.as_ref().map_or("", |p| p.name.as_str());
+
+        let area = centered_rect(50, 8, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Routing weight — {name}"))
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1); 4])
+            .split(area.inner(Margin::new(2, 1)));
+
+        let text = self.weight.display(true);
+        frame.render_widget(Paragraph::new(text).style(theme.selected), chunks[0]);
+        frame.render_widget(
+            Paragraph::new("Share of traffic relative to other queue providers (empty = 1)")
+                .style(theme.inactive),
+            chunks[1],
+        );
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), chunks[2]);
+        }
+        frame.render_widget(
+            Paragraph::new("Enter:Save  Esc:Cancel").style(theme.inactive),
+            chunks[3],
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crossterm::event::KeyCode;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::TextInput;
+use crate::tui::theme::Theme;
+
+/// 表单中的一行：勾选框或单行输入
+pub enum FormField {
+    Toggle { label: String, on: bool },
+    Text { input: TextInput, digits_only: bool },
+}
+
+impl FormField {
+    pub fn toggle(label: &str, on: bool) -> Self {
+        Self::Toggle {
+            label: label.to_string(),
+            on,
+        }
+    }
+
+    pub fn text(label: &str, value: &str) -> Self {
+        Self::Text {
+            input: TextInput::with_value(label, value),
+            digits_only: false,
+        }
+    }
+
+    /// 只接受数字输入
+    pub fn number(label: &str, value: impl ToString) -> Self {
+        Self::Text {
+            input: TextInput::with_value(label, &value.to_string()),
+            digits_only: true,
+        }
+    }
+}
+
+pub enum FormEvent {
+    Submit,
+    Cancel,
+    None,
+}
+
+/// 设置弹窗共用的字段列表、焦点切换与渲染：每个字段占一行，下方依次是说明、错误与按键提示
+pub struct Form<K> {
+    fields: Vec<(K, FormField)>,
+    focus: usize,
+    message: Option<String>,
+}
+
+impl<K: Copy + PartialEq> Form<K> {
+    pub fn new(fields: Vec<(K, FormField)>) -> Self {
+        Self {
+            fields,
+            focus: 0,
+            message: None,
+        }
+    }
+
+    /// 弹窗总高度（含边框）
+    pub fn height(&self) -> u16 {
+        self.fields.len() as u16 + 5
+    }
+
+    pub fn is_on(&self, key: K) -> bool {
+        self.fields
+            .iter()
+            .any(|(k, field)| *k == key && matches!(field, FormField::Toggle { on: true, .. }))
+    }
+
+    pub fn input(&self, key: K) -> &TextInput {
+        self.fields
+            .iter()
+            .find_map(|(k, field)| match field {
+                FormField::Text { input, .. } if *k == key => Some(input),
+                _ => None,
+            })
+            .expect("form has no text field for this key")
+    }
+
+    /// 保存失败时在弹窗内显示错误
+    pub fn set_error(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> FormEvent {
+        let count = self.fields.len().max(1);
+        match key {
+            KeyCode::Esc => return FormEvent::Cancel,
+            KeyCode::Enter => return FormEvent::Submit,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % count,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + count - 1) % count,
+            code => match self.fields.get_mut(self.focus) {
+                Some((_, FormField::Toggle { on, .. })) => {
+                    if matches!(code, KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right) {
+                        *on = !*on;
+                    }
+                }
+                Some((_, FormField::Text { input, digits_only })) => match code {
+                    KeyCode::Backspace => input.backspace(),
+                    KeyCode::Delete => input.delete(),
+                    KeyCode::Left => input.move_left(),
+                    KeyCode::Right => input.move_right(),
+                    KeyCode::Home => input.home(),
+                    KeyCode::End => input.end(),
+                    KeyCode::Char(c) if !*digits_only || c.is_ascii_digit() => input.insert(c),
+                    _ => {}
+                },
+                None => {}
+            },
+        }
+        FormEvent::None
+    }
+
+    pub fn render(&self, frame: &mut Frame, theme: &Theme, area: Rect, title: &str, note: &str) {
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(title.to_string())
+            .borders(Borders::ALL)
+            .style(theme.border);
+        frame.render_widget(block, area);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); self.fields.len() + 3])
+            .split(area.inner(Margin::new(2, 1)));
+
+        for (i, (_, field)) in self.fields.iter().enumerate() {
+            let active = i == self.focus;
+            let text = match field {
+                FormField::Toggle { label, on } => {
+                    format!("[{}] {label}", if *on { "x" } else { " " })
+                }
+                FormField::Text { input, .. } => input.display(active),
+            };
+            let style = if active { theme.selected } else { theme.normal };
+            frame.render_widget(Paragraph::new(text).style(style), rows[i]);
+        }
+
+        let n = self.fields.len();
+        frame.render_widget(Paragraph::new(note).style(theme.inactive), rows[n]);
+        if let Some(msg) = &self.message {
+            frame.render_widget(Paragraph::new(msg.as_str()).style(theme.error), rows[n + 1]);
+        }
+        let has_toggle = self
+            .fields
+            .iter()
+            .any(|(_, field)| matches!(field, FormField::Toggle { .. }));
+        let hint = if has_toggle {
+            "Tab:Next field  Space:Toggle  Enter:Save  Esc:Cancel"
+        } else {
+            "Tab:Next field  Enter:Save  Esc:Cancel"
+        };
+        frame.render_widget(Paragraph::new(hint).style(theme.inactive), rows[n + 2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Field {
+        Enabled,
+        Port,
+    }
+
+    fn form() -> Form<Field> {
+        Form::new(vec![
+            (Field::Enabled, FormField::toggle("Enabled", false)),
+            (Field::Port, FormField::number("Port", 80)),
+        ])
+    }
+
+    #[test]
+    fn focus_wraps_in_both_directions() {
+        let mut form = form();
+        form.handle_key(KeyCode::BackTab);
+        assert_eq!(form.fields[form.focus].0, Field::Port);
+        form.handle_key(KeyCode::Tab);
+        assert_eq!(form.fields[form.focus].0, Field::Enabled);
+    }
+
+    #[test]
+    fn edits_go_to_the_focused_field() {
+        let mut form = form();
+        form.handle_key(KeyCode::Char(' '));
+        assert!(form.is_on(Field::Enabled));
+
+        form.handle_key(KeyCode::Down);
+        form.handle_key(KeyCode::Char('8'));
+        form.handle_key(KeyCode::Char('x'));
+        assert_eq!(form.input(Field::Port).value, "808");
+        assert!(matches!(form.handle_key(KeyCode::Enter), FormEvent::Submit));
+    }
+}
@@ -67,4 +67,36 @@ impl TextInput {
         self.value.clear();
         self.cursor = 0;
     }
+
+    /// `label: value`，聚焦时在光标处显示 `│`
+    pub fn display(&self, active: bool) -> String {
+        format!("{}: {}", self.label, self.display_value(active))
+    }
+
+    /// 仅输入内容，聚焦时在光标处显示 `│`
+    pub fn display_value(&self, active: bool) -> String {
+        if active {
+            format!(
+                "{}│{}",
+                &self.value[..self.cursor],
+                &self.value[self.cursor..]
+            )
+        } else {
+            self.value.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_shows_cursor_only_when_active() {
+        let mut input = TextInput::with_value("Port", "8080");
+        input.move_left();
+        assert_eq!(input.display(true), "Port: 808│0");
+        assert_eq!(input.display(false), "Port: 8080");
+        assert_eq!(input.display_value(true), "808│0");
+    }
 }
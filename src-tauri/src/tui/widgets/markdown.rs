@@ -0,0 +1,159 @@
+use ratatui::prelude::*;
+
+use crate::tui::theme::Theme;
+
+/// 将 Markdown 文本渲染为带样式的行，仅支持标题、列表、引用、分隔线、代码块及行内代码/粗体
+pub fn markdown_lines(text: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            lines.push(Line::styled(raw.to_string(), theme.inactive));
+            continue;
+        }
+        if in_code {
+            lines.push(Line::styled(format!("  {raw}"), theme.highlight));
+            continue;
+        }
+
+        let indent = &raw[..raw.len() - trimmed.len()];
+        if let Some((level, heading)) = parse_heading(trimmed) {
+            let mut style = theme.title.add_modifier(Modifier::BOLD);
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::styled(heading.to_string(), style));
+        } else if is_rule(trimmed) {
+            lines.push(Line::styled("─".repeat(40), theme.inactive));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let mut spans = vec![Span::styled(format!("{indent}│ "), theme.inactive)];
+            spans.extend(
+                inline_spans(quote.trim_start(), theme.normal, theme)
+                    .into_iter()
+                    .map(|s| s.italic()),
+            );
+            lines.push(Line::from(spans));
+        } else if let Some((marker, item)) = parse_list_item(trimmed) {
+            let mut spans = vec![Span::styled(format!("{indent}{marker} "), theme.highlight)];
+            spans.extend(inline_spans(item, theme.normal, theme));
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(inline_spans(raw, theme.normal, theme)));
+        }
+    }
+
+    lines
+}
+
+/// `# 标题` → (级别, 标题文本)
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        return Some((level, rest));
+    }
+    rest.strip_prefix(' ').map(|text| (level, text.trim()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim_end();
+    let first = line.chars().next();
+    matches!(first, Some('-' | '*' | '_'))
+        && line.len() >= 3
+        && line.chars().all(|c| Some(c) == first)
+}
+
+/// 无序列表统一显示为 `•`，有序列表保留序号
+fn parse_list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(("•".to_string(), item));
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(item) = line[digits..].strip_prefix(". ") {
+            return Some((line[..digits + 1].to_string(), item));
+        }
+    }
+    None
+}
+
+/// 解析行内 `代码` 与 **粗体**，未闭合的标记按原文显示
+fn inline_spans(text: &str, base: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let code = rest.find('`');
+        let bold = rest.find("**");
+        let (start, marker, style) = match (code, bold) {
+            (Some(c), Some(b)) if b < c => (b, "**", base.add_modifier(Modifier::BOLD)),
+            (Some(c), _) => (c, "`", theme.highlight),
+            (None, Some(b)) => (b, "**", base.add_modifier(Modifier::BOLD)),
+            (None, None) => break,
+        };
+
+        let inner_start = start + marker.len();
+        let Some(len) = rest[inner_start..].find(marker) else {
+            break;
+        };
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), base));
+        }
+        spans.push(Span::styled(
+            rest[inner_start..inner_start + len].to_string(),
+            style,
+        ));
+        rest = &rest[inner_start + len + marker.len()..];
+    }
+
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), base));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn renders_headings_lists_and_code_fences() {
+        let theme = Theme::default();
+        let text = "# Title\n- item `x`\n2. second\n```\n# not a heading\n```\n**bold** text";
+        let lines = markdown_lines(text, &theme);
+
+        assert_eq!(plain(&lines[0]), "Title");
+        assert!(lines[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(plain(&lines[1]), "• item x");
+        assert_eq!(lines[1].spans.last().unwrap().style, theme.highlight);
+        assert_eq!(plain(&lines[2]), "2. second");
+        assert_eq!(plain(&lines[4]), "  # not a heading");
+        assert_eq!(lines[4].style, theme.highlight);
+        assert_eq!(plain(&lines[6]), "bold text");
+        assert!(lines[6].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn unclosed_inline_markers_are_kept_verbatim() {
+        let theme = Theme::default();
+        let lines = markdown_lines("use `foo and **bar", &theme);
+        assert_eq!(plain(&lines[0]), "use `foo and **bar");
+        assert!(parse_heading("#hashtag").is_none());
+    }
+}
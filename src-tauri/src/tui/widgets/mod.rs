@@ -1,3 +1,11 @@
+mod form;
 mod input;
+mod markdown;
+mod spinner;
+mod textarea;
 
+pub use form::{Form, FormEvent, FormField};
 pub use input::TextInput;
+pub use markdown::markdown_lines;
+pub use spinner::{loading_title, spinner_frame};
+pub use textarea::TextArea;
@@ -0,0 +1,22 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const FRAME_MILLIS: u128 = 100;
+
+/// 按当前时间取加载动画的帧，随每次重绘自然推进
+pub fn spinner_frame() -> &'static str {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    FRAMES[(millis / FRAME_MILLIS) as usize % FRAMES.len()]
+}
+
+/// 加载中时在区块标题后追加动画
+pub fn loading_title(title: &str, loading: bool) -> String {
+    if loading {
+        format!("{title} {} loading…", spinner_frame())
+    } else {
+        title.to_string()
+    }
+}
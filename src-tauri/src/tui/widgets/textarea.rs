@@ -0,0 +1,148 @@
+/// 多行文本编辑器的内容与光标（列按字符计）
+pub struct TextArea {
+    lines: Vec<String>,
+    pub row: usize,
+    pub col: usize,
+}
+
+impl TextArea {
+    pub fn new(text: &str) -> Self {
+        // 按 '\n' 拆分以保留末尾空行，保存时原样还原
+        let lines = text.split('\n').map(str::to_string).collect();
+        Self {
+            lines,
+            row: 0,
+            col: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    /// 插入粘贴的多行文本
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.replace("\r\n", "\n").chars() {
+            match c {
+                '\n' | '\r' => self.newline(),
+                c => self.insert(c),
+            }
+        }
+    }
+
+    pub fn newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    /// 行首退格时与上一行合并
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// 行尾删除时合并下一行
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self, count: usize) {
+        self.row = self.row.saturating_sub(count);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    pub fn move_down(&mut self, count: usize) {
+        self.row = (self.row + count).min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editing_splits_and_joins_lines() {
+        let mut area = TextArea::new("héllo\nworld");
+        area.col = 2;
+        area.newline();
+        assert_eq!(area.text(), "hé\nllo\nworld");
+
+        area.backspace();
+        assert_eq!(area.text(), "héllo\nworld");
+        assert_eq!((area.row, area.col), (0, 2));
+
+        area.end();
+        area.delete();
+        area.insert('!');
+        assert_eq!(area.text(), "héllo!world");
+
+        area.move_down(5);
+        assert_eq!((area.row, area.col), (0, 6));
+    }
+}
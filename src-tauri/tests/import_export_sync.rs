@@ -3,8 +3,9 @@ use std::fs;
 use std::path::PathBuf;
 
 use cc_switch_lib::{
-    get_claude_settings_path, read_json_file, AppError, AppType, ConfigService, MultiAppConfig,
-    Provider, ProviderMeta,
+    get_claude_settings_path, read_json_file, AppError, AppType, ClientRateLimit, ConfigService,
+    CorsConfig, Database, ImportAction, ImportCategory, ImportStrategies, MultiAppConfig, Provider,
+    ProviderMeta, ProviderService, SizeLimitConfig, UiPreferences,
 };
 
 #[path = "support.rs"]
@@ -1066,3 +1067,337 @@ fn import_sql_accepts_cc_switch_exported_backup() {
         "imported providers should contain test-provider"
     );
 }
+
+#[test]
+fn restore_backup_replaces_database_and_keeps_a_safety_snapshot() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "test-provider".to_string();
+        manager.providers.insert(
+            "test-provider".to_string(),
+            Provider::with_id(
+                "test-provider".to_string(),
+                "Test Provider".to_string(),
+                json!({"env": {"ANTHROPIC_API_KEY": "test-key"}}),
+                None,
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    let backup_path = state.db.create_auto_backup(3).expect("create backup");
+    let summary = state
+        .db
+        .inspect_backup(&backup_path)
+        .expect("inspect backup");
+    assert_eq!(summary.providers, 1);
+    assert_eq!(summary.mcp_servers, 0);
+
+    state
+        .db
+        .delete_provider(AppType::Claude.as_str(), "test-provider")
+        .expect("delete provider");
+    let safety_id = state
+        .db
+        .restore_backup(&backup_path)
+        .expect("restore should succeed");
+    assert!(!safety_id.is_empty(), "current database is backed up first");
+
+    let providers = state
+        .db
+        .get_all_providers(AppType::Claude.as_str())
+        .expect("load providers");
+    assert!(providers.contains_key("test-provider"));
+    assert!(backup_path.exists(), "restoring keeps the backup file");
+}
+
+#[test]
+fn gui_database_import_merges_providers_and_failover_queue() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.providers.insert(
+            "gui-provider".to_string(),
+            Provider::with_id(
+                "gui-provider".to_string(),
+                "GUI Provider".to_string(),
+                json!({"env": {"ANTHROPIC_API_KEY": "gui-key"}}),
+                None,
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+    state
+        .db
+        .add_to_failover_queue(AppType::Claude.as_str(), "gui-provider")
+        .expect("queue provider");
+
+    let runtime = tokio::runtime::Runtime::new().expect("create runtime");
+    // 传入目录时读取其中的 cc-switch.db
+    let gui_dir = Database::file_path()
+        .parent()
+        .expect("database dir")
+        .to_path_buf();
+    let bundle = runtime
+        .block_on(ConfigService::read_import_source(&state, &gui_dir))
+        .expect("read GUI database");
+
+    state
+        .db
+        .delete_provider(AppType::Claude.as_str(), "gui-provider")
+        .expect("delete provider");
+
+    let plan = ConfigService::plan_import(&state, &bundle, &ImportStrategies::default())
+        .expect("plan import");
+    let item = plan
+        .iter()
+        .find(|item| item.id == "gui-provider")
+        .expect("provider is planned");
+    assert_eq!(item.action, ImportAction::Add);
+
+    let summary = runtime
+        .block_on(ConfigService::apply_import(&state, &bundle, &plan))
+        .expect("apply import");
+    assert_eq!(summary.added, 1);
+    assert!(state
+        .db
+        .is_in_failover_queue(AppType::Claude.as_str(), "gui-provider")
+        .expect("check queue"));
+}
+
+#[test]
+fn provider_roster_updates_by_name_and_adds_new_providers() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.providers.insert(
+            "team-relay".to_string(),
+            Provider::with_id(
+                "team-relay".to_string(),
+                "Team Relay".to_string(),
+                json!({"env": {"ANTHROPIC_AUTH_TOKEN": "old-key", "ANTHROPIC_MODEL": "keep"}}),
+                None,
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    let roster_path = home.join("roster.yaml");
+    fs::write(
+        &roster_path,
+        r#"
+providers:
+  - app: claude
+    name: Team Relay
+    key: new-key
+    urls: [https://relay.example.com/, https://backup.example.com]
+    failover: true
+  - app: codex
+    name: Codex Relay
+    key: sk-codex
+    urls: [https://codex.example.com/v1]
+    category: third_party
+"#,
+    )
+    .expect("write roster");
+
+    let runtime = tokio::runtime::Runtime::new().expect("create runtime");
+    let bundle = runtime
+        .block_on(ConfigService::read_import_source(&state, &roster_path))
+        .expect("read roster");
+    assert!(bundle.is_roster());
+
+    let plan = ConfigService::plan_import(&state, &bundle, &bundle.default_strategies())
+        .expect("plan import");
+    let update = plan
+        .iter()
+        .find(|item| item.id == "team-relay")
+        .expect("existing provider matched by name");
+    assert_eq!(update.action, ImportAction::Overwrite);
+
+    let summary = runtime
+        .block_on(ConfigService::apply_import(&state, &bundle, &plan))
+        .expect("apply import");
+    assert_eq!(summary.overwritten, 1);
+    assert_eq!(summary.added, 1);
+
+    let claude = state
+        .db
+        .get_all_providers(AppType::Claude.as_str())
+        .expect("load claude providers");
+    let env = &claude["team-relay"].settings_config["env"];
+    assert_eq!(env["ANTHROPIC_AUTH_TOKEN"], "new-key");
+    assert_eq!(env["ANTHROPIC_BASE_URL"], "https://relay.example.com");
+    assert_eq!(env["ANTHROPIC_MODEL"], "keep", "unlisted settings are kept");
+    assert!(state
+        .db
+        .is_in_failover_queue(AppType::Claude.as_str(), "team-relay")
+        .expect("check queue"));
+    let endpoints = state
+        .db
+        .get_provider_endpoints_with_health(AppType::Claude.as_str(), "team-relay")
+        .expect("load endpoints");
+    assert_eq!(endpoints.len(), 2);
+
+    let codex = state
+        .db
+        .get_all_providers(AppType::Codex.as_str())
+        .expect("load codex providers");
+    let added = codex
+        .values()
+        .find(|p| p.name == "Codex Relay")
+        .expect("new provider created");
+    assert_eq!(added.category.as_deref(), Some("third_party"));
+    assert_eq!(added.settings_config["auth"]["OPENAI_API_KEY"], "sk-codex");
+}
+
+#[test]
+fn export_round_trips_endpoint_details_and_proxy_settings() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({"env": {"ANTHROPIC_API_KEY": "relay-key"}}),
+                None,
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+    let app = AppType::Claude;
+    for url in ["https://a.example.com", "https://b.example.com"] {
+        ProviderService::add_custom_endpoint(&state, app.clone(), "relay", url.to_string())
+            .expect("add endpoint");
+    }
+    ProviderService::set_endpoint_weight(
+        &state,
+        app.clone(),
+        "relay",
+        "https://a.example.com".to_string(),
+        Some(3),
+    )
+    .expect("set weight");
+    ProviderService::set_endpoint_region(
+        &state,
+        app.clone(),
+        "relay",
+        "https://b.example.com".to_string(),
+        Some("eu".to_string()),
+    )
+    .expect("set region");
+    ProviderService::set_endpoint_pinned(
+        &state,
+        app.clone(),
+        "relay",
+        "https://b.example.com".to_string(),
+        true,
+    )
+    .expect("pin endpoint");
+
+    let cors = CorsConfig {
+        enabled: true,
+        allowed_origins: vec!["https://app.example.com".to_string()],
+        allowed_methods: vec!["POST".to_string()],
+        allowed_headers: vec!["content-type".to_string()],
+    };
+    let limits = SizeLimitConfig {
+        max_request_kb: 64,
+        max_response_kb: 128,
+    };
+    let rate_limit = ClientRateLimit {
+        requests_per_minute: 30,
+        max_concurrent: 2,
+    };
+    state.db.set_proxy_cors_config(&cors).expect("set cors");
+    state.db.set_proxy_size_limits(&limits).expect("set limits");
+    state
+        .db
+        .set_proxy_upstream_url(Some("http://127.0.0.1:7890"))
+        .expect("set upstream");
+    state
+        .db
+        .set_client_rate_limit(app.as_str(), rate_limit)
+        .expect("set rate limit");
+
+    let runtime = tokio::runtime::Runtime::new().expect("create runtime");
+    let export_path = home.join("export.json");
+    runtime
+        .block_on(ConfigService::export_to_file(
+            &state,
+            &export_path,
+            false,
+            &UiPreferences::default(),
+        ))
+        .expect("export");
+
+    reset_test_fs();
+    let state = create_test_state().expect("create fresh state");
+    let bundle = ConfigService::read_import_bundle(&export_path).expect("read export");
+    let mut strategies = ImportStrategies::default();
+    strategies.cycle(ImportCategory::Proxy);
+    let plan = ConfigService::plan_import(&state, &bundle, &strategies).expect("plan import");
+    runtime
+        .block_on(ConfigService::apply_import(&state, &bundle, &plan))
+        .expect("apply import");
+
+    let endpoints = state
+        .db
+        .get_provider_endpoints_with_health(app.as_str(), "relay")
+        .expect("load endpoints");
+    let a = endpoints
+        .iter()
+        .find(|e| e.url == "https://a.example.com")
+        .expect("endpoint a");
+    let b = endpoints
+        .iter()
+        .find(|e| e.url == "https://b.example.com")
+        .expect("endpoint b");
+    assert_eq!(a.weight, Some(3));
+    assert!(!a.is_primary);
+    assert!(b.is_pinned && b.is_primary);
+    assert_eq!(b.region.as_deref(), Some("eu"));
+
+    assert_eq!(state.db.get_proxy_cors_config().expect("cors"), cors);
+    assert_eq!(state.db.get_proxy_size_limits().expect("limits"), limits);
+    assert_eq!(
+        state
+            .db
+            .get_proxy_upstream_url()
+            .expect("upstream")
+            .as_deref(),
+        Some("http://127.0.0.1:7890")
+    );
+    assert_eq!(
+        state
+            .db
+            .get_client_rate_limit(app.as_str())
+            .expect("rate limit"),
+        rate_limit
+    );
+}
@@ -369,6 +369,59 @@ fn upsert_mcp_server_disabling_app_removes_from_claude_live_config() {
     );
 }
 
+#[test]
+fn toggle_app_bulk_updates_all_servers_in_claude_live_config() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    fs::create_dir_all(home.join(".claude")).expect("create ~/.claude dir");
+
+    let state = support::create_test_state().expect("create test state");
+    for (id, claude) in [("alpha", false), ("beta", true)] {
+        McpService::upsert_server(
+            &state,
+            McpServer {
+                id: id.to_string(),
+                name: id.to_string(),
+                server: json!({ "type": "stdio", "command": "echo" }),
+                apps: McpApps {
+                    claude,
+                    codex: false,
+                    gemini: false,
+                },
+                description: None,
+                homepage: None,
+                docs: None,
+                tags: Vec::new(),
+            },
+        )
+        .expect("upsert server");
+    }
+    let ids = vec!["alpha".to_string(), "beta".to_string()];
+    let read_live = || {
+        let text = fs::read_to_string(get_claude_mcp_path()).expect("read ~/.claude.json");
+        serde_json::from_str::<serde_json::Value>(&text).expect("parse ~/.claude.json")
+    };
+
+    // 仅状态实际变化的服务器计入
+    let changed = McpService::toggle_app_bulk(&state, &ids, AppType::Claude, true)
+        .expect("bulk enable should succeed");
+    assert_eq!(changed, 1);
+    let live = read_live();
+    assert!(live.pointer("/mcpServers/alpha").is_some());
+    assert!(live.pointer("/mcpServers/beta").is_some());
+
+    let changed = McpService::toggle_app_bulk(&state, &ids, AppType::Claude, false)
+        .expect("bulk disable should succeed");
+    assert_eq!(changed, 2);
+    let live = read_live();
+    assert!(live.pointer("/mcpServers/alpha").is_none());
+    assert!(live.pointer("/mcpServers/beta").is_none());
+
+    let servers = state.db.get_all_mcp_servers().expect("get all mcp servers");
+    assert!(servers.values().all(|s| !s.apps.claude));
+}
+
 #[test]
 fn import_mcp_from_multiple_apps_merges_enabled_flags() {
     let _guard = test_mutex().lock().expect("acquire test mutex");